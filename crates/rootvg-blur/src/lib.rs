@@ -0,0 +1,5 @@
+mod primitive;
+
+pub mod pipeline;
+
+pub use primitive::BackdropBlurPrimitive;
@@ -0,0 +1,752 @@
+use wgpu::PipelineCompilationOptions;
+
+use rootvg_core::math::{
+    PhysicalPointI32, PhysicalSizeI32, PointI32, RectI32, ScaleFactor, SizeI32,
+};
+
+use crate::primitive::{BackdropBlurPrimitive, SIGMA_EXTENT};
+
+/// The maximum standard deviation of a single blur pass, in texels. Larger
+/// blurs are done on a downsampled copy of the backdrop, so the cost of a blur
+/// stays roughly the same no matter how large its standard deviation is.
+///
+/// This must be a third of `MAX_TAPS` in the shader.
+const MAX_PASS_SIGMA: f32 = 4.0;
+
+/// The maximum number of times the backdrop is halved in size before it is
+/// blurred.
+const MAX_DOWNSAMPLES: u32 = 6;
+
+#[repr(C)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+struct PassUniforms {
+    input_texel: [f32; 2],
+    direction: [f32; 2],
+    bounds: [f32; 4],
+    source_origin: [f32; 2],
+    source_size: [f32; 2],
+    blurred_size: [f32; 2],
+    sigma: f32,
+    corner_radius: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PassKind {
+    Downsample,
+    Blur,
+    Composite,
+}
+
+/// A texture that a pass of a blur renders into.
+#[derive(Debug)]
+struct PassTexture {
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+}
+
+#[derive(Debug)]
+struct Pass {
+    kind: PassKind,
+    /// The index of the texture in [`PreparedBlur::textures`] which is
+    /// rendered into, or `None` if this pass renders into the backdrop.
+    output: Option<usize>,
+    uniforms: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+}
+
+#[derive(Debug)]
+struct PreparedBlur {
+    /// The region of the backdrop which is copied into the source texture
+    /// (the first texture in `textures`), in physical pixels.
+    source_region: RectI32,
+    /// The region of the backdrop which is replaced with the blurred texture,
+    /// in physical pixels.
+    scissor_rect: RectI32,
+    /// The number of times the source texture is halved in size.
+    downsamples: u32,
+
+    textures: Vec<PassTexture>,
+    passes: Vec<Pass>,
+}
+
+#[derive(Debug)]
+pub struct BackdropBlurBatchBuffer {
+    blurs: Vec<PreparedBlur>,
+    num_blurs: usize,
+}
+
+impl BackdropBlurBatchBuffer {
+    fn new() -> Self {
+        Self {
+            blurs: Vec::new(),
+            num_blurs: 0,
+        }
+    }
+}
+
+/// The texture that the canvas is rendered into when a frame contains
+/// backdrop blurs, since the render target itself can't be sampled.
+#[derive(Debug)]
+struct Backdrop {
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    /// The bind group used to composite the backdrop onto the render target.
+    bind_group: wgpu::BindGroup,
+    size: PhysicalSizeI32,
+}
+
+#[derive(Debug)]
+pub struct BackdropBlurPipeline {
+    format: wgpu::TextureFormat,
+
+    downsample_pipeline: wgpu::RenderPipeline,
+    blur_pipeline: wgpu::RenderPipeline,
+    composite_pipeline: wgpu::RenderPipeline,
+    present_pipeline: wgpu::RenderPipeline,
+
+    constants_bind_group: wgpu::BindGroup,
+    pass_layout: wgpu::BindGroupLayout,
+    /// The uniforms of the bind group of the backdrop, which are unused.
+    present_uniforms: wgpu::Buffer,
+
+    backdrop: Option<Backdrop>,
+    has_batches: bool,
+
+    screen_size: PhysicalSizeI32,
+    scale_factor: ScaleFactor,
+    origin: PhysicalPointI32,
+}
+
+impl BackdropBlurPipeline {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        // Linear filtering is needed to downsample and upsample the backdrop,
+        // and clamping keeps the edges of the canvas from being darkened.
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("rootvg-blur sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let constants_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("rootvg-blur constants layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            }],
+        });
+
+        let constants_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("rootvg-blur constants bind group"),
+            layout: &constants_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::Sampler(&sampler),
+            }],
+        });
+
+        let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+
+        let pass_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("rootvg-blur pass layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: wgpu::BufferSize::new(
+                            std::mem::size_of::<PassUniforms>() as u64,
+                        ),
+                    },
+                    count: None,
+                },
+                texture_entry(1),
+                texture_entry(2),
+            ],
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("rootvg-blur pipeline layout"),
+            push_constant_ranges: &[],
+            bind_group_layouts: &[&constants_layout, &pass_layout],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("rootvg-blur shader"),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(include_str!(
+                "shader/backdrop_blur.wgsl"
+            ))),
+        });
+
+        let create_pipeline = |label, entry_point, blend| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: "vs_main",
+                    buffers: &[],
+                    compilation_options: PipelineCompilationOptions::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point,
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: PipelineCompilationOptions::default(),
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    ..Default::default()
+                },
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState {
+                    count: 1,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                multiview: None,
+                cache: None,
+            })
+        };
+
+        let downsample_pipeline = create_pipeline(
+            "rootvg-blur downsample pipeline",
+            "downsample_fs_main",
+            None,
+        );
+        let blur_pipeline = create_pipeline("rootvg-blur blur pipeline", "blur_fs_main", None);
+        let composite_pipeline =
+            create_pipeline("rootvg-blur composite pipeline", "composite_fs_main", None);
+        let present_pipeline = create_pipeline(
+            "rootvg-blur present pipeline",
+            "present_fs_main",
+            Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+        );
+
+        let present_uniforms = create_uniforms(device);
+
+        Self {
+            format,
+            downsample_pipeline,
+            blur_pipeline,
+            composite_pipeline,
+            present_pipeline,
+            constants_bind_group,
+            pass_layout,
+            present_uniforms,
+            backdrop: None,
+            has_batches: false,
+            screen_size: PhysicalSizeI32::default(),
+            scale_factor: ScaleFactor::default(),
+            origin: PhysicalPointI32::default(),
+        }
+    }
+
+    pub fn create_batch(&mut self, _device: &wgpu::Device) -> BackdropBlurBatchBuffer {
+        BackdropBlurBatchBuffer::new()
+    }
+
+    pub fn start_preparations(
+        &mut self,
+        _device: &wgpu::Device,
+        _queue: &wgpu::Queue,
+        screen_size: PhysicalSizeI32,
+        scale_factor: ScaleFactor,
+        origin: PhysicalPointI32,
+    ) {
+        self.screen_size = screen_size;
+        self.scale_factor = scale_factor;
+        self.origin = origin;
+        self.has_batches = false;
+    }
+
+    /// Prepare a batch of backdrop blurs.
+    ///
+    /// * `scissor_rect` - The region of the backdrop that the blurs may
+    ///   change, in physical pixels.
+    /// * `canvas_rect` - The region of the backdrop that the canvas is
+    ///   rendered into, in physical pixels. Pixels outside of this region are
+    ///   never sampled.
+    ///
+    /// Returns `false` if no blur in the batch covers any pixels, in which
+    /// case the batch doesn't need to be rendered.
+    pub fn prepare_batch(
+        &mut self,
+        batch: &mut BackdropBlurBatchBuffer,
+        primitives: &[BackdropBlurPrimitive],
+        scissor_rect: RectI32,
+        canvas_rect: RectI32,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> bool {
+        let scale_factor = self.scale_factor.0;
+        let origin = [self.origin.x as f32, self.origin.y as f32];
+
+        batch.num_blurs = 0;
+
+        for primitive in primitives.iter() {
+            let sigma = primitive.blur_sigma * scale_factor;
+            if sigma.is_nan() || sigma <= 0.0 {
+                continue;
+            }
+
+            let bounds = [
+                primitive.bounds.origin.x * scale_factor + origin[0],
+                primitive.bounds.origin.y * scale_factor + origin[1],
+                primitive.bounds.size.width * scale_factor,
+                primitive.bounds.size.height * scale_factor,
+            ];
+
+            let covered_rect = RectI32::new(
+                PointI32::new(bounds[0].floor() as i32, bounds[1].floor() as i32),
+                SizeI32::new(
+                    (bounds[0] + bounds[2]).ceil() as i32 - bounds[0].floor() as i32,
+                    (bounds[1] + bounds[3]).ceil() as i32 - bounds[1].floor() as i32,
+                ),
+            );
+            let Some(blur_scissor_rect) = intersect_rect(covered_rect, scissor_rect) else {
+                continue;
+            };
+
+            // Only the pixels around the region which is redrawn are sampled.
+            // An extra pixel is added for the linear filtering of the
+            // downsampled textures.
+            let extent = (sigma * SIGMA_EXTENT).ceil() as i32 + 1;
+            let Some(source_region) =
+                intersect_rect(blur_scissor_rect.inflate(extent, extent), canvas_rect)
+            else {
+                continue;
+            };
+
+            // Downsample until a single pass can cover the blur. Averaging
+            // `d`x`d` pixels adds a variance of `(d^2 - 1) / 12`, which is
+            // subtracted from the blur.
+            let mut downsamples = 0;
+            let mut pass_sigma = sigma;
+            while pass_sigma > MAX_PASS_SIGMA && downsamples < MAX_DOWNSAMPLES {
+                downsamples += 1;
+
+                let d = (1 << downsamples) as f32;
+                pass_sigma = (sigma * sigma - (d * d - 1.0) / 12.0).max(0.0).sqrt() / d;
+            }
+            let pass_sigma = pass_sigma.max(0.25);
+
+            if batch.num_blurs == batch.blurs.len() {
+                batch
+                    .blurs
+                    .push(self.create_blur(device, source_region, downsamples));
+            } else {
+                let blur = &batch.blurs[batch.num_blurs];
+                if blur.source_region.size != source_region.size || blur.downsamples != downsamples
+                {
+                    batch.blurs[batch.num_blurs] =
+                        self.create_blur(device, source_region, downsamples);
+                }
+            }
+
+            let blur = &mut batch.blurs[batch.num_blurs];
+            blur.source_region = source_region;
+            blur.scissor_rect = blur_scissor_rect;
+
+            batch.num_blurs += 1;
+
+            let level_sizes = level_sizes(source_region.size, downsamples);
+            let blurred_size = level_sizes[downsamples as usize];
+            let d = (1 << downsamples) as f32;
+
+            for pass in blur.passes.iter() {
+                let input_size = match (pass.kind, pass.output) {
+                    (PassKind::Downsample, Some(output)) => level_sizes[output - 1],
+                    _ => blurred_size,
+                };
+
+                let uniforms = PassUniforms {
+                    input_texel: [
+                        1.0 / input_size.width as f32,
+                        1.0 / input_size.height as f32,
+                    ],
+                    direction: if pass.output == Some(blur.textures.len() - 1) {
+                        [0.0, 1.0]
+                    } else {
+                        [1.0, 0.0]
+                    },
+                    bounds,
+                    source_origin: [source_region.origin.x as f32, source_region.origin.y as f32],
+                    source_size: [
+                        source_region.size.width as f32,
+                        source_region.size.height as f32,
+                    ],
+                    blurred_size: [
+                        blurred_size.width as f32 * d,
+                        blurred_size.height as f32 * d,
+                    ],
+                    sigma: pass_sigma,
+                    corner_radius: primitive.radius * scale_factor,
+                };
+
+                queue.write_buffer(&pass.uniforms, 0, bytemuck::bytes_of(&uniforms));
+            }
+        }
+
+        // Release the textures of blurs which are no longer used.
+        batch.blurs.truncate(batch.num_blurs);
+
+        if batch.num_blurs == 0 {
+            return false;
+        }
+
+        if self.backdrop.as_ref().map(|b| b.size) != Some(self.screen_size) {
+            self.backdrop = Some(self.create_backdrop(device));
+        }
+        self.has_batches = true;
+
+        true
+    }
+
+    /// Release the backdrop texture if no batches were prepared.
+    pub fn finish_preparations(&mut self) {
+        if !self.has_batches {
+            self.backdrop = None;
+        }
+    }
+
+    /// The texture that the canvas must be rendered into, or `None` if no
+    /// batches were prepared.
+    ///
+    /// The texture must be cleared before it is rendered into, then the
+    /// batches are rendered in between the other primitives, and finally the
+    /// texture is composited onto the render target with
+    /// [`BackdropBlurPipeline::render_to_target`].
+    pub fn backdrop(&self) -> Option<&wgpu::TextureView> {
+        self.backdrop
+            .as_ref()
+            .filter(|_| self.has_batches)
+            .map(|backdrop| &backdrop.view)
+    }
+
+    /// Blur the backdrop within the bounds of each primitive in the batch.
+    ///
+    /// This must be called outside of a render pass, since the backdrop is
+    /// copied into another texture.
+    pub fn render_batch(
+        &self,
+        batch: &BackdropBlurBatchBuffer,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        let Some(backdrop) = self.backdrop.as_ref() else {
+            return;
+        };
+
+        for blur in batch.blurs[..batch.num_blurs].iter() {
+            encoder.copy_texture_to_texture(
+                wgpu::ImageCopyTexture {
+                    texture: &backdrop.texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d {
+                        x: blur.source_region.origin.x as u32,
+                        y: blur.source_region.origin.y as u32,
+                        z: 0,
+                    },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                blur.textures[0].texture.as_image_copy(),
+                wgpu::Extent3d {
+                    width: blur.source_region.size.width as u32,
+                    height: blur.source_region.size.height as u32,
+                    depth_or_array_layers: 1,
+                },
+            );
+
+            for pass in blur.passes.iter() {
+                let (view, load) = match pass.output {
+                    Some(i) => (
+                        &blur.textures[i].view,
+                        wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    ),
+                    None => (&backdrop.view, wgpu::LoadOp::Load),
+                };
+
+                let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("rootvg-blur render pass"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load,
+                            store: wgpu::StoreOp::Store,
+                        },
+                    })],
+                    depth_stencil_attachment: None,
+                    timestamp_writes: None,
+                    occlusion_query_set: None,
+                });
+
+                render_pass.set_pipeline(match pass.kind {
+                    PassKind::Downsample => &self.downsample_pipeline,
+                    PassKind::Blur => &self.blur_pipeline,
+                    PassKind::Composite => &self.composite_pipeline,
+                });
+                render_pass.set_bind_group(0, &self.constants_bind_group, &[]);
+                render_pass.set_bind_group(1, &pass.bind_group, &[]);
+
+                if pass.output.is_none() {
+                    let rect = blur.scissor_rect;
+                    render_pass.set_scissor_rect(
+                        rect.origin.x as u32,
+                        rect.origin.y as u32,
+                        rect.size.width as u32,
+                        rect.size.height as u32,
+                    );
+                }
+
+                render_pass.draw(0..3, 0..1);
+            }
+        }
+    }
+
+    /// Composite the backdrop onto the given target.
+    ///
+    /// Only the pixels within `scissor_rect` (in physical pixels) are
+    /// composited, or none if it is `None`. The target is still cleared with
+    /// the clear color, if one is given.
+    pub fn render_to_target(
+        &self,
+        target: &wgpu::TextureView,
+        clear_color: Option<wgpu::Color>,
+        scissor_rect: Option<RectI32>,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        let load = if let Some(color) = clear_color {
+            wgpu::LoadOp::Clear(color)
+        } else {
+            wgpu::LoadOp::Load
+        };
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("rootvg-blur present render pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        let (Some(backdrop), Some(rect)) = (self.backdrop.as_ref(), scissor_rect) else {
+            return;
+        };
+
+        render_pass.set_pipeline(&self.present_pipeline);
+        render_pass.set_bind_group(0, &self.constants_bind_group, &[]);
+        render_pass.set_bind_group(1, &backdrop.bind_group, &[]);
+        render_pass.set_scissor_rect(
+            rect.origin.x as u32,
+            rect.origin.y as u32,
+            rect.size.width as u32,
+            rect.size.height as u32,
+        );
+        render_pass.draw(0..3, 0..1);
+    }
+
+    fn create_backdrop(&self, device: &wgpu::Device) -> Backdrop {
+        let texture = self.create_texture(
+            device,
+            "rootvg-blur backdrop texture",
+            self.screen_size.width,
+            self.screen_size.height,
+            wgpu::TextureUsages::COPY_SRC,
+        );
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let bind_group = self.create_bind_group(device, &self.present_uniforms, &view, &view);
+
+        Backdrop {
+            texture,
+            view,
+            bind_group,
+            size: self.screen_size,
+        }
+    }
+
+    /// Create the textures and passes of a blur of the given region.
+    fn create_blur(
+        &self,
+        device: &wgpu::Device,
+        source_region: RectI32,
+        downsamples: u32,
+    ) -> PreparedBlur {
+        let level_sizes = level_sizes(source_region.size, downsamples);
+        let blurred_size = level_sizes[downsamples as usize];
+
+        // The source texture and each downsampled level, followed by the
+        // output of the horizontal and vertical blur passes.
+        let textures: Vec<PassTexture> = level_sizes
+            .iter()
+            .chain([blurred_size, blurred_size].iter())
+            .map(|size| {
+                let texture = self.create_texture(
+                    device,
+                    "rootvg-blur pass texture",
+                    size.width,
+                    size.height,
+                    wgpu::TextureUsages::COPY_DST,
+                );
+                let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+                PassTexture { texture, view }
+            })
+            .collect();
+
+        let num_levels = level_sizes.len();
+        let mut passes = Vec::with_capacity(num_levels + 2);
+
+        let mut push_pass = |kind, input: usize, output: Option<usize>| {
+            let uniforms = create_uniforms(device);
+            let bind_group =
+                self.create_bind_group(device, &uniforms, &textures[input].view, &textures[0].view);
+
+            passes.push(Pass {
+                kind,
+                output,
+                uniforms,
+                bind_group,
+            });
+        };
+
+        for level in 1..num_levels {
+            push_pass(PassKind::Downsample, level - 1, Some(level));
+        }
+        push_pass(PassKind::Blur, num_levels - 1, Some(num_levels));
+        push_pass(PassKind::Blur, num_levels, Some(num_levels + 1));
+        push_pass(PassKind::Composite, num_levels + 1, None);
+
+        PreparedBlur {
+            source_region,
+            scissor_rect: RectI32::default(),
+            downsamples,
+            textures,
+            passes,
+        }
+    }
+
+    fn create_texture(
+        &self,
+        device: &wgpu::Device,
+        label: &'static str,
+        width: i32,
+        height: i32,
+        usage: wgpu::TextureUsages,
+    ) -> wgpu::Texture {
+        assert!(width > 0);
+        assert!(height > 0);
+
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width: width as u32,
+                height: height as u32,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | usage,
+            view_formats: &[],
+        })
+    }
+
+    fn create_bind_group(
+        &self,
+        device: &wgpu::Device,
+        uniforms: &wgpu::Buffer,
+        input: &wgpu::TextureView,
+        source: &wgpu::TextureView,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("rootvg-blur pass bind group"),
+            layout: &self.pass_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniforms.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(input),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(source),
+                },
+            ],
+        })
+    }
+}
+
+fn create_uniforms(device: &wgpu::Device) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("rootvg-blur uniforms buffer"),
+        size: std::mem::size_of::<PassUniforms>() as u64,
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+/// The sizes of the source texture and of each downsampled level.
+fn level_sizes(source_size: SizeI32, downsamples: u32) -> Vec<SizeI32> {
+    let mut sizes = Vec::with_capacity(downsamples as usize + 1);
+    sizes.push(source_size);
+
+    let mut size = source_size;
+    for _ in 0..downsamples {
+        size = SizeI32::new((size.width + 1) / 2, (size.height + 1) / 2);
+        sizes.push(size);
+    }
+
+    sizes
+}
+
+/// Returns the intersection of two rectangles, or `None` if they do not
+/// overlap.
+fn intersect_rect(a: RectI32, b: RectI32) -> Option<RectI32> {
+    let min_x = a.min_x().max(b.min_x());
+    let min_y = a.min_y().max(b.min_y());
+    let max_x = a.max_x().min(b.max_x());
+    let max_y = a.max_y().min(b.max_y());
+
+    if max_x <= min_x || max_y <= min_y {
+        return None;
+    }
+
+    Some(RectI32::new(
+        PointI32::new(min_x, min_y),
+        SizeI32::new(max_x - min_x, max_y - min_y),
+    ))
+}
@@ -0,0 +1,57 @@
+use rootvg_core::math::{Point, Rect, Size};
+
+/// A primitive which blurs everything that was drawn behind it within a
+/// rounded rectangle, for example to draw a frosted glass panel.
+///
+/// A backdrop blur is drawn before all other primitives with the same z index
+/// and scissor rect, so the background and contents of the panel can be added
+/// with the same z index and they are drawn on top of the blurred backdrop.
+///
+/// Only the pixels drawn by the canvas in the current frame (including the
+/// clear color) are blurred, not the previous contents of the render target.
+/// Rounded clipping rects and the global alpha have no effect on backdrop
+/// blurs.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BackdropBlurPrimitive {
+    /// The bounds of the blurred region in logical points.
+    pub bounds: Rect,
+
+    /// The radius of the corners of the blurred region in logical points.
+    pub radius: f32,
+
+    /// The standard deviation of the Gaussian blur in logical points. A value
+    /// of `0.0` or less means the backdrop is not blurred.
+    pub blur_sigma: f32,
+}
+
+impl BackdropBlurPrimitive {
+    pub fn new(bounds: Rect, radius: f32, blur_sigma: f32) -> Self {
+        Self {
+            bounds,
+            radius,
+            blur_sigma,
+        }
+    }
+
+    pub fn from_pos_size(position: Point, size: Size, radius: f32, blur_sigma: f32) -> Self {
+        Self::new(Rect::new(position, size), radius, blur_sigma)
+    }
+
+    /// The distance around the bounds which is sampled by the blur, in
+    /// logical points.
+    pub fn extent(&self) -> f32 {
+        self.blur_sigma.max(0.0) * SIGMA_EXTENT
+    }
+
+    /// The bounds of all pixels which are sampled by the blur, in logical
+    /// points.
+    pub fn sampled_bounds(&self) -> Rect {
+        let extent = self.extent();
+        self.bounds.inflate(extent, extent)
+    }
+}
+
+/// The number of standard deviations after which the weights of the blur are
+/// treated as zero.
+pub(crate) const SIGMA_EXTENT: f32 = 3.0;
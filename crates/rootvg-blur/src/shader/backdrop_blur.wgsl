@@ -0,0 +1,101 @@
+// The passes of a backdrop blur. The region of the backdrop around the blur is
+// copied into a source texture, downsampled, blurred horizontally and then
+// vertically, and finally composited back into the backdrop.
+
+struct Uniforms {
+    // The size of a texel of the input texture in uv coordinates.
+    input_texel: vec2<f32>,
+    // The direction of a blur pass, either `(1, 0)` or `(0, 1)`.
+    direction: vec2<f32>,
+    // The rounded rectangle which is blurred, in pixels of the backdrop
+    // (`x`, `y`, `width`, `height`).
+    bounds: vec4<f32>,
+    // The position of the source texture in the backdrop.
+    source_origin: vec2<f32>,
+    // The size of the source texture.
+    source_size: vec2<f32>,
+    // The size of the region of the backdrop which the blurred texture covers.
+    blurred_size: vec2<f32>,
+    // The standard deviation of a blur pass, in texels of the input texture.
+    sigma: f32,
+    // The radius of the corners of `bounds`.
+    corner_radius: f32,
+}
+
+// The maximum number of taps on each side of a texel in a blur pass. The
+// backdrop is downsampled so that the standard deviation of a blur pass is at
+// most a third of this.
+const MAX_TAPS: i32 = 12;
+
+@group(0) @binding(0) var u_sampler: sampler;
+
+@group(1) @binding(0) var<uniform> u: Uniforms;
+@group(1) @binding(1) var t_input: texture_2d<f32>;
+@group(1) @binding(2) var t_source: texture_2d<f32>;
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> @builtin(position) vec4<f32> {
+    // A single triangle which covers the whole render target.
+    let uv = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+    return vec4<f32>(uv * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0), 0.0, 1.0);
+}
+
+// Halve the size of the input texture. Sampling at the corner which is shared
+// by 2x2 texels averages them.
+@fragment
+fn downsample_fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    return textureSampleLevel(t_input, u_sampler, position.xy * 2.0 * u.input_texel, 0.0);
+}
+
+// One direction of a separable Gaussian blur. The output has the same size as
+// the input. Taps outside of the input are clamped to its edge, so the edges of
+// the canvas are not darkened.
+@fragment
+fn blur_fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let uv = position.xy * u.input_texel;
+    let step = u.direction * u.input_texel;
+
+    let taps = min(i32(ceil(u.sigma * 3.0)), MAX_TAPS);
+    let k = -0.5 / (u.sigma * u.sigma);
+
+    var color = textureSampleLevel(t_input, u_sampler, uv, 0.0);
+    var total_weight = 1.0;
+    for (var i = 1; i <= taps; i++) {
+        let offset = f32(i);
+        let weight = exp(offset * offset * k);
+
+        color += (textureSampleLevel(t_input, u_sampler, uv + step * offset, 0.0)
+            + textureSampleLevel(t_input, u_sampler, uv - step * offset, 0.0)) * weight;
+        total_weight += weight * 2.0;
+    }
+
+    return color / total_weight;
+}
+
+// Replace the pixels of the backdrop within the rounded rectangle with the
+// blurred texture. The edges of the rectangle are antialiased by mixing with
+// the unblurred source texture.
+@fragment
+fn composite_fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let pos = position.xy - u.source_origin;
+    let source = textureSampleLevel(t_source, u_sampler, pos / u.source_size, 0.0);
+    let blurred = textureSampleLevel(t_input, u_sampler, pos / u.blurred_size, 0.0);
+
+    let half_size = u.bounds.zw * 0.5;
+    let to_center = position.xy - (u.bounds.xy + half_size);
+    let radius = clamp(u.corner_radius, 0.0, min(half_size.x, half_size.y));
+
+    let q = abs(to_center) - half_size + vec2<f32>(radius, radius);
+    let dist = min(max(q.x, q.y), 0.0) + length(max(q, vec2<f32>(0.0, 0.0))) - radius;
+    let coverage = clamp(0.5 - dist, 0.0, 1.0);
+
+    return mix(source, blurred, coverage);
+}
+
+// Composite the backdrop onto the render target. The backdrop is rendered onto
+// a transparent texture with the same size as the target, so its colors are
+// premultiplied.
+@fragment
+fn present_fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    return textureLoad(t_input, vec2<i32>(position.xy), 0);
+}
@@ -1,5 +1,66 @@
+use std::future::Future;
 use std::marker::PhantomData;
 use std::ops::Range;
+use std::task::{Context, Poll, Waker};
+
+/// An error that occurred while allocating a GPU buffer, because the buffer
+/// is larger than the `max_buffer_size` limit of the device or because the
+/// device is out of memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BufferAllocationError {
+    /// The size of the buffer that was requested, in bytes.
+    pub requested_bytes: u64,
+}
+
+impl std::fmt::Display for BufferAllocationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "failed to allocate a GPU buffer of {} bytes",
+            self.requested_bytes
+        )
+    }
+}
+
+impl std::error::Error for BufferAllocationError {}
+
+/// Create a new [`wgpu::Buffer`], returning an error instead of panicking if
+/// the allocation fails.
+///
+/// The size is checked against the limits of the device, and the allocation
+/// is wrapped in an out of memory error scope. On the web, the error scope
+/// resolves asynchronously, so only the size limit is checked there.
+pub fn try_create_buffer(
+    device: &wgpu::Device,
+    descriptor: &wgpu::BufferDescriptor,
+) -> Result<wgpu::Buffer, BufferAllocationError> {
+    let error = BufferAllocationError {
+        requested_bytes: descriptor.size,
+    };
+
+    if descriptor.size > device.limits().max_buffer_size {
+        return Err(error);
+    }
+
+    device.push_error_scope(wgpu::ErrorFilter::OutOfMemory);
+    let buffer = device.create_buffer(descriptor);
+
+    match poll_once(device.pop_error_scope()) {
+        Some(Some(_)) => Err(error),
+        _ => Ok(buffer),
+    }
+}
+
+/// Poll the future once, and return its output if it is ready.
+fn poll_once<F: Future>(future: F) -> Option<F::Output> {
+    let mut future = std::pin::pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+
+    match future.as_mut().poll(&mut cx) {
+        Poll::Ready(output) => Some(output),
+        Poll::Pending => None,
+    }
+}
 
 /// A helper struct for a [`wgpu::Buffer`].
 pub struct Buffer<T> {
@@ -37,22 +98,32 @@ impl<T: bytemuck::Pod> Buffer<T> {
     }
 
     /// Returns `true` if the buffer was expanded.
-    pub fn expand_to_fit_new_size(&mut self, device: &wgpu::Device, new_count: usize) -> bool {
-        let new_size = (std::mem::size_of::<T>() * new_count) as u64;
+    ///
+    /// If the new buffer can't be allocated, the old buffer is kept and an
+    /// error is returned. See [`try_create_buffer`].
+    pub fn expand_to_fit_new_size(
+        &mut self,
+        device: &wgpu::Device,
+        new_count: usize,
+    ) -> Result<bool, BufferAllocationError> {
+        let new_size = (std::mem::size_of::<T>() as u64).saturating_mul(new_count as u64);
 
         if self.size < new_size {
-            self.raw = device.create_buffer(&wgpu::BufferDescriptor {
-                label: Some(self.label),
-                size: new_size,
-                usage: self.usage,
-                mapped_at_creation: false,
-            });
+            self.raw = try_create_buffer(
+                device,
+                &wgpu::BufferDescriptor {
+                    label: Some(self.label),
+                    size: new_size,
+                    usage: self.usage,
+                    mapped_at_creation: false,
+                },
+            )?;
 
             self.size = new_size;
 
-            true
+            Ok(true)
         } else {
-            false
+            Ok(false)
         }
     }
 
@@ -78,9 +149,404 @@ impl<T: bytemuck::Pod> Buffer<T> {
     }
 }
 
+/// A helper struct for a [`wgpu::Buffer`] that is rewritten every frame.
+///
+/// Data is staged with [`DynamicBuffer::push`], which returns the byte offset
+/// of the data in the buffer, and is then uploaded all at once with
+/// [`DynamicBuffer::upload`]. The buffer grows as needed to fit everything
+/// that was staged in a frame, so calling [`DynamicBuffer::clear`] at the
+/// start of every frame reclaims the space used by the previous frame.
+///
+/// Two GPU buffers are used in turn, so the buffer written to in one frame is
+/// not the one the GPU may still be reading from the previous frame. Because
+/// of this, bind groups which use the buffer should be created once for each
+/// of the two buffers, see [`DynamicBuffer::frame_index`].
+///
+/// Every push is aligned to [`DynamicBuffer::alignment`], which depends on the
+/// usage of the buffer. For uniform and storage buffers this is the minimum
+/// offset alignment of the device, so that the returned offsets can be used
+/// as dynamic offsets of a bind group.
+pub struct DynamicBuffer<T> {
+    raw: [wgpu::Buffer; 2],
+    sizes: [u64; 2],
+    frame_index: usize,
+
+    staging: Vec<u8>,
+
+    label: &'static str,
+    usage: wgpu::BufferUsages,
+    alignment: u64,
+    type_: PhantomData<T>,
+}
+
+impl<T: bytemuck::Pod> DynamicBuffer<T> {
+    /// Create a new dynamic buffer with enough initial space for `amount`
+    /// elements.
+    ///
+    /// `wgpu::BufferUsages::COPY_DST` is added to `usage` automatically.
+    pub fn new(
+        device: &wgpu::Device,
+        label: &'static str,
+        amount: usize,
+        usage: wgpu::BufferUsages,
+    ) -> Self {
+        let usage = usage | wgpu::BufferUsages::COPY_DST;
+
+        let limits = device.limits();
+        let mut alignment = wgpu::COPY_BUFFER_ALIGNMENT;
+        if usage.contains(wgpu::BufferUsages::UNIFORM) {
+            alignment = alignment.max(limits.min_uniform_buffer_offset_alignment as u64);
+        }
+        if usage.contains(wgpu::BufferUsages::STORAGE) {
+            alignment = alignment.max(limits.min_storage_buffer_offset_alignment as u64);
+        }
+
+        let size = next_copy_size::<T>(amount);
+        let create = || {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size,
+                usage,
+                mapped_at_creation: false,
+            })
+        };
+
+        Self {
+            raw: [create(), create()],
+            sizes: [size; 2],
+            frame_index: 0,
+            staging: Vec::new(),
+            label,
+            usage,
+            alignment,
+            type_: PhantomData,
+        }
+    }
+
+    /// Start a new frame.
+    ///
+    /// This discards all data staged in the previous frame and switches to
+    /// the other GPU buffer.
+    pub fn clear(&mut self) {
+        self.staging.clear();
+        self.frame_index = 1 - self.frame_index;
+    }
+
+    /// Stage the given elements to be written to the buffer, and return the
+    /// byte offset where they will be written.
+    ///
+    /// The offset is a multiple of [`DynamicBuffer::alignment`]. Nothing is
+    /// written to the GPU until [`DynamicBuffer::upload`] is called.
+    pub fn push(&mut self, contents: &[T]) -> u64 {
+        let offset = align_to(self.staging.len() as u64, self.alignment);
+
+        self.staging.resize(offset as usize, 0);
+        self.staging
+            .extend_from_slice(bytemuck::cast_slice(contents));
+
+        offset
+    }
+
+    /// Write all of the data staged in this frame to the current GPU buffer.
+    ///
+    /// Returns `true` if the current GPU buffer was recreated to fit the
+    /// data, in which case any bind groups using it must be recreated too.
+    ///
+    /// If the new buffer can't be allocated, nothing is written and an error
+    /// is returned. See [`try_create_buffer`].
+    pub fn upload(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> Result<bool, BufferAllocationError> {
+        if self.staging.is_empty() {
+            return Ok(false);
+        }
+
+        // Writes must be a multiple of `wgpu::COPY_BUFFER_ALIGNMENT` in size.
+        let len = align_to(self.staging.len() as u64, wgpu::COPY_BUFFER_ALIGNMENT);
+        self.staging.resize(len as usize, 0);
+
+        let i = self.frame_index;
+        let expanded = self.sizes[i] < len;
+        if expanded {
+            let new_size = next_copy_size::<u8>(len as usize);
+
+            self.raw[i] = try_create_buffer(
+                device,
+                &wgpu::BufferDescriptor {
+                    label: Some(self.label),
+                    size: new_size,
+                    usage: self.usage,
+                    mapped_at_creation: false,
+                },
+            )?;
+
+            self.sizes[i] = new_size;
+        }
+
+        queue.write_buffer(&self.raw[i], 0, &self.staging);
+
+        Ok(expanded)
+    }
+
+    /// The GPU buffer used in the current frame.
+    pub fn raw(&self) -> &wgpu::Buffer {
+        &self.raw[self.frame_index]
+    }
+
+    /// The GPU buffer with the given index (either `0` or `1`).
+    ///
+    /// Use this to create a bind group for each of the two buffers.
+    pub fn raw_at(&self, frame_index: usize) -> &wgpu::Buffer {
+        &self.raw[frame_index]
+    }
+
+    /// The index of the GPU buffer used in the current frame (either `0` or
+    /// `1`).
+    pub fn frame_index(&self) -> usize {
+        self.frame_index
+    }
+
+    /// A slice of `count` elements of the current GPU buffer, starting at the
+    /// byte offset returned by [`DynamicBuffer::push`].
+    pub fn slice(&self, offset: u64, count: usize) -> wgpu::BufferSlice<'_> {
+        self.raw()
+            .slice(offset..offset + (count * std::mem::size_of::<T>()) as u64)
+    }
+
+    /// The alignment in bytes of every offset returned by
+    /// [`DynamicBuffer::push`].
+    pub fn alignment(&self) -> u64 {
+        self.alignment
+    }
+
+    /// The number of bytes staged in this frame.
+    pub fn len(&self) -> u64 {
+        self.staging.len() as u64
+    }
+
+    /// Returns `true` if nothing has been staged in this frame.
+    pub fn is_empty(&self) -> bool {
+        self.staging.is_empty()
+    }
+
+    pub fn label(&self) -> &'static str {
+        self.label
+    }
+}
+
+fn align_to(value: u64, alignment: u64) -> u64 {
+    value.div_ceil(alignment) * alignment
+}
+
 pub fn next_copy_size<T>(amount: usize) -> u64 {
     let align_mask = wgpu::COPY_BUFFER_ALIGNMENT - 1;
 
     (((std::mem::size_of::<T>() * amount).next_power_of_two() as u64 + align_mask) & !align_mask)
         .max(wgpu::COPY_BUFFER_ALIGNMENT)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns `None` if there is no GPU adapter, in which case the test is
+    /// skipped.
+    fn device() -> Option<(wgpu::Device, wgpu::Queue)> {
+        device_with_max_buffer_size(None)
+    }
+
+    fn device_with_max_buffer_size(
+        max_buffer_size: Option<u64>,
+    ) -> Option<(wgpu::Device, wgpu::Queue)> {
+        pollster::block_on(async {
+            let instance = wgpu::Instance::default();
+            let adapter = instance
+                .request_adapter(&wgpu::RequestAdapterOptions::default())
+                .await?;
+
+            let mut limits = adapter.limits();
+            if let Some(max_buffer_size) = max_buffer_size {
+                limits.max_buffer_size = max_buffer_size;
+            }
+
+            adapter
+                .request_device(
+                    &wgpu::DeviceDescriptor {
+                        required_limits: limits,
+                        ..Default::default()
+                    },
+                    None,
+                )
+                .await
+                .ok()
+        })
+    }
+
+    /// Read back the first `len` bytes of a buffer created with
+    /// `wgpu::BufferUsages::COPY_SRC`.
+    fn read(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        buffer: &wgpu::Buffer,
+        len: u64,
+    ) -> Vec<u8> {
+        let readback = device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: len,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        encoder.copy_buffer_to_buffer(buffer, 0, &readback, 0, len);
+        queue.submit(Some(encoder.finish()));
+
+        readback
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, |res| res.unwrap());
+        device.poll(wgpu::Maintain::Wait);
+
+        let bytes = readback.slice(..).get_mapped_range().to_vec();
+        readback.unmap();
+
+        bytes
+    }
+
+    #[test]
+    fn pushes_are_written_at_aligned_offsets() {
+        let Some((device, queue)) = device() else {
+            return;
+        };
+
+        let mut buffer: DynamicBuffer<[u32; 3]> = DynamicBuffer::new(
+            &device,
+            "test buffer",
+            4,
+            wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_SRC,
+        );
+
+        let alignment = buffer.alignment();
+        assert_eq!(
+            alignment,
+            device.limits().min_uniform_buffer_offset_alignment as u64
+        );
+
+        assert_eq!(buffer.push(&[[1; 3]]), 0);
+        assert_eq!(buffer.push(&[[2; 3], [3; 3]]), alignment);
+        assert_eq!(buffer.push(&[[4; 3]]), alignment * 2);
+        assert_eq!(buffer.len(), alignment * 2 + 12);
+
+        buffer.upload(&device, &queue).unwrap();
+
+        let bytes = read(&device, &queue, buffer.raw(), buffer.len());
+        let word =
+            |offset: u64| u32::from_ne_bytes(bytes[offset as usize..][..4].try_into().unwrap());
+
+        assert_eq!(word(0), 1);
+        assert_eq!(word(alignment), 2);
+        assert_eq!(word(alignment + 12), 3);
+        assert_eq!(word(alignment * 2), 4);
+        // The gaps between the pushes are padded with zeros.
+        assert_eq!(word(12), 0);
+        assert_eq!(word(alignment - 4), 0);
+    }
+
+    #[test]
+    fn vertex_buffers_are_aligned_to_the_copy_alignment() {
+        let Some((device, _queue)) = device() else {
+            return;
+        };
+
+        let mut buffer: DynamicBuffer<u16> =
+            DynamicBuffer::new(&device, "test buffer", 4, wgpu::BufferUsages::VERTEX);
+
+        assert_eq!(buffer.alignment(), wgpu::COPY_BUFFER_ALIGNMENT);
+        assert_eq!(buffer.push(&[1]), 0);
+        assert_eq!(buffer.push(&[2, 3, 4]), 4);
+        assert_eq!(buffer.push(&[5]), 12);
+    }
+
+    #[test]
+    fn capacity_grows_across_frames() {
+        let Some((device, queue)) = device() else {
+            return;
+        };
+
+        let mut buffer: DynamicBuffer<u32> =
+            DynamicBuffer::new(&device, "test buffer", 4, wgpu::BufferUsages::VERTEX);
+
+        // Nothing to write.
+        assert!(!buffer.upload(&device, &queue).unwrap());
+
+        // Fits in the initial capacity.
+        buffer.push(&[0; 4]);
+        assert!(!buffer.upload(&device, &queue).unwrap());
+        assert_eq!(buffer.raw().size(), 16);
+
+        // The second buffer grows to the next power of two, and the first
+        // buffer is left alone.
+        buffer.clear();
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.frame_index(), 1);
+        buffer.push(&[0; 10]);
+        assert!(buffer.upload(&device, &queue).unwrap());
+        assert_eq!(buffer.raw().size(), 64);
+        assert_eq!(buffer.raw_at(0).size(), 16);
+
+        buffer.clear();
+        assert_eq!(buffer.frame_index(), 0);
+        buffer.push(&[0; 10]);
+        assert!(buffer.upload(&device, &queue).unwrap());
+        assert_eq!(buffer.raw().size(), 64);
+
+        // Both buffers fit the data now.
+        buffer.clear();
+        buffer.push(&[0; 16]);
+        assert!(!buffer.upload(&device, &queue).unwrap());
+        assert_eq!(buffer.raw_at(0).size(), 64);
+        assert_eq!(buffer.raw_at(1).size(), 64);
+    }
+
+    #[test]
+    fn buffers_larger_than_the_device_limit_are_not_allocated() {
+        let Some((device, queue)) = device_with_max_buffer_size(Some(256)) else {
+            return;
+        };
+
+        let res = try_create_buffer(
+            &device,
+            &wgpu::BufferDescriptor {
+                label: None,
+                size: 257,
+                usage: wgpu::BufferUsages::VERTEX,
+                mapped_at_creation: false,
+            },
+        );
+        assert_eq!(res.unwrap_err().requested_bytes, 257);
+
+        // The old buffer is kept.
+        let mut buffer: Buffer<u32> =
+            Buffer::new(&device, "test buffer", 4, wgpu::BufferUsages::VERTEX);
+        let err = buffer.expand_to_fit_new_size(&device, 65).unwrap_err();
+        assert_eq!(err.requested_bytes, 260);
+        assert_eq!(buffer.raw.size(), 16);
+        assert!(buffer.expand_to_fit_new_size(&device, 64).unwrap());
+
+        // Nothing is written, and the buffer can be used again in the next
+        // frame.
+        let mut buffer: DynamicBuffer<u32> =
+            DynamicBuffer::new(&device, "test buffer", 4, wgpu::BufferUsages::VERTEX);
+        buffer.push(&[0; 65]);
+        let err = buffer.upload(&device, &queue).unwrap_err();
+        assert_eq!(err.requested_bytes, 512);
+        assert_eq!(buffer.raw().size(), 16);
+
+        buffer.clear();
+        buffer.clear();
+        buffer.push(&[0; 64]);
+        assert!(buffer.upload(&device, &queue).unwrap());
+        assert_eq!(buffer.raw().size(), 256);
+    }
+}
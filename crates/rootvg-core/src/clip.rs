@@ -0,0 +1,137 @@
+use crate::math::{Rect, ScaleFactor};
+
+/// A rounded rectangle used to clip primitives, in logical points.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RoundedClip {
+    pub rect: Rect,
+    pub radius: f32,
+}
+
+impl RoundedClip {
+    pub const fn new(rect: Rect, radius: f32) -> Self {
+        Self { rect, radius }
+    }
+}
+
+/// The shader uniform struct for a rounded clipping rectangle, in physical
+/// pixels.
+///
+/// Since this is bound once per batch, it also holds the depth of the batch
+/// which is used when the depth buffer is enabled.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, bytemuck::Zeroable, bytemuck::Pod)]
+pub struct ClipUniforms {
+    /// The rectangle in the form `[x, y, width, height]`.
+    pub rect: [f32; 4],
+    pub radius: f32,
+    /// `1` if clipping is enabled, `0` otherwise.
+    pub enabled: u32,
+    /// The depth of the batch in the range `[0.0, 1.0]`.
+    pub depth: f32,
+    pub _padding: f32,
+}
+
+impl ClipUniforms {
+    pub fn new(clip: Option<RoundedClip>, depth: f32, scale_factor: ScaleFactor) -> Self {
+        let Some(clip) = clip else {
+            return Self {
+                depth,
+                ..Default::default()
+            };
+        };
+
+        Self {
+            rect: [
+                clip.rect.origin.x * scale_factor.0,
+                clip.rect.origin.y * scale_factor.0,
+                clip.rect.size.width * scale_factor.0,
+                clip.rect.size.height * scale_factor.0,
+            ],
+            radius: clip.radius * scale_factor.0,
+            enabled: 1,
+            depth,
+            _padding: 0.0,
+        }
+    }
+
+    pub fn layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("rootvg-core clip layout"),
+            entries: &[Self::entry(0)],
+        })
+    }
+
+    pub fn entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
+        wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: wgpu::BufferSize::new(
+                    std::mem::size_of::<Self>() as wgpu::BufferAddress
+                ),
+            },
+            count: None,
+        }
+    }
+}
+
+/// A helper struct holding the rounded clipping uniforms of a single batch.
+///
+/// A newly created buffer has clipping disabled.
+#[derive(Debug)]
+pub struct ClipBuffer {
+    buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    uniforms: ClipUniforms,
+}
+
+impl ClipBuffer {
+    pub fn new(device: &wgpu::Device, layout: &wgpu::BindGroupLayout) -> Self {
+        // Buffers are zero-initialized, so clipping starts out disabled.
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("rootvg-core clip buffer"),
+            size: std::mem::size_of::<ClipUniforms>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("rootvg-core clip bind group"),
+            layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+        });
+
+        Self {
+            buffer,
+            bind_group,
+            uniforms: ClipUniforms::default(),
+        }
+    }
+
+    /// Update the clipping rectangle and the depth of the batch. The buffer
+    /// is only written to if the uniforms have changed.
+    pub fn prepare(
+        &mut self,
+        clip: Option<RoundedClip>,
+        depth: f32,
+        scale_factor: ScaleFactor,
+        queue: &wgpu::Queue,
+    ) {
+        let uniforms = ClipUniforms::new(clip, depth, scale_factor);
+
+        if self.uniforms != uniforms {
+            self.uniforms = uniforms;
+            queue.write_buffer(&self.buffer, 0, bytemuck::bytes_of(&uniforms));
+        }
+    }
+
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+}
@@ -74,6 +74,175 @@ pub const fn hex(color: u32) -> RGBA8 {
     )
 }
 
+/// Returns a color from its HSL components.
+///
+/// The hue `h` is in degrees and wraps around, so `-120.0`, `240.0`, and
+/// `600.0` are all the same hue. The saturation `s` and lightness `l` are in
+/// the range `[0.0, 1.0]`.
+pub fn from_hsl(h: f32, s: f32, l: f32, a: u8) -> RGBA8 {
+    let s = s.clamp(0.0, 1.0);
+    let l = l.clamp(0.0, 1.0);
+
+    let [r, g, b] = hsl_to_rgb(h, s, l);
+
+    RGBA8::new(unorm_to_u8(r), unorm_to_u8(g), unorm_to_u8(b), a)
+}
+
+/// Returns the `[hue, saturation, lightness]` components of a color.
+///
+/// The hue is in degrees in the range `[0.0, 360.0)`, and the saturation and
+/// lightness are in the range `[0.0, 1.0]`. Grays have a hue and saturation
+/// of `0.0`.
+pub fn to_hsl(color: RGBA8) -> [f32; 3] {
+    let [r, g, b] = u8_to_unorm(color);
+    rgb_to_hsl(r, g, b)
+}
+
+/// Returns a color from its HSV components.
+///
+/// The hue `h` is in degrees and wraps around. The saturation `s` and value
+/// `v` are in the range `[0.0, 1.0]`.
+pub fn from_hsv(h: f32, s: f32, v: f32, a: u8) -> RGBA8 {
+    let s = s.clamp(0.0, 1.0);
+    let v = v.clamp(0.0, 1.0);
+
+    let chroma = v * s;
+    let [r, g, b] = hue_to_rgb(h, chroma, v - chroma);
+
+    RGBA8::new(unorm_to_u8(r), unorm_to_u8(g), unorm_to_u8(b), a)
+}
+
+/// Returns the `[hue, saturation, value]` components of a color.
+///
+/// The hue is in degrees in the range `[0.0, 360.0)`, and the saturation and
+/// value are in the range `[0.0, 1.0]`. Grays have a hue and saturation of
+/// `0.0`.
+pub fn to_hsv(color: RGBA8) -> [f32; 3] {
+    let [r, g, b] = u8_to_unorm(color);
+
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+
+    let s = if max > 0.0 { delta / max } else { 0.0 };
+
+    [hue(r, g, b, max, delta), s, max]
+}
+
+/// Extension methods for [`RGBA8`] which convert colors from and to HSL and
+/// HSV, so that they can be called as `RGBA8::from_hsl(210.0, 0.5, 0.5, 255)`
+/// and `color.to_hsl()`.
+///
+/// See [`from_hsl`], [`to_hsl`], [`from_hsv`], and [`to_hsv`].
+pub trait Rgba8Ext {
+    /// Returns a color from its HSL components. See [`from_hsl`].
+    fn from_hsl(h: f32, s: f32, l: f32, a: u8) -> Self;
+
+    /// Returns the `[hue, saturation, lightness]` components of the color.
+    /// See [`to_hsl`].
+    fn to_hsl(&self) -> [f32; 3];
+
+    /// Returns a color from its HSV components. See [`from_hsv`].
+    fn from_hsv(h: f32, s: f32, v: f32, a: u8) -> Self;
+
+    /// Returns the `[hue, saturation, value]` components of the color. See
+    /// [`to_hsv`].
+    fn to_hsv(&self) -> [f32; 3];
+}
+
+impl Rgba8Ext for RGBA8 {
+    fn from_hsl(h: f32, s: f32, l: f32, a: u8) -> Self {
+        from_hsl(h, s, l, a)
+    }
+
+    fn to_hsl(&self) -> [f32; 3] {
+        to_hsl(*self)
+    }
+
+    fn from_hsv(h: f32, s: f32, v: f32, a: u8) -> Self {
+        from_hsv(h, s, v, a)
+    }
+
+    fn to_hsv(&self) -> [f32; 3] {
+        to_hsv(*self)
+    }
+}
+
+fn rgb_to_hsl(r: f32, g: f32, b: f32) -> [f32; 3] {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+
+    let l = (max + min) * 0.5;
+    let s = if delta > 0.0 {
+        (delta / (1.0 - (2.0 * l - 1.0).abs())).min(1.0)
+    } else {
+        0.0
+    };
+
+    [hue(r, g, b, max, delta), s, l]
+}
+
+fn hsl_to_rgb(h: f32, s: f32, l: f32) -> [f32; 3] {
+    let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    hue_to_rgb(h, chroma, l - chroma * 0.5)
+}
+
+/// The hue in degrees of the given color, where `max` is the largest
+/// component and `delta` is the difference between the largest and smallest
+/// components.
+fn hue(r: f32, g: f32, b: f32, max: f32, delta: f32) -> f32 {
+    if delta <= 0.0 {
+        return 0.0;
+    }
+
+    let h = if max == r {
+        ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        (b - r) / delta + 2.0
+    } else {
+        (r - g) / delta + 4.0
+    };
+
+    // `rem_euclid` can return exactly `360.0` due to rounding.
+    let h = h * 60.0;
+    if h >= 360.0 {
+        0.0
+    } else {
+        h
+    }
+}
+
+/// Convert a hue in degrees with the given chroma into RGB components, adding
+/// `m` to each component.
+fn hue_to_rgb(h: f32, chroma: f32, m: f32) -> [f32; 3] {
+    let h = h.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (h.rem_euclid(2.0) - 1.0).abs());
+
+    let [r, g, b] = match h as u32 {
+        0 => [chroma, x, 0.0],
+        1 => [x, chroma, 0.0],
+        2 => [0.0, chroma, x],
+        3 => [0.0, x, chroma],
+        4 => [x, 0.0, chroma],
+        _ => [chroma, 0.0, x],
+    };
+
+    [r + m, g + m, b + m]
+}
+
+fn u8_to_unorm(color: RGBA8) -> [f32; 3] {
+    [
+        f32::from(color.r) * (1.0 / 255.0),
+        f32::from(color.g) * (1.0 / 255.0),
+        f32::from(color.b) * (1.0 / 255.0),
+    ]
+}
+
+fn unorm_to_u8(c: f32) -> u8 {
+    (c * 255.0).round().clamp(0.0, 255.0) as u8
+}
+
 /// A color packed as 4 floats in the `sRGB` color space.
 #[repr(C)]
 #[derive(Default, Debug, Clone, Copy, PartialEq, bytemuck::Zeroable, bytemuck::Pod)]
@@ -171,6 +340,183 @@ impl PackedSrgb {
     pub const fn raw(&self) -> [f32; 4] {
         self.0
     }
+
+    /// Convert this color to 8-bit sRGB.
+    ///
+    /// These are the same bytes that a render target with the default
+    /// texture format stores for this color.
+    pub fn to_rgba8(&self) -> RGBA8 {
+        let [r, g, b, a] = self.0;
+
+        RGBA8::new(
+            unorm_to_u8(encode_gamma(r.clamp(0.0, 1.0))),
+            unorm_to_u8(encode_gamma(g.clamp(0.0, 1.0))),
+            unorm_to_u8(encode_gamma(b.clamp(0.0, 1.0))),
+            unorm_to_u8(a),
+        )
+    }
+
+    /// Returns this color with its HSL lightness increased by `amount`, where
+    /// `amount` is in the range `[0.0, 1.0]`.
+    pub fn lighten(&self, amount: f32) -> Self {
+        self.map_lightness(|l| l + amount)
+    }
+
+    /// Returns this color with its HSL lightness decreased by `amount`, where
+    /// `amount` is in the range `[0.0, 1.0]`.
+    pub fn darken(&self, amount: f32) -> Self {
+        self.map_lightness(|l| l - amount)
+    }
+
+    /// Returns the result of compositing `source` on top of this color with
+    /// the given Porter-Duff operation.
+    ///
+    /// This performs the same math as the GPU does when drawing, on the
+    /// premultiplied components as they are stored in [`PackedSrgb`]. With
+    /// the `web-colors` feature (the default) these are gamma encoded, like
+    /// the values blended into the render target, and otherwise they are in
+    /// linear light.
+    pub fn blend(&self, source: Self, op: CompositeOperation) -> Self {
+        let src = source.premultiplied();
+        let dst = self.premultiplied();
+
+        let (src_factor, dst_factor) = op.factors(src[3], dst[3]);
+
+        Self::from_premultiplied(std::array::from_fn(|i| {
+            (src[i] * src_factor + dst[i] * dst_factor).clamp(0.0, 1.0)
+        }))
+    }
+
+    /// Linearly interpolate between this color and `other` in linear-light
+    /// space, where `t = 0.0` returns this color and `t = 1.0` returns
+    /// `other`.
+    ///
+    /// The components are interpolated with premultiplied alpha so that
+    /// mixing with a transparent color does not darken the result. `t` is
+    /// clamped to the range `[0.0, 1.0]`.
+    pub fn mix(&self, other: Self, t: f32) -> Self {
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+
+        let a = self.premultiplied_linear_light();
+        let b = other.premultiplied_linear_light();
+
+        Self::from_premultiplied_linear_light(std::array::from_fn(|i| a[i] + (b[i] - a[i]) * t))
+    }
+
+    fn premultiplied(&self) -> [f32; 4] {
+        let [r, g, b, a] = self.0;
+        let a = a.clamp(0.0, 1.0);
+
+        [
+            r.clamp(0.0, 1.0) * a,
+            g.clamp(0.0, 1.0) * a,
+            b.clamp(0.0, 1.0) * a,
+            a,
+        ]
+    }
+
+    fn from_premultiplied([r, g, b, a]: [f32; 4]) -> Self {
+        if a <= 0.0 {
+            return Self::TRANSPARENT;
+        }
+
+        let unpremultiply = |c: f32| (c / a).clamp(0.0, 1.0);
+
+        Self([unpremultiply(r), unpremultiply(g), unpremultiply(b), a])
+    }
+
+    fn premultiplied_linear_light(&self) -> [f32; 4] {
+        let [r, g, b, a] = self.0;
+        let a = a.clamp(0.0, 1.0);
+
+        [
+            to_linear_light(r.clamp(0.0, 1.0)) * a,
+            to_linear_light(g.clamp(0.0, 1.0)) * a,
+            to_linear_light(b.clamp(0.0, 1.0)) * a,
+            a,
+        ]
+    }
+
+    fn from_premultiplied_linear_light([r, g, b, a]: [f32; 4]) -> Self {
+        if a <= 0.0 {
+            return Self::TRANSPARENT;
+        }
+
+        let unpremultiply = |c: f32| from_linear_light((c / a).clamp(0.0, 1.0));
+
+        Self([unpremultiply(r), unpremultiply(g), unpremultiply(b), a])
+    }
+
+    fn map_lightness(&self, f: impl FnOnce(f32) -> f32) -> Self {
+        // HSL is defined on gamma-encoded components.
+        let [r, g, b, a] = self.0;
+        let [h, s, l] = rgb_to_hsl(
+            encode_gamma(r.clamp(0.0, 1.0)),
+            encode_gamma(g.clamp(0.0, 1.0)),
+            encode_gamma(b.clamp(0.0, 1.0)),
+        );
+
+        let [r, g, b] = hsl_to_rgb(h, s, f(l).clamp(0.0, 1.0));
+
+        Self([
+            decode_gamma(r.clamp(0.0, 1.0)),
+            decode_gamma(g.clamp(0.0, 1.0)),
+            decode_gamma(b.clamp(0.0, 1.0)),
+            a,
+        ])
+    }
+}
+
+/// A Porter-Duff compositing operation, which describes how a source color is
+/// combined with the destination color it is drawn on top of.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CompositeOperation {
+    /// Draw the source on top of the destination.
+    #[default]
+    SourceOver,
+    /// Only keep the source where it overlaps the destination.
+    SourceIn,
+    /// Only keep the source where it does not overlap the destination.
+    SourceOut,
+    /// Draw the source on top of the destination, but only where it overlaps
+    /// the destination.
+    Atop,
+    /// Draw the destination on top of the source.
+    DestinationOver,
+    /// Only keep the destination where it overlaps the source.
+    DestinationIn,
+    /// Only keep the destination where it does not overlap the source.
+    DestinationOut,
+    /// Draw the destination on top of the source, but only where it overlaps
+    /// the source.
+    DestinationAtop,
+    /// Add the source and the destination together.
+    Lighter,
+    /// Replace the destination with the source.
+    Copy,
+    /// Only keep the source and the destination where they do not overlap.
+    Xor,
+}
+
+impl CompositeOperation {
+    /// The `(source, destination)` factors which the premultiplied source and
+    /// destination colors are multiplied by, given their alpha values.
+    fn factors(&self, src_alpha: f32, dst_alpha: f32) -> (f32, f32) {
+        match self {
+            Self::SourceOver => (1.0, 1.0 - src_alpha),
+            Self::SourceIn => (dst_alpha, 0.0),
+            Self::SourceOut => (1.0 - dst_alpha, 0.0),
+            Self::Atop => (dst_alpha, 1.0 - src_alpha),
+            Self::DestinationOver => (1.0 - dst_alpha, 1.0),
+            Self::DestinationIn => (0.0, src_alpha),
+            Self::DestinationOut => (0.0, 1.0 - src_alpha),
+            Self::DestinationAtop => (1.0 - dst_alpha, src_alpha),
+            Self::Lighter => (1.0, 1.0),
+            Self::Copy => (1.0, 0.0),
+            Self::Xor => (1.0 - dst_alpha, 1.0 - src_alpha),
+        }
+    }
 }
 
 impl From<RGB8> for PackedSrgb {
@@ -191,8 +537,85 @@ impl From<PackedSrgb> for [f32; 4] {
     }
 }
 
+/// Converts the color into a clear color for a render pass.
+///
+/// The components are passed through as is, which is the same value the
+/// shaders output for this color. The clear color is then converted to the
+/// format of the render target in the same way as the output of the shaders,
+/// so a render target cleared with this color matches an opaque quad of the
+/// same color, both with and without the `web-colors` feature.
+impl From<PackedSrgb> for wgpu::Color {
+    fn from(color: PackedSrgb) -> Self {
+        let [r, g, b, a] = color.raw();
+
+        wgpu::Color {
+            r: r as f64,
+            g: g as f64,
+            b: b as f64,
+            a: a as f64,
+        }
+    }
+}
+
 pub use srgb8_to_linear::{srgb8_to_linear, GAMMA_CORRECTION, SRGBA8_TEXTURE_FORMAT};
 
+/// Convert a component as stored in [`PackedSrgb`] into a gamma-encoded sRGB
+/// component.
+fn encode_gamma(c: f32) -> f32 {
+    if GAMMA_CORRECTION {
+        linear_to_srgb(c)
+    } else {
+        c
+    }
+}
+
+/// Convert a gamma-encoded sRGB component into a component as stored in
+/// [`PackedSrgb`].
+fn decode_gamma(c: f32) -> f32 {
+    if GAMMA_CORRECTION {
+        srgb_to_linear(c)
+    } else {
+        c
+    }
+}
+
+/// Convert a component as stored in [`PackedSrgb`] into a linear-light
+/// component.
+fn to_linear_light(c: f32) -> f32 {
+    if GAMMA_CORRECTION {
+        c
+    } else {
+        srgb_to_linear(c)
+    }
+}
+
+/// Convert a linear-light component into a component as stored in
+/// [`PackedSrgb`].
+fn from_linear_light(c: f32) -> f32 {
+    if GAMMA_CORRECTION {
+        c
+    } else {
+        linear_to_srgb(c)
+    }
+}
+
+// As described in: https://en.wikipedia.org/wiki/SRGB
+pub(crate) fn linear_to_srgb(c: f32) -> f32 {
+    if c < 0.0031308 {
+        12.92 * c
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+pub(crate) fn srgb_to_linear(c: f32) -> f32 {
+    if c < 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
 #[cfg(feature = "web-colors")]
 mod srgb8_to_linear {
     /// A flag that indicates whether the renderer should perform gamma correction.
@@ -263,3 +686,175 @@ mod srgb8_to_linear {
         0.93011117, 0.9386859, 0.9473069, 0.9559735, 0.9646866, 0.9734455, 0.98225087, 0.9911022, 1.0
     ];
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRIMARIES: [(RGBA8, f32); 6] = [
+        (rgb(255, 0, 0), 0.0),
+        (rgb(255, 255, 0), 60.0),
+        (rgb(0, 255, 0), 120.0),
+        (rgb(0, 255, 255), 180.0),
+        (rgb(0, 0, 255), 240.0),
+        (rgb(255, 0, 255), 300.0),
+    ];
+
+    fn assert_components(actual: [f32; 3], expected: [f32; 3]) {
+        for (a, e) in actual.iter().zip(expected.iter()) {
+            assert!((a - e).abs() < 1e-4, "{actual:?} != {expected:?}");
+        }
+    }
+
+    #[test]
+    fn hsl_round_trips_primaries() {
+        for (color, hue) in PRIMARIES {
+            assert_components(color.to_hsl(), [hue, 1.0, 0.5]);
+            assert_eq!(RGBA8::from_hsl(hue, 1.0, 0.5, 255), color);
+        }
+    }
+
+    #[test]
+    fn hsv_round_trips_primaries() {
+        for (color, hue) in PRIMARIES {
+            assert_components(color.to_hsv(), [hue, 1.0, 1.0]);
+            assert_eq!(RGBA8::from_hsv(hue, 1.0, 1.0, 255), color);
+        }
+    }
+
+    #[test]
+    fn grays_have_zero_saturation() {
+        for v in [0, 1, 128, 254, 255] {
+            let color = gray(v);
+            let unorm = f32::from(v) / 255.0;
+
+            assert_components(color.to_hsl(), [0.0, 0.0, unorm]);
+            assert_components(color.to_hsv(), [0.0, 0.0, unorm]);
+
+            // The hue of a gray doesn't matter.
+            for hue in [0.0, 90.0, 200.0] {
+                assert_eq!(RGBA8::from_hsl(hue, 0.0, unorm, 255), color);
+                assert_eq!(RGBA8::from_hsv(hue, 0.0, unorm, 255), color);
+            }
+        }
+    }
+
+    #[test]
+    fn hue_wraps_around_at_360_degrees() {
+        let red = rgb(255, 0, 0);
+        let blue = rgb(0, 0, 255);
+
+        assert_eq!(RGBA8::from_hsl(360.0, 1.0, 0.5, 255), red);
+        assert_eq!(RGBA8::from_hsl(720.0, 1.0, 0.5, 255), red);
+        assert_eq!(RGBA8::from_hsl(-120.0, 1.0, 0.5, 255), blue);
+        assert_eq!(RGBA8::from_hsl(600.0, 1.0, 0.5, 255), blue);
+
+        assert_eq!(RGBA8::from_hsv(360.0, 1.0, 1.0, 255), red);
+        assert_eq!(RGBA8::from_hsv(-120.0, 1.0, 1.0, 255), blue);
+
+        // Hues just below 360 degrees stay below it instead of wrapping.
+        let [h, _, _] = rgb(255, 0, 1).to_hsl();
+        assert!(h > 359.0 && h < 360.0, "{h}");
+    }
+
+    #[test]
+    fn hsl_and_hsv_round_trip_all_colors() {
+        for r in (0..=255).step_by(17) {
+            for g in (0..=255).step_by(17) {
+                for b in (0..=255).step_by(17) {
+                    let color = rgba(r, g, b, 100);
+
+                    let [h, s, l] = color.to_hsl();
+                    assert_eq!(RGBA8::from_hsl(h, s, l, 100), color);
+
+                    let [h, s, v] = color.to_hsv();
+                    assert_eq!(RGBA8::from_hsv(h, s, v, 100), color);
+                }
+            }
+        }
+    }
+
+    fn assert_color(actual: PackedSrgb, expected: [f32; 4]) {
+        for (a, e) in actual.raw().iter().zip(expected.iter()) {
+            assert!((a - e).abs() < 1e-4, "{actual:?} != {expected:?}");
+        }
+    }
+
+    #[test]
+    fn mixing_black_and_white_gives_the_linear_light_midpoint() {
+        let mid = PackedSrgb::BLACK.mix(PackedSrgb::WHITE, 0.5);
+
+        let [r, g, b, a] = mid.raw();
+        assert_components(
+            [to_linear_light(r), to_linear_light(g), to_linear_light(b)],
+            [0.5, 0.5, 0.5],
+        );
+        assert_eq!(a, 1.0);
+
+        // Half of the light is brighter than half of the sRGB range.
+        assert_eq!(mid.to_rgba8(), rgb(188, 188, 188));
+
+        assert_color(
+            PackedSrgb::BLACK.mix(PackedSrgb::WHITE, 0.0),
+            [0.0, 0.0, 0.0, 1.0],
+        );
+        assert_color(
+            PackedSrgb::BLACK.mix(PackedSrgb::WHITE, 1.0),
+            [1.0, 1.0, 1.0, 1.0],
+        );
+    }
+
+    #[test]
+    fn mixing_with_transparent_keeps_the_color() {
+        let red = PackedSrgb([1.0, 0.0, 0.0, 1.0]);
+
+        assert_color(red.mix(PackedSrgb::TRANSPARENT, 0.5), [1.0, 0.0, 0.0, 0.5]);
+    }
+
+    #[test]
+    fn blend_applies_each_composite_operation() {
+        use CompositeOperation::*;
+
+        // Translucent blue drawn on top of more opaque red, so that every
+        // operation gives a different result.
+        let source = PackedSrgb([0.0, 0.0, 1.0, 0.25]);
+        let destination = PackedSrgb([1.0, 0.0, 0.0, 0.75]);
+
+        let cases = [
+            (SourceOver, [9.0 / 13.0, 0.0, 4.0 / 13.0, 0.8125]),
+            (SourceIn, [0.0, 0.0, 1.0, 0.1875]),
+            (SourceOut, [0.0, 0.0, 1.0, 0.0625]),
+            (Atop, [0.75, 0.0, 0.25, 0.75]),
+            (DestinationOver, [12.0 / 13.0, 0.0, 1.0 / 13.0, 0.8125]),
+            (DestinationIn, [1.0, 0.0, 0.0, 0.1875]),
+            (DestinationOut, [1.0, 0.0, 0.0, 0.5625]),
+            (DestinationAtop, [0.75, 0.0, 0.25, 0.25]),
+            (Lighter, [0.75, 0.0, 0.25, 1.0]),
+            (Copy, [0.0, 0.0, 1.0, 0.25]),
+            (Xor, [0.9, 0.0, 0.1, 0.625]),
+        ];
+
+        for (op, expected) in cases {
+            assert_color(destination.blend(source, op), expected);
+        }
+    }
+
+    #[test]
+    fn blending_opaque_colors() {
+        let source = PackedSrgb([0.0, 0.0, 1.0, 1.0]);
+        let destination = PackedSrgb([1.0, 0.0, 0.0, 1.0]);
+
+        assert_eq!(
+            destination.blend(source, CompositeOperation::SourceOver),
+            source
+        );
+        assert_eq!(
+            destination.blend(source, CompositeOperation::DestinationOver),
+            destination
+        );
+        assert_eq!(
+            destination.blend(source, CompositeOperation::Xor),
+            PackedSrgb::TRANSPARENT
+        );
+    }
+}
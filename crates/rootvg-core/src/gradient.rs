@@ -2,22 +2,53 @@
 // https://github.com/iced-rs/iced/blob/31d1d5fecbef50fa319cabd5d4194f1e4aaefa21/graphics/src/gradient.rs
 // Iced license (MIT): https://github.com/iced-rs/iced/blob/31d1d5fecbef50fa319cabd5d4194f1e4aaefa21/LICENSE
 
-use half::f16;
 use std::cmp::Ordering;
 use std::f32::consts::FRAC_PI_2;
 
-use super::color::PackedSrgb;
+use super::color::{PackedSrgb, GAMMA_CORRECTION};
 use crate::math::{Angle, Point, Rect};
 
-pub const MAX_STOPS: usize = 4;
+mod ramp;
 
-/// A fill which transitions colors progressively along a direction, either linearly, radially (TBD),
-/// or conically (TBD).
+pub use ramp::{GradientRamp, GradientRampCache, RAMP_WIDTH};
+
+/// The maximum number of color stops in a gradient.
+///
+/// Up to [`MAX_INLINE_STOPS`] stops are passed to the shaders in the vertex
+/// data of each primitive. Gradients with more stops are baked into a ramp
+/// texture by the quad and mesh pipelines.
+pub const MAX_STOPS: usize = 16;
+
+/// The maximum number of color stops which are passed inline to the quad and
+/// mesh shaders. Gradients with more stops are baked into a
+/// [`GradientRampCache`] instead.
+pub const MAX_INLINE_STOPS: usize = 8;
+
+/// The number of bits used to store the offset of a color stop.
+const OFFSET_BITS: u32 = 12;
+const OFFSET_MAX: f32 = ((1 << OFFSET_BITS) - 1) as f32;
+/// The number of words needed to store the offsets of [`MAX_STOPS`] stops.
+const OFFSET_WORDS: usize = (MAX_STOPS * OFFSET_BITS as usize).div_ceil(32);
+
+/// The bit of the packed gradient kind which is set if the colors are gamma
+/// encoded and need to be converted to linear RGB by the shader.
+const GAMMA_ENCODED_FLAG: u32 = 1 << 24;
+
+/// The bit of the kind of a [`ShaderGradient`] which is set if the gradient
+/// is sampled from a ramp texture instead of its inline color stops.
+const RAMP_FLAG: u32 = 1 << 25;
+
+/// A fill which transitions colors progressively along a direction, either linearly, radially,
+/// or conically.
 #[derive(Debug, Clone, Copy, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Gradient {
     /// A linear gradient interpolates colors along a direction at a specific angle.
     Linear(LinearGradient),
+    /// A radial gradient interpolates colors outwards from a center point.
+    Radial(RadialGradient),
+    /// A conic gradient interpolates colors around a center point.
+    Conic(ConicGradient),
 }
 
 impl Gradient {
@@ -29,6 +60,16 @@ impl Gradient {
                     *stop.color.a_mut() *= multiplier;
                 }
             }
+            Gradient::Radial(radial) => {
+                for stop in radial.stops.iter_mut().flatten() {
+                    *stop.color.a_mut() *= multiplier;
+                }
+            }
+            Gradient::Conic(conic) => {
+                for stop in conic.stops.iter_mut().flatten() {
+                    *stop.color.a_mut() *= multiplier;
+                }
+            }
         }
     }
 
@@ -43,6 +84,18 @@ impl From<LinearGradient> for Gradient {
     }
 }
 
+impl From<RadialGradient> for Gradient {
+    fn from(gradient: RadialGradient) -> Self {
+        Self::Radial(gradient)
+    }
+}
+
+impl From<ConicGradient> for Gradient {
+    fn from(gradient: ConicGradient) -> Self {
+        Self::Conic(gradient)
+    }
+}
+
 impl Default for Gradient {
     fn default() -> Self {
         Gradient::Linear(LinearGradient::new(Angle::default()))
@@ -64,6 +117,25 @@ pub struct ColorStop {
     pub color: PackedSrgb,
 }
 
+/// The color space in which the [`ColorStop`]s of a gradient are interpolated.
+#[repr(u32)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InterpolationSpace {
+    /// Interpolate in gamma-encoded sRGB space.
+    ///
+    /// This matches how most web browsers interpolate gradients, but can
+    /// produce dark and muddy midpoints between saturated colors.
+    Srgb = 0,
+    /// Interpolate in linear RGB space.
+    LinearRgb = 1,
+    /// Interpolate in the perceptually uniform Oklab space.
+    ///
+    /// This produces the smoothest transitions between colors.
+    #[default]
+    Oklab = 2,
+}
+
 /// A linear gradient.
 #[derive(Debug, Clone, Copy, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -72,6 +144,8 @@ pub struct LinearGradient {
     pub angle: Angle,
     /// [`ColorStop`]s along the linear gradient path.
     pub stops: [Option<ColorStop>; MAX_STOPS],
+    /// The color space in which the stops are interpolated.
+    pub interpolation: InterpolationSpace,
 }
 
 impl LinearGradient {
@@ -80,37 +154,92 @@ impl LinearGradient {
         Self {
             angle,
             stops: [None; MAX_STOPS],
+            interpolation: InterpolationSpace::Oklab,
         }
     }
 
+    /// Sets the color space in which the stops are interpolated.
+    pub fn interpolation(mut self, interpolation: InterpolationSpace) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+
     /// Adds a new [`ColorStop`], defined by an offset and a color, to the gradient.
     ///
     /// Any `offset` that is not within `0.0..=1.0` will be silently ignored.
     ///
-    /// Any stop added after the 8th will be silently ignored.
+    /// Any stops added beyond [`MAX_STOPS`] will be ignored, and a warning is
+    /// logged.
     pub fn add_stop(mut self, offset: f32, color: impl Into<PackedSrgb>) -> Self {
-        if offset.is_finite() && (0.0..=1.0).contains(&offset) {
-            let (Ok(index) | Err(index)) = self.stops.binary_search_by(|stop| match stop {
-                None => Ordering::Greater,
-                Some(stop) => stop.offset.partial_cmp(&offset).unwrap(),
-            });
-
-            if index < 8 {
-                self.stops[index] = Some(ColorStop {
-                    offset,
-                    color: color.into(),
-                });
-            }
-        } else {
-            log::warn!("Gradient color stop must be within 0.0..=1.0 range.");
-        };
+        add_stop(&mut self.stops, offset, color.into());
+        self
+    }
+
+    /// Adds multiple [`ColorStop`]s to the gradient.
+    ///
+    /// Any stops added beyond [`MAX_STOPS`] will be ignored, and a warning is
+    /// logged.
+    pub fn add_stops(mut self, stops: impl IntoIterator<Item = ColorStop>) -> Self {
+        for stop in stops {
+            self = self.add_stop(stop.offset, stop.color);
+        }
+
+        self
+    }
+}
+
+/// A radial gradient.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RadialGradient {
+    /// The center of the gradient in logical points, relative to the origin
+    /// of the bounds the gradient is packed with.
+    pub center: Point,
+    /// The distance from the center at which the first stop (offset `0.0`)
+    /// is reached.
+    pub inner_radius: f32,
+    /// The distance from the center at which the last stop (offset `1.0`)
+    /// is reached.
+    pub outer_radius: f32,
+    /// [`ColorStop`]s along the radius of the gradient.
+    pub stops: [Option<ColorStop>; MAX_STOPS],
+    /// The color space in which the stops are interpolated.
+    pub interpolation: InterpolationSpace,
+}
 
+impl RadialGradient {
+    /// Creates a new [`RadialGradient`] with the given center point and radii.
+    pub const fn new(center: Point, inner_radius: f32, outer_radius: f32) -> Self {
+        Self {
+            center,
+            inner_radius,
+            outer_radius,
+            stops: [None; MAX_STOPS],
+            interpolation: InterpolationSpace::Oklab,
+        }
+    }
+
+    /// Sets the color space in which the stops are interpolated.
+    pub fn interpolation(mut self, interpolation: InterpolationSpace) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+
+    /// Adds a new [`ColorStop`], defined by an offset and a color, to the gradient.
+    ///
+    /// Any `offset` that is not within `0.0..=1.0` will be silently ignored.
+    ///
+    /// Any stops added beyond [`MAX_STOPS`] will be ignored, and a warning is
+    /// logged.
+    pub fn add_stop(mut self, offset: f32, color: impl Into<PackedSrgb>) -> Self {
+        add_stop(&mut self.stops, offset, color.into());
         self
     }
 
     /// Adds multiple [`ColorStop`]s to the gradient.
     ///
-    /// Any stop added after the 8th will be silently ignored.
+    /// Any stops added beyond [`MAX_STOPS`] will be ignored, and a warning is
+    /// logged.
     pub fn add_stops(mut self, stops: impl IntoIterator<Item = ColorStop>) -> Self {
         for stop in stops {
             self = self.add_stop(stop.offset, stop.color);
@@ -120,16 +249,120 @@ impl LinearGradient {
     }
 }
 
+/// A conic (angular) gradient.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConicGradient {
+    /// The center of the gradient in logical points, relative to the origin
+    /// of the bounds the gradient is packed with.
+    pub center: Point,
+    /// The angle at which the gradient starts (offset `0.0`), clockwise rotation
+    /// from the positive x-axis.
+    ///
+    /// The gradient sweeps clockwise for one full turn, so offset `1.0` lands
+    /// back on this angle.
+    pub start_angle: Angle,
+    /// [`ColorStop`]s around the gradient.
+    pub stops: [Option<ColorStop>; MAX_STOPS],
+    /// The color space in which the stops are interpolated.
+    pub interpolation: InterpolationSpace,
+}
+
+impl ConicGradient {
+    /// Creates a new [`ConicGradient`] with the given center point and starting angle.
+    pub const fn new(center: Point, start_angle: Angle) -> Self {
+        Self {
+            center,
+            start_angle,
+            stops: [None; MAX_STOPS],
+            interpolation: InterpolationSpace::Oklab,
+        }
+    }
+
+    /// Sets the color space in which the stops are interpolated.
+    pub fn interpolation(mut self, interpolation: InterpolationSpace) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+
+    /// Adds a new [`ColorStop`], defined by an offset and a color, to the gradient.
+    ///
+    /// Any `offset` that is not within `0.0..=1.0` will be silently ignored.
+    ///
+    /// Any stops added beyond [`MAX_STOPS`] will be ignored, and a warning is
+    /// logged.
+    pub fn add_stop(mut self, offset: f32, color: impl Into<PackedSrgb>) -> Self {
+        add_stop(&mut self.stops, offset, color.into());
+        self
+    }
+
+    /// Adds multiple [`ColorStop`]s to the gradient.
+    ///
+    /// Any stops added beyond [`MAX_STOPS`] will be ignored, and a warning is
+    /// logged.
+    pub fn add_stops(mut self, stops: impl IntoIterator<Item = ColorStop>) -> Self {
+        for stop in stops {
+            self = self.add_stop(stop.offset, stop.color);
+        }
+
+        self
+    }
+}
+
+fn add_stop(stops: &mut [Option<ColorStop>; MAX_STOPS], offset: f32, color: PackedSrgb) {
+    if offset.is_finite() && (0.0..=1.0).contains(&offset) {
+        let (Ok(index) | Err(index)) = stops.binary_search_by(|stop| match stop {
+            None => Ordering::Greater,
+            Some(stop) => stop.offset.partial_cmp(&offset).unwrap(),
+        });
+
+        if index < MAX_STOPS {
+            stops[index] = Some(ColorStop { offset, color });
+        } else {
+            log::warn!("Gradient can have at most {MAX_STOPS} color stops, ignoring extra stop.");
+        }
+    } else {
+        log::warn!("Gradient color stop must be within 0.0..=1.0 range.");
+    };
+}
+
+/// The discriminant of a [`PackedGradient`] used by the shaders to determine
+/// how to interpret [`PackedGradient::direction`].
+#[repr(u32)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackedGradientKind {
+    #[default]
+    Linear = 0,
+    Radial = 1,
+    Conic = 2,
+}
+
 /// Packed [`Gradient`] data for use in shader code.
 #[repr(C)]
 #[derive(Default, Debug, Copy, Clone, PartialEq, bytemuck::Zeroable, bytemuck::Pod)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PackedGradient {
-    /// 4 colors, each channel = 16 bit float, 2 colors packed into 1 u32
-    pub colors: [[u32; 2]; MAX_STOPS],
-    /// 4 offsets, 4x 16 bit floats packed into 2 u32s
-    pub offsets: [u32; 2],
-    /// `[start.x, start.y, end.x, end.y]` in logical points
+    /// 16 colors, each packed into 1 u32 as 8 bit sRGBA with the red channel in
+    /// the lowest byte
+    ///
+    /// The colors are gamma encoded if gamma correction is enabled, which
+    /// keeps the precision of 8 bit sRGB colors.
+    pub colors: [u32; MAX_STOPS],
+    /// 16 offsets, each a 12 bit unorm, packed into 192 bits with the first
+    /// offset in the lowest bits
+    pub offsets: [u32; OFFSET_WORDS],
+    /// The lower 8 bits hold the [`PackedGradientKind`] of this gradient, the
+    /// next 8 bits hold the [`InterpolationSpace`], and the next 8 bits hold
+    /// the number of color stops. The bit after that is set if the colors are
+    /// gamma encoded.
+    pub kind: u32,
+    /// For linear gradients: `[start.x, start.y, end.x, end.y]` in logical points
+    ///
+    /// For radial gradients: `[center.x, center.y, inner_radius, outer_radius]`
+    /// in logical points
+    ///
+    /// For conic gradients: `[center.x, center.y, start_angle, 0.0]` with the
+    /// center in logical points and the angle in radians
     pub direction: [f32; 4],
 }
 
@@ -137,32 +370,7 @@ impl PackedGradient {
     pub fn new(gradient: &Gradient, bounds: Rect) -> Self {
         match gradient {
             Gradient::Linear(linear) => {
-                let mut colors = [[0u32; 2]; MAX_STOPS];
-                let mut offsets = [f16::from(0u8); MAX_STOPS];
-
-                for (index, stop) in linear.stops.iter().enumerate() {
-                    let packed_color = stop.map(|s| s.color).unwrap_or(PackedSrgb::default());
-
-                    colors[index] = [
-                        pack_f16s([
-                            f16::from_f32(packed_color.r()),
-                            f16::from_f32(packed_color.g()),
-                        ]),
-                        pack_f16s([
-                            f16::from_f32(packed_color.b()),
-                            f16::from_f32(packed_color.a()),
-                        ]),
-                    ];
-
-                    offsets[index] = f16::from_f32(stop.map(|s| s.offset).unwrap_or(2.0));
-                }
-
-                let offsets = [
-                    pack_f16s([offsets[0], offsets[1]]),
-                    pack_f16s([offsets[2], offsets[3]]),
-                    //pack_f16s([offsets[4], offsets[5]]),
-                    //pack_f16s([offsets[6], offsets[7]]),
-                ];
+                let (colors, offsets, num_stops) = pack_stops(&linear.stops);
 
                 let (start, end) = to_distance(linear.angle, &bounds);
 
@@ -171,11 +379,181 @@ impl PackedGradient {
                 PackedGradient {
                     colors,
                     offsets,
+                    kind: pack_kind(PackedGradientKind::Linear, linear.interpolation, num_stops),
+                    direction,
+                }
+            }
+            Gradient::Radial(radial) => {
+                let (colors, offsets, num_stops) = pack_stops(&radial.stops);
+
+                let center = bounds.origin + radial.center.to_vector();
+                let inner_radius = radial.inner_radius.max(0.0);
+                let outer_radius = radial.outer_radius.max(inner_radius);
+
+                let direction = [center.x, center.y, inner_radius, outer_radius];
+
+                PackedGradient {
+                    colors,
+                    offsets,
+                    kind: pack_kind(PackedGradientKind::Radial, radial.interpolation, num_stops),
                     direction,
                 }
             }
+            Gradient::Conic(conic) => {
+                let (colors, offsets, num_stops) = pack_stops(&conic.stops);
+
+                let center = bounds.origin + conic.center.to_vector();
+
+                let direction = [
+                    center.x,
+                    center.y,
+                    conic.start_angle.positive().radians,
+                    0.0,
+                ];
+
+                PackedGradient {
+                    colors,
+                    offsets,
+                    kind: pack_kind(PackedGradientKind::Conic, conic.interpolation, num_stops),
+                    direction,
+                }
+            }
+        }
+    }
+
+    /// The [`PackedGradientKind`] of this gradient.
+    ///
+    /// Unknown discriminants are treated as [`PackedGradientKind::Linear`].
+    pub fn kind(&self) -> PackedGradientKind {
+        match self.kind & 0xff {
+            1 => PackedGradientKind::Radial,
+            2 => PackedGradientKind::Conic,
+            _ => PackedGradientKind::Linear,
+        }
+    }
+
+    /// The number of color stops in this gradient.
+    pub fn num_stops(&self) -> usize {
+        (((self.kind >> 16) & 0xff) as usize).min(MAX_STOPS)
+    }
+
+    /// Multiply the alpha of every color stop by the given multiplier.
+    pub fn multiply_alpha(&mut self, multiplier: f32) {
+        for color in self.colors.iter_mut() {
+            // The alpha channel is stored in the highest byte (see
+            // `pack_stops`).
+            let alpha = f32::from((*color >> 24) as u8) * (1.0 / 255.0);
+            let alpha = ((alpha * multiplier).clamp(0.0, 1.0) * 255.0).round() as u32;
+
+            *color = (*color & 0x00ffffff) | (alpha << 24);
         }
     }
+
+    /// Unpack the color stops of this gradient. Unused stops are `None`.
+    ///
+    /// The colors are stored as 8 bit sRGBA and the offsets as 12 bit unorms,
+    /// so they may differ slightly from the original stops.
+    pub fn stops(&self) -> [Option<ColorStop>; MAX_STOPS] {
+        let num_stops = self.num_stops();
+
+        std::array::from_fn(|index| {
+            if index >= num_stops {
+                return None;
+            }
+
+            let offset = unpack_offset(&self.offsets, index);
+            let [r, g, b, a] = self.colors[index].to_le_bytes();
+
+            Some(ColorStop {
+                offset: offset as f32 / OFFSET_MAX,
+                color: PackedSrgb::from_rgba8(r, g, b, a),
+            })
+        })
+    }
+
+    /// The [`InterpolationSpace`] of this gradient.
+    ///
+    /// Unknown values are treated as [`InterpolationSpace::Oklab`].
+    pub fn interpolation(&self) -> InterpolationSpace {
+        match (self.kind >> 8) & 0xff {
+            0 => InterpolationSpace::Srgb,
+            1 => InterpolationSpace::LinearRgb,
+            _ => InterpolationSpace::Oklab,
+        }
+    }
+}
+
+/// The data of a [`PackedGradient`] as it is passed to the quad and mesh
+/// shaders, see [`PackedGradient::to_shader_gradient`].
+#[repr(C)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, bytemuck::Zeroable, bytemuck::Pod)]
+pub struct ShaderGradient {
+    /// The packed colors of the first [`MAX_INLINE_STOPS`] stops.
+    ///
+    /// If the gradient is sampled from a ramp texture, then the first
+    /// element holds the row of the ramp instead.
+    pub colors: [u32; MAX_INLINE_STOPS],
+    /// The packed offsets of the first [`MAX_INLINE_STOPS`] stops, followed
+    /// by the packed kind of the gradient. Bit 25 of the kind is set if the
+    /// gradient is sampled from a ramp texture.
+    pub offsets_and_kind: [u32; 4],
+    /// The same as [`PackedGradient::direction`].
+    pub direction: [f32; 4],
+}
+
+fn pack_kind(kind: PackedGradientKind, interpolation: InterpolationSpace, num_stops: u32) -> u32 {
+    let mut packed = (kind as u32) | ((interpolation as u32) << 8) | (num_stops << 16);
+    if GAMMA_CORRECTION {
+        packed |= GAMMA_ENCODED_FLAG;
+    }
+    packed
+}
+
+/// Packs the colors and offsets of the given stops, and returns the number of
+/// stops.
+fn pack_stops(
+    stops: &[Option<ColorStop>; MAX_STOPS],
+) -> ([u32; MAX_STOPS], [u32; OFFSET_WORDS], u32) {
+    let mut colors = [0u32; MAX_STOPS];
+    let mut offsets = [0u32; OFFSET_WORDS];
+    let mut num_stops = 0;
+
+    for (index, stop) in stops.iter().flatten().enumerate() {
+        let color = stop.color.to_rgba8();
+        colors[index] = u32::from_le_bytes([color.r, color.g, color.b, color.a]);
+
+        let offset = (stop.offset.clamp(0.0, 1.0) * OFFSET_MAX).round() as u32;
+        pack_offset(&mut offsets, index, offset);
+
+        num_stops += 1;
+    }
+
+    (colors, offsets, num_stops)
+}
+
+/// Writes the offset of the stop at `index` into the bitstream of offsets,
+/// where an offset may straddle two words.
+fn pack_offset(offsets: &mut [u32; OFFSET_WORDS], index: usize, offset: u32) {
+    let bit = index * OFFSET_BITS as usize;
+    let (word, shift) = (bit / 32, bit as u32 % 32);
+
+    offsets[word] |= offset << shift;
+    if shift + OFFSET_BITS > 32 {
+        offsets[word + 1] |= offset >> (32 - shift);
+    }
+}
+
+/// Reads the offset of the stop at `index` from the bitstream of offsets.
+fn unpack_offset(offsets: &[u32; OFFSET_WORDS], index: usize) -> u32 {
+    let bit = index * OFFSET_BITS as usize;
+    let (word, shift) = (bit / 32, bit as u32 % 32);
+
+    let mut offset = offsets[word] >> shift;
+    if shift + OFFSET_BITS > 32 {
+        offset |= offsets[word + 1] << (32 - shift);
+    }
+
+    offset & ((1 << OFFSET_BITS) - 1)
 }
 
 /// Calculates the line in which the angle intercepts the `bounds`.
@@ -202,10 +580,97 @@ fn to_distance(angle: Angle, bounds: &Rect) -> (Point, Point) {
     (start, end)
 }
 
-/// Packs two f16s into one u32.
-fn pack_f16s(f: [f16; 2]) -> u32 {
-    let one = (f[0].to_bits() as u32) << 16;
-    let two = f[1].to_bits() as u32;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::Size;
 
-    one | two
+    #[test]
+    fn ten_stops_are_packed_and_sampled_at_their_midpoints() {
+        let color = |i: usize| PackedSrgb::from_rgba8(i as u8 * 25, 255 - i as u8 * 25, 0, 255);
+
+        let gradient = LinearGradient::new(Angle::default())
+            .add_stops((0..10).map(|i| ColorStop {
+                offset: i as f32 / 9.0,
+                color: color(i),
+            }))
+            .interpolation(InterpolationSpace::LinearRgb);
+
+        let packed =
+            Gradient::Linear(gradient).packed(Rect::new(Point::zero(), Size::new(10.0, 10.0)));
+
+        assert_eq!(packed.num_stops(), 10);
+
+        for (i, stop) in packed.stops().iter().enumerate() {
+            let Some(stop) = stop else {
+                assert!(i >= 10);
+                continue;
+            };
+
+            assert!((stop.offset - i as f32 / 9.0).abs() <= 1.0 / OFFSET_MAX);
+            assert_eq!(stop.color.to_rgba8(), color(i).to_rgba8());
+        }
+
+        // The centers of the texels of a ramp which is 9 texels wide lie
+        // halfway between two stops, where the colors are mixed evenly.
+        let texels = packed.ramp_texels(9);
+
+        for i in 0..9 {
+            let [r0, g0, b0, a0] = color(i).raw();
+            let [r1, g1, b1, a1] = color(i + 1).raw();
+
+            let expected = PackedSrgb::from_srgba(
+                (r0 + r1) * 0.5,
+                (g0 + g1) * 0.5,
+                (b0 + b1) * 0.5,
+                (a0 + a1) * 0.5,
+            )
+            .to_rgba8();
+
+            let texel = &texels[i * 4..i * 4 + 4];
+            for (channel, expected) in texel
+                .iter()
+                .zip([expected.r, expected.g, expected.b, expected.a])
+            {
+                assert!(channel.abs_diff(expected) <= 1);
+            }
+        }
+    }
+
+    #[test]
+    fn offsets_straddling_words_round_trip() {
+        let mut offsets = [0; OFFSET_WORDS];
+
+        for index in 0..MAX_STOPS {
+            pack_offset(&mut offsets, index, (index as u32 * 273) & 0xfff);
+        }
+
+        for index in 0..MAX_STOPS {
+            assert_eq!(unpack_offset(&offsets, index), (index as u32 * 273) & 0xfff);
+        }
+    }
+
+    #[test]
+    fn packed_stops_round_trip() {
+        let gradient = RadialGradient::new(Point::new(5.0, 5.0), 0.0, 5.0)
+            .add_stop(0.0, PackedSrgb::from_rgba8(255, 0, 0, 255))
+            .add_stop(0.25, PackedSrgb::from_rgba8(0, 255, 0, 128))
+            .add_stop(1.0, PackedSrgb::from_rgba8(0, 0, 255, 0))
+            .interpolation(InterpolationSpace::LinearRgb);
+
+        let packed =
+            Gradient::Radial(gradient).packed(Rect::new(Point::zero(), Size::new(10.0, 10.0)));
+
+        assert_eq!(packed.kind(), PackedGradientKind::Radial);
+        assert_eq!(packed.interpolation(), InterpolationSpace::LinearRgb);
+        assert_eq!(packed.num_stops(), 3);
+
+        let stops = packed.stops();
+        assert!(stops[3..].iter().all(Option::is_none));
+
+        for (stop, expected) in stops.iter().flatten().zip(gradient.stops.iter().flatten()) {
+            assert!((stop.offset - expected.offset).abs() <= 1.0 / OFFSET_MAX);
+            assert_eq!(stop.color.to_rgba8(), expected.color.to_rgba8());
+        }
+    }
 }
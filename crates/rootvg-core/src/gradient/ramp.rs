@@ -0,0 +1,630 @@
+//! Baking gradients into ramp textures.
+
+use std::collections::HashMap;
+
+use super::{
+    InterpolationSpace, PackedGradient, ShaderGradient, MAX_INLINE_STOPS, MAX_STOPS, OFFSET_WORDS,
+    RAMP_FLAG,
+};
+use crate::color::{self, PackedSrgb, SRGBA8_TEXTURE_FORMAT};
+
+/// The width in texels of each ramp in a [`GradientRampCache`].
+pub const RAMP_WIDTH: u32 = 256;
+
+/// The number of rows the texture of a [`GradientRampCache`] starts with.
+const INITIAL_ROWS: u32 = 8;
+
+// The same (column major) matrices as the ones used by the gradient shaders,
+// so that a baked ramp matches a gradient computed on the GPU.
+const TO_LMS: [[f32; 3]; 3] = [
+    [0.4121656120, 0.2118591070, 0.0883097947],
+    [0.5362752080, 0.6807189584, 0.2818474174],
+    [0.0514575653, 0.1074065790, 0.6302613616],
+];
+
+const TO_RGB: [[f32; 3]; 3] = [
+    [4.0767245293, -3.3072168827, 0.2307590544],
+    [-1.2681437731, 2.6093323231, -0.3411344290],
+    [-0.0041119885, -0.7034763098, 1.7068625689],
+];
+
+impl PackedGradient {
+    /// Bake the color stops of this gradient into a new texture which is
+    /// `width` texels wide and 1 texel high.
+    ///
+    /// The texel at `x` holds the color of the gradient at the offset
+    /// `(x + 0.5) / width`. The stops are interpolated the same way the
+    /// quad and mesh shaders interpolate them.
+    ///
+    /// The texture uses [`SRGBA8_TEXTURE_FORMAT`], so sampling it returns
+    /// colors in the same space as [`PackedSrgb`]. The direction and kind of
+    /// the gradient are not part of the ramp.
+    ///
+    /// The quad and mesh pipelines don't use this texture directly. They bake
+    /// gradients into the rows of a [`GradientRampCache`] instead, so that
+    /// gradients which are drawn every frame share a single texture.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is `0`.
+    pub fn to_ramp_texture(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        width: u32,
+    ) -> wgpu::Texture {
+        assert!(width > 0);
+
+        let texels = self.ramp_texels(width);
+
+        let texture_size = wgpu::Extent3d {
+            width,
+            height: 1,
+            depth_or_array_layers: 1,
+        };
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            size: texture_size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: SRGBA8_TEXTURE_FORMAT,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            label: Some("rootvg-core gradient ramp texture"),
+            view_formats: &[],
+        });
+
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &texels,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * width),
+                rows_per_image: Some(1),
+            },
+            texture_size,
+        );
+
+        texture
+    }
+
+    /// Evaluate this gradient at the center of each of the `width` texels
+    /// of a ramp, and return the texels as 8 bit RGBA.
+    pub(super) fn ramp_texels(&self, width: u32) -> Vec<u8> {
+        let stops = self.stops();
+        let stops: Vec<_> = stops.iter().flatten().collect();
+        let interpolation = self.interpolation();
+
+        (0..width)
+            .flat_map(|x| {
+                let offset = (x as f32 + 0.5) / width as f32;
+
+                let (Some(first), Some(last)) = (stops.first(), stops.last()) else {
+                    return [0; 4];
+                };
+
+                let color = if offset <= first.offset {
+                    first.color.raw()
+                } else if offset >= last.offset {
+                    last.color.raw()
+                } else {
+                    // The offset is strictly between the first and last
+                    // stops, so there always is a matching segment.
+                    let (from, to) = stops
+                        .windows(2)
+                        .map(|w| (w[0], w[1]))
+                        .find(|(_, to)| offset <= to.offset)
+                        .unwrap();
+
+                    let factor = smoothstep(from.offset, to.offset, offset);
+
+                    interpolate_color(from.color.raw(), to.color.raw(), factor, interpolation)
+                };
+
+                let [r, g, b, a] = color.map(|c| c.clamp(0.0, 1.0));
+
+                let texel = PackedSrgb::from_srgba(r, g, b, a).to_rgba8();
+
+                [texel.r, texel.g, texel.b, texel.a]
+            })
+            .collect()
+    }
+
+    /// Convert this gradient into the data which is passed to the quad and
+    /// mesh shaders.
+    ///
+    /// Gradients with more than [`MAX_INLINE_STOPS`] color stops are baked
+    /// into a row of the given [`GradientRampCache`]. Other gradients are
+    /// passed inline, and the cache is not touched.
+    pub fn to_shader_gradient(&self, ramps: &mut GradientRampCache) -> ShaderGradient {
+        if self.num_stops() <= MAX_INLINE_STOPS {
+            let mut colors = [0; MAX_INLINE_STOPS];
+            colors.copy_from_slice(&self.colors[..MAX_INLINE_STOPS]);
+
+            // The offsets of the first `MAX_INLINE_STOPS` stops are stored in
+            // the first three words.
+            return ShaderGradient {
+                colors,
+                offsets_and_kind: [self.offsets[0], self.offsets[1], self.offsets[2], self.kind],
+                direction: self.direction,
+            };
+        }
+
+        let mut colors = [0; MAX_INLINE_STOPS];
+        colors[0] = ramps.get(self).row();
+
+        ShaderGradient {
+            colors,
+            offsets_and_kind: [0, 0, 0, self.kind | RAMP_FLAG],
+            direction: self.direction,
+        }
+    }
+}
+
+/// A handle to a gradient baked into one row of the texture of a
+/// [`GradientRampCache`].
+///
+/// Gradients with identical color stops share the same handle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GradientRamp {
+    row: u32,
+}
+
+impl GradientRamp {
+    /// The row of the ramp in the texture of the cache.
+    pub fn row(&self) -> u32 {
+        self.row
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct RampKey {
+    colors: [u32; MAX_STOPS],
+    offsets: [u32; OFFSET_WORDS],
+    /// The packed kind without the [`PackedGradientKind`](super::PackedGradientKind),
+    /// since linear, radial, and conic gradients with the same stops share
+    /// the same ramp.
+    kind: u32,
+}
+
+impl RampKey {
+    fn new(gradient: &PackedGradient) -> Self {
+        Self {
+            colors: gradient.colors,
+            offsets: gradient.offsets,
+            kind: gradient.kind & !0xff,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct CachedRamp {
+    ramp: GradientRamp,
+    gradient: PackedGradient,
+    last_used_frame: u64,
+}
+
+#[derive(Debug)]
+struct RampTexture {
+    texture: wgpu::Texture,
+    bind_group: wgpu::BindGroup,
+}
+
+impl RampTexture {
+    fn new(device: &wgpu::Device, layout: &wgpu::BindGroupLayout, rows: u32) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            size: wgpu::Extent3d {
+                width: RAMP_WIDTH,
+                height: rows,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: SRGBA8_TEXTURE_FORMAT,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            label: Some("rootvg-core gradient ramp cache texture"),
+            view_formats: &[],
+        });
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("rootvg-core gradient ramp bind group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+
+        Self {
+            texture,
+            bind_group,
+        }
+    }
+}
+
+/// A cache of gradients baked into the rows of a single ramp texture, keyed
+/// by their color stops and interpolation space.
+///
+/// Each row is [`RAMP_WIDTH`] texels wide and holds one gradient, see
+/// [`PackedGradient::to_ramp_texture`]. Gradients with identical stops share
+/// a single row, regardless of their direction. Ramps which have not been
+/// requested since the last call to [`GradientRampCache::next_frame`] are
+/// evicted, and their rows are reused.
+///
+/// Requesting a ramp only allocates its row. The texels are written to the
+/// texture in [`GradientRampCache::upload`].
+#[derive(Debug, Default)]
+pub struct GradientRampCache {
+    ramps: HashMap<RampKey, CachedRamp>,
+    free_rows: Vec<u32>,
+    num_rows: u32,
+    pending: Vec<(u32, PackedGradient)>,
+    texture: Option<RampTexture>,
+    frame: u64,
+}
+
+impl GradientRampCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the ramp of the given gradient, allocating a new row for it if it
+    /// isn't in the cache yet.
+    pub fn get(&mut self, gradient: &PackedGradient) -> GradientRamp {
+        let frame = self.frame;
+
+        let cached = self.ramps.entry(RampKey::new(gradient)).or_insert_with(|| {
+            let row = self.free_rows.pop().unwrap_or_else(|| {
+                self.num_rows += 1;
+                self.num_rows - 1
+            });
+
+            self.pending.push((row, *gradient));
+
+            CachedRamp {
+                ramp: GradientRamp { row },
+                gradient: *gradient,
+                last_used_frame: frame,
+            }
+        });
+
+        cached.last_used_frame = frame;
+        cached.ramp
+    }
+
+    /// The layout of the bind group returned by
+    /// [`GradientRampCache::bind_group`], with the ramp texture at binding
+    /// `0` and a linear sampler at binding `1`.
+    pub fn layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("rootvg-core gradient ramp layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        })
+    }
+
+    /// Write the ramps which were added since the last upload to the
+    /// texture, creating or growing the texture first if needed.
+    ///
+    /// `layout` must be the layout returned by [`GradientRampCache::layout`].
+    pub fn upload(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+    ) {
+        let capacity = self
+            .texture
+            .as_ref()
+            .map(|t| t.texture.height())
+            .unwrap_or(0);
+
+        if self.texture.is_none() || self.num_rows > capacity {
+            let rows = self
+                .num_rows
+                .max(INITIAL_ROWS)
+                .next_power_of_two()
+                .min(device.limits().max_texture_dimension_2d);
+
+            self.texture = Some(RampTexture::new(device, layout, rows));
+
+            // The new texture is empty, so every ramp has to be written again.
+            self.pending.clear();
+            self.pending.extend(
+                self.ramps
+                    .values()
+                    .map(|cached| (cached.ramp.row, cached.gradient)),
+            );
+        }
+
+        let texture = &self.texture.as_ref().unwrap().texture;
+
+        for (row, gradient) in self.pending.drain(..) {
+            if row >= texture.height() {
+                log::warn!("Gradient ramp cache is full, ignoring gradient ramp.");
+                continue;
+            }
+
+            queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d { x: 0, y: row, z: 0 },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                &gradient.ramp_texels(RAMP_WIDTH),
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(4 * RAMP_WIDTH),
+                    rows_per_image: Some(1),
+                },
+                wgpu::Extent3d {
+                    width: RAMP_WIDTH,
+                    height: 1,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+    }
+
+    /// The ramp texture, or `None` if nothing has been uploaded yet.
+    pub fn texture(&self) -> Option<&wgpu::Texture> {
+        self.texture.as_ref().map(|t| &t.texture)
+    }
+
+    /// The bind group of the ramp texture, or `None` if nothing has been
+    /// uploaded yet.
+    ///
+    /// The bind group changes whenever the texture has to grow, so fetch it
+    /// again after every call to [`GradientRampCache::upload`].
+    pub fn bind_group(&self) -> Option<&wgpu::BindGroup> {
+        self.texture.as_ref().map(|t| &t.bind_group)
+    }
+
+    /// The number of ramps in the cache.
+    pub fn len(&self) -> usize {
+        self.ramps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ramps.is_empty()
+    }
+
+    /// Evict every ramp in the cache.
+    pub fn clear(&mut self) {
+        self.ramps.clear();
+        self.free_rows.clear();
+        self.pending.clear();
+        self.num_rows = 0;
+    }
+
+    /// Evict the ramps which were not requested in the current frame, and
+    /// advance to the next frame. Call this once per frame before requesting
+    /// the ramps of that frame.
+    pub fn next_frame(&mut self) {
+        let frame = self.frame;
+        let free_rows = &mut self.free_rows;
+
+        self.ramps.retain(|_, cached| {
+            let keep = cached.last_used_frame >= frame;
+            if !keep {
+                free_rows.push(cached.ramp.row);
+            }
+            keep
+        });
+
+        self.frame += 1;
+    }
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// The CPU equivalent of `interpolate_color` in the gradient shaders.
+fn interpolate_color(
+    from: [f32; 4],
+    to: [f32; 4],
+    factor: f32,
+    space: InterpolationSpace,
+) -> [f32; 4] {
+    let mix = |a: f32, b: f32| a + (b - a) * factor;
+    let alpha = mix(from[3], to[3]);
+
+    match space {
+        InterpolationSpace::LinearRgb => std::array::from_fn(|i| mix(from[i], to[i])),
+        InterpolationSpace::Srgb => {
+            let [r, g, b] = std::array::from_fn(|i| {
+                color::srgb_to_linear(mix(
+                    color::linear_to_srgb(from[i]),
+                    color::linear_to_srgb(to[i]),
+                ))
+            });
+
+            [r, g, b, alpha]
+        }
+        InterpolationSpace::Oklab => {
+            let lms_a = vec_mul(from, &TO_LMS).map(f32::cbrt);
+            let lms_b = vec_mul(to, &TO_LMS).map(f32::cbrt);
+
+            let mixed: [f32; 3] = std::array::from_fn(|i| mix(lms_a[i], lms_b[i]).powi(3));
+            let [r, g, b] = mat_mul(&TO_RGB, mixed);
+
+            [r, g, b, alpha]
+        }
+    }
+}
+
+/// `color * matrix` with a column major matrix, ignoring the alpha channel.
+fn vec_mul(color: [f32; 4], matrix: &[[f32; 3]; 3]) -> [f32; 3] {
+    std::array::from_fn(|col| (0..3).map(|row| color[row] * matrix[col][row]).sum())
+}
+
+/// `matrix * v` with a column major matrix.
+fn mat_mul(matrix: &[[f32; 3]; 3], v: [f32; 3]) -> [f32; 3] {
+    std::array::from_fn(|row| (0..3).map(|col| matrix[col][row] * v[col]).sum())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gradient::{ColorStop, Gradient, LinearGradient, RadialGradient};
+    use crate::math::{Angle, Point, Rect, Size};
+
+    fn bounds() -> Rect {
+        Rect::new(Point::zero(), Size::new(10.0, 10.0))
+    }
+
+    fn gradient(seed: u8) -> LinearGradient {
+        LinearGradient::new(Angle::default())
+            .add_stop(0.0, PackedSrgb::from_rgba8(seed, 0, 0, 255))
+            .add_stop(0.5, PackedSrgb::from_rgba8(0, seed, 0, 255))
+            .add_stop(1.0, PackedSrgb::from_rgba8(0, 0, seed, 255))
+    }
+
+    fn many_stops() -> LinearGradient {
+        LinearGradient::new(Angle::default()).add_stops((0..=MAX_INLINE_STOPS).map(|i| ColorStop {
+            offset: i as f32 / MAX_INLINE_STOPS as f32,
+            color: PackedSrgb::from_rgba8(0, i as u8 * 20, 0, 255),
+        }))
+    }
+
+    #[test]
+    fn identical_gradients_share_a_ramp() {
+        let mut cache = GradientRampCache::new();
+
+        let a = Gradient::Linear(gradient(0)).packed(bounds());
+        let b = Gradient::Linear(LinearGradient {
+            angle: Angle { radians: 1.0 },
+            ..gradient(0)
+        })
+        .packed(bounds());
+        let c = Gradient::Linear(gradient(1)).packed(bounds());
+
+        let ramp_a = cache.get(&a);
+        assert_eq!(cache.get(&b), ramp_a);
+        assert_ne!(cache.get(&c), ramp_a);
+        assert_eq!(cache.get(&a), ramp_a);
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn unused_ramps_are_evicted_and_their_rows_reused() {
+        let mut cache = GradientRampCache::new();
+
+        let a = Gradient::Linear(gradient(0)).packed(bounds());
+        let b = Gradient::Linear(gradient(1)).packed(bounds());
+        let c = Gradient::Linear(gradient(2)).packed(bounds());
+
+        let ramp_a = cache.get(&a);
+        let ramp_b = cache.get(&b);
+        cache.next_frame();
+
+        // Only `a` is used in this frame.
+        assert_eq!(cache.get(&a), ramp_a);
+        cache.next_frame();
+
+        assert_eq!(cache.len(), 1);
+
+        // `c` takes over the row of the evicted `b`.
+        assert_eq!(cache.get(&c).row(), ramp_b.row());
+        assert_eq!(cache.get(&a), ramp_a);
+    }
+
+    #[test]
+    fn inline_gradients_dont_use_the_cache() {
+        let mut cache = GradientRampCache::new();
+
+        let gradient = RadialGradient::new(Point::new(5.0, 5.0), 0.0, 5.0)
+            .add_stop(0.0, PackedSrgb::from_rgba8(255, 0, 0, 255))
+            .add_stop(1.0, PackedSrgb::from_rgba8(0, 0, 255, 255));
+        let packed = Gradient::Radial(gradient).packed(bounds());
+
+        let shader = packed.to_shader_gradient(&mut cache);
+
+        assert!(cache.is_empty());
+        assert_eq!(shader.offsets_and_kind[3], packed.kind);
+        assert_eq!(shader.colors[..2], packed.colors[..2]);
+        assert_eq!(shader.offsets_and_kind[..3], packed.offsets[..3]);
+    }
+
+    #[test]
+    fn gradients_with_many_stops_use_a_ramp() {
+        let mut cache = GradientRampCache::new();
+
+        // Fill the first row.
+        cache.get(&Gradient::Linear(gradient(1)).packed(bounds()));
+
+        let packed = Gradient::Linear(many_stops()).packed(bounds());
+        let shader = packed.to_shader_gradient(&mut cache);
+
+        assert_eq!(shader.offsets_and_kind[3], packed.kind | RAMP_FLAG);
+        assert_eq!(shader.colors[0], cache.get(&packed).row());
+        assert_eq!(shader.colors[0], 1);
+        assert_eq!(shader.direction, packed.direction);
+    }
+
+    #[test]
+    fn ramp_texels_interpolate_the_stops() {
+        let gradient = LinearGradient::new(Angle::default())
+            .add_stop(0.0, PackedSrgb::from_rgba8(0, 0, 0, 255))
+            .add_stop(0.5, PackedSrgb::from_rgba8(255, 0, 0, 255))
+            .add_stop(1.0, PackedSrgb::from_rgba8(255, 255, 255, 0))
+            .interpolation(InterpolationSpace::LinearRgb);
+        let packed = Gradient::Linear(gradient).packed(bounds());
+
+        let texels = packed.ramp_texels(4);
+        let texel = |x: usize| &texels[x * 4..x * 4 + 4];
+
+        assert_eq!(texels.len(), 16);
+        // The texels on either side of the middle stop are on the segments
+        // from and to red.
+        assert_eq!(texel(1)[1..3], [0, 0]);
+        assert_eq!(texel(2)[0], 255);
+        assert_eq!(texel(1)[3], 255);
+        assert!(texel(0)[0] < texel(1)[0]);
+        assert!(texel(3)[3] < texel(2)[3]);
+        assert!(texel(3)[1] > texel(2)[1]);
+    }
+}
@@ -1,7 +1,11 @@
 pub mod buffer;
+pub mod clip;
 pub mod color;
 pub mod math;
 pub mod pipeline;
 
+#[cfg(feature = "serde")]
+pub mod serde_handle;
+
 #[cfg(feature = "gradient")]
 pub mod gradient;
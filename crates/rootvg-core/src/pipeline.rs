@@ -1,8 +1,9 @@
 use std::any::Any;
 use std::error::Error;
+use std::ops::Range;
 use std::rc::Rc;
 
-use crate::math::{PhysicalSizeI32, ScaleFactor, Vector};
+use crate::math::{PhysicalPointI32, PhysicalSizeI32, ScaleFactor, Vector};
 
 pub type PrimitiveID = u32;
 
@@ -69,6 +70,33 @@ pub trait CustomPipeline: Any {
         primitives: &[CustomPipelinePrimitive],
     ) -> Result<(), Box<dyn Error>>;
 
+    /// Prepare to render the given list of primitives into a sub-region of
+    /// the render target whose top-left corner is at `origin` (in physical
+    /// pixels), where `screen_size` is the size of the whole render target.
+    ///
+    /// The origin is `(0, 0)` and the render target is the same size as the
+    /// canvas unless the canvas is rendered with
+    /// `Canvas::render_to_target_in_rect`. Primitives should be offset by
+    /// `origin` when converting them to clip space, for example with
+    /// [`DefaultConstantUniforms::prepare_buffer_with_origin`].
+    ///
+    /// This is the method the canvas calls. By default it calls
+    /// [`CustomPipeline::prepare`] and ignores the origin, so pipelines which
+    /// don't override it are drawn relative to the top-left corner of the
+    /// render target.
+    fn prepare_with_origin(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        screen_size: PhysicalSizeI32,
+        scale_factor: ScaleFactor,
+        origin: PhysicalPointI32,
+        primitives: &[CustomPipelinePrimitive],
+    ) -> Result<(), Box<dyn Error>> {
+        let _ = origin;
+        self.prepare(device, queue, screen_size, scale_factor, primitives)
+    }
+
     /// Render a primitive
     ///
     /// The `primitive_index` is the index into the slice of primitives that
@@ -78,6 +106,54 @@ pub trait CustomPipeline: Any {
         primitive_index: usize,
         render_pass: &mut wgpu::RenderPass<'pass>,
     ) -> Result<(), Box<dyn Error>>;
+
+    /// Render a contiguous range of primitives
+    ///
+    /// Consecutive primitives which use this pipeline and have the same z
+    /// index and clipping are grouped together into a single batch, and
+    /// rendered with a single call to this method. The `primitives` range
+    /// is a range of indices into the slice of primitives that was previously
+    /// passed into `CustomPipeline::prepare`.
+    ///
+    /// Override this to draw the whole range with a single draw call. By
+    /// default this calls [`CustomPipeline::render_primitive`] for each
+    /// primitive in the range.
+    fn render_primitives<'pass>(
+        &'pass self,
+        primitives: Range<usize>,
+        render_pass: &mut wgpu::RenderPass<'pass>,
+    ) -> Result<(), Box<dyn Error>> {
+        for primitive_index in primitives {
+            self.render_primitive(primitive_index, render_pass)?;
+        }
+
+        Ok(())
+    }
+
+    /// Whether or not this pipeline depth-tests its primitives.
+    ///
+    /// If this returns `true`, then the primitives of this pipeline are
+    /// rendered in a render pass with a depth attachment of format
+    /// [`DEPTH_FORMAT`], so the pipeline must be created with a depth
+    /// stencil state of that format, such as the one returned by
+    /// [`depth_stencil_state`]. The depth attachment has the same sample
+    /// count as the color attachment. It is cleared to `1.0` at the start of
+    /// each frame and shared by all batches in the frame which use the depth
+    /// buffer, including the built-in ones when the canvas has its depth
+    /// buffer enabled.
+    ///
+    /// If this returns `false`, then the pipeline must be created without a
+    /// depth stencil state.
+    ///
+    /// The canvas starts a new render pass whenever consecutive batches
+    /// differ in whether they use the depth buffer, so avoid interleaving
+    /// them with other primitives more than needed. This must always return
+    /// the same value for the same pipeline.
+    ///
+    /// By default this returns `false`.
+    fn wants_depth(&self) -> bool {
+        false
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -94,6 +170,18 @@ impl PartialEq for CustomPipelinePrimitive {
 
 /// A default shader uniform struct containing the scale factor and a scaling vector
 /// used to convert from screen space to clip space.
+///
+/// In WGSL this struct is declared as:
+///
+/// ```wgsl
+/// struct Globals {
+///     screen_size_recip: vec2<f32>,
+///     scale_factor: f32,
+///     origin: vec2<f32>,
+/// }
+/// ```
+///
+/// Shaders which don't need the `origin` may leave it out of the declaration.
 #[repr(C)]
 #[derive(Debug, Clone, Copy, bytemuck::Zeroable, bytemuck::Pod)]
 pub struct DefaultConstantUniforms {
@@ -101,10 +189,28 @@ pub struct DefaultConstantUniforms {
     pub screen_size_recip: [f32; 2],
     pub scale_factor: f32,
     pub _padding: f32,
+    /// The position of the top-left corner of the viewport in the render
+    /// target, in physical pixels.
+    ///
+    /// Add this to positions before converting them to clip space. Fragment
+    /// positions (`@builtin(position)`) are relative to the render target,
+    /// so subtract this from them to get a position relative to the
+    /// viewport. This is `(0, 0)` unless the canvas is rendered into a
+    /// sub-region of the target.
+    pub origin: [f32; 2],
+    pub _padding2: [f32; 2],
 }
 
 impl DefaultConstantUniforms {
     pub fn new(screen_size: PhysicalSizeI32, scale_factor: ScaleFactor) -> Self {
+        Self::new_with_origin(screen_size, scale_factor, PhysicalPointI32::zero())
+    }
+
+    pub fn new_with_origin(
+        screen_size: PhysicalSizeI32,
+        scale_factor: ScaleFactor,
+        origin: PhysicalPointI32,
+    ) -> Self {
         Self {
             screen_size_recip: [
                 2.0 * (screen_size.width as f32).recip(),
@@ -112,6 +218,8 @@ impl DefaultConstantUniforms {
             ],
             scale_factor: scale_factor.0,
             _padding: 0.0,
+            origin: [origin.x as f32, origin.y as f32],
+            _padding2: [0.0; 2],
         }
     }
 
@@ -148,14 +256,32 @@ impl DefaultConstantUniforms {
         scale_factor: ScaleFactor,
         queue: &wgpu::Queue,
     ) {
-        let uniforms = Self::new(screen_size, scale_factor);
+        Self::prepare_buffer_with_origin(
+            buffer,
+            screen_size,
+            scale_factor,
+            PhysicalPointI32::zero(),
+            queue,
+        );
+    }
+
+    /// Prepare the buffer for a viewport whose top-left corner is at `origin`
+    /// in the render target.
+    pub fn prepare_buffer_with_origin(
+        buffer: &wgpu::Buffer,
+        screen_size: PhysicalSizeI32,
+        scale_factor: ScaleFactor,
+        origin: PhysicalPointI32,
+        queue: &wgpu::Queue,
+    ) {
+        let uniforms = Self::new_with_origin(screen_size, scale_factor, origin);
         queue.write_buffer(buffer, 0, bytemuck::bytes_of(&uniforms));
     }
 
     pub fn entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
         wgpu::BindGroupLayoutEntry {
             binding,
-            visibility: wgpu::ShaderStages::VERTEX,
+            visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
             ty: wgpu::BindingType::Buffer {
                 ty: wgpu::BufferBindingType::Uniform,
                 has_dynamic_offset: false,
@@ -167,3 +293,22 @@ impl DefaultConstantUniforms {
         }
     }
 }
+
+/// The format of the depth buffer used when the depth buffer of the canvas is
+/// enabled.
+pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+/// The depth stencil state used by pipelines when the depth buffer of the
+/// canvas is enabled.
+///
+/// Fragments with a smaller depth are in front. The depth buffer is cleared
+/// to `1.0` at the start of each frame.
+pub fn depth_stencil_state() -> wgpu::DepthStencilState {
+    wgpu::DepthStencilState {
+        format: DEPTH_FORMAT,
+        depth_write_enabled: true,
+        depth_compare: wgpu::CompareFunction::LessEqual,
+        stencil: wgpu::StencilState::default(),
+        bias: wgpu::DepthBiasState::default(),
+    }
+}
@@ -0,0 +1,90 @@
+//! Helpers for serializing shared resource handles (like textures and text
+//! buffers) as opaque IDs.
+//!
+//! A handle is serialized as an ID which is only meaningful to the program
+//! that serialized it. When deserializing, the consumer must re-resolve each
+//! ID back into a handle by wrapping the deserialization inside of
+//! [`with_resolver`].
+
+use std::any::Any;
+use std::cell::RefCell;
+
+/// An opaque ID identifying a shared resource handle.
+pub type HandleID = u64;
+
+type Resolver<T> = Box<dyn FnMut(HandleID) -> Option<T>>;
+
+thread_local! {
+    static RESOLVERS: RefCell<Vec<Box<dyn Any>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Run `f` with the given resolver active for handles of type `T` on the
+/// current thread.
+///
+/// The resolver maps the IDs that were serialized back into handles. It
+/// should return `None` if the ID is unknown, in which case deserialization
+/// fails.
+pub fn with_resolver<T: 'static, R>(
+    resolver: impl FnMut(HandleID) -> Option<T> + 'static,
+    f: impl FnOnce() -> R,
+) -> R {
+    struct PopOnDrop;
+
+    impl Drop for PopOnDrop {
+        fn drop(&mut self) {
+            RESOLVERS.with_borrow_mut(|resolvers| resolvers.pop());
+        }
+    }
+
+    let resolver: Resolver<T> = Box::new(resolver);
+    RESOLVERS.with_borrow_mut(|resolvers| resolvers.push(Box::new(resolver)));
+
+    let _guard = PopOnDrop;
+
+    f()
+}
+
+/// Resolve the given ID into a handle of type `T` using the innermost active
+/// resolver for `T`.
+///
+/// Returns `None` if there is no active resolver for `T` or if the resolver
+/// did not recognize the ID.
+pub fn resolve<T: 'static>(id: HandleID) -> Option<T> {
+    RESOLVERS.with_borrow_mut(|resolvers| {
+        resolvers
+            .iter_mut()
+            .rev()
+            .find_map(|resolver| resolver.downcast_mut::<Resolver<T>>())
+            .and_then(|resolver| resolver(id))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn innermost_resolver_of_the_type_is_used() {
+        assert_eq!(resolve::<u32>(1), None);
+
+        with_resolver(
+            |id| Some(id as u32),
+            || {
+                assert_eq!(resolve::<u32>(1), Some(1));
+
+                with_resolver(
+                    |id| (id == 2).then_some(20u32),
+                    || {
+                        assert_eq!(resolve::<u32>(2), Some(20));
+                        assert_eq!(resolve::<u32>(1), None);
+                        assert_eq!(resolve::<String>(1), None);
+                    },
+                );
+
+                assert_eq!(resolve::<u32>(2), Some(2));
+            },
+        );
+
+        assert_eq!(resolve::<u32>(1), None);
+    }
+}
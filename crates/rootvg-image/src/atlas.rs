@@ -0,0 +1,383 @@
+use std::ops::Range;
+
+use image::RgbaImage;
+use rootvg_core::math::{PhysicalPointU32, PhysicalRectU32, PhysicalSizeU32, Point, Rect, Size};
+use rustc_hash::FxHashMap;
+
+use crate::{ImagePrimitive, ImagePrimitiveBuilder, RcTexture};
+
+/// The number of pixels added to each side of an image in the atlas. The
+/// edge pixels of the image are repeated into this padding so that linear
+/// filtering does not bleed neighboring images into each other.
+const PADDING: u32 = 1;
+
+/// A handle to an image stored in an [`ImageAtlas`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AtlasEntry {
+    id: u64,
+    size: PhysicalSizeU32,
+}
+
+impl AtlasEntry {
+    /// The size of the image in pixels.
+    pub fn size(&self) -> PhysicalSizeU32 {
+        self.size
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AtlasError {
+    #[error("the image of size {0:?} is too large to fit in the atlas")]
+    TooLarge(PhysicalSizeU32),
+    #[error("the atlas is full and no images could be evicted")]
+    Full,
+    #[error("the atlas entry has been removed or evicted")]
+    NotFound,
+    #[error("the new image has a different size than the atlas entry")]
+    DifferentSize,
+}
+
+#[derive(Debug)]
+struct EntryState {
+    /// The allocated rectangle, including padding.
+    allocation: PhysicalRectU32,
+    last_used_frame: u64,
+}
+
+/// Packs many small images into a single texture, so that all images drawn
+/// from the same atlas are rendered in a single draw call.
+///
+/// When there is no space left for a new image, images which have not been
+/// used since the last call to [`ImageAtlas::next_frame`] are evicted, least
+/// recently used first. Evicted entries must be re-inserted before they can be
+/// drawn again.
+#[derive(Debug)]
+pub struct ImageAtlas {
+    texture: RcTexture,
+    allocator: ShelfAllocator,
+    entries: FxHashMap<u64, EntryState>,
+    next_id: u64,
+    frame: u64,
+}
+
+impl ImageAtlas {
+    /// Create a new empty atlas with the given size in pixels.
+    ///
+    /// The GPU texture is allocated the first time the atlas is drawn.
+    pub fn new(size: PhysicalSizeU32) -> Self {
+        assert!(size.width > 0);
+        assert!(size.height > 0);
+
+        Self {
+            texture: RcTexture::new_atlas(size),
+            allocator: ShelfAllocator::new(size),
+            entries: FxHashMap::default(),
+            next_id: 0,
+            frame: 0,
+        }
+    }
+
+    /// The texture backing this atlas.
+    pub fn texture(&self) -> &RcTexture {
+        &self.texture
+    }
+
+    pub fn size(&self) -> PhysicalSizeU32 {
+        self.texture.size()
+    }
+
+    /// The number of images currently stored in the atlas.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Pack the given image into the atlas.
+    ///
+    /// If the atlas is full, then images which have not been used since the
+    /// last call to [`ImageAtlas::next_frame`] are evicted to make room.
+    pub fn insert(&mut self, image: impl Into<RgbaImage>) -> Result<AtlasEntry, AtlasError> {
+        let image: RgbaImage = image.into();
+        let (width, height) = image.dimensions();
+        let size = PhysicalSizeU32::new(width, height);
+
+        let padded_size = PhysicalSizeU32::new(width + PADDING * 2, height + PADDING * 2);
+        let atlas_size = self.size();
+        if width == 0
+            || height == 0
+            || padded_size.width > atlas_size.width
+            || padded_size.height > atlas_size.height
+        {
+            return Err(AtlasError::TooLarge(size));
+        }
+
+        let allocation = loop {
+            if let Some(allocation) = self.allocator.allocate(padded_size) {
+                break allocation;
+            }
+
+            if !self.evict_least_recently_used() {
+                return Err(AtlasError::Full);
+            }
+        };
+
+        let id = self.next_id;
+        self.next_id += 1;
+
+        self.entries.insert(
+            id,
+            EntryState {
+                allocation,
+                last_used_frame: self.frame,
+            },
+        );
+
+        self.texture
+            .write_atlas_region(allocation.origin, pad_image(&image));
+
+        Ok(AtlasEntry { id, size })
+    }
+
+    /// Replace the contents of an image in the atlas with a new image of the
+    /// same size.
+    pub fn replace(
+        &mut self,
+        entry: AtlasEntry,
+        image: impl Into<RgbaImage>,
+    ) -> Result<(), AtlasError> {
+        let image: RgbaImage = image.into();
+        let (width, height) = image.dimensions();
+
+        if PhysicalSizeU32::new(width, height) != entry.size {
+            return Err(AtlasError::DifferentSize);
+        }
+
+        let state = self
+            .entries
+            .get_mut(&entry.id)
+            .ok_or(AtlasError::NotFound)?;
+        state.last_used_frame = self.frame;
+
+        self.texture
+            .write_atlas_region(state.allocation.origin, pad_image(&image));
+
+        Ok(())
+    }
+
+    /// Remove an image from the atlas, freeing up its space.
+    pub fn remove(&mut self, entry: AtlasEntry) {
+        if let Some(state) = self.entries.remove(&entry.id) {
+            self.allocator.deallocate(state.allocation);
+        }
+    }
+
+    /// Returns `true` if the given entry is still stored in the atlas (it has
+    /// not been removed or evicted).
+    pub fn contains(&self, entry: AtlasEntry) -> bool {
+        self.entries.contains_key(&entry.id)
+    }
+
+    /// The normalized uv rectangle of the given entry in the atlas texture.
+    ///
+    /// Returns `None` if the entry has been removed or evicted.
+    pub fn uv_rect(&self, entry: AtlasEntry) -> Option<Rect> {
+        let state = self.entries.get(&entry.id)?;
+        let atlas_size = self.size();
+
+        Some(Rect::new(
+            Point::new(
+                (state.allocation.origin.x + PADDING) as f32 / atlas_size.width as f32,
+                (state.allocation.origin.y + PADDING) as f32 / atlas_size.height as f32,
+            ),
+            Size::new(
+                entry.size.width as f32 / atlas_size.width as f32,
+                entry.size.height as f32 / atlas_size.height as f32,
+            ),
+        ))
+    }
+
+    /// Create a builder for an image primitive which draws the given entry,
+    /// and mark the entry as used in this frame.
+    ///
+    /// Returns `None` if the entry has been removed or evicted.
+    pub fn builder(&mut self, entry: AtlasEntry) -> Option<ImagePrimitiveBuilder> {
+        let uv_rect = self.uv_rect(entry)?;
+
+        self.entries.get_mut(&entry.id).unwrap().last_used_frame = self.frame;
+
+        Some(
+            ImagePrimitive::builder(self.texture.clone())
+                .size(Size::new(entry.size.width as f32, entry.size.height as f32))
+                .normalized_uv_rect(uv_rect),
+        )
+    }
+
+    /// Create an image primitive which draws the given entry at its original
+    /// size, and mark the entry as used in this frame.
+    ///
+    /// Returns `None` if the entry has been removed or evicted.
+    pub fn primitive(&mut self, entry: AtlasEntry, position: Point) -> Option<ImagePrimitive> {
+        self.builder(entry).map(|b| b.position(position).build())
+    }
+
+    /// Advance to the next frame.
+    ///
+    /// Images which are used in the current frame are never evicted, since
+    /// overwriting them would corrupt the current frame. Call this once
+    /// after each frame has been rendered.
+    pub fn next_frame(&mut self) {
+        self.frame += 1;
+    }
+
+    fn evict_least_recently_used(&mut self) -> bool {
+        let Some((&id, _)) = self
+            .entries
+            .iter()
+            .filter(|(_, state)| state.last_used_frame < self.frame)
+            .min_by_key(|(_, state)| state.last_used_frame)
+        else {
+            return false;
+        };
+
+        let state = self.entries.remove(&id).unwrap();
+        self.allocator.deallocate(state.allocation);
+
+        true
+    }
+}
+
+/// Copy the image into a new image with the edge pixels repeated into the
+/// padding.
+fn pad_image(image: &RgbaImage) -> RgbaImage {
+    let (width, height) = image.dimensions();
+
+    RgbaImage::from_fn(width + PADDING * 2, height + PADDING * 2, |x, y| {
+        *image.get_pixel(
+            x.saturating_sub(PADDING).min(width - 1),
+            y.saturating_sub(PADDING).min(height - 1),
+        )
+    })
+}
+
+/// A simple shelf allocator. The atlas is split into horizontal shelves, and
+/// each shelf keeps a sorted list of its free horizontal spans.
+#[derive(Debug)]
+struct ShelfAllocator {
+    size: PhysicalSizeU32,
+    shelves: Vec<Shelf>,
+    /// The top of the unused space below the last shelf.
+    next_shelf_y: u32,
+}
+
+#[derive(Debug)]
+struct Shelf {
+    y: u32,
+    height: u32,
+    free_spans: Vec<Range<u32>>,
+}
+
+impl ShelfAllocator {
+    fn new(size: PhysicalSizeU32) -> Self {
+        Self {
+            size,
+            shelves: Vec::new(),
+            next_shelf_y: 0,
+        }
+    }
+
+    fn allocate(&mut self, size: PhysicalSizeU32) -> Option<PhysicalRectU32> {
+        // Prefer the shelf which wastes the least amount of vertical space.
+        // Shelves which are more than twice as tall as the image are only used
+        // as a last resort.
+        let mut best: Option<(usize, usize)> = None;
+        let mut best_fallback: Option<(usize, usize)> = None;
+        for (shelf_i, shelf) in self.shelves.iter().enumerate() {
+            if shelf.height < size.height {
+                continue;
+            }
+
+            let Some(span_i) = shelf
+                .free_spans
+                .iter()
+                .position(|span| span.end - span.start >= size.width)
+            else {
+                continue;
+            };
+
+            let slot = if shelf.height <= size.height * 2 {
+                &mut best
+            } else {
+                &mut best_fallback
+            };
+
+            let is_better = match slot {
+                Some((i, _)) => shelf.height < self.shelves[*i].height,
+                None => true,
+            };
+            if is_better {
+                *slot = Some((shelf_i, span_i));
+            }
+        }
+
+        if best.is_none() && self.next_shelf_y + size.height <= self.size.height {
+            self.shelves.push(Shelf {
+                y: self.next_shelf_y,
+                height: size.height,
+                free_spans: vec![0..self.size.width],
+            });
+            self.next_shelf_y += size.height;
+
+            best = Some((self.shelves.len() - 1, 0));
+        }
+
+        let (shelf_i, span_i) = best.or(best_fallback)?;
+        let shelf = &mut self.shelves[shelf_i];
+
+        let x = shelf.free_spans[span_i].start;
+        shelf.free_spans[span_i].start += size.width;
+        if shelf.free_spans[span_i].is_empty() {
+            shelf.free_spans.remove(span_i);
+        }
+
+        Some(PhysicalRectU32::new(
+            PhysicalPointU32::new(x, shelf.y),
+            size,
+        ))
+    }
+
+    fn deallocate(&mut self, rect: PhysicalRectU32) {
+        let Some(shelf) = self.shelves.iter_mut().find(|s| s.y == rect.origin.y) else {
+            return;
+        };
+
+        let span = rect.origin.x..rect.max_x();
+        let i = shelf.free_spans.partition_point(|s| s.start < span.start);
+        shelf.free_spans.insert(i, span);
+
+        // Merge with the neighboring spans.
+        if i + 1 < shelf.free_spans.len()
+            && shelf.free_spans[i].end == shelf.free_spans[i + 1].start
+        {
+            shelf.free_spans[i].end = shelf.free_spans[i + 1].end;
+            shelf.free_spans.remove(i + 1);
+        }
+        if i > 0 && shelf.free_spans[i - 1].end == shelf.free_spans[i].start {
+            shelf.free_spans[i - 1].end = shelf.free_spans[i].end;
+            shelf.free_spans.remove(i);
+        }
+
+        // Give the space of empty shelves at the bottom back to the atlas so
+        // that it can be reused by shelves of a different height.
+        while let Some(last) = self.shelves.last() {
+            if last.free_spans.len() != 1 || last.free_spans[0] != (0..self.size.width) {
+                break;
+            }
+
+            self.next_shelf_y = last.y;
+            self.shelves.pop();
+        }
+    }
+}
@@ -0,0 +1,134 @@
+/// A 4x5 matrix which is applied to the color of each sampled texel of an
+/// image, similar to the SVG `feColorMatrix` filter.
+///
+/// The matrix is stored in row-major order, where each row computes one
+/// output channel:
+///
+/// ```text
+/// | R' |   | m[0]  m[1]  m[2]  m[3]  m[4]  |   | R |
+/// | G' |   | m[5]  m[6]  m[7]  m[8]  m[9]  |   | G |
+/// | B' | = | m[10] m[11] m[12] m[13] m[14] | * | B |
+/// | A' |   | m[15] m[16] m[17] m[18] m[19] |   | A |
+///                                              | 1 |
+/// ```
+///
+/// The color channels are in the range `[0.0, 1.0]` and are not
+/// premultiplied. The resulting channels are clamped to the range
+/// `[0.0, 1.0]`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ColorMatrix(pub [f32; 20]);
+
+impl ColorMatrix {
+    pub const IDENTITY: Self = Self([
+        1.0, 0.0, 0.0, 0.0, 0.0, //
+        0.0, 1.0, 0.0, 0.0, 0.0, //
+        0.0, 0.0, 1.0, 0.0, 0.0, //
+        0.0, 0.0, 0.0, 1.0, 0.0, //
+    ]);
+
+    pub const fn new(matrix: [f32; 20]) -> Self {
+        Self(matrix)
+    }
+
+    /// Remove all color from the image.
+    ///
+    /// This is equivalent to `ColorMatrix::saturate(0.0)`.
+    pub fn grayscale() -> Self {
+        Self::saturate(0.0)
+    }
+
+    /// Change the saturation of the image, where `0.0` is fully desaturated
+    /// and `1.0` leaves the image unchanged. Values greater than `1.0`
+    /// oversaturate the image.
+    ///
+    /// This uses the same coefficients as the SVG `saturate` filter.
+    pub fn saturate(amount: f32) -> Self {
+        let s = amount;
+
+        Self([
+            0.213 + 0.787 * s,
+            0.715 - 0.715 * s,
+            0.072 - 0.072 * s,
+            0.0,
+            0.0,
+            //
+            0.213 - 0.213 * s,
+            0.715 + 0.285 * s,
+            0.072 - 0.072 * s,
+            0.0,
+            0.0,
+            //
+            0.213 - 0.213 * s,
+            0.715 - 0.715 * s,
+            0.072 + 0.928 * s,
+            0.0,
+            0.0,
+            //
+            0.0,
+            0.0,
+            0.0,
+            1.0,
+            0.0,
+        ])
+    }
+
+    /// Multiply the color channels of the image by `amount`, where `0.0`
+    /// results in black and `1.0` leaves the image unchanged.
+    pub fn brightness(amount: f32) -> Self {
+        let b = amount;
+
+        Self([
+            b, 0.0, 0.0, 0.0, 0.0, //
+            0.0, b, 0.0, 0.0, 0.0, //
+            0.0, 0.0, b, 0.0, 0.0, //
+            0.0, 0.0, 0.0, 1.0, 0.0, //
+        ])
+    }
+
+    /// Returns a matrix which is equivalent to applying `self` and then
+    /// `other`.
+    pub fn then(&self, other: &Self) -> Self {
+        let a = &self.0;
+        let b = &other.0;
+        let mut m = [0.0; 20];
+
+        for row in 0..4 {
+            for col in 0..5 {
+                let mut v = 0.0;
+                for i in 0..4 {
+                    v += b[row * 5 + i] * a[i * 5 + col];
+                }
+                if col == 4 {
+                    v += b[row * 5 + 4];
+                }
+
+                m[row * 5 + col] = v;
+            }
+        }
+
+        Self(m)
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == Self::IDENTITY
+    }
+
+    /// Convert to the column-major layout used by [`ImageVertex`].
+    ///
+    /// The first four columns are multiplied with the `r`, `g`, `b`, and `a`
+    /// channels respectively, and the last column is the offset.
+    ///
+    /// [`ImageVertex`]: crate::ImageVertex
+    pub fn to_columns(&self) -> [[f32; 4]; 5] {
+        let m = &self.0;
+
+        std::array::from_fn(|col| [m[col], m[5 + col], m[10 + col], m[15 + col]])
+    }
+}
+
+impl Default for ColorMatrix {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
@@ -1,7 +1,11 @@
+mod atlas;
+mod color_matrix;
 mod primitive;
 mod texture;
 
 pub mod pipeline;
 
+pub use atlas::{AtlasEntry, AtlasError, ImageAtlas};
+pub use color_matrix::ColorMatrix;
 pub use primitive::*;
-pub use texture::RcTexture;
+pub use texture::{RcTexture, TextureReplaceError};
@@ -4,8 +4,9 @@ use std::{cell::RefCell, ops::Range, rc::Rc};
 use wgpu::PipelineCompilationOptions;
 
 use rootvg_core::{
-    buffer::Buffer,
-    math::{PhysicalSizeI32, ScaleFactor},
+    buffer::{Buffer, BufferAllocationError},
+    clip::{ClipBuffer, ClipUniforms, RoundedClip},
+    math::{PhysicalPointI32, PhysicalSizeI32, ScaleFactor, Size},
     pipeline::DefaultConstantUniforms,
 };
 
@@ -18,6 +19,39 @@ use crate::{
 const INITIAL_INSTANCES: usize = 16;
 const INITIAL_SUB_BATCHES: usize = 16;
 
+/// Limits how many bytes of texture data are uploaded while preparing a
+/// frame, see [`ImagePipeline::set_upload_budget`].
+struct UploadBudget {
+    limit: Option<u64>,
+    used: u64,
+    exceeded: bool,
+}
+
+impl UploadBudget {
+    /// Returns `true` if this many bytes may be uploaded in the current
+    /// frame, and counts them against the budget.
+    ///
+    /// The first upload of a frame is always allowed, so that textures which
+    /// are larger than the whole budget are still uploaded eventually.
+    fn allow(&mut self, bytes: u64) -> bool {
+        let Some(limit) = self.limit else {
+            return true;
+        };
+
+        if bytes == 0 {
+            return true;
+        }
+
+        if self.used > 0 && self.used.saturating_add(bytes) > limit {
+            self.exceeded = true;
+            return false;
+        }
+
+        self.used += bytes;
+        true
+    }
+}
+
 struct Batch {
     range_in_buffer: Range<u32>,
     texture: RcTexture,
@@ -27,12 +61,13 @@ pub struct ImageBatchBuffer {
     buffer: Buffer<ImageVertex>,
     sub_batches: Vec<Batch>,
     num_instances: usize,
+    clip: ClipBuffer,
 
     prev_primitives: Vec<ImagePrimitive>,
 }
 
 impl ImageBatchBuffer {
-    fn new(device: &wgpu::Device) -> Self {
+    fn new(device: &wgpu::Device, clip_layout: &wgpu::BindGroupLayout) -> Self {
         Self {
             buffer: Buffer::new(
                 device,
@@ -42,6 +77,7 @@ impl ImageBatchBuffer {
             ),
             sub_batches: Vec::with_capacity(INITIAL_SUB_BATCHES),
             num_instances: 0,
+            clip: ClipBuffer::new(device, clip_layout),
             prev_primitives: Vec::new(),
         }
     }
@@ -52,18 +88,15 @@ impl ImageBatchBuffer {
         device: &wgpu::Device,
         queue: &wgpu::Queue,
         texture_bind_group_layout: &wgpu::BindGroupLayout,
-    ) {
+        upload_budget: &mut UploadBudget,
+    ) -> Result<(), BufferAllocationError> {
         // Don't prepare if primitives have not changed since the last
         // prepare.
         if primitives == self.prev_primitives {
-            return;
+            return Ok(());
         }
-        self.prev_primitives = primitives.into();
 
         self.sub_batches.clear();
-        self.num_instances = primitives.len();
-
-        self.buffer.expand_to_fit_new_size(device, primitives.len());
 
         struct TempSubBatchEntry {
             vertices: SmallVec<[ImageVertex; INITIAL_INSTANCES]>,
@@ -75,10 +108,23 @@ impl ImageBatchBuffer {
             FxHashMap::default();
         sub_batches_map.reserve(INITIAL_SUB_BATCHES);
 
+        let mut deferred_upload = false;
+
         for image in primitives.iter() {
-            image
-                .texture
-                .upload_if_needed(device, queue, texture_bind_group_layout);
+            if upload_budget.allow(image.texture.pending_upload_bytes()) {
+                image
+                    .texture
+                    .upload_if_needed(device, queue, texture_bind_group_layout);
+            } else {
+                deferred_upload = true;
+            }
+
+            // A texture which has never been uploaded can't be drawn yet.
+            // Textures which already have been uploaded are drawn with their
+            // previous data until the new data is uploaded.
+            if !image.texture.has_bind_group() {
+                continue;
+            }
 
             let sub_batch = sub_batches_map
                 .entry(Rc::as_ptr(&image.texture.inner))
@@ -87,9 +133,26 @@ impl ImageBatchBuffer {
                     texture: image.texture.clone(),
                 });
 
-            sub_batch.vertices.push(image.vertex);
+            if let Some(nine_patch) = &image.nine_patch {
+                let texture_size = image.texture.size();
+
+                sub_batch.vertices.extend(nine_patch.slice(
+                    &image.vertex,
+                    Size::new(texture_size.width as f32, texture_size.height as f32),
+                ));
+            } else {
+                sub_batch.vertices.push(image.vertex);
+            }
         }
 
+        self.num_instances = sub_batches_map
+            .values()
+            .map(|sub_batch| sub_batch.vertices.len())
+            .sum();
+
+        self.buffer
+            .expand_to_fit_new_size(device, self.num_instances)?;
+
         let mut range_start = 0;
         for sub_batch in sub_batches_map.values() {
             self.buffer.write(queue, range_start, &sub_batch.vertices);
@@ -102,6 +165,14 @@ impl ImageBatchBuffer {
 
             range_start += sub_batch.vertices.len();
         }
+
+        // Only skip the next preparation once this one has succeeded, and
+        // all of the textures have been uploaded.
+        if !deferred_upload {
+            self.prev_primitives = primitives.into();
+        }
+
+        Ok(())
     }
 }
 
@@ -111,9 +182,13 @@ pub struct ImagePipeline {
     constants_buffer: wgpu::Buffer,
     constants_bind_group: wgpu::BindGroup,
     texture_layout: wgpu::BindGroupLayout,
+    clip_layout: wgpu::BindGroupLayout,
 
     screen_size: PhysicalSizeI32,
     scale_factor: ScaleFactor,
+    origin: PhysicalPointI32,
+
+    upload_budget: UploadBudget,
 }
 
 impl ImagePipeline {
@@ -121,6 +196,7 @@ impl ImagePipeline {
         device: &wgpu::Device,
         format: wgpu::TextureFormat,
         multisample: wgpu::MultisampleState,
+        depth_stencil: Option<wgpu::DepthStencilState>,
     ) -> Self {
         let constants_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some("rootvg-image constants layout"),
@@ -139,7 +215,7 @@ impl ImagePipeline {
 
         let constants_buffer = device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("rootvg-image constants buffer"),
-            size: std::mem::size_of::<Self>() as wgpu::BufferAddress,
+            size: std::mem::size_of::<DefaultConstantUniforms>() as wgpu::BufferAddress,
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
             mapped_at_creation: false,
         });
@@ -183,17 +259,21 @@ impl ImagePipeline {
             }],
         });
 
+        let clip_layout = ClipUniforms::layout(device);
+
         let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("rootvg-image pipeline layout"),
             push_constant_ranges: &[],
-            bind_group_layouts: &[&constants_layout, &texture_layout],
+            bind_group_layouts: &[&constants_layout, &texture_layout, &clip_layout],
         });
 
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("rootvg-image shader"),
-            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(concat!(include_str!(
-                "shader/image.wgsl"
-            ),))),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(concat!(
+                include_str!("shader/image.wgsl"),
+                "\n",
+                include_str!("shader/clip.wgsl"),
+            ))),
         });
 
         let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
@@ -220,6 +300,18 @@ impl ImagePipeline {
                         6 => Float32x2,
                         // Has Transformation
                         7 => Uint32,
+                        // Tint
+                        8 => Float32x4,
+                        // Opacity
+                        9 => Float32,
+                        // Color Matrix 4x5
+                        10 => Float32x4,
+                        11 => Float32x4,
+                        12 => Float32x4,
+                        13 => Float32x4,
+                        14 => Float32x4,
+                        // Has Color Matrix
+                        15 => Uint32,
                     ),
                 }],
                 compilation_options: PipelineCompilationOptions::default(),
@@ -250,7 +342,7 @@ impl ImagePipeline {
                 front_face: wgpu::FrontFace::Cw,
                 ..Default::default()
             },
-            depth_stencil: None,
+            depth_stencil,
             multisample,
             multiview: None,
             cache: None,
@@ -261,13 +353,43 @@ impl ImagePipeline {
             constants_buffer,
             constants_bind_group,
             texture_layout,
+            clip_layout,
             screen_size: PhysicalSizeI32::default(),
             scale_factor: ScaleFactor::default(),
+            origin: PhysicalPointI32::default(),
+            upload_budget: UploadBudget {
+                limit: None,
+                used: 0,
+                exceeded: false,
+            },
         }
     }
 
+    /// Limit how many bytes of texture data are uploaded while preparing a
+    /// single frame, or `None` for no limit (the default).
+    ///
+    /// Images whose textures don't fit inside of the budget are left out of
+    /// the frame, and are uploaded in one of the following frames instead.
+    /// This keeps the frame in which a large image is first added from
+    /// stalling, at the cost of the image appearing a frame late. One texture
+    /// is always uploaded per frame, even if it is larger than the budget.
+    pub fn set_upload_budget(&mut self, bytes: Option<u64>) {
+        self.upload_budget.limit = bytes;
+    }
+
+    pub fn upload_budget(&self) -> Option<u64> {
+        self.upload_budget.limit
+    }
+
+    /// Returns `true` if uploads have been deferred to a later frame because
+    /// the upload budget ran out, in which case the batches of this pipeline
+    /// must be prepared again.
+    pub fn needs_preparing(&self) -> bool {
+        self.upload_budget.exceeded
+    }
+
     pub fn create_batch(&mut self, device: &wgpu::Device) -> ImageBatchBuffer {
-        ImageBatchBuffer::new(device)
+        ImageBatchBuffer::new(device, &self.clip_layout)
     }
 
     pub fn start_preparations(
@@ -276,18 +398,27 @@ impl ImagePipeline {
         queue: &wgpu::Queue,
         screen_size: PhysicalSizeI32,
         scale_factor: ScaleFactor,
+        origin: PhysicalPointI32,
     ) {
-        if self.screen_size == screen_size && self.scale_factor == scale_factor {
+        self.upload_budget.used = 0;
+        self.upload_budget.exceeded = false;
+
+        if self.screen_size == screen_size
+            && self.scale_factor == scale_factor
+            && self.origin == origin
+        {
             return;
         }
 
         self.screen_size = screen_size;
         self.scale_factor = scale_factor;
+        self.origin = origin;
 
-        DefaultConstantUniforms::prepare_buffer(
+        DefaultConstantUniforms::prepare_buffer_with_origin(
             &self.constants_buffer,
             screen_size,
             scale_factor,
+            origin,
             queue,
         );
     }
@@ -296,10 +427,19 @@ impl ImagePipeline {
         &mut self,
         batch: &mut ImageBatchBuffer,
         primitives: &[ImagePrimitive],
+        clip: Option<RoundedClip>,
+        depth: f32,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
-    ) {
-        batch.prepare(primitives, device, queue, &self.texture_layout);
+    ) -> Result<(), BufferAllocationError> {
+        batch.clip.prepare(clip, depth, self.scale_factor, queue);
+        batch.prepare(
+            primitives,
+            device,
+            queue,
+            &self.texture_layout,
+            &mut self.upload_budget,
+        )
     }
 
     pub fn render_batch<'pass>(
@@ -313,6 +453,7 @@ impl ImagePipeline {
 
         render_pass.set_pipeline(&self.pipeline);
         render_pass.set_bind_group(0, &self.constants_bind_group, &[]);
+        render_pass.set_bind_group(2, batch.clip.bind_group(), &[]);
 
         render_pass.set_vertex_buffer(0, batch.buffer.slice(0..batch.num_instances));
 
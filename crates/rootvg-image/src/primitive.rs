@@ -1,12 +1,145 @@
 use bytemuck::{Pod, Zeroable};
-use rootvg_core::math::{Angle, Point, Rect, Scale, Size, Transform, Vector};
+use rootvg_core::color::PackedSrgb;
+use rootvg_core::math::{Angle, Box2D, Point, Rect, Scale, SideOffsets, Size, Transform, Vector};
+use smallvec::SmallVec;
 
+use crate::color_matrix::ColorMatrix;
 use crate::texture::RcTexture;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ImagePrimitive {
     pub texture: RcTexture,
     pub vertex: ImageVertex,
+
+    /// If this is `Some`, then the image is sliced into 9 regions which are
+    /// stretched to fill the size of the image.
+    ///
+    /// By default this is set to `None`.
+    pub nine_patch: Option<NinePatch>,
+}
+
+/// Describes how to slice an image into 9 regions. The corners keep their
+/// size, the edges are stretched along one axis, and the center is stretched
+/// along both axes to fill the size of the image.
+///
+/// Note, the transform of the image is ignored when drawing a nine-patch.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NinePatch {
+    /// The distances from each edge of the source rectangle in the texture
+    /// to the center region, in pixels.
+    pub insets: SideOffsets,
+
+    /// The number of logical points each pixel of the corner and edge regions
+    /// takes up when drawn.
+    ///
+    /// If the image is too small to fit the corners, then the corners are
+    /// scaled down to fit.
+    ///
+    /// By default this is set to `1.0`.
+    pub border_scale: f32,
+}
+
+impl NinePatch {
+    pub const fn new(insets: SideOffsets) -> Self {
+        Self {
+            insets,
+            border_scale: 1.0,
+        }
+    }
+
+    pub const fn border_scale(mut self, border_scale: f32) -> Self {
+        self.border_scale = border_scale;
+        self
+    }
+
+    /// Slice the given vertex into the vertices of the (up to) 9 patches.
+    ///
+    /// Patches with a zero width or height are skipped.
+    pub fn slice(&self, vertex: &ImageVertex, texture_size: Size) -> SmallVec<[ImageVertex; 9]> {
+        let mut vertices = SmallVec::new();
+
+        let mut dst_w = [
+            self.insets.left * self.border_scale,
+            0.0,
+            self.insets.right * self.border_scale,
+        ];
+        let mut dst_h = [
+            self.insets.top * self.border_scale,
+            0.0,
+            self.insets.bottom * self.border_scale,
+        ];
+
+        // Scale the corners down if they don't fit.
+        let fit_x = (vertex.size[0] / (dst_w[0] + dst_w[2])).min(1.0);
+        let fit_y = (vertex.size[1] / (dst_h[0] + dst_h[2])).min(1.0);
+        if fit_x.is_finite() {
+            dst_w[0] *= fit_x;
+            dst_w[2] *= fit_x;
+        }
+        if fit_y.is_finite() {
+            dst_h[0] *= fit_y;
+            dst_h[2] *= fit_y;
+        }
+        dst_w[1] = (vertex.size[0] - dst_w[0] - dst_w[2]).max(0.0);
+        dst_h[1] = (vertex.size[1] - dst_h[0] - dst_h[2]).max(0.0);
+
+        let uv_w = [
+            self.insets.left / texture_size.width,
+            0.0,
+            self.insets.right / texture_size.width,
+        ];
+        let uv_h = [
+            self.insets.top / texture_size.height,
+            0.0,
+            self.insets.bottom / texture_size.height,
+        ];
+        let uv_w = [
+            uv_w[0],
+            (vertex.normalized_uv_size[0] - uv_w[0] - uv_w[2]).max(0.0),
+            uv_w[2],
+        ];
+        let uv_h = [
+            uv_h[0],
+            (vertex.normalized_uv_size[1] - uv_h[0] - uv_h[2]).max(0.0),
+            uv_h[2],
+        ];
+
+        // Accumulate the edges of each patch from the same starting values so
+        // that neighboring patches share the exact same edges and no seams
+        // appear between them.
+        let mut y = vertex.position[1];
+        let mut v = vertex.normalized_uv_pos[1];
+        for row in 0..3 {
+            let mut x = vertex.position[0];
+            let mut u = vertex.normalized_uv_pos[0];
+
+            for col in 0..3 {
+                if dst_w[col] > 0.0 && dst_h[row] > 0.0 {
+                    vertices.push(ImageVertex {
+                        position: [x, y],
+                        size: [dst_w[col], dst_h[row]],
+                        normalized_uv_pos: [u, v],
+                        normalized_uv_size: [uv_w[col], uv_h[row]],
+                        tint: vertex.tint,
+                        opacity: vertex.opacity,
+                        color_matrix: vertex.color_matrix,
+                        has_color_matrix: vertex.has_color_matrix,
+                        ..Default::default()
+                    });
+                }
+
+                x += dst_w[col];
+                u += uv_w[col];
+            }
+
+            y += dst_h[row];
+            v += uv_h[row];
+        }
+
+        vertices
+    }
 }
 
 impl ImagePrimitive {
@@ -20,6 +153,7 @@ impl ImagePrimitive {
                 size: [size.width as f32, size.height as f32],
                 ..Default::default()
             },
+            nine_patch: None,
         }
     }
 
@@ -34,6 +168,26 @@ impl ImagePrimitive {
     pub fn set_position(&mut self, position: Point) {
         self.vertex.position = position.into();
     }
+
+    /// The area covered by the image in logical points after its transform
+    /// is applied.
+    pub fn bounds(&self) -> Rect {
+        let v = &self.vertex;
+        let transform = (v.has_transform != 0 && self.nine_patch.is_none())
+            .then(|| Transform::from_array(v.transform));
+
+        let corners = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)].map(|(x, y)| {
+            let p = Point::new(x, y);
+            let p = transform.map(|t| t.transform_point(p)).unwrap_or(p);
+
+            Point::new(
+                v.position[0] + p.x * v.size[0],
+                v.position[1] + p.y * v.size[1],
+            )
+        });
+
+        Box2D::from_points(corners).to_rect()
+    }
 }
 
 pub struct ImagePrimitiveBuilder {
@@ -53,6 +207,47 @@ impl ImagePrimitiveBuilder {
         self
     }
 
+    /// The size of the image in logical points.
+    ///
+    /// By default this is set to the size of the texture.
+    pub fn size(mut self, size: Size) -> Self {
+        self.primitive.vertex.size = size.into();
+        self
+    }
+
+    /// Slice the image into 9 regions which are stretched to fill the size of
+    /// the image. See [`NinePatch`].
+    pub fn nine_patch(mut self, nine_patch: NinePatch) -> Self {
+        self.primitive.nine_patch = Some(nine_patch);
+        self
+    }
+
+    /// A color which is multiplied with each sampled texel of the image. This
+    /// is useful for theming the same image asset in multiple colors.
+    ///
+    /// By default this is set to white (no tint).
+    pub fn tint(mut self, tint: impl Into<PackedSrgb>) -> Self {
+        self.primitive.vertex.tint = tint.into();
+        self
+    }
+
+    /// A multiplier for the alpha of the image in the range `[0.0, 1.0]`.
+    ///
+    /// By default this is set to `1.0`.
+    pub fn opacity(mut self, opacity: f32) -> Self {
+        self.primitive.vertex.opacity = opacity;
+        self
+    }
+
+    /// A color matrix which is applied to each sampled texel of the image
+    /// before the tint. See [`ColorMatrix`].
+    ///
+    /// By default no color matrix is applied.
+    pub fn color_matrix(mut self, color_matrix: ColorMatrix) -> Self {
+        self.primitive.vertex.set_color_matrix(color_matrix);
+        self
+    }
+
     pub fn scale(mut self, scale_x: Scale, scale_y: Scale) -> Self {
         self.primitive.vertex.size[0] *= scale_x.0;
         self.primitive.vertex.size[1] *= scale_y.0;
@@ -75,10 +270,29 @@ impl ImagePrimitiveBuilder {
         self
     }
 
+    /// Only draw the given rectangle of the texture, for example a single
+    /// sprite of a sprite sheet. The rectangle is in pixels.
+    ///
+    /// This also sets the size of the image to the size of the rectangle, so
+    /// call [`ImagePrimitiveBuilder::size`] afterwards to draw it at a
+    /// different size.
+    ///
+    /// By default the whole texture is drawn.
+    pub fn source_rect(mut self, source_rect: Rect) -> Self {
+        self.primitive.vertex.size = source_rect.size.into();
+        self.unnormalized_uv_rect(source_rect)
+    }
+
+    /// The rectangle of the texture to draw, in pixels.
+    ///
+    /// Unlike [`ImagePrimitiveBuilder::source_rect`], this does not change
+    /// the size of the image.
     pub fn unnormalized_uv_rect(mut self, uv_rect: Rect) -> Self {
+        let texture_size = self.primitive.texture.size();
+
         let normal_uv_rect = normalized_uv_rect(
             uv_rect,
-            Size::new(self.primitive.vertex.size[0], self.primitive.vertex.size[1]),
+            Size::new(texture_size.width as f32, texture_size.height as f32),
         );
 
         self.primitive.vertex.normalized_uv_pos = normal_uv_rect.origin.into();
@@ -86,6 +300,8 @@ impl ImagePrimitiveBuilder {
         self
     }
 
+    /// The rectangle of the texture to draw, normalized to the range
+    /// `[0.0, 1.0]`.
     pub fn normalized_uv_rect(mut self, uv_rect: Rect) -> Self {
         self.primitive.vertex.normalized_uv_pos = uv_rect.origin.into();
         self.primitive.vertex.normalized_uv_size = uv_rect.size.into();
@@ -105,6 +321,7 @@ impl From<ImagePrimitiveBuilder> for ImagePrimitive {
 
 #[repr(C)]
 #[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ImageVertex {
     /// The position of the top-left corner of the [`Image`] (before rotation)
     /// in logical points.
@@ -137,6 +354,35 @@ pub struct ImageVertex {
     ///
     /// By default this is set to `0` (false).
     pub has_transform: u32,
+
+    /// A color which is multiplied with each sampled texel of the image.
+    ///
+    /// By default this is set to white (no tint).
+    pub tint: PackedSrgb,
+
+    /// A multiplier for the alpha of the image in the range `[0.0, 1.0]`.
+    ///
+    /// By default this is set to `1.0`.
+    pub opacity: f32,
+
+    /// A column-major 4 by 5 color matrix which is applied to each sampled
+    /// texel of the image before the tint. See [`ColorMatrix`].
+    pub color_matrix: [[f32; 4]; 5],
+
+    /// Whether or not to apply the `color_matrix`. This is used to optimize
+    /// images with no color matrix.
+    ///
+    /// By default this is set to `0` (false).
+    pub has_color_matrix: u32,
+}
+
+impl ImageVertex {
+    /// Set the color matrix of this vertex. No matrix is applied if the
+    /// given matrix is the identity matrix.
+    pub fn set_color_matrix(&mut self, color_matrix: ColorMatrix) {
+        self.color_matrix = color_matrix.to_columns();
+        self.has_color_matrix = if color_matrix.is_identity() { 0 } else { 1 };
+    }
 }
 
 impl Default for ImageVertex {
@@ -148,6 +394,10 @@ impl Default for ImageVertex {
             normalized_uv_size: [1.0; 2],
             transform: [0.0; 6],
             has_transform: 0,
+            tint: PackedSrgb::WHITE,
+            opacity: 1.0,
+            color_matrix: ColorMatrix::IDENTITY.to_columns(),
+            has_color_matrix: 0,
         }
     }
 }
@@ -164,3 +414,39 @@ fn normalized_uv_rect(uv_rect: Rect, texture_size: Size) -> Rect {
         ),
     )
 }
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use image::RgbaImage;
+    use rootvg_core::color::RGBA8;
+    use rootvg_core::serde_handle::with_resolver;
+
+    use super::*;
+
+    #[test]
+    fn serde_round_trip() {
+        let texture = RcTexture::new(RgbaImage::new(4, 4));
+
+        let primitive = ImagePrimitive::builder(texture.clone())
+            .position(Point::new(5.0, 10.0))
+            .size(Size::new(20.0, 30.0))
+            .tint(RGBA8::new(255, 0, 0, 255))
+            .opacity(0.5)
+            .rotation(Angle::degrees(30.0), Point::new(0.5, 0.5))
+            .nine_patch(NinePatch::new(SideOffsets::new(1.0, 2.0, 1.0, 2.0)).border_scale(2.0))
+            .build();
+
+        let json = serde_json::to_string(&primitive).unwrap();
+
+        // The texture is serialized as its ID, which has to be resolved.
+        assert!(serde_json::from_str::<ImagePrimitive>(&json).is_err());
+
+        let id = texture.id();
+        let deserialized: ImagePrimitive = with_resolver(
+            move |resolved_id| (resolved_id == id).then(|| texture.clone()),
+            || serde_json::from_str(&json).unwrap(),
+        );
+
+        assert_eq!(deserialized, primitive);
+    }
+}
@@ -0,0 +1,25 @@
+// A rounded clipping rectangle in physical pixels, along with the depth of
+// the batch.
+struct ClipUniforms {
+    // x, y, width, height
+    rect: vec4<f32>,
+    radius: f32,
+    enabled: u32,
+    depth: f32,
+}
+
+// Returns the coverage of the fragment by the rounded clipping rectangle.
+fn clip_alpha(uniforms: ClipUniforms, frag_coord: vec2<f32>) -> f32 {
+    // The enabled flag is uniform across the whole draw call, so this branch
+    // is cheap when no rounded clip is active.
+    if uniforms.enabled == 0u {
+        return 1.0;
+    }
+
+    let half_size = uniforms.rect.zw * 0.5;
+    let radius = clamp(uniforms.radius, 0.0, min(half_size.x, half_size.y));
+    let q = abs(frag_coord - (uniforms.rect.xy + half_size)) - half_size + vec2<f32>(radius, radius);
+    let dist = min(max(q.x, q.y), 0.0) + length(max(q, vec2<f32>(0.0, 0.0))) - radius;
+
+    return clamp(0.5 - dist, 0.0, 1.0);
+}
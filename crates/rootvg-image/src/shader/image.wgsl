@@ -1,6 +1,7 @@
 struct Globals {
     screen_size_recip: vec2<f32>,
     scale_factor: f32,
+    origin: vec2<f32>,
 }
 
 @group(0) @binding(0) var<uniform> globals: Globals;
@@ -8,6 +9,8 @@ struct Globals {
 
 @group(1) @binding(0) var tex: texture_2d<f32>;
 
+@group(2) @binding(0) var<uniform> clip: ClipUniforms;
+
 struct VertexInput {
     @builtin(vertex_index) vertex_index: u32,
     @location(0) pos: vec2<f32>,
@@ -18,11 +21,26 @@ struct VertexInput {
     @location(5) transform2: vec2<f32>,
     @location(6) transform3: vec2<f32>,
     @location(7) do_transform: u32,
+    @location(8) tint: vec4<f32>,
+    @location(9) opacity: f32,
+    @location(10) color_matrix1: vec4<f32>,
+    @location(11) color_matrix2: vec4<f32>,
+    @location(12) color_matrix3: vec4<f32>,
+    @location(13) color_matrix4: vec4<f32>,
+    @location(14) color_matrix5: vec4<f32>,
+    @location(15) has_color_matrix: u32,
 }
 
 struct VertexOutput {
     @builtin(position) position: vec4<f32>,
     @location(0) uv_pos: vec2<f32>,
+    @location(1) @interpolate(flat) tint: vec4<f32>,
+    @location(2) @interpolate(flat) color_matrix1: vec4<f32>,
+    @location(3) @interpolate(flat) color_matrix2: vec4<f32>,
+    @location(4) @interpolate(flat) color_matrix3: vec4<f32>,
+    @location(5) @interpolate(flat) color_matrix4: vec4<f32>,
+    @location(6) @interpolate(flat) color_matrix5: vec4<f32>,
+    @location(7) @interpolate(flat) has_color_matrix: u32,
 }
 
 @vertex
@@ -42,13 +60,20 @@ fn vs_main(input: VertexInput) -> VertexOutput {
         (input.pos + (transformed_vertex_pos * input.size))
         * globals.scale_factor;
     out.position = vec4<f32>(
-        (screen_pos.x * globals.screen_size_recip.x) - 1.0,
-        1.0 - (screen_pos.y * globals.screen_size_recip.y),
-        0.0,
+        ((screen_pos.x + globals.origin.x) * globals.screen_size_recip.x) - 1.0,
+        1.0 - ((screen_pos.y + globals.origin.y) * globals.screen_size_recip.y),
+        clip.depth,
         1.0
     );
 
     out.uv_pos = input.uv_pos + (vertex_pos * input.uv_size);
+    out.tint = vec4<f32>(input.tint.rgb, input.tint.a * input.opacity);
+    out.color_matrix1 = input.color_matrix1;
+    out.color_matrix2 = input.color_matrix2;
+    out.color_matrix3 = input.color_matrix3;
+    out.color_matrix4 = input.color_matrix4;
+    out.color_matrix5 = input.color_matrix5;
+    out.has_color_matrix = input.has_color_matrix;
 
     return out;
 }
@@ -63,5 +88,18 @@ fn vertex_position(vertex_index: u32) -> vec2<f32> {
 
 @fragment
 fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
-    return textureSample(tex, smp, input.uv_pos);
+    var texel = textureSample(tex, smp, input.uv_pos);
+
+    if input.has_color_matrix != 0 {
+        let m = mat4x4<f32>(
+            input.color_matrix1,
+            input.color_matrix2,
+            input.color_matrix3,
+            input.color_matrix4,
+        );
+        texel = clamp((m * texel) + input.color_matrix5, vec4<f32>(0.0), vec4<f32>(1.0));
+    }
+
+    let color = texel * input.tint;
+    return vec4<f32>(color.rgb, color.a * clip_alpha(clip, input.position.xy - globals.origin));
 }
\ No newline at end of file
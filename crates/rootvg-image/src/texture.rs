@@ -1,8 +1,9 @@
 use std::cell::RefCell;
+use std::fmt;
 use std::rc::Rc;
 
 use image::RgbaImage;
-use rootvg_core::math::PhysicalSizeU32;
+use rootvg_core::math::{PhysicalPointU32, PhysicalSizeU32};
 
 #[derive(Debug)]
 enum TextureSource {
@@ -13,18 +14,33 @@ enum TextureSource {
     Prepass {
         view: wgpu::TextureView,
     },
+    Atlas {
+        regions_to_upload: Vec<(PhysicalPointU32, RgbaImage)>,
+        uploaded_texture: Option<wgpu::Texture>,
+    },
 }
 
 #[derive(Debug)]
 pub(crate) struct TextureInner {
     source: TextureSource,
     pub(crate) bind_group: Option<wgpu::BindGroup>,
+    upload_callbacks: Vec<UploadCallback>,
+}
+
+struct UploadCallback(Box<dyn FnOnce()>);
+
+impl fmt::Debug for UploadCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("UploadCallback")
+    }
 }
 
 #[derive(Debug)]
 pub enum TextureReplaceError {
     DifferentSize,
     DifferentSourceType,
+    /// The region to update does not fit inside of the texture.
+    OutOfBounds,
 }
 
 /// A source of raw image data.
@@ -51,6 +67,7 @@ impl RcTexture {
                     uploaded_texture: None,
                 },
                 bind_group: None,
+                upload_callbacks: Vec::new(),
             })),
             size: PhysicalSizeU32::new(dimensions.0, dimensions.1),
             generation: 0,
@@ -62,12 +79,47 @@ impl RcTexture {
             inner: Rc::new(RefCell::new(TextureInner {
                 source: TextureSource::Prepass { view: texture_view },
                 bind_group: None,
+                upload_callbacks: Vec::new(),
+            })),
+            size,
+            generation: 0,
+        }
+    }
+
+    /// Create an empty texture which is filled in one region at a time. This
+    /// is used by [`ImageAtlas`](crate::ImageAtlas).
+    pub(crate) fn new_atlas(size: PhysicalSizeU32) -> Self {
+        Self {
+            inner: Rc::new(RefCell::new(TextureInner {
+                source: TextureSource::Atlas {
+                    regions_to_upload: Vec::new(),
+                    uploaded_texture: None,
+                },
+                bind_group: None,
+                upload_callbacks: Vec::new(),
             })),
             size,
             generation: 0,
         }
     }
 
+    /// Queue the given image to be written into the region of the atlas
+    /// starting at `origin`.
+    pub(crate) fn write_atlas_region(&mut self, origin: PhysicalPointU32, image: RgbaImage) {
+        let mut inner = RefCell::borrow_mut(&self.inner);
+
+        let TextureSource::Atlas {
+            regions_to_upload, ..
+        } = &mut inner.source
+        else {
+            return;
+        };
+
+        regions_to_upload.push((origin, image));
+
+        self.generation += 1;
+    }
+
     pub fn replace_with_image(
         &mut self,
         image: impl Into<RgbaImage>,
@@ -93,6 +145,89 @@ impl RcTexture {
         Ok(())
     }
 
+    /// Write the given image into the region of this texture starting at
+    /// `origin`.
+    ///
+    /// If the texture has already been uploaded, then the region is written
+    /// to the existing GPU texture right away, without reallocating it. The
+    /// write takes effect with the next submission to `queue`. Otherwise the
+    /// region is written into the image data which is yet to be uploaded.
+    ///
+    /// Returns an error if the region does not fit inside of the texture, or
+    /// if this is a prepass texture.
+    pub fn update_region(
+        &mut self,
+        queue: &wgpu::Queue,
+        origin: PhysicalPointU32,
+        image: &RgbaImage,
+    ) -> Result<(), TextureReplaceError> {
+        let (width, height) = image.dimensions();
+
+        let fits = |origin: u32, size: u32, max: u32| {
+            origin.checked_add(size).is_some_and(|end| end <= max)
+        };
+        if !fits(origin.x, width, self.size.width) || !fits(origin.y, height, self.size.height) {
+            return Err(TextureReplaceError::OutOfBounds);
+        }
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
+
+        let mut inner = RefCell::borrow_mut(&self.inner);
+
+        match &mut inner.source {
+            TextureSource::Image {
+                data_to_upload,
+                uploaded_texture,
+            } => {
+                if let Some(data) = data_to_upload {
+                    image::imageops::replace(data, image, origin.x as i64, origin.y as i64);
+                } else if let Some(texture) = uploaded_texture {
+                    write_region(queue, texture, origin, image);
+                }
+            }
+            TextureSource::Atlas {
+                regions_to_upload,
+                uploaded_texture,
+            } => {
+                if let Some(texture) = uploaded_texture {
+                    write_region(queue, texture, origin, image);
+                } else {
+                    regions_to_upload.push((origin, image.clone()));
+                }
+            }
+            TextureSource::Prepass { .. } => {
+                return Err(TextureReplaceError::DifferentSourceType);
+            }
+        }
+
+        self.generation += 1;
+
+        Ok(())
+    }
+
+    /// Write the given image over the whole texture. See
+    /// [`RcTexture::update_region`].
+    ///
+    /// Unlike [`RcTexture::replace_with_image`], an already uploaded texture
+    /// is written to right away instead of on the next prepare.
+    ///
+    /// Returns an error if the image is not the same size as this texture,
+    /// or if this is a prepass texture.
+    pub fn update_full(
+        &mut self,
+        queue: &wgpu::Queue,
+        image: &RgbaImage,
+    ) -> Result<(), TextureReplaceError> {
+        let (width, height) = image.dimensions();
+
+        if PhysicalSizeU32::new(width, height) != self.size {
+            return Err(TextureReplaceError::DifferentSize);
+        }
+
+        self.update_region(queue, PhysicalPointU32::zero(), image)
+    }
+
     pub fn replace_prepass_texture(
         &mut self,
         texture_view: wgpu::TextureView,
@@ -125,15 +260,107 @@ impl RcTexture {
         self.size
     }
 
+    /// An ID which uniquely identifies this texture (and its clones) for as
+    /// long as it is alive.
+    pub fn id(&self) -> u64 {
+        Rc::as_ptr(&self.inner) as usize as u64
+    }
+
+    /// Returns `true` if the data of this texture has been uploaded to the
+    /// GPU, and there is no newer data which is still waiting to be
+    /// uploaded.
+    ///
+    /// Textures are uploaded when the first frame which uses them is
+    /// prepared. Prepass textures are always uploaded.
+    pub fn is_uploaded(&self) -> bool {
+        let inner = RefCell::borrow(&self.inner);
+
+        match &inner.source {
+            TextureSource::Image {
+                data_to_upload,
+                uploaded_texture,
+            } => data_to_upload.is_none() && uploaded_texture.is_some(),
+            TextureSource::Prepass { .. } => true,
+            TextureSource::Atlas {
+                regions_to_upload,
+                uploaded_texture,
+            } => regions_to_upload.is_empty() && uploaded_texture.is_some(),
+        }
+    }
+
+    /// Call the given closure once this texture has been uploaded, see
+    /// [`RcTexture::is_uploaded`].
+    ///
+    /// The closure is called while the frame which uploads the texture is
+    /// being prepared, so the texture is visible in that frame. If the
+    /// texture is already uploaded, then it is called right away.
+    pub fn on_uploaded(&self, callback: impl FnOnce() + 'static) {
+        if self.is_uploaded() {
+            callback();
+            return;
+        }
+
+        RefCell::borrow_mut(&self.inner)
+            .upload_callbacks
+            .push(UploadCallback(Box::new(callback)));
+    }
+
+    /// The number of bytes of data which are waiting to be uploaded.
+    pub(crate) fn pending_upload_bytes(&self) -> u64 {
+        let inner = RefCell::borrow(&self.inner);
+
+        match &inner.source {
+            TextureSource::Image { data_to_upload, .. } => data_to_upload
+                .as_ref()
+                .map(|data| data.len() as u64)
+                .unwrap_or(0),
+            TextureSource::Prepass { .. } => 0,
+            TextureSource::Atlas {
+                regions_to_upload, ..
+            } => regions_to_upload
+                .iter()
+                .map(|(_, image)| image.len() as u64)
+                .sum(),
+        }
+    }
+
+    /// Whether or not the texture can be drawn, which is once it has been
+    /// uploaded for the first time.
+    pub(crate) fn has_bind_group(&self) -> bool {
+        RefCell::borrow(&self.inner).bind_group.is_some()
+    }
+
     pub(crate) fn upload_if_needed(
         &self,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
         texture_bind_group_layout: &wgpu::BindGroupLayout,
+    ) {
+        self.write_pending_data(device, queue, texture_bind_group_layout);
+
+        if !self.is_uploaded() {
+            return;
+        }
+
+        // Don't hold on to the borrow while calling the callbacks, since they
+        // may want to access this texture.
+        let callbacks = std::mem::take(&mut RefCell::borrow_mut(&self.inner).upload_callbacks);
+        for UploadCallback(callback) in callbacks {
+            callback();
+        }
+    }
+
+    fn write_pending_data(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        texture_bind_group_layout: &wgpu::BindGroupLayout,
     ) {
         let mut inner = RefCell::borrow_mut(&self.inner);
 
-        let TextureInner { source, bind_group } = &mut *inner;
+        let TextureInner {
+            source, bind_group, ..
+        } = &mut *inner;
 
         match source {
             TextureSource::Image {
@@ -145,41 +372,10 @@ impl RcTexture {
                 };
 
                 if bind_group.is_none() {
-                    let dimensions = data_to_upload.dimensions();
-                    let texture_size = wgpu::Extent3d {
-                        width: dimensions.0,
-                        height: dimensions.1,
-                        depth_or_array_layers: 1,
-                    };
-
-                    let texture = device.create_texture(&wgpu::TextureDescriptor {
-                        // All textures are stored as 3D, we represent our 2D texture
-                        // by setting depth to 1.
-                        size: texture_size,
-                        mip_level_count: 1,
-                        sample_count: 1,
-                        dimension: wgpu::TextureDimension::D2,
-                        format: rootvg_core::color::SRGBA8_TEXTURE_FORMAT,
-                        // TEXTURE_BINDING tells wgpu that we want to use this texture in shaders
-                        // COPY_DST means that we want to copy data to this texture
-                        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-                        label: None,
-                        view_formats: &[],
-                    });
-
-                    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-
-                    let new_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-                        layout: texture_bind_group_layout,
-                        entries: &[wgpu::BindGroupEntry {
-                            binding: 0,
-                            resource: wgpu::BindingResource::TextureView(&view),
-                        }],
-                        label: None,
-                    });
+                    let (texture, new_bind_group) =
+                        create_texture(device, texture_bind_group_layout, self.size);
 
                     *bind_group = Some(new_bind_group);
-
                     *uploaded_texture = Some(texture);
                 };
 
@@ -224,10 +420,101 @@ impl RcTexture {
 
                 *bind_group = Some(new_bind_group);
             }
+            TextureSource::Atlas {
+                regions_to_upload,
+                uploaded_texture,
+            } => {
+                if bind_group.is_none() {
+                    let (texture, new_bind_group) =
+                        create_texture(device, texture_bind_group_layout, self.size);
+
+                    *bind_group = Some(new_bind_group);
+                    *uploaded_texture = Some(texture);
+                };
+
+                let uploaded_texture = uploaded_texture.as_ref().unwrap();
+
+                for (origin, image) in regions_to_upload.drain(..) {
+                    write_region(queue, uploaded_texture, origin, &image);
+                }
+            }
         }
     }
 }
 
+fn write_region(
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+    origin: PhysicalPointU32,
+    image: &RgbaImage,
+) {
+    let (width, height) = image.dimensions();
+
+    queue.write_texture(
+        wgpu::ImageCopyTexture {
+            texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d {
+                x: origin.x,
+                y: origin.y,
+                z: 0,
+            },
+            aspect: wgpu::TextureAspect::All,
+        },
+        image,
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(4 * width),
+            rows_per_image: Some(height),
+        },
+        wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    );
+}
+
+fn create_texture(
+    device: &wgpu::Device,
+    texture_bind_group_layout: &wgpu::BindGroupLayout,
+    size: PhysicalSizeU32,
+) -> (wgpu::Texture, wgpu::BindGroup) {
+    let texture_size = wgpu::Extent3d {
+        width: size.width,
+        height: size.height,
+        depth_or_array_layers: 1,
+    };
+
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        // All textures are stored as 3D, we represent our 2D texture
+        // by setting depth to 1.
+        size: texture_size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: rootvg_core::color::SRGBA8_TEXTURE_FORMAT,
+        // TEXTURE_BINDING tells wgpu that we want to use this texture in shaders
+        // COPY_DST means that we want to copy data to this texture
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        label: None,
+        view_formats: &[],
+    });
+
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout: texture_bind_group_layout,
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: wgpu::BindingResource::TextureView(&view),
+        }],
+        label: None,
+    });
+
+    (texture, bind_group)
+}
+
 impl Clone for RcTexture {
     fn clone(&self) -> Self {
         Self {
@@ -249,3 +536,26 @@ impl From<RgbaImage> for RcTexture {
         RcTexture::new(image)
     }
 }
+
+/// Textures are serialized as the opaque [`RcTexture::id`]. Deserializing
+/// requires a resolver for [`RcTexture`] to be active, see
+/// [`rootvg_core::serde_handle::with_resolver`].
+#[cfg(feature = "serde")]
+impl serde::Serialize for RcTexture {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.id())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for RcTexture {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let id = <u64 as serde::Deserialize>::deserialize(deserializer)?;
+
+        rootvg_core::serde_handle::resolve(id).ok_or_else(|| {
+            <D::Error as serde::de::Error>::custom(format!(
+                "could not resolve texture with ID {id}"
+            ))
+        })
+    }
+}
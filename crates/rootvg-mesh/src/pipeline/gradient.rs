@@ -1,16 +1,27 @@
 use std::ops::Range;
 
 use rootvg_core::{
-    buffer::Buffer,
-    math::{PhysicalSizeI32, ScaleFactor},
+    buffer::{Buffer, BufferAllocationError},
+    clip::{ClipBuffer, ClipUniforms, RoundedClip},
+    gradient::{GradientRampCache, PackedGradient, ShaderGradient, MAX_INLINE_STOPS},
+    math::{PhysicalPointI32, PhysicalSizeI32, ScaleFactor},
     pipeline::DefaultConstantUniforms,
 };
 use wgpu::PipelineCompilationOptions;
 
-use crate::{GradientMeshPrimitive, GradientVertex2D};
+use crate::GradientMeshPrimitive;
 
 use super::{InstanceUniforms, INITIAL_INDEX_COUNT, INITIAL_VERTEX_COUNT};
 
+/// A [`GradientVertex2D`](crate::GradientVertex2D) with its gradient
+/// converted into the format used by the shader.
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Zeroable, bytemuck::Pod)]
+struct GradientShaderVertex {
+    position: [f32; 2],
+    gradient: ShaderGradient,
+}
+
 struct Instance {
     range_in_vertex_buffer: Range<u32>,
     range_in_index_buffer: Range<u32>,
@@ -18,19 +29,26 @@ struct Instance {
 
 pub struct GradientMeshBatchBuffer {
     instances: Vec<Instance>,
-    vertex_buffer: Buffer<GradientVertex2D>,
+    vertex_buffer: Buffer<GradientShaderVertex>,
     index_buffer: Buffer<u32>,
     instance_uniforms_buffer: Buffer<InstanceUniforms>,
     instance_uniforms_bind_group: wgpu::BindGroup,
-    temp_vertex_buffer: Vec<GradientVertex2D>,
+    clip: ClipBuffer,
+    temp_vertex_buffer: Vec<GradientShaderVertex>,
     temp_index_buffer: Vec<u32>,
     temp_instance_uniforms_buffer: Vec<InstanceUniforms>,
 
     prev_primitives: Vec<GradientMeshPrimitive>,
+    /// The gradients in `prev_primitives` which are baked into ramps.
+    prev_ramp_gradients: Vec<PackedGradient>,
 }
 
 impl GradientMeshBatchBuffer {
-    pub fn new(device: &wgpu::Device, instance_uniforms_layout: &wgpu::BindGroupLayout) -> Self {
+    pub fn new(
+        device: &wgpu::Device,
+        instance_uniforms_layout: &wgpu::BindGroupLayout,
+        clip_layout: &wgpu::BindGroupLayout,
+    ) -> Self {
         let vertex_buffer = Buffer::new(
             device,
             "rootvg-mesh gradient vertex buffer",
@@ -64,10 +82,12 @@ impl GradientMeshBatchBuffer {
             index_buffer,
             instance_uniforms_buffer,
             instance_uniforms_bind_group,
+            clip: ClipBuffer::new(device, clip_layout),
             temp_vertex_buffer: Vec::new(),
             temp_index_buffer: Vec::new(),
             temp_instance_uniforms_buffer: Vec::new(),
             prev_primitives: Vec::new(),
+            prev_ramp_gradients: Vec::new(),
         }
     }
 
@@ -96,13 +116,18 @@ impl GradientMeshBatchBuffer {
         device: &wgpu::Device,
         queue: &wgpu::Queue,
         instance_uniforms_layout: &wgpu::BindGroupLayout,
-    ) {
+        ramps: &mut GradientRampCache,
+    ) -> Result<(), BufferAllocationError> {
         // Don't prepare if the list of primitives hasn't changed since the last
-        // preparation.
+        // preparation, but keep the ramps which are still in use from being
+        // evicted. They keep the same rows while they stay in the cache.
         if primitives == self.prev_primitives {
-            return;
+            for gradient in self.prev_ramp_gradients.iter() {
+                ramps.get(gradient);
+            }
+
+            return Ok(());
         }
-        self.prev_primitives = primitives.into();
 
         // TODO: Detect when multiple primitives share the same mesh and batch them
         // together into a separate draw call to reduce the amount of duplicated data
@@ -113,13 +138,24 @@ impl GradientMeshBatchBuffer {
         self.temp_index_buffer.clear();
         self.temp_vertex_buffer.clear();
         self.temp_instance_uniforms_buffer.clear();
+        self.prev_ramp_gradients.clear();
 
         for mesh in primitives.iter() {
             let vertex_buffer_start = self.temp_vertex_buffer.len() as u32;
             let index_buffer_start = self.temp_index_buffer.len() as u32;
 
-            self.temp_vertex_buffer
-                .extend_from_slice(&mesh.mesh.buffers.vertices);
+            for vertex in mesh.mesh.buffers.vertices.iter() {
+                if vertex.gradient.num_stops() > MAX_INLINE_STOPS
+                    && !self.prev_ramp_gradients.contains(&vertex.gradient)
+                {
+                    self.prev_ramp_gradients.push(vertex.gradient);
+                }
+
+                self.temp_vertex_buffer.push(GradientShaderVertex {
+                    position: vertex.position,
+                    gradient: vertex.gradient.to_shader_gradient(ramps),
+                });
+            }
             self.temp_index_buffer
                 .extend_from_slice(&mesh.mesh.buffers.indices);
 
@@ -132,19 +168,17 @@ impl GradientMeshBatchBuffer {
                 .push(InstanceUniforms::new(mesh.uniform));
         }
 
-        let _ = self
-            .vertex_buffer
-            .expand_to_fit_new_size(device, self.temp_vertex_buffer.len());
-        let _ = self
-            .index_buffer
-            .expand_to_fit_new_size(device, self.temp_index_buffer.len());
+        self.vertex_buffer
+            .expand_to_fit_new_size(device, self.temp_vertex_buffer.len())?;
+        self.index_buffer
+            .expand_to_fit_new_size(device, self.temp_index_buffer.len())?;
 
         let _ = self.vertex_buffer.write(queue, 0, &self.temp_vertex_buffer);
         let _ = self.index_buffer.write(queue, 0, &self.temp_index_buffer);
 
         if self
             .instance_uniforms_buffer
-            .expand_to_fit_new_size(device, self.instances.len())
+            .expand_to_fit_new_size(device, self.instances.len())?
         {
             self.instance_uniforms_bind_group = Self::bind_group(
                 device,
@@ -156,6 +190,11 @@ impl GradientMeshBatchBuffer {
         let _ = self
             .instance_uniforms_buffer
             .write(queue, 0, &self.temp_instance_uniforms_buffer);
+
+        // Only skip the next preparation once this one has succeeded.
+        self.prev_primitives = primitives.into();
+
+        Ok(())
     }
 }
 
@@ -165,9 +204,13 @@ pub struct GradientMeshPipeline {
     constants_buffer: wgpu::Buffer,
     constants_bind_group: wgpu::BindGroup,
     instance_uniforms_layout: wgpu::BindGroupLayout,
+    clip_layout: wgpu::BindGroupLayout,
+    ramp_layout: wgpu::BindGroupLayout,
+    ramps: GradientRampCache,
 
     screen_size: PhysicalSizeI32,
     scale_factor: ScaleFactor,
+    origin: PhysicalPointI32,
 }
 
 impl GradientMeshPipeline {
@@ -175,15 +218,23 @@ impl GradientMeshPipeline {
         device: &wgpu::Device,
         format: wgpu::TextureFormat,
         multisample: wgpu::MultisampleState,
+        depth_stencil: Option<wgpu::DepthStencilState>,
     ) -> Self {
         let (constants_layout, constants_buffer, constants_bind_group) =
             DefaultConstantUniforms::layout_buffer_and_bind_group(device);
 
         let instance_uniforms_layout = super::instance_uniforms_layout(device);
+        let clip_layout = ClipUniforms::layout(device);
+        let ramp_layout = GradientRampCache::layout(device);
 
         let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("rootvg-mesh gradient pipeline layout"),
-            bind_group_layouts: &[&constants_layout, &instance_uniforms_layout],
+            bind_group_layouts: &[
+                &constants_layout,
+                &instance_uniforms_layout,
+                &clip_layout,
+                &ramp_layout,
+            ],
             push_constant_ranges: &[],
         });
 
@@ -192,6 +243,8 @@ impl GradientMeshPipeline {
             source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(concat!(
                 include_str!("../shader/mesh.wgsl"),
                 "\n",
+                include_str!("../shader/clip.wgsl"),
+                "\n",
                 include_str!("../shader/gradient.wgsl"),
                 "\n",
                 include_str!("../shader/oklab.wgsl"),
@@ -205,17 +258,17 @@ impl GradientMeshPipeline {
                 module: &shader,
                 entry_point: "gradient_vs_main",
                 buffers: &[wgpu::VertexBufferLayout {
-                    array_stride: std::mem::size_of::<GradientVertex2D>() as u64,
+                    array_stride: std::mem::size_of::<GradientShaderVertex>() as u64,
                     step_mode: wgpu::VertexStepMode::Vertex,
                     attributes: &wgpu::vertex_attr_array!(
                         // Position
                         0 => Float32x2,
-                        // Colors 1-2
+                        // Colors 1-4
                         1 => Uint32x4,
-                        // Colors 3-4
+                        // Colors 5-8
                         2 => Uint32x4,
-                        // Offsets
-                        3 => Uint32x2,
+                        // Offsets and gradient kind
+                        3 => Uint32x4,
                         // Direction
                         4 => Float32x4
                     ),
@@ -233,7 +286,7 @@ impl GradientMeshPipeline {
                 front_face: wgpu::FrontFace::Cw,
                 ..Default::default()
             },
-            depth_stencil: None,
+            depth_stencil,
             multisample,
             multiview: None,
             cache: None,
@@ -244,13 +297,17 @@ impl GradientMeshPipeline {
             constants_buffer,
             constants_bind_group,
             instance_uniforms_layout,
+            clip_layout,
+            ramp_layout,
+            ramps: GradientRampCache::new(),
             screen_size: PhysicalSizeI32::default(),
             scale_factor: ScaleFactor::default(),
+            origin: PhysicalPointI32::default(),
         }
     }
 
     pub fn create_batch(&mut self, device: &wgpu::Device) -> GradientMeshBatchBuffer {
-        GradientMeshBatchBuffer::new(device, &self.instance_uniforms_layout)
+        GradientMeshBatchBuffer::new(device, &self.instance_uniforms_layout, &self.clip_layout)
     }
 
     pub fn start_preparations(
@@ -259,18 +316,26 @@ impl GradientMeshPipeline {
         queue: &wgpu::Queue,
         screen_size: PhysicalSizeI32,
         scale_factor: ScaleFactor,
+        origin: PhysicalPointI32,
     ) {
-        if self.screen_size == screen_size && self.scale_factor == scale_factor {
+        self.ramps.next_frame();
+
+        if self.screen_size == screen_size
+            && self.scale_factor == scale_factor
+            && self.origin == origin
+        {
             return;
         }
 
         self.screen_size = screen_size;
         self.scale_factor = scale_factor;
+        self.origin = origin;
 
-        DefaultConstantUniforms::prepare_buffer(
+        DefaultConstantUniforms::prepare_buffer_with_origin(
             &self.constants_buffer,
             screen_size,
             scale_factor,
+            origin,
             queue,
         );
     }
@@ -279,10 +344,23 @@ impl GradientMeshPipeline {
         &mut self,
         batch: &mut GradientMeshBatchBuffer,
         primitives: &[GradientMeshPrimitive],
+        clip: Option<RoundedClip>,
+        depth: f32,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
-    ) {
-        batch.prepare(primitives, device, queue, &self.instance_uniforms_layout);
+    ) -> Result<(), BufferAllocationError> {
+        batch.clip.prepare(clip, depth, self.scale_factor, queue);
+        batch.prepare(
+            primitives,
+            device,
+            queue,
+            &self.instance_uniforms_layout,
+            &mut self.ramps,
+        )?;
+
+        self.ramps.upload(device, queue, &self.ramp_layout);
+
+        Ok(())
     }
 
     pub fn render_batch<'pass>(
@@ -293,9 +371,15 @@ impl GradientMeshPipeline {
         if batch.instances.is_empty() {
             return;
         }
+        // The ramp texture is created when the first batch is prepared.
+        let Some(ramp_bind_group) = self.ramps.bind_group() else {
+            return;
+        };
 
         render_pass.set_pipeline(&self.pipeline);
         render_pass.set_bind_group(0, &self.constants_bind_group, &[]);
+        render_pass.set_bind_group(2, batch.clip.bind_group(), &[]);
+        render_pass.set_bind_group(3, ramp_bind_group, &[]);
 
         let vertex_end = batch.instances.last().unwrap().range_in_vertex_buffer.end;
         let index_end = batch.instances.last().unwrap().range_in_index_buffer.end;
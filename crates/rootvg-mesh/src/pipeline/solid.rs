@@ -1,8 +1,9 @@
 use std::ops::Range;
 
 use rootvg_core::{
-    buffer::Buffer,
-    math::{PhysicalSizeI32, ScaleFactor},
+    buffer::{Buffer, BufferAllocationError},
+    clip::{ClipBuffer, ClipUniforms, RoundedClip},
+    math::{PhysicalPointI32, PhysicalSizeI32, ScaleFactor},
     pipeline::DefaultConstantUniforms,
 };
 use wgpu::PipelineCompilationOptions;
@@ -22,6 +23,7 @@ pub struct SolidMeshBatchBuffer {
     index_buffer: Buffer<u32>,
     instance_uniforms_buffer: Buffer<InstanceUniforms>,
     instance_uniforms_bind_group: wgpu::BindGroup,
+    clip: ClipBuffer,
     temp_vertex_buffer: Vec<SolidVertex2D>,
     temp_index_buffer: Vec<u32>,
     temp_instance_uniforms_buffer: Vec<InstanceUniforms>,
@@ -30,7 +32,11 @@ pub struct SolidMeshBatchBuffer {
 }
 
 impl SolidMeshBatchBuffer {
-    pub fn new(device: &wgpu::Device, instance_uniforms_layout: &wgpu::BindGroupLayout) -> Self {
+    pub fn new(
+        device: &wgpu::Device,
+        instance_uniforms_layout: &wgpu::BindGroupLayout,
+        clip_layout: &wgpu::BindGroupLayout,
+    ) -> Self {
         let vertex_buffer = Buffer::new(
             device,
             "rootvg-mesh solid vertex buffer",
@@ -64,6 +70,7 @@ impl SolidMeshBatchBuffer {
             index_buffer,
             instance_uniforms_buffer,
             instance_uniforms_bind_group,
+            clip: ClipBuffer::new(device, clip_layout),
             temp_vertex_buffer: Vec::new(),
             temp_index_buffer: Vec::new(),
             temp_instance_uniforms_buffer: Vec::new(),
@@ -96,13 +103,12 @@ impl SolidMeshBatchBuffer {
         device: &wgpu::Device,
         queue: &wgpu::Queue,
         instance_uniforms_layout: &wgpu::BindGroupLayout,
-    ) {
+    ) -> Result<(), BufferAllocationError> {
         // Don't prepare if the list of primitives hasn't changed since the last
         // preparation.
         if primitives == self.prev_primitives {
-            return;
+            return Ok(());
         }
-        self.prev_primitives = primitives.into();
 
         // TODO: Detect when multiple primitives share the same mesh and batch them
         // together into a separate draw call to reduce the amount of duplicated data
@@ -132,19 +138,17 @@ impl SolidMeshBatchBuffer {
                 .push(InstanceUniforms::new(mesh.uniform));
         }
 
-        let _ = self
-            .vertex_buffer
-            .expand_to_fit_new_size(device, self.temp_vertex_buffer.len());
-        let _ = self
-            .index_buffer
-            .expand_to_fit_new_size(device, self.temp_index_buffer.len());
+        self.vertex_buffer
+            .expand_to_fit_new_size(device, self.temp_vertex_buffer.len())?;
+        self.index_buffer
+            .expand_to_fit_new_size(device, self.temp_index_buffer.len())?;
 
         let _ = self.vertex_buffer.write(queue, 0, &self.temp_vertex_buffer);
         let _ = self.index_buffer.write(queue, 0, &self.temp_index_buffer);
 
         if self
             .instance_uniforms_buffer
-            .expand_to_fit_new_size(device, self.instances.len())
+            .expand_to_fit_new_size(device, self.instances.len())?
         {
             self.instance_uniforms_bind_group = Self::bind_group(
                 device,
@@ -156,6 +160,11 @@ impl SolidMeshBatchBuffer {
         let _ = self
             .instance_uniforms_buffer
             .write(queue, 0, &self.temp_instance_uniforms_buffer);
+
+        // Only skip the next preparation once this one has succeeded.
+        self.prev_primitives = primitives.into();
+
+        Ok(())
     }
 }
 
@@ -165,9 +174,11 @@ pub struct SolidMeshPipeline {
     constants_buffer: wgpu::Buffer,
     constants_bind_group: wgpu::BindGroup,
     instance_uniforms_layout: wgpu::BindGroupLayout,
+    clip_layout: wgpu::BindGroupLayout,
 
     screen_size: PhysicalSizeI32,
     scale_factor: ScaleFactor,
+    origin: PhysicalPointI32,
 }
 
 impl SolidMeshPipeline {
@@ -175,15 +186,17 @@ impl SolidMeshPipeline {
         device: &wgpu::Device,
         format: wgpu::TextureFormat,
         multisample: wgpu::MultisampleState,
+        depth_stencil: Option<wgpu::DepthStencilState>,
     ) -> Self {
         let (constants_layout, constants_buffer, constants_bind_group) =
             DefaultConstantUniforms::layout_buffer_and_bind_group(device);
 
         let instance_uniforms_layout = super::instance_uniforms_layout(device);
+        let clip_layout = ClipUniforms::layout(device);
 
         let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("rootvg-mesh solid pipeline layout"),
-            bind_group_layouts: &[&constants_layout, &instance_uniforms_layout],
+            bind_group_layouts: &[&constants_layout, &instance_uniforms_layout, &clip_layout],
             push_constant_ranges: &[],
         });
 
@@ -192,6 +205,8 @@ impl SolidMeshPipeline {
             source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(concat!(
                 include_str!("../shader/mesh.wgsl"),
                 "\n",
+                include_str!("../shader/clip.wgsl"),
+                "\n",
                 include_str!("../shader/solid.wgsl"),
             ))),
         });
@@ -225,7 +240,7 @@ impl SolidMeshPipeline {
                 front_face: wgpu::FrontFace::Cw,
                 ..Default::default()
             },
-            depth_stencil: None,
+            depth_stencil,
             multisample,
             multiview: None,
             cache: None,
@@ -236,13 +251,15 @@ impl SolidMeshPipeline {
             constants_buffer,
             constants_bind_group,
             instance_uniforms_layout,
+            clip_layout,
             screen_size: PhysicalSizeI32::default(),
             scale_factor: ScaleFactor::default(),
+            origin: PhysicalPointI32::default(),
         }
     }
 
     pub fn create_batch(&mut self, device: &wgpu::Device) -> SolidMeshBatchBuffer {
-        SolidMeshBatchBuffer::new(device, &self.instance_uniforms_layout)
+        SolidMeshBatchBuffer::new(device, &self.instance_uniforms_layout, &self.clip_layout)
     }
 
     pub fn start_preparations(
@@ -251,18 +268,24 @@ impl SolidMeshPipeline {
         queue: &wgpu::Queue,
         screen_size: PhysicalSizeI32,
         scale_factor: ScaleFactor,
+        origin: PhysicalPointI32,
     ) {
-        if self.screen_size == screen_size && self.scale_factor == scale_factor {
+        if self.screen_size == screen_size
+            && self.scale_factor == scale_factor
+            && self.origin == origin
+        {
             return;
         }
 
         self.screen_size = screen_size;
         self.scale_factor = scale_factor;
+        self.origin = origin;
 
-        DefaultConstantUniforms::prepare_buffer(
+        DefaultConstantUniforms::prepare_buffer_with_origin(
             &self.constants_buffer,
             screen_size,
             scale_factor,
+            origin,
             queue,
         );
     }
@@ -271,10 +294,13 @@ impl SolidMeshPipeline {
         &mut self,
         batch: &mut SolidMeshBatchBuffer,
         primitives: &[SolidMeshPrimitive],
+        clip: Option<RoundedClip>,
+        depth: f32,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
-    ) {
-        batch.prepare(primitives, device, queue, &self.instance_uniforms_layout);
+    ) -> Result<(), BufferAllocationError> {
+        batch.clip.prepare(clip, depth, self.scale_factor, queue);
+        batch.prepare(primitives, device, queue, &self.instance_uniforms_layout)
     }
 
     pub fn render_batch<'pass>(
@@ -288,6 +314,7 @@ impl SolidMeshPipeline {
 
         render_pass.set_pipeline(&self.pipeline);
         render_pass.set_bind_group(0, &self.constants_bind_group, &[]);
+        render_pass.set_bind_group(2, batch.clip.bind_group(), &[]);
 
         let vertex_end = batch.instances.last().unwrap().range_in_vertex_buffer.end;
         let index_end = batch.instances.last().unwrap().range_in_index_buffer.end;
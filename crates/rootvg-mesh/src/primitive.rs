@@ -1,5 +1,5 @@
 //! Draw triangles!
-use rootvg_core::math::{Angle, Point, Transform, Vector};
+use rootvg_core::math::{Angle, Box2D, Point, Rect, Transform, Vector};
 
 mod solid;
 pub use solid::*;
@@ -11,6 +11,7 @@ pub use gradient::*;
 
 /// A set of vertices and indices representing a list of triangles.
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Indexed<T> {
     /// The vertices of the mesh
     pub vertices: Vec<T>,
@@ -38,6 +39,7 @@ impl<T> Indexed<T> {
 
 #[repr(C)]
 #[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MeshUniforms {
     /// A 2d transform represented by a column-major 3 by 3 matrix, compressed down
     /// to 3 by 2.
@@ -61,6 +63,21 @@ pub struct MeshUniforms {
     ///
     /// By default this is set to `0` (false).
     pub snap_to_nearest_pixel: u32,
+
+    /// A multiplier applied to the alpha of every vertex color.
+    ///
+    /// By default this is set to `1.0`.
+    pub alpha: f32,
+
+    /// Whether or not to dither the colors of a gradient mesh to hide the
+    /// banding of smooth gradients. This has no effect on solid meshes.
+    ///
+    /// The dither is less than half of an 8 bit step, so colors which are
+    /// exactly representable are left unchanged. Disabling it is useful for
+    /// comparing screenshots exactly.
+    ///
+    /// By default this is set to `1` (true).
+    pub dither: u32,
 }
 
 impl MeshUniforms {
@@ -76,7 +93,59 @@ impl MeshUniforms {
             transform,
             has_transform,
             snap_to_nearest_pixel: if snap_to_nearest_pixel { 1 } else { 0 },
+            alpha: 1.0,
+            dither: 1,
+        }
+    }
+}
+
+impl MeshUniforms {
+    /// The transform of the mesh, or `None` if it has no transform.
+    pub fn transform(&self) -> Option<Transform> {
+        (self.has_transform != 0).then(|| Transform::from_array(self.transform))
+    }
+
+    /// Set the transform of the mesh, replacing the current one.
+    pub fn set_transform(&mut self, transform: Transform) {
+        self.transform = transform.to_array();
+        self.has_transform = 1;
+    }
+
+    /// Apply the given transform after the current transform of the mesh.
+    pub fn then_transform(&mut self, transform: Transform) {
+        let transform = match self.transform() {
+            Some(current) => current.then(&transform),
+            None => transform,
+        };
+
+        self.set_transform(transform);
+    }
+
+    /// Returns the bounding box of the given rectangle in the local space of
+    /// the mesh after the transform and offset are applied.
+    ///
+    /// Like in the shader, the transform is applied first and then the
+    /// offset. The result is the bounding box of the transformed corners of
+    /// the rectangle, so for a rotated mesh it is only exact if the mesh is a
+    /// rectangle that fills `bounds`, and otherwise it may be larger than the
+    /// transformed vertices.
+    ///
+    /// If the mesh snaps its vertices to the nearest physical pixel, then the
+    /// bounding box is expanded by one point on each side.
+    pub fn transform_bounds(&self, bounds: Rect) -> Rect {
+        let mut bounds = if self.has_transform != 0 {
+            Transform::from_array(self.transform).outer_transformed_rect(&bounds)
+        } else {
+            bounds
+        };
+
+        bounds.origin += Vector::from(self.offset);
+
+        if self.snap_to_nearest_pixel != 0 {
+            bounds = bounds.inflate(1.0, 1.0);
         }
+
+        bounds
     }
 }
 
@@ -87,11 +156,14 @@ impl Default for MeshUniforms {
             transform: [0.0; 6],
             has_transform: 0,
             snap_to_nearest_pixel: 0,
+            alpha: 1.0,
+            dither: 1,
         }
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MeshPrimitive {
     Solid(SolidMeshPrimitive),
     #[cfg(feature = "gradient")]
@@ -108,24 +180,51 @@ impl MeshPrimitive {
     }
 
     pub fn set_rotation(&mut self, angle: Angle, rotation_origin: Point) {
-        let transform = Transform::translation(-rotation_origin.x, -rotation_origin.y)
-            .then_rotate(angle)
-            .then_translate(Vector::new(rotation_origin.x, rotation_origin.y));
+        self.set_transform(rotation_transform(angle, rotation_origin));
+    }
 
-        self.set_transform(transform);
+    /// Scale the mesh around the given anchor point, replacing its current
+    /// transform.
+    pub fn set_scale(&mut self, scale: Vector, anchor: Point) {
+        self.set_transform(scale_transform(scale, anchor));
     }
 
     pub fn set_transform(&mut self, transform: Transform) {
+        self.uniform_mut().set_transform(transform);
+    }
+
+    /// Rotate the mesh around the given origin after its current transform
+    /// is applied.
+    ///
+    /// The origin is in the space of the transformed mesh, before the offset
+    /// is applied.
+    pub fn then_rotate(&mut self, angle: Angle, rotation_origin: Point) {
+        self.then_transform(rotation_transform(angle, rotation_origin));
+    }
+
+    /// Scale the mesh around the given anchor point after its current
+    /// transform is applied.
+    ///
+    /// The anchor is in the space of the transformed mesh, before the offset
+    /// is applied.
+    pub fn then_scale(&mut self, scale: Vector, anchor: Point) {
+        self.then_transform(scale_transform(scale, anchor));
+    }
+
+    /// Apply the given transform after the current transform of the mesh.
+    ///
+    /// Unlike [`MeshPrimitive::set_transform`], this keeps any rotation or
+    /// scale which was set before. The offset (see
+    /// [`MeshPrimitive::set_offset`]) is still applied last.
+    pub fn then_transform(&mut self, transform: Transform) {
+        self.uniform_mut().then_transform(transform);
+    }
+
+    fn uniform_mut(&mut self) -> &mut MeshUniforms {
         match self {
-            MeshPrimitive::Solid(mesh) => {
-                mesh.uniform.transform = transform.to_array();
-                mesh.uniform.has_transform = 1;
-            }
+            MeshPrimitive::Solid(mesh) => &mut mesh.uniform,
             #[cfg(feature = "gradient")]
-            MeshPrimitive::Gradient(mesh) => {
-                mesh.uniform.transform = transform.to_array();
-                mesh.uniform.has_transform = 1;
-            }
+            MeshPrimitive::Gradient(mesh) => &mut mesh.uniform,
         }
     }
 
@@ -136,4 +235,236 @@ impl MeshPrimitive {
             MeshPrimitive::Gradient(mesh) => mesh.snap_to_nearest_pixel(snap),
         }
     }
+
+    /// The bounding box of the mesh in logical points after its transform
+    /// and offset are applied, or `None` if the mesh has no vertices. See
+    /// [`MeshUniforms::transform_bounds`].
+    pub fn bounds(&self) -> Option<Rect> {
+        match self {
+            MeshPrimitive::Solid(mesh) => mesh.bounds(),
+            #[cfg(feature = "gradient")]
+            MeshPrimitive::Gradient(mesh) => mesh.bounds(),
+        }
+    }
+}
+
+/// A transform which rotates around the given origin.
+fn rotation_transform(angle: Angle, rotation_origin: Point) -> Transform {
+    Transform::translation(-rotation_origin.x, -rotation_origin.y)
+        .then_rotate(angle)
+        .then_translate(Vector::new(rotation_origin.x, rotation_origin.y))
+}
+
+/// A transform which scales around the given anchor point.
+fn scale_transform(scale: Vector, anchor: Point) -> Transform {
+    Transform::translation(-anchor.x, -anchor.y)
+        .then_scale(scale.x, scale.y)
+        .then_translate(Vector::new(anchor.x, anchor.y))
+}
+
+/// Returns the bounding box of the given vertex positions, or `None` if there
+/// are no vertices.
+fn vertex_bounds(positions: impl Iterator<Item = [f32; 2]>) -> Option<Rect> {
+    let mut positions = positions.map(Point::from);
+    let first = positions.next()?;
+
+    let bounds = positions.fold(Box2D::new(first, first), |b, p| {
+        Box2D::new(b.min.min(p), b.max.max(p))
+    });
+
+    Some(bounds.to_rect())
+}
+
+#[cfg(test)]
+mod tests {
+    use rootvg_core::color::RGBA8;
+    use rootvg_core::math::Size;
+
+    use super::*;
+
+    /// A mesh covering the rectangle from (10, 20) to (40, 60).
+    fn rect_mesh() -> MeshPrimitive {
+        MeshPrimitive::Solid(SolidMeshPrimitive::from_rect(
+            Rect::new(Point::new(10.0, 20.0), Size::new(30.0, 40.0)),
+            RGBA8::new(255, 255, 255, 255),
+        ))
+    }
+
+    /// The positions of the vertices in logical points, transformed the same
+    /// way as in the vertex shader.
+    fn vertices(mesh: &MeshPrimitive) -> Vec<Point> {
+        let mesh = match mesh {
+            MeshPrimitive::Solid(mesh) => mesh,
+            #[cfg(feature = "gradient")]
+            MeshPrimitive::Gradient(_) => unreachable!(),
+        };
+
+        mesh.mesh
+            .buffers
+            .vertices
+            .iter()
+            .map(|v| {
+                let position = Point::from(v.position);
+                let position = match mesh.uniform.transform() {
+                    Some(transform) => transform.transform_point(position),
+                    None => position,
+                };
+
+                position + Vector::from(mesh.uniform.offset)
+            })
+            .collect()
+    }
+
+    fn assert_rect(a: Rect, b: Rect) {
+        assert!(
+            (a.min_x() - b.min_x()).abs() < 1e-3
+                && (a.min_y() - b.min_y()).abs() < 1e-3
+                && (a.max_x() - b.max_x()).abs() < 1e-3
+                && (a.max_y() - b.max_y()).abs() < 1e-3,
+            "{a:?} != {b:?}"
+        );
+    }
+
+    /// Asserts that the bounds of the mesh are the given rectangle, and that
+    /// its vertices span the same rectangle.
+    fn assert_bounds(mesh: &MeshPrimitive, min: Point, max: Point) {
+        let expected = Box2D::new(min, max).to_rect();
+
+        assert_rect(mesh.bounds().unwrap(), expected);
+        assert_rect(
+            vertex_bounds(vertices(mesh).into_iter().map(Into::into)).unwrap(),
+            expected,
+        );
+    }
+
+    #[test]
+    fn offset_moves_vertices_and_bounds() {
+        let mut mesh = rect_mesh();
+        mesh.set_offset(Vector::new(5.0, -10.0));
+
+        assert_bounds(&mesh, Point::new(15.0, 10.0), Point::new(45.0, 50.0));
+    }
+
+    #[test]
+    fn transform_is_applied_before_the_offset() {
+        let mut mesh = rect_mesh();
+        mesh.set_rotation(Angle::degrees(90.0), Point::new(10.0, 20.0));
+        mesh.set_offset(Vector::new(5.0, 5.0));
+
+        assert_bounds(&mesh, Point::new(-25.0, 25.0), Point::new(15.0, 55.0));
+    }
+
+    #[test]
+    fn snapped_bounds_are_inflated_by_one_point() {
+        let mut mesh = rect_mesh();
+        mesh.snap_to_nearest_pixel(true);
+
+        assert_rect(
+            mesh.bounds().unwrap(),
+            Box2D::new(Point::new(9.0, 19.0), Point::new(41.0, 61.0)).to_rect(),
+        );
+    }
+
+    #[test]
+    fn empty_mesh_has_no_bounds() {
+        let mesh = MeshPrimitive::Solid(SolidMeshPrimitive::new(&Default::default()));
+
+        assert_eq!(mesh.bounds(), None);
+    }
+
+    #[test]
+    fn set_scale_scales_around_the_anchor() {
+        let mut mesh = rect_mesh();
+        mesh.set_rotation(Angle::degrees(45.0), Point::new(0.0, 0.0));
+        mesh.set_scale(Vector::new(2.0, 0.5), Point::new(10.0, 20.0));
+
+        // The rotation is replaced.
+        assert_bounds(&mesh, Point::new(10.0, 20.0), Point::new(70.0, 40.0));
+    }
+
+    #[test]
+    fn then_rotate_applies_after_the_current_transform() {
+        let anchor = Point::new(10.0, 20.0);
+
+        let mut mesh = rect_mesh();
+        mesh.set_scale(Vector::new(2.0, 1.0), anchor);
+        mesh.then_rotate(Angle::degrees(90.0), anchor);
+        assert_bounds(&mesh, Point::new(-30.0, 20.0), Point::new(10.0, 80.0));
+
+        // The other order stretches the rotated mesh horizontally instead.
+        let mut mesh = rect_mesh();
+        mesh.set_rotation(Angle::degrees(90.0), anchor);
+        mesh.then_scale(Vector::new(2.0, 1.0), anchor);
+        assert_bounds(&mesh, Point::new(-70.0, 20.0), Point::new(10.0, 50.0));
+    }
+
+    #[test]
+    fn then_transform_without_a_transform_sets_it() {
+        let mut mesh = rect_mesh();
+        mesh.then_transform(Transform::translation(1.0, 2.0));
+
+        assert_bounds(&mesh, Point::new(11.0, 22.0), Point::new(41.0, 62.0));
+    }
+
+    #[test]
+    fn offset_is_applied_after_composed_transforms() {
+        let mut mesh = rect_mesh();
+        mesh.set_offset(Vector::new(100.0, 0.0));
+        mesh.then_scale(Vector::new(2.0, 2.0), Point::new(10.0, 20.0));
+
+        assert_bounds(&mesh, Point::new(110.0, 20.0), Point::new(170.0, 100.0));
+    }
+
+    /// Asserts that the mesh survives a round trip through JSON. Meshes are
+    /// serialized by value, so the deserialized mesh is a new allocation.
+    #[cfg(feature = "serde")]
+    fn assert_serde_round_trip(mesh: &MeshPrimitive) {
+        let json = serde_json::to_string(mesh).unwrap();
+        let deserialized: MeshPrimitive = serde_json::from_str(&json).unwrap();
+
+        match (mesh, &deserialized) {
+            (MeshPrimitive::Solid(a), MeshPrimitive::Solid(b)) => {
+                assert_eq!(a.mesh, b.mesh);
+                assert_eq!(a.uniform, b.uniform);
+            }
+            #[cfg(feature = "gradient")]
+            (MeshPrimitive::Gradient(a), MeshPrimitive::Gradient(b)) => {
+                assert_eq!(a.mesh, b.mesh);
+                assert_eq!(a.uniform, b.uniform);
+            }
+            #[cfg(feature = "gradient")]
+            _ => panic!("{deserialized:?} != {mesh:?}"),
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn solid_mesh_serde_round_trip() {
+        let mut mesh = rect_mesh();
+        mesh.set_rotation(Angle::degrees(30.0), Point::new(10.0, 20.0));
+        mesh.set_offset(Vector::new(5.0, 5.0));
+        mesh.snap_to_nearest_pixel(true);
+
+        assert_serde_round_trip(&mesh);
+    }
+
+    #[cfg(all(feature = "serde", feature = "gradient"))]
+    #[test]
+    fn gradient_mesh_serde_round_trip() {
+        use rootvg_core::gradient::{Gradient, LinearGradient};
+
+        let gradient = LinearGradient::new(Angle::degrees(45.0))
+            .add_stop(0.0, RGBA8::new(255, 0, 0, 255))
+            .add_stop(1.0, RGBA8::new(0, 0, 255, 128));
+        let mut mesh = GradientMeshPrimitive::from_rect(
+            Rect::new(Point::new(10.0, 20.0), Size::new(30.0, 40.0)),
+            &Gradient::Linear(gradient),
+        );
+        mesh.set_dithering(false);
+
+        let mut mesh = MeshPrimitive::Gradient(mesh);
+        mesh.set_scale(Vector::new(2.0, 0.5), Point::new(10.0, 20.0));
+
+        assert_serde_round_trip(&mesh);
+    }
 }
@@ -5,10 +5,11 @@ use std::rc::Rc;
 use rootvg_core::gradient::{Gradient, PackedGradient};
 use rootvg_core::math::{Angle, Point, Rect, Transform, Vector};
 
-use super::{Indexed, MeshUniforms};
+use super::{rotation_transform, vertex_bounds, Indexed, MeshUniforms};
 
 /// A low-level primitive to render a mesh of triangles with a gradient.
 #[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GradientMesh {
     /// The vertices and indices of the mesh.
     pub buffers: Indexed<GradientVertex2D>,
@@ -18,11 +19,25 @@ impl GradientMesh {
     pub fn new() -> Self {
         Self::default()
     }
+
+    pub fn from_buffers(buffers: Indexed<GradientVertex2D>) -> Self {
+        Self { buffers }
+    }
+
+    /// The bounding box of the vertices in the local space of the mesh, or
+    /// `None` if the mesh has no vertices.
+    ///
+    /// This is computed from the vertices every time it is called, so it is
+    /// always up to date with `buffers`.
+    pub fn bounds(&self) -> Option<Rect> {
+        vertex_bounds(self.buffers.vertices.iter().map(|v| v.position))
+    }
 }
 
 /// A vertex which contains 2D position & packed gradient data.
 #[derive(Copy, Clone, Debug, PartialEq, Zeroable, Pod)]
 #[repr(C)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GradientVertex2D {
     /// The vertex position in 2D space.
     pub position: [f32; 2],
@@ -41,6 +56,7 @@ impl GradientVertex2D {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GradientMeshPrimitive {
     pub mesh: Rc<GradientMesh>,
     pub uniform: MeshUniforms,
@@ -70,11 +86,7 @@ impl GradientMeshPrimitive {
         rotation_origin: Point,
         offset: Vector,
     ) -> Self {
-        let transform = Transform::translation(-rotation_origin.x, -rotation_origin.y)
-            .then_rotate(angle)
-            .then_translate(Vector::new(rotation_origin.x, rotation_origin.y));
-
-        Self::new_with_transform(mesh, offset, transform)
+        Self::new_with_transform(mesh, offset, rotation_transform(angle, rotation_origin))
     }
 
     pub fn new_with_transform(
@@ -92,6 +104,20 @@ impl GradientMeshPrimitive {
         self.uniform.snap_to_nearest_pixel = if snap { 1 } else { 0 };
     }
 
+    /// Whether or not to dither the gradient, see [`MeshUniforms::dither`].
+    pub fn set_dithering(&mut self, dither: bool) {
+        self.uniform.dither = if dither { 1 } else { 0 };
+    }
+
+    /// The bounding box of the mesh in logical points after its transform
+    /// and offset are applied, or `None` if the mesh has no vertices. See
+    /// [`MeshUniforms::transform_bounds`].
+    pub fn bounds(&self) -> Option<Rect> {
+        self.mesh
+            .bounds()
+            .map(|bounds| self.uniform.transform_bounds(bounds))
+    }
+
     /// Contruct a non-rotated rectangle mesh with the given gradient.
     ///
     /// This is more performant than using the `lyon` drawing API.
@@ -106,29 +132,27 @@ impl GradientMeshPrimitive {
         let gradient: PackedGradient = gradient.into();
 
         GradientMeshPrimitive {
-            mesh: Rc::new(GradientMesh {
-                buffers: Indexed {
-                    vertices: vec![
-                        GradientVertex2D {
-                            position: [rect.min_x(), rect.min_y()],
-                            gradient,
-                        },
-                        GradientVertex2D {
-                            position: [rect.max_x(), rect.min_y()],
-                            gradient,
-                        },
-                        GradientVertex2D {
-                            position: [rect.max_x(), rect.max_y()],
-                            gradient,
-                        },
-                        GradientVertex2D {
-                            position: [rect.min_x(), rect.max_y()],
-                            gradient,
-                        },
-                    ],
-                    indices: vec![0, 1, 2, 0, 3, 2],
-                },
-            }),
+            mesh: Rc::new(GradientMesh::from_buffers(Indexed {
+                vertices: vec![
+                    GradientVertex2D {
+                        position: [rect.min_x(), rect.min_y()],
+                        gradient,
+                    },
+                    GradientVertex2D {
+                        position: [rect.max_x(), rect.min_y()],
+                        gradient,
+                    },
+                    GradientVertex2D {
+                        position: [rect.max_x(), rect.max_y()],
+                        gradient,
+                    },
+                    GradientVertex2D {
+                        position: [rect.min_x(), rect.max_y()],
+                        gradient,
+                    },
+                ],
+                indices: vec![0, 1, 2, 0, 3, 2],
+            })),
             uniform: MeshUniforms::default(),
         }
     }
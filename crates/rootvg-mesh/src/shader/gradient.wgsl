@@ -2,11 +2,16 @@
 // https://github.com/iced-rs/iced/blob/31d1d5fecbef50fa319cabd5d4194f1e4aaefa21/wgpu/src/shader/triangle/gradient.wgsl
 // Iced license (MIT): https://github.com/iced-rs/iced/blob/31d1d5fecbef50fa319cabd5d4194f1e4aaefa21/LICENSE
 
+// Gradients with too many color stops to pass inline are baked into the rows
+// of this texture.
+@group(3) @binding(0) var ramp_texture: texture_2d<f32>;
+@group(3) @binding(1) var ramp_sampler: sampler;
+
 struct GradientVertexInput {
     @location(0) position: vec2<f32>,
     @location(1) @interpolate(flat) colors_1: vec4<u32>,
     @location(2) @interpolate(flat) colors_2: vec4<u32>,
-    @location(3) @interpolate(flat) offsets: vec2<u32>,
+    @location(3) @interpolate(flat) offsets_and_kind: vec4<u32>,
     @location(4) direction: vec4<f32>,
 }
 
@@ -15,8 +20,9 @@ struct GradientVertexOutput {
     @location(0) raw_position: vec2<f32>,
     @location(1) @interpolate(flat) colors_1: vec4<u32>,
     @location(2) @interpolate(flat) colors_2: vec4<u32>,
-    @location(3) @interpolate(flat) offsets: vec2<u32>,
+    @location(3) @interpolate(flat) offsets: vec3<u32>,
     @location(4) direction: vec4<f32>,
+    @location(5) @interpolate(flat) kind: u32,
 }
 
 @vertex
@@ -44,99 +50,168 @@ fn gradient_vs_main(input: GradientVertexInput) -> GradientVertexOutput {
     }
 
     out.position = vec4<f32>(
-        (screen_pos.x * globals.screen_size_recip.x) - 1.0,
-        1.0 - (screen_pos.y * globals.screen_size_recip.y),
-        0.0,
+        ((screen_pos.x + globals.origin.x) * globals.screen_size_recip.x) - 1.0,
+        1.0 - ((screen_pos.y + globals.origin.y) * globals.screen_size_recip.y),
+        clip.depth,
         1.0
     );
 
     out.raw_position = input.position;
     out.colors_1 = input.colors_1;
     out.colors_2 = input.colors_2;
-    out.offsets = input.offsets;
+    out.offsets = input.offsets_and_kind.xyz;
     out.direction = input.direction;
+    out.kind = input.offsets_and_kind.w;
 
     return out;
 }
 
-/// Returns the current interpolated color with a max 8-stop gradient
+/// Returns the current interpolated color with a max 8-stop inline gradient,
+/// or the color sampled from the ramp of the gradient
 fn gradient(
     raw_position: vec2<f32>,
     direction: vec4<f32>,
-    colors: array<vec4<f32>, 4>,
-    offsets: vec4<f32>,
-    last_index: i32
+    kind: u32,
+    colors: array<u32, 8>,
+    offsets: array<f32, 8>
 ) -> vec4<f32> {
-    let start = direction.xy;
-    let end = direction.zw;
+    // The lower 8 bits hold the gradient kind, the next 8 bits hold the
+    // interpolation space, and the next 8 bits hold the number of stops.
+    let gradient_kind = kind & 0xffu;
+    let space = (kind >> 8u) & 0xffu;
+
+    var coord_offset: f32;
+    if (gradient_kind == 1u) {
+        // Radial gradient, `direction` is `[center.x, center.y, inner_radius, outer_radius]`.
+        let dist = distance(raw_position, direction.xy);
+        let range = direction.w - direction.z;
+
+        if (range <= 0.0) {
+            coord_offset = select(0.0, 1.0, dist >= direction.z);
+        } else {
+            coord_offset = clamp((dist - direction.z) / range, 0.0, 1.0);
+        }
+    } else if (gradient_kind == 2u) {
+        // Conic gradient, `direction` is `[center.x, center.y, start_angle, 0.0]`.
+        let v = raw_position - direction.xy;
+        let turns = (atan2(v.y, v.x) - direction.z) / 6.283185307179586;
+
+        // Wrap into `[0.0, 1.0)` so the sweep always starts at `start_angle`.
+        coord_offset = turns - floor(turns);
+    } else {
+        let start = direction.xy;
+        let end = direction.zw;
+
+        let v1 = end - start;
+        let v2 = raw_position - start;
+        let unit = normalize(v1);
+        coord_offset = dot(unit, v2) / length(v1);
+    }
 
-    let v1 = end - start;
-    let v2 = raw_position - start;
-    let unit = normalize(v1);
-    let coord_offset = dot(unit, v2) / length(v1);
+    // Bit 25 of the kind is set if the gradient is baked into a ramp, in
+    // which case the first color holds the row of the ramp.
+    if (kind & 0x2000000u) != 0u {
+        return sample_ramp(coord_offset, colors[0]);
+    }
+
+    let last_index = i32(stop_count(kind)) - 1;
+    if (last_index < 0) {
+        return vec4<f32>(0.0, 0.0, 0.0, 0.0);
+    }
 
     //need to store these as a var to use dynamic indexing in a loop
     //this is already added to wgsl spec but not in wgpu yet
     var colors_arr = colors;
     var offsets_arr = offsets;
 
+    // Only the colors of the segment containing the coordinate are decoded.
     var color: vec4<f32>;
-
-    let noise_granularity: f32 = 0.3/255.0;
-
-    for (var i: i32 = 0; i < last_index; i++) {
-        let curr_offset = offsets_arr[i];
-        let next_offset = offsets_arr[i+1];
-
-        if (coord_offset <= offsets_arr[0]) {
-            color = colors_arr[0];
-        }
-
-        if (curr_offset <= coord_offset && coord_offset <= next_offset) {
-            let from_ = colors_arr[i];
-            let to_ = colors_arr[i+1];
-            let factor = smoothstep(curr_offset, next_offset, coord_offset);
-
-            color = interpolate_color(from_, to_, factor);
-        }
-
-        if (coord_offset >= offsets_arr[last_index]) {
-            color = colors_arr[last_index];
+    if (coord_offset <= offsets_arr[0]) {
+        color = unpack_color(colors_arr[0], kind);
+    } else if (coord_offset >= offsets_arr[last_index]) {
+        color = unpack_color(colors_arr[last_index], kind);
+    } else {
+        for (var i: i32 = 0; i < last_index; i++) {
+            let curr_offset = offsets_arr[i];
+            let next_offset = offsets_arr[i+1];
+
+            if (coord_offset <= next_offset) {
+                let from_ = unpack_color(colors_arr[i], kind);
+                let to_ = unpack_color(colors_arr[i+1], kind);
+                let factor = smoothstep(curr_offset, next_offset, coord_offset);
+
+                color = interpolate_color(from_, to_, factor, space);
+                break;
+            }
         }
     }
 
-    return color + mix(-noise_granularity, noise_granularity, random(raw_position));
+    return color;
 }
 
 @fragment
 fn gradient_fs_main(input: GradientVertexOutput) -> @location(0) vec4<f32> {
-    let colors = array<vec4<f32>, 4>(
-        unpack_u32(input.colors_1.xy),
-        unpack_u32(input.colors_1.zw),
-        unpack_u32(input.colors_2.xy),
-        unpack_u32(input.colors_2.zw),
+    let colors = array<u32, 8>(
+        input.colors_1.x,
+        input.colors_1.y,
+        input.colors_1.z,
+        input.colors_1.w,
+        input.colors_2.x,
+        input.colors_2.y,
+        input.colors_2.z,
+        input.colors_2.w,
     );
 
-    let offsets: vec4<f32> = unpack_u32(input.offsets);
+    let offsets = unpack_offsets(input.offsets);
 
-    var last_index = 3;
-    for (var i: i32 = 0; i <= 3; i++) {
-        if (offsets[i] >= 1.0) {
-            last_index = i;
-            break;
-        }
+    var color = gradient(input.raw_position, input.direction, input.kind, colors, offsets);
+
+    if instance_uniforms.dither != 0 {
+        color = dither(color, input.position.xy, input.kind);
     }
 
-    return gradient(input.raw_position, input.direction, colors, offsets, last_index);
+    return vec4<f32>(color.rgb, color.a * instance_uniforms.alpha * clip_alpha(clip, input.position.xy - globals.origin));
+}
+
+// Samples the gradient ramp in the given row of the ramp texture. The colors
+// of the texture are in the same space as the unpacked inline colors.
+fn sample_ramp(coord_offset: f32, row: u32) -> vec4<f32> {
+    let height = f32(textureDimensions(ramp_texture).y);
+    let uv = vec2<f32>(clamp(coord_offset, 0.0, 1.0), (f32(row) + 0.5) / height);
+
+    return textureSampleLevel(ramp_texture, ramp_sampler, uv, 0.0);
 }
 
-fn unpack_u32(color: vec2<u32>) -> vec4<f32> {
-    let rg: vec2<f32> = unpack2x16float(color.x);
-    let ba: vec2<f32> = unpack2x16float(color.y);
+// The number of color stops is stored in bits 16-23 of the gradient kind.
+fn stop_count(kind: u32) -> u32 {
+    return min((kind >> 16u) & 0xffu, 8u);
+}
 
-    return vec4<f32>(rg.y, rg.x, ba.y, ba.x);
+// Unpacks the 8 offsets, which are stored as 12 bit unorms in a 96 bit
+// bitstream with the first offset in the lowest bits.
+fn unpack_offsets(packed: vec3<u32>) -> array<f32, 8> {
+    let scale = 1.0 / 4095.0;
+
+    return array<f32, 8>(
+        f32(extractBits(packed.x, 0u, 12u)) * scale,
+        f32(extractBits(packed.x, 12u, 12u)) * scale,
+        f32(extractBits(packed.x, 24u, 8u) | (extractBits(packed.y, 0u, 4u) << 8u)) * scale,
+        f32(extractBits(packed.y, 4u, 12u)) * scale,
+        f32(extractBits(packed.y, 16u, 12u)) * scale,
+        f32(extractBits(packed.y, 28u, 4u) | (extractBits(packed.z, 0u, 8u) << 4u)) * scale,
+        f32(extractBits(packed.z, 8u, 12u)) * scale,
+        f32(extractBits(packed.z, 20u, 12u)) * scale,
+    );
 }
 
-fn random(coords: vec2<f32>) -> f32 {
-    return fract(sin(dot(coords, vec2(12.9898,78.233))) * 43758.5453);
+// Unpacks an 8 bit RGBA color. Bit 24 of the gradient kind is set if the
+// colors are gamma encoded.
+fn unpack_color(packed: u32, kind: u32) -> vec4<f32> {
+    let color = unpack4x8unorm(packed);
+
+    if (kind & 0x1000000u) != 0u {
+        return vec4<f32>(srgb_to_linear(color.rgb), color.a);
+    }
+
+    return color;
 }
\ No newline at end of file
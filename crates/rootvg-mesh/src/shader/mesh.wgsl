@@ -1,6 +1,7 @@
 struct Globals {
     screen_size_recip: vec2<f32>,
     scale_factor: f32,
+    origin: vec2<f32>,
 }
 
 struct InstanceUniforms {
@@ -15,7 +16,10 @@ struct InstanceUniforms {
     offset: vec2<f32>,
     do_transform: u32,
     snap_to_nearest_pixel: u32,
+    alpha: f32,
+    dither: u32,
 }
 
 @group(0) @binding(0) var<uniform> globals: Globals;
-@group(1) @binding(0) var<uniform> instance_uniforms: InstanceUniforms;
\ No newline at end of file
+@group(1) @binding(0) var<uniform> instance_uniforms: InstanceUniforms;
+@group(2) @binding(0) var<uniform> clip: ClipUniforms;
\ No newline at end of file
@@ -35,9 +35,9 @@ fn solid_vs_main(input: SolidVertexInput) -> SolidVertexOutput {
     }
 
     out.position = vec4<f32>(
-        (screen_pos.x * globals.screen_size_recip.x) - 1.0,
-        1.0 - (screen_pos.y * globals.screen_size_recip.y),
-        0.0,
+        ((screen_pos.x + globals.origin.x) * globals.screen_size_recip.x) - 1.0,
+        1.0 - ((screen_pos.y + globals.origin.y) * globals.screen_size_recip.y),
+        clip.depth,
         1.0
     );
 
@@ -46,5 +46,5 @@ fn solid_vs_main(input: SolidVertexInput) -> SolidVertexOutput {
 
 @fragment
 fn solid_fs_main(input: SolidVertexOutput) -> @location(0) vec4<f32> {
-    return input.color;
+    return vec4<f32>(input.color.rgb, input.color.a * instance_uniforms.alpha * clip_alpha(clip, input.position.xy - globals.origin));
 }
\ No newline at end of file
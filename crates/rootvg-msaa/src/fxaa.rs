@@ -0,0 +1,216 @@
+use rootvg_core::math::PhysicalSizeI32;
+use wgpu::PipelineCompilationOptions;
+
+/// A post-process pass which applies FXAA while compositing an intermediate
+/// texture onto the render target.
+#[derive(Debug)]
+pub struct FxaaPipeline {
+    format: wgpu::TextureFormat,
+    pipeline: wgpu::RenderPipeline,
+    constants: wgpu::BindGroup,
+    texture_layout: wgpu::BindGroupLayout,
+    target: Option<Target>,
+}
+
+impl FxaaPipeline {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        // Linear filtering is needed to blend the pixels along an edge.
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("rootvg-fxaa sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let constant_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("rootvg-fxaa uniforms layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            }],
+        });
+
+        let constant_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("rootvg-fxaa uniforms bind group"),
+            layout: &constant_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::Sampler(&sampler),
+            }],
+        });
+
+        let texture_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("rootvg-fxaa texture layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            }],
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("rootvg-fxaa pipeline layout"),
+            push_constant_ranges: &[],
+            bind_group_layouts: &[&constant_layout, &texture_layout],
+        });
+
+        // Texels of sRGB textures are converted to linear space when sampled.
+        let shader_src = format!(
+            "const LINEAR_TEXELS: bool = {};\n{}",
+            format.is_srgb(),
+            include_str!("shader/fxaa.wgsl")
+        );
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("rootvg-fxaa shader"),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Owned(shader_src)),
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("rootvg-fxaa pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+                compilation_options: PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                front_face: wgpu::FrontFace::Cw,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            format,
+            pipeline,
+            constants: constant_bind_group,
+            texture_layout,
+            target: None,
+        }
+    }
+
+    /// The intermediate texture that the canvas should be rendered into,
+    /// creating a new one if the size has changed.
+    pub fn target(&mut self, device: &wgpu::Device, size: PhysicalSizeI32) -> &wgpu::TextureView {
+        if self.target.as_ref().map(|t| t.size) != Some(size) {
+            self.target = Some(Target::new(device, self.format, &self.texture_layout, size));
+        }
+
+        &self.target.as_ref().unwrap().view
+    }
+
+    /// Apply FXAA to the intermediate texture and composite it onto the
+    /// given target.
+    pub fn render_to_target(
+        &self,
+        target: &wgpu::TextureView,
+        clear_color: Option<wgpu::Color>,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        let load = if let Some(color) = clear_color {
+            wgpu::LoadOp::Clear(color)
+        } else {
+            wgpu::LoadOp::Load
+        };
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("rootvg-fxaa render pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.constants, &[]);
+        render_pass.set_bind_group(1, &self.target.as_ref().unwrap().bind_group, &[]);
+        render_pass.draw(0..6, 0..1);
+    }
+}
+
+#[derive(Debug)]
+struct Target {
+    view: wgpu::TextureView,
+    bind_group: wgpu::BindGroup,
+    size: PhysicalSizeI32,
+}
+
+impl Target {
+    fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        texture_layout: &wgpu::BindGroupLayout,
+        size: PhysicalSizeI32,
+    ) -> Self {
+        assert!(size.width > 0);
+        assert!(size.height > 0);
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("rootvg-fxaa intermediate texture"),
+            size: wgpu::Extent3d {
+                width: size.width as u32,
+                height: size.height as u32,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("rootvg-fxaa texture bind group"),
+            layout: texture_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&view),
+            }],
+        });
+
+        Self {
+            view,
+            bind_group,
+            size,
+        }
+    }
+}
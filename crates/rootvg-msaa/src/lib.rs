@@ -1,5 +1,7 @@
+mod fxaa;
 mod pipeline;
 
+pub use fxaa::FxaaPipeline;
 pub use pipeline::MsaaPipeline;
 
 /// An antialiasing strategy.
@@ -14,16 +16,67 @@ pub enum Antialiasing {
     MSAAx8,
     /// Multisample AA with 16 samples
     MSAAx16,
+    /// Fast approximate AA, a post-process pass which smooths the edges in
+    /// the rendered canvas. This is cheaper than MSAA on low-end GPUs, but
+    /// lower quality.
+    Fxaa,
 }
 
 impl Antialiasing {
     /// Returns the amount of samples of the [`Antialiasing`].
+    ///
+    /// This is `1` for [`Antialiasing::Fxaa`].
     pub fn sample_count(self) -> u32 {
         match self {
             Antialiasing::MSAAx2 => 2,
             Antialiasing::MSAAx4 => 4,
             Antialiasing::MSAAx8 => 8,
             Antialiasing::MSAAx16 => 16,
+            Antialiasing::Fxaa => 1,
         }
     }
+
+    /// Returns the next lower MSAA mode, or `None` if this is the lowest
+    /// mode.
+    pub fn lower(self) -> Option<Self> {
+        match self {
+            Antialiasing::MSAAx2 | Antialiasing::Fxaa => None,
+            Antialiasing::MSAAx4 => Some(Antialiasing::MSAAx2),
+            Antialiasing::MSAAx8 => Some(Antialiasing::MSAAx4),
+            Antialiasing::MSAAx16 => Some(Antialiasing::MSAAx8),
+        }
+    }
+
+    /// Returns `true` if a texture with the given format features can be
+    /// antialiased with this mode.
+    pub fn is_supported(self, format_feature_flags: wgpu::TextureFormatFeatureFlags) -> bool {
+        let flag = match self {
+            Antialiasing::MSAAx2 => wgpu::TextureFormatFeatureFlags::MULTISAMPLE_X2,
+            Antialiasing::MSAAx4 => wgpu::TextureFormatFeatureFlags::MULTISAMPLE_X4,
+            Antialiasing::MSAAx8 => wgpu::TextureFormatFeatureFlags::MULTISAMPLE_X8,
+            Antialiasing::MSAAx16 => wgpu::TextureFormatFeatureFlags::MULTISAMPLE_X16,
+            // Render targets can always be sampled.
+            Antialiasing::Fxaa => return true,
+        };
+
+        format_feature_flags.contains(flag)
+    }
+
+    /// Returns the largest antialiasing mode which is less than or equal to
+    /// `requested` and is supported by a texture with the given format
+    /// features, or `None` if no mode is supported.
+    pub fn largest_compatible(
+        requested: Option<Self>,
+        format_feature_flags: wgpu::TextureFormatFeatureFlags,
+    ) -> Option<Self> {
+        let mut aa = requested;
+        while let Some(mode) = aa {
+            if mode.is_supported(format_feature_flags) {
+                break;
+            }
+            aa = mode.lower();
+        }
+
+        aa
+    }
 }
@@ -0,0 +1,206 @@
+// An implementation of FXAA 3.11 by Timothy Lottes.
+//
+// `LINEAR_TEXELS` is prepended to this file by the pipeline.
+
+var<private> positions: array<vec2<f32>, 6> = array<vec2<f32>, 6>(
+    vec2<f32>(-1.0, 1.0),
+    vec2<f32>(-1.0, -1.0),
+    vec2<f32>(1.0, -1.0),
+    vec2<f32>(-1.0, 1.0),
+    vec2<f32>(1.0, 1.0),
+    vec2<f32>(1.0, -1.0)
+);
+
+var<private> uvs: array<vec2<f32>, 6> = array<vec2<f32>, 6>(
+    vec2<f32>(0.0, 0.0),
+    vec2<f32>(0.0, 1.0),
+    vec2<f32>(1.0, 1.0),
+    vec2<f32>(0.0, 0.0),
+    vec2<f32>(1.0, 0.0),
+    vec2<f32>(1.0, 1.0)
+);
+
+// The minimum difference in luma between a pixel and its neighbors for it to
+// be considered an edge. This leaves very dark regions untouched.
+const EDGE_THRESHOLD_MIN: f32 = 0.0312;
+// The minimum difference in luma relative to the brightest neighbor for a
+// pixel to be considered an edge. This leaves flat regions and soft gradients
+// untouched.
+const EDGE_THRESHOLD_MAX: f32 = 0.125;
+// The amount of subpixel antialiasing. This is lower than the usual value of
+// `0.75` so that thin features like text stems aren't blurred excessively.
+const SUBPIXEL_QUALITY: f32 = 0.5;
+
+const ITERATIONS: i32 = 12;
+var<private> QUALITY: array<f32, 12> = array<f32, 12>(
+    1.0, 1.0, 1.0, 1.0, 1.5, 2.0, 2.0, 2.0, 2.0, 4.0, 8.0, 8.0
+);
+
+@group(0) @binding(0) var u_sampler: sampler;
+@group(1) @binding(0) var u_texture: texture_2d<f32>;
+
+struct VertexInput {
+    @builtin(vertex_index) vertex_index: u32,
+}
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+@vertex
+fn vs_main(input: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.uv = uvs[input.vertex_index];
+    out.position = vec4<f32>(positions[input.vertex_index], 0.0, 1.0);
+
+    return out;
+}
+
+fn luma(color: vec4<f32>) -> f32 {
+    let l = dot(color.rgb, vec3<f32>(0.299, 0.587, 0.114));
+
+    // Edges are detected in (approximately) perceptual space.
+    if LINEAR_TEXELS {
+        return sqrt(l);
+    }
+    return l;
+}
+
+fn sample_at(uv: vec2<f32>) -> vec4<f32> {
+    return textureSampleLevel(u_texture, u_sampler, uv, 0.0);
+}
+
+fn luma_at(uv: vec2<f32>, texel: vec2<f32>, x: f32, y: f32) -> f32 {
+    return luma(sample_at(uv + vec2<f32>(x, y) * texel));
+}
+
+@fragment
+fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
+    let texel = 1.0 / vec2<f32>(textureDimensions(u_texture));
+    let uv = input.uv;
+
+    let color_center = sample_at(uv);
+    let luma_center = luma(color_center);
+
+    let luma_up = luma_at(uv, texel, 0.0, -1.0);
+    let luma_down = luma_at(uv, texel, 0.0, 1.0);
+    let luma_left = luma_at(uv, texel, -1.0, 0.0);
+    let luma_right = luma_at(uv, texel, 1.0, 0.0);
+
+    let luma_min = min(luma_center, min(min(luma_up, luma_down), min(luma_left, luma_right)));
+    let luma_max = max(luma_center, max(max(luma_up, luma_down), max(luma_left, luma_right)));
+    let luma_range = luma_max - luma_min;
+
+    if luma_range < max(EDGE_THRESHOLD_MIN, luma_max * EDGE_THRESHOLD_MAX) {
+        return color_center;
+    }
+
+    let luma_up_left = luma_at(uv, texel, -1.0, -1.0);
+    let luma_up_right = luma_at(uv, texel, 1.0, -1.0);
+    let luma_down_left = luma_at(uv, texel, -1.0, 1.0);
+    let luma_down_right = luma_at(uv, texel, 1.0, 1.0);
+
+    let luma_up_down = luma_up + luma_down;
+    let luma_left_right = luma_left + luma_right;
+    let luma_left_corners = luma_up_left + luma_down_left;
+    let luma_right_corners = luma_up_right + luma_down_right;
+    let luma_up_corners = luma_up_left + luma_up_right;
+    let luma_down_corners = luma_down_left + luma_down_right;
+
+    // Find the direction of the edge.
+    let edge_horizontal = abs(-2.0 * luma_left + luma_left_corners)
+        + abs(-2.0 * luma_center + luma_up_down) * 2.0
+        + abs(-2.0 * luma_right + luma_right_corners);
+    let edge_vertical = abs(-2.0 * luma_up + luma_up_corners)
+        + abs(-2.0 * luma_center + luma_left_right) * 2.0
+        + abs(-2.0 * luma_down + luma_down_corners);
+    let is_horizontal = edge_horizontal >= edge_vertical;
+
+    // Find which side of the pixel the edge is on. `luma1` is the neighbor in
+    // the negative direction and `luma2` is the one in the positive direction.
+    let luma1 = select(luma_left, luma_up, is_horizontal);
+    let luma2 = select(luma_right, luma_down, is_horizontal);
+    let gradient1 = luma1 - luma_center;
+    let gradient2 = luma2 - luma_center;
+    let is_1_steepest = abs(gradient1) >= abs(gradient2);
+    let gradient_scaled = 0.25 * max(abs(gradient1), abs(gradient2));
+
+    var step_length = select(texel.x, texel.y, is_horizontal);
+    var luma_local_average: f32;
+    if is_1_steepest {
+        step_length = -step_length;
+        luma_local_average = 0.5 * (luma1 + luma_center);
+    } else {
+        luma_local_average = 0.5 * (luma2 + luma_center);
+    }
+
+    // Move to the border between the pixel and its neighbor.
+    var current_uv = uv;
+    if is_horizontal {
+        current_uv.y += step_length * 0.5;
+    } else {
+        current_uv.x += step_length * 0.5;
+    }
+
+    // Search along the edge in both directions until the end of the edge is
+    // found.
+    let offset = select(vec2<f32>(0.0, texel.y), vec2<f32>(texel.x, 0.0), is_horizontal);
+    var uv1 = current_uv - offset;
+    var uv2 = current_uv + offset;
+    var luma_end1 = 0.0;
+    var luma_end2 = 0.0;
+    var reached1 = false;
+    var reached2 = false;
+    for (var i = 0; i < ITERATIONS; i++) {
+        if !reached1 {
+            luma_end1 = luma(sample_at(uv1)) - luma_local_average;
+            reached1 = abs(luma_end1) >= gradient_scaled;
+        }
+        if !reached2 {
+            luma_end2 = luma(sample_at(uv2)) - luma_local_average;
+            reached2 = abs(luma_end2) >= gradient_scaled;
+        }
+        if reached1 && reached2 {
+            break;
+        }
+
+        if !reached1 {
+            uv1 -= offset * QUALITY[i];
+        }
+        if !reached2 {
+            uv2 += offset * QUALITY[i];
+        }
+    }
+
+    let distance1 = select(uv.y - uv1.y, uv.x - uv1.x, is_horizontal);
+    let distance2 = select(uv2.y - uv.y, uv2.x - uv.x, is_horizontal);
+    let is_direction1 = distance1 < distance2;
+    let distance_final = min(distance1, distance2);
+    let edge_thickness = distance1 + distance2;
+    let pixel_offset = -distance_final / edge_thickness + 0.5;
+
+    // Only offset the pixel if the luma at the closest end of the edge varies
+    // in the same direction as the luma at the center.
+    let is_luma_center_smaller = luma_center < luma_local_average;
+    let correct_variation =
+        select(luma_end2 < 0.0, luma_end1 < 0.0, is_direction1) != is_luma_center_smaller;
+    var final_offset = select(0.0, pixel_offset, correct_variation);
+
+    // Subpixel antialiasing
+    let luma_average = (1.0 / 12.0) * (2.0 * (luma_up_down + luma_left_right)
+        + luma_left_corners + luma_right_corners);
+    let sub_pixel_offset1 = clamp(abs(luma_average - luma_center) / luma_range, 0.0, 1.0);
+    let sub_pixel_offset2 = (-2.0 * sub_pixel_offset1 + 3.0) * sub_pixel_offset1 * sub_pixel_offset1;
+    let sub_pixel_offset_final = sub_pixel_offset2 * sub_pixel_offset2 * SUBPIXEL_QUALITY;
+    final_offset = max(final_offset, sub_pixel_offset_final);
+
+    var final_uv = uv;
+    if is_horizontal {
+        final_uv.y += final_offset * step_length;
+    } else {
+        final_uv.x += final_offset * step_length;
+    }
+
+    return sample_at(final_uv);
+}
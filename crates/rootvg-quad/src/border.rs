@@ -12,10 +12,57 @@ pub struct Border {
     pub color: PackedSrgb,
 
     /// The width of the border in logical points.
-    pub width: f32,
+    pub width: BorderWidth,
 
     /// The radius of the border in logical points.
     pub radius: Radius,
+
+    /// The dash pattern of the border. If this is `None`, then the border
+    /// is solid.
+    pub dash: Option<BorderDash>,
+}
+
+/// The maximum number of segments in a [`BorderDash`] pattern.
+pub const MAX_DASH_SEGMENTS: usize = 4;
+
+/// The dash pattern of a [`Border`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BorderDash {
+    /// The alternating lengths of dashes and gaps in logical points, starting
+    /// with a dash. Unused segments are set to `0.0`.
+    pub segments: [f32; MAX_DASH_SEGMENTS],
+
+    /// The offset along the perimeter of the border at which the pattern
+    /// starts, in logical points.
+    ///
+    /// Incrementing this every frame animates the dashes around the quad.
+    pub offset: f32,
+}
+
+impl BorderDash {
+    /// Create a new dash pattern from a list of alternating dash and gap
+    /// lengths in logical points.
+    ///
+    /// If the number of segments is odd, then the segments are repeated to
+    /// form an even number of segments. Any segments past
+    /// [`MAX_DASH_SEGMENTS`] are ignored.
+    pub fn new(segments: &[f32], offset: f32) -> Self {
+        let mut packed = [0.0; MAX_DASH_SEGMENTS];
+
+        let repeats = if segments.len() % 2 == 1 { 2 } else { 1 };
+        for (dst, src) in packed
+            .iter_mut()
+            .zip(segments.iter().cycle().take(segments.len() * repeats))
+        {
+            *dst = src.max(0.0);
+        }
+
+        Self {
+            segments: packed,
+            offset,
+        }
+    }
 }
 
 /// The border radii in logical points
@@ -111,3 +158,75 @@ impl From<Radius> for [f32; 4] {
 pub const fn radius(val: f32) -> Radius {
     Radius::all_same(val)
 }
+
+/// The border widths of each edge in logical points
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BorderWidth {
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub left: f32,
+}
+
+impl BorderWidth {
+    pub const fn new(top: f32, right: f32, bottom: f32, left: f32) -> Self {
+        Self {
+            top,
+            right,
+            bottom,
+            left,
+        }
+    }
+
+    pub const fn all_same(val: f32) -> Self {
+        Self {
+            top: val,
+            right: val,
+            bottom: val,
+            left: val,
+        }
+    }
+
+    pub const ZERO: Self = Self {
+        top: 0.0,
+        right: 0.0,
+        bottom: 0.0,
+        left: 0.0,
+    };
+
+    /// Returns `true` if all edges have the same width.
+    pub fn is_uniform(&self) -> bool {
+        self.top == self.right && self.top == self.bottom && self.top == self.left
+    }
+}
+
+impl From<f32> for BorderWidth {
+    fn from(w: f32) -> Self {
+        Self::all_same(w)
+    }
+}
+
+impl From<u8> for BorderWidth {
+    fn from(w: u8) -> Self {
+        Self::all_same(f32::from(w))
+    }
+}
+
+impl From<[f32; 4]> for BorderWidth {
+    fn from(widths: [f32; 4]) -> Self {
+        Self {
+            top: widths[0],
+            right: widths[1],
+            bottom: widths[2],
+            left: widths[3],
+        }
+    }
+}
+
+impl From<BorderWidth> for [f32; 4] {
+    fn from(widths: BorderWidth) -> Self {
+        [widths.top, widths.right, widths.bottom, widths.left]
+    }
+}
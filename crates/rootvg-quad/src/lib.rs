@@ -1,9 +1,9 @@
 mod border;
 mod primitive;
-//mod shadow;
+mod shadow;
 
 pub mod pipeline;
 
 pub use border::*;
 pub use primitive::*;
-//pub use shadow::*;
+pub use shadow::*;
@@ -1,3 +1,4 @@
+pub mod line;
 pub mod solid;
 
 #[cfg(feature = "gradient")]
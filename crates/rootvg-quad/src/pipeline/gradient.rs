@@ -4,8 +4,11 @@
 // Iced MIT license: https://github.com/iced-rs/iced/blob/master/LICENSE
 
 use rootvg_core::{
-    buffer::Buffer,
-    math::{PhysicalSizeI32, ScaleFactor},
+    buffer::{Buffer, BufferAllocationError},
+    clip::{ClipBuffer, ClipUniforms, RoundedClip},
+    color::PackedSrgb,
+    gradient::{GradientRampCache, ShaderGradient},
+    math::{PhysicalPointI32, PhysicalSizeI32, ScaleFactor},
     pipeline::DefaultConstantUniforms,
 };
 use wgpu::PipelineCompilationOptions;
@@ -14,9 +17,47 @@ use super::INITIAL_INSTANCES;
 
 use crate::GradientQuadPrimitive;
 
+/// A [`GradientQuadPrimitive`] with its gradients converted into the format
+/// used by the shader.
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Zeroable, bytemuck::Pod)]
+struct GradientQuadInstance {
+    gradient: ShaderGradient,
+    position: [f32; 2],
+    size: [f32; 2],
+    border_color: PackedSrgb,
+    border_radius: [f32; 4],
+    border_width: [f32; 4],
+    flags: u32,
+    border_dash: [f32; 4],
+    border_dash_offset: f32,
+    border_gradient: ShaderGradient,
+    transform: [f32; 4],
+}
+
+impl GradientQuadInstance {
+    fn new(primitive: &GradientQuadPrimitive, ramps: &mut GradientRampCache) -> Self {
+        Self {
+            gradient: primitive.gradient.to_shader_gradient(ramps),
+            position: primitive.position,
+            size: primitive.size,
+            border_color: primitive.border_color,
+            border_radius: primitive.border_radius,
+            border_width: primitive.border_width,
+            flags: primitive.flags,
+            border_dash: primitive.border_dash,
+            border_dash_offset: primitive.border_dash_offset,
+            border_gradient: primitive.border_gradient.to_shader_gradient(ramps),
+            transform: primitive.transform,
+        }
+    }
+}
+
 pub struct GradientQuadBatchBuffer {
-    buffer: Buffer<GradientQuadPrimitive>,
+    buffer: Buffer<GradientQuadInstance>,
+    clip: ClipBuffer,
     num_primitives: usize,
+    temp_instances: Vec<GradientQuadInstance>,
 }
 
 pub struct GradientQuadPipeline {
@@ -24,9 +65,13 @@ pub struct GradientQuadPipeline {
 
     constants_buffer: wgpu::Buffer,
     constants_bind_group: wgpu::BindGroup,
+    clip_layout: wgpu::BindGroupLayout,
+    ramp_layout: wgpu::BindGroupLayout,
+    ramps: GradientRampCache,
 
     screen_size: PhysicalSizeI32,
     scale_factor: ScaleFactor,
+    origin: PhysicalPointI32,
 }
 
 impl GradientQuadPipeline {
@@ -34,14 +79,18 @@ impl GradientQuadPipeline {
         device: &wgpu::Device,
         format: wgpu::TextureFormat,
         multisample: wgpu::MultisampleState,
+        depth_stencil: Option<wgpu::DepthStencilState>,
     ) -> Self {
         let (constants_layout, constants_buffer, constants_bind_group) =
             DefaultConstantUniforms::layout_buffer_and_bind_group(device);
 
+        let clip_layout = ClipUniforms::layout(device);
+        let ramp_layout = GradientRampCache::layout(device);
+
         let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("rootvg-quad gradient pipeline layout"),
             push_constant_ranges: &[],
-            bind_group_layouts: &[&constants_layout],
+            bind_group_layouts: &[&constants_layout, &clip_layout, &ramp_layout],
         });
 
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
@@ -49,6 +98,8 @@ impl GradientQuadPipeline {
             source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(concat!(
                 include_str!("../shader/quad.wgsl"),
                 "\n",
+                include_str!("../shader/clip.wgsl"),
+                "\n",
                 include_str!("../shader/gradient.wgsl"),
                 "\n",
                 include_str!("../shader/oklab.wgsl")
@@ -62,29 +113,42 @@ impl GradientQuadPipeline {
                 module: &shader,
                 entry_point: "gradient_vs_main",
                 buffers: &[wgpu::VertexBufferLayout {
-                    array_stride: std::mem::size_of::<GradientQuadPrimitive>() as u64,
+                    array_stride: std::mem::size_of::<GradientQuadInstance>() as u64,
                     step_mode: wgpu::VertexStepMode::Instance,
                     attributes: &wgpu::vertex_attr_array!(
-                        // Colors 1-2
+                        // Colors 1-4
                         0 => Uint32x4,
-                        // Colors 3-4
+                        // Colors 5-8
                         1 => Uint32x4,
-                        // Offsets 1-4
-                        2 => Uint32x2,
+                        // Offsets 1-8 and gradient kind
+                        2 => Uint32x4,
                         // Direction
                         3 => Float32x4,
-                        // Position
-                        4 => Float32x2,
-                        // Size
-                        5 => Float32x2,
+                        // Position and size (read as a single attribute to
+                        // stay within the limit of 16 vertex attributes)
+                        4 => Float32x4,
                         // Border color
-                        6 => Float32x4,
+                        5 => Float32x4,
                         // Border radius
-                        7 => Float32x4,
+                        6 => Float32x4,
                         // Border width
-                        8 => Float32,
+                        7 => Float32x4,
                         // Flags
-                        9 => Uint32,
+                        8 => Uint32,
+                        // Border dash
+                        9 => Float32x4,
+                        // Border dash offset
+                        10 => Float32,
+                        // Border gradient colors 1-4
+                        11 => Uint32x4,
+                        // Border gradient colors 5-8
+                        12 => Uint32x4,
+                        // Border gradient offsets 1-8 and gradient kind
+                        13 => Uint32x4,
+                        // Border gradient direction
+                        14 => Float32x4,
+                        // Transform
+                        15 => Float32x4,
                     ),
                 }],
                 compilation_options: PipelineCompilationOptions::default(),
@@ -100,7 +164,7 @@ impl GradientQuadPipeline {
                 front_face: wgpu::FrontFace::Cw,
                 ..Default::default()
             },
-            depth_stencil: None,
+            depth_stencil,
             multisample,
             multiview: None,
             cache: None,
@@ -109,9 +173,13 @@ impl GradientQuadPipeline {
         Self {
             constants_buffer,
             constants_bind_group,
+            clip_layout,
+            ramp_layout,
+            ramps: GradientRampCache::new(),
             pipeline,
             screen_size: PhysicalSizeI32::default(),
             scale_factor: ScaleFactor::default(),
+            origin: PhysicalPointI32::default(),
         }
     }
 
@@ -123,7 +191,9 @@ impl GradientQuadPipeline {
                 INITIAL_INSTANCES,
                 wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
             ),
+            clip: ClipBuffer::new(device, &self.clip_layout),
             num_primitives: 0,
+            temp_instances: Vec::new(),
         }
     }
 
@@ -133,18 +203,26 @@ impl GradientQuadPipeline {
         queue: &wgpu::Queue,
         screen_size: PhysicalSizeI32,
         scale_factor: ScaleFactor,
+        origin: PhysicalPointI32,
     ) {
-        if self.screen_size == screen_size && self.scale_factor == scale_factor {
+        self.ramps.next_frame();
+
+        if self.screen_size == screen_size
+            && self.scale_factor == scale_factor
+            && self.origin == origin
+        {
             return;
         }
 
         self.screen_size = screen_size;
         self.scale_factor = scale_factor;
+        self.origin = origin;
 
-        DefaultConstantUniforms::prepare_buffer(
+        DefaultConstantUniforms::prepare_buffer_with_origin(
             &self.constants_buffer,
             screen_size,
             scale_factor,
+            origin,
             queue,
         );
     }
@@ -153,15 +231,29 @@ impl GradientQuadPipeline {
         &mut self,
         batch: &mut GradientQuadBatchBuffer,
         primitives: &[GradientQuadPrimitive],
+        clip: Option<RoundedClip>,
+        depth: f32,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
-    ) {
-        let _ = batch
+    ) -> Result<(), BufferAllocationError> {
+        batch.clip.prepare(clip, depth, self.scale_factor, queue);
+
+        batch.temp_instances.clear();
+        batch.temp_instances.extend(
+            primitives
+                .iter()
+                .map(|p| GradientQuadInstance::new(p, &mut self.ramps)),
+        );
+        self.ramps.upload(device, queue, &self.ramp_layout);
+
+        batch
             .buffer
-            .expand_to_fit_new_size(device, primitives.len());
-        let _ = batch.buffer.write(queue, 0, primitives);
+            .expand_to_fit_new_size(device, primitives.len())?;
+        let _ = batch.buffer.write(queue, 0, &batch.temp_instances);
 
         batch.num_primitives = primitives.len();
+
+        Ok(())
     }
 
     pub fn render_batch<'pass>(
@@ -172,9 +264,15 @@ impl GradientQuadPipeline {
         if batch.num_primitives == 0 {
             return;
         }
+        // The ramp texture is created when the first batch is prepared.
+        let Some(ramp_bind_group) = self.ramps.bind_group() else {
+            return;
+        };
 
         render_pass.set_pipeline(&self.pipeline);
         render_pass.set_bind_group(0, &self.constants_bind_group, &[]);
+        render_pass.set_bind_group(1, batch.clip.bind_group(), &[]);
+        render_pass.set_bind_group(2, ramp_bind_group, &[]);
 
         render_pass.set_vertex_buffer(0, batch.buffer.slice(0..batch.num_primitives));
 
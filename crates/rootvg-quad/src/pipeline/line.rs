@@ -0,0 +1,194 @@
+use rootvg_core::{
+    buffer::{Buffer, BufferAllocationError},
+    clip::{ClipBuffer, ClipUniforms, RoundedClip},
+    math::{PhysicalPointI32, PhysicalSizeI32, ScaleFactor},
+    pipeline::DefaultConstantUniforms,
+};
+use wgpu::PipelineCompilationOptions;
+
+use super::INITIAL_INSTANCES;
+
+use crate::LinePrimitive;
+
+pub struct LineBatchBuffer {
+    buffer: Buffer<LinePrimitive>,
+    clip: ClipBuffer,
+    num_primitives: usize,
+}
+
+#[derive(Debug)]
+pub struct LinePipeline {
+    pipeline: wgpu::RenderPipeline,
+
+    constants_buffer: wgpu::Buffer,
+    constants_bind_group: wgpu::BindGroup,
+    clip_layout: wgpu::BindGroupLayout,
+
+    screen_size: PhysicalSizeI32,
+    scale_factor: ScaleFactor,
+    origin: PhysicalPointI32,
+}
+
+impl LinePipeline {
+    pub fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        multisample: wgpu::MultisampleState,
+        depth_stencil: Option<wgpu::DepthStencilState>,
+    ) -> Self {
+        let (constants_layout, constants_buffer, constants_bind_group) =
+            DefaultConstantUniforms::layout_buffer_and_bind_group(device);
+
+        let clip_layout = ClipUniforms::layout(device);
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("rootvg-quad line pipeline layout"),
+            push_constant_ranges: &[],
+            bind_group_layouts: &[&constants_layout, &clip_layout],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("rootvg-quad line shader"),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(concat!(
+                include_str!("../shader/quad.wgsl"),
+                "\n",
+                include_str!("../shader/clip.wgsl"),
+                "\n",
+                include_str!("../shader/line.wgsl"),
+            ))),
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("rootvg-quad line pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "line_vs_main",
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<LinePrimitive>() as u64,
+                    step_mode: wgpu::VertexStepMode::Instance,
+                    attributes: &wgpu::vertex_attr_array!(
+                        // From
+                        0 => Float32x2,
+                        // To
+                        1 => Float32x2,
+                        // Color
+                        2 => Float32x4,
+                        // End color
+                        3 => Float32x4,
+                        // Width
+                        4 => Float32,
+                        // Cap
+                        5 => Uint32,
+                    ),
+                }],
+                compilation_options: PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "line_fs_main",
+                targets: &super::color_target_state(format),
+                compilation_options: PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                front_face: wgpu::FrontFace::Cw,
+                ..Default::default()
+            },
+            depth_stencil,
+            multisample,
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            constants_buffer,
+            constants_bind_group,
+            clip_layout,
+            pipeline,
+            screen_size: PhysicalSizeI32::default(),
+            scale_factor: ScaleFactor::default(),
+            origin: PhysicalPointI32::default(),
+        }
+    }
+
+    pub fn create_batch(&mut self, device: &wgpu::Device) -> LineBatchBuffer {
+        LineBatchBuffer {
+            buffer: Buffer::new(
+                device,
+                "rootvg-quad line buffer",
+                INITIAL_INSTANCES,
+                wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            ),
+            clip: ClipBuffer::new(device, &self.clip_layout),
+            num_primitives: 0,
+        }
+    }
+
+    pub fn start_preparations(
+        &mut self,
+        _device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        screen_size: PhysicalSizeI32,
+        scale_factor: ScaleFactor,
+        origin: PhysicalPointI32,
+    ) {
+        if self.screen_size == screen_size
+            && self.scale_factor == scale_factor
+            && self.origin == origin
+        {
+            return;
+        }
+
+        self.screen_size = screen_size;
+        self.scale_factor = scale_factor;
+        self.origin = origin;
+
+        DefaultConstantUniforms::prepare_buffer_with_origin(
+            &self.constants_buffer,
+            screen_size,
+            scale_factor,
+            origin,
+            queue,
+        );
+    }
+
+    pub fn prepare_batch(
+        &mut self,
+        batch: &mut LineBatchBuffer,
+        primitives: &[LinePrimitive],
+        clip: Option<RoundedClip>,
+        depth: f32,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> Result<(), BufferAllocationError> {
+        batch.clip.prepare(clip, depth, self.scale_factor, queue);
+
+        batch
+            .buffer
+            .expand_to_fit_new_size(device, primitives.len())?;
+        let _ = batch.buffer.write(queue, 0, primitives);
+
+        batch.num_primitives = primitives.len();
+
+        Ok(())
+    }
+
+    pub fn render_batch<'pass>(
+        &'pass self,
+        batch: &'pass LineBatchBuffer,
+        render_pass: &mut wgpu::RenderPass<'pass>,
+    ) {
+        if batch.num_primitives == 0 {
+            return;
+        }
+
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.constants_bind_group, &[]);
+        render_pass.set_bind_group(1, batch.clip.bind_group(), &[]);
+
+        render_pass.set_vertex_buffer(0, batch.buffer.slice(0..batch.num_primitives));
+
+        render_pass.draw(0..6, 0..batch.num_primitives as u32);
+    }
+}
@@ -1,6 +1,7 @@
 use rootvg_core::{
-    buffer::Buffer,
-    math::{PhysicalSizeI32, ScaleFactor},
+    buffer::{Buffer, BufferAllocationError},
+    clip::{ClipBuffer, ClipUniforms, RoundedClip},
+    math::{PhysicalPointI32, PhysicalSizeI32, ScaleFactor},
     pipeline::DefaultConstantUniforms,
 };
 use wgpu::PipelineCompilationOptions;
@@ -11,6 +12,7 @@ use crate::SolidQuadPrimitive;
 
 pub struct SolidQuadBatchBuffer {
     buffer: Buffer<SolidQuadPrimitive>,
+    clip: ClipBuffer,
     num_primitives: usize,
 }
 
@@ -20,9 +22,11 @@ pub struct SolidQuadPipeline {
 
     constants_buffer: wgpu::Buffer,
     constants_bind_group: wgpu::BindGroup,
+    clip_layout: wgpu::BindGroupLayout,
 
     screen_size: PhysicalSizeI32,
     scale_factor: ScaleFactor,
+    origin: PhysicalPointI32,
 }
 
 impl SolidQuadPipeline {
@@ -30,14 +34,17 @@ impl SolidQuadPipeline {
         device: &wgpu::Device,
         format: wgpu::TextureFormat,
         multisample: wgpu::MultisampleState,
+        depth_stencil: Option<wgpu::DepthStencilState>,
     ) -> Self {
         let (constants_layout, constants_buffer, constants_bind_group) =
             DefaultConstantUniforms::layout_buffer_and_bind_group(device);
 
+        let clip_layout = ClipUniforms::layout(device);
+
         let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("rootvg-quad solid pipeline layout"),
             push_constant_ranges: &[],
-            bind_group_layouts: &[&constants_layout],
+            bind_group_layouts: &[&constants_layout, &clip_layout],
         });
 
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
@@ -45,6 +52,8 @@ impl SolidQuadPipeline {
             source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(concat!(
                 include_str!("../shader/quad.wgsl"),
                 "\n",
+                include_str!("../shader/clip.wgsl"),
+                "\n",
                 include_str!("../shader/solid.wgsl"),
             ))),
         });
@@ -70,17 +79,25 @@ impl SolidQuadPipeline {
                         // Border radius
                         4 => Float32x4,
                         // Border width
-                        5 => Float32,
+                        5 => Float32x4,
                         // Flags
                         6 => Uint32,
-                        /*
                         // Shadow color
                         7 => Float32x4,
                         // Shadow offset
                         8 => Float32x2,
                         // Shadow blur radius
                         9 => Float32,
-                        */
+                        // Shadow spread
+                        10 => Float32,
+                        // Shadow inset
+                        11 => Uint32,
+                        // Border dash
+                        12 => Float32x4,
+                        // Border dash offset
+                        13 => Float32,
+                        // Transform
+                        14 => Float32x4,
                     ),
                 }],
                 compilation_options: PipelineCompilationOptions::default(),
@@ -96,7 +113,7 @@ impl SolidQuadPipeline {
                 front_face: wgpu::FrontFace::Cw,
                 ..Default::default()
             },
-            depth_stencil: None,
+            depth_stencil,
             multisample,
             multiview: None,
             cache: None,
@@ -105,9 +122,11 @@ impl SolidQuadPipeline {
         Self {
             constants_buffer,
             constants_bind_group,
+            clip_layout,
             pipeline,
             screen_size: PhysicalSizeI32::default(),
             scale_factor: ScaleFactor::default(),
+            origin: PhysicalPointI32::default(),
         }
     }
 
@@ -119,6 +138,7 @@ impl SolidQuadPipeline {
                 INITIAL_INSTANCES,
                 wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
             ),
+            clip: ClipBuffer::new(device, &self.clip_layout),
             num_primitives: 0,
         }
     }
@@ -129,18 +149,24 @@ impl SolidQuadPipeline {
         queue: &wgpu::Queue,
         screen_size: PhysicalSizeI32,
         scale_factor: ScaleFactor,
+        origin: PhysicalPointI32,
     ) {
-        if self.screen_size == screen_size && self.scale_factor == scale_factor {
+        if self.screen_size == screen_size
+            && self.scale_factor == scale_factor
+            && self.origin == origin
+        {
             return;
         }
 
         self.screen_size = screen_size;
         self.scale_factor = scale_factor;
+        self.origin = origin;
 
-        DefaultConstantUniforms::prepare_buffer(
+        DefaultConstantUniforms::prepare_buffer_with_origin(
             &self.constants_buffer,
             screen_size,
             scale_factor,
+            origin,
             queue,
         );
     }
@@ -149,15 +175,21 @@ impl SolidQuadPipeline {
         &mut self,
         batch: &mut SolidQuadBatchBuffer,
         primitives: &[SolidQuadPrimitive],
+        clip: Option<RoundedClip>,
+        depth: f32,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
-    ) {
-        let _ = batch
+    ) -> Result<(), BufferAllocationError> {
+        batch.clip.prepare(clip, depth, self.scale_factor, queue);
+
+        batch
             .buffer
-            .expand_to_fit_new_size(device, primitives.len());
+            .expand_to_fit_new_size(device, primitives.len())?;
         let _ = batch.buffer.write(queue, 0, primitives);
 
         batch.num_primitives = primitives.len();
+
+        Ok(())
     }
 
     pub fn render_batch<'pass>(
@@ -171,6 +203,7 @@ impl SolidQuadPipeline {
 
         render_pass.set_pipeline(&self.pipeline);
         render_pass.set_bind_group(0, &self.constants_bind_group, &[]);
+        render_pass.set_bind_group(1, batch.clip.bind_group(), &[]);
 
         render_pass.set_vertex_buffer(0, batch.buffer.slice(0..batch.num_primitives));
 
@@ -0,0 +1,159 @@
+use std::f32::consts::TAU;
+
+use bytemuck::Zeroable;
+
+use rootvg_core::color::PackedSrgb;
+use rootvg_core::math::{Angle, Point};
+
+use super::{QuadFlags, SolidQuadPrimitive, IDENTITY_2X2};
+
+/// The shape of the ends of an [`ArcQuad`].
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ArcCap {
+    /// The arc ends exactly at its start and end angles.
+    #[default]
+    Butt,
+    /// The ends of the arc are rounded off with a half circle whose diameter
+    /// is the thickness of the arc.
+    Round,
+}
+
+/// A circular arc with a constant thickness, for example for circular
+/// progress meters and the value arcs of knobs.
+///
+/// This is rendered by the quad pipeline with a signed distance function, so
+/// changing the angles of the arc every frame doesn't require tessellating a
+/// new mesh.
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct ArcQuad {
+    /// The center of the arc in logical points.
+    pub center: Point,
+    /// The radius of the line running through the middle of the arc in
+    /// logical points.
+    pub radius: f32,
+    /// The thickness of the arc in logical points.
+    pub thickness: f32,
+    /// The angle at which the arc starts. An angle of zero points to the
+    /// right, and positive angles go clockwise.
+    pub start_angle: Angle,
+    /// The angle that the arc sweeps through from `start_angle`. A negative
+    /// sweep goes counter-clockwise.
+    ///
+    /// Sweeps greater than a full turn are clamped to a full turn, in which
+    /// case the arc is drawn as a closed ring without caps.
+    pub sweep: Angle,
+    /// The shape of the ends of the arc.
+    pub cap: ArcCap,
+    /// The color of the arc.
+    pub color: PackedSrgb,
+}
+
+impl ArcQuad {
+    pub fn packed(&self) -> SolidQuadPrimitive {
+        let radius = self.radius.max(0.0);
+        let thickness = self.thickness.max(0.0);
+
+        // Normalize the arc so that it always sweeps clockwise.
+        let sweep = self.sweep.radians.clamp(-TAU, TAU);
+        let (start, sweep) = if sweep < 0.0 {
+            (self.start_angle.radians + sweep, -sweep)
+        } else {
+            (self.start_angle.radians, sweep)
+        };
+
+        // Leave an extra point on each side for antialiasing, and for arcs
+        // which are thinner than a physical pixel.
+        let extent = radius + thickness * 0.5 + 1.0;
+
+        SolidQuadPrimitive {
+            color: self.color,
+            position: [self.center.x - extent, self.center.y - extent],
+            size: [extent * 2.0; 2],
+            // See `QuadFlags::ARC` for how the arc is packed.
+            border_radius: [
+                start.rem_euclid(TAU),
+                sweep,
+                (self.cap == ArcCap::Round) as u32 as f32,
+                radius,
+            ],
+            border_width: [thickness; 4],
+            flags: QuadFlags::ARC.bits(),
+            transform: IDENTITY_2X2,
+            ..Zeroable::zeroed()
+        }
+    }
+
+    pub fn builder(radius: f32, thickness: f32) -> ArcQuadBuilder {
+        ArcQuadBuilder::new(radius, thickness)
+    }
+}
+
+pub struct ArcQuadBuilder {
+    arc: ArcQuad,
+}
+
+impl ArcQuadBuilder {
+    pub fn new(radius: f32, thickness: f32) -> Self {
+        Self {
+            arc: ArcQuad {
+                radius,
+                thickness,
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn center(mut self, center: Point) -> Self {
+        self.arc.center = center;
+        self
+    }
+
+    pub fn start_angle(mut self, angle: Angle) -> Self {
+        self.arc.start_angle = angle;
+        self
+    }
+
+    pub fn sweep(mut self, sweep: Angle) -> Self {
+        self.arc.sweep = sweep;
+        self
+    }
+
+    pub fn cap(mut self, cap: ArcCap) -> Self {
+        self.arc.cap = cap;
+        self
+    }
+
+    pub fn color(mut self, color: impl Into<PackedSrgb>) -> Self {
+        self.arc.color = color.into();
+        self
+    }
+
+    pub fn build(self) -> ArcQuad {
+        self.arc
+    }
+}
+
+impl From<ArcQuad> for SolidQuadPrimitive {
+    fn from(a: ArcQuad) -> SolidQuadPrimitive {
+        a.packed()
+    }
+}
+
+impl<'a> From<&'a ArcQuad> for SolidQuadPrimitive {
+    fn from(a: &'a ArcQuad) -> SolidQuadPrimitive {
+        a.packed()
+    }
+}
+
+impl From<ArcQuadBuilder> for SolidQuadPrimitive {
+    fn from(a: ArcQuadBuilder) -> SolidQuadPrimitive {
+        a.build().packed()
+    }
+}
+
+impl From<ArcQuadBuilder> for ArcQuad {
+    fn from(a: ArcQuadBuilder) -> ArcQuad {
+        a.build()
+    }
+}
@@ -1,11 +1,11 @@
 use rootvg_core::color::PackedSrgb;
 use rootvg_core::gradient::{Gradient, PackedGradient};
-use rootvg_core::math::{Point, Rect, Size};
+use rootvg_core::math::{Angle, Point, Rect, Size, Transform, Vector};
 
-use crate::border::Border;
+use crate::border::{Border, BorderDash, BorderWidth};
 use crate::Radius;
 
-use super::QuadFlags;
+use super::{pack_transform, transform_bounds, QuadFlags};
 
 /// A quad primitive with a gradient background.
 #[derive(Default, Debug, Clone, Copy, PartialEq)]
@@ -16,8 +16,17 @@ pub struct GradientQuad {
     pub bg_gradient: Gradient,
     /// The [`Border`] of the quad
     pub border: Border,
+    /// An optional gradient for the border of the quad. If this is `Some`,
+    /// then it is used instead of the border color.
+    ///
+    /// A gradient with a single color stop can be used to get a solid
+    /// background with a gradient border, or vice versa.
+    pub border_gradient: Option<Gradient>,
     /// Additional flags for a quad primitive.
     pub flags: QuadFlags,
+    /// An optional transform of the quad in logical points, relative to the
+    /// top-left corner of the quad.
+    pub transform: Option<Transform>,
     /*
     /// The shadow of the quad
     pub shadow: Shadow,
@@ -66,8 +75,13 @@ impl GradientQuadBuilder {
         self
     }
 
-    pub fn border_width(mut self, width: f32) -> Self {
-        self.quad.border.width = width;
+    pub fn border_gradient(mut self, gradient: impl Into<Gradient>) -> Self {
+        self.quad.border_gradient = Some(gradient.into());
+        self
+    }
+
+    pub fn border_width(mut self, width: impl Into<BorderWidth>) -> Self {
+        self.quad.border.width = width.into();
         self
     }
 
@@ -76,6 +90,11 @@ impl GradientQuadBuilder {
         self
     }
 
+    pub fn border_dash(mut self, dash: BorderDash) -> Self {
+        self.quad.border.dash = Some(dash);
+        self
+    }
+
     pub fn border(mut self, border: Border) -> Self {
         self.quad.border = border;
         self
@@ -86,6 +105,13 @@ impl GradientQuadBuilder {
         self
     }
 
+    /// Draw the quad as an ellipse which fills its bounds, see
+    /// [`QuadFlags::ELLIPSE`].
+    pub fn ellipse(mut self) -> Self {
+        self.quad.flags |= QuadFlags::ELLIPSE;
+        self
+    }
+
     /*
     pub fn shadow_color(mut self, color: impl Into<PackedSrgb>) -> Self {
         self.quad.shadow.color = color.into();
@@ -108,6 +134,30 @@ impl GradientQuadBuilder {
     }
     */
 
+    /// Rotate the quad by the given angle about the given origin, where the
+    /// origin is normalized to the size of the quad (i.e. `(0.5, 0.5)` is the
+    /// center of the quad).
+    pub fn rotation(mut self, angle: Angle, origin_normal: Point) -> Self {
+        let origin = Vector::new(
+            origin_normal.x * self.quad.bounds.size.width,
+            origin_normal.y * self.quad.bounds.size.height,
+        );
+
+        self.quad.transform = Some(
+            Transform::translation(-origin.x, -origin.y)
+                .then_rotate(angle)
+                .then_translate(origin),
+        );
+        self
+    }
+
+    /// Transform the quad in logical points, relative to the top-left corner
+    /// of the quad.
+    pub fn transform(mut self, transform: Transform) -> Self {
+        self.quad.transform = Some(transform);
+        self
+    }
+
     pub fn build(self) -> GradientQuad {
         self.quad
     }
@@ -117,6 +167,7 @@ impl GradientQuadBuilder {
 /// for use in rendering.
 #[repr(C)]
 #[derive(Clone, Copy, Debug, PartialEq, bytemuck::Zeroable, bytemuck::Pod)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GradientQuadPrimitive {
     /// The background gradient data of the quad.
     pub gradient: PackedGradient,
@@ -133,24 +184,65 @@ pub struct GradientQuadPrimitive {
     /// The border radii of the [`Quad`] in logical points.
     pub border_radius: [f32; 4],
 
-    /// The border width of the [`Quad`] in logical points.
-    pub border_width: f32,
+    /// The border widths of the [`Quad`] in logical points, in the order
+    /// `[top, right, bottom, left]`.
+    pub border_width: [f32; 4],
 
     /// Additional flags for a quad primitive.
     pub flags: u32,
+
+    /// The dash pattern of the border of the [`Quad`] in logical points. A
+    /// pattern of all zeros means that the border is solid.
+    pub border_dash: [f32; 4],
+
+    /// The offset of the dash pattern of the border in logical points.
+    pub border_dash_offset: f32,
+
+    /// The border gradient data of the quad. A gradient with no color stops
+    /// means that [`GradientQuadPrimitive::border_color`] is used instead.
+    pub border_gradient: PackedGradient,
+
+    /// The linear part of the transform of the [`Quad`] as a column-major 2 by 2
+    /// matrix, which is applied about the position of the quad. The
+    /// translation of the transform is added to the position of the quad.
+    ///
+    /// By default this is set to the identity matrix.
+    pub transform: [f32; 4],
 }
 
 impl GradientQuadPrimitive {
     pub fn new(quad: &GradientQuad) -> Self {
+        let (position, transform) = pack_transform(quad.bounds.origin, quad.transform);
+        // The gradients are in the same space as the (translated) quad.
+        let bounds = Rect::new(position, quad.bounds.size);
+
         Self {
-            gradient: quad.bg_gradient.packed(quad.bounds),
-            position: quad.bounds.origin.into(),
-            size: quad.bounds.size.into(),
+            gradient: quad.bg_gradient.packed(bounds),
+            position: position.into(),
+            size: bounds.size.into(),
             border_color: quad.border.color,
             border_radius: quad.border.radius.into(),
-            border_width: quad.border.width,
+            border_width: quad.border.width.into(),
             flags: quad.flags.bits(),
+            border_dash: quad.border.dash.map(|d| d.segments).unwrap_or_default(),
+            border_dash_offset: quad.border.dash.map(|d| d.offset).unwrap_or_default(),
+            // The default gradient has no color stops.
+            border_gradient: quad.border_gradient.unwrap_or_default().packed(bounds),
+            transform,
+        }
+    }
+
+    /// The area covered by the quad in logical points after its transform is
+    /// applied.
+    pub fn bounds(&self) -> Rect {
+        let position = Point::from(self.position);
+        let mut bounds = Rect::new(position, Size::from(self.size));
+
+        if QuadFlags::from_bits_retain(self.flags).snaps_any_edge() {
+            bounds = bounds.inflate(1.0, 1.0);
         }
+
+        transform_bounds(bounds, position, self.transform)
     }
 }
 
@@ -177,3 +269,31 @@ impl From<GradientQuadBuilder> for GradientQuad {
         q.build()
     }
 }
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use rootvg_core::color::RGBA8;
+    use rootvg_core::gradient::LinearGradient;
+
+    use super::*;
+
+    #[test]
+    fn serde_round_trip() {
+        let gradient = LinearGradient::new(Angle::degrees(45.0))
+            .add_stop(0.0, RGBA8::new(255, 0, 0, 255))
+            .add_stop(0.5, RGBA8::new(0, 255, 0, 128))
+            .add_stop(1.0, RGBA8::new(0, 0, 255, 255));
+        let primitive = GradientQuad::builder(Size::new(30.0, 20.0))
+            .position(Point::new(5.0, 10.0))
+            .bg_gradient(gradient)
+            .border_width(2.0)
+            .border_radius(4.0)
+            .build()
+            .packed();
+
+        let json = serde_json::to_string(&primitive).unwrap();
+        let deserialized: GradientQuadPrimitive = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(deserialized, primitive);
+    }
+}
@@ -0,0 +1,173 @@
+use bytemuck::{Pod, Zeroable};
+
+use rootvg_core::color::PackedSrgb;
+use rootvg_core::math::{Point, Rect, Size};
+
+/// The shape of the ends of a [`Line`].
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LineCap {
+    /// The line ends exactly at its end points.
+    #[default]
+    Butt = 0,
+    /// The line is extended past its end points by half of its width.
+    Square = 1,
+    /// The ends of the line are rounded off with a half circle whose
+    /// diameter is the width of the line.
+    Round = 2,
+}
+
+/// A straight line segment with a constant width, for example for the
+/// connectors of a node graph or the grid of a piano roll.
+///
+/// Lines are drawn by a dedicated pipeline with a signed distance function,
+/// which is much cheaper than tessellating a stroke when there are thousands
+/// of them.
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct Line {
+    /// The start of the line in logical points.
+    pub from: Point,
+    /// The end of the line in logical points.
+    pub to: Point,
+    /// The width of the line in logical points.
+    ///
+    /// Lines which are thinner than a physical pixel are drawn one pixel wide
+    /// and faded out instead, so that thin lines look the same at every scale
+    /// factor.
+    pub width: f32,
+    /// The color of the line at `from`.
+    pub color: PackedSrgb,
+    /// The color of the line at `to`. The color is interpolated along the
+    /// line. If this is `None`, then the line has a solid color.
+    pub end_color: Option<PackedSrgb>,
+    /// The shape of the ends of the line.
+    pub cap: LineCap,
+}
+
+impl Line {
+    pub fn packed(&self) -> LinePrimitive {
+        LinePrimitive::new(self)
+    }
+
+    pub fn builder(from: Point, to: Point) -> LineBuilder {
+        LineBuilder::new(from, to)
+    }
+}
+
+pub struct LineBuilder {
+    line: Line,
+}
+
+impl LineBuilder {
+    pub fn new(from: Point, to: Point) -> Self {
+        Self {
+            line: Line {
+                from,
+                to,
+                width: 1.0,
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn width(mut self, width: f32) -> Self {
+        self.line.width = width;
+        self
+    }
+
+    pub fn color(mut self, color: impl Into<PackedSrgb>) -> Self {
+        self.line.color = color.into();
+        self
+    }
+
+    /// Interpolate the color of the line from its color at `from` to the
+    /// given color at `to`.
+    pub fn end_color(mut self, color: impl Into<PackedSrgb>) -> Self {
+        self.line.end_color = Some(color.into());
+        self
+    }
+
+    pub fn cap(mut self, cap: LineCap) -> Self {
+        self.line.cap = cap;
+        self
+    }
+
+    pub fn build(self) -> Line {
+        self.line
+    }
+}
+
+/// A line primitive, packed into a format for use in rendering.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LinePrimitive {
+    /// The start of the line in logical points.
+    pub from: [f32; 2],
+
+    /// The end of the line in logical points.
+    pub to: [f32; 2],
+
+    /// The color of the line at `from`.
+    pub color: PackedSrgb,
+
+    /// The color of the line at `to`.
+    pub end_color: PackedSrgb,
+
+    /// The width of the line in logical points.
+    pub width: f32,
+
+    /// The [`LineCap`] of the line.
+    pub cap: u32,
+}
+
+impl LinePrimitive {
+    pub fn new(line: &Line) -> Self {
+        Self {
+            from: line.from.into(),
+            to: line.to.into(),
+            color: line.color,
+            end_color: line.end_color.unwrap_or(line.color),
+            width: line.width.max(0.0),
+            cap: line.cap as u32,
+        }
+    }
+
+    /// The area covered by the line in logical points, including the caps
+    /// and room for antialiasing.
+    pub fn bounds(&self) -> Rect {
+        // Covers the area that is expanded in the shader. The caps extend by
+        // at most half of the width, and extra room is left for antialiasing
+        // and for lines thinner than a physical pixel.
+        let extent = self.width * 0.5 + 2.0;
+
+        let min = Point::new(self.from[0].min(self.to[0]), self.from[1].min(self.to[1]));
+        let max = Point::new(self.from[0].max(self.to[0]), self.from[1].max(self.to[1]));
+
+        Rect::new(min, Size::new(max.x - min.x, max.y - min.y)).inflate(extent, extent)
+    }
+}
+
+impl From<Line> for LinePrimitive {
+    fn from(l: Line) -> LinePrimitive {
+        l.packed()
+    }
+}
+
+impl<'a> From<&'a Line> for LinePrimitive {
+    fn from(l: &'a Line) -> LinePrimitive {
+        l.packed()
+    }
+}
+
+impl From<LineBuilder> for LinePrimitive {
+    fn from(l: LineBuilder) -> LinePrimitive {
+        l.build().packed()
+    }
+}
+
+impl From<LineBuilder> for Line {
+    fn from(l: LineBuilder) -> Line {
+        l.build()
+    }
+}
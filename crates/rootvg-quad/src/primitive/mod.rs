@@ -1,4 +1,8 @@
+mod arc;
+mod line;
 mod solid;
+pub use arc::*;
+pub use line::*;
 pub use solid::*;
 
 #[cfg(feature = "gradient")]
@@ -6,6 +10,8 @@ mod gradient;
 #[cfg(feature = "gradient")]
 pub use gradient::*;
 
+use rootvg_core::math::{Box2D, Point, Rect, Transform};
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum QuadPrimitive {
     Solid(SolidQuadPrimitive),
@@ -24,6 +30,44 @@ bitflags::bitflags! {
         /// In the shader, snap the border width to the nearest physical
         /// pixel to preserve perceived sharpness.
         const SNAP_BORDER_WIDTH_TO_NEAREST_PIXEL = 0b0010;
+        /// In the shader, don't dither the colors of a gradient quad.
+        ///
+        /// Gradients are dithered by less than half of an 8 bit step to hide
+        /// banding, so colors which are exactly representable are left
+        /// unchanged. Disabling it is useful for comparing screenshots
+        /// exactly. This has no effect on solid quads.
+        const DISABLE_DITHERING = 0b0100;
+        /// In the shader, snap the left edge of the quad to the nearest
+        /// physical pixel. This is useful for a divider which must start
+        /// exactly at a pixel boundary on one side only.
+        const SNAP_LEFT_TO_NEAREST_PIXEL = 0b0000_1000;
+        /// In the shader, snap the right edge of the quad to the nearest
+        /// physical pixel.
+        const SNAP_RIGHT_TO_NEAREST_PIXEL = 0b0001_0000;
+        /// In the shader, snap the top edge of the quad to the nearest
+        /// physical pixel.
+        const SNAP_TOP_TO_NEAREST_PIXEL = 0b0010_0000;
+        /// In the shader, snap the bottom edge of the quad to the nearest
+        /// physical pixel.
+        const SNAP_BOTTOM_TO_NEAREST_PIXEL = 0b0100_0000;
+        /// Draw the quad as an ellipse which fills its bounds, for example
+        /// for dots and knobs. This is much cheaper than tessellating a
+        /// circle, and the edges stay crisp at any size.
+        ///
+        /// The border radius and the border dash pattern are ignored. The
+        /// border follows the edge of the ellipse, and its inner edge is
+        /// inset by the width of each side of the border. Shadows take the
+        /// shape of the ellipse as well.
+        const ELLIPSE = 0b1000_0000;
+        /// Draw the quad as an [`ArcQuad`]. This is set by
+        /// [`ArcQuad::packed`] and has no effect on gradient quads.
+        ///
+        /// The border radius holds the start angle and the sweep of the arc
+        /// in radians, whether the arc has round caps (`1.0`) or not (`0.0`),
+        /// and the radius of the arc in logical points. The border width
+        /// holds the thickness of the arc. The border and the shadow of the
+        /// quad are ignored.
+        const ARC = 0b1_0000_0000;
 
         /// In the shader, snap the edges and the border width of the
         /// quad to the nearest physical pixel to preserve perceived
@@ -37,3 +81,59 @@ impl Default for QuadFlags {
         QuadFlags::empty()
     }
 }
+
+impl QuadFlags {
+    /// Returns `true` if any of the edges of the quad are snapped to the
+    /// nearest physical pixel.
+    pub fn snaps_any_edge(self) -> bool {
+        self.intersects(
+            Self::SNAP_EDGES_TO_NEAREST_PIXEL
+                | Self::SNAP_LEFT_TO_NEAREST_PIXEL
+                | Self::SNAP_RIGHT_TO_NEAREST_PIXEL
+                | Self::SNAP_TOP_TO_NEAREST_PIXEL
+                | Self::SNAP_BOTTOM_TO_NEAREST_PIXEL,
+        )
+    }
+}
+
+const IDENTITY_2X2: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
+
+/// Split the transform of a quad into the position of the quad with the
+/// translation of the transform added, and the linear part of the transform
+/// as a column-major 2 by 2 matrix.
+fn pack_transform(position: Point, transform: Option<Transform>) -> (Point, [f32; 4]) {
+    let Some(t) = transform else {
+        return (position, IDENTITY_2X2);
+    };
+
+    (
+        Point::new(position.x + t.m31, position.y + t.m32),
+        [t.m11, t.m12, t.m21, t.m22],
+    )
+}
+
+/// Returns the bounding box of the given rectangle after the column-major 2 by
+/// 2 `transform` matrix is applied about `origin`, matching `transform_about`
+/// in the shader.
+fn transform_bounds(rect: Rect, origin: Point, transform: [f32; 4]) -> Rect {
+    if transform == IDENTITY_2X2 {
+        return rect;
+    }
+
+    let [m11, m12, m21, m22] = transform;
+    let corners = [
+        rect.min(),
+        Point::new(rect.max_x(), rect.min_y()),
+        rect.max(),
+        Point::new(rect.min_x(), rect.max_y()),
+    ]
+    .map(|p| {
+        let d = p - origin;
+        Point::new(
+            origin.x + m11 * d.x + m21 * d.y,
+            origin.y + m12 * d.x + m22 * d.y,
+        )
+    });
+
+    Box2D::from_points(corners).to_rect()
+}
@@ -1,12 +1,12 @@
 use bytemuck::{Pod, Zeroable};
 
 use rootvg_core::color::PackedSrgb;
-use rootvg_core::math::{Point, Rect, Size};
+use rootvg_core::math::{Angle, Point, Rect, Size, Transform, Vector};
 
-use crate::border::Border;
-use crate::Radius;
+use crate::border::{Border, BorderDash, BorderWidth};
+use crate::{Radius, Shadow};
 
-use super::QuadFlags;
+use super::{pack_transform, transform_bounds, QuadFlags};
 
 /// A quad primitive with a solid background.
 #[derive(Default, Debug, Clone, Copy, PartialEq)]
@@ -19,10 +19,11 @@ pub struct SolidQuad {
     pub border: Border,
     /// Additional flags for a quad primitive.
     pub flags: QuadFlags,
-    /*
-    /// The shadow of the quad
-    pub shadow: Shadow,
-    */
+    /// The drop shadow of the quad
+    pub shadow: Option<Shadow>,
+    /// An optional transform of the quad in logical points, relative to the
+    /// top-left corner of the quad.
+    pub transform: Option<Transform>,
 }
 
 impl SolidQuad {
@@ -67,8 +68,8 @@ impl SolidQuadBuilder {
         self
     }
 
-    pub fn border_width(mut self, width: f32) -> Self {
-        self.quad.border.width = width;
+    pub fn border_width(mut self, width: impl Into<BorderWidth>) -> Self {
+        self.quad.border.width = width.into();
         self
     }
 
@@ -77,6 +78,11 @@ impl SolidQuadBuilder {
         self
     }
 
+    pub fn border_dash(mut self, dash: BorderDash) -> Self {
+        self.quad.border.dash = Some(dash);
+        self
+    }
+
     pub fn border(mut self, border: Border) -> Self {
         self.quad.border = border;
         self
@@ -87,27 +93,69 @@ impl SolidQuadBuilder {
         self
     }
 
-    /*
+    /// Draw the quad as an ellipse which fills its bounds, see
+    /// [`QuadFlags::ELLIPSE`].
+    pub fn ellipse(mut self) -> Self {
+        self.quad.flags |= QuadFlags::ELLIPSE;
+        self
+    }
+
     pub fn shadow_color(mut self, color: impl Into<PackedSrgb>) -> Self {
-        self.quad.shadow.color = color.into();
+        self.quad.shadow.get_or_insert_with(Default::default).color = color.into();
         self
     }
 
     pub fn shadow_offset(mut self, offset: Vector) -> Self {
-        self.quad.shadow.offset = offset;
+        self.quad.shadow.get_or_insert_with(Default::default).offset = offset;
         self
     }
 
     pub fn shadow_blur_radius(mut self, blur_radius: f32) -> Self {
-        self.quad.shadow.blur_radius = blur_radius;
+        self.quad
+            .shadow
+            .get_or_insert_with(Default::default)
+            .blur_radius = blur_radius;
+        self
+    }
+
+    pub fn shadow_spread(mut self, spread: f32) -> Self {
+        self.quad.shadow.get_or_insert_with(Default::default).spread = spread;
+        self
+    }
+
+    pub fn shadow_inset(mut self, inset: bool) -> Self {
+        self.quad.shadow.get_or_insert_with(Default::default).inset = inset;
         self
     }
 
     pub fn shadow(mut self, shadow: Shadow) -> Self {
-        self.quad.shadow = shadow;
+        self.quad.shadow = Some(shadow);
+        self
+    }
+
+    /// Rotate the quad by the given angle about the given origin, where the
+    /// origin is normalized to the size of the quad (i.e. `(0.5, 0.5)` is the
+    /// center of the quad).
+    pub fn rotation(mut self, angle: Angle, origin_normal: Point) -> Self {
+        let origin = Vector::new(
+            origin_normal.x * self.quad.bounds.size.width,
+            origin_normal.y * self.quad.bounds.size.height,
+        );
+
+        self.quad.transform = Some(
+            Transform::translation(-origin.x, -origin.y)
+                .then_rotate(angle)
+                .then_translate(origin),
+        );
+        self
+    }
+
+    /// Transform the quad in logical points, relative to the top-left corner
+    /// of the quad.
+    pub fn transform(mut self, transform: Transform) -> Self {
+        self.quad.transform = Some(transform);
         self
     }
-    */
 
     pub fn build(self) -> SolidQuad {
         self.quad
@@ -118,6 +166,7 @@ impl SolidQuadBuilder {
 /// use in rendering.
 #[repr(C)]
 #[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SolidQuadPrimitive {
     /// The background color data of the quad.
     pub color: PackedSrgb,
@@ -134,13 +183,15 @@ pub struct SolidQuadPrimitive {
     /// The border radii of the [`Quad`] in logical points.
     pub border_radius: [f32; 4],
 
-    /// The border width of the [`Quad`] in logical points.
-    pub border_width: f32,
+    /// The border widths of the [`Quad`] in logical points, in the order
+    /// `[top, right, bottom, left]`.
+    pub border_width: [f32; 4],
 
     /// Additional flags for a quad primitive.
     pub flags: u32,
-    /*
-    /// The shadow color of the [`Quad`].
+
+    /// The shadow color of the [`Quad`]. A fully transparent color means
+    /// that the quad has no shadow.
     pub shadow_color: PackedSrgb,
 
     /// The shadow offset of the [`Quad`] in logical points.
@@ -148,23 +199,74 @@ pub struct SolidQuadPrimitive {
 
     /// The shadow blur radius of the [`Quad`] in logical points.
     pub shadow_blur_radius: f32,
-    */
+
+    /// The shadow spread of the [`Quad`] in logical points.
+    pub shadow_spread: f32,
+
+    /// Whether the shadow of the [`Quad`] is an inner shadow (`1`) or a
+    /// drop shadow (`0`).
+    pub shadow_inset: u32,
+
+    /// The dash pattern of the border of the [`Quad`] in logical points. A
+    /// pattern of all zeros means that the border is solid.
+    pub border_dash: [f32; 4],
+
+    /// The offset of the dash pattern of the border in logical points.
+    pub border_dash_offset: f32,
+
+    /// The linear part of the transform of the [`Quad`] as a column-major 2 by 2
+    /// matrix, which is applied about the position of the quad. The
+    /// translation of the transform is added to the position of the quad.
+    ///
+    /// By default this is set to the identity matrix.
+    pub transform: [f32; 4],
 }
 
 impl SolidQuadPrimitive {
     pub fn new(quad: &SolidQuad) -> Self {
+        let shadow = quad.shadow.unwrap_or_default();
+        let (position, transform) = pack_transform(quad.bounds.origin, quad.transform);
+
         Self {
             color: quad.bg_color,
-            position: quad.bounds.origin.into(),
+            position: position.into(),
             size: quad.bounds.size.into(),
             border_color: quad.border.color,
             border_radius: quad.border.radius.into(),
-            border_width: quad.border.width,
+            border_width: quad.border.width.into(),
             flags: quad.flags.bits(),
-            //shadow_color: quad.shadow.color,
-            //shadow_offset: quad.shadow.offset.into(),
-            //shadow_blur_radius: quad.shadow.blur_radius,
+            shadow_color: shadow.color,
+            shadow_offset: shadow.offset.into(),
+            shadow_blur_radius: shadow.blur_radius,
+            shadow_spread: shadow.spread,
+            shadow_inset: shadow.inset as u32,
+            border_dash: quad.border.dash.map(|d| d.segments).unwrap_or_default(),
+            border_dash_offset: quad.border.dash.map(|d| d.offset).unwrap_or_default(),
+            transform,
+        }
+    }
+
+    /// The area covered by the quad (including its drop shadow) in logical
+    /// points after its transform is applied.
+    pub fn bounds(&self) -> Rect {
+        let position = Point::from(self.position);
+        let mut bounds = Rect::new(position, Size::from(self.size));
+
+        // Matches the area that is expanded for the shadow in the shader.
+        if self.shadow_color.a() > 0.0 && self.shadow_inset == 0 {
+            let extent = self.shadow_spread.max(0.0) + self.shadow_blur_radius.max(0.0) + 1.0;
+            let shadow_bounds = bounds
+                .translate(Vector::from(self.shadow_offset))
+                .inflate(extent, extent);
+
+            bounds = bounds.union(&shadow_bounds);
+        }
+
+        if QuadFlags::from_bits_retain(self.flags).snaps_any_edge() {
+            bounds = bounds.inflate(1.0, 1.0);
         }
+
+        transform_bounds(bounds, position, self.transform)
     }
 }
 
@@ -191,3 +293,29 @@ impl From<SolidQuadBuilder> for SolidQuad {
         q.build()
     }
 }
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use rootvg_core::color::RGBA8;
+
+    use super::*;
+
+    #[test]
+    fn serde_round_trip() {
+        let primitive = SolidQuad::builder(Size::new(30.0, 20.0))
+            .position(Point::new(5.0, 10.0))
+            .bg_color(RGBA8::new(10, 20, 30, 200))
+            .border_color(RGBA8::new(255, 0, 0, 255))
+            .border_width(2.0)
+            .border_radius(4.0)
+            .shadow_blur_radius(3.0)
+            .rotation(Angle::degrees(30.0), Point::new(0.5, 0.5))
+            .build()
+            .packed();
+
+        let json = serde_json::to_string(&primitive).unwrap();
+        let deserialized: SolidQuadPrimitive = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(deserialized, primitive);
+    }
+}
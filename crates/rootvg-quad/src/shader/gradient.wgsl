@@ -2,38 +2,66 @@
 // https://github.com/iced-rs/iced/blob/31d1d5fecbef50fa319cabd5d4194f1e4aaefa21/wgpu/src/shader/quad/gradient.wgsl
 // Iced license (MIT): https://github.com/iced-rs/iced/blob/31d1d5fecbef50fa319cabd5d4194f1e4aaefa21/LICENSE
 
+// Gradients with too many color stops to pass inline are baked into the rows
+// of this texture.
+@group(2) @binding(0) var ramp_texture: texture_2d<f32>;
+@group(2) @binding(1) var ramp_sampler: sampler;
+
 struct GradientVertexInput {
     @builtin(vertex_index) vertex_index: u32,
     @location(0) @interpolate(flat) colors_1: vec4<u32>,
     @location(1) @interpolate(flat) colors_2: vec4<u32>,
-    @location(2) @interpolate(flat) offsets: vec2<u32>,
+    @location(2) @interpolate(flat) offsets_and_kind: vec4<u32>,
     @location(3) direction: vec4<f32>,
-    @location(4) pos: vec2<f32>,
-    @location(5) size: vec2<f32>,
-    @location(6) border_color: vec4<f32>,
-    @location(7) border_radius: vec4<f32>,
-    @location(8) border_width: f32,
-    @location(9) flags: u32,
+    // The position and size are read as a single attribute to stay within the
+    // limit of 16 vertex attributes.
+    @location(4) pos_and_size: vec4<f32>,
+    @location(5) border_color: vec4<f32>,
+    @location(6) border_radius: vec4<f32>,
+    @location(7) border_width: vec4<f32>,
+    @location(8) flags: u32,
+    @location(9) border_dash: vec4<f32>,
+    @location(10) border_dash_offset: f32,
+    @location(11) @interpolate(flat) border_colors_1: vec4<u32>,
+    @location(12) @interpolate(flat) border_colors_2: vec4<u32>,
+    @location(13) @interpolate(flat) border_offsets_and_kind: vec4<u32>,
+    @location(14) border_direction: vec4<f32>,
+    @location(15) transform: vec4<f32>,
 }
 
 struct GradientVertexOutput {
     @builtin(position) position: vec4<f32>,
     @location(1) @interpolate(flat) colors_1: vec4<u32>,
     @location(2) @interpolate(flat) colors_2: vec4<u32>,
-    @location(3) @interpolate(flat) offsets: vec2<u32>,
+    @location(3) @interpolate(flat) offsets: vec3<u32>,
     @location(4) direction: vec4<f32>,
-    @location(5) pos: vec2<f32>,
-    @location(6) size: vec2<f32>,
-    @location(7) border_color: vec4<f32>,
-    @location(8) border_radius: vec4<f32>,
-    @location(9) border_width: f32,
+    @location(5) @interpolate(flat) kind: u32,
+    @location(6) pos: vec2<f32>,
+    @location(7) size: vec2<f32>,
+    @location(8) border_color: vec4<f32>,
+    @location(9) border_radius: vec4<f32>,
+    @location(10) border_width: vec4<f32>,
+    @location(11) border_dash: vec4<f32>,
+    @location(12) border_dash_offset: f32,
+    @location(13) @interpolate(flat) border_colors_1: vec4<u32>,
+    @location(14) @interpolate(flat) border_colors_2: vec4<u32>,
+    @location(15) @interpolate(flat) border_offsets: vec3<u32>,
+    @location(16) border_direction: vec4<f32>,
+    @location(17) @interpolate(flat) border_kind: u32,
+    // The position of the fragment in the local space of the quad (before
+    // the transform is applied) in physical pixels.
+    @location(18) local_pos: vec2<f32>,
+    @location(19) @interpolate(flat) flags: u32,
 }
 
 @vertex
 fn gradient_vs_main(input: GradientVertexInput) -> GradientVertexOutput {
     var out: GradientVertexOutput;
 
-    var min_border_radius = min(input.size.x, input.size.y) * 0.5;
+    let input_pos = input.pos_and_size.xy;
+    let input_size = input.pos_and_size.zw;
+
+    var min_border_radius = min(input_size.x, input_size.y) * 0.5;
     var border_radius: vec4<f32> = vec4<f32>(
         min(input.border_radius.x, min_border_radius),
         min(input.border_radius.y, min_border_radius),
@@ -42,160 +70,316 @@ fn gradient_vs_main(input: GradientVertexInput) -> GradientVertexOutput {
     );
 
     var screen_pos: vec2<f32> =
-        (input.pos + (vertex_position(input.vertex_index) * input.size))
+        (input_pos + (vertex_position(input.vertex_index) * input_size))
         * globals.scale_factor;
 
     out.colors_1 = input.colors_1;
     out.colors_2 = input.colors_2;
-    out.offsets = input.offsets;
-    out.direction = input.direction * globals.scale_factor;
-    out.pos = input.pos * globals.scale_factor;
-    out.size = input.size * globals.scale_factor;
+    out.offsets = input.offsets_and_kind.xyz;
+    out.kind = input.offsets_and_kind.w;
+    out.direction = scale_direction(input.direction, out.kind);
+    out.border_colors_1 = input.border_colors_1;
+    out.border_colors_2 = input.border_colors_2;
+    out.border_offsets = input.border_offsets_and_kind.xyz;
+    out.border_kind = input.border_offsets_and_kind.w;
+    out.border_direction = scale_direction(input.border_direction, out.border_kind);
+    out.pos = input_pos * globals.scale_factor;
+    out.size = input_size * globals.scale_factor;
     out.border_color = input.border_color;
     out.border_radius = border_radius * globals.scale_factor;
     out.border_width = input.border_width * globals.scale_factor;
+    out.border_dash = input.border_dash * globals.scale_factor;
+    out.border_dash_offset = input.border_dash_offset * globals.scale_factor;
+    out.flags = input.flags;
 
     // Snap edges to nearest physical pixel.
-    if (input.flags & 1u) > 0 {
-        let snapped_end_pos = round((input.pos + input.size) * globals.scale_factor);
-
-        screen_pos = round(screen_pos);
-        out.pos = round(out.pos);
-        out.size = snapped_end_pos - out.pos;
+    let snap_start = snap_start_edges(input.flags);
+    let snap_end = snap_end_edges(input.flags);
+    if any(snap_start) || any(snap_end) {
+        let end_pos = (input_pos + input_size) * globals.scale_factor;
+
+        // Snap the vertex along with the edges it lies on.
+        let snap_vertex = select(snap_start, snap_end, vertex_position(input.vertex_index) > vec2<f32>(0.5));
+        screen_pos = select(screen_pos, round(screen_pos), snap_vertex);
+        out.pos = select(out.pos, round(out.pos), snap_start);
+        out.size = select(end_pos, round(end_pos), snap_end) - out.pos;
     }
     // Snap border width to nearest physical pixel.
     if (input.flags & 2u) > 0 {
         out.border_width = round(out.border_width);
     }
 
+    out.local_pos = screen_pos;
+    screen_pos = transform_about(screen_pos, out.pos, input.transform);
+
     out.position = vec4<f32>(
-        (screen_pos.x * globals.screen_size_recip.x) - 1.0,
-        1.0 - (screen_pos.y * globals.screen_size_recip.y),
-        0.0,
+        ((screen_pos.x + globals.origin.x) * globals.screen_size_recip.x) - 1.0,
+        1.0 - ((screen_pos.y + globals.origin.y) * globals.screen_size_recip.y),
+        clip.depth,
         1.0
     );
 
     return out;
 }
 
-fn random(coords: vec2<f32>) -> f32 {
-    return fract(sin(dot(coords, vec2(12.9898,78.233))) * 43758.5453);
+// Converts the direction of a gradient from logical points to physical pixels.
+fn scale_direction(direction: vec4<f32>, kind: u32) -> vec4<f32> {
+    if (kind & 0xffu) == 2u {
+        // The angle of a conic gradient is not in units of logical points.
+        return vec4<f32>(direction.xy * globals.scale_factor, direction.zw);
+    }
+
+    return direction * globals.scale_factor;
 }
 
-/// Returns the current interpolated color with a max 8-stop gradient
+/// Returns the current interpolated color with a max 8-stop inline gradient,
+/// or the color sampled from the ramp of the gradient
 fn gradient(
     raw_position: vec2<f32>,
     direction: vec4<f32>,
-    colors: array<vec4<f32>, 4>,
-    offsets: vec4<f32>,
-    last_index: i32
+    kind: u32,
+    colors: array<u32, 8>,
+    offsets: array<f32, 8>
 ) -> vec4<f32> {
-    let start = direction.xy;
-    let end = direction.zw;
+    // The lower 8 bits hold the gradient kind, the next 8 bits hold the
+    // interpolation space, and the next 8 bits hold the number of stops.
+    let gradient_kind = kind & 0xffu;
+    let space = (kind >> 8u) & 0xffu;
+
+    var coord_offset: f32;
+    if (gradient_kind == 1u) {
+        // Radial gradient, `direction` is `[center.x, center.y, inner_radius, outer_radius]`.
+        let dist = distance(raw_position, direction.xy);
+        let range = direction.w - direction.z;
+
+        if (range <= 0.0) {
+            coord_offset = select(0.0, 1.0, dist >= direction.z);
+        } else {
+            coord_offset = clamp((dist - direction.z) / range, 0.0, 1.0);
+        }
+    } else if (gradient_kind == 2u) {
+        // Conic gradient, `direction` is `[center.x, center.y, start_angle, 0.0]`.
+        let v = raw_position - direction.xy;
+        let turns = (atan2(v.y, v.x) - direction.z) / 6.283185307179586;
+
+        // Wrap into `[0.0, 1.0)` so the sweep always starts at `start_angle`.
+        coord_offset = turns - floor(turns);
+    } else {
+        let start = direction.xy;
+        let end = direction.zw;
+
+        let v1 = end - start;
+        let v2 = raw_position - start;
+        let unit = normalize(v1);
+        coord_offset = dot(unit, v2) / length(v1);
+    }
 
-    let v1 = end - start;
-    let v2 = raw_position - start;
-    let unit = normalize(v1);
-    let coord_offset = dot(unit, v2) / length(v1);
+    // Bit 25 of the kind is set if the gradient is baked into a ramp, in
+    // which case the first color holds the row of the ramp.
+    if (kind & 0x2000000u) != 0u {
+        return sample_ramp(coord_offset, colors[0]);
+    }
+
+    let last_index = i32(stop_count(kind)) - 1;
+    if (last_index < 0) {
+        return vec4<f32>(0.0, 0.0, 0.0, 0.0);
+    }
 
     //need to store these as a var to use dynamic indexing in a loop
     //this is already added to wgsl spec but not in wgpu yet
     var colors_arr = colors;
     var offsets_arr = offsets;
 
+    // Only the colors of the segment containing the coordinate are decoded.
     var color: vec4<f32>;
-
-    let noise_granularity: f32 = 0.3/255.0;
-
-    for (var i: i32 = 0; i < last_index; i++) {
-        let curr_offset = offsets_arr[i];
-        let next_offset = offsets_arr[i+1];
-
-        if (coord_offset <= offsets_arr[0]) {
-            color = colors_arr[0];
-        }
-
-        if (curr_offset <= coord_offset && coord_offset <= next_offset) {
-            let from_ = colors_arr[i];
-            let to_ = colors_arr[i+1];
-            let factor = smoothstep(curr_offset, next_offset, coord_offset);
-
-            color = interpolate_color(from_, to_, factor);
-        }
-
-        if (coord_offset >= offsets_arr[last_index]) {
-            color = colors_arr[last_index];
+    if (coord_offset <= offsets_arr[0]) {
+        color = unpack_color(colors_arr[0], kind);
+    } else if (coord_offset >= offsets_arr[last_index]) {
+        color = unpack_color(colors_arr[last_index], kind);
+    } else {
+        for (var i: i32 = 0; i < last_index; i++) {
+            let curr_offset = offsets_arr[i];
+            let next_offset = offsets_arr[i+1];
+
+            if (coord_offset <= next_offset) {
+                let from_ = unpack_color(colors_arr[i], kind);
+                let to_ = unpack_color(colors_arr[i+1], kind);
+                let factor = smoothstep(curr_offset, next_offset, coord_offset);
+
+                color = interpolate_color(from_, to_, factor, space);
+                break;
+            }
         }
     }
 
-    return color + mix(-noise_granularity, noise_granularity, random(raw_position));
+    return color;
 }
 
 @fragment
 fn gradient_fs_main(input: GradientVertexOutput) -> @location(0) vec4<f32> {
-    let colors = array<vec4<f32>, 4>(
-        unpack_u32(input.colors_1.xy),
-        unpack_u32(input.colors_1.zw),
-        unpack_u32(input.colors_2.xy),
-        unpack_u32(input.colors_2.zw),
+    let colors = array<u32, 8>(
+        input.colors_1.x,
+        input.colors_1.y,
+        input.colors_1.z,
+        input.colors_1.w,
+        input.colors_2.x,
+        input.colors_2.y,
+        input.colors_2.z,
+        input.colors_2.w,
     );
 
-    let offsets: vec4<f32> = unpack_u32(input.offsets);
+    let offsets = unpack_offsets(input.offsets);
 
-    // TODO could just pass this in to the shader but is probably more performant to just check it here
-    var last_index = 3;
-    for (var i: i32 = 0; i <= 3; i++) {
-        if (offsets[i] > 1.0) {
-            last_index = i - 1;
-            break;
-        }
-    }
+    var mixed_color: vec4<f32> = gradient(input.local_pos, input.direction, input.kind, colors, offsets);
 
-    var mixed_color: vec4<f32> = gradient(input.position.xy, input.direction, colors, offsets, last_index);
+    var radius_alpha: f32;
+    if (input.flags & 128u) != 0u {
+        let ellipse = ellipse_coverage(input.local_pos, input.pos, input.size, input.border_width);
 
-    var border_radius = select_border_radius(
-        input.border_radius,
-        input.position.xy,
-        (input.pos + input.size * 0.5).xy
-    );
+        if (ellipse.y > 0.0) {
+            mixed_color = mix(mixed_color, border_color(input), vec4<f32>(ellipse.y, ellipse.y, ellipse.y, ellipse.y));
+        }
+        radius_alpha = ellipse.x;
+    } else {
+        var border_radius = select_border_radius(
+            input.border_radius,
+            input.local_pos,
+            (input.pos + input.size * 0.5).xy
+        );
 
-    if (input.border_width > 0.0) {
-        var internal_border: f32 = max(border_radius - input.border_width, 0.0);
+        if (any(input.border_width > vec4<f32>(0.0, 0.0, 0.0, 0.0))) {
+            // Blend between the widths of the two edges adjacent to the nearest corner.
+            let corner_widths = select_border_widths(
+                input.border_width,
+                input.local_pos,
+                (input.pos + input.size * 0.5).xy
+            );
+
+            var internal_border: f32 = max(border_radius - max(corner_widths.x, corner_widths.y), 0.0);
+
+            var internal_distance: f32 = distance_alg(
+                input.local_pos,
+                input.pos + input.border_width.wx,
+                input.size - input.border_width.wx - input.border_width.yz,
+                internal_border
+            );
+
+            var border_mix: f32 = smoothstep(
+                max(internal_border - 0.5, 0.0),
+                internal_border + 0.5,
+                internal_distance
+            );
+
+            // Apply the dash pattern along the line running through the middle of the border.
+            if (any(input.border_dash > vec4<f32>(0.0, 0.0, 0.0, 0.0))) {
+                let arc_position = perimeter_position(
+                    input.local_pos,
+                    input.pos + input.border_width.wx * 0.5,
+                    input.size - (input.border_width.wx + input.border_width.yz) * 0.5,
+                    border_center_line_radii(input.border_radius, input.border_width)
+                );
+
+                border_mix *= dash_coverage(arc_position, input.border_dash, input.border_dash_offset);
+            }
+
+            // Only sample the border gradient inside of the border band.
+            if (border_mix > 0.0) {
+                mixed_color = mix(mixed_color, border_color(input), vec4<f32>(border_mix, border_mix, border_mix, border_mix));
+            }
+        }
 
-        var internal_distance: f32 = distance_alg(
-            input.position.xy,
-            input.pos + vec2<f32>(input.border_width, input.border_width),
-            input.size - vec2<f32>(input.border_width * 2.0, input.border_width * 2.0),
-            internal_border
+        var dist: f32 = distance_alg(
+            input.local_pos,
+            input.pos,
+            input.size,
+            border_radius
         );
 
-        var border_mix: f32 = smoothstep(
-            max(internal_border - 0.5, 0.0),
-            internal_border + 0.5,
-            internal_distance
-        );
+        radius_alpha = 1.0 - smoothstep(
+            max(border_radius - 0.5, 0.0),
+            border_radius + 0.5,
+            dist);
+    }
 
-        mixed_color = mix(mixed_color, input.border_color, vec4<f32>(border_mix, border_mix, border_mix, border_mix));
+    // Dither unless it is disabled.
+    if (input.flags & 4u) == 0u {
+        mixed_color = dither(mixed_color, input.position.xy, input.kind);
     }
 
-    var dist: f32 = distance_alg(
-        input.position.xy,
-        input.pos,
-        input.size,
-        border_radius
+    return vec4<f32>(
+        mixed_color.x,
+        mixed_color.y,
+        mixed_color.z,
+        mixed_color.w * radius_alpha * clip_alpha(clip, input.position.xy - globals.origin)
     );
+}
 
-    var radius_alpha: f32 = 1.0 - smoothstep(
-        max(border_radius - 0.5, 0.0),
-        border_radius + 0.5,
-        dist);
+// The color of the border at the fragment, which is either the solid border
+// color or the border gradient.
+fn border_color(input: GradientVertexOutput) -> vec4<f32> {
+    if (stop_count(input.border_kind) == 0u) {
+        return input.border_color;
+    }
+
+    let border_colors = array<u32, 8>(
+        input.border_colors_1.x,
+        input.border_colors_1.y,
+        input.border_colors_1.z,
+        input.border_colors_1.w,
+        input.border_colors_2.x,
+        input.border_colors_2.y,
+        input.border_colors_2.z,
+        input.border_colors_2.w,
+    );
 
-    return vec4<f32>(mixed_color.x, mixed_color.y, mixed_color.z, mixed_color.w * radius_alpha);
+    return gradient(
+        input.local_pos,
+        input.border_direction,
+        input.border_kind,
+        border_colors,
+        unpack_offsets(input.border_offsets)
+    );
 }
 
-fn unpack_u32(color: vec2<u32>) -> vec4<f32> {
-    let rg: vec2<f32> = unpack2x16float(color.x);
-    let ba: vec2<f32> = unpack2x16float(color.y);
+// Samples the gradient ramp in the given row of the ramp texture. The colors
+// of the texture are in the same space as the unpacked inline colors.
+fn sample_ramp(coord_offset: f32, row: u32) -> vec4<f32> {
+    let height = f32(textureDimensions(ramp_texture).y);
+    let uv = vec2<f32>(clamp(coord_offset, 0.0, 1.0), (f32(row) + 0.5) / height);
 
-    return vec4<f32>(rg.y, rg.x, ba.y, ba.x);
-}
\ No newline at end of file
+    return textureSampleLevel(ramp_texture, ramp_sampler, uv, 0.0);
+}
+
+// The number of color stops is stored in bits 16-23 of the gradient kind.
+fn stop_count(kind: u32) -> u32 {
+    return min((kind >> 16u) & 0xffu, 8u);
+}
+
+// Unpacks the 8 offsets, which are stored as 12 bit unorms in a 96 bit
+// bitstream with the first offset in the lowest bits.
+fn unpack_offsets(packed: vec3<u32>) -> array<f32, 8> {
+    let scale = 1.0 / 4095.0;
+
+    return array<f32, 8>(
+        f32(extractBits(packed.x, 0u, 12u)) * scale,
+        f32(extractBits(packed.x, 12u, 12u)) * scale,
+        f32(extractBits(packed.x, 24u, 8u) | (extractBits(packed.y, 0u, 4u) << 8u)) * scale,
+        f32(extractBits(packed.y, 4u, 12u)) * scale,
+        f32(extractBits(packed.y, 16u, 12u)) * scale,
+        f32(extractBits(packed.y, 28u, 4u) | (extractBits(packed.z, 0u, 8u) << 4u)) * scale,
+        f32(extractBits(packed.z, 8u, 12u)) * scale,
+        f32(extractBits(packed.z, 20u, 12u)) * scale,
+    );
+}
+
+// Unpacks an 8 bit RGBA color. Bit 24 of the gradient kind is set if the
+// colors are gamma encoded.
+fn unpack_color(packed: u32, kind: u32) -> vec4<f32> {
+    let color = unpack4x8unorm(packed);
+
+    if (kind & 0x1000000u) != 0u {
+        return vec4<f32>(srgb_to_linear(color.rgb), color.a);
+    }
+
+    return color;
+}
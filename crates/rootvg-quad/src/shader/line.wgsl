@@ -0,0 +1,100 @@
+struct LineVertexInput {
+    @builtin(vertex_index) vertex_index: u32,
+    @location(0) start: vec2<f32>,
+    @location(1) end: vec2<f32>,
+    @location(2) color: vec4<f32>,
+    @location(3) end_color: vec4<f32>,
+    @location(4) width: f32,
+    @location(5) cap: u32,
+}
+
+struct LineVertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    @location(1) end_color: vec4<f32>,
+    // The position of the fragment along (x) and across (y) the line, relative
+    // to the start of the line in physical pixels.
+    @location(2) line_pos: vec2<f32>,
+    @location(3) length: f32,
+    @location(4) half_width: f32,
+    @location(5) fade: f32,
+    @location(6) @interpolate(flat) cap: u32,
+}
+
+@vertex
+fn line_vs_main(input: LineVertexInput) -> LineVertexOutput {
+    var out: LineVertexOutput;
+
+    let start = input.start * globals.scale_factor;
+    let end = input.end * globals.scale_factor;
+
+    let delta = end - start;
+    let line_length = length(delta);
+    let dir = select(vec2<f32>(1.0, 0.0), delta / line_length, line_length > 0.0);
+    let normal = vec2<f32>(-dir.y, dir.x);
+
+    // Lines which are thinner than a pixel are drawn one pixel wide and faded
+    // out instead, so that they don't break up into disconnected fragments.
+    let width = input.width * globals.scale_factor;
+    let half_width = max(width, 1.0) * 0.5;
+
+    // Square and round caps extend past the ends of the line by half of its
+    // width. Add an extra pixel on each side to leave room for antialiasing.
+    let cap_extent = select(0.0, half_width, input.cap != 0u) + 1.0;
+    let side_extent = half_width + 1.0;
+
+    let corner = vertex_position(input.vertex_index);
+    let line_pos = vec2<f32>(
+        mix(-cap_extent, line_length + cap_extent, corner.x),
+        mix(-side_extent, side_extent, corner.y)
+    );
+
+    let screen_pos = start + dir * line_pos.x + normal * line_pos.y;
+
+    out.position = vec4<f32>(
+        ((screen_pos.x + globals.origin.x) * globals.screen_size_recip.x) - 1.0,
+        1.0 - ((screen_pos.y + globals.origin.y) * globals.screen_size_recip.y),
+        clip.depth,
+        1.0
+    );
+
+    out.color = input.color;
+    out.end_color = input.end_color;
+    out.line_pos = line_pos;
+    out.length = line_length;
+    out.half_width = half_width;
+    out.fade = clamp(width, 0.0, 1.0);
+    out.cap = input.cap;
+
+    return out;
+}
+
+@fragment
+fn line_fs_main(input: LineVertexOutput) -> @location(0) vec4<f32> {
+    var dist: f32;
+    if input.cap == 2u {
+        // Round caps form a capsule around the line.
+        let nearest = clamp(input.line_pos.x, 0.0, input.length);
+        dist = length(vec2<f32>(input.line_pos.x - nearest, input.line_pos.y)) - input.half_width;
+    } else {
+        let cap_extent = select(0.0, input.half_width, input.cap == 1u);
+        let half_size = vec2<f32>(input.length * 0.5 + cap_extent, input.half_width);
+
+        dist = signed_rounded_box_sdf(
+            input.line_pos - vec2<f32>(input.length * 0.5, 0.0),
+            half_size,
+            0.0
+        );
+    }
+
+    let alpha = (1.0 - smoothstep(-0.5, 0.5, dist)) * input.fade;
+
+    // Interpolate the color along the line, holding the end colors over the caps.
+    var t = 0.0;
+    if input.length > 0.0 {
+        t = clamp(input.line_pos.x / input.length, 0.0, 1.0);
+    }
+    let color = mix(input.color, input.end_color, vec4<f32>(t, t, t, t));
+
+    return vec4<f32>(color.rgb, color.a * alpha * clip_alpha(clip, input.position.xy - globals.origin));
+}
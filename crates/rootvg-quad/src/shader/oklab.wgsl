@@ -1,4 +1,4 @@
-// The following code was copied from
+// The following code was copied and modified from
 // https://github.com/iced-rs/iced/blob/31d1d5fecbef50fa319cabd5d4194f1e4aaefa21/wgpu/src/shader/color/oklab.wgsl
 // Iced license (MIT): https://github.com/iced-rs/iced/blob/31d1d5fecbef50fa319cabd5d4194f1e4aaefa21/LICENSE
 
@@ -14,7 +14,18 @@ const to_rgb = mat3x4<f32>(
     vec4<f32>(-0.0041119885, -0.7034763098,  1.7068625689, 0.0),
 );
 
-fn interpolate_color(from_: vec4<f32>, to_: vec4<f32>, factor: f32) -> vec4<f32> {
+fn interpolate_color(from_: vec4<f32>, to_: vec4<f32>, factor: f32, space: u32) -> vec4<f32> {
+    // Linear RGB
+    if (space == 1u) {
+        return mix(from_, to_, factor);
+    }
+
+    // sRGB
+    if (space == 0u) {
+        let mixed = mix(linear_to_srgb(from_.rgb), linear_to_srgb(to_.rgb), factor);
+        return vec4<f32>(srgb_to_linear(mixed), mix(from_.a, to_.a, factor));
+    }
+
     // To Oklab
     let lms_a = pow(from_ * to_lms, vec3<f32>(1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0));
     let lms_b = pow(to_ * to_lms, vec3<f32>(1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0));
@@ -27,4 +38,44 @@ fn interpolate_color(from_: vec4<f32>, to_: vec4<f32>, factor: f32) -> vec4<f32>
     color.a = mix(from_.a, to_.a, factor);
 
     return color;
-}
\ No newline at end of file
+}
+
+fn linear_to_srgb(c: vec3<f32>) -> vec3<f32> {
+    let cutoff = c <= vec3<f32>(0.0031308);
+    let lower = c * 12.92;
+    let higher = 1.055 * pow(max(c, vec3<f32>(0.0)), vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(higher, lower, cutoff);
+}
+
+fn srgb_to_linear(c: vec3<f32>) -> vec3<f32> {
+    let cutoff = c <= vec3<f32>(0.04045);
+    let lower = c / 12.92;
+    let higher = pow((max(c, vec3<f32>(0.0)) + 0.055) / 1.055, vec3<f32>(2.4));
+    return select(higher, lower, cutoff);
+}
+
+// Adds an ordered dither of less than half of an 8 bit step to the color, so
+// that smooth gradients don't show visible bands while colors which are
+// exactly representable are left unchanged. The dither is added in the gamma
+// encoded space that the output is quantized in. Bit 24 of the gradient kind
+// is set if the output is linear and gets gamma encoded by the target.
+fn dither(color: vec4<f32>, frag_coord: vec2<f32>, kind: u32) -> vec4<f32> {
+    // A 4x4 Bayer matrix, need to store it as a var to use dynamic indexing.
+    var bayer = array<u32, 16>(
+        0u, 8u, 2u, 10u,
+        12u, 4u, 14u, 6u,
+        3u, 11u, 1u, 9u,
+        15u, 7u, 13u, 5u,
+    );
+
+    let cell = vec2<u32>(frag_coord) % vec2<u32>(4u);
+    let offset = ((f32(bayer[cell.y * 4u + cell.x]) + 0.5) / 16.0 - 0.5) / 255.0;
+
+    let rgb = clamp(color.rgb, vec3<f32>(0.0), vec3<f32>(1.0));
+
+    if (kind & 0x1000000u) != 0u {
+        return vec4<f32>(srgb_to_linear(linear_to_srgb(rgb) + offset), color.a);
+    }
+
+    return vec4<f32>(rgb + offset, color.a);
+}
@@ -5,9 +5,11 @@
 struct Globals {
     screen_size_recip: vec2<f32>,
     scale_factor: f32,
+    origin: vec2<f32>,
 }
 
 @group(0) @binding(0) var<uniform> globals: Globals;
+@group(1) @binding(0) var<uniform> clip: ClipUniforms;
 
 fn distance_alg(
     frag_coord: vec2<f32>,
@@ -26,6 +28,119 @@ fn rounded_box_sdf(to_center: vec2<f32>, size: vec2<f32>, radius: f32) -> f32 {
     return length(max(abs(to_center) - size + vec2<f32>(radius, radius), vec2<f32>(0.0, 0.0))) - radius;
 }
 
+// The signed distance from a point to the edge of a rounded rectangle, where the
+// distance is negative inside of the rectangle.
+fn signed_rounded_box_sdf(to_center: vec2<f32>, half_size: vec2<f32>, radius: f32) -> f32 {
+    let q = abs(to_center) - half_size + vec2<f32>(radius, radius);
+    return min(max(q.x, q.y), 0.0) + length(max(q, vec2<f32>(0.0, 0.0))) - radius;
+}
+
+// An approximation of the signed distance from a point to the edge of an
+// ellipse with the given radii, where `to_center` is the vector from the center
+// of the ellipse to the point. The distance is exact on the edge and accurate
+// enough near it for antialiasing, including for ellipses with very different
+// radii.
+fn ellipse_sdf(to_center: vec2<f32>, radii: vec2<f32>) -> f32 {
+    let r = max(radii, vec2<f32>(0.0001, 0.0001));
+    let k0 = length(to_center / r);
+    let k1 = length(to_center / (r * r));
+
+    if k1 == 0.0 {
+        return -min(r.x, r.y);
+    }
+
+    return k0 * (k0 - 1.0) / k1;
+}
+
+// The coverage of an ellipse which fills the given bounds (x), and the amount
+// with which its border is mixed in (y). The ellipse inside of the border is
+// inset by the width of each side of the border.
+fn ellipse_coverage(
+    frag_coord: vec2<f32>,
+    position: vec2<f32>,
+    size: vec2<f32>,
+    border_width: vec4<f32>
+) -> vec2<f32> {
+    let half_size = size * 0.5;
+    let alpha = 1.0 - smoothstep(-0.5, 0.5, ellipse_sdf(frag_coord - position - half_size, half_size));
+
+    var border_mix = 0.0;
+    if (any(border_width > vec4<f32>(0.0, 0.0, 0.0, 0.0))) {
+        let inner_half_size = (size - border_width.wx - border_width.yz) * 0.5;
+
+        if (any(inner_half_size <= vec2<f32>(0.0, 0.0))) {
+            border_mix = 1.0;
+        } else {
+            let inner_center = position + border_width.wx + inner_half_size;
+            border_mix = smoothstep(-0.5, 0.5, ellipse_sdf(frag_coord - inner_center, inner_half_size));
+        }
+    }
+
+    return vec2<f32>(alpha, border_mix);
+}
+
+// The unsigned distance from a point to the end of an arc at the given angle,
+// which is a radial line segment with the thickness of the arc.
+fn arc_end_distance(to_center: vec2<f32>, angle: f32, radius: f32, half_thickness: f32) -> f32 {
+    let dir = vec2<f32>(cos(angle), sin(angle));
+    let along = clamp(dot(to_center, dir), radius - half_thickness, radius + half_thickness);
+    return length(to_center - dir * along);
+}
+
+// The signed distance from a point to the edge of an arc which starts at
+// `start` and sweeps clockwise through `sweep` radians, where `to_center` is
+// the vector from the center of the arc to the point.
+fn arc_sdf(
+    to_center: vec2<f32>,
+    radius: f32,
+    half_thickness: f32,
+    start: f32,
+    sweep: f32,
+    round_caps: bool
+) -> f32 {
+    let ring = abs(length(to_center) - radius) - half_thickness;
+
+    // A full turn is a closed ring without any ends.
+    if (sweep >= 6.2831) {
+        return ring;
+    }
+
+    // The angle of the point relative to the start of the arc, in the range
+    // `[0, 2pi)`.
+    var angle = atan2(to_center.y, to_center.x) - start;
+    angle = angle - floor(angle / 6.2831853) * 6.2831853;
+    let in_sweep = angle <= sweep;
+
+    if round_caps {
+        let start_end = vec2<f32>(cos(start), sin(start)) * radius;
+        let end_end = vec2<f32>(cos(start + sweep), sin(start + sweep)) * radius;
+
+        let caps = min(length(to_center - start_end), length(to_center - end_end)) - half_thickness;
+        return select(caps, min(ring, caps), in_sweep);
+    }
+
+    let ends = min(
+        arc_end_distance(to_center, start, radius, half_thickness),
+        arc_end_distance(to_center, start + sweep, radius, half_thickness)
+    );
+    // Inside of the sweep, the ends cut off the ring. Outside of it, the
+    // nearest part of the arc is always on one of the ends.
+    return select(ends, max(ring, -ends), in_sweep);
+}
+
+// The coverage of an arc packed into the border radius and border width of a
+// solid quad, see `QuadFlags::ARC`.
+fn arc_coverage(to_center: vec2<f32>, arc: vec4<f32>, thickness: f32) -> f32 {
+    // Arcs which are thinner than a pixel are drawn one pixel thick and faded
+    // out instead, so that they don't break up into disconnected fragments.
+    let half_thickness = max(thickness, 1.0) * 0.5;
+    let fade = clamp(thickness, 0.0, 1.0);
+
+    let dist = arc_sdf(to_center, arc.w, half_thickness, arc.x, arc.y, arc.z > 0.5);
+
+    return (1.0 - smoothstep(-0.5, 0.5, dist)) * fade;
+}
+
 // Based on the fragement position and the center of the quad, select one of the 4 radi.
 // Order matches CSS border radius attribute:
 // radi.x = top-left, radi.y = top-right, radi.z = bottom-right, radi.w = bottom-left
@@ -38,11 +153,159 @@ fn select_border_radius(radi: vec4<f32>, position: vec2<f32>, center: vec2<f32>)
     return rx;
 }
 
+// Based on the fragment position and the center of the quad, select the widths of the
+// horizontal and vertical edges nearest to the fragment.
+// widths.x = top, widths.y = right, widths.z = bottom, widths.w = left
+fn select_border_widths(widths: vec4<f32>, position: vec2<f32>, center: vec2<f32>) -> vec2<f32> {
+    return vec2<f32>(
+        select(widths.w, widths.y, position.x > center.x),
+        select(widths.x, widths.z, position.y > center.y)
+    );
+}
+
+// Computes the arc-length position of the point along the perimeter of a rounded
+// rectangle, going clockwise starting at the beginning of the top edge.
+// radii.x = top-left, radii.y = top-right, radii.z = bottom-right, radii.w = bottom-left
+fn perimeter_position(frag_coord: vec2<f32>, position: vec2<f32>, size: vec2<f32>, radii: vec4<f32>) -> f32 {
+    let half_pi: f32 = 1.5707963267948966;
+
+    let p = frag_coord - position;
+    let w = size.x;
+    let h = size.y;
+
+    let top = max(w - radii.x - radii.y, 0.0);
+    let right = max(h - radii.y - radii.z, 0.0);
+    let bottom = max(w - radii.z - radii.w, 0.0);
+    let left = max(h - radii.w - radii.x, 0.0);
+
+    let top_start = 0.0;
+    let tr_start = top_start + top;
+    let right_start = tr_start + radii.y * half_pi;
+    let br_start = right_start + right;
+    let bottom_start = br_start + radii.z * half_pi;
+    let bl_start = bottom_start + bottom;
+    let left_start = bl_start + radii.w * half_pi;
+    let tl_start = left_start + left;
+
+    // Corners
+    if (p.x > w - radii.y && p.y < radii.y) {
+        let v = p - vec2<f32>(w - radii.y, radii.y);
+        let f = clamp((atan2(v.y, v.x) + half_pi) / half_pi, 0.0, 1.0);
+        return tr_start + f * radii.y * half_pi;
+    }
+    if (p.x > w - radii.z && p.y > h - radii.z) {
+        let v = p - vec2<f32>(w - radii.z, h - radii.z);
+        let f = clamp(atan2(v.y, v.x) / half_pi, 0.0, 1.0);
+        return br_start + f * radii.z * half_pi;
+    }
+    if (p.x < radii.w && p.y > h - radii.w) {
+        let v = p - vec2<f32>(radii.w, h - radii.w);
+        let f = clamp((atan2(v.y, v.x) - half_pi) / half_pi, 0.0, 1.0);
+        return bl_start + f * radii.w * half_pi;
+    }
+    if (p.x < radii.x && p.y < radii.x) {
+        let v = p - vec2<f32>(radii.x, radii.x);
+        var angle = atan2(v.y, v.x);
+        angle = select(angle, angle + 4.0 * half_pi, angle < 0.0);
+        let f = clamp((angle - 2.0 * half_pi) / half_pi, 0.0, 1.0);
+        return tl_start + f * radii.x * half_pi;
+    }
+
+    // Edges
+    let d_top = p.y;
+    let d_right = w - p.x;
+    let d_bottom = h - p.y;
+    let d_left = p.x;
+    let d_min = min(min(d_top, d_right), min(d_bottom, d_left));
+
+    if (d_min == d_top) {
+        return top_start + clamp(p.x - radii.x, 0.0, top);
+    }
+    if (d_min == d_right) {
+        return right_start + clamp(p.y - radii.y, 0.0, right);
+    }
+    if (d_min == d_bottom) {
+        return bottom_start + clamp(w - radii.z - p.x, 0.0, bottom);
+    }
+    return left_start + clamp(h - radii.w - p.y, 0.0, left);
+}
+
+// Returns the coverage of a dash pattern at the given arc-length position.
+// dash.x and dash.z are the lengths of the dashes, dash.y and dash.w are the
+// lengths of the gaps.
+fn dash_coverage(arc_position: f32, dash: vec4<f32>, offset: f32) -> f32 {
+    let pattern_len = dash.x + dash.y + dash.z + dash.w;
+    if (pattern_len <= 0.0) {
+        return 1.0;
+    }
+
+    let t = arc_position + offset;
+    let pos = t - pattern_len * floor(t / pattern_len);
+
+    let first = select(
+        0.0,
+        clamp(min(pos, dash.x - pos) + 0.5, 0.0, 1.0),
+        dash.x > 0.0
+    );
+
+    let second_start = dash.x + dash.y;
+    let second = select(
+        0.0,
+        clamp(min(pos - second_start, second_start + dash.z - pos) + 0.5, 0.0, 1.0),
+        dash.z > 0.0
+    );
+
+    // The end of the pattern wraps around to the start of the first dash.
+    let wrapped = pos - pattern_len;
+    let first_wrapped = select(
+        0.0,
+        clamp(min(wrapped, dash.x - wrapped) + 0.5, 0.0, 1.0),
+        dash.x > 0.0
+    );
+
+    return max(max(first, second), first_wrapped);
+}
+
+// The corner radii of the line running through the middle of the border.
+fn border_center_line_radii(border_radius: vec4<f32>, widths: vec4<f32>) -> vec4<f32> {
+    return max(
+        border_radius - 0.5 * vec4<f32>(
+            max(widths.x, widths.w),
+            max(widths.x, widths.y),
+            max(widths.z, widths.y),
+            max(widths.z, widths.w),
+        ),
+        vec4<f32>(0.0, 0.0, 0.0, 0.0)
+    );
+}
+
 // The following code was copied from
 // https://github.com/iced-rs/iced/blob/31d1d5fecbef50fa319cabd5d4194f1e4aaefa21/wgpu/src/shader/vertex.wgsl
 // Iced license (MIT): https://github.com/iced-rs/iced/blob/31d1d5fecbef50fa319cabd5d4194f1e4aaefa21/LICENSE
 
 // Compute the normalized quad coordinates based on the vertex index.
+// Applies the column-major 2 by 2 `transform` matrix to the position about the
+// given origin.
+fn transform_about(position: vec2<f32>, origin: vec2<f32>, transform: vec4<f32>) -> vec2<f32> {
+    return origin + mat2x2<f32>(transform.xy, transform.zw) * (position - origin);
+}
+
+// Whether the left and top edges of a quad are snapped to the nearest physical
+// pixel. The `1u` flag snaps all four edges, and the `8u` and `32u` flags snap
+// the left and top edges.
+fn snap_start_edges(flags: u32) -> vec2<bool> {
+    let all = (flags & 1u) != 0u;
+    return vec2<bool>(all || (flags & 8u) != 0u, all || (flags & 32u) != 0u);
+}
+
+// Whether the right and bottom edges of a quad are snapped to the nearest
+// physical pixel. The `1u` flag snaps all four edges, and the `16u` and `64u`
+// flags snap the right and bottom edges.
+fn snap_end_edges(flags: u32) -> vec2<bool> {
+    let all = (flags & 1u) != 0u;
+    return vec2<bool>(all || (flags & 16u) != 0u, all || (flags & 64u) != 0u);
+}
+
 fn vertex_position(vertex_index: u32) -> vec2<f32> {
     // #: 0 1 2 3 4 5
     // x: 1 1 0 0 0 1
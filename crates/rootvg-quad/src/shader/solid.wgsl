@@ -9,11 +9,16 @@ struct SolidVertexInput {
     @location(2) size: vec2<f32>,
     @location(3) border_color: vec4<f32>,
     @location(4) border_radius: vec4<f32>,
-    @location(5) border_width: f32,
+    @location(5) border_width: vec4<f32>,
     @location(6) flags: u32,
-    //@location(6) shadow_color: vec4<f32>,
-    //@location(7) shadow_offset: vec2<f32>,
-    //@location(8) shadow_blur_radius: f32,
+    @location(7) shadow_color: vec4<f32>,
+    @location(8) shadow_offset: vec2<f32>,
+    @location(9) shadow_blur_radius: f32,
+    @location(10) shadow_spread: f32,
+    @location(11) shadow_inset: u32,
+    @location(12) border_dash: vec4<f32>,
+    @location(13) border_dash_offset: f32,
+    @location(14) transform: vec4<f32>,
 }
 
 struct SolidVertexOutput {
@@ -23,10 +28,18 @@ struct SolidVertexOutput {
     @location(2) pos: vec2<f32>,
     @location(3) size: vec2<f32>,
     @location(4) border_radius: vec4<f32>,
-    @location(5) border_width: f32,
-    //@location(6) shadow_color: vec4<f32>,
-    //@location(7) shadow_offset: vec2<f32>,
-    //@location(8) shadow_blur_radius: f32,
+    @location(5) border_width: vec4<f32>,
+    @location(6) shadow_color: vec4<f32>,
+    @location(7) shadow_offset: vec2<f32>,
+    @location(8) shadow_blur_radius: f32,
+    @location(9) shadow_spread: f32,
+    @location(10) @interpolate(flat) shadow_inset: u32,
+    @location(11) border_dash: vec4<f32>,
+    @location(12) border_dash_offset: f32,
+    // The position of the fragment in the local space of the quad (before
+    // the transform is applied) in physical pixels.
+    @location(13) local_pos: vec2<f32>,
+    @location(14) @interpolate(flat) flags: u32,
 }
 
 @vertex
@@ -41,8 +54,20 @@ fn solid_vs_main(input: SolidVertexInput) -> SolidVertexOutput {
         min(input.border_radius.w, min_border_radius)
     );
 
+    // Expand the area covered by the quad so that the shadow doesn't get cut off.
+    var min_pos = input.pos;
+    var max_pos = input.pos + input.size;
+    // Inner shadows are drawn inside of the quad, so no expansion is needed.
+    if input.shadow_color.a > 0.0 && input.shadow_inset == 0u {
+        // Add an extra point to leave room for antialiasing.
+        let extent = max(input.shadow_spread, 0.0) + max(input.shadow_blur_radius, 0.0) + 1.0;
+
+        min_pos = min(min_pos, input.pos + input.shadow_offset - vec2<f32>(extent, extent));
+        max_pos = max(max_pos, input.pos + input.size + input.shadow_offset + vec2<f32>(extent, extent));
+    }
+
     var screen_pos: vec2<f32> =
-        (input.pos + (vertex_position(input.vertex_index) * input.size))
+        (min_pos + (vertex_position(input.vertex_index) * (max_pos - min_pos)))
         * globals.scale_factor;
 
     out.color = input.color;
@@ -51,30 +76,47 @@ fn solid_vs_main(input: SolidVertexInput) -> SolidVertexOutput {
     out.size = input.size * globals.scale_factor;
     out.border_radius = border_radius * globals.scale_factor;
     out.border_width = input.border_width * globals.scale_factor;
+    out.border_dash = input.border_dash * globals.scale_factor;
+    out.border_dash_offset = input.border_dash_offset * globals.scale_factor;
+    out.flags = input.flags;
+
+    // The border radius of an arc holds its angles and radius instead.
+    if (input.flags & 256u) != 0u {
+        out.border_radius = vec4<f32>(input.border_radius.xyz, input.border_radius.w * globals.scale_factor);
+    }
 
     // Snap edges to nearest physical pixel.
-    if (input.flags & 1u) > 0 {
-        let snapped_end_pos = round((input.pos + input.size) * globals.scale_factor);
+    let snap_start = snap_start_edges(input.flags);
+    let snap_end = snap_end_edges(input.flags);
+    if any(snap_start) || any(snap_end) {
+        let end_pos = (input.pos + input.size) * globals.scale_factor;
 
-        screen_pos = round(screen_pos);
-        out.pos = round(out.pos);
-        out.size = snapped_end_pos - out.pos;
+        // Snap the vertex along with the edges it lies on.
+        let snap_vertex = select(snap_start, snap_end, vertex_position(input.vertex_index) > vec2<f32>(0.5));
+        screen_pos = select(screen_pos, round(screen_pos), snap_vertex);
+        out.pos = select(out.pos, round(out.pos), snap_start);
+        out.size = select(end_pos, round(end_pos), snap_end) - out.pos;
     }
     // Snap border width to nearest physical pixel.
     if (input.flags & 2u) > 0 {
         out.border_width = round(out.border_width);
     }
 
+    out.local_pos = screen_pos;
+    screen_pos = transform_about(screen_pos, out.pos, input.transform);
+
     out.position = vec4<f32>(
-        (screen_pos.x * globals.screen_size_recip.x) - 1.0,
-        1.0 - (screen_pos.y * globals.screen_size_recip.y),
-        0.0,
+        ((screen_pos.x + globals.origin.x) * globals.screen_size_recip.x) - 1.0,
+        1.0 - ((screen_pos.y + globals.origin.y) * globals.screen_size_recip.y),
+        clip.depth,
         1.0
     );
 
-    //out.shadow_color = input.shadow_color;
-    //out.shadow_offset = input.shadow_offset * globals.scale_factor;
-    //out.shadow_blur_radius = input.shadow_blur_radius * globals.scale_factor;
+    out.shadow_color = input.shadow_color;
+    out.shadow_offset = input.shadow_offset * globals.scale_factor;
+    out.shadow_blur_radius = input.shadow_blur_radius * globals.scale_factor;
+    out.shadow_spread = input.shadow_spread * globals.scale_factor;
+    out.shadow_inset = input.shadow_inset;
 
     return out;
 }
@@ -83,64 +125,170 @@ fn solid_vs_main(input: SolidVertexInput) -> SolidVertexOutput {
 fn solid_fs_main(
     input: SolidVertexOutput
 ) -> @location(0) vec4<f32> {
+    // Arcs have no border or shadow.
+    if (input.flags & 256u) != 0u {
+        let arc_alpha = arc_coverage(
+            input.local_pos - (input.pos + input.size * 0.5),
+            input.border_radius,
+            input.border_width.x
+        );
+
+        return vec4<f32>(input.color.rgb, input.color.a * arc_alpha * clip_alpha(clip, input.position.xy - globals.origin));
+    }
+
     var mixed_color: vec4<f32> = input.color;
 
-    var border_radius = select_border_radius(
-        input.border_radius,
-        input.position.xy,
-        (input.pos + (input.size * 0.5)).xy
-    );
+    let is_ellipse = (input.flags & 128u) != 0u;
 
-    if (input.border_width > 0.0) {
-        var internal_border: f32 = max(border_radius - input.border_width, 0.0);
+    var radius_alpha: f32;
+    if is_ellipse {
+        let ellipse = ellipse_coverage(input.local_pos, input.pos, input.size, input.border_width);
 
-        var internal_distance: f32 = distance_alg(
-            input.position.xy,
-            input.pos + vec2<f32>(input.border_width, input.border_width),
-            input.size - vec2<f32>(input.border_width * 2.0, input.border_width * 2.0),
-            internal_border
+        mixed_color = mix(input.color, input.border_color, vec4<f32>(ellipse.y, ellipse.y, ellipse.y, ellipse.y));
+        radius_alpha = ellipse.x;
+    } else {
+        var border_radius = select_border_radius(
+            input.border_radius,
+            input.local_pos,
+            (input.pos + (input.size * 0.5)).xy
         );
 
-        var border_mix: f32 = smoothstep(
-            max(internal_border - 0.5, 0.0),
-            internal_border + 0.5,
-            internal_distance
+        if (any(input.border_width > vec4<f32>(0.0, 0.0, 0.0, 0.0))) {
+            // Blend between the widths of the two edges adjacent to the nearest corner.
+            let corner_widths = select_border_widths(
+                input.border_width,
+                input.local_pos,
+                (input.pos + (input.size * 0.5)).xy
+            );
+
+            var internal_border: f32 = max(border_radius - max(corner_widths.x, corner_widths.y), 0.0);
+
+            var internal_distance: f32 = distance_alg(
+                input.local_pos,
+                input.pos + input.border_width.wx,
+                input.size - input.border_width.wx - input.border_width.yz,
+                internal_border
+            );
+
+            var border_mix: f32 = smoothstep(
+                max(internal_border - 0.5, 0.0),
+                internal_border + 0.5,
+                internal_distance
+            );
+
+            // Apply the dash pattern along the line running through the middle of the border.
+            if (any(input.border_dash > vec4<f32>(0.0, 0.0, 0.0, 0.0))) {
+                let arc_position = perimeter_position(
+                    input.local_pos,
+                    input.pos + input.border_width.wx * 0.5,
+                    input.size - (input.border_width.wx + input.border_width.yz) * 0.5,
+                    border_center_line_radii(input.border_radius, input.border_width)
+                );
+
+                border_mix *= dash_coverage(arc_position, input.border_dash, input.border_dash_offset);
+            }
+
+            mixed_color = mix(input.color, input.border_color, vec4<f32>(border_mix, border_mix, border_mix, border_mix));
+        }
+
+        var dist: f32 = distance_alg(
+            input.local_pos,
+            input.pos,
+            input.size,
+            border_radius
         );
 
-        mixed_color = mix(input.color, input.border_color, vec4<f32>(border_mix, border_mix, border_mix, border_mix));
+        radius_alpha = 1.0 - smoothstep(
+            max(border_radius - 0.5, 0.0),
+            border_radius + 0.5,
+            dist
+        );
     }
 
-    var dist: f32 = distance_alg(
-        vec2<f32>(input.position.x, input.position.y),
-        input.pos,
-        input.size,
-        border_radius
-    );
+    if input.shadow_color.a > 0.0 && input.shadow_inset != 0u {
+        // The inner shadow is cast by the edges of the quad onto a rounded
+        // rectangle which is shrunk by the spread and moved by the offset.
+        let spread = vec2<f32>(input.shadow_spread, input.shadow_spread);
+        let inner_half_size = max(input.size * 0.5 - spread, vec2<f32>(0.0, 0.0));
+        let inner_center = input.pos + input.shadow_offset + (input.size * 0.5);
 
-    var radius_alpha: f32 = 1.0 - smoothstep(
-        max(border_radius - 0.5, 0.0),
-        border_radius + 0.5,
-        dist
-    );
+        var inner_radius = select_border_radius(
+            input.border_radius,
+            input.local_pos,
+            inner_center
+        );
+        inner_radius = clamp(
+            inner_radius - input.shadow_spread,
+            0.0,
+            min(inner_half_size.x, inner_half_size.y)
+        );
+
+        let inner_distance = select(
+            signed_rounded_box_sdf(input.local_pos - inner_center, inner_half_size, inner_radius),
+            ellipse_sdf(input.local_pos - inner_center, inner_half_size),
+            is_ellipse
+        );
+
+        let blur = max(input.shadow_blur_radius, 0.5);
+
+        // Invert the falloff so that the shadow is darkest outside of the inner
+        // rectangle.
+        let shadow_alpha = smoothstep(-blur, blur, inner_distance) * input.shadow_color.a;
+
+        // Composite the shadow over the background and border. The result is
+        // then clipped to the quad below.
+        let out_alpha = shadow_alpha + mixed_color.a * (1.0 - shadow_alpha);
+        if out_alpha > 0.0 {
+            let out_rgb = (input.shadow_color.rgb * shadow_alpha
+                + mixed_color.rgb * mixed_color.a * (1.0 - shadow_alpha)) / out_alpha;
+
+            mixed_color = vec4<f32>(out_rgb, out_alpha);
+        }
+    }
 
     let quad_color = vec4<f32>(mixed_color.x, mixed_color.y, mixed_color.z, mixed_color.w * radius_alpha);
 
-    /*
-    if input.shadow_color.a > 0.0 {
-        let shadow_distance = rounded_box_sdf(input.position.xy - input.pos - input.shadow_offset - (input.size / 2.0), input.size / 2.0, border_radius);
-        let shadow_alpha = 1.0 - smoothstep(-input.shadow_blur_radius, input.shadow_blur_radius, shadow_distance);
-        let shadow_color = input.shadow_color;
-        let base_color = select(
-            vec4<f32>(shadow_color.x, shadow_color.y, shadow_color.z, 0.0),
-            quad_color,
-            quad_color.a > 0.0
+    if input.shadow_color.a > 0.0 && input.shadow_inset == 0u {
+        let spread = vec2<f32>(input.shadow_spread, input.shadow_spread);
+        let shadow_half_size = max(input.size * 0.5 + spread, vec2<f32>(0.0, 0.0));
+        let shadow_center = input.pos + input.shadow_offset + (input.size * 0.5);
+
+        var shadow_radius = select_border_radius(
+            input.border_radius,
+            input.local_pos,
+            shadow_center
+        );
+        shadow_radius = clamp(
+            shadow_radius + input.shadow_spread,
+            0.0,
+            min(shadow_half_size.x, shadow_half_size.y)
         );
 
-        return mix(base_color, shadow_color, (1.0 - radius_alpha) * shadow_alpha);
-    } else {
-        return quad_color;
+        let shadow_distance = select(
+            signed_rounded_box_sdf(input.local_pos - shadow_center, shadow_half_size, shadow_radius),
+            ellipse_sdf(input.local_pos - shadow_center, shadow_half_size),
+            is_ellipse
+        );
+
+        let blur = max(input.shadow_blur_radius, 0.5);
+
+        // Clip the shadow out from under the quad so that it doesn't show through
+        // semi-transparent quads.
+        let shadow_alpha = (1.0 - smoothstep(-blur, blur, shadow_distance))
+            * input.shadow_color.a
+            * (1.0 - radius_alpha);
+
+        // Composite the quad over the shadow.
+        let out_alpha = quad_color.a + shadow_alpha * (1.0 - quad_color.a);
+        if out_alpha <= 0.0 {
+            return vec4<f32>(0.0, 0.0, 0.0, 0.0);
+        }
+
+        let out_rgb = (quad_color.rgb * quad_color.a
+            + input.shadow_color.rgb * shadow_alpha * (1.0 - quad_color.a)) / out_alpha;
+
+        return vec4<f32>(out_rgb, out_alpha * clip_alpha(clip, input.position.xy - globals.origin));
     }
-    */
 
-    return quad_color;
+    return vec4<f32>(quad_color.rgb, quad_color.a * clip_alpha(clip, input.position.xy - globals.origin));
 }
\ No newline at end of file
@@ -17,4 +17,12 @@ pub struct Shadow {
 
     /// The blur radius of the shadow in logical points.
     pub blur_radius: f32,
+
+    /// The amount the shadow is expanded (or contracted if negative) in
+    /// logical points before the blur is applied.
+    pub spread: f32,
+
+    /// Whether this is an inner shadow that is drawn inside of the quad
+    /// instead of behind it.
+    pub inset: bool,
 }
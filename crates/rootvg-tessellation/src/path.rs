@@ -3,15 +3,30 @@
 // Iced license (MIT): https://github.com/iced-rs/iced/blob/31d1d5fecbef50fa319cabd5d4194f1e4aaefa21/LICENSE
 
 mod arc;
+mod boolean;
 mod builder;
+mod hit_test;
+mod offset;
+mod svg;
 
 #[doc(no_inline)]
 pub use arc::{ArcPath, EllipticalArcPath};
 pub use builder::PathBuilder;
+pub use svg::ParseSvgPathError;
 
 //pub use lyon::path as lyon_path;
 
-use rootvg_core::math::{Point, Size};
+#[doc(no_inline)]
+pub use lyon::path::PathEvent;
+
+use lyon::path::iterator::PathIterator;
+use rootvg_core::math::{Point, Rect, Size};
+
+use crate::fill::FillRule;
+use crate::stroke::LineJoin;
+
+/// The tolerance used to flatten curves when measuring a [`Path`].
+const MEASURE_TOLERANCE: f32 = 0.01;
 
 /// An immutable set of points that may or may not be connected.
 ///
@@ -44,10 +59,246 @@ impl Path {
         PathBuilder::new().circle(center, radius).build()
     }
 
+    /// Parses a [`Path`] from SVG path data, such as the `d` attribute of a
+    /// `<path>` element.
+    ///
+    /// All of the commands are supported in both their absolute and relative
+    /// forms. Elliptical arcs are converted into Bézier curves.
+    ///
+    /// Returns an error if the path data is malformed. Unlike an SVG renderer,
+    /// this does not keep the part of the path before the error.
+    pub fn from_svg_path_data(data: &str) -> Result<Self, ParseSvgPathError> {
+        svg::parse(data)
+    }
+
     /// Returns the current [`Path`] with the given transform applied to it.
     pub fn transform(&self, transform: &lyon::path::math::Transform) -> Path {
         Path {
             raw: self.raw.clone().transformed(transform),
         }
     }
+
+    /// Returns an iterator over the events of the [`Path`] with its curves
+    /// flattened into line segments.
+    ///
+    /// Every sub-path starts with a [`PathEvent::Begin`], followed by
+    /// [`PathEvent::Line`]s and a [`PathEvent::End`] which tells whether the
+    /// sub-path is closed. Bézier curves are subdivided so that the segments
+    /// are never further than `tolerance` away from them, the same way the
+    /// tessellator flattens them.
+    pub fn flattened(&self, tolerance: f32) -> impl Iterator<Item = PathEvent> + '_ {
+        self.raw.iter().flattened(tolerance)
+    }
+
+    /// Returns the smallest rectangle containing the [`Path`].
+    ///
+    /// Curves are flattened before measuring, so the bounds are tight around
+    /// the curves instead of including their control points. An empty path
+    /// has zero-sized bounds at the origin.
+    pub fn bounds(&self) -> Rect {
+        let mut min = lyon::math::Point::new(f32::MAX, f32::MAX);
+        let mut max = lyon::math::Point::new(f32::MIN, f32::MIN);
+
+        for event in self.flattened(MEASURE_TOLERANCE) {
+            let point = match event {
+                PathEvent::Begin { at } => at,
+                PathEvent::Line { to, .. } => to,
+                _ => continue,
+            };
+
+            min = min.min(point);
+            max = max.max(point);
+        }
+
+        if min.x > max.x {
+            return Rect::zero();
+        }
+
+        Rect::new(
+            Point::new(min.x, min.y),
+            Size::new(max.x - min.x, max.y - min.y),
+        )
+    }
+
+    /// Returns the smallest rectangle containing the [`Path`] when it is
+    /// stroked with the given line width.
+    ///
+    /// This expands [`Path::bounds`] by half of the line width on each side,
+    /// so it does not account for miter joins and square caps which extend
+    /// further than that.
+    pub fn bounds_with_stroke(&self, width: f32) -> Rect {
+        if self.raw.iter().next().is_none() {
+            return Rect::zero();
+        }
+
+        self.bounds().inflate(width * 0.5, width * 0.5)
+    }
+
+    /// Returns the total length of all of the sub-paths in the [`Path`],
+    /// including the closing segments of closed sub-paths.
+    ///
+    /// This is an estimate which is computed from the flattened curves, so it
+    /// may be slightly shorter than the exact length.
+    pub fn length(&self) -> f32 {
+        self.flattened(MEASURE_TOLERANCE)
+            .map(|event| match event {
+                PathEvent::Line { from, to } => (to - from).length(),
+                PathEvent::End {
+                    last,
+                    first,
+                    close: true,
+                } => (first - last).length(),
+                _ => 0.0,
+            })
+            .sum()
+    }
+
+    /// Returns a new [`Path`] covering the area which is covered by either
+    /// this path or the other one when they are filled.
+    ///
+    /// Both paths are filled with the non-zero fill rule, so self-intersecting
+    /// sub-paths and overlapping sub-paths are merged together. The result has
+    /// no overlapping sub-paths, and its holes wind in the opposite direction
+    /// to the outlines around them, so it looks the same with either fill rule.
+    ///
+    /// Curves are flattened, so the result only consists of straight lines.
+    pub fn union(&self, other: &Path) -> Path {
+        boolean::combine(self, other, boolean::Operation::Union)
+    }
+
+    /// Returns a new [`Path`] covering the area which is covered by both this
+    /// path and the other one when they are filled.
+    ///
+    /// See [`Path::union`] for how the paths are combined.
+    pub fn intersection(&self, other: &Path) -> Path {
+        boolean::combine(self, other, boolean::Operation::Intersection)
+    }
+
+    /// Returns a new [`Path`] covering the area which is covered by this path
+    /// but not by the other one when they are filled.
+    ///
+    /// See [`Path::union`] for how the paths are combined.
+    pub fn difference(&self, other: &Path) -> Path {
+        boolean::combine(self, other, boolean::Operation::Difference)
+    }
+
+    /// Returns a new [`Path`] with the area covered by this path when it is
+    /// filled grown outwards by the given distance, or shrunk inwards if the
+    /// distance is negative.
+    ///
+    /// The `join` is the shape of the corners which the offset outline goes
+    /// around, which are the convex corners when growing and the concave
+    /// corners when shrinking. Miter joins which are longer than the default
+    /// miter limit of a [`Stroke`] are bevelled instead. The loops which form
+    /// where the offset edges cross each other are removed, and parts which
+    /// are thinner than twice the distance disappear when shrinking.
+    ///
+    /// See [`Path::union`] for how the path is filled.
+    ///
+    /// [`Stroke`]: crate::stroke::Stroke
+    pub fn offset(&self, distance: f32, join: LineJoin) -> Path {
+        if distance == 0.0 || !distance.is_finite() {
+            return self.clone();
+        }
+
+        offset::offset(self, distance, join)
+    }
+
+    /// Returns `true` if the given point is inside of the area which is
+    /// covered when the [`Path`] is filled with the given fill rule.
+    ///
+    /// Sub-paths which are not closed are treated as if they were, the same
+    /// way they are when filling the path. Points which lie exactly on an
+    /// edge of the path are considered to be inside of it.
+    ///
+    /// Curves are flattened before testing, so points very close to a curve
+    /// may be classified as if they were on the other side of it.
+    pub fn contains(&self, point: Point, fill_rule: FillRule) -> bool {
+        hit_test::fill_contains(self, lyon::math::point(point.x, point.y), fill_rule)
+    }
+
+    /// Returns `true` if the given point is within `width / 2 + tolerance` of
+    /// the outline of the [`Path`], i.e. if it is on the line drawn when the
+    /// path is stroked with the given line width.
+    ///
+    /// The distance is measured as if the stroke had round caps and joins.
+    /// The `tolerance` makes it easier to hit thin lines, such as cables
+    /// between the nodes of a graph.
+    ///
+    /// Curves are flattened before testing, so the distance to a curve may be
+    /// off by a tiny fraction of a point.
+    pub fn stroke_contains(&self, point: Point, width: f32, tolerance: f32) -> bool {
+        hit_test::stroke_contains(
+            self,
+            lyon::math::point(point.x, point.y),
+            (width * 0.5 + tolerance).max(0.0),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_rect(a: Rect, b: Rect, tolerance: f32) {
+        assert!(
+            (a.min_x() - b.min_x()).abs() < tolerance
+                && (a.min_y() - b.min_y()).abs() < tolerance
+                && (a.width() - b.width()).abs() < tolerance
+                && (a.height() - b.height()).abs() < tolerance,
+            "{a:?} != {b:?}"
+        );
+    }
+
+    #[test]
+    fn rectangle_bounds_and_perimeter() {
+        let rect = Rect::new(Point::new(-5.0, 10.0), Size::new(20.0, 30.0));
+        let path = Path::rectangle(rect.origin, rect.size);
+
+        assert_rect(path.bounds(), rect, 1e-3);
+        assert_rect(
+            path.bounds_with_stroke(4.0),
+            Rect::new(Point::new(-7.0, 8.0), Size::new(24.0, 34.0)),
+            1e-3,
+        );
+        assert!((path.length() - 100.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn curve_bounds_exclude_control_points() {
+        let path = Path::circle(Point::new(10.0, 20.0), 5.0);
+
+        assert_rect(
+            path.bounds(),
+            Rect::new(Point::new(5.0, 15.0), Size::new(10.0, 10.0)),
+            MEASURE_TOLERANCE * 2.0,
+        );
+        assert!((path.length() - 10.0 * std::f32::consts::PI).abs() < 0.05);
+    }
+
+    #[test]
+    fn length_adds_up_sub_paths() {
+        let path = PathBuilder::new()
+            .move_to(Point::new(0.0, 0.0))
+            .line_to(Point::new(3.0, 4.0))
+            .move_to(Point::new(10.0, 0.0))
+            .line_to(Point::new(10.0, 2.0))
+            .build();
+
+        assert!((path.length() - 7.0).abs() < 1e-3);
+        assert_rect(
+            path.bounds(),
+            Rect::new(Point::new(0.0, 0.0), Size::new(10.0, 4.0)),
+            1e-3,
+        );
+    }
+
+    #[test]
+    fn empty_path_has_zero_bounds_and_length() {
+        let path = PathBuilder::new().build();
+
+        assert_eq!(path.bounds(), Rect::zero());
+        assert_eq!(path.bounds_with_stroke(4.0), Rect::zero());
+        assert_eq!(path.length(), 0.0);
+    }
 }
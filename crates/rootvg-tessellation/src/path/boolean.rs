@@ -0,0 +1,387 @@
+//! Boolean operations which combine the filled areas of two [`Path`]s.
+//!
+//! Both paths are flattened into polygons, and every edge is split at the
+//! points where it crosses or overlaps another edge. Each of the resulting
+//! edges is then kept if the area on one side of it is inside of the result
+//! and the area on the other side isn't, and the kept edges are linked back up
+//! into closed sub-paths.
+
+use std::collections::HashSet;
+
+use lyon::math::{Point, Vector};
+use rootvg_core::math;
+
+use super::hit_test::crossing;
+use super::{Path, PathBuilder, PathEvent};
+
+/// The tolerance used to flatten curves before combining [`Path`]s.
+pub(super) const FLATTEN_TOLERANCE: f32 = 0.01;
+
+/// Points which are closer together than this are merged into one.
+const MERGE_DISTANCE: f32 = 1e-4;
+
+/// How far from the middle of an edge the areas on either side of it are
+/// sampled.
+const SIDE_OFFSET: f32 = 1e-3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Operation {
+    Union,
+    Intersection,
+    Difference,
+}
+
+impl Operation {
+    fn contains(self, in_a: bool, in_b: bool) -> bool {
+        match self {
+            Self::Union => in_a || in_b,
+            Self::Intersection => in_a && in_b,
+            Self::Difference => in_a && !in_b,
+        }
+    }
+}
+
+pub(super) fn combine(a: &Path, b: &Path, operation: Operation) -> Path {
+    combine_contours(&contours(a), &contours(b), operation)
+}
+
+/// Combines the areas covered by two sets of polygons when they are filled
+/// with the non-zero fill rule.
+pub(super) fn combine_contours(a: &[Vec<Point>], b: &[Vec<Point>], operation: Operation) -> Path {
+    let edges: Vec<(Point, Point)> = a
+        .iter()
+        .chain(b.iter())
+        .flat_map(|contour| contour_edges(contour))
+        .collect();
+
+    let mut vertices = Vertices::default();
+    let mut kept: Vec<(usize, usize)> = Vec::new();
+
+    for (from, to) in split_at_intersections(&edges) {
+        let from_index = vertices.insert(from);
+        let to_index = vertices.insert(to);
+        if from_index == to_index {
+            continue;
+        }
+
+        let inside = |point: Point| {
+            // Both paths are filled with the non-zero fill rule.
+            operation.contains(winding_number(point, a) != 0, winding_number(point, b) != 0)
+        };
+
+        let middle = from.lerp(to, 0.5);
+        let normal = normal(to - from) * SIDE_OFFSET;
+        let inside_left = inside(middle + normal);
+        let inside_right = inside(middle - normal);
+
+        // Orient every kept edge so that the result is on the same side of
+        // it, which makes the outer boundaries and the holes wind in opposite
+        // directions.
+        if inside_left && !inside_right {
+            kept.push((from_index, to_index));
+        } else if inside_right && !inside_left {
+            kept.push((to_index, from_index));
+        }
+    }
+
+    // Edges which overlap are split into identical pieces, so only one of
+    // them is kept. Pieces which go back and forth enclose no area at all.
+    kept.sort_unstable();
+    kept.dedup();
+    let set: HashSet<(usize, usize)> = kept.iter().copied().collect();
+    kept.retain(|&(from, to)| !set.contains(&(to, from)));
+
+    link(&vertices.points, &kept)
+}
+
+/// Flattens the [`Path`] into polygons. Every sub-path is implicitly closed,
+/// the same way it is when the path is filled.
+pub(super) fn contours(path: &Path) -> Vec<Vec<Point>> {
+    let mut contours: Vec<Vec<Point>> = Vec::new();
+
+    for event in path.flattened(FLATTEN_TOLERANCE) {
+        match event {
+            PathEvent::Begin { at } => contours.push(vec![at]),
+            PathEvent::Line { to, .. } => {
+                if let Some(contour) = contours.last_mut() {
+                    contour.push(to);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    contours.retain(|contour| contour.len() > 2);
+    contours
+}
+
+pub(super) fn contour_edges(contour: &[Point]) -> impl Iterator<Item = (Point, Point)> + '_ {
+    let closing = (contour[contour.len() - 1], contour[0]);
+
+    contour
+        .windows(2)
+        .map(|pair| (pair[0], pair[1]))
+        .chain(std::iter::once(closing))
+        .filter(|(from, to)| from != to)
+}
+
+fn winding_number(point: Point, contours: &[Vec<Point>]) -> i32 {
+    contours
+        .iter()
+        .flat_map(|contour| contour_edges(contour))
+        .map(|(from, to)| crossing(point, from, to))
+        .sum()
+}
+
+/// Splits every edge at the points where it crosses another edge, and at the
+/// end points of the edges which it overlaps with.
+fn split_at_intersections(edges: &[(Point, Point)]) -> Vec<(Point, Point)> {
+    // The position along each edge and the point where it is split.
+    let mut splits: Vec<Vec<(f32, Point)>> = vec![Vec::new(); edges.len()];
+
+    for (i, &(p0, p1)) in edges.iter().enumerate() {
+        for (j, &(q0, q1)) in edges.iter().enumerate().skip(i + 1) {
+            let r = p1 - p0;
+            let s = q1 - q0;
+            let denom = r.cross(s);
+            let offset = q0 - p0;
+
+            if denom.abs() > f32::EPSILON * r.length() * s.length() {
+                let t = offset.cross(s) / denom;
+                let u = offset.cross(r) / denom;
+
+                if (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u) {
+                    // Both edges are split at the exact same point, so that
+                    // their pieces link up.
+                    let point = p0 + r * t;
+                    splits[i].push((t, point));
+                    splits[j].push((u, point));
+                }
+            } else if offset.cross(r).abs() <= MERGE_DISTANCE * r.length() {
+                // The edges are collinear, so split each of them at the end
+                // points of the other one which lie on it.
+                for point in [q0, q1] {
+                    let t = (point - p0).dot(r) / r.square_length();
+                    if t > 0.0 && t < 1.0 {
+                        splits[i].push((t, point));
+                    }
+                }
+                for point in [p0, p1] {
+                    let u = (point - q0).dot(s) / s.square_length();
+                    if u > 0.0 && u < 1.0 {
+                        splits[j].push((u, point));
+                    }
+                }
+            }
+        }
+    }
+
+    let mut pieces = Vec::with_capacity(edges.len());
+
+    for (&(from, to), mut splits) in edges.iter().zip(splits) {
+        splits.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut start = from;
+        for (_, point) in splits {
+            pieces.push((start, point));
+            start = point;
+        }
+        pieces.push((start, to));
+    }
+
+    pieces
+}
+
+/// The unit vector perpendicular to the given direction.
+pub(super) fn normal(direction: Vector) -> Vector {
+    let length = direction.length();
+    if length > 0.0 {
+        Vector::new(-direction.y, direction.x) / length
+    } else {
+        Vector::zero()
+    }
+}
+
+#[derive(Default)]
+struct Vertices {
+    points: Vec<Point>,
+}
+
+impl Vertices {
+    /// Returns the index of the vertex at the given point, adding it if there
+    /// is no vertex close enough to it yet.
+    fn insert(&mut self, point: Point) -> usize {
+        if let Some(index) = self
+            .points
+            .iter()
+            .position(|p| (*p - point).square_length() <= MERGE_DISTANCE * MERGE_DISTANCE)
+        {
+            return index;
+        }
+
+        self.points.push(point);
+        self.points.len() - 1
+    }
+}
+
+/// Links the edges up into closed sub-paths.
+fn link(vertices: &[Point], edges: &[(usize, usize)]) -> Path {
+    let mut outgoing: Vec<Vec<usize>> = vec![Vec::new(); vertices.len()];
+    for (index, &(from, _)) in edges.iter().enumerate() {
+        outgoing[from].push(index);
+    }
+
+    let mut used = vec![false; edges.len()];
+    let mut builder = PathBuilder::new();
+
+    for start in 0..edges.len() {
+        if used[start] {
+            continue;
+        }
+
+        let mut contour: Vec<Point> = Vec::new();
+        let mut edge = start;
+
+        loop {
+            used[edge] = true;
+            contour.push(vertices[edges[edge].0]);
+
+            let to = edges[edge].1;
+            match outgoing[to].iter().find(|&&next| !used[next]) {
+                Some(&next) => edge = next,
+                None => break,
+            }
+        }
+
+        let contour = remove_collinear(contour);
+        if contour.len() > 2 {
+            builder = builder.polygon(&contour);
+        }
+    }
+
+    builder.build()
+}
+
+/// Removes the points in the middle of straight runs, which are left over from
+/// where edges were split.
+fn remove_collinear(contour: Vec<Point>) -> Vec<math::Point> {
+    let len = contour.len();
+
+    (0..len)
+        .filter(|&i| {
+            let prev = contour[(i + len - 1) % len];
+            let point = contour[i];
+            let next = contour[(i + 1) % len];
+
+            let a = point - prev;
+            let b = next - point;
+            a.cross(b).abs() > MERGE_DISTANCE * (a.length() + b.length()) || a.dot(b) < 0.0
+        })
+        .map(|i| math::Point::new(contour[i].x, contour[i].y))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rootvg_core::math::Size;
+
+    use super::*;
+    use crate::fill::FillRule;
+
+    fn rectangle(x: f32, y: f32, width: f32, height: f32) -> Path {
+        Path::rectangle(math::Point::new(x, y), Size::new(width, height))
+    }
+
+    /// The filled area of the path, where holes which wind in the opposite
+    /// direction to their outline are subtracted.
+    fn area(path: &Path) -> f32 {
+        let twice_area: f32 = contours(path)
+            .iter()
+            .flat_map(|contour| contour_edges(contour))
+            .map(|(from, to)| from.to_vector().cross(to.to_vector()))
+            .sum();
+
+        twice_area.abs() * 0.5
+    }
+
+    fn num_vertices(path: &Path) -> Vec<usize> {
+        contours(path).iter().map(Vec::len).collect()
+    }
+
+    fn assert_bounds(path: &Path, x: f32, y: f32, width: f32, height: f32) {
+        let bounds = path.bounds();
+
+        assert!((bounds.min_x() - x).abs() < 1e-3, "{bounds:?}");
+        assert!((bounds.min_y() - y).abs() < 1e-3, "{bounds:?}");
+        assert!((bounds.width() - width).abs() < 1e-3, "{bounds:?}");
+        assert!((bounds.height() - height).abs() < 1e-3, "{bounds:?}");
+    }
+
+    #[test]
+    fn union_of_overlapping_rectangles() {
+        let path = rectangle(0.0, 0.0, 10.0, 10.0).union(&rectangle(5.0, 5.0, 10.0, 10.0));
+
+        assert!((area(&path) - 175.0).abs() < 1e-3);
+        assert_eq!(num_vertices(&path), [8]);
+        assert_bounds(&path, 0.0, 0.0, 15.0, 15.0);
+    }
+
+    #[test]
+    fn intersection_of_overlapping_rectangles() {
+        let path = rectangle(0.0, 0.0, 10.0, 10.0).intersection(&rectangle(5.0, 5.0, 10.0, 10.0));
+
+        assert!((area(&path) - 25.0).abs() < 1e-3);
+        assert_eq!(num_vertices(&path), [4]);
+        assert_bounds(&path, 5.0, 5.0, 5.0, 5.0);
+    }
+
+    #[test]
+    fn difference_of_overlapping_rectangles() {
+        let path = rectangle(0.0, 0.0, 10.0, 10.0).difference(&rectangle(5.0, 5.0, 10.0, 10.0));
+
+        assert!((area(&path) - 75.0).abs() < 1e-3);
+        assert_eq!(num_vertices(&path), [6]);
+        assert_bounds(&path, 0.0, 0.0, 10.0, 10.0);
+
+        assert!(path.contains(math::Point::new(2.0, 2.0), FillRule::NonZero));
+        assert!(!path.contains(math::Point::new(7.0, 7.0), FillRule::NonZero));
+    }
+
+    #[test]
+    fn difference_inside_of_a_rectangle_leaves_a_hole() {
+        let path = rectangle(0.0, 0.0, 10.0, 10.0).difference(&rectangle(3.0, 3.0, 4.0, 4.0));
+
+        assert!((area(&path) - 84.0).abs() < 1e-3);
+        assert_eq!(num_vertices(&path), [4, 4]);
+
+        for fill_rule in [FillRule::NonZero, FillRule::EvenOdd] {
+            assert!(path.contains(math::Point::new(1.0, 1.0), fill_rule));
+            assert!(!path.contains(math::Point::new(5.0, 5.0), fill_rule));
+        }
+    }
+
+    #[test]
+    fn union_merges_adjacent_rectangles() {
+        let path = rectangle(0.0, 0.0, 10.0, 10.0).union(&rectangle(10.0, 0.0, 10.0, 10.0));
+
+        assert!((area(&path) - 200.0).abs() < 1e-3);
+        assert_eq!(num_vertices(&path), [4]);
+        assert_bounds(&path, 0.0, 0.0, 20.0, 10.0);
+    }
+
+    #[test]
+    fn disjoint_rectangles() {
+        let a = rectangle(0.0, 0.0, 10.0, 10.0);
+        let b = rectangle(20.0, 0.0, 10.0, 10.0);
+
+        let union = a.union(&b);
+        assert!((area(&union) - 200.0).abs() < 1e-3);
+        assert_eq!(num_vertices(&union), [4, 4]);
+
+        assert!(num_vertices(&a.intersection(&b)).is_empty());
+
+        let difference = a.difference(&b);
+        assert!((area(&difference) - 100.0).abs() < 1e-3);
+        assert_eq!(num_vertices(&difference), [4]);
+    }
+}
@@ -38,6 +38,91 @@ impl PathBuilder {
         self
     }
 
+    /// Adds a new sub-path to the [`Path`] connecting the given points with
+    /// straight lines.
+    ///
+    /// This does nothing if less than two points are given.
+    pub fn polyline(mut self, points: &[Point]) -> Self {
+        let [first, rest @ ..] = points else {
+            return self;
+        };
+        if rest.is_empty() {
+            return self;
+        }
+
+        self = self.move_to(*first);
+        for point in rest.iter() {
+            self = self.line_to(*point);
+        }
+
+        self
+    }
+
+    /// Adds a new closed sub-path to the [`Path`] connecting the given points
+    /// with straight lines.
+    ///
+    /// This does nothing if less than two points are given.
+    pub fn polygon(self, points: &[Point]) -> Self {
+        if points.len() < 2 {
+            return self;
+        }
+
+        self.polyline(points).close()
+    }
+
+    /// Adds a new sub-path to the [`Path`] with a smooth Catmull-Rom spline
+    /// which passes through all of the given points.
+    ///
+    /// The spline is converted into a series of cubic Bézier curves. The
+    /// `tension` is the exponent used to space out the knots of the spline,
+    /// where `0.0` is a uniform spline, `0.5` is a centripetal spline, and
+    /// `1.0` is a chordal spline. A centripetal spline (`0.5`) is recommended
+    /// since it never forms cusps or self-intersections within a segment.
+    ///
+    /// The tangents at the first and last points are computed by mirroring
+    /// their neighboring points, so the spline starts and ends heading
+    /// straight towards its neighbors.
+    ///
+    /// This does nothing if less than two points are given.
+    pub fn catmull_rom(mut self, points: &[Point], tension: f32) -> Self {
+        let n = points.len();
+        if n < 2 {
+            return self;
+        }
+
+        let point_at = |i: usize| -> Point {
+            if i == 0 {
+                points[0] + (points[0] - points[1])
+            } else if i > n {
+                points[n - 1] + (points[n - 1] - points[n - 2])
+            } else {
+                points[i - 1]
+            }
+        };
+
+        self = self.move_to(points[0]);
+
+        for i in 0..n - 1 {
+            // `point_at` is offset by one so that the mirrored first point is
+            // at index zero.
+            let p0 = point_at(i);
+            let p1 = points[i];
+            let p2 = points[i + 1];
+            let p3 = point_at(i + 3);
+
+            // Skip duplicated points.
+            if p1 == p2 {
+                continue;
+            }
+
+            let (control_a, control_b) = catmull_rom_control_points(p0, p1, p2, p3, tension);
+
+            self = self.bezier_curve_to(control_a, control_b, p2);
+        }
+
+        self
+    }
+
     /// Adds an [`Arc`] to the [`Path`] from `start_angle` to `end_angle` in
     /// a clockwise direction.
     pub fn arc(self, arc: ArcPath) -> Self {
@@ -190,3 +275,209 @@ impl Default for PathBuilder {
         Self::new()
     }
 }
+
+/// Returns the two Bézier control points of the Catmull-Rom segment between
+/// `p1` and `p2`.
+///
+/// See "On the parameterization of Catmull-Rom curves" by Yuksel et al.
+fn catmull_rom_control_points(
+    p0: Point,
+    p1: Point,
+    p2: Point,
+    p3: Point,
+    tension: f32,
+) -> (Point, Point) {
+    let d1 = (p1 - p0).length().powf(tension);
+    let d2 = (p2 - p1).length().powf(tension);
+    let d3 = (p3 - p2).length().powf(tension);
+
+    let control_a = if d1 > 0.0 {
+        ((p2.to_vector() * (d1 * d1)) - (p0.to_vector() * (d2 * d2))
+            + (p1.to_vector() * (2.0 * d1 * d1 + 3.0 * d1 * d2 + d2 * d2)))
+            / (3.0 * d1 * (d1 + d2))
+    } else {
+        p1.to_vector()
+    };
+
+    let control_b = if d3 > 0.0 {
+        ((p1.to_vector() * (d3 * d3)) - (p3.to_vector() * (d2 * d2))
+            + (p2.to_vector() * (2.0 * d3 * d3 + 3.0 * d3 * d2 + d2 * d2)))
+            / (3.0 * d3 * (d3 + d2))
+    } else {
+        p2.to_vector()
+    };
+
+    (control_a.to_point(), control_b.to_point())
+}
+
+#[cfg(test)]
+mod tests {
+    use lyon::path::PathEvent;
+    use rootvg_core::math::Rect;
+
+    use super::*;
+
+    fn cubics(path: &Path) -> Vec<[math::Point; 4]> {
+        path.raw
+            .iter()
+            .filter_map(|event| match event {
+                PathEvent::Cubic {
+                    from,
+                    ctrl1,
+                    ctrl2,
+                    to,
+                } => Some([from, ctrl1, ctrl2, to]),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn assert_near(a: math::Point, b: Point) {
+        assert!(
+            (a.x - b.x).abs() < 1e-4 && (a.y - b.y).abs() < 1e-4,
+            "{a:?} != {b:?}"
+        );
+    }
+
+    /// Asserts that the spline passes through every point, and that the
+    /// tangents on both sides of each inner point have the same direction.
+    fn assert_spline(points: &[Point], tension: f32) {
+        let path = PathBuilder::new().catmull_rom(points, tension).build();
+        let cubics = cubics(&path);
+
+        assert_eq!(cubics.len(), points.len() - 1);
+
+        for (cubic, pair) in cubics.iter().zip(points.windows(2)) {
+            assert_near(cubic[0], pair[0]);
+            assert_near(cubic[3], pair[1]);
+        }
+
+        for pair in cubics.windows(2) {
+            let joint = pair[0][3];
+            let incoming = joint - pair[0][2];
+            let outgoing = pair[1][1] - joint;
+
+            let sin = incoming.cross(outgoing) / (incoming.length() * outgoing.length());
+            assert!(sin.abs() < 1e-4, "{incoming:?} {outgoing:?}");
+            assert!(incoming.dot(outgoing) > 0.0, "{incoming:?} {outgoing:?}");
+        }
+    }
+
+    #[test]
+    fn catmull_rom_with_two_points_is_a_straight_line() {
+        let from = Point::new(0.0, 0.0);
+        let to = Point::new(30.0, 15.0);
+
+        for tension in [0.0, 0.5, 1.0] {
+            let path = PathBuilder::new().catmull_rom(&[from, to], tension).build();
+            let cubics = cubics(&path);
+
+            // The mirrored end points put the control points on the line,
+            // a third of the way from each end.
+            assert_eq!(cubics.len(), 1);
+            assert_near(cubics[0][0], from);
+            assert_near(cubics[0][1], Point::new(10.0, 5.0));
+            assert_near(cubics[0][2], Point::new(20.0, 10.0));
+            assert_near(cubics[0][3], to);
+        }
+    }
+
+    #[test]
+    fn catmull_rom_with_three_points() {
+        let points = [
+            Point::new(0.0, 0.0),
+            Point::new(10.0, 0.0),
+            Point::new(10.0, 10.0),
+        ];
+
+        for tension in [0.0, 0.5, 1.0] {
+            assert_spline(&points, tension);
+        }
+    }
+
+    #[test]
+    fn catmull_rom_with_many_points() {
+        let points = [
+            Point::new(0.0, 0.0),
+            Point::new(10.0, 5.0),
+            Point::new(20.0, -5.0),
+            Point::new(30.0, 10.0),
+            Point::new(35.0, 0.0),
+            Point::new(50.0, 3.0),
+        ];
+
+        for tension in [0.0, 0.5, 1.0] {
+            assert_spline(&points, tension);
+        }
+    }
+
+    #[test]
+    fn catmull_rom_skips_duplicated_points() {
+        let points = [
+            Point::new(0.0, 0.0),
+            Point::new(10.0, 0.0),
+            Point::new(10.0, 0.0),
+            Point::new(20.0, 10.0),
+        ];
+
+        let path = PathBuilder::new().catmull_rom(&points, 0.5).build();
+        let cubics = cubics(&path);
+
+        assert_eq!(cubics.len(), 2);
+        assert_near(cubics[0][3], points[1]);
+        assert_near(cubics[1][0], points[2]);
+        assert_near(cubics[1][3], points[3]);
+    }
+
+    #[test]
+    fn catmull_rom_with_less_than_two_points_does_nothing() {
+        for points in [&[][..], &[Point::new(1.0, 2.0)][..]] {
+            let path = PathBuilder::new().catmull_rom(points, 0.5).build();
+
+            assert!(path.raw.iter().next().is_none());
+        }
+    }
+
+    /// A 3-4-5 right triangle.
+    fn triangle() -> [Point; 3] {
+        [
+            Point::new(10.0, 10.0),
+            Point::new(13.0, 10.0),
+            Point::new(13.0, 14.0),
+        ]
+    }
+
+    #[test]
+    fn polyline_is_open() {
+        let path = PathBuilder::new().polyline(&triangle()).build();
+        let events: Vec<PathEvent> = path.raw.iter().collect();
+
+        assert_eq!(events.len(), 4);
+        assert!(matches!(events[3], PathEvent::End { close: false, .. }));
+        assert!((path.length() - 7.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn polygon_is_closed() {
+        let path = PathBuilder::new().polygon(&triangle()).build();
+        let events: Vec<PathEvent> = path.raw.iter().collect();
+
+        assert_eq!(events.len(), 4);
+        assert!(matches!(events[3], PathEvent::End { close: true, .. }));
+        assert!((path.length() - 12.0).abs() < 1e-3);
+
+        assert_eq!(
+            path.bounds(),
+            Rect::new(Point::new(10.0, 10.0), Size::new(3.0, 4.0))
+        );
+    }
+
+    #[test]
+    fn polyline_and_polygon_with_less_than_two_points_do_nothing() {
+        for points in [&[][..], &[Point::new(1.0, 2.0)][..]] {
+            let path = PathBuilder::new().polyline(points).polygon(points).build();
+
+            assert!(path.raw.iter().next().is_none());
+        }
+    }
+}
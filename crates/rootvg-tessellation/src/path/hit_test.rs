@@ -0,0 +1,250 @@
+//! Testing whether a point is inside of a filled or stroked [`Path`].
+
+use lyon::math::Point;
+
+use super::{Path, PathEvent, MEASURE_TOLERANCE};
+use crate::fill::FillRule;
+
+pub(super) fn fill_contains(path: &Path, point: Point, fill_rule: FillRule) -> bool {
+    let mut winding_number = 0;
+
+    for event in path.flattened(MEASURE_TOLERANCE) {
+        // Every sub-path is implicitly closed, whether or not it was closed
+        // explicitly.
+        let (from, to) = match event {
+            PathEvent::Line { from, to } => (from, to),
+            PathEvent::End { last, first, .. } => (last, first),
+            _ => continue,
+        };
+
+        if is_on_segment(point, from, to) {
+            return true;
+        }
+
+        winding_number += crossing(point, from, to);
+    }
+
+    match fill_rule {
+        FillRule::NonZero => winding_number != 0,
+        FillRule::EvenOdd => winding_number % 2 != 0,
+    }
+}
+
+pub(super) fn stroke_contains(path: &Path, point: Point, radius: f32) -> bool {
+    let radius_squared = radius * radius;
+
+    path.flattened(MEASURE_TOLERANCE).any(|event| match event {
+        PathEvent::Line { from, to } => {
+            distance_squared_to_segment(point, from, to, radius) <= radius_squared
+        }
+        // Only closed sub-paths are stroked along their closing segment.
+        PathEvent::End {
+            last,
+            first,
+            close: true,
+        } => distance_squared_to_segment(point, last, first, radius) <= radius_squared,
+        _ => false,
+    })
+}
+
+/// The squared distance from the point to the closest point on the segment
+/// from `a` to `b`, which is one of the end points if the point lies beyond
+/// them.
+///
+/// Returns `f32::INFINITY` early if the point lies further than `radius` away
+/// from the bounding box of the segment, which is cheaper to check.
+fn distance_squared_to_segment(point: Point, a: Point, b: Point, radius: f32) -> f32 {
+    if point.x < a.x.min(b.x) - radius
+        || point.x > a.x.max(b.x) + radius
+        || point.y < a.y.min(b.y) - radius
+        || point.y > a.y.max(b.y) + radius
+    {
+        return f32::INFINITY;
+    }
+
+    let segment = b - a;
+    let length_squared = segment.square_length();
+
+    let t = if length_squared > 0.0 {
+        ((point - a).dot(segment) / length_squared).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+
+    (point - (a + segment * t)).square_length()
+}
+
+/// How the segment from `a` to `b` changes the winding number around the
+/// point, based on whether it crosses the horizontal ray going right from the
+/// point upwards or downwards.
+///
+/// The start of a segment counts as being above the ray when it lies on it
+/// and the end doesn't, so that a vertex on the ray is only counted once.
+pub(super) fn crossing(point: Point, a: Point, b: Point) -> i32 {
+    if a.y <= point.y {
+        if b.y > point.y && side(a, b, point) > 0.0 {
+            return 1;
+        }
+    } else if b.y <= point.y && side(a, b, point) < 0.0 {
+        return -1;
+    }
+
+    0
+}
+
+/// Returns a positive value if `point` is to the left of the line through `a`
+/// and `b`, a negative value if it is to the right, or `0.0` if it is on it.
+fn side(a: Point, b: Point, point: Point) -> f32 {
+    (b - a).cross(point - a)
+}
+
+fn is_on_segment(point: Point, a: Point, b: Point) -> bool {
+    side(a, b, point) == 0.0
+        && point.x >= a.x.min(b.x)
+        && point.x <= a.x.max(b.x)
+        && point.y >= a.y.min(b.y)
+        && point.y <= a.y.max(b.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use rootvg_core::math::{Point, Size};
+
+    use super::*;
+    use crate::path::PathBuilder;
+
+    fn rectangle(min: f32, max: f32) -> PathBuilder {
+        PathBuilder::new().rectangle(Point::new(min, min), Size::new(max - min, max - min))
+    }
+
+    #[test]
+    fn fill_contains_points_inside_of_a_rectangle() {
+        let path = rectangle(0.0, 10.0).build();
+
+        for fill_rule in [FillRule::NonZero, FillRule::EvenOdd] {
+            assert!(path.contains(Point::new(5.0, 5.0), fill_rule));
+            assert!(!path.contains(Point::new(15.0, 5.0), fill_rule));
+            assert!(!path.contains(Point::new(-1.0, 5.0), fill_rule));
+            assert!(!path.contains(Point::new(5.0, 10.5), fill_rule));
+
+            // Points on an edge or a corner are inside.
+            assert!(path.contains(Point::new(5.0, 10.0), fill_rule));
+            assert!(path.contains(Point::new(0.0, 0.0), fill_rule));
+        }
+    }
+
+    #[test]
+    fn even_odd_and_non_zero_differ_for_nested_sub_paths() {
+        // The inner square winds in the same direction as the outer one.
+        let path = rectangle(0.0, 30.0)
+            .polygon(&[
+                Point::new(10.0, 10.0),
+                Point::new(20.0, 10.0),
+                Point::new(20.0, 20.0),
+                Point::new(10.0, 20.0),
+            ])
+            .build();
+
+        assert!(path.contains(Point::new(15.0, 15.0), FillRule::NonZero));
+        assert!(!path.contains(Point::new(15.0, 15.0), FillRule::EvenOdd));
+
+        assert!(path.contains(Point::new(5.0, 5.0), FillRule::NonZero));
+        assert!(path.contains(Point::new(5.0, 5.0), FillRule::EvenOdd));
+    }
+
+    #[test]
+    fn holes_which_wind_the_other_way_are_empty_with_both_fill_rules() {
+        let path = rectangle(0.0, 30.0)
+            .polygon(&[
+                Point::new(10.0, 10.0),
+                Point::new(10.0, 20.0),
+                Point::new(20.0, 20.0),
+                Point::new(20.0, 10.0),
+            ])
+            .build();
+
+        assert!(!path.contains(Point::new(15.0, 15.0), FillRule::NonZero));
+        assert!(!path.contains(Point::new(15.0, 15.0), FillRule::EvenOdd));
+    }
+
+    #[test]
+    fn open_sub_paths_are_filled_as_if_they_were_closed() {
+        let path = PathBuilder::new()
+            .move_to(Point::new(0.0, 0.0))
+            .line_to(Point::new(10.0, 0.0))
+            .line_to(Point::new(10.0, 10.0))
+            .build();
+
+        assert!(path.contains(Point::new(7.0, 3.0), FillRule::NonZero));
+        assert!(!path.contains(Point::new(3.0, 7.0), FillRule::NonZero));
+    }
+
+    #[test]
+    fn stroke_contains_points_within_half_of_the_width() {
+        let path = PathBuilder::new()
+            .move_to(Point::new(0.0, 0.0))
+            .line_to(Point::new(10.0, 0.0))
+            .build();
+
+        assert!(path.stroke_contains(Point::new(5.0, 0.9), 2.0, 0.0));
+        assert!(!path.stroke_contains(Point::new(5.0, 1.1), 2.0, 0.0));
+
+        // The ends are tested as if the stroke had round caps.
+        assert!(path.stroke_contains(Point::new(-0.9, 0.0), 2.0, 0.0));
+        assert!(path.stroke_contains(Point::new(10.5, 0.5), 2.0, 0.0));
+        assert!(!path.stroke_contains(Point::new(10.8, 0.8), 2.0, 0.0));
+    }
+
+    #[test]
+    fn stroke_tolerance_extends_the_hit_area() {
+        let path = PathBuilder::new()
+            .move_to(Point::new(0.0, 0.0))
+            .line_to(Point::new(10.0, 0.0))
+            .build();
+
+        assert!(!path.stroke_contains(Point::new(5.0, 1.4), 2.0, 0.0));
+        assert!(path.stroke_contains(Point::new(5.0, 1.4), 2.0, 0.5));
+        assert!(!path.stroke_contains(Point::new(5.0, 1.6), 2.0, 0.5));
+
+        // A hairline can still be hit with a tolerance.
+        assert!(!path.stroke_contains(Point::new(5.0, 2.0), 0.0, 0.0));
+        assert!(path.stroke_contains(Point::new(5.0, 2.0), 0.0, 3.0));
+    }
+
+    #[test]
+    fn only_closed_sub_paths_are_stroked_along_their_closing_segment() {
+        let points = [
+            Point::new(0.0, 0.0),
+            Point::new(10.0, 0.0),
+            Point::new(10.0, 10.0),
+        ];
+
+        let open = PathBuilder::new().polyline(&points).build();
+        let closed = PathBuilder::new().polygon(&points).build();
+
+        // A point on the diagonal between the first and last points.
+        let point = Point::new(5.0, 5.0);
+
+        assert!(!open.stroke_contains(point, 1.0, 0.0));
+        assert!(closed.stroke_contains(point, 1.0, 0.0));
+    }
+
+    #[test]
+    fn crossing_counts_vertices_on_the_ray_once() {
+        let point = lyon::math::point(0.0, 0.0);
+
+        // Two edges which meet at a vertex on the ray, going upwards.
+        let a = lyon::math::point(1.0, 1.0);
+        let b = lyon::math::point(1.0, 0.0);
+        let c = lyon::math::point(1.0, -1.0);
+
+        assert_eq!(crossing(point, a, b) + crossing(point, b, c), -1);
+        assert_eq!(crossing(point, c, b) + crossing(point, b, a), 1);
+
+        // Edges to the left of the point don't cross the ray.
+        let d = lyon::math::point(-1.0, 1.0);
+        let e = lyon::math::point(-1.0, -1.0);
+
+        assert_eq!(crossing(point, d, e), 0);
+    }
+}
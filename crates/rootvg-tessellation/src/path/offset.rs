@@ -0,0 +1,244 @@
+//! Growing or shrinking the filled area of a [`Path`].
+//!
+//! The outline of the path is swept with a band which is twice as wide as the
+//! offset, with the joins filling in the gaps between the bands at the corners.
+//! The bands are then added to or subtracted from the filled area with a
+//! boolean operation, which removes the loops where the offset edges of
+//! concave corners cross each other.
+
+use lyon::math::{Point, Vector};
+
+use super::boolean::{self, normal, Operation, FLATTEN_TOLERANCE};
+use super::Path;
+use crate::stroke::LineJoin;
+
+/// Miter joins which are longer than this times the offset are bevelled
+/// instead, the same as the default miter limit of a stroke.
+const MITER_LIMIT: f32 = lyon::tessellation::StrokeOptions::DEFAULT_MITER_LIMIT;
+
+pub(super) fn offset(path: &Path, distance: f32, join: LineJoin) -> Path {
+    let contours = boolean::contours(path);
+    let radius = distance.abs();
+
+    let mut bands: Vec<Vec<Point>> = Vec::new();
+
+    for contour in contours.iter() {
+        let edges: Vec<(Point, Point)> = boolean::contour_edges(contour).collect();
+
+        for (i, &(from, to)) in edges.iter().enumerate() {
+            let normal = normal(to - from) * radius;
+            bands.push(vec![from + normal, to + normal, to - normal, from - normal]);
+
+            let (prev_from, _) = edges[(i + edges.len() - 1) % edges.len()];
+            if let Some(join) = join_polygon(from, from - prev_from, to - from, radius, join) {
+                bands.push(join);
+            }
+        }
+    }
+
+    // The bands overlap each other, so they all have to wind in the same
+    // direction to be merged by the non-zero fill rule.
+    for band in bands.iter_mut() {
+        if signed_area(band) < 0.0 {
+            band.reverse();
+        }
+    }
+
+    let operation = if distance > 0.0 {
+        Operation::Union
+    } else {
+        Operation::Difference
+    };
+
+    boolean::combine_contours(&contours, &bands, operation)
+}
+
+/// The polygon which fills in the gap between the bands of two edges on the
+/// outer side of the corner at `point`, or `None` if there is no gap.
+fn join_polygon(
+    point: Point,
+    incoming: Vector,
+    outgoing: Vector,
+    radius: f32,
+    join: LineJoin,
+) -> Option<Vec<Point>> {
+    let turn = incoming.cross(outgoing);
+    if turn == 0.0 && incoming.dot(outgoing) >= 0.0 {
+        return None;
+    }
+
+    // The gap is on the opposite side of the direction the corner turns to.
+    let side = if turn > 0.0 { -radius } else { radius };
+    let a = normal(incoming) * side;
+    let b = normal(outgoing) * side;
+
+    let mut polygon = vec![point, point + a];
+
+    match join {
+        LineJoin::Miter => {
+            // The miter point is where the offset edges meet.
+            let cos = a.dot(b) / (radius * radius);
+            if cos > -1.0 && (2.0 / (1.0 + cos)).sqrt() <= MITER_LIMIT {
+                polygon.push(point + (a + b) / (1.0 + cos));
+            }
+        }
+        LineJoin::Round => {
+            let start = a.angle_from_x_axis().radians;
+            let mut sweep = b.angle_from_x_axis().radians - start;
+            if sweep > std::f32::consts::PI {
+                sweep -= std::f32::consts::TAU;
+            } else if sweep < -std::f32::consts::PI {
+                sweep += std::f32::consts::TAU;
+            }
+
+            // The largest step for which the chords stay within the tolerance
+            // of the arc.
+            let max_step = 2.0 * (1.0 - (FLATTEN_TOLERANCE / radius).min(1.0)).acos();
+            let steps = (sweep.abs() / max_step.max(0.01)).ceil() as usize;
+
+            for step in 1..steps {
+                let angle = start + sweep * step as f32 / steps as f32;
+                polygon.push(point + Vector::new(angle.cos(), angle.sin()) * radius);
+            }
+        }
+        LineJoin::Bevel => {}
+    }
+
+    polygon.push(point + b);
+
+    Some(polygon)
+}
+
+/// Twice the area of the polygon, which is positive or negative depending on
+/// the direction it winds in.
+fn signed_area(polygon: &[Point]) -> f32 {
+    boolean::contour_edges(polygon)
+        .map(|(from, to)| from.to_vector().cross(to.to_vector()))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use rootvg_core::math::{Point as CorePoint, Size};
+
+    use super::*;
+
+    const JOINS: [LineJoin; 3] = [LineJoin::Miter, LineJoin::Round, LineJoin::Bevel];
+
+    fn square() -> Path {
+        Path::rectangle(CorePoint::new(0.0, 0.0), Size::new(10.0, 10.0))
+    }
+
+    /// The filled area of the path, where holes which wind in the opposite
+    /// direction to their outline are subtracted.
+    fn area(path: &Path) -> f32 {
+        let twice_area: f32 = boolean::contours(path)
+            .iter()
+            .map(|contour| signed_area(contour))
+            .sum();
+
+        twice_area.abs() * 0.5
+    }
+
+    fn num_vertices(path: &Path) -> Vec<usize> {
+        boolean::contours(path).iter().map(Vec::len).collect()
+    }
+
+    fn assert_bounds(path: &Path, min: f32, max: f32) {
+        let bounds = path.bounds();
+
+        assert!((bounds.min_x() - min).abs() < 1e-3, "{bounds:?}");
+        assert!((bounds.min_y() - min).abs() < 1e-3, "{bounds:?}");
+        assert!((bounds.max_x() - max).abs() < 1e-3, "{bounds:?}");
+        assert!((bounds.max_y() - max).abs() < 1e-3, "{bounds:?}");
+    }
+
+    #[test]
+    fn growing_a_square_with_a_miter_join() {
+        let path = square().offset(5.0, LineJoin::Miter);
+
+        assert!((area(&path) - 400.0).abs() < 1e-3);
+        assert_eq!(num_vertices(&path), [4]);
+        assert_bounds(&path, -5.0, 15.0);
+    }
+
+    #[test]
+    fn growing_a_square_with_a_bevel_join() {
+        let path = square().offset(5.0, LineJoin::Bevel);
+
+        // The corners of the grown square are cut off by the bevels.
+        assert!((area(&path) - 350.0).abs() < 1e-3);
+        assert_eq!(num_vertices(&path), [8]);
+        assert_bounds(&path, -5.0, 15.0);
+    }
+
+    #[test]
+    fn growing_a_square_with_a_round_join() {
+        let path = square().offset(5.0, LineJoin::Round);
+
+        // The corners are quarter circles, which are flattened into chords
+        // that lie slightly inside of the exact circles.
+        let exact_area = 300.0 + std::f32::consts::PI * 25.0;
+        assert!((area(&path) - exact_area).abs() < 0.5);
+        assert!(num_vertices(&path)[0] > 8);
+        assert_bounds(&path, -5.0, 15.0);
+    }
+
+    #[test]
+    fn shrinking_a_square_with_each_join() {
+        for join in JOINS {
+            let path = square().offset(-2.0, join);
+
+            assert!((area(&path) - 36.0).abs() < 1e-3, "{join:?}");
+            assert_eq!(num_vertices(&path), [4], "{join:?}");
+            assert_bounds(&path, 2.0, 8.0);
+        }
+    }
+
+    #[test]
+    fn shrinking_a_square_by_more_than_half_of_its_width_leaves_nothing() {
+        for join in JOINS {
+            let path = square().offset(-6.0, join);
+
+            assert!(num_vertices(&path).is_empty(), "{join:?}");
+        }
+    }
+
+    #[test]
+    fn shrinking_uses_the_join_at_concave_corners() {
+        let l_shape = crate::path::PathBuilder::new()
+            .polygon(&[
+                CorePoint::new(0.0, 0.0),
+                CorePoint::new(20.0, 0.0),
+                CorePoint::new(20.0, 10.0),
+                CorePoint::new(10.0, 10.0),
+                CorePoint::new(10.0, 20.0),
+                CorePoint::new(0.0, 20.0),
+            ])
+            .build();
+
+        let miter = l_shape.offset(-2.0, LineJoin::Miter);
+        assert!((area(&miter) - 156.0).abs() < 1e-3);
+        assert_eq!(num_vertices(&miter), [6]);
+
+        // The bevel cuts a triangle with an area of 2 off of the inner corner.
+        let bevel = l_shape.offset(-2.0, LineJoin::Bevel);
+        assert!((area(&bevel) - 158.0).abs() < 1e-3);
+        assert_eq!(num_vertices(&bevel), [7]);
+
+        // The inner corner is rounded with a radius of 2.
+        let round = l_shape.offset(-2.0, LineJoin::Round);
+        let exact_area = 156.0 + 4.0 - std::f32::consts::PI;
+        assert!((area(&round) - exact_area).abs() < 0.05);
+    }
+
+    #[test]
+    fn zero_offset_returns_the_same_path() {
+        for join in JOINS {
+            let path = square().offset(0.0, join);
+
+            assert!((area(&path) - 100.0).abs() < 1e-3);
+            assert_eq!(num_vertices(&path), [4]);
+        }
+    }
+}
@@ -0,0 +1,390 @@
+//! Parsing of SVG path data.
+//!
+//! See <https://www.w3.org/TR/SVG2/paths.html#PathData> for the grammar.
+
+use lyon::math;
+use lyon::path::builder::{SvgPathBuilder, WithSvg};
+use lyon::path::path::BuilderImpl;
+use lyon::path::ArcFlags;
+
+use super::Path;
+
+/// The commands in uppercase.
+const COMMANDS: &[u8] = b"MLHVCSQTAZ";
+
+/// An error that occurred while parsing SVG path data with
+/// [`Path::from_svg_path_data`].
+///
+/// The positions are byte offsets into the path data.
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseSvgPathError {
+    #[error("the path data must start with a move to command")]
+    MissingMoveTo,
+    #[error("expected a command at byte {0}")]
+    ExpectedCommand(usize),
+    #[error("unknown command '{0}' at byte {1}")]
+    UnknownCommand(char, usize),
+    #[error("expected a finite number at byte {0}")]
+    ExpectedNumber(usize),
+    #[error("expected an arc flag (`0` or `1`) at byte {0}")]
+    ExpectedFlag(usize),
+}
+
+pub(super) fn parse(data: &str) -> Result<Path, ParseSvgPathError> {
+    let mut parser = Parser {
+        src: data.as_bytes(),
+        pos: 0,
+    };
+    let mut builder = lyon::path::Path::builder().with_svg();
+
+    let mut is_first = true;
+
+    loop {
+        parser.skip_whitespace();
+
+        let position = parser.pos;
+        let Some(&c) = parser.src.get(position) else {
+            break;
+        };
+
+        if !c.is_ascii_alphabetic() {
+            return Err(ParseSvgPathError::ExpectedCommand(position));
+        }
+        parser.pos += 1;
+
+        if is_first && c != b'M' && c != b'm' {
+            return Err(ParseSvgPathError::MissingMoveTo);
+        }
+        is_first = false;
+
+        let relative = c.is_ascii_lowercase();
+        let mut command = c.to_ascii_uppercase();
+
+        if !COMMANDS.contains(&command) {
+            return Err(ParseSvgPathError::UnknownCommand(c as char, position));
+        }
+
+        // The arguments of a command may be repeated without repeating the
+        // command itself.
+        loop {
+            parse_command(&mut parser, &mut builder, command, relative)?;
+
+            if command == b'Z' || !parser.has_number() {
+                break;
+            }
+
+            // Extra coordinate pairs after a move to are treated as line to
+            // commands.
+            if command == b'M' {
+                command = b'L';
+            }
+        }
+    }
+
+    Ok(Path {
+        raw: builder.build(),
+    })
+}
+
+/// Parses the arguments of a single command and adds it to the builder.
+fn parse_command(
+    parser: &mut Parser,
+    builder: &mut WithSvg<BuilderImpl>,
+    command: u8,
+    relative: bool,
+) -> Result<(), ParseSvgPathError> {
+    match command {
+        b'M' => {
+            let to = parser.point()?;
+            if relative {
+                builder.relative_move_to(to.to_vector());
+            } else {
+                builder.move_to(to);
+            }
+        }
+        b'L' => {
+            let to = parser.point()?;
+            if relative {
+                builder.relative_line_to(to.to_vector());
+            } else {
+                builder.line_to(to);
+            }
+        }
+        b'H' => {
+            let x = parser.number()?;
+            if relative {
+                builder.relative_horizontal_line_to(x);
+            } else {
+                builder.horizontal_line_to(x);
+            }
+        }
+        b'V' => {
+            let y = parser.number()?;
+            if relative {
+                builder.relative_vertical_line_to(y);
+            } else {
+                builder.vertical_line_to(y);
+            }
+        }
+        b'C' => {
+            let ctrl1 = parser.point()?;
+            let ctrl2 = parser.point()?;
+            let to = parser.point()?;
+            if relative {
+                builder.relative_cubic_bezier_to(
+                    ctrl1.to_vector(),
+                    ctrl2.to_vector(),
+                    to.to_vector(),
+                );
+            } else {
+                builder.cubic_bezier_to(ctrl1, ctrl2, to);
+            }
+        }
+        b'S' => {
+            let ctrl2 = parser.point()?;
+            let to = parser.point()?;
+            if relative {
+                builder.smooth_relative_cubic_bezier_to(ctrl2.to_vector(), to.to_vector());
+            } else {
+                builder.smooth_cubic_bezier_to(ctrl2, to);
+            }
+        }
+        b'Q' => {
+            let ctrl = parser.point()?;
+            let to = parser.point()?;
+            if relative {
+                builder.relative_quadratic_bezier_to(ctrl.to_vector(), to.to_vector());
+            } else {
+                builder.quadratic_bezier_to(ctrl, to);
+            }
+        }
+        b'T' => {
+            let to = parser.point()?;
+            if relative {
+                builder.smooth_relative_quadratic_bezier_to(to.to_vector());
+            } else {
+                builder.smooth_quadratic_bezier_to(to);
+            }
+        }
+        b'A' => {
+            // Negative radii are treated as positive, as required by the
+            // spec. The arc is approximated with Bézier curves by lyon, or
+            // with a straight line if a radius is zero.
+            let rx = parser.number()?.abs();
+            let ry = parser.number()?.abs();
+            let x_rotation = parser.number()?;
+            let large_arc = parser.flag()?;
+            let sweep = parser.flag()?;
+            let to = parser.point()?;
+
+            let radii = math::Vector::new(rx, ry);
+            let x_rotation = math::Angle::degrees(x_rotation);
+            let flags = ArcFlags { large_arc, sweep };
+
+            if relative {
+                builder.relative_arc_to(radii, x_rotation, flags, to.to_vector());
+            } else {
+                builder.arc_to(radii, x_rotation, flags, to);
+            }
+        }
+        b'Z' => builder.close(),
+        _ => unreachable!(),
+    }
+
+    Ok(())
+}
+
+struct Parser<'a> {
+    src: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.src.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|c| c.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    /// Skips whitespace and at most one comma.
+    fn skip_separator(&mut self) {
+        self.skip_whitespace();
+        if self.peek() == Some(b',') {
+            self.pos += 1;
+            self.skip_whitespace();
+        }
+    }
+
+    /// Returns `true` if the next argument is a number.
+    fn has_number(&mut self) -> bool {
+        self.skip_separator();
+        self.peek()
+            .is_some_and(|c| c.is_ascii_digit() || matches!(c, b'.' | b'-' | b'+'))
+    }
+
+    fn number(&mut self) -> Result<f32, ParseSvgPathError> {
+        self.skip_separator();
+
+        let start = self.pos;
+
+        if matches!(self.peek(), Some(b'-' | b'+')) {
+            self.pos += 1;
+        }
+
+        let mut num_digits = self.skip_digits();
+        if self.peek() == Some(b'.') {
+            self.pos += 1;
+            num_digits += self.skip_digits();
+        }
+
+        if num_digits == 0 {
+            return Err(ParseSvgPathError::ExpectedNumber(start));
+        }
+
+        // Only consume the exponent if it is followed by digits.
+        if matches!(self.peek(), Some(b'e' | b'E')) {
+            let mantissa_end = self.pos;
+            self.pos += 1;
+            if matches!(self.peek(), Some(b'-' | b'+')) {
+                self.pos += 1;
+            }
+            if self.skip_digits() == 0 {
+                self.pos = mantissa_end;
+            }
+        }
+
+        // The slice only contains ASCII characters.
+        let text = std::str::from_utf8(&self.src[start..self.pos]).unwrap();
+
+        match text.parse::<f32>() {
+            Ok(n) if n.is_finite() => Ok(n),
+            _ => Err(ParseSvgPathError::ExpectedNumber(start)),
+        }
+    }
+
+    fn point(&mut self) -> Result<math::Point, ParseSvgPathError> {
+        let x = self.number()?;
+        let y = self.number()?;
+        Ok(math::Point::new(x, y))
+    }
+
+    /// Parses an arc flag, which doesn't need to be separated from the next
+    /// argument.
+    fn flag(&mut self) -> Result<bool, ParseSvgPathError> {
+        self.skip_separator();
+
+        let flag = match self.peek() {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => return Err(ParseSvgPathError::ExpectedFlag(self.pos)),
+        };
+        self.pos += 1;
+
+        Ok(flag)
+    }
+
+    fn skip_digits(&mut self) -> usize {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        self.pos - start
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_bounds(path: &Path, min: (f32, f32), max: (f32, f32)) {
+        let bounds = path.bounds();
+
+        assert!((bounds.min_x() - min.0).abs() < 1e-3, "{bounds:?}");
+        assert!((bounds.min_y() - min.1).abs() < 1e-3, "{bounds:?}");
+        assert!((bounds.max_x() - max.0).abs() < 1e-3, "{bounds:?}");
+        assert!((bounds.max_y() - max.1).abs() < 1e-3, "{bounds:?}");
+    }
+
+    #[test]
+    fn parses_absolute_commands() {
+        let path = parse("M 0 0 H 10 V 10 H 0 Z").unwrap();
+
+        assert_bounds(&path, (0.0, 0.0), (10.0, 10.0));
+        assert!((path.length() - 40.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn parses_relative_commands() {
+        let path = parse("m10,10 l10,0 l0,10 z").unwrap();
+
+        assert_bounds(&path, (10.0, 10.0), (20.0, 20.0));
+        assert!((path.length() - (20.0 + 200.0f32.sqrt())).abs() < 1e-3);
+    }
+
+    #[test]
+    fn repeated_arguments_continue_the_command() {
+        // The extra coordinate pairs after the move to are line tos.
+        let path = parse("M 0 0 10 0 10 10 0 10 Z").unwrap();
+
+        assert_bounds(&path, (0.0, 0.0), (10.0, 10.0));
+        assert!((path.length() - 40.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn parses_arc_flags_without_separators() {
+        // A half circle with a radius of 5 from (0, 0) to (10, 0).
+        let path = parse("M0 0a5 5 0 1010 0").unwrap();
+        let bounds = path.bounds();
+
+        assert!((bounds.width() - 10.0).abs() < 0.05, "{bounds:?}");
+        assert!((bounds.height() - 5.0).abs() < 0.05, "{bounds:?}");
+        assert!((path.length() - std::f32::consts::PI * 5.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn empty_data_is_an_empty_path() {
+        let path = parse("  ").unwrap();
+
+        assert!(path.raw.iter().next().is_none());
+    }
+
+    #[test]
+    fn malformed_data_returns_an_error() {
+        assert_eq!(
+            parse("L 10 10").unwrap_err(),
+            ParseSvgPathError::MissingMoveTo
+        );
+        assert_eq!(
+            parse("10 10").unwrap_err(),
+            ParseSvgPathError::ExpectedCommand(0)
+        );
+        assert_eq!(
+            parse("M 0 0 X 1 2").unwrap_err(),
+            ParseSvgPathError::UnknownCommand('X', 6)
+        );
+        assert_eq!(
+            parse("M 10").unwrap_err(),
+            ParseSvgPathError::ExpectedNumber(4)
+        );
+        assert_eq!(
+            parse("M0,0 5").unwrap_err(),
+            ParseSvgPathError::ExpectedNumber(6)
+        );
+        assert_eq!(
+            parse("M 0 0 L 1e 2").unwrap_err(),
+            ParseSvgPathError::ExpectedNumber(9)
+        );
+        assert_eq!(
+            parse("M 0 0 L 1e99 0").unwrap_err(),
+            ParseSvgPathError::ExpectedNumber(8)
+        );
+        assert_eq!(
+            parse("M 0 0 A 5 5 0 2 0 10 10").unwrap_err(),
+            ParseSvgPathError::ExpectedFlag(14)
+        );
+    }
+}
@@ -22,6 +22,19 @@ pub struct Stroke<'a> {
     pub line_join: LineJoin,
     /// The dash pattern used when stroking the line.
     pub line_dash: LineDash<'a>,
+    /// The limit of the ratio between the length of a miter join and the
+    /// width of the stroke. Miter joins which exceed this limit are bevelled
+    /// instead, which keeps sharp angles from spiking out.
+    ///
+    /// This is clamped to a minimum of `1.0`. By default this is set to `4.0`.
+    pub miter_limit: f32,
+    /// The tolerance used to approximate round joins, round caps, and curves
+    /// with line segments.
+    ///
+    /// If this is `None`, then the tolerance of the [`Tessellator`] is used.
+    ///
+    /// [`Tessellator`]: crate::Tessellator
+    pub tolerance: Option<f32>,
 }
 
 impl<'a> Stroke<'a> {
@@ -47,6 +60,22 @@ impl<'a> Stroke<'a> {
     pub fn with_line_join(self, line_join: LineJoin) -> Self {
         Stroke { line_join, ..self }
     }
+
+    /// Sets the miter limit of the [`Stroke`].
+    pub fn with_miter_limit(self, miter_limit: f32) -> Self {
+        Stroke {
+            miter_limit,
+            ..self
+        }
+    }
+
+    /// Sets the tolerance of the [`Stroke`].
+    pub fn with_tolerance(self, tolerance: f32) -> Self {
+        Stroke {
+            tolerance: Some(tolerance),
+            ..self
+        }
+    }
 }
 
 impl<'a> Default for Stroke<'a> {
@@ -57,6 +86,8 @@ impl<'a> Default for Stroke<'a> {
             line_cap: LineCap::default(),
             line_join: LineJoin::default(),
             line_dash: LineDash::default(),
+            miter_limit: lyon::tessellation::StrokeOptions::DEFAULT_MITER_LIMIT,
+            tolerance: None,
         }
     }
 }
@@ -13,7 +13,7 @@ use rootvg_mesh::{
 };
 
 #[cfg(feature = "gradient")]
-use rootvg_core::gradient::PackedGradient;
+use rootvg_core::gradient::{PackedGradient, PackedGradientKind};
 #[cfg(feature = "gradient")]
 use rootvg_mesh::{GradientMesh, GradientMeshPrimitive, GradientVertex2D};
 
@@ -29,6 +29,7 @@ pub struct Tessellator {
     transforms: Transforms,
     fill_tessellator: tessellation::FillTessellator,
     stroke_tessellator: tessellation::StrokeTessellator,
+    tolerance: f32,
 }
 
 enum Buffer {
@@ -137,6 +138,14 @@ impl Transform {
     }
     */
 
+    /// The largest factor that this transform scales a length by.
+    fn max_scale(&self) -> f32 {
+        let x = lyon::math::Vector::new(self.0.m11, self.0.m12).length();
+        let y = lyon::math::Vector::new(self.0.m21, self.0.m22).length();
+
+        x.max(y)
+    }
+
     #[cfg(feature = "gradient")]
     fn transform_point(&self, point: Point) -> Point {
         let transformed = self
@@ -156,16 +165,48 @@ impl Transform {
 
     #[cfg(feature = "gradient")]
     fn transform_gradient(&self, mut gradient: PackedGradient) -> PackedGradient {
-        let start = self.transform_point(Point::new(gradient.direction[0], gradient.direction[1]));
-        let end = self.transform_point(Point::new(gradient.direction[2], gradient.direction[3]));
-
-        gradient.direction[0] = start.x;
-        gradient.direction[1] = start.y;
-        gradient.direction[2] = end.x;
-        gradient.direction[3] = end.y;
+        match gradient.kind() {
+            PackedGradientKind::Linear => {
+                let start =
+                    self.transform_point(Point::new(gradient.direction[0], gradient.direction[1]));
+                let end =
+                    self.transform_point(Point::new(gradient.direction[2], gradient.direction[3]));
+
+                gradient.direction[0] = start.x;
+                gradient.direction[1] = start.y;
+                gradient.direction[2] = end.x;
+                gradient.direction[3] = end.y;
+            }
+            PackedGradientKind::Radial => {
+                let center =
+                    self.transform_point(Point::new(gradient.direction[0], gradient.direction[1]));
+
+                gradient.direction[0] = center.x;
+                gradient.direction[1] = center.y;
+                gradient.direction[2] = self.transform_length(gradient.direction[2]);
+                gradient.direction[3] = self.transform_length(gradient.direction[3]);
+            }
+            PackedGradientKind::Conic => {
+                let center =
+                    self.transform_point(Point::new(gradient.direction[0], gradient.direction[1]));
+                let rotation = f32::atan2(self.0.m12, self.0.m11);
+
+                gradient.direction[0] = center.x;
+                gradient.direction[1] = center.y;
+                gradient.direction[2] += rotation;
+            }
+        }
 
         gradient
     }
+
+    /// Transforms a length along the x axis, ignoring translation.
+    #[cfg(feature = "gradient")]
+    fn transform_length(&self, length: f32) -> f32 {
+        self.0
+            .transform_vector(lyon::math::Vector::new(length, 0.0))
+            .length()
+    }
 }
 
 impl Default for Tessellator {
@@ -179,6 +220,7 @@ impl Default for Tessellator {
             },
             fill_tessellator: tessellation::FillTessellator::new(),
             stroke_tessellator: tessellation::StrokeTessellator::new(),
+            tolerance: tessellation::FillOptions::DEFAULT_TOLERANCE,
         }
     }
 }
@@ -192,6 +234,22 @@ impl Tessellator {
         Self::default()
     }
 
+    /// Sets the maximum distance between a curve and the line segments that
+    /// approximate it (`0.1` by default).
+    ///
+    /// A higher tolerance results in fewer vertices at the cost of coarser
+    /// curves, and a lower tolerance results in smoother curves at the cost
+    /// of more vertices.
+    ///
+    /// The tolerance is in the coordinate space of the output mesh, so it is
+    /// not affected by [`Tessellator::scale`] or the other transforms. This
+    /// means that zoomed in meshes should use a lower tolerance if they are
+    /// scaled up after tessellation (for example with a mesh transform).
+    pub fn with_tolerance(mut self, tolerance: f32) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
     /// Draws the given [`Path`] on the [`Tessellator`] by filling it with the
     /// provided style.
     pub fn fill(mut self, path: &Path, fill: impl Into<Fill>) -> Self {
@@ -202,7 +260,8 @@ impl Tessellator {
                 .buffers
                 .get_fill(&self.transforms.current.transform_style(style));
 
-            let options = tessellation::FillOptions::default().with_fill_rule(into_fill_rule(rule));
+            let options = tessellation::FillOptions::tolerance(self.tolerance)
+                .with_fill_rule(into_fill_rule(rule));
 
             if self.transforms.current.is_identity() {
                 self.fill_tessellator
@@ -241,7 +300,8 @@ impl Tessellator {
                 .0
                 .transform_vector(lyon::math::Vector::new(size.width, size.height));
 
-            let options = tessellation::FillOptions::default().with_fill_rule(into_fill_rule(rule));
+            let options = tessellation::FillOptions::tolerance(self.tolerance)
+                .with_fill_rule(into_fill_rule(rule));
 
             self.fill_tessellator
                 .tessellate_rectangle(
@@ -265,16 +325,30 @@ impl Tessellator {
                 .buffers
                 .get_stroke(&self.transforms.current.transform_style(stroke.style));
 
-            let mut options = tessellation::StrokeOptions::default();
+            let tolerance = stroke.tolerance.unwrap_or(self.tolerance);
+
+            let mut options = tessellation::StrokeOptions::tolerance(tolerance);
             options.line_width = stroke.width;
             options.start_cap = into_line_cap(stroke.line_cap);
             options.end_cap = into_line_cap(stroke.line_cap);
             options.line_join = into_line_join(stroke.line_join);
+            options.miter_limit = stroke
+                .miter_limit
+                .max(tessellation::StrokeOptions::MINIMUM_MITER_LIMIT);
 
             let path = if stroke.line_dash.segments.is_empty() {
                 Cow::Borrowed(path)
             } else {
-                Cow::Owned(dashed(path, stroke.line_dash))
+                // The path is dashed before it is transformed, so convert the
+                // tolerance into the coordinate space of the path.
+                let scale = self.transforms.current.max_scale();
+                let tolerance = if scale > 0.0 {
+                    tolerance / scale
+                } else {
+                    tolerance
+                };
+
+                Cow::Owned(dashed_with_tolerance(path, stroke.line_dash, tolerance))
             };
 
             if self.transforms.current.is_identity() {
@@ -292,6 +366,22 @@ impl Tessellator {
         self
     }
 
+    /// Draws the given [`Path`] on the [`Tessellator`] by filling it and then
+    /// stroking it, so that the stroke is drawn on top of the fill.
+    ///
+    /// The fill and the stroke can use different styles. Note, solid meshes
+    /// are drawn before gradient meshes with the same z index, so if the fill
+    /// is a gradient and the stroke is a solid color, use a higher z index for
+    /// the stroke when adding the primitives to the canvas.
+    pub fn fill_and_stroke<'a>(
+        self,
+        path: &Path,
+        fill: impl Into<Fill>,
+        stroke: impl Into<Stroke<'a>>,
+    ) -> Self {
+        self.fill(path, fill).stroke(path, stroke)
+    }
+
     /*
     /// Stores the current transform of the [`Tessellator`] and executes the given
     /// drawing operations, restoring the transform afterwards.
@@ -362,12 +452,10 @@ impl Tessellator {
             Buffer::Solid(buffer) => {
                 if !buffer.indices.is_empty() {
                     return Some(MeshPrimitive::Solid(SolidMeshPrimitive {
-                        mesh: Rc::new(SolidMesh {
-                            buffers: Indexed {
-                                vertices: buffer.vertices,
-                                indices: buffer.indices,
-                            },
-                        }),
+                        mesh: Rc::new(SolidMesh::from_buffers(Indexed {
+                            vertices: buffer.vertices,
+                            indices: buffer.indices,
+                        })),
                         uniform: MeshUniforms::default(),
                     }));
                 }
@@ -376,12 +464,10 @@ impl Tessellator {
             Buffer::Gradient(buffer) => {
                 if !buffer.indices.is_empty() {
                     return Some(MeshPrimitive::Gradient(GradientMeshPrimitive {
-                        mesh: Rc::new(GradientMesh {
-                            buffers: Indexed {
-                                vertices: buffer.vertices,
-                                indices: buffer.indices,
-                            },
-                        }),
+                        mesh: Rc::new(GradientMesh::from_buffers(Indexed {
+                            vertices: buffer.vertices,
+                            indices: buffer.indices,
+                        })),
                         uniform: MeshUniforms::default(),
                     }));
                 }
@@ -398,12 +484,10 @@ impl Tessellator {
                     if !buffer.indices.is_empty() {
                         self.primitives
                             .push(MeshPrimitive::Solid(SolidMeshPrimitive {
-                                mesh: Rc::new(SolidMesh {
-                                    buffers: Indexed {
-                                        vertices: buffer.vertices,
-                                        indices: buffer.indices,
-                                    },
-                                }),
+                                mesh: Rc::new(SolidMesh::from_buffers(Indexed {
+                                    vertices: buffer.vertices,
+                                    indices: buffer.indices,
+                                })),
                                 uniform: MeshUniforms::default(),
                             }));
                     }
@@ -413,12 +497,10 @@ impl Tessellator {
                     if !buffer.indices.is_empty() {
                         self.primitives
                             .push(MeshPrimitive::Gradient(GradientMeshPrimitive {
-                                mesh: Rc::new(GradientMesh {
-                                    buffers: Indexed {
-                                        vertices: buffer.vertices,
-                                        indices: buffer.indices,
-                                    },
-                                }),
+                                mesh: Rc::new(GradientMesh::from_buffers(Indexed {
+                                    vertices: buffer.vertices,
+                                    indices: buffer.indices,
+                                })),
                                 uniform: MeshUniforms::default(),
                             }));
                     }
@@ -507,6 +589,16 @@ fn into_fill_rule(rule: FillRule) -> lyon::tessellation::FillRule {
 }
 
 pub fn dashed(path: &Path, line_dash: LineDash<'_>) -> Path {
+    dashed_with_tolerance(
+        path,
+        line_dash,
+        lyon::tessellation::StrokeOptions::DEFAULT_TOLERANCE,
+    )
+}
+
+/// Splits the given path into dashes, where `tolerance` is the maximum
+/// distance between the curves of the path and the dashes that follow them.
+pub fn dashed_with_tolerance(path: &Path, line_dash: LineDash<'_>, tolerance: f32) -> Path {
     use lyon::algorithms::walk::{walk_along_path, RepeatedPattern, WalkerEvent};
     use lyon::path::iterator::PathIterator;
 
@@ -518,9 +610,11 @@ pub fn dashed(path: &Path, line_dash: LineDash<'_>) -> Path {
     let mut draw_line = false;
 
     walk_along_path(
-        path.raw.iter().flattened(0.01),
+        // The curves are flattened more finely than the tolerance so that
+        // the dashes are evenly spaced along them.
+        path.raw.iter().flattened(tolerance * 0.1),
         0.0,
-        lyon::tessellation::StrokeOptions::DEFAULT_TOLERANCE,
+        tolerance,
         &mut RepeatedPattern {
             callback: |event: WalkerEvent<'_>| {
                 let point = Point::new(event.position.x, event.position.y);
@@ -1,11 +1,16 @@
-use glyphon::cosmic_text::{Align, BufferRef};
-use glyphon::{Edit, FontSystem};
-use std::cell::{Ref, RefCell};
+use glyphon::cosmic_text::fontdb;
+use glyphon::cosmic_text::{Align, BufferRef, Cursor, Selection};
+use glyphon::{Attrs, Edit, FontSystem};
+use rustc_hash::FxHashMap;
+use smallvec::SmallVec;
+use std::cell::{Cell, Ref, RefCell};
 use std::fmt::Debug;
 use std::hint::unreachable_unchecked;
+use std::ops::Range;
 use std::rc::Rc;
 
-use rootvg_core::math::Size;
+use rootvg_core::color::RGBA8;
+use rootvg_core::math::{Point, Rect, Size};
 
 use super::TextProperties;
 
@@ -68,16 +73,77 @@ struct TextBufferInner {
     bounds_width: Option<f32>,
     bounds_height: Option<f32>,
     has_text: bool,
+    decorations: Vec<TextDecoration>,
 }
 
+/// An underline or strikethrough line of a text buffer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextDecoration {
+    /// The rectangle of the line in logical points, relative to the top-left
+    /// corner of the buffer.
+    pub rect: Rect,
+    /// The color of the glyphs the line spans. If this is `None`, then the
+    /// color of the text primitive is used.
+    pub color: Option<RGBA8>,
+}
+
+/// The layout of a single visual line of a text buffer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LineInfo {
+    /// The index of the line in the text, counting only hard line breaks.
+    ///
+    /// When a line is wrapped, each of its visual lines is reported
+    /// separately with the same index.
+    pub line_index: usize,
+    /// The offset of the top of the line in logical points, relative to the
+    /// top of the buffer.
+    pub y: f32,
+    /// The height of the line in logical points.
+    pub height: f32,
+    /// The width of the glyphs in the line in logical points.
+    pub width: f32,
+}
+
+/// A reference-counted text buffer which can be shared between text
+/// primitives.
+///
+/// Mutating the buffer through any handle changes the text of every clone,
+/// including the clones held by existing [`crate::TextPrimitive`]s. Clones
+/// made after a mutation compare unequal to those made before it, so the
+/// canvas prepares the text again on the next frame the primitive is added.
 #[derive(Debug)]
 pub struct RcTextBuffer {
     inner: Rc<RefCell<TextBufferInner>>,
     /// Used to quickly diff text primitives for changes.
+    ///
+    /// This is the generation of the buffer when this handle was created or
+    /// cloned, so clones made after a mutation compare unequal to clones made
+    /// before it.
     generation: u64,
+    /// The latest generation of the buffer, shared by all clones.
+    shared_generation: Rc<Cell<u64>>,
 }
 
 impl RcTextBuffer {
+    /// Create a new text buffer.
+    ///
+    /// * `bounds_width` - The width in logical points that the text is
+    ///   wrapped and aligned to, or `None` to never wrap the text. This is
+    ///   the same for [`Wrap::Word`], [`Wrap::Glyph`], and
+    ///   [`Wrap::WordOrGlyph`]. With [`Wrap::None`], the width is only used
+    ///   to align the text.
+    /// * `bounds_height` - The height in logical points of the area that the
+    ///   text is laid out in. Lines which start below this are not laid out.
+    ///
+    /// Neither of these clip the text when it is drawn. The visible region
+    /// is set separately with [`crate::TextPrimitive::clipping_bounds`], so
+    /// text can be wrapped at one width and clipped to a different
+    /// rectangle.
+    ///
+    /// [`Wrap::Word`]: crate::Wrap::Word
+    /// [`Wrap::Glyph`]: crate::Wrap::Glyph
+    /// [`Wrap::WordOrGlyph`]: crate::Wrap::WordOrGlyph
+    /// [`Wrap::None`]: crate::Wrap::None
     pub fn new(
         text: &str,
         props: TextProperties,
@@ -86,16 +152,73 @@ impl RcTextBuffer {
         is_editor: bool,
         font_system: &mut FontSystem,
     ) -> Self {
-        let mut raw_buffer = glyphon::Buffer::new(font_system, props.metrics);
-
-        raw_buffer.set_size(font_system, bounds_width, bounds_height);
-        raw_buffer.set_wrap(font_system, props.wrap);
+        let mut raw_buffer = new_raw_buffer(&props, bounds_width, bounds_height, font_system);
         raw_buffer.set_text(font_system, text, props.attrs, props.shaping);
 
-        let has_text = !text.is_empty();
-        if has_text {
+        Self::from_raw_buffer(
+            raw_buffer,
+            props,
+            bounds_width,
+            bounds_height,
+            !text.is_empty(),
+            is_editor,
+            font_system,
+        )
+    }
+
+    /// Create a text buffer from a list of spans of text, where each span has
+    /// its own attributes (for example for syntax highlighting).
+    ///
+    /// The attributes of each span can set the color, weight, style, and
+    /// family of its glyphs. Glyphs whose attributes do not set a color use
+    /// the color of the [`crate::TextPrimitive`]. The attributes in `props`
+    /// are only used for an empty buffer and for text that is inserted later.
+    pub fn new_rich<'r, 's>(
+        spans: impl IntoIterator<Item = (&'s str, Attrs<'r>)>,
+        props: TextProperties,
+        bounds_width: Option<f32>,
+        bounds_height: Option<f32>,
+        is_editor: bool,
+        font_system: &mut FontSystem,
+    ) -> Self {
+        let mut raw_buffer = new_raw_buffer(&props, bounds_width, bounds_height, font_system);
+
+        let mut has_text = false;
+        raw_buffer.set_rich_text(
+            font_system,
+            spans
+                .into_iter()
+                .inspect(|(text, _)| has_text |= !text.is_empty()),
+            props.attrs,
+            props.shaping,
+        );
+
+        Self::from_raw_buffer(
+            raw_buffer,
+            props,
+            bounds_width,
+            bounds_height,
+            has_text,
+            is_editor,
+            font_system,
+        )
+    }
+
+    fn from_raw_buffer(
+        mut raw_buffer: glyphon::Buffer,
+        props: TextProperties,
+        bounds_width: Option<f32>,
+        bounds_height: Option<f32>,
+        has_text: bool,
+        is_editor: bool,
+        font_system: &mut FontSystem,
+    ) -> Self {
+        let decorations = if has_text {
             shape(&mut raw_buffer, font_system, props.align);
-        }
+            text_decorations(&raw_buffer, font_system, &props)
+        } else {
+            Vec::new()
+        };
 
         let raw_buffer = if is_editor {
             BufferType::Editor(glyphon::Editor::new(raw_buffer))
@@ -110,11 +233,19 @@ impl RcTextBuffer {
                 bounds_width,
                 bounds_height,
                 has_text,
+                decorations,
             })),
             generation: 0,
+            shared_generation: Rc::new(Cell::new(0)),
         }
     }
 
+    /// An ID which uniquely identifies this text buffer (and its clones) for
+    /// as long as it is alive.
+    pub fn id(&self) -> u64 {
+        Rc::as_ptr(&self.inner) as usize as u64
+    }
+
     pub fn bounds_width(&self) -> Option<f32> {
         RefCell::borrow(&self.inner).bounds_width
     }
@@ -129,17 +260,226 @@ impl RcTextBuffer {
     }
 
     /// The minimum size (in logical points) needed to fit the text contents.
+    ///
+    /// The width is the width of the widest visual line, and the height is
+    /// the bottom of the last visual line, so wrapped lines and lines with
+    /// different heights are accounted for. The size is updated whenever the
+    /// text or bounds of the buffer change.
     pub fn measure(&self) -> Size {
         let inner = RefCell::borrow(&self.inner);
         let buffer = inner.raw_buffer.raw();
 
-        let (width, total_lines) = buffer
+        buffer.layout_runs().fold(Size::zero(), |size, run| {
+            Size::new(
+                run.line_w.max(size.width),
+                (run.line_top + run.line_height).max(size.height),
+            )
+        })
+    }
+
+    /// The layout of each visual line of the text, from top to bottom.
+    ///
+    /// Wrapped lines are reported as one entry per visual line. The layout is
+    /// copied when this is called, so it reflects any edits made through
+    /// [`RcTextBuffer::with_editor_mut`] before then.
+    pub fn lines(&self) -> impl Iterator<Item = LineInfo> {
+        let inner = RefCell::borrow(&self.inner);
+        let buffer = inner.raw_buffer.raw();
+
+        let lines: SmallVec<[LineInfo; 8]> = buffer
             .layout_runs()
-            .fold((0.0, 0usize), |(width, total_lines), run| {
-                (run.line_w.max(width), total_lines + 1)
-            });
+            .map(|run| LineInfo {
+                line_index: run.line_i,
+                y: run.line_top,
+                height: run.line_height,
+                width: run.line_w,
+            })
+            .collect();
+
+        lines.into_iter()
+    }
+
+    /// The rectangles (in logical points, relative to the top-left corner of
+    /// the buffer) which cover the glyphs in the given byte range of the text.
+    ///
+    /// The range is in bytes of the whole text, including line endings. One
+    /// rectangle is returned per contiguous span of selected glyphs in each
+    /// visual line, so a bidirectional line may produce multiple rectangles.
+    /// If the range covers a line ending, a small rectangle is added at the
+    /// end of that line to show that the newline is selected.
+    pub fn highlight_rects(&self, range: Range<usize>) -> SmallVec<[Rect; 4]> {
+        let mut rects = SmallVec::new();
+
+        if range.is_empty() {
+            return rects;
+        }
+
+        let inner = RefCell::borrow(&self.inner);
+        let buffer = inner.raw_buffer.raw();
+
+        let line_starts = line_starts(buffer);
+
+        let newline_width = buffer.metrics().font_size * 0.5;
+
+        let mut runs = buffer.layout_runs().peekable();
+        while let Some(run) = runs.next() {
+            let line_start = line_starts[run.line_i];
+            let line_end = line_start + run.text.len();
+
+            let is_last_run_in_line = runs
+                .peek()
+                .map(|next| next.line_i != run.line_i)
+                .unwrap_or(true);
+
+            if range.end <= line_start {
+                break;
+            }
+
+            let mut push_rect = |x0: f32, x1: f32| {
+                rects.push(Rect::new(
+                    Point::new(x0, run.line_top),
+                    Size::new(x1 - x0, run.line_height),
+                ));
+            };
+
+            if range.start < line_end {
+                let sel_start = range.start.max(line_start) - line_start;
+                let sel_end = range.end.min(line_end) - line_start;
+
+                // Glyphs are stored in visual order, so merging neighboring
+                // selected glyphs gives one span per visual run of the
+                // selection.
+                let mut span: Option<(f32, f32)> = None;
+                for glyph in run.glyphs.iter() {
+                    if glyph.start < sel_end && glyph.end > sel_start {
+                        span = Some(match span {
+                            Some((x0, x1)) => (x0.min(glyph.x), x1.max(glyph.x + glyph.w)),
+                            None => (glyph.x, glyph.x + glyph.w),
+                        });
+                    } else if let Some((x0, x1)) = span.take() {
+                        push_rect(x0, x1);
+                    }
+                }
+                if let Some((x0, x1)) = span {
+                    push_rect(x0, x1);
+                }
+            }
+
+            let ending_len = buffer.lines[run.line_i].ending().as_str().len();
+            if is_last_run_in_line
+                && ending_len > 0
+                && range.start < line_end + ending_len
+                && range.end > line_end
+            {
+                // The line ending goes at the visual end of the line.
+                let x = run_visual_end(&run);
+                if run.rtl {
+                    push_rect(x - newline_width, x);
+                } else {
+                    push_rect(x, x + newline_width);
+                }
+            }
+        }
+
+        rects
+    }
+
+    /// The rectangle of a text cursor (caret) placed before the byte at
+    /// `index` in the whole text, in logical points relative to the top-left
+    /// corner of the buffer. The rectangle has a width of zero.
+    ///
+    /// The index is in bytes of the whole text, including line endings. An
+    /// index which lies in a line ending or past the end of the text places
+    /// the cursor at the end of that line. When a line is wrapped, an index at
+    /// the wrap point places the cursor at the start of the next visual line.
+    ///
+    /// The height of the rectangle is the height of the visual line, which
+    /// accounts for lines with mixed font sizes.
+    pub fn cursor_rect(&self, index: usize) -> Rect {
+        let inner = RefCell::borrow(&self.inner);
+        let buffer = inner.raw_buffer.raw();
+
+        let line_starts = line_starts(buffer);
+
+        // Find the line containing the index, clamping indices in a line
+        // ending or past the end of the text to the end of the line.
+        let mut line_i = 0;
+        let mut local_index = 0;
+        for (i, line) in buffer.lines.iter().enumerate() {
+            line_i = i;
+            local_index = index.saturating_sub(line_starts[i]).min(line.text().len());
+
+            if index < line_starts[i] + line.text().len() + line.ending().as_str().len() {
+                break;
+            }
+        }
 
-        Size::new(width, total_lines as f32 * buffer.metrics().line_height)
+        let mut last_run_in_line = None;
+        for run in buffer.layout_runs() {
+            if run.line_i < line_i {
+                continue;
+            }
+            if run.line_i > line_i {
+                break;
+            }
+
+            for glyph in run.glyphs.iter() {
+                if local_index >= glyph.start && local_index < glyph.end {
+                    let x = if glyph.level.is_rtl() {
+                        glyph.x + glyph.w
+                    } else {
+                        glyph.x
+                    };
+
+                    return Rect::new(Point::new(x, run.line_top), Size::new(0.0, run.line_height));
+                }
+            }
+
+            last_run_in_line = Some((run.line_top, run.line_height, run_visual_end(&run)));
+        }
+
+        // The index is at the end of the line.
+        if let Some((line_top, line_height, x)) = last_run_in_line {
+            Rect::new(Point::new(x, line_top), Size::new(0.0, line_height))
+        } else {
+            Rect::new(
+                Point::default(),
+                Size::new(0.0, buffer.metrics().line_height),
+            )
+        }
+    }
+
+    /// Map a point (in logical points, relative to the top-left corner of the
+    /// buffer) to the byte index of the nearest cursor position in the whole
+    /// text.
+    ///
+    /// Points past the end of a line snap to the nearest glyph edge in that
+    /// line. An empty buffer always returns `Some(0)`. Returns `None` if the
+    /// point could not be mapped to a line.
+    pub fn hit_test(&self, point: Point) -> Option<usize> {
+        let inner = RefCell::borrow(&self.inner);
+        let buffer = inner.raw_buffer.raw();
+
+        if !inner.has_text {
+            return Some(0);
+        }
+
+        let cursor = buffer.hit(point.x, point.y)?;
+
+        let line_starts = line_starts(buffer);
+
+        line_starts
+            .get(cursor.line)
+            .map(|line_start| line_start + cursor.index)
+    }
+
+    /// The top-left point of the text cursor placed before the byte at
+    /// `index` in the whole text, in logical points relative to the top-left
+    /// corner of the buffer.
+    ///
+    /// This is the origin of [`RcTextBuffer::cursor_rect`].
+    pub fn byte_to_point(&self, index: usize) -> Point {
+        self.cursor_rect(index).origin
     }
 
     pub fn set_text_and_props(
@@ -153,6 +493,7 @@ impl RcTextBuffer {
             raw_buffer,
             props,
             has_text,
+            decorations,
             ..
         } = &mut *inner;
 
@@ -176,7 +517,51 @@ impl RcTextBuffer {
 
         *props = new_props;
 
-        self.generation += 1;
+        *decorations = if *has_text {
+            text_decorations(raw_buffer, font_system, props)
+        } else {
+            Vec::new()
+        };
+
+        self.generation = next_generation(&self.shared_generation);
+    }
+
+    /// Replace the text with a list of spans of text, where each span has its
+    /// own attributes. See [`RcTextBuffer::new_rich`].
+    pub fn set_rich_text<'r, 's>(
+        &mut self,
+        spans: impl IntoIterator<Item = (&'s str, Attrs<'r>)>,
+        font_system: &mut FontSystem,
+    ) {
+        let mut inner = RefCell::borrow_mut(&self.inner);
+        let TextBufferInner {
+            raw_buffer,
+            props,
+            has_text,
+            decorations,
+            ..
+        } = &mut *inner;
+
+        let raw_buffer = raw_buffer.raw_mut();
+
+        *has_text = false;
+        raw_buffer.set_rich_text(
+            font_system,
+            spans
+                .into_iter()
+                .inspect(|(text, _)| *has_text |= !text.is_empty()),
+            props.attrs,
+            props.shaping,
+        );
+
+        *decorations = if *has_text {
+            shape(raw_buffer, font_system, props.align);
+            text_decorations(raw_buffer, font_system, props)
+        } else {
+            Vec::new()
+        };
+
+        self.generation = next_generation(&self.shared_generation);
     }
 
     pub fn set_text(&mut self, text: &str, font_system: &mut FontSystem) {
@@ -187,6 +572,7 @@ impl RcTextBuffer {
             bounds_width: _,
             bounds_height: _,
             has_text,
+            decorations,
         } = &mut *inner;
 
         let raw_buffer = raw_buffer.raw_mut();
@@ -195,14 +581,148 @@ impl RcTextBuffer {
 
         *has_text = !text.is_empty();
 
-        if *has_text {
+        *decorations = if *has_text {
             shape(raw_buffer, font_system, props.align);
+            text_decorations(raw_buffer, font_system, props)
+        } else {
+            Vec::new()
+        };
+
+        self.generation = next_generation(&self.shared_generation);
+    }
+
+    /// Insert text before the byte at `index` in the whole text, and shape the
+    /// buffer again.
+    ///
+    /// The index is in bytes of the whole text, including line endings. An
+    /// index which lies in a line ending or past the end of the text inserts
+    /// at the end of that line. The inserted text uses the attributes of the
+    /// text before it.
+    ///
+    /// Only the edited lines are changed, so the rest of the buffer is
+    /// reused. If this buffer has an editor, its cursor is moved to the end
+    /// of the inserted text.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if `index` does not lie on a char boundary.
+    pub fn insert_str(&mut self, index: usize, text: &str, font_system: &mut FontSystem) {
+        if text.is_empty() {
+            return;
         }
 
-        self.generation += 1;
+        let mut inner = RefCell::borrow_mut(&self.inner);
+        let TextBufferInner {
+            raw_buffer,
+            props,
+            has_text,
+            decorations,
+            ..
+        } = &mut *inner;
+
+        let cursor = byte_to_cursor(raw_buffer.raw(), index);
+
+        match raw_buffer {
+            BufferType::Normal(buffer) => {
+                glyphon::Editor::new(buffer).insert_at(cursor, text, None);
+            }
+            BufferType::Editor(editor) => {
+                let cursor = editor.insert_at(cursor, text, None);
+                editor.set_cursor(cursor);
+                editor.set_selection(Selection::None);
+            }
+        }
+
+        let raw_buffer = raw_buffer.raw_mut();
+
+        *has_text = true;
+
+        shape(raw_buffer, font_system, props.align);
+        *decorations = text_decorations(raw_buffer, font_system, props);
+
+        self.generation = next_generation(&self.shared_generation);
     }
 
-    /// Set the bounds of the text in logical points.
+    /// Delete the given byte range of the whole text, and shape the buffer
+    /// again.
+    ///
+    /// The range is in bytes of the whole text, including line endings, and
+    /// is clamped the same way as the index in [`RcTextBuffer::insert_str`].
+    /// Deleting a line ending joins the two lines.
+    ///
+    /// Only the edited lines are changed, so the rest of the buffer is
+    /// reused. If this buffer has an editor, its cursor is moved to the start
+    /// of the deleted range.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if the start or end of the range does not lie on a
+    /// char boundary.
+    pub fn delete_range(&mut self, range: Range<usize>, font_system: &mut FontSystem) {
+        if range.is_empty() {
+            return;
+        }
+
+        let mut inner = RefCell::borrow_mut(&self.inner);
+        let TextBufferInner {
+            raw_buffer,
+            props,
+            has_text,
+            decorations,
+            ..
+        } = &mut *inner;
+
+        let start = byte_to_cursor(raw_buffer.raw(), range.start);
+        let end = byte_to_cursor(raw_buffer.raw(), range.end);
+        if start == end {
+            return;
+        }
+
+        match raw_buffer {
+            BufferType::Normal(buffer) => {
+                glyphon::Editor::new(buffer).delete_range(start, end);
+            }
+            BufferType::Editor(editor) => {
+                editor.delete_range(start, end);
+                editor.set_cursor(start);
+                editor.set_selection(Selection::None);
+            }
+        }
+
+        let raw_buffer = raw_buffer.raw_mut();
+
+        *has_text = raw_buffer.lines.len() > 1
+            || raw_buffer
+                .lines
+                .first()
+                .is_some_and(|line| !line.text().is_empty());
+
+        *decorations = if *has_text {
+            shape(raw_buffer, font_system, props.align);
+            text_decorations(raw_buffer, font_system, props)
+        } else {
+            Vec::new()
+        };
+
+        self.generation = next_generation(&self.shared_generation);
+    }
+
+    /// The width in logical points that the text is wrapped to.
+    ///
+    /// This is the same as [`RcTextBuffer::bounds_width`].
+    pub fn wrap_width(&self) -> Option<f32> {
+        self.bounds_width()
+    }
+
+    /// Set the width in logical points that the text is wrapped to, without
+    /// changing the height of the bounds (see [`RcTextBuffer::new`]).
+    pub fn set_wrap_width(&mut self, wrap_width: Option<f32>, font_system: &mut FontSystem) {
+        let bounds_height = self.bounds_height();
+        self.set_bounds(wrap_width, bounds_height, font_system);
+    }
+
+    /// Set the bounds of the text in logical points (see
+    /// [`RcTextBuffer::new`]).
     pub fn set_bounds(
         &mut self,
         bounds_width: Option<f32>,
@@ -216,6 +736,7 @@ impl RcTextBuffer {
             bounds_width: inner_bounds_width,
             bounds_height: inner_bounds_height,
             has_text,
+            decorations,
         } = &mut *inner;
 
         if *inner_bounds_width == bounds_width && *inner_bounds_height == bounds_height {
@@ -230,9 +751,18 @@ impl RcTextBuffer {
 
         if *has_text {
             shape(raw_buffer, font_system, props.align);
+            *decorations = text_decorations(raw_buffer, font_system, props);
         }
 
-        self.generation += 1;
+        self.generation = next_generation(&self.shared_generation);
+    }
+
+    /// The underline and strikethrough lines of the text, computed from the
+    /// [`TextProperties::underline`] and [`TextProperties::strikethrough`]
+    /// properties when the text is shaped.
+    pub fn decorations(&self) -> Ref<'_, [TextDecoration]> {
+        let inner = RefCell::borrow(&self.inner);
+        Ref::map(inner, |inner| inner.decorations.as_slice())
     }
 
     pub fn buffer(&self) -> Ref<'_, BufferType> {
@@ -263,6 +793,7 @@ impl RcTextBuffer {
             bounds_width: _,
             bounds_height: _,
             has_text,
+            decorations,
         } = &mut *inner;
 
         if let BufferType::Editor(editor) = raw_buffer {
@@ -271,16 +802,19 @@ impl RcTextBuffer {
             if status.text_changed {
                 *has_text = status.has_text;
 
-                if *has_text {
+                *decorations = if *has_text {
                     let b = match editor.buffer_ref_mut() {
                         BufferRef::Owned(b) => b,
                         _ => unreachable!(),
                     };
 
                     shape(b, font_system, props.align);
-                }
+                    text_decorations(b, font_system, props)
+                } else {
+                    Vec::new()
+                };
 
-                self.generation += 1;
+                self.generation = next_generation(&self.shared_generation);
             }
         }
     }
@@ -305,7 +839,7 @@ impl RcTextBuffer {
 
     pub fn sync_state_from_editor(&mut self) {
         // TODO
-        self.generation += 1;
+        self.generation = next_generation(&self.shared_generation);
     }
 }
 
@@ -319,7 +853,8 @@ impl Clone for RcTextBuffer {
     fn clone(&self) -> Self {
         Self {
             inner: Rc::clone(&self.inner),
-            generation: self.generation,
+            generation: self.shared_generation.get(),
+            shared_generation: Rc::clone(&self.shared_generation),
         }
     }
 }
@@ -330,6 +865,233 @@ impl PartialEq for RcTextBuffer {
     }
 }
 
+/// Text buffers are serialized as the opaque [`RcTextBuffer::id`].
+/// Deserializing requires a resolver for [`RcTextBuffer`] to be active, see
+/// [`rootvg_core::serde_handle::with_resolver`].
+#[cfg(feature = "serde")]
+impl serde::Serialize for RcTextBuffer {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.id())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for RcTextBuffer {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let id = <u64 as serde::Deserialize>::deserialize(deserializer)?;
+
+        rootvg_core::serde_handle::resolve(id).ok_or_else(|| {
+            <D::Error as serde::de::Error>::custom(format!(
+                "could not resolve text buffer with ID {id}"
+            ))
+        })
+    }
+}
+
+/// Create a raw buffer with the metrics, bounds, and wrapping of the given
+/// properties.
+fn new_raw_buffer(
+    props: &TextProperties,
+    bounds_width: Option<f32>,
+    bounds_height: Option<f32>,
+    font_system: &mut FontSystem,
+) -> glyphon::Buffer {
+    let mut raw_buffer = glyphon::Buffer::new(font_system, props.metrics);
+
+    raw_buffer.set_size(font_system, bounds_width, bounds_height);
+    raw_buffer.set_wrap(font_system, props.wrap);
+
+    raw_buffer
+}
+
+/// Increment the shared generation of a text buffer, returning the new
+/// generation.
+fn next_generation(shared_generation: &Cell<u64>) -> u64 {
+    let generation = shared_generation.get() + 1;
+    shared_generation.set(generation);
+    generation
+}
+
+/// The cursor placed before the byte at `index` in the whole text. Indices in
+/// a line ending or past the end of the text are clamped to the end of the
+/// line.
+fn byte_to_cursor(buffer: &glyphon::Buffer, index: usize) -> Cursor {
+    let mut cursor = Cursor::new(0, 0);
+
+    let mut line_start = 0;
+    for (i, line) in buffer.lines.iter().enumerate() {
+        let line_end = line_start + line.text().len();
+        cursor = Cursor::new(i, index.saturating_sub(line_start).min(line.text().len()));
+
+        if index < line_end + line.ending().as_str().len() {
+            break;
+        }
+        line_start = line_end + line.ending().as_str().len();
+    }
+
+    cursor
+}
+
+/// The byte offset of the start of each line in the whole text.
+fn line_starts(buffer: &glyphon::Buffer) -> SmallVec<[usize; 16]> {
+    let mut line_starts = SmallVec::with_capacity(buffer.lines.len());
+
+    let mut offset = 0;
+    for line in buffer.lines.iter() {
+        line_starts.push(offset);
+        offset += line.text().len() + line.ending().as_str().len();
+    }
+
+    line_starts
+}
+
+/// The x coordinate of the visual end of a layout run, which is on the left
+/// side for right-to-left runs.
+fn run_visual_end(run: &glyphon::cosmic_text::LayoutRun) -> f32 {
+    if run.rtl {
+        run.glyphs
+            .iter()
+            .map(|g| g.x)
+            .reduce(f32::min)
+            .unwrap_or(0.0)
+    } else {
+        run.glyphs
+            .iter()
+            .map(|g| g.x + g.w)
+            .reduce(f32::max)
+            .unwrap_or(0.0)
+    }
+}
+
+/// The position and thickness of decoration lines of a font, in ems.
+#[derive(Debug, Clone, Copy)]
+struct DecorationMetrics {
+    /// The distance from the baseline to the top of the underline (positive
+    /// is up).
+    underline_offset: f32,
+    /// The distance from the baseline to the top of the strikethrough line
+    /// (positive is up).
+    strikeout_offset: f32,
+    stroke_size: f32,
+}
+
+impl DecorationMetrics {
+    /// Used when the font does not provide decoration metrics.
+    const FALLBACK: Self = Self {
+        underline_offset: -0.1,
+        strikeout_offset: 0.3,
+        stroke_size: 1.0 / 14.0,
+    };
+
+    fn new(font_system: &mut FontSystem, font_id: fontdb::ID) -> Self {
+        let Some(font) = font_system.get_font(font_id) else {
+            return Self::FALLBACK;
+        };
+
+        let metrics = font.as_swash().metrics(&[]);
+        let units_per_em = metrics.units_per_em as f32;
+
+        if units_per_em <= 0.0 || metrics.stroke_size <= 0.0 {
+            return Self::FALLBACK;
+        }
+
+        Self {
+            underline_offset: metrics.underline_offset / units_per_em,
+            strikeout_offset: metrics.strikeout_offset / units_per_em,
+            stroke_size: metrics.stroke_size / units_per_em,
+        }
+    }
+}
+
+fn text_decorations(
+    buffer: &glyphon::Buffer,
+    font_system: &mut FontSystem,
+    props: &TextProperties,
+) -> Vec<TextDecoration> {
+    let mut decorations = Vec::new();
+
+    if !props.underline && !props.strikethrough {
+        return decorations;
+    }
+
+    let mut font_metrics: FxHashMap<fontdb::ID, DecorationMetrics> = FxHashMap::default();
+
+    struct Span {
+        x0: f32,
+        x1: f32,
+        color: Option<RGBA8>,
+        /// The size and metrics of the largest glyph in the span.
+        font_size: f32,
+        metrics: DecorationMetrics,
+    }
+
+    for run in buffer.layout_runs() {
+        let mut push_span = |span: Span| {
+            let thickness = span.metrics.stroke_size * span.font_size;
+
+            let mut push_line = |offset: f32| {
+                decorations.push(TextDecoration {
+                    rect: Rect::new(
+                        Point::new(span.x0, run.line_y - (offset * span.font_size)),
+                        Size::new(span.x1 - span.x0, thickness),
+                    ),
+                    color: span.color,
+                });
+            };
+
+            if props.underline {
+                push_line(span.metrics.underline_offset);
+            }
+            if props.strikethrough {
+                push_line(span.metrics.strikeout_offset);
+            }
+        };
+
+        // Split the lines wherever the color of the glyphs changes.
+        let mut span: Option<Span> = None;
+        for glyph in run.glyphs.iter() {
+            let color = glyph
+                .color_opt
+                .map(|c| RGBA8::new(c.r(), c.g(), c.b(), c.a()));
+
+            let metrics = *font_metrics
+                .entry(glyph.font_id)
+                .or_insert_with(|| DecorationMetrics::new(font_system, glyph.font_id));
+
+            match &mut span {
+                Some(s) if s.color == color => {
+                    s.x0 = s.x0.min(glyph.x);
+                    s.x1 = s.x1.max(glyph.x + glyph.w);
+
+                    if glyph.font_size > s.font_size {
+                        s.font_size = glyph.font_size;
+                        s.metrics = metrics;
+                    }
+                }
+                _ => {
+                    if let Some(s) = span.take() {
+                        push_span(s);
+                    }
+
+                    span = Some(Span {
+                        x0: glyph.x,
+                        x1: glyph.x + glyph.w,
+                        color,
+                        font_size: glyph.font_size,
+                        metrics,
+                    });
+                }
+            }
+        }
+
+        if let Some(s) = span {
+            push_span(s);
+        }
+    }
+
+    decorations
+}
+
 fn shape(
     buffer: &mut glyphon::Buffer,
     font_system: &mut glyphon::FontSystem,
@@ -343,3 +1105,242 @@ fn shape(
 
     buffer.shape_until_scroll(font_system, true);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Metrics, Shaping, Wrap};
+
+    const FONT_SIZE: f32 = 20.0;
+    const LINE_HEIGHT: f32 = 25.0;
+
+    /// A font system with a single font, so the layout doesn't depend on the
+    /// fonts installed on the system.
+    fn font_system() -> FontSystem {
+        let mut db = fontdb::Database::new();
+        db.load_font_data(include_bytes!("../../../assets/fonts/DejaVuSans.ttf").to_vec());
+        db.set_sans_serif_family("DejaVu Sans");
+
+        FontSystem::new_with_locale_and_db("en-US".into(), db)
+    }
+
+    fn props() -> TextProperties {
+        TextProperties {
+            metrics: Metrics::new(FONT_SIZE, LINE_HEIGHT),
+            shaping: Shaping::Advanced,
+            ..Default::default()
+        }
+    }
+
+    fn buffer(text: &str, font_system: &mut FontSystem) -> RcTextBuffer {
+        RcTextBuffer::new(text, props(), None, None, false, font_system)
+    }
+
+    #[test]
+    fn highlight_rects_cover_each_line_of_a_multi_line_selection() {
+        let mut font_system = font_system();
+        let buffer = buffer("abc\ndef\nghi", &mut font_system);
+
+        // Selects "bc\ndef\ng".
+        let rects = buffer.highlight_rects(1..9);
+
+        // The glyphs and the line ending of the first two lines, and the
+        // glyph of the last line.
+        assert_eq!(rects.len(), 5, "{rects:?}");
+
+        let tops: Vec<f32> = rects.iter().map(|r| r.min_y()).collect();
+        assert_eq!(
+            tops,
+            [0.0, 0.0, LINE_HEIGHT, LINE_HEIGHT, LINE_HEIGHT * 2.0]
+        );
+        assert!(rects.iter().all(|r| r.height() == LINE_HEIGHT));
+
+        // The line endings follow the glyphs of their line.
+        assert!((rects[1].min_x() - rects[0].max_x()).abs() < 1e-3);
+        assert!((rects[3].min_x() - rects[2].max_x()).abs() < 1e-3);
+    }
+
+    #[test]
+    fn highlight_rects_include_a_trailing_line_ending() {
+        let mut font_system = font_system();
+        let buffer = buffer("abc\n", &mut font_system);
+
+        let rects = buffer.highlight_rects(0..4);
+        assert_eq!(rects.len(), 2, "{rects:?}");
+        assert_eq!(rects[0].min_x(), 0.0);
+        assert!((rects[1].min_x() - rects[0].max_x()).abs() < 1e-3);
+        assert!((rects[1].width() - FONT_SIZE * 0.5).abs() < 1e-3);
+
+        // Only the line ending.
+        let rects = buffer.highlight_rects(3..4);
+        assert_eq!(rects.len(), 1, "{rects:?}");
+        assert!((rects[0].width() - FONT_SIZE * 0.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn highlight_rects_split_a_selection_across_bidi_runs() {
+        let mut font_system = font_system();
+        // Displayed as "abc גבא def".
+        let buffer = buffer("abc \u{5d0}\u{5d1}\u{5d2} def", &mut font_system);
+
+        // Selects "c \u{5d0}\u{5d1}", which is contiguous in the text but
+        // not on screen, since "\u{5d2}" is displayed between the space and
+        // "\u{5d1}".
+        let rects = buffer.highlight_rects(2..8);
+
+        assert_eq!(rects.len(), 2, "{rects:?}");
+        assert!(rects.iter().all(|r| r.min_y() == 0.0));
+        assert!(rects[0].max_x() < rects[1].min_x());
+    }
+
+    #[test]
+    fn cursor_rect_is_placed_before_the_glyph_at_the_index() {
+        let mut font_system = font_system();
+        let buffer = buffer("abcd", &mut font_system);
+
+        let width = buffer.lines().next().unwrap().width;
+
+        let start = buffer.cursor_rect(0);
+        let middle = buffer.cursor_rect(2);
+        let end = buffer.cursor_rect(4);
+
+        assert_eq!(start.origin, Point::new(0.0, 0.0));
+        assert!(middle.min_x() > 0.0 && middle.min_x() < width);
+        assert!((end.min_x() - width).abs() < 1e-3);
+
+        for rect in [start, middle, end] {
+            assert_eq!(rect.min_y(), 0.0);
+            assert_eq!(rect.size, Size::new(0.0, LINE_HEIGHT));
+        }
+    }
+
+    #[test]
+    fn hit_test_inverts_byte_to_point() {
+        let mut font_system = font_system();
+        let buffer = buffer("hello\nworld", &mut font_system);
+
+        // Includes the line ending and the start and end of each line.
+        for index in [0, 3, 5, 6, 9, 11] {
+            assert_eq!(
+                buffer.hit_test(buffer.byte_to_point(index)),
+                Some(index),
+                "{index}"
+            );
+        }
+    }
+
+    #[test]
+    fn hit_test_past_the_end_of_a_line_snaps_to_the_line_end() {
+        let mut font_system = font_system();
+        let buffer = buffer("hello\nworld!", &mut font_system);
+
+        assert_eq!(buffer.hit_test(Point::new(1000.0, 5.0)), Some(5));
+        assert_eq!(
+            buffer.hit_test(Point::new(1000.0, LINE_HEIGHT + 5.0)),
+            Some(12)
+        );
+    }
+
+    #[test]
+    fn hit_test_in_an_empty_buffer_is_the_start() {
+        let mut font_system = font_system();
+        let buffer = buffer("", &mut font_system);
+
+        assert_eq!(buffer.hit_test(Point::new(0.0, 0.0)), Some(0));
+        assert_eq!(buffer.hit_test(Point::new(50.0, 100.0)), Some(0));
+    }
+
+    #[test]
+    fn lines_are_stacked_by_the_line_height() {
+        let mut font_system = font_system();
+        let buffer = buffer("one\ntwo\nthree", &mut font_system);
+
+        let lines: Vec<LineInfo> = buffer.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        for (i, line) in lines.iter().enumerate() {
+            assert_eq!(line.line_index, i);
+            assert_eq!(line.y, LINE_HEIGHT * i as f32);
+            assert_eq!(line.height, LINE_HEIGHT);
+            assert!(line.width > 0.0);
+        }
+    }
+
+    #[test]
+    fn wrapped_lines_share_a_line_index() {
+        let mut font_system = font_system();
+        let props = TextProperties {
+            wrap: Wrap::Word,
+            ..props()
+        };
+        let buffer = RcTextBuffer::new(
+            "hello world again",
+            props,
+            Some(80.0),
+            None,
+            false,
+            &mut font_system,
+        );
+
+        let lines: Vec<LineInfo> = buffer.lines().collect();
+
+        assert!(lines.len() > 1, "{lines:?}");
+        for (i, line) in lines.iter().enumerate() {
+            assert_eq!(line.line_index, 0);
+            assert_eq!(line.y, LINE_HEIGHT * i as f32);
+            assert!(line.width <= 80.0);
+        }
+    }
+
+    fn glyph_count(buffer: &RcTextBuffer) -> usize {
+        buffer
+            .raw_buffer()
+            .layout_runs()
+            .map(|run| run.glyphs.len())
+            .sum()
+    }
+
+    #[test]
+    fn edits_change_the_shaped_glyphs() {
+        let mut font_system = font_system();
+        let mut buffer = buffer("abc", &mut font_system);
+        assert_eq!(glyph_count(&buffer), 3);
+
+        buffer.insert_str(3, "de", &mut font_system);
+        assert_eq!(glyph_count(&buffer), 5);
+
+        buffer.insert_str(1, "\nxy", &mut font_system);
+        assert_eq!(glyph_count(&buffer), 7);
+        assert_eq!(buffer.lines().count(), 2);
+
+        // Joins the lines again and removes "xy".
+        buffer.delete_range(1..4, &mut font_system);
+        assert_eq!(glyph_count(&buffer), 5);
+        assert_eq!(buffer.lines().count(), 1);
+    }
+
+    #[test]
+    fn clones_made_after_an_edit_compare_unequal() {
+        let mut font_system = font_system();
+        let mut buffer = buffer("abc", &mut font_system);
+
+        let before = buffer.clone();
+        assert_eq!(before, buffer);
+
+        buffer.insert_str(0, "x", &mut font_system);
+        let after_insert = buffer.clone();
+        assert_ne!(before, after_insert);
+        assert_eq!(after_insert, buffer);
+
+        buffer.delete_range(0..1, &mut font_system);
+        let after_delete = buffer.clone();
+        assert_ne!(after_insert, after_delete);
+        assert_ne!(before, after_delete);
+        assert_eq!(after_delete, buffer);
+
+        // Empty edits don't change anything.
+        buffer.insert_str(0, "", &mut font_system);
+        buffer.delete_range(1..1, &mut font_system);
+        assert_eq!(after_delete, buffer.clone());
+    }
+}
@@ -4,6 +4,7 @@
 mod buffer;
 mod primitive;
 mod properties;
+mod rotated;
 
 pub mod pipeline;
 
@@ -14,12 +15,13 @@ pub use glyphon;
 
 pub use glyphon::cosmic_text::Align;
 pub use glyphon::{
-    Attrs, ContentType, Family, FamilyOwned, FontSystem, Metrics, Shaping, Stretch, Style, Weight,
-    Wrap,
+    Attrs, Color, ContentType, Family, FamilyOwned, FontSystem, Metrics, Shaping, Stretch, Style,
+    Weight, Wrap,
 };
 
-pub use buffer::{EditorBorrowStatus, RcTextBuffer};
-pub use primitive::TextPrimitive;
+pub use buffer::{EditorBorrowStatus, LineInfo, RcTextBuffer, TextDecoration};
+pub use pipeline::SharedTextResources;
+pub use primitive::{TextHighlight, TextOutline, TextPrimitive, TextRotation, TextShadow};
 pub use properties::TextProperties;
 
 #[cfg(feature = "svg-icons")]
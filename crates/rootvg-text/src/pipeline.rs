@@ -1,29 +1,167 @@
-use std::cell::Ref;
+use std::cell::{Ref, RefCell};
+use std::rc::Rc;
 
 use glyphon::{
     Cache, FontSystem, Resolution, SwashCache, TextArea, TextAtlas, TextRenderer, Viewport,
 };
 
-use rootvg_core::math::{PhysicalSizeI32, ScaleFactor};
+use rootvg_core::color::RGBA8;
+use rootvg_core::math::{Angle, PhysicalPointI32, PhysicalSizeI32, Point, ScaleFactor};
 
-use crate::{primitive::TextPrimitive, RcTextBuffer};
+use crate::primitive::{TextPrimitive, TextRotation};
+use crate::rotated::{RotatedTextBatch, RotatedTextInstance, RotatedTextPipeline};
+use crate::RcTextBuffer;
+
+/// A 3x3 kernel used to approximate the blur of text shadows, in the form
+/// `(x, y, weight)`.
+const SHADOW_KERNEL: [(f32, f32, f32); 9] = [
+    (-1.0, -1.0, 1.0 / 16.0),
+    (0.0, -1.0, 2.0 / 16.0),
+    (1.0, -1.0, 1.0 / 16.0),
+    (-1.0, 0.0, 2.0 / 16.0),
+    (0.0, 0.0, 4.0 / 16.0),
+    (1.0, 0.0, 2.0 / 16.0),
+    (-1.0, 1.0, 1.0 / 16.0),
+    (0.0, 1.0, 2.0 / 16.0),
+    (1.0, 1.0, 1.0 / 16.0),
+];
+
+const OUTLINE_DIRECTIONS: [(f32, f32); 8] = [
+    (1.0, 0.0),
+    (
+        std::f32::consts::FRAC_1_SQRT_2,
+        std::f32::consts::FRAC_1_SQRT_2,
+    ),
+    (0.0, 1.0),
+    (
+        -std::f32::consts::FRAC_1_SQRT_2,
+        std::f32::consts::FRAC_1_SQRT_2,
+    ),
+    (-1.0, 0.0),
+    (
+        -std::f32::consts::FRAC_1_SQRT_2,
+        -std::f32::consts::FRAC_1_SQRT_2,
+    ),
+    (0.0, -1.0),
+    (
+        std::f32::consts::FRAC_1_SQRT_2,
+        -std::f32::consts::FRAC_1_SQRT_2,
+    ),
+];
 
 pub struct TextBatchBuffer {
     text_renderer: TextRenderer,
+    rotated: RotatedTextBatch,
     prev_primitives: Vec<TextPrimitive>,
+    prev_depth: f32,
 }
 
-pub struct TextPipeline {
+/// The glyph atlas and caches used by a [`TextPipeline`]. See
+/// [`SharedTextResources`].
+pub struct TextResources {
     swash_cache: SwashCache,
-    //cache: Cache,
+    cache: Cache,
     atlas: TextAtlas,
+    format: wgpu::TextureFormat,
+    /// Incremented every time the atlas is trimmed.
+    trim_generation: u64,
+}
+
+impl TextResources {
+    fn trim(&mut self) {
+        self.atlas.trim();
+        self.trim_generation += 1;
+    }
+}
+
+/// A glyph atlas and glyph caches which can be shared by the text pipelines
+/// of multiple canvases, so that glyphs used by several canvases are only
+/// rasterized and stored once.
+///
+/// Cloning this creates another handle to the same resources. The
+/// resources are not thread-safe (just like `FontSystem`), so all canvases
+/// sharing them must live on the same thread. They must also be created
+/// with the same device and the same texture format as the resources; the
+/// `FontSystem` passed to each canvas should be the same one as well.
+///
+/// Glyphs are only evicted from a shared atlas once it is full and can't
+/// grow anymore. When that happens, every canvas sharing the atlas prepares
+/// all of its text again the next time it is rendered. Because evicted
+/// glyphs are overwritten right away, the commands of a canvas must be
+/// submitted to the queue before another canvas sharing the atlas is
+/// rendered. This is the case when each window renders and presents on its
+/// own.
+#[derive(Clone)]
+pub struct SharedTextResources {
+    inner: Rc<RefCell<TextResources>>,
+}
+
+impl SharedTextResources {
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue, format: wgpu::TextureFormat) -> Self {
+        let cache = Cache::new(device);
+        let atlas = TextAtlas::with_color_mode(
+            device,
+            queue,
+            &cache,
+            format,
+            if rootvg_core::color::GAMMA_CORRECTION {
+                glyphon::ColorMode::Accurate
+            } else {
+                glyphon::ColorMode::Web
+            },
+        );
+
+        Self {
+            inner: Rc::new(RefCell::new(TextResources {
+                swash_cache: SwashCache::new(),
+                cache,
+                atlas,
+                format,
+                trim_generation: 0,
+            })),
+        }
+    }
+
+    /// The texture format of the render targets these resources can be used
+    /// with.
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.inner.borrow().format
+    }
+
+    /// Borrow the resources, for example to pass them to
+    /// [`TextPipeline::render_batch`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if a pipeline using these resources is currently preparing.
+    pub fn borrow(&self) -> Ref<'_, TextResources> {
+        self.inner.borrow()
+    }
+}
+
+pub struct TextPipeline {
+    resources: SharedTextResources,
+    /// Whether or not `resources` are shared with other pipelines.
+    shared: bool,
+    /// The trim generation of the atlas when the batches of this pipeline
+    /// were last prepared.
+    trim_generation: u64,
     viewport: Viewport,
     multisample: wgpu::MultisampleState,
+    depth_stencil: Option<wgpu::DepthStencilState>,
     screen_size: PhysicalSizeI32,
     scale_factor: ScaleFactor,
+    origin: PhysicalPointI32,
     prepare_all_batches: bool,
+    /// Whether or not all batches were prepared since the glyph atlas was
+    /// last trimmed.
+    all_batches_prepared: bool,
+    /// Whether or not any batch was prepared since the glyph atlas was last
+    /// trimmed.
     atlas_needs_trimmed: bool,
     empty_text_buffer: RcTextBuffer,
+    /// Created the first time rotated text is prepared.
+    rotated_pipeline: Option<RotatedTextPipeline>,
 }
 
 impl TextPipeline {
@@ -32,44 +170,113 @@ impl TextPipeline {
         queue: &wgpu::Queue,
         format: wgpu::TextureFormat,
         multisample: wgpu::MultisampleState,
+        depth_stencil: Option<wgpu::DepthStencilState>,
         font_system: &mut FontSystem,
     ) -> Self {
-        let swash_cache = SwashCache::new();
-        let cache = Cache::new(device);
-        let viewport = Viewport::new(device, &cache);
-        let atlas = TextAtlas::with_color_mode(
+        let resources = SharedTextResources::new(device, queue, format);
+
+        Self::new_inner(
             device,
-            queue,
-            &cache,
-            format,
-            if rootvg_core::color::GAMMA_CORRECTION {
-                glyphon::ColorMode::Accurate
-            } else {
-                glyphon::ColorMode::Web
-            },
-        );
+            resources,
+            false,
+            multisample,
+            depth_stencil,
+            font_system,
+        )
+    }
+
+    /// Create a new pipeline which uses the given shared glyph atlas and
+    /// caches.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `format` is not the format of `resources`.
+    pub fn with_shared_resources(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        multisample: wgpu::MultisampleState,
+        depth_stencil: Option<wgpu::DepthStencilState>,
+        font_system: &mut FontSystem,
+        resources: SharedTextResources,
+    ) -> Self {
+        assert_eq!(format, resources.format());
+
+        Self::new_inner(
+            device,
+            resources,
+            true,
+            multisample,
+            depth_stencil,
+            font_system,
+        )
+    }
+
+    fn new_inner(
+        device: &wgpu::Device,
+        resources: SharedTextResources,
+        shared: bool,
+        multisample: wgpu::MultisampleState,
+        depth_stencil: Option<wgpu::DepthStencilState>,
+        font_system: &mut FontSystem,
+    ) -> Self {
+        let (viewport, trim_generation) = {
+            let res = resources.borrow();
+            (Viewport::new(device, &res.cache), res.trim_generation)
+        };
 
         let empty_text_buffer =
             RcTextBuffer::new("", Default::default(), None, None, false, font_system);
 
         Self {
-            swash_cache,
-            //cache,
-            atlas,
+            resources,
+            shared,
+            trim_generation,
             viewport,
             multisample,
+            depth_stencil,
             screen_size: PhysicalSizeI32::default(),
             scale_factor: ScaleFactor::default(),
+            origin: PhysicalPointI32::default(),
             prepare_all_batches: true,
+            all_batches_prepared: true,
             atlas_needs_trimmed: false,
             empty_text_buffer,
+            rotated_pipeline: None,
         }
     }
 
+    /// Change the multisample state of the batches created by this pipeline.
+    ///
+    /// Batches which were created before this call must be discarded.
+    pub fn set_multisample(&mut self, multisample: wgpu::MultisampleState) {
+        self.multisample = multisample;
+        self.prepare_all_batches = true;
+        self.rotated_pipeline = None;
+    }
+
+    /// The glyph atlas and caches used by this pipeline.
+    pub fn resources(&self) -> &SharedTextResources {
+        &self.resources
+    }
+
+    /// Returns `true` if the batches of this pipeline must be prepared again
+    /// because glyphs may have been evicted from a shared atlas by another
+    /// pipeline.
+    pub fn needs_preparing(&self) -> bool {
+        self.resources.borrow().trim_generation != self.trim_generation
+    }
+
     pub fn create_batch(&mut self, device: &wgpu::Device) -> TextBatchBuffer {
         TextBatchBuffer {
-            text_renderer: TextRenderer::new(&mut self.atlas, device, self.multisample, None),
+            text_renderer: TextRenderer::new(
+                &mut self.resources.inner.borrow_mut().atlas,
+                device,
+                self.multisample,
+                self.depth_stencil.clone(),
+            ),
+            rotated: RotatedTextBatch::new(),
             prev_primitives: Vec::new(),
+            prev_depth: 0.0,
         }
     }
 
@@ -79,15 +286,30 @@ impl TextPipeline {
         queue: &wgpu::Queue,
         screen_size: PhysicalSizeI32,
         scale_factor: ScaleFactor,
+        origin: PhysicalPointI32,
     ) {
-        if self.screen_size == screen_size && self.scale_factor == scale_factor {
+        let trim_generation = self.resources.borrow().trim_generation;
+        if self.trim_generation != trim_generation {
+            self.trim_generation = trim_generation;
+            self.prepare_all_batches = true;
+        }
+
+        if self.screen_size == screen_size
+            && self.scale_factor == scale_factor
+            && self.origin == origin
+        {
             return;
         }
 
         self.screen_size = screen_size;
         self.scale_factor = scale_factor;
+        self.origin = origin;
         self.prepare_all_batches = true;
 
+        if let Some(rotated_pipeline) = &self.rotated_pipeline {
+            rotated_pipeline.update_constants(queue, screen_size, scale_factor, origin);
+        }
+
         self.viewport.update(
             queue,
             Resolution {
@@ -101,6 +323,7 @@ impl TextPipeline {
         &mut self,
         batch: &mut TextBatchBuffer,
         primitives: &[TextPrimitive],
+        depth: f32,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
         font_system: &mut FontSystem,
@@ -108,13 +331,16 @@ impl TextPipeline {
     ) -> Result<(), glyphon::PrepareError> {
         // Don't prepare if the list of primitives hasn't changed since the last
         // preparation.
-        let primitives_are_the_same = primitives == batch.prev_primitives;
+        let primitives_are_the_same =
+            primitives == batch.prev_primitives && depth == batch.prev_depth;
         if primitives_are_the_same && !self.prepare_all_batches {
+            self.all_batches_prepared = false;
             return Ok(());
         }
 
         if !primitives_are_the_same {
             batch.prev_primitives = primitives.into();
+            batch.prev_depth = depth;
         }
 
         self.atlas_needs_trimmed = true;
@@ -137,51 +363,239 @@ impl TextPipeline {
             })
             .collect();
 
-        let text_areas: Vec<TextArea<'_>> = primitives
-            .iter()
-            .zip(borrowed_buffers.iter())
-            .map(|(p, b)| TextArea {
-                buffer: b,
-                left: p.pos.x * self.scale_factor,
-                top: (p.pos.y * self.scale_factor).round(),
-                scale: self.scale_factor.0,
-                bounds: p
-                    .clipping_bounds
-                    .map(|bounds| glyphon::TextBounds {
-                        left: ((p.pos.x + bounds.min_x()) * self.scale_factor).floor() as i32,
-                        top: ((p.pos.y + bounds.min_y()) * self.scale_factor).floor() as i32,
-                        right: ((p.pos.x + bounds.min_x() + bounds.width()) * self.scale_factor)
-                            .ceil() as i32,
-                        bottom: ((p.pos.y + bounds.min_y() + bounds.height()) * self.scale_factor)
-                            .ceil() as i32,
-                    })
-                    .unwrap_or(default_clipping_bounds),
-                default_color: glyphon::Color::rgba(p.color.r, p.color.g, p.color.b, p.color.a),
+        let scale_factor = self.scale_factor.0;
+        let (origin_x, origin_y) = (self.origin.x, self.origin.y);
+
+        let mut text_areas: Vec<TextArea<'_>> = Vec::with_capacity(primitives.len());
+
+        // Rotated text is stacked vertically in an intermediate texture, with
+        // a pixel of space around each primitive so that they don't bleed
+        // into each other when sampled.
+        let mut rotated_areas: Vec<TextArea<'_>> = Vec::new();
+        let mut rotated_instances: Vec<RotatedTextInstance> = Vec::new();
+        let mut rotated_texture_size = PhysicalSizeI32::new(2, 1);
+        let max_texture_size = device.limits().max_texture_dimension_2d as i32;
+
+        for (p, b) in primitives.iter().zip(borrowed_buffers.iter()) {
+            if p.alpha <= 0.0 {
+                continue;
+            }
+
+            let subpixel_positioning = p
+                .buffer
+                .as_ref()
+                .is_some_and(|b| b.props().subpixel_positioning);
+
+            let (left, top) = if subpixel_positioning {
+                (p.pos.x * scale_factor, p.pos.y * scale_factor)
+            } else {
+                (
+                    (p.pos.x * scale_factor).round(),
+                    (p.pos.y * scale_factor).round(),
+                )
+            };
+
+            // The text is scrolled up inside of its clipping bounds, which stay
+            // in place.
+            let scroll = if subpixel_positioning {
+                p.scroll_offset * scale_factor
+            } else {
+                (p.scroll_offset * scale_factor).round()
+            };
+
+            // glyphon can't change the opacity of glyphs which have their own
+            // color set in the attributes of the buffer, so faded text is
+            // drawn the same way as rotated text.
+            let rotation = p.rotation.or_else(|| {
+                (p.alpha < 1.0).then(|| TextRotation::new(Angle::default(), Point::zero()))
+            });
+
+            if let Some(rotation) = rotation {
+                // The area of the text in physical pixels, relative to its
+                // position.
+                let (min_x, min_y, max_x, max_y) = if let Some(bounds) = p.clipping_bounds {
+                    (
+                        (bounds.min_x() * scale_factor).floor(),
+                        (bounds.min_y() * scale_factor).floor(),
+                        (bounds.max_x() * scale_factor).ceil(),
+                        (bounds.max_y() * scale_factor).ceil(),
+                    )
+                } else {
+                    let size = p.buffer.as_ref().map(|b| b.measure()).unwrap_or_default();
+                    let extent = effect_extent(p, scale_factor);
+
+                    (
+                        -extent,
+                        -extent,
+                        (size.width * scale_factor).ceil() + extent,
+                        (size.height * scale_factor).ceil() + extent,
+                    )
+                };
+                let width = max_x - min_x;
+                let height = max_y - min_y;
+
+                let slot_y = rotated_texture_size.height;
+                if width <= 0.0
+                    || height <= 0.0
+                    || slot_y as f32 + height + 1.0 > max_texture_size as f32
+                    || width + 2.0 > max_texture_size as f32
+                {
+                    continue;
+                }
+
+                rotated_texture_size.width = rotated_texture_size.width.max(width as i32 + 2);
+                rotated_texture_size.height = slot_y + height as i32 + 1;
+
+                let bounds = glyphon::TextBounds {
+                    left: 1,
+                    top: slot_y,
+                    right: 1 + width as i32,
+                    bottom: slot_y + height as i32,
+                };
+                push_text_areas(
+                    &mut rotated_areas,
+                    p,
+                    b,
+                    1.0 - min_x,
+                    slot_y as f32 - min_y - scroll,
+                    bounds,
+                    scale_factor,
+                );
+
+                let mut pos = Point::new(left + min_x, top + min_y);
+                let mut anchor = Point::new(
+                    left + rotation.anchor.x * scale_factor,
+                    top + rotation.anchor.y * scale_factor,
+                );
+
+                // Snap rotations by a multiple of 90 degrees to whole pixels,
+                // so that the texels line up with the physical pixels.
+                let quarter_turns = rotation.angle.radians / std::f32::consts::FRAC_PI_2;
+                let (sin, cos) = if (quarter_turns - quarter_turns.round()).abs() < 1.0e-4 {
+                    pos = pos.round();
+                    anchor = anchor.round();
+
+                    match (quarter_turns.round() as i32).rem_euclid(4) {
+                        0 => (0.0, 1.0),
+                        1 => (1.0, 0.0),
+                        2 => (0.0, -1.0),
+                        _ => (-1.0, 0.0),
+                    }
+                } else {
+                    rotation.angle.radians.sin_cos()
+                };
+
+                rotated_instances.push(RotatedTextInstance {
+                    pos: pos.into(),
+                    size: [width, height],
+                    uv_pos: [1.0, slot_y as f32],
+                    anchor: anchor.into(),
+                    sin_cos: [sin, cos],
+                    alpha: p.alpha.min(1.0),
+                    depth,
+                });
+
+                continue;
+            }
+
+            let left = left + origin_x as f32;
+            let top = top + origin_y as f32;
+
+            let bounds = p
+                .clipping_bounds
+                .map(|bounds| glyphon::TextBounds {
+                    left: ((p.pos.x + bounds.min_x()) * scale_factor).floor() as i32 + origin_x,
+                    top: ((p.pos.y + bounds.min_y()) * scale_factor).floor() as i32 + origin_y,
+                    right: ((p.pos.x + bounds.min_x() + bounds.width()) * scale_factor).ceil()
+                        as i32
+                        + origin_x,
+                    bottom: ((p.pos.y + bounds.min_y() + bounds.height()) * scale_factor).ceil()
+                        as i32
+                        + origin_y,
+                })
+                .unwrap_or(default_clipping_bounds);
+
+            // Glyphs are only prepared for the lines which intersect the
+            // bounds, so scrolling through a long text stays cheap.
+            push_text_areas(
+                &mut text_areas,
+                p,
+                b,
+                left,
+                top - scroll,
+                bounds,
+                scale_factor,
+            );
+        }
+
+        let mut resources = self.resources.inner.borrow_mut();
+        let TextResources {
+            atlas,
+            swash_cache,
+            cache,
+            format,
+            ..
+        } = &mut *resources;
+
+        if rotated_instances.is_empty() {
+            batch.rotated.clear();
+        } else {
+            let rotated_pipeline = self.rotated_pipeline.get_or_insert_with(|| {
+                let rotated_pipeline = RotatedTextPipeline::new(
+                    device,
+                    *format,
+                    self.multisample,
+                    self.depth_stencil.clone(),
+                    atlas,
+                    cache,
+                );
+                rotated_pipeline.update_constants(
+                    queue,
+                    self.screen_size,
+                    self.scale_factor,
+                    self.origin,
+                );
+                rotated_pipeline
+            });
+
+            rotated_pipeline.prepare_batch(
+                &mut batch.rotated,
+                rotated_areas,
+                &rotated_instances,
+                rotated_texture_size,
+                device,
+                queue,
+                font_system,
+                atlas,
+                swash_cache,
                 #[cfg(feature = "svg-icons")]
-                custom_glyphs: p.icons.as_slice(),
-            })
-            .collect();
+                svg_system,
+            )?;
+        }
 
+        // All glyphs in a batch share the same depth.
         #[cfg(not(feature = "svg-icons"))]
-        return batch.text_renderer.prepare(
+        return batch.text_renderer.prepare_with_depth(
             device,
             queue,
             font_system,
-            &mut self.atlas,
+            atlas,
             &self.viewport,
             text_areas,
-            &mut self.swash_cache,
+            swash_cache,
+            |_| depth,
         );
 
         #[cfg(feature = "svg-icons")]
-        return batch.text_renderer.prepare_with_custom(
+        return batch.text_renderer.prepare_with_depth_and_custom(
             device,
             queue,
             font_system,
-            &mut self.atlas,
+            atlas,
             &self.viewport,
             text_areas,
-            &mut self.swash_cache,
+            swash_cache,
+            |_| depth,
             |input| svg_system.render_custom_glyph(input),
         );
     }
@@ -189,21 +603,164 @@ impl TextPipeline {
     pub fn finish_preparations(&mut self, _device: &wgpu::Device, _queue: &wgpu::Queue) {
         self.prepare_all_batches = false;
 
-        if !self.atlas_needs_trimmed {
+        // Trimming marks every glyph in the atlas as unused, so that it can be
+        // evicted to make space for new glyphs. The glyphs of batches which
+        // were skipped are not marked as used again, so the atlas is only
+        // trimmed once every batch has been prepared. Glyphs which are no
+        // longer used are otherwise evicted by `evict_unused_glyphs` once the
+        // atlas is full.
+        //
+        // A shared atlas is only trimmed once it is full, since trimming it
+        // makes every other pipeline prepare all of its batches again.
+        let all_batches_prepared = std::mem::replace(&mut self.all_batches_prepared, true);
+        if !self.atlas_needs_trimmed || !all_batches_prepared || self.shared {
             return;
         }
         self.atlas_needs_trimmed = false;
 
-        self.atlas.trim();
+        self.trim_atlas();
+    }
+
+    /// Mark every glyph in the atlas as unused and prepare all batches again
+    /// on the next preparation, so that glyphs which are no longer used can be
+    /// evicted.
+    ///
+    /// Call this when preparing a batch fails with
+    /// [`glyphon::PrepareError::AtlasFull`], and then prepare every batch
+    /// again. If that fails as well, the glyphs of a single frame don't fit
+    /// into the atlas, even at the maximum texture size of the device.
+    ///
+    /// If the atlas is shared, every other pipeline using it prepares all of
+    /// its batches again too.
+    pub fn evict_unused_glyphs(&mut self) {
+        self.prepare_all_batches = true;
+        self.all_batches_prepared = true;
+        self.atlas_needs_trimmed = false;
+
+        self.trim_atlas();
     }
 
+    fn trim_atlas(&mut self) {
+        let mut resources = self.resources.inner.borrow_mut();
+        resources.trim();
+
+        // The batches of this pipeline are prepared again by the caller if
+        // needed.
+        self.trim_generation = resources.trim_generation;
+    }
+
+    /// Render a batch. `resources` must be borrowed from
+    /// [`TextPipeline::resources`].
     pub fn render_batch<'pass>(
         &'pass self,
         batch: &'pass TextBatchBuffer,
+        resources: &'pass TextResources,
         render_pass: &mut wgpu::RenderPass<'pass>,
     ) -> Result<(), glyphon::RenderError> {
         batch
             .text_renderer
-            .render(&self.atlas, &self.viewport, render_pass)
+            .render(&resources.atlas, &self.viewport, render_pass)?;
+
+        // Rotated and faded text is drawn on top of the rest of the text in
+        // the batch.
+        if let Some(rotated_pipeline) = &self.rotated_pipeline {
+            rotated_pipeline.render_batch(&batch.rotated, render_pass);
+        }
+
+        Ok(())
+    }
+}
+
+/// Push the text areas of a primitive, including the copies of the glyphs
+/// used for its shadow and outline.
+///
+/// `left` and `top` are the position of the text in physical pixels.
+fn push_text_areas<'a>(
+    text_areas: &mut Vec<TextArea<'a>>,
+    p: &'a TextPrimitive,
+    buffer: &'a glyphon::Buffer,
+    left: f32,
+    top: f32,
+    bounds: glyphon::TextBounds,
+    scale_factor: f32,
+) {
+    // Copies of the glyphs used for effects, offset in physical pixels.
+    // Icons are not included in the copies.
+    let mut push_copy = |offset_x: f32, offset_y: f32, color: RGBA8| {
+        text_areas.push(TextArea {
+            buffer,
+            left: left + offset_x,
+            top: top + offset_y,
+            scale: scale_factor,
+            bounds,
+            default_color: glyphon::Color::rgba(color.r, color.g, color.b, color.a),
+            #[cfg(feature = "svg-icons")]
+            custom_glyphs: &[],
+        });
+    };
+
+    if let Some(shadow) = &p.shadow {
+        let offset_x = shadow.offset.x * scale_factor;
+        let offset_y = (shadow.offset.y * scale_factor).round();
+        let spread = shadow.blur * scale_factor * 0.5;
+
+        if spread > 0.0 {
+            for (kx, ky, weight) in SHADOW_KERNEL {
+                let mut color = shadow.color;
+                color.a = (color.a as f32 * weight).round() as u8;
+
+                push_copy(
+                    offset_x + (kx * spread),
+                    offset_y + (ky * spread).round(),
+                    color,
+                );
+            }
+        } else {
+            push_copy(offset_x, offset_y, shadow.color);
+        }
+    }
+
+    if let Some(outline) = &p.outline {
+        let width_px = (outline.width * scale_factor).round().max(1.0) as u32;
+
+        // Fill in each ring of the outline so that there are no gaps
+        // between the copies of wide outlines.
+        for radius in 1..=width_px {
+            let radius = radius as f32;
+
+            for (dx, dy) in OUTLINE_DIRECTIONS {
+                push_copy(dx * radius, (dy * radius).round(), outline.color);
+            }
+        }
     }
+
+    text_areas.push(TextArea {
+        buffer,
+        left,
+        top,
+        scale: scale_factor,
+        bounds,
+        default_color: glyphon::Color::rgba(p.color.r, p.color.g, p.color.b, p.color.a),
+        #[cfg(feature = "svg-icons")]
+        custom_glyphs: p.icons.as_slice(),
+    });
+}
+
+/// How far the glyphs of a primitive may extend past its measured size in
+/// physical pixels, including its shadow and outline.
+fn effect_extent(p: &TextPrimitive, scale_factor: f32) -> f32 {
+    let outline = p
+        .outline
+        .map(|outline| (outline.width * scale_factor).round().max(1.0))
+        .unwrap_or(0.0);
+    let shadow = p
+        .shadow
+        .map(|shadow| {
+            (shadow.offset.x.abs().max(shadow.offset.y.abs()) + shadow.blur) * scale_factor
+        })
+        .unwrap_or(0.0);
+
+    // Leave a couple of pixels for glyphs which overhang their advance, such
+    // as italics.
+    (outline.max(shadow) + 2.0).ceil()
 }
@@ -1,17 +1,60 @@
-use rootvg_core::color::RGBA8;
-use rootvg_core::math::{Point, Rect};
+use rootvg_core::color::{PackedSrgb, RGBA8};
+use rootvg_core::math::{Angle, Point, Rect, Vector};
 use smallvec::SmallVec;
+use std::ops::Range;
 
 use super::RcTextBuffer;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TextPrimitive {
     pub buffer: Option<RcTextBuffer>,
     pub pos: Point,
     pub color: RGBA8,
     pub clipping_bounds: Option<Rect>,
 
+    /// Solid rectangles which are drawn behind the text, such as a text
+    /// selection.
+    ///
+    /// By default this is set to `None`.
+    pub highlight: Option<TextHighlight>,
+
+    /// A shadow which is drawn behind the text.
+    ///
+    /// By default this is set to `None`.
+    pub shadow: Option<TextShadow>,
+
+    /// A solid outline which is drawn around the glyphs.
+    ///
+    /// By default this is set to `None`.
+    pub outline: Option<TextOutline>,
+
+    /// Rotates the text about an anchor point.
+    ///
+    /// By default this is set to `None`.
+    pub rotation: Option<TextRotation>,
+
+    /// How far the text is scrolled up in logical points. The clipping
+    /// bounds don't move with the text.
+    ///
+    /// By default this is set to `0.0`.
+    pub scroll_offset: f32,
+
+    /// An opacity multiplier which is applied to everything drawn by the
+    /// primitive, including glyphs which have their own color set in the
+    /// attributes of the buffer, the shadow, the outline, the highlight, and
+    /// the decorations.
+    ///
+    /// Text with an alpha below `1.0` is first rendered into an intermediate
+    /// texture in the same way as rotated text, and is then drawn with the
+    /// given opacity.
+    ///
+    /// By default this is set to `1.0`.
+    pub alpha: f32,
+
+    /// Note, icons are not serialized.
     #[cfg(feature = "svg-icons")]
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub icons: smallvec::SmallVec<[glyphon::CustomGlyph; 2]>,
 }
 
@@ -35,6 +78,12 @@ impl TextPrimitive {
             pos,
             color,
             clipping_bounds,
+            highlight: None,
+            shadow: None,
+            outline: None,
+            rotation: None,
+            scroll_offset: 0.0,
+            alpha: 1.0,
             #[cfg(feature = "svg-icons")]
             icons: SmallVec::new(),
         }
@@ -62,7 +111,214 @@ impl TextPrimitive {
             pos,
             color,
             clipping_bounds,
+            highlight: None,
+            shadow: None,
+            outline: None,
+            rotation: None,
+            scroll_offset: 0.0,
+            alpha: 1.0,
             icons,
         }
     }
+
+    /// Highlight the given byte range of the text with a solid color, such
+    /// as for a text selection in a text field.
+    ///
+    /// The rectangles are computed from the current layout of the buffer
+    /// (see [`RcTextBuffer::highlight_rects`]), so this must be called again
+    /// if the buffer changes. The highlight is drawn behind the text at the
+    /// same z index. This does nothing if there is no buffer.
+    pub fn with_highlight(mut self, range: Range<usize>, color: impl Into<PackedSrgb>) -> Self {
+        self.highlight = self.buffer.as_ref().map(|buffer| TextHighlight {
+            rects: buffer.highlight_rects(range),
+            color: color.into(),
+        });
+        self
+    }
+
+    /// Draw a shadow behind the text. See [`TextShadow`].
+    pub fn with_shadow(mut self, shadow: TextShadow) -> Self {
+        self.shadow = Some(shadow);
+        self
+    }
+
+    /// Draw a solid outline around the glyphs. See [`TextOutline`].
+    pub fn with_outline(mut self, outline: TextOutline) -> Self {
+        self.outline = Some(outline);
+        self
+    }
+
+    /// Scroll the text up by the given offset in logical points, for example
+    /// to show the end of a long log in a console view.
+    ///
+    /// The text moves inside of its clipping bounds, which cut off the lines
+    /// that are only partly visible. Only the lines which are inside of the
+    /// clipping bounds are prepared for rendering, so set the clipping bounds
+    /// to the visible area when showing a small part of a long text.
+    pub fn with_scroll(mut self, offset_y: f32) -> Self {
+        self.scroll_offset = offset_y;
+        self
+    }
+
+    /// Multiply the opacity of everything drawn by the primitive by the given
+    /// alpha. See [`TextPrimitive::alpha`].
+    pub fn with_alpha(mut self, alpha: f32) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    /// Rotate the text clockwise by the given angle about the given anchor,
+    /// which is in logical points relative to the position of the text. See
+    /// [`TextRotation`].
+    pub fn with_rotation(mut self, angle: Angle, anchor: Point) -> Self {
+        self.rotation = Some(TextRotation::new(angle, anchor));
+        self
+    }
+}
+
+/// Rotates a [`TextPrimitive`] about an anchor point.
+///
+/// Rotated text is first rendered into an intermediate texture at the
+/// physical resolution of the canvas, which is then drawn rotated. Rotations
+/// by a multiple of 90 degrees are snapped to whole physical pixels so that
+/// the glyphs stay as crisp as unrotated text, while other angles are
+/// filtered bilinearly. The clipping bounds of the primitive are applied
+/// before the text is rotated.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextRotation {
+    /// The clockwise angle of the rotation.
+    pub angle: Angle,
+    /// The point which the text is rotated about in logical points, relative
+    /// to the position of the text primitive.
+    pub anchor: Point,
+}
+
+impl TextRotation {
+    pub const fn new(angle: Angle, anchor: Point) -> Self {
+        Self { angle, anchor }
+    }
+}
+
+/// A shadow which is drawn behind a [`TextPrimitive`].
+///
+/// The shadow is drawn by rendering copies of the glyphs in the shadow
+/// color. Note, glyphs which have their own color set in their attributes
+/// keep that color in the shadow.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextShadow {
+    pub color: RGBA8,
+    /// The offset of the shadow in logical points.
+    pub offset: Vector,
+    /// The blur radius of the shadow in logical points.
+    ///
+    /// The blur is approximated with a small fixed kernel of 9 copies, so
+    /// it is cheap but looks best with small radii.
+    pub blur: f32,
+}
+
+impl TextShadow {
+    pub const fn new(color: RGBA8, offset: Vector, blur: f32) -> Self {
+        Self {
+            color,
+            offset,
+            blur,
+        }
+    }
+}
+
+/// A solid outline which is drawn around the glyphs of a [`TextPrimitive`].
+///
+/// The outline is drawn by rendering copies of the glyphs in the outline
+/// color, offset in 8 directions. Note, glyphs which have their own color set
+/// in their attributes keep that color in the outline.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextOutline {
+    pub color: RGBA8,
+    /// The width of the outline in logical points.
+    ///
+    /// The width is rounded to a whole number of physical pixels (with a
+    /// minimum of one pixel) so that the outline has a consistent thickness
+    /// at every scale factor. The cost of the outline grows with its width in
+    /// physical pixels.
+    pub width: f32,
+}
+
+impl TextOutline {
+    pub const fn new(color: RGBA8, width: f32) -> Self {
+        Self { color, width }
+    }
+}
+
+/// Solid rectangles which are drawn behind a [`TextPrimitive`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextHighlight {
+    /// The rectangles in logical points, relative to the position of the
+    /// text primitive.
+    pub rects: SmallVec<[Rect; 4]>,
+    pub color: PackedSrgb,
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use glyphon::cosmic_text::fontdb;
+    use glyphon::FontSystem;
+    use rootvg_core::math::Size;
+    use rootvg_core::serde_handle::with_resolver;
+    use smallvec::smallvec;
+
+    use super::*;
+
+    #[test]
+    fn serde_round_trip() {
+        let mut font_system =
+            FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+        let buffer = RcTextBuffer::new(
+            "hello",
+            Default::default(),
+            None,
+            None,
+            false,
+            &mut font_system,
+        );
+
+        let mut primitive = TextPrimitive::new(
+            buffer.clone(),
+            Point::new(5.0, 10.0),
+            RGBA8::new(10, 20, 30, 255),
+            Some(Rect::new(Point::new(0.0, 0.0), Size::new(100.0, 20.0))),
+        );
+        primitive.highlight = Some(TextHighlight {
+            rects: smallvec![Rect::new(Point::new(1.0, 2.0), Size::new(3.0, 4.0))],
+            color: RGBA8::new(0, 0, 255, 100).into(),
+        });
+        primitive.shadow = Some(TextShadow::new(
+            RGBA8::new(0, 0, 0, 128),
+            Vector::new(1.0, 2.0),
+            3.0,
+        ));
+        primitive.outline = Some(TextOutline::new(RGBA8::new(255, 255, 255, 255), 1.0));
+        primitive.rotation = Some(TextRotation::new(
+            Angle::degrees(30.0),
+            Point::new(4.0, 5.0),
+        ));
+        primitive.scroll_offset = 6.0;
+        primitive.alpha = 0.5;
+
+        let json = serde_json::to_string(&primitive).unwrap();
+
+        // The buffer is serialized as its ID, which has to be resolved.
+        assert!(serde_json::from_str::<TextPrimitive>(&json).is_err());
+
+        let id = buffer.id();
+        let deserialized: TextPrimitive = with_resolver(
+            move |resolved_id| (resolved_id == id).then(|| buffer.clone()),
+            || serde_json::from_str(&json).unwrap(),
+        );
+
+        assert_eq!(deserialized, primitive);
+    }
 }
@@ -37,6 +37,29 @@ pub struct TextProperties {
     ///
     /// By default this is set to `Shaping::Basic`.
     pub shaping: Shaping,
+    /// Whether or not to draw a line under the text
+    ///
+    /// By default this is set to `false`.
+    pub underline: bool,
+    /// Whether or not to draw a line through the text
+    ///
+    /// By default this is set to `false`.
+    pub strikethrough: bool,
+    /// Whether or not the text is positioned at fractional physical pixel
+    /// offsets
+    ///
+    /// When this is `false`, the position of the text is snapped to the
+    /// nearest physical pixel, which keeps static text crisp and stops it from
+    /// shimmering when it is moved by a fraction of a pixel. Enable this for
+    /// smoothly animated text, such as text that scrolls by less than a pixel
+    /// each frame.
+    ///
+    /// Glyphs are rasterized at one of 4 subpixel offsets per axis, so every
+    /// glyph takes up at most 16 entries in the glyph atlas. Entries which are
+    /// no longer used are trimmed from the atlas after each frame.
+    ///
+    /// By default this is set to `false`.
+    pub subpixel_positioning: bool,
 }
 
 impl Default for TextProperties {
@@ -59,6 +82,9 @@ impl Default for TextProperties {
             },
             wrap: Wrap::None,
             shaping: Shaping::Basic,
+            underline: false,
+            strikethrough: false,
+            subpixel_positioning: false,
         }
     }
 }
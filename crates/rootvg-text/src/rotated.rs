@@ -0,0 +1,408 @@
+use glyphon::{
+    Cache, FontSystem, Resolution, SwashCache, TextArea, TextAtlas, TextRenderer, Viewport,
+};
+use wgpu::PipelineCompilationOptions;
+
+use rootvg_core::buffer::Buffer;
+use rootvg_core::math::{PhysicalPointI32, PhysicalSizeI32, ScaleFactor};
+use rootvg_core::pipeline::DefaultConstantUniforms;
+
+/// A rotated text primitive, drawn as a quad which samples the area of the
+/// intermediate texture that the text was rendered into.
+///
+/// Everything is in physical pixels, relative to the viewport.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct RotatedTextInstance {
+    /// The top-left corner of the text before it is rotated.
+    pub pos: [f32; 2],
+    pub size: [f32; 2],
+    /// The top-left corner of the text in the intermediate texture, in
+    /// texels.
+    pub uv_pos: [f32; 2],
+    pub anchor: [f32; 2],
+    /// The sine and cosine of the angle of the rotation.
+    pub sin_cos: [f32; 2],
+    /// The opacity the text is drawn with.
+    pub alpha: f32,
+    pub depth: f32,
+}
+
+struct RotatedTextTexture {
+    bind_group: wgpu::BindGroup,
+    view: wgpu::TextureView,
+    size: PhysicalSizeI32,
+}
+
+/// The rotated text of a single text batch.
+pub(crate) struct RotatedTextBatch {
+    texture: Option<RotatedTextTexture>,
+    instances: Option<Buffer<RotatedTextInstance>>,
+    instance_capacity: usize,
+    num_instances: usize,
+}
+
+impl RotatedTextBatch {
+    pub fn new() -> Self {
+        Self {
+            texture: None,
+            instances: None,
+            instance_capacity: 0,
+            num_instances: 0,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.num_instances = 0;
+    }
+}
+
+/// Renders rotated and faded text into an intermediate texture, and then
+/// draws it rotated and with its opacity.
+///
+/// glyphon renders the glyphs with its own shaders, which can't rotate them
+/// or change the opacity of glyphs which have their own color.
+/// Rendering the text at the physical resolution of the canvas first keeps
+/// the glyphs exactly as they would be drawn without rotation.
+pub(crate) struct RotatedTextPipeline {
+    pipeline: wgpu::RenderPipeline,
+    constants_buffer: wgpu::Buffer,
+    constants_bind_group: wgpu::BindGroup,
+    texture_layout: wgpu::BindGroupLayout,
+
+    text_renderer: TextRenderer,
+    viewport: Viewport,
+    format: wgpu::TextureFormat,
+}
+
+impl RotatedTextPipeline {
+    pub fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        multisample: wgpu::MultisampleState,
+        depth_stencil: Option<wgpu::DepthStencilState>,
+        atlas: &mut TextAtlas,
+        cache: &Cache,
+    ) -> Self {
+        let constants_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("rootvg-text rotated constants layout"),
+            entries: &[
+                DefaultConstantUniforms::entry(0),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let constants_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("rootvg-text rotated constants buffer"),
+            size: std::mem::size_of::<DefaultConstantUniforms>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        // Rotations by a multiple of 90 degrees line up the texels with the
+        // physical pixels, in which case linear filtering leaves them as is.
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let constants_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("rootvg-text rotated constants bind group"),
+            layout: &constants_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: constants_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+
+        let texture_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("rootvg-text rotated texture layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    multisampled: false,
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                },
+                count: None,
+            }],
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("rootvg-text rotated pipeline layout"),
+            push_constant_ranges: &[],
+            bind_group_layouts: &[&constants_layout, &texture_layout],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("rootvg-text rotated shader"),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(include_str!(
+                "shader/rotated.wgsl"
+            ))),
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("rootvg-text rotated pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<RotatedTextInstance>() as u64,
+                    step_mode: wgpu::VertexStepMode::Instance,
+                    attributes: &wgpu::vertex_attr_array!(
+                        // Position
+                        0 => Float32x2,
+                        // Size
+                        1 => Float32x2,
+                        // UV position
+                        2 => Float32x2,
+                        // Anchor
+                        3 => Float32x2,
+                        // Sine and cosine
+                        4 => Float32x2,
+                        // Alpha
+                        5 => Float32,
+                        // Depth
+                        6 => Float32,
+                    ),
+                }],
+                compilation_options: PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                front_face: wgpu::FrontFace::Cw,
+                ..Default::default()
+            },
+            depth_stencil,
+            multisample,
+            multiview: None,
+            cache: None,
+        });
+
+        // The intermediate textures are not multisampled and have no depth.
+        let text_renderer =
+            TextRenderer::new(atlas, device, wgpu::MultisampleState::default(), None);
+
+        Self {
+            pipeline,
+            constants_buffer,
+            constants_bind_group,
+            texture_layout,
+            text_renderer,
+            viewport: Viewport::new(device, cache),
+            format,
+        }
+    }
+
+    pub fn update_constants(
+        &self,
+        queue: &wgpu::Queue,
+        screen_size: PhysicalSizeI32,
+        scale_factor: ScaleFactor,
+        origin: PhysicalPointI32,
+    ) {
+        DefaultConstantUniforms::prepare_buffer_with_origin(
+            &self.constants_buffer,
+            screen_size,
+            scale_factor,
+            origin,
+            queue,
+        );
+    }
+
+    /// Render the text areas into the intermediate texture of the batch, and
+    /// upload the instances which draw them.
+    ///
+    /// The text areas must lie inside of `texture_size`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn prepare_batch(
+        &mut self,
+        batch: &mut RotatedTextBatch,
+        text_areas: Vec<TextArea<'_>>,
+        instances: &[RotatedTextInstance],
+        texture_size: PhysicalSizeI32,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        font_system: &mut FontSystem,
+        atlas: &mut TextAtlas,
+        swash_cache: &mut SwashCache,
+        #[cfg(feature = "svg-icons")] svg_system: &mut crate::svg::SvgIconSystem,
+    ) -> Result<(), glyphon::PrepareError> {
+        batch.num_instances = 0;
+
+        if instances.is_empty() {
+            return Ok(());
+        }
+
+        // Textures only grow, so that they aren't created again every time
+        // the text changes.
+        let needs_new_texture = batch.texture.as_ref().map_or(true, |t| {
+            t.size.width < texture_size.width || t.size.height < texture_size.height
+        });
+        if needs_new_texture {
+            let size = batch.texture.as_ref().map_or(texture_size, |t| {
+                PhysicalSizeI32::new(
+                    t.size.width.max(texture_size.width),
+                    t.size.height.max(texture_size.height),
+                )
+            });
+
+            let texture = device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("rootvg-text rotated texture"),
+                size: wgpu::Extent3d {
+                    width: size.width as u32,
+                    height: size.height as u32,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: self.format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            });
+            let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("rootvg-text rotated texture bind group"),
+                layout: &self.texture_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                }],
+            });
+
+            batch.texture = Some(RotatedTextTexture {
+                bind_group,
+                view,
+                size,
+            });
+        }
+        let texture = batch.texture.as_ref().unwrap();
+
+        self.viewport.update(
+            queue,
+            Resolution {
+                width: texture.size.width as u32,
+                height: texture.size.height as u32,
+            },
+        );
+
+        #[cfg(not(feature = "svg-icons"))]
+        self.text_renderer.prepare_with_depth(
+            device,
+            queue,
+            font_system,
+            atlas,
+            &self.viewport,
+            text_areas,
+            swash_cache,
+            |_| 0.0,
+        )?;
+
+        #[cfg(feature = "svg-icons")]
+        self.text_renderer.prepare_with_depth_and_custom(
+            device,
+            queue,
+            font_system,
+            atlas,
+            &self.viewport,
+            text_areas,
+            swash_cache,
+            |_| 0.0,
+            |input| svg_system.render_custom_glyph(input),
+        )?;
+
+        // The text renderer and the viewport are shared by all batches, so
+        // the text is rendered right away before the next batch is prepared.
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("rootvg-text rotated encoder"),
+        });
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("rootvg-text rotated render pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &texture.view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            // The glyphs were prepared above, so they can't be missing from
+            // the atlas.
+            let _ = self
+                .text_renderer
+                .render(atlas, &self.viewport, &mut render_pass);
+        }
+        queue.submit(Some(encoder.finish()));
+
+        if batch.instance_capacity < instances.len() {
+            batch.instances = Some(Buffer::new(
+                device,
+                "rootvg-text rotated instance buffer",
+                instances.len(),
+                wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            ));
+            batch.instance_capacity = instances.len();
+        }
+        batch.instances.as_mut().unwrap().write(queue, 0, instances);
+        batch.num_instances = instances.len();
+
+        Ok(())
+    }
+
+    pub fn render_batch<'pass>(
+        &'pass self,
+        batch: &'pass RotatedTextBatch,
+        render_pass: &mut wgpu::RenderPass<'pass>,
+    ) {
+        if batch.num_instances == 0 {
+            return;
+        }
+        let (Some(texture), Some(instances)) = (&batch.texture, &batch.instances) else {
+            return;
+        };
+
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.constants_bind_group, &[]);
+        render_pass.set_bind_group(1, &texture.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, instances.slice(0..batch.num_instances));
+
+        render_pass.draw(0..6, 0..batch.num_instances as u32);
+    }
+}
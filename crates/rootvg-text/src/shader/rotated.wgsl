@@ -0,0 +1,71 @@
+struct Globals {
+    screen_size_recip: vec2<f32>,
+    scale_factor: f32,
+    origin: vec2<f32>,
+}
+
+@group(0) @binding(0) var<uniform> globals: Globals;
+@group(0) @binding(1) var smp: sampler;
+
+@group(1) @binding(0) var tex: texture_2d<f32>;
+
+struct VertexInput {
+    @builtin(vertex_index) vertex_index: u32,
+    @location(0) pos: vec2<f32>,
+    @location(1) size: vec2<f32>,
+    @location(2) uv_pos: vec2<f32>,
+    @location(3) anchor: vec2<f32>,
+    @location(4) sin_cos: vec2<f32>,
+    @location(5) alpha: f32,
+    @location(6) depth: f32,
+}
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) @interpolate(flat) alpha: f32,
+}
+
+@vertex
+fn vs_main(input: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+
+    let corner = vertex_position(input.vertex_index);
+
+    // Rotate the corner about the anchor. Everything is in physical pixels.
+    let local = input.pos + (corner * input.size) - input.anchor;
+    let sin = input.sin_cos.x;
+    let cos = input.sin_cos.y;
+    let screen_pos = vec2<f32>(
+        (local.x * cos) - (local.y * sin),
+        (local.x * sin) + (local.y * cos)
+    ) + input.anchor;
+
+    out.position = vec4<f32>(
+        ((screen_pos.x + globals.origin.x) * globals.screen_size_recip.x) - 1.0,
+        1.0 - ((screen_pos.y + globals.origin.y) * globals.screen_size_recip.y),
+        input.depth,
+        1.0
+    );
+
+    // The text is rendered into the texture at the same scale, so one texel
+    // covers one physical pixel.
+    out.uv = (input.uv_pos + (corner * input.size)) / vec2<f32>(textureDimensions(tex));
+    out.alpha = input.alpha;
+
+    return out;
+}
+
+// Compute the normalized quad coordinates based on the vertex index.
+fn vertex_position(vertex_index: u32) -> vec2<f32> {
+    // #: 0 1 2 3 4 5
+    // x: 1 1 0 0 0 1
+    // y: 1 0 0 0 1 1
+    return vec2<f32>((vec2(1u, 2u) + vertex_index) % vec2(6u) < vec2(3u));
+}
+
+@fragment
+fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
+    // The texture holds premultiplied colors.
+    return textureSample(tex, smp, input.uv) * input.alpha;
+}
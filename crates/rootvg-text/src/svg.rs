@@ -18,9 +18,12 @@ impl SvgIconSystem {
     ///
     /// * id - A unique identifier for this resource.
     /// * tree - The parsed SVG data.
-    /// * is_symbolic - If `true`, then only the alpha channel will be used and the icon can
-    /// be filled with any solid color. If `false`, then the icon will be rendered in full
-    /// color.
+    /// * content_type - If `ContentType::Mask`, then only the alpha channel will be used
+    /// and the icon will be filled with the color of the [`CustomGlyph`] (or the color of
+    /// the text primitive if that is `None`). If `ContentType::Color`, then the icon will
+    /// be rendered in full color.
+    ///
+    /// [`CustomGlyph`]: glyphon::CustomGlyph
     pub fn add_from_tree(
         &mut self,
         id: impl Into<CustomGlyphId>,
@@ -35,9 +38,12 @@ impl SvgIconSystem {
     /// * id - A unique identifier for this resource
     /// * data - The raw SVG file as bytes
     /// * opts - Additional options for parsing the SVG file
-    /// * is_symbolic - If `true`, then only the alpha channel will be used and the icon can
-    /// be filled with any solid color. If `false`, then the icon will be rendered in full
-    /// color.
+    /// * content_type - If `ContentType::Mask`, then only the alpha channel will be used
+    /// and the icon will be filled with the color of the [`CustomGlyph`] (or the color of
+    /// the text primitive if that is `None`). If `ContentType::Color`, then the icon will
+    /// be rendered in full color.
+    ///
+    /// [`CustomGlyph`]: glyphon::CustomGlyph
     pub fn add_from_bytes(
         &mut self,
         id: impl Into<CustomGlyphId>,
@@ -55,9 +61,12 @@ impl SvgIconSystem {
     /// * id - A unique identifier for this resource
     /// * str - The SVG data as a string
     /// * opts - Additional options for parsing the SVG file
-    /// * is_symbolic - If `true`, then only the alpha channel will be used and the icon can
-    /// be filled with any solid color. If `false`, then the icon will be rendered in full
-    /// color.
+    /// * content_type - If `ContentType::Mask`, then only the alpha channel will be used
+    /// and the icon will be filled with the color of the [`CustomGlyph`] (or the color of
+    /// the text primitive if that is `None`). If `ContentType::Color`, then the icon will
+    /// be rendered in full color.
+    ///
+    /// [`CustomGlyph`]: glyphon::CustomGlyph
     pub fn add_from_str(
         &mut self,
         id: impl Into<CustomGlyphId>,
@@ -75,9 +84,12 @@ impl SvgIconSystem {
     /// * id - A unique identifier for this resource
     /// * path - The path to the SVG file
     /// * opts - Additional options for parsing the SVG file
-    /// * is_symbolic - If `true`, then only the alpha channel will be used and the icon can
-    /// be filled with any solid color. If `false`, then the icon will be rendered in full
-    /// color.
+    /// * content_type - If `ContentType::Mask`, then only the alpha channel will be used
+    /// and the icon will be filled with the color of the [`CustomGlyph`] (or the color of
+    /// the text primitive if that is `None`). If `ContentType::Color`, then the icon will
+    /// be rendered in full color.
+    ///
+    /// [`CustomGlyph`]: glyphon::CustomGlyph
     pub fn add_from_path(
         &mut self,
         id: impl Into<CustomGlyphId>,
@@ -98,6 +110,12 @@ impl SvgIconSystem {
     }
 
     /// Rasterize the SVG icon.
+    ///
+    /// Rasterized icons are cached by the text renderer using the id, the physical
+    /// size, and the subpixel offset of the icon, so an icon which is drawn at a new
+    /// size is rasterized again at that size instead of being scaled. The tint color
+    /// of a mask icon is applied when drawing, so instances of the same icon with
+    /// different colors share one rasterization.
     pub fn render_custom_glyph(
         &mut self,
         input: RasterizationRequest,
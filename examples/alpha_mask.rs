@@ -0,0 +1,161 @@
+//! Renders text and a rounded rectangle into an alpha mask, and then draws the
+//! mask as a tinted image.
+//!
+//! Run with `cargo run --example alpha_mask --features headless`.
+
+use rootvg::color::RGBA8;
+use rootvg::headless::{HeadlessConfig, HeadlessContext};
+use rootvg::image::{ColorMatrix, ImagePrimitive, RcTexture};
+use rootvg::math::{PhysicalSizeI32, PhysicalSizeU32, Point, ScaleFactor, Size};
+use rootvg::quad::SolidQuad;
+use rootvg::text::{Metrics, RcTextBuffer, TextPrimitive, TextProperties};
+use rootvg::{Canvas, CanvasConfig, TargetKind};
+use rootvg_text::{glyphon::FontSystem, svg::SvgIconSystem};
+
+const PHYSICAL_SIZE: PhysicalSizeI32 = PhysicalSizeI32::new(400, 200);
+const MASK_SIZE: PhysicalSizeI32 = PhysicalSizeI32::new(240, 80);
+
+fn main() {
+    // Set up logging stuff
+    let env = env_logger::Env::default().filter_or("LOG_LEVEL", "info");
+    env_logger::init_from_env(env);
+
+    let mut font_system = FontSystem::new();
+    let mut svg_icon_system = SvgIconSystem::default();
+
+    let context = HeadlessContext::new(HeadlessConfig::default()).unwrap();
+
+    // --- Mask -------------------------------------------------------------------------
+
+    let mask_texture = context.device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("mask texture"),
+        size: wgpu::Extent3d {
+            width: MASK_SIZE.width as u32,
+            height: MASK_SIZE.height as u32,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::R8Unorm,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    });
+    let mask_view = mask_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    let mut mask_canvas = Canvas::new(
+        &context.device,
+        &context.queue,
+        wgpu::TextureFormat::R8Unorm,
+        CanvasConfig {
+            target_kind: TargetKind::AlphaMask,
+            ..context.canvas_config()
+        },
+        &mut font_system,
+    );
+
+    {
+        let mut cx = mask_canvas.begin(MASK_SIZE, ScaleFactor::new(1.0));
+
+        // Only the alpha of the primitives ends up in the mask.
+        cx.add(
+            SolidQuad::builder(Size::new(80.0, 80.0))
+                .bg_color(RGBA8::new(255, 0, 0, 255))
+                .border_radius(20.0)
+                .build(),
+        );
+        cx.add(TextPrimitive::new(
+            RcTextBuffer::new(
+                "Masked",
+                TextProperties {
+                    metrics: Metrics {
+                        font_size: 40.0,
+                        line_height: 48.0,
+                    },
+                    ..Default::default()
+                },
+                None,
+                None,
+                false,
+                &mut font_system,
+            ),
+            Point::new(95.0, 16.0),
+            RGBA8::new(0, 0, 255, 255),
+            None,
+        ));
+    }
+
+    let mut encoder = context
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+    mask_canvas
+        .render_to_target(
+            Some(RGBA8::new(0, 0, 0, 0).into()),
+            &context.device,
+            &context.queue,
+            &mut encoder,
+            &mask_view,
+            MASK_SIZE,
+            &mut font_system,
+            &mut svg_icon_system,
+        )
+        .unwrap();
+
+    context.queue.submit(Some(encoder.finish()));
+
+    // --- Composite --------------------------------------------------------------------
+
+    let mut canvas = Canvas::new(
+        &context.device,
+        &context.queue,
+        context.format(),
+        context.canvas_config(),
+        &mut font_system,
+    );
+
+    let mask = RcTexture::from_prepass_texture(
+        mask_view,
+        PhysicalSizeU32::new(MASK_SIZE.width as u32, MASK_SIZE.height as u32),
+    );
+
+    // The mask is sampled as `(coverage, 0, 0, 1)`, so move the coverage into
+    // the alpha channel and make the color white, which is then tinted.
+    let coverage_to_alpha = ColorMatrix::new([
+        0.0, 0.0, 0.0, 0.0, 1.0, //
+        0.0, 0.0, 0.0, 0.0, 1.0, //
+        0.0, 0.0, 0.0, 0.0, 1.0, //
+        1.0, 0.0, 0.0, 0.0, 0.0, //
+    ]);
+
+    {
+        let mut cx = canvas.begin(PHYSICAL_SIZE, ScaleFactor::new(1.0));
+
+        for (i, tint) in [RGBA8::new(230, 120, 40, 255), RGBA8::new(60, 200, 140, 255)]
+            .into_iter()
+            .enumerate()
+        {
+            cx.add(
+                ImagePrimitive::builder(mask.clone())
+                    .position(Point::new(20.0 + i as f32 * 120.0, 20.0 + i as f32 * 80.0))
+                    .color_matrix(coverage_to_alpha)
+                    .tint(tint)
+                    .build(),
+            );
+        }
+    }
+
+    let image = context
+        .render_to_image(
+            &mut canvas,
+            PHYSICAL_SIZE,
+            Some(RGBA8::new(20, 20, 24, 255).into()),
+            &mut font_system,
+            &mut svg_icon_system,
+        )
+        .unwrap();
+
+    image.save("alpha_mask.png").unwrap();
+
+    log::info!("saved render to alpha_mask.png");
+}
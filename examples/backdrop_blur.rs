@@ -0,0 +1,86 @@
+use rootvg::blur::BackdropBlurPrimitive;
+use rootvg::color::RGBA8;
+use rootvg::headless::{HeadlessConfig, HeadlessContext};
+use rootvg::math::{PhysicalSizeI32, Point, ScaleFactor, Size};
+use rootvg::quad::SolidQuad;
+use rootvg::Canvas;
+use rootvg_text::{glyphon::FontSystem, svg::SvgIconSystem};
+
+const PHYSICAL_SIZE: PhysicalSizeI32 = PhysicalSizeI32::new(400, 300);
+
+fn main() {
+    // Set up logging stuff
+    let env = env_logger::Env::default().filter_or("LOG_LEVEL", "info");
+    env_logger::init_from_env(env);
+
+    let mut font_system = FontSystem::new();
+    let mut svg_icon_system = SvgIconSystem::default();
+
+    let context = HeadlessContext::new(HeadlessConfig::default()).unwrap();
+
+    let mut canvas = Canvas::new(
+        &context.device,
+        &context.queue,
+        context.format(),
+        context.canvas_config(),
+        &mut font_system,
+    );
+
+    {
+        let mut cx = canvas.begin(PHYSICAL_SIZE, ScaleFactor::new(1.0));
+
+        // A colorful background to blur.
+        let colors = [
+            RGBA8::new(230, 60, 60, 255),
+            RGBA8::new(240, 180, 40, 255),
+            RGBA8::new(60, 180, 90, 255),
+            RGBA8::new(50, 110, 220, 255),
+        ];
+        for (i, color) in colors.iter().enumerate() {
+            cx.add(
+                SolidQuad::builder(Size::new(120.0, 120.0))
+                    .position(Point::new(
+                        20.0 + i as f32 * 90.0,
+                        30.0 + (i % 2) as f32 * 120.0,
+                    ))
+                    .bg_color(*color)
+                    .border_radius(60.0)
+                    .build(),
+            );
+        }
+
+        // A frosted glass panel on top of the background. The blur is drawn
+        // before the background of the panel since they share a z index.
+        cx.set_z_index(1);
+
+        let panel_pos = Point::new(70.0, 70.0);
+        let panel_size = Size::new(260.0, 160.0);
+
+        cx.add(BackdropBlurPrimitive::from_pos_size(
+            panel_pos, panel_size, 16.0, 12.0,
+        ));
+        cx.add(
+            SolidQuad::builder(panel_size)
+                .position(panel_pos)
+                .bg_color(RGBA8::new(255, 255, 255, 60))
+                .border_color(RGBA8::new(255, 255, 255, 120))
+                .border_width(1.0)
+                .border_radius(16.0)
+                .build(),
+        );
+    }
+
+    let image = context
+        .render_to_image(
+            &mut canvas,
+            PHYSICAL_SIZE,
+            Some(RGBA8::new(15, 15, 15, 255).into()),
+            &mut font_system,
+            &mut svg_icon_system,
+        )
+        .unwrap();
+
+    image.save("backdrop_blur.png").unwrap();
+
+    log::info!("saved render to backdrop_blur.png");
+}
@@ -0,0 +1,214 @@
+use rootvg_text::{glyphon::FontSystem, svg::SvgIconSystem};
+use std::sync::Arc;
+use winit::{
+    application::ApplicationHandler,
+    event::WindowEvent,
+    event_loop::{ActiveEventLoop, EventLoop},
+    window::{Window, WindowId},
+};
+
+use rootvg::color::{PackedSrgb, RGBA8};
+use rootvg::math::{degrees, PhysicalSizeI32, Point, ScaleFactor};
+use rootvg::quad::{ArcCap, ArcQuad};
+use rootvg::{surface::DefaultSurface, Canvas};
+
+const WINDOW_SIZE: (f32, f32) = (400.0, 300.0);
+
+/// How much the progress advances each frame.
+const PROGRESS_SPEED: f32 = 0.004;
+
+fn main() {
+    // Set up logging stuff
+    let env = env_logger::Env::default().filter_or("LOG_LEVEL", "info");
+    env_logger::init_from_env(env);
+
+    let event_loop = EventLoop::new().unwrap();
+    event_loop
+        .run_app(&mut CircularProgressApp {
+            state: None,
+            font_system: FontSystem::new(),
+            svg_icon_system: SvgIconSystem::default(),
+        })
+        .unwrap();
+}
+
+struct State {
+    window: Arc<Window>,
+    physical_size: PhysicalSizeI32,
+    scale_factor: ScaleFactor,
+    surface: DefaultSurface<'static>,
+    canvas: Canvas,
+    clear_color: PackedSrgb,
+    progress: f32,
+}
+
+struct CircularProgressApp {
+    state: Option<State>,
+    font_system: FontSystem,
+    svg_icon_system: SvgIconSystem,
+}
+
+impl CircularProgressApp {
+    fn create_window(&mut self, event_loop: &ActiveEventLoop) {
+        let window_attributes = Window::default_attributes()
+            .with_inner_size(winit::dpi::LogicalSize::new(
+                WINDOW_SIZE.0 as f64,
+                WINDOW_SIZE.1 as f64,
+            ))
+            .with_title("RootVG Circular Progress Demo");
+        let window = Arc::new(event_loop.create_window(window_attributes).unwrap());
+
+        let physical_size = window.inner_size();
+        let physical_size =
+            PhysicalSizeI32::new(physical_size.width as i32, physical_size.height as i32);
+        let scale_factor: ScaleFactor = window.scale_factor().into();
+
+        let surface = rootvg::surface::DefaultSurface::new(
+            physical_size,
+            scale_factor,
+            Arc::clone(&window),
+            rootvg::surface::DefaultSurfaceConfig {
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let canvas = rootvg::Canvas::new(
+            &surface.device,
+            &surface.queue,
+            surface.format(),
+            surface.canvas_config(),
+            &mut self.font_system,
+        );
+
+        self.state = Some(State {
+            window,
+            physical_size,
+            scale_factor,
+            surface,
+            canvas,
+            clear_color: RGBA8::new(15, 15, 15, 255).into(),
+            progress: 0.0,
+        });
+    }
+}
+
+impl ApplicationHandler for CircularProgressApp {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.state.is_none() {
+            self.create_window(event_loop);
+        }
+    }
+
+    fn window_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        _window_id: WindowId,
+        event: WindowEvent,
+    ) {
+        let Some(state) = &mut self.state else {
+            return;
+        };
+
+        match event {
+            WindowEvent::RedrawRequested => {
+                {
+                    let mut cx = state.canvas.begin(state.physical_size, state.scale_factor);
+
+                    let center = Point::new(200.0, 150.0);
+
+                    // The track behind the progress arc.
+                    cx.add(
+                        ArcQuad::builder(80.0, 12.0)
+                            .center(center)
+                            .sweep(degrees(360.0))
+                            .color(RGBA8::new(45, 45, 50, 255))
+                            .build(),
+                    );
+
+                    // The progress arc starts at the top and fills up clockwise.
+                    // Only the sweep changes each frame, so no mesh needs to be
+                    // tessellated.
+                    cx.add(
+                        ArcQuad::builder(80.0, 12.0)
+                            .center(center)
+                            .start_angle(degrees(-90.0))
+                            .sweep(degrees(state.progress * 360.0))
+                            .cap(ArcCap::Round)
+                            .color(RGBA8::new(90, 170, 240, 255))
+                            .build(),
+                    );
+
+                    // A thin arc which spins around the outside of the track.
+                    cx.add(
+                        ArcQuad::builder(96.0, 0.75)
+                            .center(center)
+                            .start_angle(degrees(state.progress * 720.0))
+                            .sweep(degrees(60.0))
+                            .color(RGBA8::new(200, 200, 200, 255))
+                            .build(),
+                    );
+                }
+
+                let frame = state.surface.get_current_texture().unwrap();
+                let view = frame
+                    .texture
+                    .create_view(&wgpu::TextureViewDescriptor::default());
+                let mut encoder = state
+                    .surface
+                    .device
+                    .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+                state
+                    .canvas
+                    .render_to_target(
+                        Some(state.clear_color),
+                        &state.surface.device,
+                        &state.surface.queue,
+                        &mut encoder,
+                        &view,
+                        state.physical_size,
+                        &mut self.font_system,
+                        &mut self.svg_icon_system,
+                    )
+                    .unwrap();
+
+                state.window.pre_present_notify();
+
+                state.surface.queue.submit(Some(encoder.finish()));
+                frame.present();
+
+                // Advance the progress on the next frame, starting over once
+                // it is complete.
+                state.progress = (state.progress + PROGRESS_SPEED) % 1.0;
+                state.window.request_redraw();
+            }
+            WindowEvent::Resized(new_size) => {
+                state.physical_size =
+                    PhysicalSizeI32::new(new_size.width as i32, new_size.height as i32);
+                state
+                    .surface
+                    .resize(state.physical_size, state.scale_factor);
+                state.window.request_redraw();
+            }
+            WindowEvent::ScaleFactorChanged {
+                scale_factor: new_scale,
+                inner_size_writer: _,
+            } => {
+                state.scale_factor = new_scale.into();
+                state
+                    .surface
+                    .resize(state.physical_size, state.scale_factor);
+                state.window.request_redraw();
+            }
+            WindowEvent::CloseRequested => event_loop.exit(),
+            _ => {}
+        }
+    }
+
+    fn exiting(&mut self, _event_loop: &ActiveEventLoop) {
+        // Be sure to drop the wgpu surface before the window closes,
+        // or else the program might segfault.
+        self.state = None;
+    }
+}
@@ -0,0 +1,118 @@
+//! Scrolls through a log with 10,000 lines in a viewport which shows about 40
+//! of them at a time.
+//!
+//! Run with `cargo run --release --example console_log --features headless`.
+
+use std::time::Instant;
+
+use rootvg::color::RGBA8;
+use rootvg::headless::{HeadlessConfig, HeadlessContext};
+use rootvg::math::{PhysicalSizeI32, Point, Rect, ScaleFactor, Size};
+use rootvg::quad::SolidQuad;
+use rootvg::text::{Attrs, Family, Metrics, RcTextBuffer, TextPrimitive, TextProperties};
+use rootvg::Canvas;
+use rootvg_text::{glyphon::FontSystem, svg::SvgIconSystem};
+
+const PHYSICAL_SIZE: PhysicalSizeI32 = PhysicalSizeI32::new(640, 480);
+
+const NUM_LINES: usize = 10_000;
+const LINE_HEIGHT: f32 = 16.0;
+
+const VIEWPORT: Rect = Rect::new(Point::new(10.0, 10.0), Size::new(620.0, 460.0));
+
+fn main() {
+    // Set up logging stuff
+    let env = env_logger::Env::default().filter_or("LOG_LEVEL", "info");
+    env_logger::init_from_env(env);
+
+    let mut font_system = FontSystem::new();
+    let mut svg_icon_system = SvgIconSystem::default();
+
+    let context = HeadlessContext::new(HeadlessConfig::default()).unwrap();
+
+    let mut canvas = Canvas::new(
+        &context.device,
+        &context.queue,
+        context.format(),
+        context.canvas_config(),
+        &mut font_system,
+    );
+
+    let text: String = (0..NUM_LINES)
+        .map(|i| {
+            format!(
+                "[{:>6.3}] worker {}: processed block {i:05}\n",
+                i as f32 * 0.016,
+                i % 8
+            )
+        })
+        .collect();
+
+    let start = Instant::now();
+    let buffer = RcTextBuffer::new(
+        &text,
+        TextProperties {
+            metrics: Metrics {
+                font_size: 13.0,
+                line_height: LINE_HEIGHT,
+            },
+            attrs: Attrs::new().family(Family::Monospace),
+            ..Default::default()
+        },
+        None,
+        None,
+        false,
+        &mut font_system,
+    );
+    log::info!("laid out {NUM_LINES} lines in {:?}", start.elapsed());
+
+    let content_height = buffer.measure().height;
+
+    // The top of the log, somewhere in the middle with a line cut in half at
+    // the top and bottom of the viewport, and the end of the log.
+    let scroll_offsets = [
+        ("top", 0.0),
+        ("middle", content_height * 0.5 + LINE_HEIGHT * 0.5),
+        ("bottom", content_height - VIEWPORT.height()),
+    ];
+
+    for (name, scroll_offset) in scroll_offsets {
+        // The clipping bounds are relative to the position of the text, and
+        // stay in place while the text scrolls.
+        let console = TextPrimitive::new(
+            buffer.clone(),
+            VIEWPORT.origin,
+            RGBA8::new(200, 220, 200, 255),
+            Some(Rect::new(Point::zero(), VIEWPORT.size)),
+        )
+        .with_scroll(scroll_offset);
+
+        let start = Instant::now();
+
+        {
+            let mut cx = canvas.begin(PHYSICAL_SIZE, ScaleFactor::new(1.0));
+
+            cx.add(
+                SolidQuad::builder(VIEWPORT.size)
+                    .position(VIEWPORT.origin)
+                    .bg_color(RGBA8::new(20, 24, 20, 255))
+                    .build(),
+            );
+            cx.add(console);
+        }
+
+        let image = context
+            .render_to_image(
+                &mut canvas,
+                PHYSICAL_SIZE,
+                Some(RGBA8::new(40, 40, 40, 255).into()),
+                &mut font_system,
+                &mut svg_icon_system,
+            )
+            .unwrap();
+
+        log::info!("rendered the {name} of the log in {:?}", start.elapsed());
+
+        image.save(format!("console_log_{name}.png")).unwrap();
+    }
+}
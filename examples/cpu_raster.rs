@@ -0,0 +1,90 @@
+use rootvg::color::{PackedSrgb, RGBA8};
+use rootvg::cpu_raster::CpuRasterizer;
+use rootvg::headless::{HeadlessConfig, HeadlessContext};
+use rootvg::math::{PhysicalSizeI32, Point, ScaleFactor, Size};
+use rootvg::quad::SolidQuad;
+use rootvg::{Canvas, Primitive};
+use rootvg_text::{glyphon::FontSystem, svg::SvgIconSystem};
+
+const PHYSICAL_SIZE: PhysicalSizeI32 = PhysicalSizeI32::new(300, 200);
+
+/// The maximum difference of any channel between the two renders.
+const TOLERANCE: u8 = 2;
+
+fn main() {
+    // Set up logging stuff
+    let env = env_logger::Env::default().filter_or("LOG_LEVEL", "info");
+    env_logger::init_from_env(env);
+
+    let mut font_system = FontSystem::new();
+    let mut svg_icon_system = SvgIconSystem::default();
+
+    let scale_factor = ScaleFactor::new(1.5);
+    let clear_color: PackedSrgb = RGBA8::new(15, 15, 15, 255).into();
+
+    let primitives: Vec<Primitive> = vec![
+        SolidQuad::builder(Size::new(120.0, 80.0))
+            .position(Point::new(20.0, 20.0))
+            .bg_color(RGBA8::new(50, 100, 200, 255))
+            .border_color(RGBA8::new(200, 200, 200, 255))
+            .border_width(2.0)
+            .border_radius(12.0)
+            .build()
+            .into(),
+        SolidQuad::builder(Size::new(60.0, 40.0))
+            .position(Point::new(100.0, 60.0))
+            .bg_color(RGBA8::new(200, 100, 50, 150))
+            .border_radius(20.0)
+            .build()
+            .into(),
+    ];
+
+    // Render on the CPU.
+    let mut rasterizer = CpuRasterizer::new(PHYSICAL_SIZE, scale_factor, Some(clear_color));
+    for primitive in primitives.iter() {
+        rasterizer.draw(primitive, &mut font_system);
+    }
+    let cpu_image = rasterizer.to_image();
+    cpu_image.save("cpu_raster_cpu.png").unwrap();
+
+    // Render the same primitives on the GPU.
+    let context = HeadlessContext::new(HeadlessConfig::default()).unwrap();
+
+    let mut canvas = Canvas::new(
+        &context.device,
+        &context.queue,
+        context.format(),
+        context.canvas_config(),
+        &mut font_system,
+    );
+
+    {
+        let mut cx = canvas.begin(PHYSICAL_SIZE, scale_factor);
+        for primitive in primitives.iter() {
+            cx.add(primitive.clone());
+        }
+    }
+
+    let gpu_image = context
+        .render_to_image(
+            &mut canvas,
+            PHYSICAL_SIZE,
+            Some(clear_color),
+            &mut font_system,
+            &mut svg_icon_system,
+        )
+        .unwrap();
+    gpu_image.save("cpu_raster_gpu.png").unwrap();
+
+    let max_diff = cpu_image
+        .pixels()
+        .zip(gpu_image.pixels())
+        .flat_map(|(a, b)| a.0.into_iter().zip(b.0).map(|(a, b)| a.abs_diff(b)))
+        .max()
+        .unwrap_or(0);
+
+    log::info!("saved renders to cpu_raster_cpu.png and cpu_raster_gpu.png");
+    log::info!("max channel difference: {}", max_diff);
+
+    assert!(max_diff <= TOLERANCE);
+}
@@ -0,0 +1,443 @@
+/// An example custom pipeline that depth-tests its triangles, so that two
+/// intersecting triangles correctly occlude each other.
+mod depth_triangle {
+    use bytemuck::{Pod, Zeroable};
+    use rootvg::{
+        buffer::Buffer,
+        color::{PackedSrgb, RGBA8},
+        math::Point,
+        pipeline::{CustomPipeline, CustomPipelinePrimitive, DefaultConstantUniforms},
+    };
+    use wgpu::PipelineCompilationOptions;
+
+    const INITIAL_VERTICES: usize = 6;
+
+    #[repr(C)]
+    #[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+    pub struct DepthVertex {
+        pub color: PackedSrgb,
+        pub position: [f32; 2],
+        /// The depth in the range `[0.0, 1.0]`, where smaller values are in
+        /// front.
+        pub depth: f32,
+    }
+
+    impl DepthVertex {
+        pub fn new(color: RGBA8, position: Point, depth: f32) -> Self {
+            Self {
+                color: color.into(),
+                position: position.into(),
+                depth,
+            }
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct DepthTriangle(pub [DepthVertex; 3]);
+
+    pub struct DepthTrianglePipeline {
+        pipeline: wgpu::RenderPipeline,
+
+        constants_buffer: wgpu::Buffer,
+        constants_bind_group: wgpu::BindGroup,
+
+        vertex_buffer: Buffer<DepthVertex>,
+    }
+
+    impl DepthTrianglePipeline {
+        pub fn new(
+            device: &wgpu::Device,
+            format: wgpu::TextureFormat,
+            multisample: wgpu::MultisampleState,
+        ) -> Self {
+            let (constants_layout, constants_buffer, constants_bind_group) =
+                DefaultConstantUniforms::layout_buffer_and_bind_group(device);
+
+            let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("depth triangle pipeline layout"),
+                push_constant_ranges: &[],
+                bind_group_layouts: &[&constants_layout],
+            });
+
+            let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("depth triangle shader"),
+                source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+            });
+
+            let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("depth triangle pipeline"),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: "vs_main",
+                    buffers: &[wgpu::VertexBufferLayout {
+                        array_stride: std::mem::size_of::<DepthVertex>() as u64,
+                        step_mode: wgpu::VertexStepMode::Vertex,
+                        attributes: &wgpu::vertex_attr_array!(
+                            // Color
+                            0 => Float32x4,
+                            // Position
+                            1 => Float32x2,
+                            // Depth
+                            2 => Float32,
+                        ),
+                    }],
+                    compilation_options: PipelineCompilationOptions::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: "fs_main",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: PipelineCompilationOptions::default(),
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    ..Default::default()
+                },
+                // Since `wants_depth` returns `true`, the pipeline must use the
+                // depth format of the canvas.
+                depth_stencil: Some(rootvg::pipeline::depth_stencil_state()),
+                multisample,
+                multiview: None,
+                cache: None,
+            });
+
+            let vertex_buffer = Buffer::new(
+                device,
+                "depth triangle vertex buffer",
+                INITIAL_VERTICES,
+                wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            );
+
+            Self {
+                pipeline,
+                constants_buffer,
+                constants_bind_group,
+                vertex_buffer,
+            }
+        }
+    }
+
+    impl CustomPipeline for DepthTrianglePipeline {
+        fn prepare(
+            &mut self,
+            device: &wgpu::Device,
+            queue: &wgpu::Queue,
+            screen_size: rootvg::math::PhysicalSizeI32,
+            scale_factor: rootvg::math::ScaleFactor,
+            primitives: &[CustomPipelinePrimitive],
+        ) -> Result<(), Box<dyn std::error::Error>> {
+            DefaultConstantUniforms::prepare_buffer(
+                &self.constants_buffer,
+                screen_size,
+                scale_factor,
+                queue,
+            );
+
+            let vertices: Vec<DepthVertex> = primitives
+                .iter()
+                .flat_map(|p| {
+                    let triangle = p.primitive.downcast_ref::<DepthTriangle>().unwrap();
+
+                    // Offset the primitive by the requested amount
+                    triangle.0.map(|mut vertex| {
+                        vertex.position[0] += p.offset.x;
+                        vertex.position[1] += p.offset.y;
+                        vertex
+                    })
+                })
+                .collect();
+
+            self.vertex_buffer
+                .expand_to_fit_new_size(device, vertices.len())?;
+            self.vertex_buffer.write(queue, 0, &vertices);
+
+            Ok(())
+        }
+
+        fn render_primitive<'pass>(
+            &'pass self,
+            primitive_index: usize,
+            render_pass: &mut wgpu::RenderPass<'pass>,
+        ) -> Result<(), Box<dyn std::error::Error>> {
+            render_pass.set_pipeline(&self.pipeline);
+            render_pass.set_bind_group(0, &self.constants_bind_group, &[]);
+
+            let start = primitive_index * 3;
+            render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(start..start + 3));
+            render_pass.draw(0..3, 0..1);
+
+            Ok(())
+        }
+
+        fn wants_depth(&self) -> bool {
+            true
+        }
+    }
+
+    static SHADER: &str = "
+struct Globals {
+    screen_size_recip: vec2f,
+    scale_factor: f32,
+}
+
+@group(0) @binding(0) var<uniform> globals: Globals;
+
+struct VertexInput {
+    @location(0) color: vec4f,
+    @location(1) pos: vec2f,
+    @location(2) depth: f32,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) color: vec4f,
+}
+
+@vertex
+fn vs_main(input: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+
+    let screen_pos: vec2f = input.pos * globals.scale_factor;
+    out.clip_position = vec4<f32>(
+        (screen_pos.x * globals.screen_size_recip.x) - 1.0,
+        1.0 - (screen_pos.y * globals.screen_size_recip.y),
+        input.depth,
+        1.0
+    );
+
+    out.color = input.color;
+
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+    return in.color;
+}
+";
+}
+
+// ---------------------------------------------------------------------------------------
+
+use rootvg::math::{PhysicalSizeI32, Point, Rect, ScaleFactor, Size};
+use rootvg::quad::{SolidQuad, SolidQuadPrimitive};
+use rootvg::{color::RGBA8, pipeline::CustomPrimitive, surface::DefaultSurface, Canvas};
+use rootvg_text::{glyphon::FontSystem, svg::SvgIconSystem};
+use std::sync::Arc;
+use winit::{
+    application::ApplicationHandler,
+    event::WindowEvent,
+    event_loop::{ActiveEventLoop, EventLoop},
+    window::{Window, WindowId},
+};
+
+use self::depth_triangle::{DepthTriangle, DepthTrianglePipeline, DepthVertex};
+
+const WINDOW_SIZE: (f32, f32) = (400.0, 300.0);
+
+fn main() {
+    // Set up logging stuff
+    let env = env_logger::Env::default().filter_or("LOG_LEVEL", "info");
+    env_logger::init_from_env(env);
+
+    let event_loop = EventLoop::new().unwrap();
+    event_loop
+        .run_app(&mut CustomDepthApp {
+            state: None,
+            font_system: FontSystem::new(),
+            svg_icon_system: SvgIconSystem::default(),
+        })
+        .unwrap();
+}
+
+struct State {
+    window: Arc<Window>,
+    physical_size: PhysicalSizeI32,
+    scale_factor: ScaleFactor,
+    surface: DefaultSurface<'static>,
+    canvas: Canvas,
+
+    triangles: [CustomPrimitive; 2],
+}
+
+struct CustomDepthApp {
+    state: Option<State>,
+    font_system: FontSystem,
+    svg_icon_system: SvgIconSystem,
+}
+
+impl CustomDepthApp {
+    fn create_window(&mut self, event_loop: &ActiveEventLoop) {
+        let window_attributes = Window::default_attributes()
+            .with_inner_size(winit::dpi::LogicalSize::new(
+                WINDOW_SIZE.0 as f64,
+                WINDOW_SIZE.1 as f64,
+            ))
+            .with_title("RootVG Custom Depth Demo");
+        let window = Arc::new(event_loop.create_window(window_attributes).unwrap());
+
+        let physical_size = window.inner_size();
+        let physical_size =
+            PhysicalSizeI32::new(physical_size.width as i32, physical_size.height as i32);
+        let scale_factor: ScaleFactor = window.scale_factor().into();
+
+        let surface = rootvg::surface::DefaultSurface::new(
+            physical_size,
+            scale_factor,
+            Arc::clone(&window),
+            rootvg::surface::DefaultSurfaceConfig {
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        // The depth buffer of the canvas doesn't need to be enabled, since
+        // the custom pipeline opts into it with `wants_depth`.
+        let canvas_config = surface.canvas_config();
+
+        let mut canvas = rootvg::Canvas::new(
+            &surface.device,
+            &surface.queue,
+            surface.format(),
+            canvas_config,
+            &mut self.font_system,
+        );
+
+        let pipeline_id = canvas.insert_custom_pipeline(DepthTrianglePipeline::new(
+            &surface.device,
+            surface.format(),
+            canvas_config.multisample,
+        ));
+
+        // Each triangle leans through the other one, so each one is partially
+        // in front of the other. Without depth testing, the triangle drawn
+        // last would simply cover the other one.
+        let red = RGBA8::new(230, 60, 60, 255);
+        let blue = RGBA8::new(60, 120, 230, 255);
+        let triangles = [
+            CustomPrimitive::new(
+                DepthTriangle([
+                    DepthVertex::new(red, Point::new(60.0, 60.0), 0.2),
+                    DepthVertex::new(red, Point::new(340.0, 150.0), 0.8),
+                    DepthVertex::new(red, Point::new(60.0, 240.0), 0.2),
+                ]),
+                pipeline_id,
+            ),
+            CustomPrimitive::new(
+                DepthTriangle([
+                    DepthVertex::new(blue, Point::new(340.0, 60.0), 0.2),
+                    DepthVertex::new(blue, Point::new(340.0, 240.0), 0.2),
+                    DepthVertex::new(blue, Point::new(60.0, 150.0), 0.8),
+                ]),
+                pipeline_id,
+            ),
+        ];
+
+        self.state = Some(State {
+            window,
+            physical_size,
+            scale_factor,
+            surface,
+            canvas,
+            triangles,
+        });
+    }
+}
+
+impl ApplicationHandler for CustomDepthApp {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.state.is_none() {
+            self.create_window(event_loop);
+        }
+    }
+
+    fn window_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        _window_id: WindowId,
+        event: WindowEvent,
+    ) {
+        let Some(state) = &mut self.state else {
+            return;
+        };
+
+        match event {
+            WindowEvent::RedrawRequested => {
+                {
+                    let mut cx = state.canvas.begin(state.physical_size, state.scale_factor);
+
+                    // The background quad doesn't use the depth buffer, so it is
+                    // rendered in a separate render pass.
+                    cx.add(SolidQuadPrimitive::new(&SolidQuad {
+                        bounds: Rect::new(Point::new(20.0, 20.0), Size::new(360.0, 260.0)),
+                        bg_color: RGBA8::new(60, 60, 60, 255).into(),
+                        ..Default::default()
+                    }));
+
+                    cx.set_z_index(1);
+
+                    cx.add_batch(state.triangles.iter().cloned());
+                }
+
+                let frame = state.surface.get_current_texture().unwrap();
+                let view = frame
+                    .texture
+                    .create_view(&wgpu::TextureViewDescriptor::default());
+                let mut encoder = state
+                    .surface
+                    .device
+                    .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+                state
+                    .canvas
+                    .render_to_target(
+                        Some(RGBA8::new(0, 0, 0, 255).into()),
+                        &state.surface.device,
+                        &state.surface.queue,
+                        &mut encoder,
+                        &view,
+                        state.physical_size,
+                        &mut self.font_system,
+                        &mut self.svg_icon_system,
+                    )
+                    .unwrap();
+
+                state.window.pre_present_notify();
+
+                state.surface.queue.submit(Some(encoder.finish()));
+                frame.present();
+            }
+            WindowEvent::Resized(new_size) => {
+                state.physical_size =
+                    PhysicalSizeI32::new(new_size.width as i32, new_size.height as i32);
+                state
+                    .surface
+                    .resize(state.physical_size, state.scale_factor);
+                state.window.request_redraw();
+            }
+            WindowEvent::ScaleFactorChanged {
+                scale_factor: new_scale,
+                inner_size_writer: _,
+            } => {
+                state.scale_factor = new_scale.into();
+                state
+                    .surface
+                    .resize(state.physical_size, state.scale_factor);
+                state.window.request_redraw();
+            }
+            WindowEvent::CloseRequested => event_loop.exit(),
+            _ => {}
+        }
+    }
+
+    fn exiting(&mut self, _event_loop: &ActiveEventLoop) {
+        // Be sure to drop the wgpu surface before the window closes,
+        // or else the program might segfault.
+        self.state = None;
+    }
+}
@@ -44,6 +44,7 @@ mod my_custom_primitive {
             device: &wgpu::Device,
             format: wgpu::TextureFormat,
             multisample: wgpu::MultisampleState,
+            depth_stencil: Option<wgpu::DepthStencilState>,
         ) -> Self {
             let (constants_layout, constants_buffer, constants_bind_group) =
                 DefaultConstantUniforms::layout_buffer_and_bind_group(device);
@@ -105,7 +106,7 @@ mod my_custom_primitive {
                     front_face: wgpu::FrontFace::Cw,
                     ..Default::default()
                 },
-                depth_stencil: None,
+                depth_stencil,
                 multisample,
                 multiview: None,
                 cache: None,
@@ -158,7 +159,7 @@ mod my_custom_primitive {
                 .collect();
 
             self.vertex_buffer
-                .expand_to_fit_new_size(device, primitives.len());
+                .expand_to_fit_new_size(device, primitives.len())?;
             self.vertex_buffer.write(queue, 0, &vertices);
 
             self.num_vertices = vertices.len();
@@ -183,6 +184,23 @@ mod my_custom_primitive {
 
             Ok(())
         }
+
+        fn render_primitives<'pass>(
+            &'pass self,
+            primitives: std::ops::Range<usize>,
+            render_pass: &mut wgpu::RenderPass<'pass>,
+        ) -> Result<(), Box<dyn std::error::Error>> {
+            render_pass.set_pipeline(&self.pipeline);
+            render_pass.set_bind_group(0, &self.constants_bind_group, &[]);
+
+            // Draw all of the primitives in the batch with a single instanced
+            // draw call.
+            let num_instances = primitives.len() as u32;
+            render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(primitives));
+            render_pass.draw(0..3, 0..num_instances);
+
+            Ok(())
+        }
     }
 
     static SHADER: &'static str = "
@@ -333,6 +351,8 @@ impl CustomPrimitiveApp {
             &surface.device,
             surface.format(),
             canvas_config.multisample,
+            // This pipeline doesn't want the depth buffer.
+            None,
         ));
 
         // --- Create custom primitives ------------------------------------------------------
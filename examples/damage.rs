@@ -0,0 +1,107 @@
+use rootvg::color::{PackedSrgb, RGBA8};
+use rootvg::headless::{HeadlessConfig, HeadlessContext};
+use rootvg::math::{PhysicalSizeI32, Point, PointI32, RectI32, ScaleFactor, Size, SizeI32};
+use rootvg::quad::SolidQuad;
+use rootvg::{Canvas, CanvasCtx};
+use rootvg_text::{glyphon::FontSystem, svg::SvgIconSystem};
+
+const PHYSICAL_SIZE: PhysicalSizeI32 = PhysicalSizeI32::new(300, 200);
+const CURSOR_SIZE: SizeI32 = SizeI32::new(2, 20);
+const NUM_FRAMES: i32 = 10;
+
+fn main() {
+    // Set up logging stuff
+    let env = env_logger::Env::default().filter_or("LOG_LEVEL", "info");
+    env_logger::init_from_env(env);
+
+    let mut font_system = FontSystem::new();
+    let mut svg_icon_system = SvgIconSystem::default();
+
+    let context = HeadlessContext::new(HeadlessConfig::default()).unwrap();
+
+    let mut canvas = Canvas::new(
+        &context.device,
+        &context.queue,
+        context.format(),
+        context.canvas_config(),
+        &mut font_system,
+    );
+
+    let clear_color: PackedSrgb = RGBA8::new(15, 15, 15, 255).into();
+
+    // The same render target is used for every frame, so the pixels outside of
+    // the damaged region are kept from the previous frame.
+    let target = context.create_render_target(PHYSICAL_SIZE);
+
+    let mut prev_cursor_rect = None;
+    for frame in 0..NUM_FRAMES {
+        let cursor_rect = RectI32::new(PointI32::new(60 + frame * 20, 90), CURSOR_SIZE);
+
+        {
+            // The first frame is drawn in full. After that, only the old and
+            // new positions of the cursor are redrawn.
+            let mut cx = if let Some(prev_cursor_rect) = prev_cursor_rect {
+                canvas.begin_with_damage(
+                    PHYSICAL_SIZE,
+                    ScaleFactor::new(1.0),
+                    &[prev_cursor_rect, cursor_rect],
+                )
+            } else {
+                canvas.begin(PHYSICAL_SIZE, ScaleFactor::new(1.0))
+            };
+
+            draw_scene(&mut cx, cursor_rect);
+        }
+
+        let mut encoder = context
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+        canvas
+            .render_to_target(
+                Some(clear_color),
+                &context.device,
+                &context.queue,
+                &mut encoder,
+                &target.view,
+                PHYSICAL_SIZE,
+                &mut font_system,
+                &mut svg_icon_system,
+            )
+            .unwrap();
+
+        context.queue.submit(Some(encoder.finish()));
+
+        prev_cursor_rect = Some(cursor_rect);
+    }
+
+    let image = context.read_target(&target).unwrap();
+    image.save("damage.png").unwrap();
+
+    log::info!("saved render to damage.png");
+}
+
+/// Every frame adds all of its primitives, even when only a small part of
+/// the canvas is redrawn.
+fn draw_scene(cx: &mut CanvasCtx, cursor_rect: RectI32) {
+    // A text field which spans the whole path of the cursor, so the damaged
+    // regions overlap primitives of multiple z indexes.
+    cx.add(
+        SolidQuad::builder(Size::new(260.0, 40.0))
+            .position(Point::new(20.0, 80.0))
+            .bg_color(RGBA8::new(40, 40, 40, 255))
+            .border_color(RGBA8::new(100, 100, 100, 255))
+            .border_width(1.0)
+            .border_radius(4.0)
+            .build(),
+    );
+
+    cx.set_z_index(1);
+
+    cx.add(
+        SolidQuad::builder(cursor_rect.size.to_f32())
+            .position(cursor_rect.origin.to_f32())
+            .bg_color(RGBA8::new(255, 255, 255, 255))
+            .build(),
+    );
+}
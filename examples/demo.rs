@@ -8,7 +8,7 @@ use winit::{
     window::{Window, WindowId},
 };
 
-use rootvg::gradient::{LinearGradient, PackedGradient};
+use rootvg::gradient::{ConicGradient, LinearGradient, PackedGradient};
 use rootvg::image::{ImagePrimitive, RcTexture};
 use rootvg::math::{
     radians, Angle, PhysicalSizeI32, Point, PointI32, Rect, RectI32, Scale, ScaleFactor, Size,
@@ -16,7 +16,7 @@ use rootvg::math::{
 };
 use rootvg::msaa::Antialiasing;
 use rootvg::quad::{
-    Border, GradientQuad, GradientQuadPrimitive, Radius, SolidQuad, SolidQuadPrimitive,
+    Border, GradientQuad, GradientQuadPrimitive, Radius, Shadow, SolidQuad, SolidQuadPrimitive,
 };
 use rootvg::tessellation::{
     path::{ArcPath, PathBuilder},
@@ -64,7 +64,10 @@ struct State {
 struct MyPrimitives {
     solid_quad: SolidQuadPrimitive,
     gradient_quad: GradientQuadPrimitive,
+    knob_quad: GradientQuadPrimitive,
+    tilted_quad: SolidQuadPrimitive,
     text_primitive: TextPrimitive,
+    underlined_text_primitive: TextPrimitive,
     svg_icon_primitive: TextPrimitive,
     image_primitive: ImagePrimitive,
     arc_mesh: MeshPrimitive,
@@ -161,11 +164,21 @@ impl DemoApp {
             border: Border {
                 // The quad can have an outline filled with a solid color.
                 color: RGBA8::new(19, 147, 94, 255).into(),
-                width: 3.0,
+                width: 3.0.into(),
                 // A large radius turns this quad into a circle.
                 radius: 50.0.into(),
+                dash: None,
             },
             flags: Default::default(),
+            // The quad can also have a drop shadow.
+            shadow: Some(Shadow {
+                color: RGBA8::new(0, 0, 0, 150).into(),
+                offset: Vector::new(3.0, 5.0),
+                blur_radius: 8.0,
+                spread: 0.0,
+                inset: false,
+            }),
+            transform: None,
         }
         .into();
 
@@ -188,6 +201,37 @@ impl DemoApp {
             })
             .into();
 
+        // A conic gradient sweeps around a center point, which is useful for
+        // things like circular knob indicators. A stop at `0.0` and a stop at
+        // `1.0` with the same color meet seamlessly.
+        let knob_quad: GradientQuadPrimitive = GradientQuad::builder(Size::new(70.0, 70.0))
+            .position(Point::new(700.0, 300.0))
+            .bg_gradient(
+                ConicGradient::new(Point::new(35.0, 35.0), radians(std::f32::consts::FRAC_PI_2))
+                    .add_stop(0.0, RGBA8::new(30, 235, 150, 255))
+                    .add_stop(0.5, RGBA8::new(20, 0, 100, 255))
+                    .add_stop(1.0, RGBA8::new(30, 235, 150, 255)),
+            )
+            .border_color(RGBA8::new(150, 150, 150, 255))
+            .border_width(2.0)
+            .border_radius(35.0)
+            .into();
+
+        // Quads can be rotated (or skewed) with a transform. The rounded corners,
+        // border, and shadow are computed before the transform is applied, so
+        // they rotate along with the quad.
+        let tilted_quad: SolidQuadPrimitive = SolidQuad::builder(Size::new(100.0, 60.0))
+            .position(Point::new(560.0, 320.0))
+            .bg_color(RGBA8::new(235, 150, 30, 255))
+            .border_color(RGBA8::new(150, 90, 20, 255))
+            .border_width(2.0)
+            .border_radius(10.0)
+            .shadow_color(RGBA8::new(0, 0, 0, 150))
+            .shadow_offset(Vector::new(3.0, 5.0))
+            .shadow_blur_radius(8.0)
+            .rotation(radians(std::f32::consts::FRAC_PI_6), Point::new(0.5, 0.5))
+            .into();
+
         // --- Text --------------------------------------------------------------------------
 
         // First create a text buffer which performs layout and shaping on some text. This
@@ -205,8 +249,10 @@ impl DemoApp {
                 },
                 ..Default::default()
             },
-            // The "bounds" denotes the visible area. Any text that lies outside of this
-            // bounds wrapped/clipped depending on the text properties.
+            // The bounds width is the width that the text wraps at (depending on the
+            // text properties), and the bounds height is the height of the area it is
+            // laid out in. The visible area is set separately with the clipping bounds
+            // of the text primitive.
             None,
             None,
             false,
@@ -220,6 +266,30 @@ impl DemoApp {
             None,
         );
 
+        // Text can also be decorated with underlines and strikethroughs. The lines are
+        // positioned using the metrics of the font.
+        let underlined_text_buffer = RcTextBuffer::new(
+            "Underlined text",
+            TextProperties {
+                metrics: Metrics {
+                    font_size: 14.0,
+                    line_height: 20.0,
+                },
+                underline: true,
+                ..Default::default()
+            },
+            None,
+            None,
+            false,
+            font_system,
+        );
+        let underlined_text_primitive = TextPrimitive::new(
+            underlined_text_buffer,
+            Point::new(310.0, 125.0),
+            RGBA8::new(200, 200, 200, 255),
+            None,
+        );
+
         // --- Svg Icon ----------------------------------------------------------------------
 
         let svg_icon_primitive = TextPrimitive::new_with_icons(
@@ -285,6 +355,7 @@ impl DemoApp {
             line_cap: LineCap::Round,
             line_join: LineJoin::default(),
             line_dash: LineDash::default(),
+            ..Default::default()
         };
 
         // A tessellator generates mesh primitives.
@@ -324,6 +395,7 @@ impl DemoApp {
             line_cap: LineCap::Round,
             line_join: LineJoin::default(),
             line_dash: LineDash::default(),
+            ..Default::default()
         };
 
         let bezier_path = PathBuilder::new()
@@ -348,7 +420,10 @@ impl DemoApp {
         MyPrimitives {
             solid_quad,
             gradient_quad,
+            knob_quad,
+            tilted_quad,
             text_primitive,
+            underlined_text_primitive,
             svg_icon_primitive,
             image_primitive,
             arc_mesh,
@@ -395,12 +470,15 @@ impl ApplicationHandler for DemoApp {
                     // Because of that, we need add the text primitive with a higher
                     // z index.
                     cx.add(state.primitives.text_primitive.clone());
+                    cx.add(state.primitives.underlined_text_primitive.clone());
                     cx.add(state.primitives.svg_icon_primitive.clone());
 
                     cx.set_z_index(0);
 
                     cx.add(state.primitives.solid_quad);
                     cx.add(state.primitives.gradient_quad);
+                    cx.add(state.primitives.knob_quad);
+                    cx.add(state.primitives.tilted_quad);
                     cx.add(state.primitives.image_primitive.clone());
 
                     // Primitives can also be constructed inline. This is a bit less
@@ -415,6 +493,21 @@ impl ApplicationHandler for DemoApp {
                             .build(),
                     );
 
+                    // An inner shadow can be used to make a button look pressed.
+                    cx.add(
+                        SolidQuad::builder(Size::new(100.0, 40.0))
+                            .position(Point::new(620.0, 220.0))
+                            .bg_color(RGBA8::new(60, 60, 60, 255))
+                            .border_color(RGBA8::new(100, 100, 100, 255))
+                            .border_width(1.0)
+                            .border_radius(6.0)
+                            .shadow_color(RGBA8::new(0, 0, 0, 200))
+                            .shadow_offset(Vector::new(0.0, 3.0))
+                            .shadow_blur_radius(4.0)
+                            .shadow_inset(true)
+                            .build(),
+                    );
+
                     // A scissoring rectangle can be used.
                     cx.set_scissor_rect(RectI32::new(
                         PointI32::new(50, 150),
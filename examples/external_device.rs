@@ -0,0 +1,145 @@
+use rootvg::color::{GAMMA_CORRECTION, RGBA8};
+use rootvg::math::{PhysicalSizeI32, Point, ScaleFactor, Size};
+use rootvg::quad::SolidQuad;
+use rootvg::{Canvas, CanvasConfig};
+use rootvg_text::{glyphon::FontSystem, svg::SvgIconSystem};
+
+const PHYSICAL_SIZE: PhysicalSizeI32 = PhysicalSizeI32::new(64, 64);
+
+const CLEAR_COLOR: RGBA8 = RGBA8::new(15, 15, 15, 255);
+const QUAD_COLOR: RGBA8 = RGBA8::new(50, 100, 200, 255);
+
+fn main() {
+    // Set up logging stuff
+    let env = env_logger::Env::default().filter_or("LOG_LEVEL", "info");
+    env_logger::init_from_env(env);
+
+    // The host application creates the device, queue, and render target
+    // however it likes, for example a plugin rendering into a texture of
+    // the host.
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+    let adapter = pollster::block_on(instance.request_adapter(&Default::default())).unwrap();
+    let (device, queue) = pollster::block_on(adapter.request_device(
+        &wgpu::DeviceDescriptor {
+            label: Some("host device"),
+            required_limits: wgpu::Limits::downlevel_defaults(),
+            ..Default::default()
+        },
+        None,
+    ))
+    .unwrap();
+
+    // Pick a BGRA format, which is what most hosts render to. Without the
+    // `web-colors` feature, the format needs to be an sRGB one.
+    let format = if GAMMA_CORRECTION {
+        wgpu::TextureFormat::Bgra8UnormSrgb
+    } else {
+        wgpu::TextureFormat::Bgra8Unorm
+    };
+
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("host texture"),
+        size: wgpu::Extent3d {
+            width: PHYSICAL_SIZE.width as u32,
+            height: PHYSICAL_SIZE.height as u32,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    let mut font_system = FontSystem::new();
+    let mut svg_icon_system = SvgIconSystem::default();
+
+    let mut canvas = Canvas::new(
+        &device,
+        &queue,
+        format,
+        CanvasConfig::default(),
+        &mut font_system,
+    );
+
+    {
+        let mut cx = canvas.begin(PHYSICAL_SIZE, ScaleFactor::new(1.0));
+
+        cx.add(
+            SolidQuad::builder(Size::new(32.0, 32.0))
+                .position(Point::new(16.0, 16.0))
+                .bg_color(QUAD_COLOR)
+                .build(),
+        );
+    }
+
+    let mut encoder =
+        device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+    canvas
+        .render_to_target(
+            Some(CLEAR_COLOR.into()),
+            &device,
+            &queue,
+            &mut encoder,
+            &view,
+            PHYSICAL_SIZE,
+            &mut font_system,
+            &mut svg_icon_system,
+        )
+        .unwrap();
+
+    // Read the texture back to check the colors that were written.
+    let bytes_per_row = PHYSICAL_SIZE.width as u32 * 4;
+    assert_eq!(bytes_per_row % wgpu::COPY_BYTES_PER_ROW_ALIGNMENT, 0);
+
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("host readback buffer"),
+        size: (bytes_per_row * PHYSICAL_SIZE.height as u32) as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(bytes_per_row),
+                rows_per_image: None,
+            },
+        },
+        texture.size(),
+    );
+
+    queue.submit(Some(encoder.finish()));
+
+    buffer
+        .slice(..)
+        .map_async(wgpu::MapMode::Read, |res| res.unwrap());
+    device.poll(wgpu::Maintain::Wait);
+
+    let pixels = buffer.slice(..).get_mapped_range();
+    let pixel = |x: u32, y: u32| {
+        let i = (y * bytes_per_row + x * 4) as usize;
+        // The texture is BGRA.
+        RGBA8::new(pixels[i + 2], pixels[i + 1], pixels[i], pixels[i + 3])
+    };
+
+    // Both the clear color and the quad are written as the same bytes that
+    // were requested, give or take rounding.
+    for (actual, expected) in [(pixel(2, 2), CLEAR_COLOR), (pixel(32, 32), QUAD_COLOR)] {
+        log::info!("expected {expected:?}, got {actual:?}");
+
+        let [r, g, b, a] = [
+            actual.r.abs_diff(expected.r),
+            actual.g.abs_diff(expected.g),
+            actual.b.abs_diff(expected.b),
+            actual.a.abs_diff(expected.a),
+        ];
+        assert!(r <= 1 && g <= 1 && b <= 1 && a <= 1);
+    }
+}
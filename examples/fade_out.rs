@@ -0,0 +1,228 @@
+use rootvg_text::{glyphon::FontSystem, svg::SvgIconSystem};
+use std::sync::Arc;
+use std::time::Instant;
+use winit::{
+    application::ApplicationHandler,
+    event::WindowEvent,
+    event_loop::{ActiveEventLoop, EventLoop},
+    window::{Window, WindowId},
+};
+
+use rootvg::color::{PackedSrgb, RGBA8};
+use rootvg::gradient::LinearGradient;
+use rootvg::math::{radians, PhysicalSizeI32, Point, ScaleFactor, Size, Vector};
+use rootvg::quad::{GradientQuad, SolidQuad};
+use rootvg::{surface::DefaultSurface, Canvas};
+
+const WINDOW_SIZE: (f32, f32) = (400.0, 300.0);
+
+/// How long it takes for the panel to fade out and back in, in seconds.
+const FADE_PERIOD: f32 = 3.0;
+
+fn main() {
+    // Set up logging stuff
+    let env = env_logger::Env::default().filter_or("LOG_LEVEL", "info");
+    env_logger::init_from_env(env);
+
+    let event_loop = EventLoop::new().unwrap();
+    event_loop
+        .run_app(&mut FadeOutApp {
+            state: None,
+            font_system: FontSystem::new(),
+            svg_icon_system: SvgIconSystem::default(),
+        })
+        .unwrap();
+}
+
+struct State {
+    window: Arc<Window>,
+    physical_size: PhysicalSizeI32,
+    scale_factor: ScaleFactor,
+    surface: DefaultSurface<'static>,
+    canvas: Canvas,
+    clear_color: PackedSrgb,
+    start_time: Instant,
+}
+
+struct FadeOutApp {
+    state: Option<State>,
+    font_system: FontSystem,
+    svg_icon_system: SvgIconSystem,
+}
+
+impl FadeOutApp {
+    fn create_window(&mut self, event_loop: &ActiveEventLoop) {
+        let window_attributes = Window::default_attributes()
+            .with_inner_size(winit::dpi::LogicalSize::new(
+                WINDOW_SIZE.0 as f64,
+                WINDOW_SIZE.1 as f64,
+            ))
+            .with_title("RootVG Fade Out Demo");
+        let window = Arc::new(event_loop.create_window(window_attributes).unwrap());
+
+        let physical_size = window.inner_size();
+        let physical_size =
+            PhysicalSizeI32::new(physical_size.width as i32, physical_size.height as i32);
+        let scale_factor: ScaleFactor = window.scale_factor().into();
+
+        let surface = rootvg::surface::DefaultSurface::new(
+            physical_size,
+            scale_factor,
+            Arc::clone(&window),
+            rootvg::surface::DefaultSurfaceConfig {
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let canvas = rootvg::Canvas::new(
+            &surface.device,
+            &surface.queue,
+            surface.format(),
+            surface.canvas_config(),
+            &mut self.font_system,
+        );
+
+        self.state = Some(State {
+            window,
+            physical_size,
+            scale_factor,
+            surface,
+            canvas,
+            clear_color: RGBA8::new(15, 15, 15, 255).into(),
+            start_time: Instant::now(),
+        });
+    }
+}
+
+impl ApplicationHandler for FadeOutApp {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.state.is_none() {
+            self.create_window(event_loop);
+        }
+    }
+
+    fn window_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        _window_id: WindowId,
+        event: WindowEvent,
+    ) {
+        let Some(state) = &mut self.state else {
+            return;
+        };
+
+        match event {
+            WindowEvent::RedrawRequested => {
+                {
+                    let mut cx = state.canvas.begin(state.physical_size, state.scale_factor);
+
+                    // Fade the panel out and back in.
+                    let t = state.start_time.elapsed().as_secs_f32() / FADE_PERIOD;
+                    let alpha = ((t * std::f32::consts::TAU).cos() * 0.5) + 0.5;
+
+                    // Every primitive of the panel is faded uniformly, including
+                    // the borders, shadows, and gradient stops.
+                    cx.push_global_alpha(alpha);
+
+                    cx.add(
+                        SolidQuad::builder(Size::new(250.0, 150.0))
+                            .position(Point::new(75.0, 75.0))
+                            .bg_color(RGBA8::new(40, 40, 45, 255))
+                            .border_color(RGBA8::new(100, 100, 110, 255))
+                            .border_width(1.0)
+                            .border_radius(8.0)
+                            .shadow_color(RGBA8::new(0, 0, 0, 200))
+                            .shadow_offset(Vector::new(0.0, 4.0))
+                            .shadow_blur_radius(12.0)
+                            .build(),
+                    );
+
+                    cx.add(
+                        GradientQuad::builder(Size::new(250.0, 30.0))
+                            .position(Point::new(75.0, 75.0))
+                            .bg_gradient(
+                                LinearGradient::new(radians(std::f32::consts::FRAC_PI_2))
+                                    .add_stop(0.0, RGBA8::new(20, 0, 100, 255))
+                                    .add_stop(1.0, RGBA8::new(200, 0, 100, 255)),
+                            )
+                            .border_radius(8.0)
+                            .build(),
+                    );
+
+                    // Nested content fades together with its parent, so this
+                    // button is always half as opaque as the panel.
+                    cx.push_global_alpha(0.5);
+
+                    cx.add(
+                        SolidQuad::builder(Size::new(100.0, 30.0))
+                            .position(Point::new(150.0, 160.0))
+                            .bg_color(RGBA8::new(50, 100, 200, 255))
+                            .border_radius(4.0)
+                            .build(),
+                    );
+
+                    cx.pop_global_alpha();
+                    cx.pop_global_alpha();
+                }
+
+                let frame = state.surface.get_current_texture().unwrap();
+                let view = frame
+                    .texture
+                    .create_view(&wgpu::TextureViewDescriptor::default());
+                let mut encoder = state
+                    .surface
+                    .device
+                    .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+                state
+                    .canvas
+                    .render_to_target(
+                        Some(state.clear_color),
+                        &state.surface.device,
+                        &state.surface.queue,
+                        &mut encoder,
+                        &view,
+                        state.physical_size,
+                        &mut self.font_system,
+                        &mut self.svg_icon_system,
+                    )
+                    .unwrap();
+
+                state.window.pre_present_notify();
+
+                state.surface.queue.submit(Some(encoder.finish()));
+                frame.present();
+
+                // Animate the fade on the next frame.
+                state.window.request_redraw();
+            }
+            WindowEvent::Resized(new_size) => {
+                state.physical_size =
+                    PhysicalSizeI32::new(new_size.width as i32, new_size.height as i32);
+                state
+                    .surface
+                    .resize(state.physical_size, state.scale_factor);
+                state.window.request_redraw();
+            }
+            WindowEvent::ScaleFactorChanged {
+                scale_factor: new_scale,
+                inner_size_writer: _,
+            } => {
+                state.scale_factor = new_scale.into();
+                state
+                    .surface
+                    .resize(state.physical_size, state.scale_factor);
+                state.window.request_redraw();
+            }
+            WindowEvent::CloseRequested => event_loop.exit(),
+            _ => {}
+        }
+    }
+
+    fn exiting(&mut self, _event_loop: &ActiveEventLoop) {
+        // Be sure to drop the wgpu surface before the window closes,
+        // or else the program might segfault.
+        self.state = None;
+    }
+}
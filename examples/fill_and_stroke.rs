@@ -0,0 +1,213 @@
+use rootvg_text::{glyphon::FontSystem, svg::SvgIconSystem};
+use std::sync::Arc;
+use winit::{
+    application::ApplicationHandler,
+    event::WindowEvent,
+    event_loop::{ActiveEventLoop, EventLoop},
+    window::{Window, WindowId},
+};
+
+use rootvg::color::{PackedSrgb, RGBA8};
+use rootvg::gradient::{LinearGradient, PackedGradient};
+use rootvg::math::{radians, PhysicalSizeI32, Point, Rect, ScaleFactor, Size};
+use rootvg::mesh::MeshPrimitive;
+use rootvg::tessellation::{
+    path::Path,
+    stroke::{LineCap, LineDash, LineJoin, Stroke},
+    Tessellator,
+};
+use rootvg::{surface::DefaultSurface, Canvas};
+
+const WINDOW_SIZE: (f32, f32) = (400.0, 300.0);
+
+fn main() {
+    // Set up logging stuff
+    let env = env_logger::Env::default().filter_or("LOG_LEVEL", "info");
+    env_logger::init_from_env(env);
+
+    let event_loop = EventLoop::new().unwrap();
+    event_loop
+        .run_app(&mut FillAndStrokeApp {
+            state: None,
+            font_system: FontSystem::new(),
+            svg_icon_system: SvgIconSystem::default(),
+        })
+        .unwrap();
+}
+
+struct State {
+    window: Arc<Window>,
+    physical_size: PhysicalSizeI32,
+    scale_factor: ScaleFactor,
+    surface: DefaultSurface<'static>,
+    canvas: Canvas,
+    clear_color: PackedSrgb,
+    circle_meshes: Vec<MeshPrimitive>,
+}
+
+struct FillAndStrokeApp {
+    state: Option<State>,
+    font_system: FontSystem,
+    svg_icon_system: SvgIconSystem,
+}
+
+impl FillAndStrokeApp {
+    fn create_window(&mut self, event_loop: &ActiveEventLoop) {
+        let window_attributes = Window::default_attributes()
+            .with_inner_size(winit::dpi::LogicalSize::new(
+                WINDOW_SIZE.0 as f64,
+                WINDOW_SIZE.1 as f64,
+            ))
+            .with_title("RootVG Fill and Stroke Demo");
+        let window = Arc::new(event_loop.create_window(window_attributes).unwrap());
+
+        let physical_size = window.inner_size();
+        let physical_size =
+            PhysicalSizeI32::new(physical_size.width as i32, physical_size.height as i32);
+        let scale_factor: ScaleFactor = window.scale_factor().into();
+
+        let surface = rootvg::surface::DefaultSurface::new(
+            physical_size,
+            scale_factor,
+            Arc::clone(&window),
+            rootvg::surface::DefaultSurfaceConfig {
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let canvas = rootvg::Canvas::new(
+            &surface.device,
+            &surface.queue,
+            surface.format(),
+            surface.canvas_config(),
+            &mut self.font_system,
+        );
+
+        self.state = Some(State {
+            window,
+            physical_size,
+            scale_factor,
+            surface,
+            canvas,
+            clear_color: RGBA8::new(15, 15, 15, 255).into(),
+            circle_meshes: circle_meshes(),
+        });
+    }
+}
+
+/// A circle with a solid fill and a contrasting gradient outline.
+fn circle_meshes() -> Vec<MeshPrimitive> {
+    let center = Point::new(WINDOW_SIZE.0 * 0.5, WINDOW_SIZE.1 * 0.5);
+    let radius = 100.0;
+
+    let path = Path::circle(center, radius);
+
+    let stroke = Stroke {
+        style: PackedGradient::new(
+            &LinearGradient::new(radians(std::f32::consts::FRAC_PI_4))
+                .add_stop(0.0, RGBA8::new(255, 200, 0, 255))
+                .add_stop(1.0, RGBA8::new(255, 0, 120, 255))
+                .into(),
+            Rect::new(
+                Point::new(center.x - radius, center.y - radius),
+                Size::new(radius * 2.0, radius * 2.0),
+            ),
+        )
+        .into(),
+        width: 8.0,
+        line_cap: LineCap::default(),
+        line_join: LineJoin::default(),
+        line_dash: LineDash::default(),
+        ..Default::default()
+    };
+
+    Tessellator::new()
+        .fill_and_stroke(&path, RGBA8::new(30, 80, 160, 255), stroke)
+        .into_primitive_batch()
+}
+
+impl ApplicationHandler for FillAndStrokeApp {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.state.is_none() {
+            self.create_window(event_loop);
+        }
+    }
+
+    fn window_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        _window_id: WindowId,
+        event: WindowEvent,
+    ) {
+        let Some(state) = &mut self.state else {
+            return;
+        };
+
+        match event {
+            WindowEvent::RedrawRequested => {
+                {
+                    let mut cx = state.canvas.begin(state.physical_size, state.scale_factor);
+
+                    // The fill and the stroke come out of the tessellator as
+                    // separate meshes in the order they were drawn in.
+                    cx.add_batch(state.circle_meshes.iter().cloned());
+                }
+
+                let frame = state.surface.get_current_texture().unwrap();
+                let view = frame
+                    .texture
+                    .create_view(&wgpu::TextureViewDescriptor::default());
+                let mut encoder = state
+                    .surface
+                    .device
+                    .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+                state
+                    .canvas
+                    .render_to_target(
+                        Some(state.clear_color),
+                        &state.surface.device,
+                        &state.surface.queue,
+                        &mut encoder,
+                        &view,
+                        state.physical_size,
+                        &mut self.font_system,
+                        &mut self.svg_icon_system,
+                    )
+                    .unwrap();
+
+                state.window.pre_present_notify();
+
+                state.surface.queue.submit(Some(encoder.finish()));
+                frame.present();
+            }
+            WindowEvent::Resized(new_size) => {
+                state.physical_size =
+                    PhysicalSizeI32::new(new_size.width as i32, new_size.height as i32);
+                state
+                    .surface
+                    .resize(state.physical_size, state.scale_factor);
+                state.window.request_redraw();
+            }
+            WindowEvent::ScaleFactorChanged {
+                scale_factor: new_scale,
+                inner_size_writer: _,
+            } => {
+                state.scale_factor = new_scale.into();
+                state
+                    .surface
+                    .resize(state.physical_size, state.scale_factor);
+                state.window.request_redraw();
+            }
+            WindowEvent::CloseRequested => event_loop.exit(),
+            _ => {}
+        }
+    }
+
+    fn exiting(&mut self, _event_loop: &ActiveEventLoop) {
+        // Be sure to drop the wgpu surface before the window closes,
+        // or else the program might segfault.
+        self.state = None;
+    }
+}
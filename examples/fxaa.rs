@@ -0,0 +1,273 @@
+use rootvg_text::{glyphon::FontSystem, svg::SvgIconSystem};
+use std::sync::Arc;
+use winit::{
+    application::ApplicationHandler,
+    event::{ElementState, WindowEvent},
+    event_loop::{ActiveEventLoop, EventLoop},
+    keyboard::{Key, NamedKey},
+    window::{Window, WindowId},
+};
+
+use rootvg::color::{PackedSrgb, RGBA8};
+use rootvg::math::{PhysicalSizeI32, Point, ScaleFactor, Size};
+use rootvg::mesh::MeshPrimitive;
+use rootvg::msaa::Antialiasing;
+use rootvg::quad::{SolidQuad, SolidQuadPrimitive};
+use rootvg::tessellation::{path::Path, stroke::Stroke, Tessellator};
+use rootvg::text::{Metrics, RcTextBuffer, TextPrimitive, TextProperties};
+use rootvg::{surface::DefaultSurface, Canvas};
+
+const WINDOW_SIZE: (f32, f32) = (400.0, 300.0);
+
+/// The antialiasing modes that are toggled between by pressing space.
+const MODES: [Antialiasing; 2] = [Antialiasing::MSAAx4, Antialiasing::Fxaa];
+
+fn main() {
+    // Set up logging stuff
+    let env = env_logger::Env::default().filter_or("LOG_LEVEL", "info");
+    env_logger::init_from_env(env);
+
+    let event_loop = EventLoop::new().unwrap();
+    event_loop
+        .run_app(&mut FxaaApp {
+            state: None,
+            font_system: FontSystem::new(),
+            svg_icon_system: SvgIconSystem::default(),
+        })
+        .unwrap();
+}
+
+struct State {
+    window: Arc<Window>,
+    physical_size: PhysicalSizeI32,
+    scale_factor: ScaleFactor,
+    surface: DefaultSurface<'static>,
+    canvas: Canvas,
+    clear_color: PackedSrgb,
+    mode_index: usize,
+    star_meshes: Vec<MeshPrimitive>,
+    quad: SolidQuadPrimitive,
+    text: TextPrimitive,
+}
+
+impl State {
+    fn set_mode(&mut self, mode_index: usize) {
+        self.mode_index = mode_index;
+
+        // Fall back to a mode that the surface supports.
+        let antialiasing = self.surface.compatible_aa(Some(MODES[mode_index]));
+
+        self.canvas
+            .set_antialiasing(antialiasing, &self.surface.device, self.surface.format());
+
+        self.window.set_title(&format!(
+            "RootVG FXAA Demo - {:?} (press space to toggle)",
+            antialiasing
+        ));
+    }
+}
+
+struct FxaaApp {
+    state: Option<State>,
+    font_system: FontSystem,
+    svg_icon_system: SvgIconSystem,
+}
+
+impl FxaaApp {
+    fn create_window(&mut self, event_loop: &ActiveEventLoop) {
+        let window_attributes = Window::default_attributes()
+            .with_inner_size(winit::dpi::LogicalSize::new(
+                WINDOW_SIZE.0 as f64,
+                WINDOW_SIZE.1 as f64,
+            ))
+            .with_title("RootVG FXAA Demo");
+        let window = Arc::new(event_loop.create_window(window_attributes).unwrap());
+
+        let physical_size = window.inner_size();
+        let physical_size =
+            PhysicalSizeI32::new(physical_size.width as i32, physical_size.height as i32);
+        let scale_factor: ScaleFactor = window.scale_factor().into();
+
+        let surface = rootvg::surface::DefaultSurface::new(
+            physical_size,
+            scale_factor,
+            Arc::clone(&window),
+            rootvg::surface::DefaultSurfaceConfig {
+                antialiasing: Some(MODES[0]),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let canvas = rootvg::Canvas::new(
+            &surface.device,
+            &surface.queue,
+            surface.format(),
+            surface.canvas_config(),
+            &mut self.font_system,
+        );
+
+        // Quads and text are already antialiased, so they should look the
+        // same in both modes.
+        let quad = SolidQuad::builder(Size::new(120.0, 60.0))
+            .position(Point::new(250.0, 200.0))
+            .bg_color(RGBA8::new(50, 100, 200, 255))
+            .border_color(RGBA8::new(200, 200, 200, 255))
+            .border_width(2.0)
+            .border_radius(12.0)
+            .build();
+
+        let text = TextPrimitive::new(
+            RcTextBuffer::new(
+                "Crisp text",
+                TextProperties {
+                    metrics: Metrics {
+                        font_size: 24.0,
+                        line_height: 30.0,
+                    },
+                    ..Default::default()
+                },
+                None,
+                None,
+                false,
+                &mut self.font_system,
+            ),
+            Point::new(30.0, 230.0),
+            RGBA8::new(255, 255, 255, 255),
+            None,
+        );
+
+        let mut state = State {
+            window,
+            physical_size,
+            scale_factor,
+            surface,
+            canvas,
+            clear_color: RGBA8::new(15, 15, 15, 255).into(),
+            mode_index: 0,
+            star_meshes: star_meshes(),
+            quad,
+            text,
+        };
+        state.set_mode(0);
+
+        self.state = Some(state);
+    }
+}
+
+/// A star made of thin lines, which shows the aliasing of mesh edges.
+fn star_meshes() -> Vec<MeshPrimitive> {
+    let center = Point::new(WINDOW_SIZE.0 * 0.5, 100.0);
+    let num_points = 24;
+
+    let mut tessellator = Tessellator::new();
+    for i in 0..num_points {
+        let angle = i as f32 * std::f32::consts::TAU / num_points as f32;
+        let end = Point::new(center.x + angle.cos() * 80.0, center.y + angle.sin() * 80.0);
+
+        tessellator = tessellator.stroke(
+            &Path::line(center, end),
+            Stroke {
+                style: RGBA8::new(255, 200, 0, 255).into(),
+                width: 1.5,
+                ..Default::default()
+            },
+        );
+    }
+
+    tessellator.into_primitive_batch()
+}
+
+impl ApplicationHandler for FxaaApp {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.state.is_none() {
+            self.create_window(event_loop);
+        }
+    }
+
+    fn window_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        _window_id: WindowId,
+        event: WindowEvent,
+    ) {
+        let Some(state) = &mut self.state else {
+            return;
+        };
+
+        match event {
+            WindowEvent::RedrawRequested => {
+                {
+                    let mut cx = state.canvas.begin(state.physical_size, state.scale_factor);
+
+                    cx.add_batch(state.star_meshes.iter().cloned());
+                    cx.add(state.quad);
+                    cx.add(state.text.clone());
+                }
+
+                let frame = state.surface.get_current_texture().unwrap();
+                let view = frame
+                    .texture
+                    .create_view(&wgpu::TextureViewDescriptor::default());
+                let mut encoder = state
+                    .surface
+                    .device
+                    .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+                state
+                    .canvas
+                    .render_to_target(
+                        Some(state.clear_color),
+                        &state.surface.device,
+                        &state.surface.queue,
+                        &mut encoder,
+                        &view,
+                        state.physical_size,
+                        &mut self.font_system,
+                        &mut self.svg_icon_system,
+                    )
+                    .unwrap();
+
+                state.window.pre_present_notify();
+
+                state.surface.queue.submit(Some(encoder.finish()));
+                frame.present();
+            }
+            WindowEvent::KeyboardInput { event, .. } => {
+                if event.state == ElementState::Pressed
+                    && !event.repeat
+                    && event.logical_key == Key::Named(NamedKey::Space)
+                {
+                    state.set_mode((state.mode_index + 1) % MODES.len());
+                    state.window.request_redraw();
+                }
+            }
+            WindowEvent::Resized(new_size) => {
+                state.physical_size =
+                    PhysicalSizeI32::new(new_size.width as i32, new_size.height as i32);
+                state
+                    .surface
+                    .resize(state.physical_size, state.scale_factor);
+                state.window.request_redraw();
+            }
+            WindowEvent::ScaleFactorChanged {
+                scale_factor: new_scale,
+                inner_size_writer: _,
+            } => {
+                state.scale_factor = new_scale.into();
+                state
+                    .surface
+                    .resize(state.physical_size, state.scale_factor);
+                state.window.request_redraw();
+            }
+            WindowEvent::CloseRequested => event_loop.exit(),
+            _ => {}
+        }
+    }
+
+    fn exiting(&mut self, _event_loop: &ActiveEventLoop) {
+        // Be sure to drop the wgpu surface before the window closes,
+        // or else the program might segfault.
+        self.state = None;
+    }
+}
@@ -0,0 +1,66 @@
+use rootvg::color::RGBA8;
+use rootvg::gradient::LinearGradient;
+use rootvg::headless::{HeadlessConfig, HeadlessContext};
+use rootvg::math::{radians, PhysicalSizeI32, Point, ScaleFactor, Size};
+use rootvg::quad::{GradientQuad, QuadFlags};
+use rootvg::Canvas;
+use rootvg_text::{glyphon::FontSystem, svg::SvgIconSystem};
+
+const PHYSICAL_SIZE: PhysicalSizeI32 = PhysicalSizeI32::new(800, 220);
+
+fn main() {
+    // Set up logging stuff
+    let env = env_logger::Env::default().filter_or("LOG_LEVEL", "info");
+    env_logger::init_from_env(env);
+
+    let mut font_system = FontSystem::new();
+    let mut svg_icon_system = SvgIconSystem::default();
+
+    let context = HeadlessContext::new(HeadlessConfig::default()).unwrap();
+
+    let mut canvas = Canvas::new(
+        &context.device,
+        &context.queue,
+        context.format(),
+        context.canvas_config(),
+        &mut font_system,
+    );
+
+    // A long gradient between two dark colors only spans a few 8 bit steps,
+    // so each step becomes a wide visible band unless it is dithered.
+    let gradient = LinearGradient::new(radians(std::f32::consts::FRAC_PI_2))
+        .add_stop(0.0, RGBA8::new(10, 10, 14, 255))
+        .add_stop(1.0, RGBA8::new(28, 30, 40, 255));
+
+    {
+        let mut cx = canvas.begin(PHYSICAL_SIZE, ScaleFactor::new(1.0));
+
+        // Dithered (the default) on top, and without dithering below.
+        for (y, flags) in [
+            (20.0, QuadFlags::empty()),
+            (120.0, QuadFlags::DISABLE_DITHERING),
+        ] {
+            cx.add(
+                GradientQuad::builder(Size::new(760.0, 80.0))
+                    .position(Point::new(20.0, y))
+                    .bg_gradient(gradient)
+                    .flags(flags)
+                    .build(),
+            );
+        }
+    }
+
+    let image = context
+        .render_to_image(
+            &mut canvas,
+            PHYSICAL_SIZE,
+            Some(RGBA8::new(0, 0, 0, 255).into()),
+            &mut font_system,
+            &mut svg_icon_system,
+        )
+        .unwrap();
+
+    image.save("gradient_dither.png").unwrap();
+
+    log::info!("saved render to gradient_dither.png");
+}
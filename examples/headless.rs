@@ -0,0 +1,74 @@
+use rootvg::color::RGBA8;
+use rootvg::headless::{HeadlessConfig, HeadlessContext};
+use rootvg::math::{PhysicalSizeI32, Point, ScaleFactor, Size};
+use rootvg::quad::SolidQuad;
+use rootvg::Canvas;
+use rootvg_text::{glyphon::FontSystem, svg::SvgIconSystem};
+
+const PHYSICAL_SIZE: PhysicalSizeI32 = PhysicalSizeI32::new(300, 200);
+
+fn main() {
+    // Set up logging stuff
+    let env = env_logger::Env::default().filter_or("LOG_LEVEL", "info");
+    env_logger::init_from_env(env);
+
+    let mut font_system = FontSystem::new();
+    let mut svg_icon_system = SvgIconSystem::default();
+
+    let context = HeadlessContext::new(HeadlessConfig::default()).unwrap();
+
+    let mut canvas = Canvas::new(
+        &context.device,
+        &context.queue,
+        context.format(),
+        context.canvas_config(),
+        &mut font_system,
+    );
+
+    {
+        let mut cx = canvas.begin(PHYSICAL_SIZE, ScaleFactor::new(1.0));
+
+        cx.add(
+            SolidQuad::builder(Size::new(200.0, 100.0))
+                .position(Point::new(50.0, 50.0))
+                .bg_color(RGBA8::new(50, 100, 200, 255))
+                .border_color(RGBA8::new(200, 200, 200, 255))
+                .border_width(2.0)
+                .border_radius(12.0)
+                .build(),
+        );
+    }
+
+    let image = context
+        .render_to_image(
+            &mut canvas,
+            PHYSICAL_SIZE,
+            Some(RGBA8::new(15, 15, 15, 255).into()),
+            &mut font_system,
+            &mut svg_icon_system,
+        )
+        .unwrap();
+
+    image.save("headless.png").unwrap();
+
+    log::info!("saved render to headless.png");
+
+    // Render the same primitives again with antialiasing disabled, without
+    // recreating the canvas.
+    canvas.set_antialiasing(None, &context.device, context.format());
+    assert_eq!(canvas.config().multisample.count, 1);
+
+    let image = context
+        .render_to_image(
+            &mut canvas,
+            PHYSICAL_SIZE,
+            Some(RGBA8::new(15, 15, 15, 255).into()),
+            &mut font_system,
+            &mut svg_icon_system,
+        )
+        .unwrap();
+
+    image.save("headless_no_aa.png").unwrap();
+
+    log::info!("saved render without antialiasing to headless_no_aa.png");
+}
@@ -0,0 +1,94 @@
+use rootvg::color::RGBA8;
+use rootvg::gradient::LinearGradient;
+use rootvg::headless::{HeadlessConfig, HeadlessContext};
+use rootvg::math::{radians, PhysicalSizeI32, Point, ScaleFactor, Size};
+use rootvg::quad::{GradientQuad, SolidQuad};
+use rootvg::Canvas;
+use rootvg_text::{glyphon::FontSystem, svg::SvgIconSystem};
+
+const PHYSICAL_SIZE: PhysicalSizeI32 = PhysicalSizeI32::new(480, 160);
+
+const KNOB_SIZE: f32 = 48.0;
+const DOT_SIZE: f32 = 6.0;
+
+fn main() {
+    // Set up logging stuff
+    let env = env_logger::Env::default().filter_or("LOG_LEVEL", "info");
+    env_logger::init_from_env(env);
+
+    let mut font_system = FontSystem::new();
+    let mut svg_icon_system = SvgIconSystem::default();
+
+    let context = HeadlessContext::new(HeadlessConfig::default()).unwrap();
+
+    let mut canvas = Canvas::new(
+        &context.device,
+        &context.queue,
+        context.format(),
+        context.canvas_config(),
+        &mut font_system,
+    );
+
+    {
+        let mut cx = canvas.begin(PHYSICAL_SIZE, ScaleFactor::new(1.0));
+
+        // A row of knobs, each turned a bit further than the last one.
+        for i in 0..6 {
+            let center = Point::new(50.0 + i as f32 * 76.0, 56.0);
+            let value = i as f32 / 5.0;
+
+            cx.add(
+                GradientQuad::builder(Size::new(KNOB_SIZE, KNOB_SIZE))
+                    .position(center - Size::new(KNOB_SIZE, KNOB_SIZE).to_vector() * 0.5)
+                    .bg_gradient(
+                        LinearGradient::new(radians(std::f32::consts::PI))
+                            .add_stop(0.0, RGBA8::new(90, 90, 100, 255))
+                            .add_stop(1.0, RGBA8::new(40, 40, 48, 255)),
+                    )
+                    .border_color(RGBA8::new(20, 20, 24, 255))
+                    .border_width(2.0)
+                    .ellipse()
+                    .build(),
+            );
+
+            // The indicator dot sweeps from the bottom left to the bottom
+            // right as the value goes from 0 to 1.
+            let angle = (0.75 + value * 1.5) * std::f32::consts::PI;
+            let dot_center = center
+                + rootvg::math::Vector::new(angle.cos(), angle.sin()) * (KNOB_SIZE * 0.5 - 9.0);
+
+            cx.add(
+                SolidQuad::builder(Size::new(DOT_SIZE, DOT_SIZE))
+                    .position(dot_center - Size::new(DOT_SIZE, DOT_SIZE).to_vector() * 0.5)
+                    .bg_color(RGBA8::new(240, 170, 60, 255))
+                    .ellipse()
+                    .build(),
+            );
+        }
+
+        // Ellipses don't need to be round.
+        cx.add(
+            SolidQuad::builder(Size::new(440.0, 16.0))
+                .position(Point::new(20.0, 124.0))
+                .bg_color(RGBA8::new(60, 120, 200, 255))
+                .border_color(RGBA8::new(200, 200, 200, 255))
+                .border_width(1.5)
+                .ellipse()
+                .build(),
+        );
+    }
+
+    let image = context
+        .render_to_image(
+            &mut canvas,
+            PHYSICAL_SIZE,
+            Some(RGBA8::new(15, 15, 15, 255).into()),
+            &mut font_system,
+            &mut svg_icon_system,
+        )
+        .unwrap();
+
+    image.save("knobs.png").unwrap();
+
+    log::info!("saved render to knobs.png");
+}
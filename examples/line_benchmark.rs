@@ -0,0 +1,131 @@
+//! Compares drawing thousands of lines with the line pipeline against
+//! tessellating the same lines into stroked meshes.
+//!
+//! Run with `cargo run --release --example line_benchmark --features headless`.
+
+use std::time::{Duration, Instant};
+
+use rootvg::color::RGBA8;
+use rootvg::headless::{HeadlessConfig, HeadlessContext};
+use rootvg::math::{PhysicalSizeI32, Point, ScaleFactor};
+use rootvg::quad::{Line, LineCap};
+use rootvg::tessellation::{path::Path, stroke, Tessellator};
+use rootvg::Canvas;
+use rootvg_text::{glyphon::FontSystem, svg::SvgIconSystem};
+
+const PHYSICAL_SIZE: PhysicalSizeI32 = PhysicalSizeI32::new(1024, 768);
+
+const NUM_LINES: usize = 5000;
+const NUM_FRAMES: u32 = 60;
+
+const LINE_WIDTH: f32 = 1.5;
+const LINE_COLOR: RGBA8 = RGBA8::new(120, 180, 240, 255);
+
+fn main() {
+    // Set up logging stuff
+    let env = env_logger::Env::default().filter_or("LOG_LEVEL", "info");
+    env_logger::init_from_env(env);
+
+    let mut font_system = FontSystem::new();
+    let mut svg_icon_system = SvgIconSystem::default();
+
+    let context = HeadlessContext::new(HeadlessConfig::default()).unwrap();
+
+    let mut canvas = Canvas::new(
+        &context.device,
+        &context.queue,
+        context.format(),
+        context.canvas_config(),
+        &mut font_system,
+    );
+
+    let mut render_frames = |name: &str, mut draw: Box<dyn FnMut(&mut Canvas, u32)>| {
+        let mut total = Duration::ZERO;
+
+        for frame in 0..NUM_FRAMES {
+            let start = Instant::now();
+
+            draw(&mut canvas, frame);
+
+            let image = context
+                .render_to_image(
+                    &mut canvas,
+                    PHYSICAL_SIZE,
+                    Some(RGBA8::new(15, 15, 15, 255).into()),
+                    &mut font_system,
+                    &mut svg_icon_system,
+                )
+                .unwrap();
+
+            total += start.elapsed();
+
+            if frame == NUM_FRAMES - 1 {
+                image.save(format!("line_benchmark_{name}.png")).unwrap();
+            }
+        }
+
+        log::info!(
+            "{name}: {:.2} ms per frame ({NUM_LINES} lines, {NUM_FRAMES} frames)",
+            total.as_secs_f64() * 1000.0 / f64::from(NUM_FRAMES)
+        );
+    };
+
+    // The line pipeline only uploads the end points of each line.
+    render_frames(
+        "lines",
+        Box::new(|canvas, frame| {
+            let mut cx = canvas.begin(PHYSICAL_SIZE, ScaleFactor::new(1.0));
+
+            for (from, to) in line_end_points(frame) {
+                cx.add(
+                    Line::builder(from, to)
+                        .width(LINE_WIDTH)
+                        .color(LINE_COLOR)
+                        .cap(LineCap::Round)
+                        .build(),
+                );
+            }
+        }),
+    );
+
+    // The mesh path has to tessellate every line again whenever it moves.
+    render_frames(
+        "meshes",
+        Box::new(|canvas, frame| {
+            let mut tessellator = Tessellator::new();
+            for (from, to) in line_end_points(frame) {
+                tessellator = tessellator.stroke(
+                    &Path::line(from, to),
+                    stroke::Stroke::default()
+                        .with_color(LINE_COLOR.into())
+                        .with_width(LINE_WIDTH)
+                        .with_line_cap(stroke::LineCap::Round),
+                );
+            }
+            let meshes = tessellator.into_primitive_batch();
+
+            let mut cx = canvas.begin(PHYSICAL_SIZE, ScaleFactor::new(1.0));
+            cx.add_batch(meshes);
+        }),
+    );
+}
+
+/// The end points of the lines in the given frame, moving a bit every frame
+/// like the connectors of a node graph that is being dragged.
+fn line_end_points(frame: u32) -> impl Iterator<Item = (Point, Point)> {
+    let shift = frame as f32;
+
+    (0..NUM_LINES).map(move |i| {
+        let i = i as f32;
+        let from = Point::new(
+            (i * 37.0 + shift) % PHYSICAL_SIZE.width as f32,
+            (i * 17.0) % PHYSICAL_SIZE.height as f32,
+        );
+        let to = Point::new(
+            (i * 53.0) % PHYSICAL_SIZE.width as f32,
+            (i * 29.0 + shift) % PHYSICAL_SIZE.height as f32,
+        );
+
+        (from, to)
+    })
+}
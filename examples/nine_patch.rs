@@ -0,0 +1,228 @@
+use rootvg_text::{glyphon::FontSystem, svg::SvgIconSystem};
+use std::sync::Arc;
+use winit::{
+    application::ApplicationHandler,
+    event::WindowEvent,
+    event_loop::{ActiveEventLoop, EventLoop},
+    window::{Window, WindowId},
+};
+
+use rootvg::color::{PackedSrgb, RGBA8};
+use rootvg::image::{ImagePrimitive, NinePatch, RcTexture};
+use rootvg::math::{PhysicalSizeI32, Point, ScaleFactor, SideOffsets, Size};
+use rootvg::{surface::DefaultSurface, Canvas};
+
+const WINDOW_SIZE: (f32, f32) = (500.0, 400.0);
+
+/// The size of the button texture in pixels.
+const TEXTURE_SIZE: u32 = 24;
+/// The size of the fixed corners of the button texture in pixels.
+const CORNER_SIZE: f32 = 8.0;
+
+fn main() {
+    // Set up logging stuff
+    let env = env_logger::Env::default().filter_or("LOG_LEVEL", "info");
+    env_logger::init_from_env(env);
+
+    let event_loop = EventLoop::new().unwrap();
+    event_loop
+        .run_app(&mut NinePatchApp {
+            state: None,
+            font_system: FontSystem::new(),
+            svg_icon_system: SvgIconSystem::default(),
+        })
+        .unwrap();
+}
+
+struct State {
+    window: Arc<Window>,
+    physical_size: PhysicalSizeI32,
+    scale_factor: ScaleFactor,
+    surface: DefaultSurface<'static>,
+    canvas: Canvas,
+    clear_color: PackedSrgb,
+    button_texture: RcTexture,
+    frame: u64,
+}
+
+struct NinePatchApp {
+    state: Option<State>,
+    font_system: FontSystem,
+    svg_icon_system: SvgIconSystem,
+}
+
+impl NinePatchApp {
+    fn create_window(&mut self, event_loop: &ActiveEventLoop) {
+        let window_attributes = Window::default_attributes()
+            .with_inner_size(winit::dpi::LogicalSize::new(
+                WINDOW_SIZE.0 as f64,
+                WINDOW_SIZE.1 as f64,
+            ))
+            .with_title("RootVG Nine-Patch Demo");
+        let window = Arc::new(event_loop.create_window(window_attributes).unwrap());
+
+        let physical_size = window.inner_size();
+        let physical_size =
+            PhysicalSizeI32::new(physical_size.width as i32, physical_size.height as i32);
+        let scale_factor: ScaleFactor = window.scale_factor().into();
+
+        let surface = rootvg::surface::DefaultSurface::new(
+            physical_size,
+            scale_factor,
+            Arc::clone(&window),
+            rootvg::surface::DefaultSurfaceConfig {
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let canvas = rootvg::Canvas::new(
+            &surface.device,
+            &surface.queue,
+            surface.format(),
+            surface.canvas_config(),
+            &mut self.font_system,
+        );
+
+        self.state = Some(State {
+            window,
+            physical_size,
+            scale_factor,
+            surface,
+            canvas,
+            clear_color: RGBA8::new(15, 15, 15, 255).into(),
+            button_texture: RcTexture::new(button_image()),
+            frame: 0,
+        });
+    }
+}
+
+impl ApplicationHandler for NinePatchApp {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.state.is_none() {
+            self.create_window(event_loop);
+        }
+    }
+
+    fn window_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        _window_id: WindowId,
+        event: WindowEvent,
+    ) {
+        let Some(state) = &mut self.state else {
+            return;
+        };
+
+        match event {
+            WindowEvent::RedrawRequested => {
+                {
+                    let mut cx = state.canvas.begin(state.physical_size, state.scale_factor);
+
+                    // Grow and shrink the buttons over time. The corners of the
+                    // texture keep their size while the edges and center stretch.
+                    let t = (state.frame as f32 * 0.02).sin() * 0.5 + 0.5;
+
+                    let sizes = [
+                        Size::new(16.0 + 300.0 * t, 40.0),
+                        Size::new(120.0, 16.0 + 120.0 * t),
+                        Size::new(16.0 + 200.0 * t, 16.0 + 80.0 * t),
+                    ];
+
+                    let mut y = 30.0;
+                    for size in sizes {
+                        cx.add(
+                            ImagePrimitive::builder(state.button_texture.clone())
+                                .position(Point::new(30.0, y))
+                                .size(size)
+                                .nine_patch(NinePatch::new(SideOffsets::new_all_same(CORNER_SIZE)))
+                                .build(),
+                        );
+
+                        y += size.height + 20.0;
+                    }
+                }
+
+                let frame = state.surface.get_current_texture().unwrap();
+                let view = frame
+                    .texture
+                    .create_view(&wgpu::TextureViewDescriptor::default());
+                let mut encoder = state
+                    .surface
+                    .device
+                    .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+                state
+                    .canvas
+                    .render_to_target(
+                        Some(state.clear_color),
+                        &state.surface.device,
+                        &state.surface.queue,
+                        &mut encoder,
+                        &view,
+                        state.physical_size,
+                        &mut self.font_system,
+                        &mut self.svg_icon_system,
+                    )
+                    .unwrap();
+
+                state.window.pre_present_notify();
+
+                state.surface.queue.submit(Some(encoder.finish()));
+                frame.present();
+
+                state.frame += 1;
+                state.window.request_redraw();
+            }
+            WindowEvent::Resized(new_size) => {
+                state.physical_size =
+                    PhysicalSizeI32::new(new_size.width as i32, new_size.height as i32);
+                state
+                    .surface
+                    .resize(state.physical_size, state.scale_factor);
+                state.window.request_redraw();
+            }
+            WindowEvent::ScaleFactorChanged {
+                scale_factor: new_scale,
+                inner_size_writer: _,
+            } => {
+                state.scale_factor = new_scale.into();
+                state
+                    .surface
+                    .resize(state.physical_size, state.scale_factor);
+                state.window.request_redraw();
+            }
+            WindowEvent::CloseRequested => event_loop.exit(),
+            _ => {}
+        }
+    }
+
+    fn exiting(&mut self, _event_loop: &ActiveEventLoop) {
+        // Be sure to drop the wgpu surface before the window closes,
+        // or else the program might segfault.
+        self.state = None;
+    }
+}
+
+/// Generate a simple rounded button background with a light border.
+fn button_image() -> image::RgbaImage {
+    let center = TEXTURE_SIZE as f32 * 0.5;
+    let half_size = center - 0.5;
+    let radius = CORNER_SIZE - 1.0;
+
+    image::RgbaImage::from_fn(TEXTURE_SIZE, TEXTURE_SIZE, |x, y| {
+        // Signed distance to the edge of a rounded rectangle.
+        let qx = (x as f32 + 0.5 - center).abs() - half_size + radius;
+        let qy = (y as f32 + 0.5 - center).abs() - half_size + radius;
+        let dist =
+            qx.max(qy).min(0.0) + (qx.max(0.0).powi(2) + qy.max(0.0).powi(2)).sqrt() - radius;
+
+        let alpha = (0.5 - dist).clamp(0.0, 1.0);
+
+        if dist > -2.0 {
+            image::Rgba([200, 200, 200, (alpha * 255.0) as u8])
+        } else {
+            image::Rgba([50, 100, 200, 255])
+        }
+    })
+}
@@ -0,0 +1,117 @@
+//! Draws the axes of a plot with a vertical Y-axis label and slanted X-axis
+//! labels.
+//!
+//! Run with `cargo run --example plot_axis_labels --features headless`.
+
+use rootvg::color::RGBA8;
+use rootvg::headless::{HeadlessConfig, HeadlessContext};
+use rootvg::math::{degrees, PhysicalSizeI32, Point, ScaleFactor, Size};
+use rootvg::quad::SolidQuad;
+use rootvg::text::{Metrics, RcTextBuffer, TextPrimitive, TextProperties};
+use rootvg::Canvas;
+use rootvg_text::{glyphon::FontSystem, svg::SvgIconSystem};
+
+const PHYSICAL_SIZE: PhysicalSizeI32 = PhysicalSizeI32::new(480, 360);
+
+const PLOT_ORIGIN: Point = Point::new(80.0, 300.0);
+const PLOT_SIZE: Size = Size::new(360.0, 260.0);
+
+const AXIS_COLOR: RGBA8 = RGBA8::new(200, 200, 200, 255);
+
+fn main() {
+    // Set up logging stuff
+    let env = env_logger::Env::default().filter_or("LOG_LEVEL", "info");
+    env_logger::init_from_env(env);
+
+    let mut font_system = FontSystem::new();
+    let mut svg_icon_system = SvgIconSystem::default();
+
+    let context = HeadlessContext::new(HeadlessConfig::default()).unwrap();
+
+    let mut canvas = Canvas::new(
+        &context.device,
+        &context.queue,
+        context.format(),
+        context.canvas_config(),
+        &mut font_system,
+    );
+
+    let props = TextProperties {
+        metrics: Metrics {
+            font_size: 14.0,
+            line_height: 18.0,
+        },
+        ..Default::default()
+    };
+
+    // The label is rotated a quarter turn counter-clockwise about its
+    // bottom-left corner, so that it reads from bottom to top. Quarter turns
+    // are snapped to whole pixels, so it is as crisp as unrotated text.
+    let y_label_buffer = RcTextBuffer::new("Gain (dB)", props, None, None, false, &mut font_system);
+    let y_label_size = y_label_buffer.measure();
+    let y_label = TextPrimitive::new(
+        y_label_buffer,
+        Point::new(
+            30.0,
+            PLOT_ORIGIN.y - (PLOT_SIZE.height - y_label_size.width) * 0.5 - y_label_size.height,
+        ),
+        AXIS_COLOR,
+        None,
+    )
+    .with_rotation(degrees(-90.0), Point::new(0.0, y_label_size.height));
+
+    // Labels at other angles are filtered, which softens them slightly.
+    let x_labels: Vec<TextPrimitive> = ["20 Hz", "200 Hz", "2 kHz", "20 kHz"]
+        .iter()
+        .enumerate()
+        .map(|(i, text)| {
+            let buffer = RcTextBuffer::new(text, props, None, None, false, &mut font_system);
+            let width = buffer.measure().width;
+            let x = PLOT_ORIGIN.x + i as f32 * PLOT_SIZE.width / 3.0;
+
+            // Rotate about the end of the label, so that it ends at the tick.
+            TextPrimitive::new(
+                buffer,
+                Point::new(x - width, PLOT_ORIGIN.y + 6.0),
+                AXIS_COLOR,
+                None,
+            )
+            .with_rotation(degrees(-30.0), Point::new(width, 0.0))
+        })
+        .collect();
+
+    {
+        let mut cx = canvas.begin(PHYSICAL_SIZE, ScaleFactor::new(1.0));
+
+        // Axes
+        cx.add(
+            SolidQuad::builder(Size::new(1.0, PLOT_SIZE.height))
+                .position(Point::new(PLOT_ORIGIN.x, PLOT_ORIGIN.y - PLOT_SIZE.height))
+                .bg_color(AXIS_COLOR)
+                .build(),
+        );
+        cx.add(
+            SolidQuad::builder(Size::new(PLOT_SIZE.width, 1.0))
+                .position(PLOT_ORIGIN)
+                .bg_color(AXIS_COLOR)
+                .build(),
+        );
+
+        cx.add(y_label);
+        cx.add_batch(x_labels);
+    }
+
+    let image = context
+        .render_to_image(
+            &mut canvas,
+            PHYSICAL_SIZE,
+            Some(RGBA8::new(15, 15, 15, 255).into()),
+            &mut font_system,
+            &mut svg_icon_system,
+        )
+        .unwrap();
+
+    image.save("plot_axis_labels.png").unwrap();
+
+    log::info!("saved render to plot_axis_labels.png");
+}
@@ -0,0 +1,79 @@
+use rootvg::color::RGBA8;
+use rootvg::headless::{HeadlessConfig, HeadlessContext};
+use rootvg::math::{PhysicalSizeI32, Point, ScaleFactor};
+use rootvg::text::{Color, Metrics, RcTextBuffer, TextPrimitive, TextProperties, Weight};
+use rootvg::Canvas;
+use rootvg_text::{glyphon::FontSystem, svg::SvgIconSystem};
+
+const PHYSICAL_SIZE: PhysicalSizeI32 = PhysicalSizeI32::new(400, 100);
+
+fn main() {
+    // Set up logging stuff
+    let env = env_logger::Env::default().filter_or("LOG_LEVEL", "info");
+    env_logger::init_from_env(env);
+
+    let mut font_system = FontSystem::new();
+    let mut svg_icon_system = SvgIconSystem::default();
+
+    let context = HeadlessContext::new(HeadlessConfig::default()).unwrap();
+
+    let mut canvas = Canvas::new(
+        &context.device,
+        &context.queue,
+        context.format(),
+        context.canvas_config(),
+        &mut font_system,
+    );
+
+    let props = TextProperties {
+        metrics: Metrics {
+            font_size: 24.0,
+            line_height: 32.0,
+        },
+        ..Default::default()
+    };
+
+    // The first and last spans don't set a color, so they use the color of
+    // the text primitive.
+    let keyword_attrs = props
+        .attrs
+        .color(Color::rgb(230, 120, 60))
+        .weight(Weight::BOLD);
+    let buffer = RcTextBuffer::new_rich(
+        [
+            ("Some words are ", props.attrs),
+            ("highlighted", keyword_attrs),
+            (" in orange.", props.attrs),
+        ],
+        props,
+        None,
+        None,
+        false,
+        &mut font_system,
+    );
+
+    {
+        let mut cx = canvas.begin(PHYSICAL_SIZE, ScaleFactor::new(1.0));
+
+        cx.add(TextPrimitive::new(
+            buffer,
+            Point::new(20.0, 30.0),
+            RGBA8::new(230, 230, 230, 255),
+            None,
+        ));
+    }
+
+    let image = context
+        .render_to_image(
+            &mut canvas,
+            PHYSICAL_SIZE,
+            Some(RGBA8::new(15, 15, 15, 255).into()),
+            &mut font_system,
+            &mut svg_icon_system,
+        )
+        .unwrap();
+
+    image.save("rich_text.png").unwrap();
+
+    log::info!("saved render to rich_text.png");
+}
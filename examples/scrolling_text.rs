@@ -0,0 +1,249 @@
+use rootvg_text::{glyphon::FontSystem, svg::SvgIconSystem};
+use std::sync::Arc;
+use winit::{
+    application::ApplicationHandler,
+    event::WindowEvent,
+    event_loop::{ActiveEventLoop, EventLoop},
+    window::{Window, WindowId},
+};
+
+use rootvg::color::{PackedSrgb, RGBA8};
+use rootvg::math::{PhysicalSizeI32, Point, ScaleFactor};
+use rootvg::text::{Metrics, RcTextBuffer, TextPrimitive, TextProperties};
+use rootvg::{surface::DefaultSurface, Canvas};
+
+const WINDOW_SIZE: (f32, f32) = (400.0, 300.0);
+
+/// How many logical points the text moves each frame. This is less than a
+/// pixel, so the difference between snapped and subpixel positioning is easy
+/// to see.
+const SCROLL_SPEED: f32 = 0.2;
+
+fn main() {
+    // Set up logging stuff
+    let env = env_logger::Env::default().filter_or("LOG_LEVEL", "info");
+    env_logger::init_from_env(env);
+
+    let event_loop = EventLoop::new().unwrap();
+    event_loop
+        .run_app(&mut ScrollingTextApp {
+            state: None,
+            font_system: FontSystem::new(),
+            svg_icon_system: SvgIconSystem::default(),
+        })
+        .unwrap();
+}
+
+struct State {
+    window: Arc<Window>,
+    physical_size: PhysicalSizeI32,
+    scale_factor: ScaleFactor,
+    surface: DefaultSurface<'static>,
+    canvas: Canvas,
+    clear_color: PackedSrgb,
+    snapped_text: TextPrimitive,
+    subpixel_text: TextPrimitive,
+    text_width: f32,
+    scroll_offset: f32,
+}
+
+struct ScrollingTextApp {
+    state: Option<State>,
+    font_system: FontSystem,
+    svg_icon_system: SvgIconSystem,
+}
+
+impl ScrollingTextApp {
+    fn create_window(&mut self, event_loop: &ActiveEventLoop) {
+        let window_attributes = Window::default_attributes()
+            .with_inner_size(winit::dpi::LogicalSize::new(
+                WINDOW_SIZE.0 as f64,
+                WINDOW_SIZE.1 as f64,
+            ))
+            .with_title("RootVG Scrolling Text Demo");
+        let window = Arc::new(event_loop.create_window(window_attributes).unwrap());
+
+        let physical_size = window.inner_size();
+        let physical_size =
+            PhysicalSizeI32::new(physical_size.width as i32, physical_size.height as i32);
+        let scale_factor: ScaleFactor = window.scale_factor().into();
+
+        let surface = rootvg::surface::DefaultSurface::new(
+            physical_size,
+            scale_factor,
+            Arc::clone(&window),
+            rootvg::surface::DefaultSurfaceConfig {
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let canvas = rootvg::Canvas::new(
+            &surface.device,
+            &surface.queue,
+            surface.format(),
+            surface.canvas_config(),
+            &mut self.font_system,
+        );
+
+        let props = TextProperties {
+            metrics: Metrics {
+                font_size: 20.0,
+                line_height: 24.0,
+            },
+            ..Default::default()
+        };
+
+        // The same line of text, once snapped to whole pixels (the default)
+        // and once with subpixel positioning. The snapped line moves in
+        // visible steps, while the other line scrolls smoothly.
+        let snapped_buffer = RcTextBuffer::new(
+            "Snapped to whole pixels",
+            props,
+            None,
+            None,
+            false,
+            &mut self.font_system,
+        );
+        let subpixel_buffer = RcTextBuffer::new(
+            "Subpixel positioning",
+            TextProperties {
+                subpixel_positioning: true,
+                ..props
+            },
+            None,
+            None,
+            false,
+            &mut self.font_system,
+        );
+
+        let text_width = snapped_buffer
+            .measure()
+            .width
+            .max(subpixel_buffer.measure().width);
+
+        let snapped_text = TextPrimitive::new(
+            snapped_buffer,
+            Point::new(0.0, 100.0),
+            RGBA8::new(255, 255, 255, 255),
+            None,
+        );
+        let subpixel_text = TextPrimitive::new(
+            subpixel_buffer,
+            Point::new(0.0, 160.0),
+            RGBA8::new(255, 255, 255, 255),
+            None,
+        );
+
+        self.state = Some(State {
+            window,
+            physical_size,
+            scale_factor,
+            surface,
+            canvas,
+            clear_color: RGBA8::new(15, 15, 15, 255).into(),
+            snapped_text,
+            subpixel_text,
+            text_width,
+            scroll_offset: 0.0,
+        });
+    }
+}
+
+impl ApplicationHandler for ScrollingTextApp {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.state.is_none() {
+            self.create_window(event_loop);
+        }
+    }
+
+    fn window_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        _window_id: WindowId,
+        event: WindowEvent,
+    ) {
+        let Some(state) = &mut self.state else {
+            return;
+        };
+
+        match event {
+            WindowEvent::RedrawRequested => {
+                {
+                    let mut cx = state.canvas.begin(state.physical_size, state.scale_factor);
+
+                    // Scroll the text from the right edge of the window to
+                    // the left, and start over once it is out of view.
+                    let x = WINDOW_SIZE.0 - state.scroll_offset;
+
+                    state.snapped_text.pos.x = x;
+                    state.subpixel_text.pos.x = x;
+
+                    cx.add(state.snapped_text.clone());
+                    cx.add(state.subpixel_text.clone());
+                }
+
+                let frame = state.surface.get_current_texture().unwrap();
+                let view = frame
+                    .texture
+                    .create_view(&wgpu::TextureViewDescriptor::default());
+                let mut encoder = state
+                    .surface
+                    .device
+                    .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+                state
+                    .canvas
+                    .render_to_target(
+                        Some(state.clear_color),
+                        &state.surface.device,
+                        &state.surface.queue,
+                        &mut encoder,
+                        &view,
+                        state.physical_size,
+                        &mut self.font_system,
+                        &mut self.svg_icon_system,
+                    )
+                    .unwrap();
+
+                state.window.pre_present_notify();
+
+                state.surface.queue.submit(Some(encoder.finish()));
+                frame.present();
+
+                // Scroll the text on the next frame.
+                state.scroll_offset += SCROLL_SPEED;
+                if state.scroll_offset > WINDOW_SIZE.0 + state.text_width {
+                    state.scroll_offset = 0.0;
+                }
+                state.window.request_redraw();
+            }
+            WindowEvent::Resized(new_size) => {
+                state.physical_size =
+                    PhysicalSizeI32::new(new_size.width as i32, new_size.height as i32);
+                state
+                    .surface
+                    .resize(state.physical_size, state.scale_factor);
+                state.window.request_redraw();
+            }
+            WindowEvent::ScaleFactorChanged {
+                scale_factor: new_scale,
+                inner_size_writer: _,
+            } => {
+                state.scale_factor = new_scale.into();
+                state
+                    .surface
+                    .resize(state.physical_size, state.scale_factor);
+                state.window.request_redraw();
+            }
+            WindowEvent::CloseRequested => event_loop.exit(),
+            _ => {}
+        }
+    }
+
+    fn exiting(&mut self, _event_loop: &ActiveEventLoop) {
+        // Be sure to drop the wgpu surface before the window closes,
+        // or else the program might segfault.
+        self.state = None;
+    }
+}
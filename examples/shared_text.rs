@@ -0,0 +1,84 @@
+use rootvg::color::RGBA8;
+use rootvg::headless::{HeadlessConfig, HeadlessContext};
+use rootvg::math::{PhysicalSizeI32, Point, ScaleFactor};
+use rootvg::text::{Metrics, RcTextBuffer, SharedTextResources, TextPrimitive, TextProperties};
+use rootvg::Canvas;
+use rootvg_text::{glyphon::FontSystem, svg::SvgIconSystem};
+
+const PHYSICAL_SIZE: PhysicalSizeI32 = PhysicalSizeI32::new(300, 60);
+
+fn main() {
+    // Set up logging stuff
+    let env = env_logger::Env::default().filter_or("LOG_LEVEL", "info");
+    env_logger::init_from_env(env);
+
+    // A single font system and glyph atlas are shared by both canvases, like
+    // the canvases of two windows in the same application.
+    let mut font_system = FontSystem::new();
+    let mut svg_icon_system = SvgIconSystem::default();
+
+    let context = HeadlessContext::new(HeadlessConfig::default()).unwrap();
+
+    let text_resources =
+        SharedTextResources::new(&context.device, &context.queue, context.format());
+
+    let mut canvases: Vec<Canvas> = (0..2)
+        .map(|_| {
+            Canvas::with_shared_text_resources(
+                &context.device,
+                &context.queue,
+                context.format(),
+                context.canvas_config(),
+                &mut font_system,
+                text_resources.clone(),
+            )
+        })
+        .collect();
+
+    let props = TextProperties {
+        metrics: Metrics {
+            font_size: 24.0,
+            line_height: 32.0,
+        },
+        ..Default::default()
+    };
+
+    // Both canvases draw the same glyphs, so they are only rasterized once.
+    for (i, canvas) in canvases.iter_mut().enumerate() {
+        let text = TextPrimitive::new(
+            RcTextBuffer::new(
+                &format!("Shared glyphs {}", i + 1),
+                props,
+                None,
+                None,
+                false,
+                &mut font_system,
+            ),
+            Point::new(10.0, 14.0),
+            RGBA8::new(255, 255, 255, 255),
+            None,
+        );
+
+        {
+            let mut cx = canvas.begin(PHYSICAL_SIZE, ScaleFactor::new(1.0));
+            cx.add(text);
+        }
+
+        // Each canvas is submitted before the next one is rendered, as
+        // required by `SharedTextResources`.
+        let image = context
+            .render_to_image(
+                canvas,
+                PHYSICAL_SIZE,
+                Some(RGBA8::new(15, 15, 15, 255).into()),
+                &mut font_system,
+                &mut svg_icon_system,
+            )
+            .unwrap();
+
+        let path = format!("shared_text_{}.png", i + 1);
+        image.save(&path).unwrap();
+
+        log::info!("saved render to {path}");
+    }
+}
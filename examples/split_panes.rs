@@ -0,0 +1,88 @@
+use rootvg::color::RGBA8;
+use rootvg::headless::{HeadlessConfig, HeadlessContext};
+use rootvg::math::{PhysicalSizeI32, Point, PointI32, RectI32, ScaleFactor, Size, SizeI32};
+use rootvg::quad::SolidQuad;
+use rootvg::Canvas;
+use rootvg_text::{glyphon::FontSystem, svg::SvgIconSystem};
+
+const TARGET_SIZE: PhysicalSizeI32 = PhysicalSizeI32::new(300, 200);
+
+fn main() {
+    // Set up logging stuff
+    let env = env_logger::Env::default().filter_or("LOG_LEVEL", "info");
+    env_logger::init_from_env(env);
+
+    let mut font_system = FontSystem::new();
+    let mut svg_icon_system = SvgIconSystem::default();
+
+    let context = HeadlessContext::new(HeadlessConfig::default()).unwrap();
+
+    let mut new_canvas = || {
+        Canvas::new(
+            &context.device,
+            &context.queue,
+            context.format(),
+            context.canvas_config(),
+            &mut font_system,
+        )
+    };
+    let mut left_canvas = new_canvas();
+    let mut right_canvas = new_canvas();
+
+    // The right pane is wider than the space left in the target, so it is
+    // clipped to the right edge of the target.
+    let left_dest = RectI32::new(PointI32::new(0, 0), SizeI32::new(150, 200));
+    let right_dest = RectI32::new(PointI32::new(150, 0), SizeI32::new(170, 200));
+
+    draw_pane(&mut left_canvas, left_dest, RGBA8::new(50, 100, 200, 255));
+    draw_pane(&mut right_canvas, right_dest, RGBA8::new(200, 100, 50, 255));
+
+    let target = context.create_render_target(TARGET_SIZE);
+
+    let mut encoder = context
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+    for (canvas, dest, clear_color) in [
+        (&mut left_canvas, left_dest, RGBA8::new(15, 15, 15, 255)),
+        (&mut right_canvas, right_dest, RGBA8::new(30, 30, 30, 255)),
+    ] {
+        canvas
+            .render_to_target_in_rect(
+                Some(clear_color.into()),
+                &context.device,
+                &context.queue,
+                &mut encoder,
+                &target.view,
+                TARGET_SIZE,
+                dest,
+                &mut font_system,
+                &mut svg_icon_system,
+            )
+            .unwrap();
+    }
+
+    context.queue.submit(Some(encoder.finish()));
+
+    let image = context.read_target(&target).unwrap();
+    image.save("split_panes.png").unwrap();
+
+    log::info!("saved render to split_panes.png");
+}
+
+/// Draw a pane with a rounded quad which touches the edges of the pane, so
+/// that it is easy to see where each pane is clipped.
+fn draw_pane(canvas: &mut Canvas, dest: RectI32, color: RGBA8) {
+    let size = PhysicalSizeI32::new(dest.size.width, dest.size.height);
+    let mut cx = canvas.begin(size, ScaleFactor::new(1.0));
+
+    cx.add(
+        SolidQuad::builder(Size::new(dest.size.width as f32, 100.0))
+            .position(Point::new(0.0, 50.0))
+            .bg_color(color)
+            .border_color(RGBA8::new(200, 200, 200, 255))
+            .border_width(2.0)
+            .border_radius(12.0)
+            .build(),
+    );
+}
@@ -0,0 +1,97 @@
+use rootvg::color::RGBA8;
+use rootvg::headless::{HeadlessConfig, HeadlessContext};
+use rootvg::image::{ImagePrimitive, RcTexture};
+use rootvg::math::{PhysicalSizeI32, Point, Rect, ScaleFactor, Size};
+use rootvg::Canvas;
+use rootvg_text::{glyphon::FontSystem, svg::SvgIconSystem};
+
+const PHYSICAL_SIZE: PhysicalSizeI32 = PhysicalSizeI32::new(240, 120);
+
+/// The size of a single sprite in the sprite sheet in pixels.
+const SPRITE_SIZE: u32 = 16;
+
+fn main() {
+    // Set up logging stuff
+    let env = env_logger::Env::default().filter_or("LOG_LEVEL", "info");
+    env_logger::init_from_env(env);
+
+    let mut font_system = FontSystem::new();
+    let mut svg_icon_system = SvgIconSystem::default();
+
+    let context = HeadlessContext::new(HeadlessConfig::default()).unwrap();
+
+    let mut canvas = Canvas::new(
+        &context.device,
+        &context.queue,
+        context.format(),
+        context.canvas_config(),
+        &mut font_system,
+    );
+
+    // Both sprites live in the same texture, so it is only uploaded once.
+    let sprite_sheet = RcTexture::new(sprite_sheet_image());
+
+    {
+        let mut cx = canvas.begin(PHYSICAL_SIZE, ScaleFactor::new(1.0));
+
+        for i in 0..2 {
+            let source_rect = Rect::new(
+                Point::new((i * SPRITE_SIZE) as f32, 0.0),
+                Size::new(SPRITE_SIZE as f32, SPRITE_SIZE as f32),
+            );
+            let x = 20.0 + i as f32 * 110.0;
+
+            // Once at the size of the sprite, and once scaled up.
+            cx.add(
+                ImagePrimitive::builder(sprite_sheet.clone())
+                    .position(Point::new(x, 20.0))
+                    .source_rect(source_rect)
+                    .build(),
+            );
+            cx.add(
+                ImagePrimitive::builder(sprite_sheet.clone())
+                    .position(Point::new(x + 24.0, 20.0))
+                    .source_rect(source_rect)
+                    .size(Size::new(80.0, 80.0))
+                    .build(),
+            );
+        }
+    }
+
+    let image = context
+        .render_to_image(
+            &mut canvas,
+            PHYSICAL_SIZE,
+            Some(RGBA8::new(15, 15, 15, 255).into()),
+            &mut font_system,
+            &mut svg_icon_system,
+        )
+        .unwrap();
+
+    image.save("sprites.png").unwrap();
+
+    log::info!("saved render to sprites.png");
+}
+
+/// A sprite sheet with two sprites next to each other: a circle on the left
+/// and a diamond on the right.
+fn sprite_sheet_image() -> image::RgbaImage {
+    let center = SPRITE_SIZE as f32 * 0.5;
+
+    image::RgbaImage::from_fn(SPRITE_SIZE * 2, SPRITE_SIZE, |x, y| {
+        let dx = (x % SPRITE_SIZE) as f32 + 0.5 - center;
+        let dy = y as f32 + 0.5 - center;
+
+        let inside = if x < SPRITE_SIZE {
+            dx * dx + dy * dy < (center - 1.0).powi(2)
+        } else {
+            dx.abs() + dy.abs() < center - 1.0
+        };
+
+        match (inside, x < SPRITE_SIZE) {
+            (true, true) => image::Rgba([220, 80, 60, 255]),
+            (true, false) => image::Rgba([60, 180, 220, 255]),
+            (false, _) => image::Rgba([0, 0, 0, 0]),
+        }
+    })
+}
@@ -0,0 +1,116 @@
+use smallvec::smallvec;
+
+use rootvg::color::RGBA8;
+use rootvg::headless::{HeadlessConfig, HeadlessContext};
+use rootvg::math::{PhysicalSizeI32, Point, Rect, ScaleFactor, Size};
+use rootvg::text::TextPrimitive;
+use rootvg::Canvas;
+use rootvg_text::{svg::SvgIconSystem, ContentType, CustomGlyph, FontSystem};
+
+const PHYSICAL_SIZE: PhysicalSizeI32 = PhysicalSizeI32::new(360, 120);
+
+static MASK_ICON: &[u8] = include_bytes!("./music.svg");
+
+static COLOR_ICON: &str = r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 64 64">
+    <circle cx="32" cy="32" r="30" fill="#f5c542"/>
+    <circle cx="22" cy="26" r="5" fill="#3a3a3a"/>
+    <circle cx="42" cy="26" r="5" fill="#3a3a3a"/>
+    <path d="M18 40 Q32 54 46 40" stroke="#c0392b" stroke-width="4" fill="none"/>
+</svg>"##;
+
+fn icon(id: u16, size: f32) -> CustomGlyph {
+    CustomGlyph {
+        id,
+        left: 0.0,
+        top: 0.0,
+        width: size,
+        height: size,
+        // Use the color of the text primitive.
+        color: None,
+        snap_to_physical_pixel: true,
+        metadata: 0,
+    }
+}
+
+fn main() {
+    // Set up logging stuff
+    let env = env_logger::Env::default().filter_or("LOG_LEVEL", "info");
+    env_logger::init_from_env(env);
+
+    let mut font_system = FontSystem::new();
+    let mut svg_icon_system = SvgIconSystem::default();
+
+    // Mask icons only keep the alpha channel, so each instance can be tinted
+    // with a different color. Color icons keep their original colors.
+    svg_icon_system
+        .add_from_bytes(0u16, MASK_ICON, &Default::default(), ContentType::Mask)
+        .unwrap();
+    svg_icon_system
+        .add_from_str(1u16, COLOR_ICON, &Default::default(), ContentType::Color)
+        .unwrap();
+
+    let context = HeadlessContext::new(HeadlessConfig::default()).unwrap();
+
+    let mut canvas = Canvas::new(
+        &context.device,
+        &context.queue,
+        context.format(),
+        context.canvas_config(),
+        &mut font_system,
+    );
+
+    {
+        let mut cx = canvas.begin(PHYSICAL_SIZE, ScaleFactor::new(1.0));
+
+        let bounds = Some(Rect::from_size(Size::new(100.0, 100.0)));
+
+        // The same icon tinted two different colors.
+        cx.add(TextPrimitive::new_with_icons(
+            None,
+            Point::new(20.0, 20.0),
+            RGBA8::new(150, 100, 200, 255),
+            bounds,
+            smallvec![icon(0, 64.0)],
+        ));
+        cx.add(TextPrimitive::new_with_icons(
+            None,
+            Point::new(100.0, 20.0),
+            RGBA8::new(80, 200, 120, 255),
+            bounds,
+            smallvec![icon(0, 64.0)],
+        ));
+
+        // The same icon at a smaller size is rasterized again at that size
+        // instead of being scaled down.
+        cx.add(TextPrimitive::new_with_icons(
+            None,
+            Point::new(180.0, 36.0),
+            RGBA8::new(80, 200, 120, 255),
+            bounds,
+            smallvec![icon(0, 32.0)],
+        ));
+
+        // A full color icon. The color of the primitive is ignored.
+        cx.add(TextPrimitive::new_with_icons(
+            None,
+            Point::new(260.0, 20.0),
+            RGBA8::new(255, 255, 255, 255),
+            bounds,
+            smallvec![icon(1, 64.0)],
+        ));
+    }
+
+    let image = context
+        .render_to_image(
+            &mut canvas,
+            PHYSICAL_SIZE,
+            Some(RGBA8::new(15, 15, 15, 255).into()),
+            &mut font_system,
+            &mut svg_icon_system,
+        )
+        .unwrap();
+
+    image.save("svg_icons.png").unwrap();
+
+    log::info!("saved render to svg_icons.png");
+}
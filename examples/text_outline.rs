@@ -0,0 +1,237 @@
+use rootvg_text::{glyphon::FontSystem, svg::SvgIconSystem};
+use std::sync::Arc;
+use winit::{
+    application::ApplicationHandler,
+    event::WindowEvent,
+    event_loop::{ActiveEventLoop, EventLoop},
+    window::{Window, WindowId},
+};
+
+use rootvg::color::{PackedSrgb, RGBA8};
+use rootvg::math::{PhysicalSizeI32, Point, ScaleFactor, Size, Vector};
+use rootvg::quad::SolidQuad;
+use rootvg::text::{Metrics, RcTextBuffer, TextOutline, TextPrimitive, TextProperties, TextShadow};
+use rootvg::{surface::DefaultSurface, Canvas};
+
+const WINDOW_SIZE: (f32, f32) = (400.0, 300.0);
+
+fn main() {
+    // Set up logging stuff
+    let env = env_logger::Env::default().filter_or("LOG_LEVEL", "info");
+    env_logger::init_from_env(env);
+
+    let event_loop = EventLoop::new().unwrap();
+    event_loop
+        .run_app(&mut TextOutlineApp {
+            state: None,
+            font_system: FontSystem::new(),
+            svg_icon_system: SvgIconSystem::default(),
+        })
+        .unwrap();
+}
+
+struct State {
+    window: Arc<Window>,
+    physical_size: PhysicalSizeI32,
+    scale_factor: ScaleFactor,
+    surface: DefaultSurface<'static>,
+    canvas: Canvas,
+    clear_color: PackedSrgb,
+    outlined_text: TextPrimitive,
+    shadowed_text: TextPrimitive,
+}
+
+struct TextOutlineApp {
+    state: Option<State>,
+    font_system: FontSystem,
+    svg_icon_system: SvgIconSystem,
+}
+
+impl TextOutlineApp {
+    fn create_window(&mut self, event_loop: &ActiveEventLoop) {
+        let window_attributes = Window::default_attributes()
+            .with_inner_size(winit::dpi::LogicalSize::new(
+                WINDOW_SIZE.0 as f64,
+                WINDOW_SIZE.1 as f64,
+            ))
+            .with_title("RootVG Text Outline Demo");
+        let window = Arc::new(event_loop.create_window(window_attributes).unwrap());
+
+        let physical_size = window.inner_size();
+        let physical_size =
+            PhysicalSizeI32::new(physical_size.width as i32, physical_size.height as i32);
+        let scale_factor: ScaleFactor = window.scale_factor().into();
+
+        let surface = rootvg::surface::DefaultSurface::new(
+            physical_size,
+            scale_factor,
+            Arc::clone(&window),
+            rootvg::surface::DefaultSurfaceConfig {
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let canvas = rootvg::Canvas::new(
+            &surface.device,
+            &surface.queue,
+            surface.format(),
+            surface.canvas_config(),
+            &mut self.font_system,
+        );
+
+        let props = TextProperties {
+            metrics: Metrics {
+                font_size: 32.0,
+                line_height: 40.0,
+            },
+            ..Default::default()
+        };
+
+        // White text with a black outline stays legible on any background.
+        let outlined_text = TextPrimitive::new(
+            RcTextBuffer::new(
+                "Outlined text",
+                props,
+                None,
+                None,
+                false,
+                &mut self.font_system,
+            ),
+            Point::new(40.0, 80.0),
+            RGBA8::new(255, 255, 255, 255),
+            None,
+        )
+        .with_outline(TextOutline::new(RGBA8::new(0, 0, 0, 255), 2.0));
+
+        let shadowed_text = TextPrimitive::new(
+            RcTextBuffer::new(
+                "Shadowed text",
+                props,
+                None,
+                None,
+                false,
+                &mut self.font_system,
+            ),
+            Point::new(40.0, 160.0),
+            RGBA8::new(255, 255, 255, 255),
+            None,
+        )
+        .with_shadow(TextShadow::new(
+            RGBA8::new(0, 0, 0, 200),
+            Vector::new(2.0, 2.0),
+            3.0,
+        ));
+
+        self.state = Some(State {
+            window,
+            physical_size,
+            scale_factor,
+            surface,
+            canvas,
+            clear_color: RGBA8::new(15, 15, 15, 255).into(),
+            outlined_text,
+            shadowed_text,
+        });
+    }
+}
+
+impl ApplicationHandler for TextOutlineApp {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.state.is_none() {
+            self.create_window(event_loop);
+        }
+    }
+
+    fn window_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        _window_id: WindowId,
+        event: WindowEvent,
+    ) {
+        let Some(state) = &mut self.state else {
+            return;
+        };
+
+        match event {
+            WindowEvent::RedrawRequested => {
+                {
+                    let mut cx = state.canvas.begin(state.physical_size, state.scale_factor);
+
+                    // A busy striped background.
+                    for i in 0..20 {
+                        let color = if i % 2 == 0 {
+                            RGBA8::new(230, 180, 40, 255)
+                        } else {
+                            RGBA8::new(40, 120, 230, 255)
+                        };
+
+                        cx.add(
+                            SolidQuad::builder(Size::new(20.0, WINDOW_SIZE.1))
+                                .position(Point::new(i as f32 * 20.0, 0.0))
+                                .bg_color(color)
+                                .build(),
+                        );
+                    }
+
+                    cx.add(state.outlined_text.clone());
+                    cx.add(state.shadowed_text.clone());
+                }
+
+                let frame = state.surface.get_current_texture().unwrap();
+                let view = frame
+                    .texture
+                    .create_view(&wgpu::TextureViewDescriptor::default());
+                let mut encoder = state
+                    .surface
+                    .device
+                    .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+                state
+                    .canvas
+                    .render_to_target(
+                        Some(state.clear_color),
+                        &state.surface.device,
+                        &state.surface.queue,
+                        &mut encoder,
+                        &view,
+                        state.physical_size,
+                        &mut self.font_system,
+                        &mut self.svg_icon_system,
+                    )
+                    .unwrap();
+
+                state.window.pre_present_notify();
+
+                state.surface.queue.submit(Some(encoder.finish()));
+                frame.present();
+            }
+            WindowEvent::Resized(new_size) => {
+                state.physical_size =
+                    PhysicalSizeI32::new(new_size.width as i32, new_size.height as i32);
+                state
+                    .surface
+                    .resize(state.physical_size, state.scale_factor);
+                state.window.request_redraw();
+            }
+            WindowEvent::ScaleFactorChanged {
+                scale_factor: new_scale,
+                inner_size_writer: _,
+            } => {
+                state.scale_factor = new_scale.into();
+                state
+                    .surface
+                    .resize(state.physical_size, state.scale_factor);
+                state.window.request_redraw();
+            }
+            WindowEvent::CloseRequested => event_loop.exit(),
+            _ => {}
+        }
+    }
+
+    fn exiting(&mut self, _event_loop: &ActiveEventLoop) {
+        // Be sure to drop the wgpu surface before the window closes,
+        // or else the program might segfault.
+        self.state = None;
+    }
+}
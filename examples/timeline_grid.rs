@@ -0,0 +1,105 @@
+//! Draws the grid of a DAW timeline at a fractional scale factor, where the
+//! spacing of the lines doesn't land on whole physical pixels.
+//!
+//! Run with `cargo run --example timeline_grid --features headless`.
+
+use rootvg::color::RGBA8;
+use rootvg::headless::{HeadlessConfig, HeadlessContext};
+use rootvg::math::{PhysicalSizeI32, Point, ScaleFactor, Size};
+use rootvg::quad::SolidQuad;
+use rootvg::{Canvas, GridAxis, PrimitiveGroup};
+use rootvg_text::{glyphon::FontSystem, svg::SvgIconSystem};
+
+const PHYSICAL_SIZE: PhysicalSizeI32 = PhysicalSizeI32::new(960, 360);
+const SCALE_FACTOR: f32 = 1.5;
+
+const HEADER_HEIGHT: f32 = 20.0;
+const TRACK_HEIGHT: f32 = 50.0;
+const NUM_TRACKS: usize = 4;
+
+const BEATS_PER_BAR: usize = 4;
+const NUM_BARS: usize = 8;
+const BEAT_WIDTH: f32 = 19.7;
+
+fn main() {
+    // Set up logging stuff
+    let env = env_logger::Env::default().filter_or("LOG_LEVEL", "info");
+    env_logger::init_from_env(env);
+
+    let mut font_system = FontSystem::new();
+    let mut svg_icon_system = SvgIconSystem::default();
+
+    let context = HeadlessContext::new(HeadlessConfig::default()).unwrap();
+
+    let mut canvas = Canvas::new(
+        &context.device,
+        &context.queue,
+        context.format(),
+        context.canvas_config(),
+        &mut font_system,
+    );
+
+    let logical_width = PHYSICAL_SIZE.width as f32 / SCALE_FACTOR;
+    let tracks_height = TRACK_HEIGHT * NUM_TRACKS as f32;
+
+    let mut grid = PrimitiveGroup::new();
+
+    grid.add_solid_quad(
+        SolidQuad::builder(Size::new(logical_width, HEADER_HEIGHT))
+            .bg_color(RGBA8::new(36, 36, 40, 255))
+            .build(),
+    );
+
+    // Beat lines, then bar lines on top of them. Each call adds a single
+    // batch of quads.
+    let num_beats = NUM_BARS * BEATS_PER_BAR + 1;
+    grid.add_grid(
+        Point::new(0.0, HEADER_HEIGHT),
+        BEAT_WIDTH,
+        num_beats,
+        tracks_height,
+        GridAxis::Vertical,
+        RGBA8::new(48, 48, 54, 255),
+        1.0 / SCALE_FACTOR,
+    );
+    grid.add_grid(
+        Point::new(0.0, 0.0),
+        BEAT_WIDTH * BEATS_PER_BAR as f32,
+        NUM_BARS + 1,
+        HEADER_HEIGHT + tracks_height,
+        GridAxis::Vertical,
+        RGBA8::new(80, 80, 90, 255),
+        1.0,
+    );
+
+    // Track separators.
+    grid.add_grid(
+        Point::new(0.0, HEADER_HEIGHT),
+        TRACK_HEIGHT,
+        NUM_TRACKS + 1,
+        logical_width,
+        GridAxis::Horizontal,
+        RGBA8::new(70, 70, 78, 255),
+        1.0,
+    );
+
+    {
+        let mut cx = canvas.begin(PHYSICAL_SIZE, ScaleFactor::new(SCALE_FACTOR));
+
+        cx.add_group(&grid);
+    }
+
+    let image = context
+        .render_to_image(
+            &mut canvas,
+            PHYSICAL_SIZE,
+            Some(RGBA8::new(24, 24, 28, 255).into()),
+            &mut font_system,
+            &mut svg_icon_system,
+        )
+        .unwrap();
+
+    image.save("timeline_grid.png").unwrap();
+
+    log::info!("saved render to timeline_grid.png");
+}
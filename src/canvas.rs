@@ -1,13 +1,15 @@
 use rustc_hash::FxHashMap;
 
+use crate::clip::RoundedClip;
 use crate::color::PackedSrgb;
-use crate::error::RenderError;
+use crate::error::{ReadbackError, RenderError};
 use crate::math::{
-    PhysicalSizeI32, PointI32, RectI32, ScaleFactor, Size, SizeI32, VectorI32, ZIndex,
+    PhysicalPointI32, PhysicalSizeI32, PointI32, Rect, RectI32, ScaleFactor, Size, SizeI32,
+    VectorI32, ZIndex,
 };
 
 #[cfg(feature = "msaa")]
-use crate::msaa::MsaaPipeline;
+use crate::msaa::{FxaaPipeline, MsaaPipeline};
 
 #[cfg(all(any(feature = "mesh", feature = "tessellation"), feature = "gradient"))]
 use crate::mesh::{
@@ -27,13 +29,14 @@ use crate::quad::{
 };
 #[cfg(feature = "quad")]
 use crate::quad::{
+    pipeline::line::{LineBatchBuffer, LinePipeline},
     pipeline::solid::{SolidQuadBatchBuffer, SolidQuadPipeline},
-    SolidQuadPrimitive,
+    LinePrimitive, SolidQuadPrimitive,
 };
 
 #[cfg(feature = "text")]
 use crate::text::{
-    pipeline::{TextBatchBuffer, TextPipeline},
+    pipeline::{SharedTextResources, TextBatchBuffer, TextPipeline, TextResources},
     FontSystem, TextPrimitive,
 };
 
@@ -46,15 +49,32 @@ use rootvg_image::{
     ImagePrimitive,
 };
 
+#[cfg(feature = "backdrop-blur")]
+use crate::blur::{
+    pipeline::{BackdropBlurBatchBuffer, BackdropBlurPipeline},
+    BackdropBlurPrimitive,
+};
+
 #[cfg(feature = "custom-primitive")]
 use rootvg_core::pipeline::{
     CustomPipeline, CustomPipelineID, CustomPipelinePrimitive, CustomPrimitive,
 };
 
+mod alpha_mask;
+mod clear;
 mod context;
 
+#[cfg(feature = "frame-stats")]
+mod stats;
+
+use alpha_mask::AlphaMaskPipeline;
+use clear::ClearPipeline;
+
 pub use context::CanvasCtx;
 
+#[cfg(feature = "frame-stats")]
+pub use stats::{FrameStats, PipelineStats};
+
 #[cfg(feature = "custom-primitive")]
 struct CustomPipelineEntry {
     pipeline: Box<dyn CustomPipeline>,
@@ -62,16 +82,196 @@ struct CustomPipelineEntry {
 
     prev_prepared_screen_size: PhysicalSizeI32,
     prev_prepared_scale_factor: ScaleFactor,
+    prev_prepared_origin: PhysicalPointI32,
     prev_prepared_primitives: Vec<CustomPipelinePrimitive>,
 }
 
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
 pub struct CanvasConfig {
     pub multisample: wgpu::MultisampleState,
+    /// Whether or not [`Canvas::render_to_texture`] should keep a copy of the
+    /// rendered frame so that it can be read back with [`Canvas::read_pixels`].
+    ///
+    /// By default this is set to `false`.
+    pub enable_readback: bool,
+    /// Whether or not the origin of the canvas is at the bottom-left corner
+    /// with the Y axis pointing up, which is useful when drawing data from
+    /// plotting libraries.
+    ///
+    /// When this is `true`, the position of quads, images, and text refers
+    /// to their bottom-left corner, and scissor rects and rounded clipping
+    /// rects are also in Y-up coordinates. The contents of quads, images, and
+    /// text are not mirrored, so glyphs stay upright. Note, offsets relative
+    /// to a primitive's own bounds (such as text clipping bounds, border
+    /// radii, and image and quad transforms) are still Y-down.
+    ///
+    /// By default this is set to `false`.
+    pub y_up: bool,
+    /// Whether or not to render with a depth buffer, where each batch of
+    /// primitives writes a depth derived from its z index.
+    ///
+    /// Batches are still drawn in order of their z index, since translucent
+    /// primitives (including the antialiased edges of opaque ones) need to be
+    /// blended back-to-front. The depth buffer is useful for custom pipelines
+    /// which draw content out of order (for example 3D content) and need it
+    /// to be correctly occluded by the rest of the canvas. The tradeoff is the
+    /// memory of an extra depth attachment the size of the render target, and
+    /// the cost of depth testing every fragment.
+    ///
+    /// Custom pipelines opt into the depth buffer with
+    /// [`CustomPipeline::wants_depth`], regardless of this setting.
+    ///
+    /// By default this is set to `false`.
+    pub depth_buffer: bool,
+    /// Whether or not only mesh and custom primitives should be rendered with
+    /// MSAA. This has no effect if the sample count of
+    /// [`CanvasConfig::multisample`] is `1` or the `msaa` feature is
+    /// disabled.
+    ///
+    /// Quads, text, and images are already antialiased in their shaders, so
+    /// when this is `true` they are rendered directly into the target. Each
+    /// run of consecutive mesh and custom batches (in z order) is rendered
+    /// into the multisampled attachment and then composited onto the
+    /// target. This saves bandwidth when meshes are only a small part of the
+    /// canvas, but it costs an extra render pass and a full-screen composite
+    /// for every run of meshes, so it is slower when meshes and other
+    /// primitives are interleaved many times.
+    ///
+    /// When this is enabled, meshes do not use the depth buffer (see
+    /// [`CanvasConfig::depth_stencil_state`]).
+    ///
+    /// By default this is set to `false`.
+    pub msaa_meshes_only: bool,
+    /// Whether or not to antialias the canvas with an FXAA post-process pass
+    /// instead of MSAA (see [`Antialiasing::Fxaa`]).
+    ///
+    /// The canvas is rendered into an intermediate texture, and then FXAA is
+    /// applied while compositing it onto the target. Flat regions are left
+    /// untouched, so quads and text are only softened slightly along their
+    /// edges. This has no effect if the sample count of
+    /// [`CanvasConfig::multisample`] is greater than `1` or the `msaa`
+    /// feature is disabled.
+    ///
+    /// By default this is set to `false`.
+    ///
+    /// [`Antialiasing::Fxaa`]: crate::msaa::Antialiasing::Fxaa
+    pub fxaa: bool,
+    /// Whether or not the render target should hold colors with premultiplied
+    /// alpha, for example when the canvas is composited onto another surface
+    /// which expects premultiplied alpha.
+    ///
+    /// The built-in pipelines blend the alpha channel with
+    /// `One, OneMinusSrcAlpha`, so drawing onto a transparent target already
+    /// accumulates premultiplied colors, including the coverage of text glyphs
+    /// and antialiased edges. Blending the straight colors output by the
+    /// shaders with `SrcAlpha` is the same as blending premultiplied colors
+    /// with `One`, so the blend states don't change. When this is `true`, the
+    /// clear color is premultiplied as well, so the whole target is
+    /// premultiplied even with a translucent clear color.
+    ///
+    /// When this is `false`, the clear color is written as is. The target then
+    /// only holds consistent colors if the clear color is opaque.
+    ///
+    /// By default this is set to `false`.
+    pub premultiplied_output: bool,
+    /// The maximum number of bytes of image data to upload to the GPU while
+    /// preparing a single frame, or `None` for no limit. This has no effect
+    /// if the `image` feature is disabled.
+    ///
+    /// Images which don't fit inside of the budget are left out of the frame
+    /// and uploaded in a later one, so adding a large image doesn't stall the
+    /// frame it is added in (see [`ImagePipeline::set_upload_budget`]).
+    /// Render another frame until [`RcTexture::is_uploaded`] returns `true`
+    /// for the images, or use [`RcTexture::on_uploaded`].
+    ///
+    /// By default this is set to `None`.
+    ///
+    /// [`ImagePipeline::set_upload_budget`]: crate::image::pipeline::ImagePipeline::set_upload_budget
+    /// [`RcTexture::is_uploaded`]: crate::image::RcTexture::is_uploaded
+    /// [`RcTexture::on_uploaded`]: crate::image::RcTexture::on_uploaded
+    pub image_upload_budget: Option<u64>,
+    /// What the render target holds. See [`TargetKind`].
+    ///
+    /// By default this is set to [`TargetKind::Color`].
+    pub target_kind: TargetKind,
+}
+
+impl CanvasConfig {
+    /// The depth stencil state of the built-in mesh pipelines, or `None` if
+    /// they don't use the depth buffer.
+    pub fn depth_stencil_state(&self) -> Option<wgpu::DepthStencilState> {
+        if self.meshes_only_msaa() {
+            return None;
+        }
+
+        self.depth_buffer
+            .then(rootvg_core::pipeline::depth_stencil_state)
+    }
+
+    fn meshes_only_msaa(&self) -> bool {
+        cfg!(feature = "msaa") && self.msaa_meshes_only && self.multisample.count > 1
+    }
+
+    /// The multisample states of the mesh pipelines and of all other
+    /// pipelines.
+    fn multisample_states(&self) -> (wgpu::MultisampleState, wgpu::MultisampleState) {
+        // Only the mesh pipelines are multisampled when MSAA is limited to
+        // meshes.
+        if self.meshes_only_msaa() {
+            (
+                self.multisample,
+                wgpu::MultisampleState {
+                    count: 1,
+                    ..self.multisample
+                },
+            )
+        } else {
+            (self.multisample, self.multisample)
+        }
+    }
+}
+
+/// What the render target of a [`Canvas`] holds.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TargetKind {
+    /// The target holds the colors of the primitives.
+    #[default]
+    Color,
+    /// The target only holds the coverage of the primitives, for example to
+    /// build a mask which is sampled by another pipeline. Colors are ignored,
+    /// and the alpha of each primitive (including the antialiased edges of
+    /// quads and the coverage of text glyphs) is written to every channel of
+    /// the target, so an `R8Unorm` target holds it in its red channel.
+    ///
+    /// The canvas is rendered into an intermediate `Rgba8Unorm` texture, and
+    /// then the coverage is composited onto the target. The built-in
+    /// pipelines, and custom pipelines, are created with the format of that
+    /// texture (see [`TargetKind::pipeline_format`]).
+    AlphaMask,
+}
+
+impl TargetKind {
+    /// The format which the pipelines of a canvas rendering to a target with
+    /// the given format should be created with.
+    pub fn pipeline_format(self, target_format: wgpu::TextureFormat) -> wgpu::TextureFormat {
+        match self {
+            Self::Color => target_format,
+            Self::AlphaMask => alpha_mask::INTERMEDIATE_FORMAT,
+        }
+    }
 }
 
 pub struct Canvas {
     batches: FxHashMap<BatchKey, BatchEntry>,
+    /// The batches which were used to prepare the current output, used to
+    /// skip preparing again when a frame is identical to the previous one.
+    prepared_batches: FxHashMap<BatchKey, BatchEntry>,
+    /// The state that the current output was prepared with, or `None` if the
+    /// output is not valid.
+    prepared_frame: Option<PreparedFrame>,
+    /// Cleared batch entries from the previous frame, kept around so that
+    /// their allocated capacity can be reused.
+    batch_entry_pool: Vec<BatchEntry>,
     temp_keys_for_sorting: Vec<BatchKey>,
 
     #[cfg(any(feature = "mesh", feature = "tessellation"))]
@@ -83,6 +283,8 @@ pub struct Canvas {
     solid_quad_pipeline: SolidQuadPipeline,
     #[cfg(all(feature = "quad", feature = "gradient"))]
     gradient_quad_pipeline: GradientQuadPipeline,
+    #[cfg(feature = "quad")]
+    line_pipeline: LinePipeline,
 
     #[cfg(feature = "text")]
     text_pipeline: TextPipeline,
@@ -90,8 +292,16 @@ pub struct Canvas {
     #[cfg(feature = "image")]
     image_pipeline: ImagePipeline,
 
+    #[cfg(feature = "backdrop-blur")]
+    backdrop_blur_pipeline: BackdropBlurPipeline,
+
     #[cfg(feature = "msaa")]
     msaa_pipeline: Option<MsaaPipeline>,
+    #[cfg(feature = "msaa")]
+    fxaa_pipeline: Option<FxaaPipeline>,
+
+    alpha_mask_pipeline: Option<AlphaMaskPipeline>,
+    clear_pipeline: ClearPipeline,
 
     #[cfg(feature = "custom-primitive")]
     custom_pipelines: thunderdome::Arena<CustomPipelineEntry>,
@@ -105,49 +315,242 @@ pub struct Canvas {
 
     scissor_rect: RectI32,
     scissor_rect_out_of_bounds: bool,
+    scissor_rect_stack: Vec<(RectI32, bool)>,
+
+    rounded_clip: Option<RoundedClip>,
+
+    global_alpha: f32,
+    global_alpha_stack: Vec<f32>,
 
     needs_preparing: bool,
 
+    /// The union of the damaged regions of the current frame (in the same
+    /// coordinate space as scissor rects), or `None` if the whole canvas is
+    /// redrawn.
+    damage_rect: Option<RectI32>,
+    /// The region of the render target that the canvas is rendered into (in
+    /// physical pixels), or `None` if the canvas fills the whole target.
+    dest_rect: Option<RectI32>,
+    /// The size of the render target of the current render.
+    target_size: PhysicalSizeI32,
+
+    enable_readback: bool,
+    readback_texture: Option<wgpu::Texture>,
+
+    y_up: bool,
+
+    depth_buffer: bool,
+    multisample: wgpu::MultisampleState,
+    msaa_meshes_only: bool,
+    fxaa: bool,
+    premultiplied_output: bool,
+    image_upload_budget: Option<u64>,
+    target_kind: TargetKind,
+    #[cfg(feature = "msaa")]
+    meshes_only_msaa: bool,
+    /// The sample count of the quad, text, and image pipelines.
+    sample_count: u32,
+    /// The depth textures, one for each sample count that is rendered with.
+    depth_textures: Vec<wgpu::Texture>,
+
+    #[cfg(feature = "frame-stats")]
+    last_frame_stats: FrameStats,
+
     pub(crate) z_index: ZIndex,
 }
 
 impl Canvas {
+    /// Create a new canvas which renders to targets with the given format.
+    ///
+    /// The device, queue, and render targets don't need to be created by
+    /// RootVG, so the canvas can be used with a device owned by a host
+    /// application. See [`Canvas::render_to_target`].
+    ///
+    /// Colors are written to the target as they are stored in
+    /// [`PackedSrgb`], which is why the format should match the `web-colors`
+    /// feature (see [`GAMMA_CORRECTION`]):
+    /// * With `web-colors`, colors are blended in sRGB space and the format
+    /// should not be an sRGB format (i.e. `Bgra8Unorm` or `Rgba8Unorm`).
+    /// * Without `web-colors`, colors are blended in linear space and the
+    /// format should be an sRGB format (i.e. `Bgra8UnormSrgb` or
+    /// `Rgba8UnormSrgb`), so that the GPU encodes them when they are written.
+    ///
+    /// Other formats still work, but all colors will appear too light or too
+    /// dark.
+    ///
+    /// [`GAMMA_CORRECTION`]: crate::color::GAMMA_CORRECTION
     pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        format: wgpu::TextureFormat,
+        config: CanvasConfig,
+        #[cfg(feature = "text")] font_system: &mut FontSystem,
+    ) -> Self {
+        Self::new_inner(
+            device,
+            queue,
+            format,
+            config,
+            #[cfg(feature = "text")]
+            font_system,
+            #[cfg(feature = "text")]
+            None,
+        )
+    }
+
+    /// Create a new canvas which shares its glyph atlas and glyph caches with
+    /// other canvases, for example the canvases of multiple windows.
+    ///
+    /// See [`SharedTextResources`] for the supported sharing model.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `format` is not the format of `text_resources`.
+    #[cfg(feature = "text")]
+    pub fn with_shared_text_resources(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        format: wgpu::TextureFormat,
+        config: CanvasConfig,
+        font_system: &mut FontSystem,
+        text_resources: SharedTextResources,
+    ) -> Self {
+        Self::new_inner(
+            device,
+            queue,
+            format,
+            config,
+            font_system,
+            Some(text_resources),
+        )
+    }
+
+    fn new_inner(
         device: &wgpu::Device,
         #[allow(unused)] // queue is unused if the "text" feature is disabled
         queue: &wgpu::Queue,
         format: wgpu::TextureFormat,
         config: CanvasConfig,
         #[cfg(feature = "text")] font_system: &mut FontSystem,
+        #[cfg(feature = "text")] text_resources: Option<SharedTextResources>,
     ) -> Self {
-        let CanvasConfig { multisample } = config;
+        let alpha_mask_pipeline = (config.target_kind == TargetKind::AlphaMask)
+            .then(|| AlphaMaskPipeline::new(device, format));
+        let clear_pipeline = ClearPipeline::new(device, format);
+        let format = config.target_kind.pipeline_format(format);
+
+        if format.is_srgb() != crate::color::GAMMA_CORRECTION
+            && config.target_kind == TargetKind::Color
+        {
+            log::warn!(
+                "the sRGB-ness of the target format {format:?} does not match the `web-colors` feature, colors will be rendered incorrectly"
+            );
+        }
+
+        let meshes_only_msaa = config.meshes_only_msaa();
+        let (mesh_multisample, multisample) = config.multisample_states();
+
+        #[allow(unused)] // unused if the "mesh" and "tessellation" features are disabled
+        let mesh_depth_stencil = config.depth_stencil_state();
+        let depth_stencil = config
+            .depth_buffer
+            .then(rootvg_core::pipeline::depth_stencil_state);
+
+        let CanvasConfig {
+            multisample: config_multisample,
+            enable_readback,
+            y_up,
+            depth_buffer,
+            msaa_meshes_only,
+            fxaa,
+            premultiplied_output,
+            image_upload_budget,
+            target_kind,
+        } = config;
 
         Self {
             batches: FxHashMap::default(),
+            prepared_batches: FxHashMap::default(),
+            prepared_frame: None,
+            batch_entry_pool: Vec::new(),
             temp_keys_for_sorting: Vec::new(),
 
             #[cfg(any(feature = "mesh", feature = "tessellation"))]
-            solid_mesh_pipeline: SolidMeshPipeline::new(device, format, multisample),
+            solid_mesh_pipeline: SolidMeshPipeline::new(
+                device,
+                format,
+                mesh_multisample,
+                mesh_depth_stencil.clone(),
+            ),
             #[cfg(all(any(feature = "mesh", feature = "tessellation"), feature = "gradient"))]
-            gradient_mesh_pipeline: GradientMeshPipeline::new(device, format, multisample),
+            gradient_mesh_pipeline: GradientMeshPipeline::new(
+                device,
+                format,
+                mesh_multisample,
+                mesh_depth_stencil.clone(),
+            ),
 
             #[cfg(feature = "quad")]
-            solid_quad_pipeline: SolidQuadPipeline::new(device, format, multisample),
+            solid_quad_pipeline: SolidQuadPipeline::new(
+                device,
+                format,
+                multisample,
+                depth_stencil.clone(),
+            ),
             #[cfg(all(feature = "quad", feature = "gradient"))]
-            gradient_quad_pipeline: GradientQuadPipeline::new(device, format, multisample),
+            gradient_quad_pipeline: GradientQuadPipeline::new(
+                device,
+                format,
+                multisample,
+                depth_stencil.clone(),
+            ),
+            #[cfg(feature = "quad")]
+            line_pipeline: LinePipeline::new(device, format, multisample, depth_stencil.clone()),
 
             #[cfg(feature = "text")]
-            text_pipeline: TextPipeline::new(device, queue, format, multisample, font_system),
+            text_pipeline: if let Some(text_resources) = text_resources {
+                TextPipeline::with_shared_resources(
+                    device,
+                    format,
+                    multisample,
+                    depth_stencil.clone(),
+                    font_system,
+                    text_resources,
+                )
+            } else {
+                TextPipeline::new(
+                    device,
+                    queue,
+                    format,
+                    multisample,
+                    depth_stencil.clone(),
+                    font_system,
+                )
+            },
 
             #[cfg(feature = "image")]
-            image_pipeline: ImagePipeline::new(device, format, multisample),
+            image_pipeline: {
+                let mut pipeline =
+                    ImagePipeline::new(device, format, multisample, depth_stencil.clone());
+                pipeline.set_upload_budget(image_upload_budget);
+                pipeline
+            },
+
+            #[cfg(feature = "backdrop-blur")]
+            backdrop_blur_pipeline: BackdropBlurPipeline::new(device, format),
 
             #[cfg(feature = "msaa")]
-            msaa_pipeline: if multisample.count > 1 {
-                Some(MsaaPipeline::new(device, format, multisample.count))
+            msaa_pipeline: if mesh_multisample.count > 1 {
+                Some(MsaaPipeline::new(device, format, mesh_multisample.count))
             } else {
                 None
             },
+            #[cfg(feature = "msaa")]
+            fxaa_pipeline: (fxaa && mesh_multisample.count == 1)
+                .then(|| FxaaPipeline::new(device, format)),
+
+            alpha_mask_pipeline,
+            clear_pipeline,
 
             #[cfg(feature = "custom-primitive")]
             custom_pipelines: thunderdome::Arena::new(),
@@ -160,11 +563,198 @@ impl Canvas {
             screen_to_clip_scale: [0.0; 2],
             scissor_rect: RectI32::default(),
             scissor_rect_out_of_bounds: true,
+            scissor_rect_stack: Vec::new(),
+            rounded_clip: None,
+            global_alpha: 1.0,
+            global_alpha_stack: Vec::new(),
             needs_preparing: false,
+            damage_rect: None,
+            dest_rect: None,
+            target_size: PhysicalSizeI32::default(),
+            enable_readback,
+            readback_texture: None,
+            y_up,
+            depth_buffer,
+            multisample: config_multisample,
+            msaa_meshes_only,
+            fxaa,
+            premultiplied_output,
+            image_upload_budget,
+            target_kind,
+            #[cfg(feature = "msaa")]
+            meshes_only_msaa,
+            sample_count: multisample.count,
+            depth_textures: Vec::new(),
+            #[cfg(feature = "frame-stats")]
+            last_frame_stats: FrameStats::default(),
             z_index: 0,
         }
     }
 
+    /// The config of this canvas, including any changes made with
+    /// [`Canvas::set_antialiasing`].
+    pub fn config(&self) -> CanvasConfig {
+        CanvasConfig {
+            multisample: self.multisample,
+            enable_readback: self.enable_readback,
+            y_up: self.y_up,
+            depth_buffer: self.depth_buffer,
+            msaa_meshes_only: self.msaa_meshes_only,
+            fxaa: self.fxaa,
+            premultiplied_output: self.premultiplied_output,
+            image_upload_budget: self.image_upload_budget,
+            target_kind: self.target_kind,
+        }
+    }
+
+    /// Change the antialiasing mode of the canvas without recreating it.
+    ///
+    /// This rebuilds the built-in pipelines in place. The primitives which
+    /// were added since the last call to [`Canvas::begin`] are kept and will
+    /// be prepared again on the next render, and the glyph atlas is kept.
+    ///
+    /// The sample count must be supported by the format of the render
+    /// target. Use [`DefaultSurface::compatible_aa`] to fall back to a
+    /// supported mode first.
+    ///
+    /// Custom pipelines are not rebuilt. They must be recreated with the
+    /// multisample and depth stencil states of the new [`Canvas::config`].
+    ///
+    /// [`DefaultSurface::compatible_aa`]: crate::surface::DefaultSurface::compatible_aa
+    #[cfg(feature = "msaa")]
+    pub fn set_antialiasing(
+        &mut self,
+        antialiasing: Option<crate::msaa::Antialiasing>,
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+    ) {
+        let format = self.target_kind.pipeline_format(format);
+
+        let fxaa = antialiasing == Some(crate::msaa::Antialiasing::Fxaa);
+        if self.fxaa != fxaa {
+            self.fxaa = fxaa;
+            self.fxaa_pipeline = fxaa.then(|| FxaaPipeline::new(device, format));
+        }
+
+        let sample_count = antialiasing.map(|aa| aa.sample_count()).unwrap_or(1);
+        if self.multisample.count == sample_count {
+            return;
+        }
+        self.multisample.count = sample_count;
+
+        let config = self.config();
+        let (mesh_multisample, multisample) = config.multisample_states();
+
+        #[allow(unused)] // unused if the "mesh" and "tessellation" features are disabled
+        let mesh_depth_stencil = config.depth_stencil_state();
+        #[allow(unused)] // unused if only the mesh features are enabled
+        let depth_stencil = config
+            .depth_buffer
+            .then(rootvg_core::pipeline::depth_stencil_state);
+
+        #[cfg(any(feature = "mesh", feature = "tessellation"))]
+        {
+            self.solid_mesh_pipeline = SolidMeshPipeline::new(
+                device,
+                format,
+                mesh_multisample,
+                mesh_depth_stencil.clone(),
+            );
+        }
+        #[cfg(all(any(feature = "mesh", feature = "tessellation"), feature = "gradient"))]
+        {
+            self.gradient_mesh_pipeline = GradientMeshPipeline::new(
+                device,
+                format,
+                mesh_multisample,
+                mesh_depth_stencil.clone(),
+            );
+        }
+
+        #[cfg(feature = "quad")]
+        {
+            self.solid_quad_pipeline =
+                SolidQuadPipeline::new(device, format, multisample, depth_stencil.clone());
+        }
+        #[cfg(all(feature = "quad", feature = "gradient"))]
+        {
+            self.gradient_quad_pipeline =
+                GradientQuadPipeline::new(device, format, multisample, depth_stencil.clone());
+        }
+        #[cfg(feature = "quad")]
+        {
+            self.line_pipeline =
+                LinePipeline::new(device, format, multisample, depth_stencil.clone());
+        }
+
+        #[cfg(feature = "text")]
+        self.text_pipeline.set_multisample(multisample);
+
+        #[cfg(feature = "image")]
+        {
+            self.image_pipeline =
+                ImagePipeline::new(device, format, multisample, depth_stencil.clone());
+            self.image_pipeline
+                .set_upload_budget(self.image_upload_budget);
+        }
+
+        self.msaa_pipeline = if mesh_multisample.count > 1 {
+            Some(MsaaPipeline::new(device, format, mesh_multisample.count))
+        } else {
+            None
+        };
+
+        self.meshes_only_msaa = config.meshes_only_msaa();
+        self.sample_count = multisample.count;
+        self.depth_textures.clear();
+
+        // The batch buffers were created by the old pipelines.
+        self.output = CanvasOutput::new();
+        self.prepared_frame = None;
+        self.needs_preparing = true;
+    }
+
+    /// Get the batch entry for the given key, reusing a pooled entry from the
+    /// previous frame if one is available.
+    fn batch_entry(&mut self, key: BatchKey) -> &mut BatchEntry {
+        let pool = &mut self.batch_entry_pool;
+
+        self.batches
+            .entry(key)
+            .or_insert_with(|| pool.pop().unwrap_or_else(BatchEntry::new))
+    }
+
+    /// Returns `true` if no primitives have been added since the last call to
+    /// [`Canvas::begin`].
+    ///
+    /// Primitives which were discarded because they lie outside of the canvas
+    /// or its scissor rect are not counted.
+    pub fn is_empty(&self) -> bool {
+        self.batches.values().all(BatchEntry::is_empty)
+    }
+
+    /// Statistics about how the primitives of the last prepared frame were
+    /// batched, useful for profiling the number of draw calls.
+    #[cfg(feature = "frame-stats")]
+    pub fn last_frame_stats(&self) -> FrameStats {
+        self.last_frame_stats
+    }
+
+    /// The logical height of the canvas if the canvas is in Y-up mode.
+    pub(crate) fn flip_height(&self) -> Option<f32> {
+        self.y_up.then_some(self.logical_size.height)
+    }
+
+    /// Begin a new frame, clearing all of the primitives of the previous
+    /// frame.
+    ///
+    /// If the same primitives are added as in the previously prepared frame
+    /// (and the screen size and scale factor have not changed), then the
+    /// previous output is rendered again without being re-uploaded to the
+    /// GPU. Primitives are compared the same way as their `PartialEq`
+    /// implementations, so shared resources such as meshes, textures, and
+    /// text buffers are compared by identity and generation rather than by
+    /// contents.
     pub fn begin(
         &mut self,
         physical_size: PhysicalSizeI32,
@@ -174,8 +764,28 @@ impl Canvas {
         assert!(physical_size.height > 0);
         assert!(scale_factor.0 > 0.0);
 
-        // TODO: Try to re-use the allocated capacity of batch entries?
-        self.batches.clear();
+        // Entries which were left in the pool were not needed in the previous
+        // frame, so drop them to keep memory from growing unbounded after a
+        // spike in the number of batches.
+        self.batch_entry_pool.clear();
+
+        // If the previous frame was prepared, then keep its batches around so
+        // that preparing can be skipped if the next frame is identical.
+        if !self.needs_preparing {
+            self.batch_entry_pool
+                .extend(self.prepared_batches.drain().map(|(_, mut entry)| {
+                    entry.clear();
+                    entry
+                }));
+
+            std::mem::swap(&mut self.batches, &mut self.prepared_batches);
+        }
+
+        self.batch_entry_pool
+            .extend(self.batches.drain().map(|(_, mut entry)| {
+                entry.clear();
+                entry
+            }));
 
         self.scale_factor = scale_factor;
         self.physical_size = physical_size;
@@ -189,12 +799,52 @@ impl Canvas {
             2.0 * scale_factor * (physical_size.height as f32).recip(),
         ];
         self.reset_scissor_rect();
+        self.scissor_rect_stack.clear();
+        self.rounded_clip = None;
+        self.global_alpha = 1.0;
+        self.global_alpha_stack.clear();
         self.needs_preparing = true;
+        self.damage_rect = None;
         self.z_index = 0;
 
         CanvasCtx { canvas: self }
     }
 
+    /// Begin a new frame which only redraws the given damaged regions of the
+    /// previous frame, for example to only redraw a blinking cursor in an
+    /// otherwise idle UI. The regions are in logical points, in the same
+    /// coordinate space as scissor rects.
+    ///
+    /// All primitives of the frame must still be added. Only the pixels within
+    /// the union of the damaged regions are cleared and redrawn: the previous
+    /// contents of the target are loaded instead of cleared, the union is
+    /// filled with the clear color, replacing its previous contents, and every
+    /// scissor rect is clipped to the union.
+    ///
+    /// The target must still contain the previous frame, so this is meant for
+    /// textures which are kept between frames. The textures of a window's
+    /// swapchain usually are not.
+    ///
+    /// When FXAA is enabled, edges which cross the border of the damaged
+    /// region may be antialiased slightly differently than in a full redraw.
+    pub fn begin_with_damage(
+        &mut self,
+        physical_size: PhysicalSizeI32,
+        scale_factor: ScaleFactor,
+        damage: &[RectI32],
+    ) -> CanvasCtx<'_> {
+        let damage_rect = damage
+            .iter()
+            .copied()
+            .reduce(|a, b| a.union(&b))
+            .unwrap_or_default();
+
+        self.begin(physical_size, scale_factor);
+        self.damage_rect = Some(damage_rect);
+
+        CanvasCtx { canvas: self }
+    }
+
     pub fn set_scissor_rect(&mut self, scissor_rect: RectI32) {
         if self.scissor_rect != scissor_rect {
             if let Some(bounded_scissor_rect) =
@@ -214,6 +864,81 @@ impl Canvas {
         self.scissor_rect_out_of_bounds = false;
     }
 
+    /// Push a new scissoring rectangle onto the stack. The new scissoring
+    /// rectangle is the intersection of the given rectangle and the current
+    /// scissoring rectangle.
+    ///
+    /// Call [`Canvas::pop_scissor_rect`] to restore the previous scissoring
+    /// rectangle.
+    pub fn push_scissor_rect(&mut self, scissor_rect: RectI32) {
+        self.scissor_rect_stack
+            .push((self.scissor_rect, self.scissor_rect_out_of_bounds));
+
+        if self.scissor_rect_out_of_bounds {
+            return;
+        }
+
+        match intersect_scissor_rect(self.scissor_rect, scissor_rect) {
+            Some(rect) => self.set_scissor_rect(rect),
+            None => {
+                self.scissor_rect = RectI32::default();
+                self.scissor_rect_out_of_bounds = true;
+            }
+        }
+    }
+
+    /// Restore the scissoring rectangle that was active before the last call
+    /// to [`Canvas::push_scissor_rect`].
+    ///
+    /// If the stack is empty, then this resets the scissoring rectangle to
+    /// cover the whole canvas.
+    pub fn pop_scissor_rect(&mut self) {
+        if let Some((scissor_rect, out_of_bounds)) = self.scissor_rect_stack.pop() {
+            self.scissor_rect = scissor_rect;
+            self.scissor_rect_out_of_bounds = out_of_bounds;
+        } else {
+            self.reset_scissor_rect();
+        }
+    }
+
+    /// Set the opacity that all primitives added after this call are
+    /// multiplied by, in the range `[0.0, 1.0]`.
+    pub fn set_global_alpha(&mut self, alpha: f32) {
+        self.global_alpha = alpha.clamp(0.0, 1.0);
+    }
+
+    /// Push a new global alpha onto the stack. The new global alpha is the
+    /// product of the given alpha and the current global alpha.
+    ///
+    /// Call [`Canvas::pop_global_alpha`] to restore the previous global alpha.
+    pub fn push_global_alpha(&mut self, alpha: f32) {
+        self.global_alpha_stack.push(self.global_alpha);
+        self.set_global_alpha(self.global_alpha * alpha);
+    }
+
+    /// Restore the global alpha that was active before the last call to
+    /// [`Canvas::push_global_alpha`].
+    ///
+    /// If the stack is empty, then this resets the global alpha to `1.0`.
+    pub fn pop_global_alpha(&mut self) {
+        self.global_alpha = self.global_alpha_stack.pop().unwrap_or(1.0);
+    }
+
+    /// Clip all primitives added after this call to the given rounded
+    /// rectangle (in logical points).
+    ///
+    /// Unlike the scissoring rectangle, this is evaluated per-pixel in the
+    /// shaders of the quad, mesh, and image pipelines. Note that text and
+    /// custom primitives are only clipped by the scissoring rectangle.
+    pub fn set_rounded_clip(&mut self, rect: Rect, radius: f32) {
+        self.rounded_clip = Some(RoundedClip::new(rect, radius));
+    }
+
+    /// Stop clipping primitives to a rounded rectangle.
+    pub fn reset_rounded_clip(&mut self) {
+        self.rounded_clip = None;
+    }
+
     #[cfg(feature = "custom-primitive")]
     pub fn insert_custom_pipeline(
         &mut self,
@@ -224,6 +949,7 @@ impl Canvas {
             primitives_to_prepare: Vec::new(),
             prev_prepared_screen_size: PhysicalSizeI32::default(),
             prev_prepared_scale_factor: ScaleFactor::default(),
+            prev_prepared_origin: PhysicalPointI32::default(),
             prev_prepared_primitives: Vec::new(),
         }))
     }
@@ -248,11 +974,28 @@ impl Canvas {
         &mut self,
         id: CustomPipelineID,
     ) -> Option<Box<dyn CustomPipeline>> {
+        // The current output may contain batches of the removed pipeline.
+        self.prepared_frame = None;
+
         self.custom_pipelines
             .remove(id.0)
             .map(|entry| entry.pipeline)
     }
 
+    /// Render the canvas to the given target.
+    ///
+    /// Returns `true` if any primitives were drawn. The target is still
+    /// cleared with the clear color (if one was given) when nothing was
+    /// drawn, including when MSAA is enabled.
+    ///
+    /// The clear color is converted to the format of the target in the same
+    /// way as the colors of primitives, so the cleared background matches a
+    /// quad of the same color regardless of whether the format is sRGB.
+    ///
+    /// The target can be any texture view with the format this canvas was
+    /// created with and the `RENDER_ATTACHMENT` usage, for example a
+    /// texture owned by a host application. See [`Canvas::new`] for how
+    /// colors are written to the target.
     pub fn render_to_target(
         &mut self,
         clear_color: Option<PackedSrgb>,
@@ -263,94 +1006,649 @@ impl Canvas {
         target_size: PhysicalSizeI32,
         #[cfg(feature = "text")] font_system: &mut FontSystem,
         #[cfg(all(feature = "text", feature = "svg-icons"))] svg_icon_system: &mut SvgIconSystem,
-    ) -> Result<(), RenderError> {
+    ) -> Result<bool, RenderError> {
         assert_eq!(target_size, self.physical_size);
 
-        self.prepare(
+        self.render_to_target_inner(
+            clear_color,
             device,
             queue,
+            encoder,
+            target,
+            target_size,
+            None,
             #[cfg(feature = "text")]
             font_system,
             #[cfg(all(feature = "text", feature = "svg-icons"))]
             svg_icon_system,
-        )?;
+        )
+    }
+
+    /// Render the canvas into the region `dest` of the given target, for
+    /// example to composite multiple canvases into one texture.
+    ///
+    /// `dest` is in physical pixels and must have the same size as the
+    /// canvas. It may lie partially (or entirely) outside of the target, in
+    /// which case the canvas is clipped to the target.
+    ///
+    /// Only the pixels within `dest` are touched: the previous contents of
+    /// the target are loaded instead of cleared, and `dest` is filled with the
+    /// clear color, replacing its previous contents.
+    ///
+    /// Custom pipelines must implement
+    /// [`crate::pipeline::CustomPipeline::prepare_with_origin`] to be drawn at
+    /// the right position.
+    ///
+    /// Returns `true` if any primitives were drawn.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_to_target_in_rect(
+        &mut self,
+        clear_color: Option<PackedSrgb>,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        target_size: PhysicalSizeI32,
+        dest: RectI32,
+        #[cfg(feature = "text")] font_system: &mut FontSystem,
+        #[cfg(all(feature = "text", feature = "svg-icons"))] svg_icon_system: &mut SvgIconSystem,
+    ) -> Result<bool, RenderError> {
+        assert_eq!(dest.size.width, self.physical_size.width);
+        assert_eq!(dest.size.height, self.physical_size.height);
+
+        self.render_to_target_inner(
+            clear_color,
+            device,
+            queue,
+            encoder,
+            target,
+            target_size,
+            Some(dest),
+            #[cfg(feature = "text")]
+            font_system,
+            #[cfg(all(feature = "text", feature = "svg-icons"))]
+            svg_icon_system,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn render_to_target_inner(
+        &mut self,
+        clear_color: Option<PackedSrgb>,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        target_size: PhysicalSizeI32,
+        dest: Option<RectI32>,
+        #[cfg(feature = "text")] font_system: &mut FontSystem,
+        #[cfg(all(feature = "text", feature = "svg-icons"))] svg_icon_system: &mut SvgIconSystem,
+    ) -> Result<bool, RenderError> {
+        self.dest_rect = dest;
+        self.target_size = target_size;
+
+        let res = self.prepare(
+            device,
+            queue,
+            #[cfg(feature = "text")]
+            font_system,
+            #[cfg(all(feature = "text", feature = "svg-icons"))]
+            svg_icon_system,
+        );
 
-        let clear_color = clear_color.map(|c| wgpu::Color {
-            r: c.r() as f64,
-            g: c.g() as f64,
-            b: c.b() as f64,
-            a: c.a() as f64,
+        // The glyph atlas may still hold glyphs which are no longer used. Evict
+        // them and prepare all text batches again, so that only the glyphs of
+        // the current frame need to fit.
+        #[cfg(feature = "text")]
+        let res = match res {
+            Err(RenderError::GlyphonPrepareError(
+                crate::text::glyphon::PrepareError::AtlasFull,
+            )) => {
+                self.text_pipeline.evict_unused_glyphs();
+
+                self.prepare(
+                    device,
+                    queue,
+                    font_system,
+                    #[cfg(feature = "svg-icons")]
+                    svg_icon_system,
+                )
+            }
+            res => res,
+        };
+
+        res?;
+
+        // Nothing is drawn if the region lies entirely outside of the target.
+        if let Some(dest) = dest {
+            let target_rect = RectI32::new(
+                PointI32::new(0, 0),
+                SizeI32::new(target_size.width, target_size.height),
+            );
+            if intersect_scissor_rect(dest, target_rect).is_none() {
+                return Ok(false);
+            }
+        }
+
+        let premultiplied_output = self.premultiplied_output;
+        let clear_color = clear_color.map(|c| {
+            let mut color = wgpu::Color::from(c);
+
+            if premultiplied_output {
+                color.r *= color.a;
+                color.g *= color.a;
+                color.b *= color.a;
+            }
+
+            color
         });
 
         #[cfg(feature = "msaa")]
         let mut msaa_pipeline = self.msaa_pipeline.take();
+        #[cfg(feature = "msaa")]
+        let mut fxaa_pipeline = self.fxaa_pipeline.take();
+        let mut alpha_mask_pipeline = self.alpha_mask_pipeline.take();
 
-        {
+        let res = self.render_passes(
+            device,
+            encoder,
+            target,
+            target_size,
+            clear_color,
             #[cfg(feature = "msaa")]
-            let (attachment, resolve_target, load) = if let Some(msaa_pipeline) = &mut msaa_pipeline
-            {
-                let (attachment, resolve_target) = msaa_pipeline.targets(device, target_size);
+            msaa_pipeline.as_mut(),
+            #[cfg(feature = "msaa")]
+            fxaa_pipeline.as_mut(),
+            alpha_mask_pipeline.as_mut(),
+        );
 
-                (
-                    attachment,
-                    Some(resolve_target),
-                    wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
-                )
+        #[cfg(feature = "msaa")]
+        {
+            self.msaa_pipeline = msaa_pipeline;
+            self.fxaa_pipeline = fxaa_pipeline;
+        }
+        self.alpha_mask_pipeline = alpha_mask_pipeline;
+
+        res.map(|()| self.output_has_primitives())
+    }
+
+    /// Returns `true` if the prepared output draws any primitives.
+    fn output_has_primitives(&self) -> bool {
+        self.output
+            .order
+            .iter()
+            .any(|order| self.pass_kind(order).is_some())
+    }
+
+    /// Render all batches, starting a new render pass whenever the batches
+    /// need different attachments (see [`Canvas::pass_kind`]).
+    ///
+    /// Each run of batches which use MSAA is rendered into the multisampled
+    /// attachment and then composited onto the target. All other batches are
+    /// rendered directly into the target, or into the intermediate texture
+    /// when FXAA is enabled.
+    ///
+    /// When the frame contains backdrop blurs, the batches are rendered into
+    /// the backdrop texture instead so that it can be sampled, and the
+    /// backdrop is then composited onto the target (or the FXAA texture).
+    ///
+    /// When rendering an alpha mask, all of the above happens in the alpha
+    /// mask texture, whose coverage is then written onto the target.
+    #[allow(clippy::too_many_arguments)]
+    fn render_passes(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        target_size: PhysicalSizeI32,
+        clear_color: Option<wgpu::Color>,
+        #[cfg(feature = "msaa")] mut msaa_pipeline: Option<&mut MsaaPipeline>,
+        #[cfg(feature = "msaa")] mut fxaa_pipeline: Option<&mut FxaaPipeline>,
+        mut alpha_mask_pipeline: Option<&mut AlphaMaskPipeline>,
+    ) -> Result<(), RenderError> {
+        // The alpha mask texture is always cleared, since it holds the
+        // previous frame.
+        let final_target = target;
+        let mut mask_clear_color = None;
+        let mut clear_color = clear_color;
+        let is_alpha_mask = alpha_mask_pipeline.is_some();
+        let target = match alpha_mask_pipeline.as_deref_mut() {
+            Some(alpha_mask_pipeline) => {
+                mask_clear_color = clear_color.replace(wgpu::Color::TRANSPARENT);
+                alpha_mask_pipeline.target(device, target_size)
+            }
+            None => target,
+        };
+
+        let mut direct_uses_depth = false;
+        #[allow(unused)] // unused if the "msaa" feature is disabled
+        let mut msaa_uses_depth = false;
+        for order in self.output.order.iter() {
+            match self.pass_kind(order) {
+                Some(pass) if pass.msaa => msaa_uses_depth |= pass.depth,
+                Some(pass) => direct_uses_depth |= pass.depth,
+                None => {}
+            }
+        }
+
+        // The depth buffers are cleared by the first pass which uses them, and
+        // then kept for the rest of the frame.
+        let depth_view =
+            direct_uses_depth.then(|| self.depth_view(device, target_size, self.sample_count));
+        let mut depth_load = wgpu::LoadOp::Clear(1.0);
+        #[cfg(feature = "msaa")]
+        let msaa_depth_view =
+            msaa_uses_depth.then(|| self.depth_view(device, target_size, self.multisample.count));
+        #[cfg(feature = "msaa")]
+        let mut msaa_depth_load = wgpu::LoadOp::Clear(1.0);
+
+        // When FXAA is enabled, the batches are rendered into the intermediate
+        // texture instead of the target.
+        #[cfg(feature = "msaa")]
+        let fxaa_target = fxaa_pipeline
+            .as_deref_mut()
+            .map(|fxaa_pipeline| fxaa_pipeline.target(device, target_size));
+        #[cfg(not(feature = "msaa"))]
+        let fxaa_target: Option<&wgpu::TextureView> = None;
+
+        #[cfg(feature = "backdrop-blur")]
+        let backdrop = self.backdrop_blur_pipeline.backdrop();
+        #[cfg(not(feature = "backdrop-blur"))]
+        let backdrop: Option<&wgpu::TextureView> = None;
+
+        let intermediate = backdrop.or(fxaa_target);
+        let direct_target = intermediate.unwrap_or(target);
+        let mut intermediate_clear = Some(wgpu::Color::TRANSPARENT);
+
+        // The clear color is drawn into the backdrop so that it is blurred
+        // too, and the target is cleared to transparent instead.
+        if backdrop.is_some() {
+            if let Some(color) = clear_color.as_mut() {
+                intermediate_clear = Some(*color);
+                *color = wgpu::Color::TRANSPARENT;
+            }
+        }
+
+        // When only part of the target is redrawn, the previous contents of
+        // the target are kept, and only the redrawn region is filled with the
+        // clear color.
+        if self.damage_rect.is_some() || self.dest_rect.is_some() {
+            let color = if is_alpha_mask {
+                mask_clear_color.take().map(alpha_mask::coverage_color)
             } else {
-                (
-                    target,
-                    None,
-                    if let Some(color) = clear_color {
-                        wgpu::LoadOp::Clear(color)
-                    } else {
-                        wgpu::LoadOp::Load
-                    },
-                )
+                clear_color.take()
             };
 
-            #[cfg(not(feature = "msaa"))]
-            let (attachment, resolve_target, load) = (
-                target,
-                None,
-                if let Some(color) = clear_color {
+            let canvas_rect = RectI32::new(PointI32::new(0, 0), self.logical_size_i32);
+            if let (Some(color), Some(rect)) = (color, self.physical_scissor_rect(canvas_rect)) {
+                self.clear_pipeline
+                    .render_to_target(final_target, color, rect, encoder);
+            }
+        }
+
+        // The glyph atlas must stay borrowed while text is rendered.
+        #[cfg(feature = "text")]
+        let text_resources = self.text_pipeline.resources().clone();
+        #[cfg(feature = "text")]
+        let text_resources = text_resources.borrow();
+
+        let num_batches = self.output.order.len();
+        let mut start = 0;
+        let mut res = Ok(());
+        loop {
+            let pass = self.output.order[start..]
+                .iter()
+                .find_map(|order| self.pass_kind(order))
+                .unwrap_or_else(|| self.default_pass_kind());
+            let end = self.output.order[start..]
+                .iter()
+                .position(|order| self.pass_kind(order).is_some_and(|p| p != pass))
+                .map(|i| start + i)
+                .unwrap_or(num_batches);
+
+            if pass.backdrop_blur {
+                #[cfg(feature = "backdrop-blur")]
+                {
+                    // The backdrop must be cleared before it is sampled.
+                    if let Some(color) = intermediate_clear.take() {
+                        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                            label: Some("rootvg clear render pass"),
+                            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                                view: direct_target,
+                                resolve_target: None,
+                                ops: wgpu::Operations {
+                                    load: wgpu::LoadOp::Clear(color),
+                                    store: wgpu::StoreOp::Store,
+                                },
+                            })],
+                            depth_stencil_attachment: None,
+                            timestamp_writes: None,
+                            occlusion_query_set: None,
+                        });
+                    }
+
+                    for order in self.output.order[start..end].iter() {
+                        if let BatchKind::BackdropBlur { batch_index } = order {
+                            self.backdrop_blur_pipeline.render_batch(
+                                &self.output.backdrop_blur_batches[*batch_index],
+                                encoder,
+                            );
+                        }
+                    }
+                }
+            } else if pass.msaa {
+                #[cfg(feature = "msaa")]
+                {
+                    let msaa_pipeline = msaa_pipeline.as_deref_mut().unwrap();
+
+                    {
+                        let (attachment, resolve_target) =
+                            msaa_pipeline.targets(device, target_size);
+
+                        let mut render_pass =
+                            encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                                label: Some("rootvg msaa render pass"),
+                                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                                    view: attachment,
+                                    resolve_target: Some(resolve_target),
+                                    ops: wgpu::Operations {
+                                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                                        store: wgpu::StoreOp::Store,
+                                    },
+                                })],
+                                depth_stencil_attachment: depth_attachment(
+                                    msaa_depth_view.as_ref().filter(|_| pass.depth),
+                                    &mut msaa_depth_load,
+                                ),
+                                timestamp_writes: None,
+                                occlusion_query_set: None,
+                            });
+
+                        res = self.render(
+                            &mut render_pass,
+                            start..end,
+                            #[cfg(feature = "text")]
+                            &text_resources,
+                        );
+                    }
+
+                    let color = if intermediate.is_some() {
+                        intermediate_clear.take()
+                    } else {
+                        clear_color.take()
+                    };
+                    msaa_pipeline.render_to_target(direct_target, color, encoder);
+                }
+            } else {
+                let color = if intermediate.is_some() {
+                    intermediate_clear.take()
+                } else {
+                    clear_color.take()
+                };
+                let load = if let Some(color) = color {
                     wgpu::LoadOp::Clear(color)
                 } else {
                     wgpu::LoadOp::Load
-                },
-            );
+                };
+
+                let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("rootvg render pass"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: direct_target,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load,
+                            store: wgpu::StoreOp::Store,
+                        },
+                    })],
+                    depth_stencil_attachment: depth_attachment(
+                        depth_view.as_ref().filter(|_| pass.depth),
+                        &mut depth_load,
+                    ),
+                    timestamp_writes: None,
+                    occlusion_query_set: None,
+                });
 
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("rootvg render pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: attachment,
-                    resolve_target,
-                    ops: wgpu::Operations {
-                        load,
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: None,
-                timestamp_writes: None,
-                occlusion_query_set: None,
-            });
+                res = self.render(
+                    &mut render_pass,
+                    start..end,
+                    #[cfg(feature = "text")]
+                    &text_resources,
+                );
+            }
+
+            if res.is_err() || end >= num_batches {
+                break;
+            }
+            start = end;
+        }
+
+        #[cfg(feature = "backdrop-blur")]
+        if backdrop.is_some() {
+            let canvas_rect = RectI32::new(PointI32::new(0, 0), self.logical_size_i32);
+            let scissor_rect = self.physical_scissor_rect(canvas_rect);
+
+            match fxaa_target {
+                Some(fxaa_target) => self.backdrop_blur_pipeline.render_to_target(
+                    fxaa_target,
+                    Some(wgpu::Color::TRANSPARENT),
+                    scissor_rect,
+                    encoder,
+                ),
+                None => self.backdrop_blur_pipeline.render_to_target(
+                    target,
+                    clear_color.take(),
+                    scissor_rect,
+                    encoder,
+                ),
+            }
+        }
+
+        #[cfg(feature = "msaa")]
+        if let Some(fxaa_pipeline) = fxaa_pipeline.as_deref() {
+            fxaa_pipeline.render_to_target(target, clear_color.take(), encoder);
+        }
+
+        if let Some(alpha_mask_pipeline) = alpha_mask_pipeline.as_deref() {
+            alpha_mask_pipeline.render_to_target(final_target, mask_clear_color, encoder);
+        }
+
+        res
+    }
+
+    /// The render pass that the given batch must be rendered in, or `None`
+    /// if it is not a draw call.
+    fn pass_kind(&self, order: &BatchKind) -> Option<PassKind> {
+        #[cfg(feature = "msaa")]
+        let (msaa, meshes_only_msaa) = (self.multisample.count > 1, self.meshes_only_msaa);
+        #[cfg(not(feature = "msaa"))]
+        let (msaa, meshes_only_msaa) = (false, false);
+
+        let (is_mesh, depth) = match order {
+            #[cfg(any(feature = "mesh", feature = "tessellation"))]
+            BatchKind::SolidMesh { .. } => (true, self.depth_buffer && !meshes_only_msaa),
+            #[cfg(all(any(feature = "mesh", feature = "tessellation"), feature = "gradient"))]
+            BatchKind::GradientMesh { .. } => (true, self.depth_buffer && !meshes_only_msaa),
+            #[cfg(feature = "custom-primitive")]
+            BatchKind::Custom { pipeline_id, .. } => (
+                true,
+                self.custom_pipelines
+                    .get(pipeline_id.0)
+                    .is_some_and(|entry| entry.pipeline.wants_depth()),
+            ),
+            #[cfg(feature = "backdrop-blur")]
+            BatchKind::BackdropBlur { .. } => {
+                return Some(PassKind {
+                    msaa: false,
+                    depth: false,
+                    backdrop_blur: true,
+                })
+            }
+            BatchKind::ScissorRect(_) => return None,
+            #[allow(unreachable_patterns)]
+            _ => (false, self.depth_buffer),
+        };
+
+        Some(PassKind {
+            msaa: msaa && (is_mesh || !meshes_only_msaa),
+            depth,
+            backdrop_blur: false,
+        })
+    }
+
+    /// The render pass used when there is nothing to draw, so that the
+    /// target is still cleared.
+    fn default_pass_kind(&self) -> PassKind {
+        #[cfg(feature = "msaa")]
+        let msaa = self.multisample.count > 1 && !self.meshes_only_msaa;
+        #[cfg(not(feature = "msaa"))]
+        let msaa = false;
+
+        PassKind {
+            msaa,
+            depth: false,
+            backdrop_blur: false,
+        }
+    }
+
+    /// Render to the given texture.
+    ///
+    /// If [`CanvasConfig::enable_readback`] is `true`, then a copy of the
+    /// rendered frame is kept so that it can be read back with
+    /// [`Canvas::read_pixels`]. In that case the texture must have been
+    /// created with `wgpu::TextureUsages::COPY_SRC`.
+    ///
+    /// When MSAA is enabled this is the resolved texture, so the copy never
+    /// contains the multisampled attachment.
+    ///
+    /// Returns `true` if any primitives were drawn.
+    pub fn render_to_texture(
+        &mut self,
+        clear_color: Option<PackedSrgb>,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::Texture,
+        #[cfg(feature = "text")] font_system: &mut FontSystem,
+        #[cfg(all(feature = "text", feature = "svg-icons"))] svg_icon_system: &mut SvgIconSystem,
+    ) -> Result<bool, RenderError> {
+        let view = target.create_view(&wgpu::TextureViewDescriptor::default());
+        let target_size = PhysicalSizeI32::new(target.width() as i32, target.height() as i32);
+
+        let has_primitives = self.render_to_target(
+            clear_color,
+            device,
+            queue,
+            encoder,
+            &view,
+            target_size,
+            #[cfg(feature = "text")]
+            font_system,
+            #[cfg(all(feature = "text", feature = "svg-icons"))]
+            svg_icon_system,
+        )?;
 
-            self.render(&mut render_pass).unwrap();
+        if self.enable_readback {
+            self.copy_to_readback_texture(device, encoder, target);
         }
 
-        #[cfg(feature = "msaa")]
+        Ok(has_primitives)
+    }
+
+    /// Copy a region of the last frame rendered with [`Canvas::render_to_texture`]
+    /// into CPU memory as tightly-packed RGBA8 pixels.
+    ///
+    /// The rectangle is in physical pixels. The bytes are the same as what is
+    /// stored in the target texture, so the channels of BGRA formats are swapped
+    /// and sRGB formats result in gamma-encoded bytes.
+    ///
+    /// This requires [`CanvasConfig::enable_readback`] to be `true`, and the
+    /// command encoder passed to [`Canvas::render_to_texture`] must have been
+    /// submitted to the queue first. This blocks until the GPU has finished all
+    /// submitted work.
+    pub fn read_pixels(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        rect: RectI32,
+    ) -> Result<Vec<u8>, ReadbackError> {
+        let Some(texture) = &self.readback_texture else {
+            return Err(ReadbackError::NoRenderedFrame);
+        };
+
+        crate::readback::read_texture_rgba8(device, queue, texture, rect)
+    }
+
+    /// Get a view of the depth texture with the given sample count, creating
+    /// a new one if the size of the render target has changed.
+    fn depth_view(
+        &mut self,
+        device: &wgpu::Device,
+        target_size: PhysicalSizeI32,
+        sample_count: u32,
+    ) -> wgpu::TextureView {
+        let size = wgpu::Extent3d {
+            width: target_size.width as u32,
+            height: target_size.height as u32,
+            depth_or_array_layers: 1,
+        };
+
+        self.depth_textures.retain(|t| t.size() == size);
+
+        let i = match self
+            .depth_textures
+            .iter()
+            .position(|t| t.sample_count() == sample_count)
         {
-            // TODO: See if it's more performant to only use an MSAA render pass for pipelines
-            // that need it (like the mesh pipelines). The tradeoff is that this will require
-            // multiple render passes for each batch of meshes instead of just two render
-            // passes for the whole canvas.
-            if let Some(msaa_pipeline) = &mut msaa_pipeline {
-                msaa_pipeline.render_to_target(target, clear_color, encoder);
+            Some(i) => i,
+            None => {
+                self.depth_textures
+                    .push(device.create_texture(&wgpu::TextureDescriptor {
+                        label: Some("rootvg depth texture"),
+                        size,
+                        mip_level_count: 1,
+                        sample_count,
+                        dimension: wgpu::TextureDimension::D2,
+                        format: rootvg_core::pipeline::DEPTH_FORMAT,
+                        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                        view_formats: &[],
+                    }));
+                self.depth_textures.len() - 1
             }
+        };
 
-            self.msaa_pipeline = msaa_pipeline;
+        self.depth_textures[i].create_view(&wgpu::TextureViewDescriptor::default())
+    }
+
+    fn copy_to_readback_texture(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::Texture,
+    ) {
+        let needs_new_texture = match &self.readback_texture {
+            Some(texture) => texture.size() != target.size() || texture.format() != target.format(),
+            None => true,
+        };
+
+        if needs_new_texture {
+            self.readback_texture = Some(device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("rootvg readback texture"),
+                size: target.size(),
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: target.format(),
+                usage: wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::COPY_SRC,
+                view_formats: &[],
+            }));
         }
 
-        Ok(())
+        encoder.copy_texture_to_texture(
+            target.as_image_copy(),
+            self.readback_texture.as_ref().unwrap().as_image_copy(),
+            target.size(),
+        );
     }
 
     fn prepare(
@@ -360,51 +1658,114 @@ impl Canvas {
         #[cfg(feature = "text")] font_system: &mut FontSystem,
         #[cfg(all(feature = "text", feature = "svg-icons"))] svg_icon_system: &mut SvgIconSystem,
     ) -> Result<(), RenderError> {
-        if !self.needs_preparing {
+        let needs_preparing = std::mem::take(&mut self.needs_preparing);
+
+        #[cfg(feature = "backdrop-blur")]
+        self.expand_damage_rect_for_blurs();
+
+        // Skip preparing if the frame is identical to the one the current
+        // output was prepared with. The output must still be prepared again
+        // without a new frame if the region it is rendered into has changed.
+        let origin = self
+            .dest_rect
+            .map(|dest| PhysicalPointI32::new(dest.origin.x, dest.origin.y))
+            .unwrap_or_default();
+        let frame = PreparedFrame {
+            physical_size: self.physical_size,
+            scale_factor: self.scale_factor,
+            target_size: self.target_size,
+            origin,
+        };
+        // Glyphs may have been evicted from a shared glyph atlas by another
+        // canvas.
+        #[cfg(feature = "text")]
+        let text_needs_preparing = self.text_pipeline.needs_preparing();
+        #[cfg(not(feature = "text"))]
+        let text_needs_preparing = false;
+        // Images may have been left out of the previous frame because the
+        // upload budget ran out.
+        #[cfg(feature = "image")]
+        let image_needs_preparing = self.image_pipeline.needs_preparing();
+        #[cfg(not(feature = "image"))]
+        let image_needs_preparing = false;
+
+        if self.prepared_frame == Some(frame)
+            && (!needs_preparing || self.batches == self.prepared_batches)
+            && !text_needs_preparing
+            && !image_needs_preparing
+        {
             return Ok(());
         }
-        self.needs_preparing = false;
+        // The output is only valid again once preparing has succeeded.
+        self.prepared_frame = None;
 
         #[cfg(feature = "quad")]
         self.solid_quad_pipeline.start_preparations(
             device,
             queue,
-            self.physical_size,
+            self.target_size,
             self.scale_factor,
+            origin,
         );
         #[cfg(all(feature = "quad", feature = "gradient"))]
         self.gradient_quad_pipeline.start_preparations(
             device,
             queue,
-            self.physical_size,
+            self.target_size,
+            self.scale_factor,
+            origin,
+        );
+        #[cfg(feature = "quad")]
+        self.line_pipeline.start_preparations(
+            device,
+            queue,
+            self.target_size,
             self.scale_factor,
+            origin,
         );
 
         #[cfg(any(feature = "mesh", feature = "tessellation"))]
         self.solid_mesh_pipeline.start_preparations(
             device,
             queue,
-            self.physical_size,
+            self.target_size,
             self.scale_factor,
+            origin,
         );
         #[cfg(all(any(feature = "mesh", feature = "tessellation"), feature = "gradient"))]
         self.gradient_mesh_pipeline.start_preparations(
             device,
             queue,
-            self.physical_size,
+            self.target_size,
             self.scale_factor,
+            origin,
         );
 
         #[cfg(feature = "text")]
-        self.text_pipeline
-            .start_preparations(device, queue, self.physical_size, self.scale_factor);
+        self.text_pipeline.start_preparations(
+            device,
+            queue,
+            self.target_size,
+            self.scale_factor,
+            origin,
+        );
 
         #[cfg(feature = "image")]
         self.image_pipeline.start_preparations(
             device,
             queue,
-            self.physical_size,
+            self.target_size,
             self.scale_factor,
+            origin,
+        );
+
+        #[cfg(feature = "backdrop-blur")]
+        self.backdrop_blur_pipeline.start_preparations(
+            device,
+            queue,
+            self.target_size,
+            self.scale_factor,
+            origin,
         );
 
         #[cfg(feature = "custom-primitive")]
@@ -415,7 +1776,9 @@ impl Canvas {
         self.output.order.clear();
 
         // Sort the keys by z index
-        self.temp_keys_for_sorting = self.batches.keys().copied().collect();
+        self.temp_keys_for_sorting.clear();
+        self.temp_keys_for_sorting
+            .extend(self.batches.keys().copied());
         self.temp_keys_for_sorting
             .sort_unstable_by(|a, b| a.z_index.cmp(&b.z_index));
 
@@ -425,6 +1788,8 @@ impl Canvas {
         let mut num_solid_quad_batches = 0;
         #[cfg(all(feature = "quad", feature = "gradient"))]
         let mut num_gradient_quad_batches = 0;
+        #[cfg(feature = "quad")]
+        let mut num_line_batches = 0;
 
         #[cfg(any(feature = "mesh", feature = "tessellation"))]
         let mut num_solid_mesh_batches = 0;
@@ -437,14 +1802,40 @@ impl Canvas {
         #[cfg(feature = "image")]
         let mut num_image_batches = 0;
 
+        #[cfg(feature = "backdrop-blur")]
+        let mut num_backdrop_blur_batches = 0;
+        // The region of the target which blurs may sample.
+        #[cfg(feature = "backdrop-blur")]
+        let blur_canvas_rect = intersect_scissor_rect(
+            RectI32::new(
+                PointI32::new(origin.x, origin.y),
+                SizeI32::new(self.physical_size.width, self.physical_size.height),
+            ),
+            RectI32::new(
+                PointI32::new(0, 0),
+                SizeI32::new(self.target_size.width, self.target_size.height),
+            ),
+        );
+
         #[cfg(feature = "custom-primitive")]
         let mut num_custom_batches = 0;
 
-        for key in self.temp_keys_for_sorting.iter() {
+        let num_keys = self.temp_keys_for_sorting.len();
+
+        for (key_index, key) in self.temp_keys_for_sorting.iter().enumerate() {
             let Some(batch_entry) = self.batches.get(key) else {
                 continue;
             };
 
+            // Batches which are drawn later are in front, so they get a smaller
+            // depth.
+            #[allow(unused)] // depth is unused if only the custom-primitive feature is enabled
+            let depth = if self.depth_buffer {
+                1.0 - ((key_index + 1) as f32 / (num_keys + 1) as f32)
+            } else {
+                0.0
+            };
+
             if key.scissor_rect != current_scissor_rect {
                 current_scissor_rect = key.scissor_rect;
 
@@ -453,6 +1844,46 @@ impl Canvas {
                     .push(BatchKind::ScissorRect(key.scissor_rect));
             };
 
+            #[allow(unused)] // rounded_clip is unused if only the text feature is enabled
+            let rounded_clip = key.rounded_clip.map(|k| {
+                let mut clip = k.clip();
+                if let Some(height) = self.flip_height() {
+                    clip.rect.origin.y = height - clip.rect.max_y();
+                }
+                clip
+            });
+
+            // Backdrop blurs are drawn before all other primitives in the
+            // batch, so that the contents of a blurred panel appear on top.
+            #[cfg(feature = "backdrop-blur")]
+            if !batch_entry.backdrop_blurs.is_empty() {
+                if let (Some(scissor_rect), Some(canvas_rect)) = (
+                    self.physical_scissor_rect(key.scissor_rect),
+                    blur_canvas_rect,
+                ) {
+                    if num_backdrop_blur_batches == self.output.backdrop_blur_batches.len() {
+                        self.output
+                            .backdrop_blur_batches
+                            .push(self.backdrop_blur_pipeline.create_batch(device));
+                    }
+
+                    if self.backdrop_blur_pipeline.prepare_batch(
+                        &mut self.output.backdrop_blur_batches[num_backdrop_blur_batches],
+                        &batch_entry.backdrop_blurs,
+                        scissor_rect,
+                        canvas_rect,
+                        device,
+                        queue,
+                    ) {
+                        self.output.order.push(BatchKind::BackdropBlur {
+                            batch_index: num_backdrop_blur_batches,
+                        });
+
+                        num_backdrop_blur_batches += 1;
+                    }
+                }
+            }
+
             #[cfg(feature = "quad")]
             if !batch_entry.solid_quads.is_empty() {
                 if num_solid_quad_batches == self.output.solid_quad_batches.len() {
@@ -464,9 +1895,11 @@ impl Canvas {
                 self.solid_quad_pipeline.prepare_batch(
                     &mut self.output.solid_quad_batches[num_solid_quad_batches],
                     &batch_entry.solid_quads,
+                    rounded_clip,
+                    depth,
                     device,
                     queue,
-                );
+                )?;
 
                 self.output.order.push(BatchKind::SolidQuad {
                     batch_index: num_solid_quad_batches,
@@ -486,9 +1919,11 @@ impl Canvas {
                 self.gradient_quad_pipeline.prepare_batch(
                     &mut self.output.gradient_quad_batches[num_gradient_quad_batches],
                     &batch_entry.gradient_quads,
+                    rounded_clip,
+                    depth,
                     device,
                     queue,
-                );
+                )?;
 
                 self.output.order.push(BatchKind::GradientQuad {
                     batch_index: num_gradient_quad_batches,
@@ -497,6 +1932,30 @@ impl Canvas {
                 num_gradient_quad_batches += 1;
             }
 
+            #[cfg(feature = "quad")]
+            if !batch_entry.lines.is_empty() {
+                if num_line_batches == self.output.line_batches.len() {
+                    self.output
+                        .line_batches
+                        .push(self.line_pipeline.create_batch(device));
+                }
+
+                self.line_pipeline.prepare_batch(
+                    &mut self.output.line_batches[num_line_batches],
+                    &batch_entry.lines,
+                    rounded_clip,
+                    depth,
+                    device,
+                    queue,
+                )?;
+
+                self.output.order.push(BatchKind::Line {
+                    batch_index: num_line_batches,
+                });
+
+                num_line_batches += 1;
+            }
+
             #[cfg(any(feature = "mesh", feature = "tessellation"))]
             if !batch_entry.solid_meshes.is_empty() {
                 if num_solid_mesh_batches == self.output.solid_mesh_batches.len() {
@@ -508,9 +1967,11 @@ impl Canvas {
                 self.solid_mesh_pipeline.prepare_batch(
                     &mut self.output.solid_mesh_batches[num_solid_mesh_batches],
                     &batch_entry.solid_meshes,
+                    rounded_clip,
+                    depth,
                     device,
                     queue,
-                );
+                )?;
 
                 self.output.order.push(BatchKind::SolidMesh {
                     batch_index: num_solid_mesh_batches,
@@ -530,9 +1991,11 @@ impl Canvas {
                 self.gradient_mesh_pipeline.prepare_batch(
                     &mut self.output.gradient_mesh_batches[num_gradient_mesh_batches],
                     &batch_entry.gradient_meshes,
+                    rounded_clip,
+                    depth,
                     device,
                     queue,
-                );
+                )?;
 
                 self.output.order.push(BatchKind::GradientMesh {
                     batch_index: num_gradient_mesh_batches,
@@ -552,6 +2015,7 @@ impl Canvas {
                 self.text_pipeline.prepare_batch(
                     &mut self.output.text_batches[num_text_batches],
                     &batch_entry.text,
+                    depth,
                     device,
                     queue,
                     font_system,
@@ -577,9 +2041,11 @@ impl Canvas {
                 self.image_pipeline.prepare_batch(
                     &mut self.output.image_batches[num_image_batches],
                     &batch_entry.images,
+                    rounded_clip,
+                    depth,
                     device,
                     queue,
-                );
+                )?;
 
                 self.output.order.push(BatchKind::Image {
                     batch_index: num_image_batches,
@@ -590,6 +2056,8 @@ impl Canvas {
 
             #[cfg(feature = "custom-primitive")]
             if !batch_entry.custom_primitives.is_empty() {
+                let mut last_custom_batch: Option<(CustomPipelineID, usize)> = None;
+
                 for custom_primitive in batch_entry.custom_primitives.iter() {
                     let Some(pipeline) = self
                         .custom_pipelines
@@ -600,14 +2068,7 @@ impl Canvas {
                         ));
                     };
 
-                    if num_custom_batches == self.output.custom_batches.len() {
-                        self.output.custom_batches.push(CustomBatchBuffer {
-                            primitive_index: pipeline.primitives_to_prepare.len(),
-                        });
-                    } else {
-                        self.output.custom_batches[num_custom_batches].primitive_index =
-                            pipeline.primitives_to_prepare.len();
-                    }
+                    let primitive_index = pipeline.primitives_to_prepare.len();
 
                     pipeline
                         .primitives_to_prepare
@@ -616,11 +2077,33 @@ impl Canvas {
                             offset: custom_primitive.offset,
                         });
 
+                    // Group consecutive primitives of the same pipeline into a
+                    // single batch.
+                    if let Some((pipeline_id, batch_index)) = last_custom_batch {
+                        let batch = &mut self.output.custom_batches[batch_index];
+                        if pipeline_id == custom_primitive.pipeline_id
+                            && batch.primitives.end == primitive_index
+                        {
+                            batch.primitives.end += 1;
+                            continue;
+                        }
+                    }
+
+                    let primitives = primitive_index..primitive_index + 1;
+                    if num_custom_batches == self.output.custom_batches.len() {
+                        self.output
+                            .custom_batches
+                            .push(CustomBatchBuffer { primitives });
+                    } else {
+                        self.output.custom_batches[num_custom_batches].primitives = primitives;
+                    }
+
                     self.output.order.push(BatchKind::Custom {
                         pipeline_id: custom_primitive.pipeline_id,
                         batch_index: num_custom_batches,
                     });
 
+                    last_custom_batch = Some((custom_primitive.pipeline_id, num_custom_batches));
                     num_custom_batches += 1;
                 }
             }
@@ -633,22 +2116,25 @@ impl Canvas {
                 continue;
             }
 
-            if entry.prev_prepared_screen_size == self.physical_size
+            if entry.prev_prepared_screen_size == self.target_size
                 && entry.prev_prepared_scale_factor == self.scale_factor
+                && entry.prev_prepared_origin == origin
                 && entry.prev_prepared_primitives == entry.primitives_to_prepare
             {
                 continue;
             }
 
-            entry.prev_prepared_screen_size = self.physical_size;
+            entry.prev_prepared_screen_size = self.target_size;
             entry.prev_prepared_scale_factor = self.scale_factor;
+            entry.prev_prepared_origin = origin;
             entry.prev_prepared_primitives = entry.primitives_to_prepare.clone();
 
-            if let Err(e) = entry.pipeline.prepare(
+            if let Err(e) = entry.pipeline.prepare_with_origin(
                 device,
                 queue,
-                self.physical_size,
+                self.target_size,
                 self.scale_factor,
+                origin,
                 &entry.primitives_to_prepare,
             ) {
                 return Err(RenderError::CustomPipelinePrepareError(e));
@@ -673,6 +2159,12 @@ impl Canvas {
                     self.gradient_quad_pipeline.create_batch(device)
                 });
         }
+        #[cfg(feature = "quad")]
+        if num_line_batches < self.output.line_batches.len() {
+            self.output
+                .line_batches
+                .resize_with(num_line_batches, || self.line_pipeline.create_batch(device));
+        }
 
         #[cfg(any(feature = "mesh", feature = "tessellation"))]
         if num_solid_mesh_batches < self.output.solid_mesh_batches.len() {
@@ -711,25 +2203,67 @@ impl Canvas {
                 });
         }
 
+        #[cfg(feature = "backdrop-blur")]
+        {
+            if num_backdrop_blur_batches < self.output.backdrop_blur_batches.len() {
+                self.output
+                    .backdrop_blur_batches
+                    .resize_with(num_backdrop_blur_batches, || {
+                        self.backdrop_blur_pipeline.create_batch(device)
+                    });
+            }
+
+            self.backdrop_blur_pipeline.finish_preparations();
+        }
+
         #[cfg(feature = "custom-primitive")]
         if num_custom_batches < self.output.custom_batches.len() {
             self.output
                 .custom_batches
                 .resize_with(num_custom_batches, || CustomBatchBuffer {
-                    primitive_index: 0,
+                    primitives: 0..0,
                 });
         }
 
+        #[cfg(feature = "frame-stats")]
+        {
+            self.last_frame_stats = FrameStats::collect(self.batches.values(), &self.output.order);
+        }
+
+        self.prepared_frame = Some(frame);
+
         Ok(())
     }
 
+    /// Render the given range of batches in [`CanvasOutput::order`].
     fn render<'pass>(
-        &'pass mut self,
+        &'pass self,
         render_pass: &mut wgpu::RenderPass<'pass>,
+        range: std::ops::Range<usize>,
+        #[cfg(feature = "text")] text_resources: &'pass TextResources,
     ) -> Result<(), RenderError> {
         let mut scissor_rect_in_bounds = true;
 
-        for order in self.output.order.iter() {
+        // The scissor rect is reset at the start of each render pass, so
+        // restore the one which was set before this range.
+        let prev_scissor_rect = self.output.order[..range.start]
+            .iter()
+            .rev()
+            .find_map(|order| match order {
+                BatchKind::ScissorRect(scissor_rect) => Some(*scissor_rect),
+                #[allow(unreachable_patterns)]
+                _ => None,
+            });
+        // When rendering into a region of the target, the scissor rect must
+        // always be set so that nothing is drawn outside of the region.
+        let canvas_rect = self
+            .dest_rect
+            .map(|_| RectI32::new(PointI32::new(0, 0), self.logical_size_i32));
+        if let Some(scissor_rect) = prev_scissor_rect.or(self.damage_rect).or(canvas_rect) {
+            scissor_rect_in_bounds = self.apply_scissor_rect(render_pass, scissor_rect);
+        }
+
+        for order in self.output.order[range].iter() {
             match order {
                 #[cfg(feature = "quad")]
                 BatchKind::SolidQuad { batch_index } => {
@@ -751,14 +2285,26 @@ impl Canvas {
                         render_pass,
                     );
                 }
+                #[cfg(feature = "quad")]
+                BatchKind::Line { batch_index } => {
+                    if !scissor_rect_in_bounds {
+                        continue;
+                    }
+
+                    self.line_pipeline
+                        .render_batch(&self.output.line_batches[*batch_index], render_pass);
+                }
                 #[cfg(feature = "text")]
                 BatchKind::Text { batch_index } => {
                     if !scissor_rect_in_bounds {
                         continue;
                     }
 
-                    self.text_pipeline
-                        .render_batch(&self.output.text_batches[*batch_index], render_pass)?;
+                    self.text_pipeline.render_batch(
+                        &self.output.text_batches[*batch_index],
+                        text_resources,
+                        render_pass,
+                    )?;
                 }
                 #[cfg(any(feature = "mesh", feature = "tessellation"))]
                 BatchKind::SolidMesh { batch_index } => {
@@ -800,78 +2346,243 @@ impl Canvas {
 
                     let entry = self.custom_pipelines.get(pipeline_id.0).unwrap();
 
-                    if let Err(e) = entry.pipeline.render_primitive(
-                        self.output.custom_batches[*batch_index].primitive_index,
+                    if let Err(e) = entry.pipeline.render_primitives(
+                        self.output.custom_batches[*batch_index].primitives.clone(),
                         render_pass,
                     ) {
                         return Err(RenderError::CustomPipelineRenderError(e));
                     }
                 }
+                // Backdrop blurs are rendered outside of render passes (see
+                // `Canvas::render_passes`).
+                #[cfg(feature = "backdrop-blur")]
+                BatchKind::BackdropBlur { .. } => {}
                 BatchKind::ScissorRect(scissor_rect) => {
-                    let mut x = (scissor_rect.origin.x as f32 * self.scale_factor).round() as i32;
-                    let mut y = (scissor_rect.origin.y as f32 * self.scale_factor).round() as i32;
-                    let mut width =
-                        (scissor_rect.size.width as f32 * self.scale_factor).round() as i32;
-                    let mut height =
-                        (scissor_rect.size.height as f32 * self.scale_factor).round() as i32;
-
-                    if x + scissor_rect.size.width <= 0
-                        || x >= self.physical_size.width
-                        || y + scissor_rect.size.height <= 0
-                        || y >= self.physical_size.height
-                    {
-                        // Scissor rect is off screen
-                        scissor_rect_in_bounds = false;
+                    scissor_rect_in_bounds = self.apply_scissor_rect(render_pass, *scissor_rect);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Set the scissor rect of the render pass. Returns `false` if the
+    /// scissor rect is off screen, in which case it is not set.
+    fn apply_scissor_rect(
+        &self,
+        render_pass: &mut wgpu::RenderPass<'_>,
+        scissor_rect: RectI32,
+    ) -> bool {
+        let Some(rect) = self.physical_scissor_rect(scissor_rect) else {
+            return false;
+        };
+
+        // TODO: There seems to be a bug in wgpu when setting scissoring rectangles
+        // with the OpenGL backend, causing rendering issues. Investigate this
+        // further.
+        //
+        // I have only tested the OpenGL backend on my Arch Linux system running on
+        // an AMD RX570 graphics card with the KDE desktop environment.
+        render_pass.set_scissor_rect(
+            rect.origin.x as u32,
+            rect.origin.y as u32,
+            rect.size.width as u32,
+            rect.size.height as u32,
+        );
+
+        true
+    }
+
+    /// The region of the render target (in physical pixels) which the given
+    /// scissor rect covers, or `None` if the scissor rect is off screen.
+    fn physical_scissor_rect(&self, scissor_rect: RectI32) -> Option<RectI32> {
+        // Nothing outside of the damaged region is redrawn.
+        let scissor_rect = match self.damage_rect {
+            Some(damage_rect) => intersect_scissor_rect(scissor_rect, damage_rect)?,
+            None => scissor_rect,
+        };
+
+        // Scissor rects are stored in the coordinate space of the
+        // user and only flipped once here.
+        let origin_y = if self.y_up {
+            self.logical_size_i32.height - scissor_rect.max_y()
+        } else {
+            scissor_rect.origin.y
+        };
+
+        let mut x = (scissor_rect.origin.x as f32 * self.scale_factor).round() as i32;
+        let mut y = (origin_y as f32 * self.scale_factor).round() as i32;
+        let mut width = (scissor_rect.size.width as f32 * self.scale_factor).round() as i32;
+        let mut height = (scissor_rect.size.height as f32 * self.scale_factor).round() as i32;
+
+        if x + scissor_rect.size.width <= 0
+            || x >= self.physical_size.width
+            || y + scissor_rect.size.height <= 0
+            || y >= self.physical_size.height
+        {
+            // Scissor rect is off screen
+            return None;
+        }
+
+        // Scissor rect must be in bounds or wgpu will panic.
+        if x < 0 {
+            width += x;
+            x = 0;
+        }
+        if y < 0 {
+            height += y;
+            y = 0;
+        }
+        if x + width > self.physical_size.width {
+            width = self.physical_size.width - x;
+        }
+        if y + height > self.physical_size.height {
+            height = self.physical_size.height - y;
+        }
+
+        // Move the scissor rect into the region of the target that the
+        // canvas is rendered into, and clip it to the target.
+        if let Some(dest) = self.dest_rect {
+            x += dest.origin.x;
+            y += dest.origin.y;
+
+            if x < 0 {
+                width += x;
+                x = 0;
+            }
+            if y < 0 {
+                height += y;
+                y = 0;
+            }
+            width = width.min(self.target_size.width - x);
+            height = height.min(self.target_size.height - y);
+
+            if width <= 0 || height <= 0 {
+                return None;
+            }
+        }
+
+        Some(RectI32::new(
+            PointI32::new(x, y),
+            SizeI32::new(width, height),
+        ))
+    }
+
+    /// Expand the damaged region so that it covers every pixel sampled by
+    /// the backdrop blurs which are redrawn.
+    ///
+    /// A blur must be redrawn if any pixel it samples is redrawn, which in
+    /// turn requires all of the pixels it samples to be redrawn, so this is
+    /// repeated until the region stops growing.
+    #[cfg(feature = "backdrop-blur")]
+    fn expand_damage_rect_for_blurs(&mut self) {
+        let Some(mut damage_rect) = self.damage_rect else {
+            return;
+        };
+
+        let canvas_rect = RectI32::new(PointI32::new(0, 0), self.logical_size_i32);
+        let flip_height = self.flip_height();
+
+        // Convert a rect from the coordinate space used when rendering back to
+        // the coordinate space of scissor rects.
+        let to_scissor_space = |mut rect: Rect| -> RectI32 {
+            if let Some(height) = flip_height {
+                rect.origin.y = height - rect.max_y();
+            }
+            rect.round_out().to_i32()
+        };
+
+        loop {
+            let mut expanded = damage_rect;
+
+            for (key, batch_entry) in self.batches.iter() {
+                for blur in batch_entry.backdrop_blurs.iter() {
+                    let bounds = to_scissor_space(blur.bounds);
+                    if intersect_scissor_rect(bounds, key.scissor_rect).is_none() {
                         continue;
                     }
-                    scissor_rect_in_bounds = true;
 
-                    // Scissor rect must be in bounds or wgpu will panic.
-                    if x < 0 {
-                        width += x;
-                        x = 0;
-                    }
-                    if y < 0 {
-                        height += y;
-                        y = 0;
-                    }
-                    if x + width > self.physical_size.width {
-                        width = self.physical_size.width - x;
-                    }
-                    if y + height > self.physical_size.height {
-                        height = self.physical_size.height - y;
+                    // Add a point for the rounding of the blur in physical
+                    // pixels.
+                    let sampled_bounds = to_scissor_space(blur.sampled_bounds().inflate(1.0, 1.0));
+                    if intersect_scissor_rect(sampled_bounds, expanded).is_none() {
+                        continue;
                     }
 
-                    // TODO: There seems to be a bug in wgpu when setting scissoring rectangles
-                    // with the OpenGL backend, causing rendering issues. Investigate this
-                    // further.
-                    //
-                    // I have only tested the OpenGL backend on my Arch Linux system running on
-                    // an AMD RX570 graphics card with the KDE desktop environment.
-                    render_pass.set_scissor_rect(x as u32, y as u32, width as u32, height as u32);
+                    if let Some(rect) = intersect_scissor_rect(sampled_bounds, canvas_rect) {
+                        expanded = expanded.union(&rect);
+                    }
                 }
             }
+
+            if expanded == damage_rect {
+                break;
+            }
+            damage_rect = expanded;
         }
 
-        Ok(())
+        self.damage_rect = Some(damage_rect);
     }
 }
 
+/// The state which the output of the canvas was prepared with.
+#[derive(Clone, Copy, PartialEq)]
+struct PreparedFrame {
+    physical_size: PhysicalSizeI32,
+    scale_factor: ScaleFactor,
+    target_size: PhysicalSizeI32,
+    origin: PhysicalPointI32,
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub(crate) struct BatchKey {
     scissor_rect: RectI32,
+    rounded_clip: Option<RoundedClipKey>,
     z_index: u32,
 }
 
 impl BatchKey {
-    fn new(scissor_rect: RectI32, main_z_index: ZIndex, inner_z_index: ZIndex) -> Self {
+    fn new(
+        scissor_rect: RectI32,
+        rounded_clip: Option<RoundedClip>,
+        main_z_index: ZIndex,
+        inner_z_index: ZIndex,
+    ) -> Self {
         Self {
             scissor_rect,
+            rounded_clip: rounded_clip.map(RoundedClipKey::new),
             z_index: (main_z_index as u32) << 16 | inner_z_index as u32,
         }
     }
 }
 
+/// The raw bits of a [`RoundedClip`] so that it can be hashed.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct RoundedClipKey([u32; 5]);
+
+impl RoundedClipKey {
+    fn new(clip: RoundedClip) -> Self {
+        Self([
+            clip.rect.origin.x.to_bits(),
+            clip.rect.origin.y.to_bits(),
+            clip.rect.size.width.to_bits(),
+            clip.rect.size.height.to_bits(),
+            clip.radius.to_bits(),
+        ])
+    }
+
+    fn clip(&self) -> RoundedClip {
+        RoundedClip::new(
+            Rect::new(
+                crate::math::Point::new(f32::from_bits(self.0[0]), f32::from_bits(self.0[1])),
+                Size::new(f32::from_bits(self.0[2]), f32::from_bits(self.0[3])),
+            ),
+            f32::from_bits(self.0[4]),
+        )
+    }
+}
+
+#[derive(PartialEq)]
 struct BatchEntry {
     #[cfg(any(feature = "mesh", feature = "tessellation"))]
     solid_meshes: Vec<SolidMeshPrimitive>,
@@ -882,6 +2593,8 @@ struct BatchEntry {
     solid_quads: Vec<SolidQuadPrimitive>,
     #[cfg(all(feature = "quad", feature = "gradient"))]
     gradient_quads: Vec<GradientQuadPrimitive>,
+    #[cfg(feature = "quad")]
+    lines: Vec<LinePrimitive>,
 
     #[cfg(feature = "text")]
     text: Vec<TextPrimitive>,
@@ -889,6 +2602,9 @@ struct BatchEntry {
     #[cfg(feature = "image")]
     images: Vec<ImagePrimitive>,
 
+    #[cfg(feature = "backdrop-blur")]
+    backdrop_blurs: Vec<BackdropBlurPrimitive>,
+
     #[cfg(feature = "custom-primitive")]
     custom_primitives: Vec<CustomPrimitive>,
 }
@@ -905,6 +2621,8 @@ impl BatchEntry {
             solid_quads: Vec::new(),
             #[cfg(all(feature = "quad", feature = "gradient"))]
             gradient_quads: Vec::new(),
+            #[cfg(feature = "quad")]
+            lines: Vec::new(),
 
             #[cfg(feature = "text")]
             text: Vec::new(),
@@ -912,10 +2630,99 @@ impl BatchEntry {
             #[cfg(feature = "image")]
             images: Vec::new(),
 
+            #[cfg(feature = "backdrop-blur")]
+            backdrop_blurs: Vec::new(),
+
             #[cfg(feature = "custom-primitive")]
             custom_primitives: Vec::new(),
         }
     }
+
+    fn is_empty(&self) -> bool {
+        #[cfg(any(feature = "mesh", feature = "tessellation"))]
+        if !self.solid_meshes.is_empty() {
+            return false;
+        }
+        #[cfg(all(any(feature = "mesh", feature = "tessellation"), feature = "gradient"))]
+        if !self.gradient_meshes.is_empty() {
+            return false;
+        }
+
+        #[cfg(feature = "quad")]
+        if !self.solid_quads.is_empty() {
+            return false;
+        }
+        #[cfg(all(feature = "quad", feature = "gradient"))]
+        if !self.gradient_quads.is_empty() {
+            return false;
+        }
+        #[cfg(feature = "quad")]
+        if !self.lines.is_empty() {
+            return false;
+        }
+
+        #[cfg(feature = "text")]
+        if !self.text.is_empty() {
+            return false;
+        }
+
+        #[cfg(feature = "image")]
+        if !self.images.is_empty() {
+            return false;
+        }
+
+        #[cfg(feature = "backdrop-blur")]
+        if !self.backdrop_blurs.is_empty() {
+            return false;
+        }
+
+        #[cfg(feature = "custom-primitive")]
+        if !self.custom_primitives.is_empty() {
+            return false;
+        }
+
+        true
+    }
+
+    /// Clear all primitives while keeping the allocated capacity.
+    fn clear(&mut self) {
+        #[cfg(any(feature = "mesh", feature = "tessellation"))]
+        clear_and_trim(&mut self.solid_meshes);
+        #[cfg(all(any(feature = "mesh", feature = "tessellation"), feature = "gradient"))]
+        clear_and_trim(&mut self.gradient_meshes);
+
+        #[cfg(feature = "quad")]
+        clear_and_trim(&mut self.solid_quads);
+        #[cfg(all(feature = "quad", feature = "gradient"))]
+        clear_and_trim(&mut self.gradient_quads);
+        #[cfg(feature = "quad")]
+        clear_and_trim(&mut self.lines);
+
+        #[cfg(feature = "text")]
+        clear_and_trim(&mut self.text);
+
+        #[cfg(feature = "image")]
+        clear_and_trim(&mut self.images);
+
+        #[cfg(feature = "backdrop-blur")]
+        clear_and_trim(&mut self.backdrop_blurs);
+
+        #[cfg(feature = "custom-primitive")]
+        clear_and_trim(&mut self.custom_primitives);
+    }
+}
+
+/// Clear the vector, and release the excess capacity if the vector used less
+/// than half of its capacity. This keeps the capacity of a vector from staying
+/// large after the number of primitives spikes and then drops.
+#[allow(unused)] // unused if no primitive features are enabled
+fn clear_and_trim<T>(v: &mut Vec<T>) {
+    let len = v.len();
+    v.clear();
+
+    if v.capacity() > len * 2 {
+        v.shrink_to(len);
+    }
 }
 
 struct CanvasOutput {
@@ -928,6 +2735,8 @@ struct CanvasOutput {
     solid_quad_batches: Vec<SolidQuadBatchBuffer>,
     #[cfg(all(feature = "quad", feature = "gradient"))]
     gradient_quad_batches: Vec<GradientQuadBatchBuffer>,
+    #[cfg(feature = "quad")]
+    line_batches: Vec<LineBatchBuffer>,
 
     #[cfg(feature = "text")]
     text_batches: Vec<TextBatchBuffer>,
@@ -935,6 +2744,9 @@ struct CanvasOutput {
     #[cfg(feature = "image")]
     image_batches: Vec<ImageBatchBuffer>,
 
+    #[cfg(feature = "backdrop-blur")]
+    backdrop_blur_batches: Vec<BackdropBlurBatchBuffer>,
+
     #[cfg(feature = "custom-primitive")]
     custom_batches: Vec<CustomBatchBuffer>,
 
@@ -953,6 +2765,8 @@ impl CanvasOutput {
             solid_quad_batches: Vec::new(),
             #[cfg(all(feature = "quad", feature = "gradient"))]
             gradient_quad_batches: Vec::new(),
+            #[cfg(feature = "quad")]
+            line_batches: Vec::new(),
 
             #[cfg(feature = "text")]
             text_batches: Vec::new(),
@@ -960,6 +2774,9 @@ impl CanvasOutput {
             #[cfg(feature = "image")]
             image_batches: Vec::new(),
 
+            #[cfg(feature = "backdrop-blur")]
+            backdrop_blur_batches: Vec::new(),
+
             #[cfg(feature = "custom-primitive")]
             custom_batches: Vec::new(),
 
@@ -977,6 +2794,10 @@ enum BatchKind {
     GradientQuad {
         batch_index: usize,
     },
+    #[cfg(feature = "quad")]
+    Line {
+        batch_index: usize,
+    },
 
     #[cfg(feature = "text")]
     Text {
@@ -997,6 +2818,11 @@ enum BatchKind {
         batch_index: usize,
     },
 
+    #[cfg(feature = "backdrop-blur")]
+    BackdropBlur {
+        batch_index: usize,
+    },
+
     #[cfg(feature = "custom-primitive")]
     Custom {
         pipeline_id: CustomPipelineID,
@@ -1006,10 +2832,65 @@ enum BatchKind {
     ScissorRect(RectI32),
 }
 
+/// The attachments needed to render a batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PassKind {
+    /// Whether the batch is rendered into the multisampled attachment.
+    msaa: bool,
+    /// Whether the batch is rendered with a depth attachment.
+    depth: bool,
+    /// Whether the batch is a backdrop blur, which is rendered outside of a
+    /// render pass.
+    backdrop_blur: bool,
+}
+
+/// The depth attachment of a render pass using the given view, if any.
+///
+/// The first attachment of each view clears it, and the following ones load
+/// its contents.
+fn depth_attachment<'a>(
+    view: Option<&'a wgpu::TextureView>,
+    load: &mut wgpu::LoadOp<f32>,
+) -> Option<wgpu::RenderPassDepthStencilAttachment<'a>> {
+    let view = view?;
+
+    let attachment = wgpu::RenderPassDepthStencilAttachment {
+        view,
+        depth_ops: Some(wgpu::Operations {
+            load: *load,
+            store: wgpu::StoreOp::Store,
+        }),
+        stencil_ops: None,
+    };
+    *load = wgpu::LoadOp::Load;
+
+    Some(attachment)
+}
+
 #[cfg(feature = "custom-primitive")]
 #[derive(Clone)]
 struct CustomBatchBuffer {
-    primitive_index: usize,
+    /// The range of primitives in the custom pipeline's list of prepared
+    /// primitives.
+    primitives: std::ops::Range<usize>,
+}
+
+/// Returns the intersection of two scissoring rectangles, or `None` if they
+/// do not overlap.
+fn intersect_scissor_rect(a: RectI32, b: RectI32) -> Option<RectI32> {
+    let min_x = a.min_x().max(b.min_x());
+    let min_y = a.min_y().max(b.min_y());
+    let max_x = a.max_x().min(b.max_x());
+    let max_y = a.max_y().min(b.max_y());
+
+    if max_x <= min_x || max_y <= min_y {
+        return None;
+    }
+
+    Some(RectI32::new(
+        PointI32::new(min_x, min_y),
+        SizeI32::new(max_x - min_x, max_y - min_y),
+    ))
 }
 
 fn offset_scissor_rect(scissor_rect: RectI32, offset: VectorI32, size: SizeI32) -> Option<RectI32> {
@@ -1027,3 +2908,139 @@ fn offset_scissor_rect(scissor_rect: RectI32, offset: VectorI32, size: SizeI32)
 
     Some(RectI32::new(PointI32::new(x, y), scissor_rect.size))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::RGBA8;
+    use crate::test_util::TestCanvas;
+
+    const SIZE: PhysicalSizeI32 = PhysicalSizeI32::new(8, 8);
+
+    fn assert_pixels_eq(pixels: &[u8], expected: &[u8], x: i32, y: i32) {
+        let i = (y * SIZE.width + x) as usize * 4;
+
+        // Clearing and blending may round differently.
+        for (a, b) in pixels[i..i + 4].iter().zip(expected[i..i + 4].iter()) {
+            assert!(
+                a.abs_diff(*b) <= 1,
+                "pixel ({x}, {y}) is {:?}, expected {:?}",
+                &pixels[i..i + 4],
+                &expected[i..i + 4]
+            );
+        }
+    }
+
+    #[test]
+    fn damaged_region_is_replaced_by_a_translucent_clear_color() {
+        let Some(mut test) = TestCanvas::new(CanvasConfig::default()) else {
+            return;
+        };
+
+        let opaque: PackedSrgb = RGBA8::new(255, 0, 0, 255).into();
+        let translucent: PackedSrgb = RGBA8::new(0, 0, 255, 128).into();
+
+        // Full redraws with each of the clear colors.
+        let reference = test.create_target(SIZE);
+        test.canvas.begin(SIZE, ScaleFactor::new(1.0));
+        test.render(&reference, Some(opaque)).unwrap();
+        let opaque_pixels = test.read(&reference);
+        test.canvas.begin(SIZE, ScaleFactor::new(1.0));
+        test.render(&reference, Some(translucent)).unwrap();
+        let translucent_pixels = test.read(&reference);
+
+        let target = test.create_target(SIZE);
+        test.canvas.begin(SIZE, ScaleFactor::new(1.0));
+        test.render(&target, Some(opaque)).unwrap();
+
+        let damage = RectI32::new(PointI32::new(2, 3), SizeI32::new(4, 2));
+        test.canvas
+            .begin_with_damage(SIZE, ScaleFactor::new(1.0), &[damage]);
+        test.render(&target, Some(translucent)).unwrap();
+        let pixels = test.read(&target);
+
+        // The damaged region is not blended with the previous frame.
+        for y in 0..SIZE.height {
+            for x in 0..SIZE.width {
+                let expected = if damage.contains(PointI32::new(x, y)) {
+                    &translucent_pixels
+                } else {
+                    &opaque_pixels
+                };
+                assert_pixels_eq(&pixels, expected, x, y);
+            }
+        }
+    }
+
+    /// Renders two text batches with a glyph atlas which can only hold a few
+    /// glyphs of this size. The first batch stays the same and is skipped,
+    /// while the second one shows new glyphs in every frame, so the atlas
+    /// fills up with glyphs which are no longer used until they are evicted.
+    #[cfg(feature = "text")]
+    #[test]
+    fn unused_glyphs_are_evicted_from_a_full_atlas() {
+        use crate::math::Point;
+        use crate::text::{Metrics, RcTextBuffer, TextProperties};
+
+        const ATLAS_SIZE: u32 = 256;
+        const TARGET_SIZE: PhysicalSizeI32 = PhysicalSizeI32::new(256, 256);
+
+        fn text(text: &str, y: f32, font_system: &mut FontSystem) -> TextPrimitive {
+            let props = TextProperties {
+                metrics: Metrics::new(100.0, 120.0),
+                ..Default::default()
+            };
+            let buffer = RcTextBuffer::new(text, props, None, None, false, font_system);
+
+            TextPrimitive::new(buffer, Point::new(0.0, y), RGBA8::new(0, 0, 0, 255), None)
+        }
+
+        // Glyphon never grows its atlas past the maximum texture size.
+        let Some(device) = crate::test_util::device_with_limits(|limits| {
+            limits.max_texture_dimension_2d = ATLAS_SIZE;
+        }) else {
+            return;
+        };
+        let mut test = TestCanvas::with_device(device, CanvasConfig::default());
+
+        let db = test.font_system.db_mut();
+        db.load_font_data(include_bytes!("../assets/fonts/DejaVuSans.ttf").to_vec());
+        db.set_sans_serif_family("DejaVu Sans");
+
+        let letters: Vec<String> = ('A'..='Z').map(String::from).collect();
+        let unchanged = text("01", 0.0, &mut test.font_system);
+        let changing: Vec<TextPrimitive> = letters
+            .chunks(2)
+            .map(|pair| text(&pair.concat(), 120.0, &mut test.font_system))
+            .collect();
+
+        let target = test.create_target(TARGET_SIZE);
+
+        for primitive in changing {
+            let mut cx = test.canvas.begin(TARGET_SIZE, ScaleFactor::new(1.0));
+            cx.set_z_index(0);
+            cx.add(unchanged.clone());
+            cx.set_z_index(1);
+            cx.add(primitive);
+
+            test.render(&target, None).unwrap();
+        }
+
+        // The glyphs of a single frame still have to fit into the atlas. The
+        // letters overlap so that none of them is culled.
+        let primitives: Vec<TextPrimitive> = letters
+            .iter()
+            .map(|letter| text(letter, 0.0, &mut test.font_system))
+            .collect();
+        test.canvas
+            .begin(TARGET_SIZE, ScaleFactor::new(1.0))
+            .add_batch(primitives);
+
+        assert!(matches!(
+            test.render(&target, None),
+            Err(RenderError::GlyphonPrepareError(
+                crate::text::glyphon::PrepareError::AtlasFull
+            ))
+        ));
+    }
+}
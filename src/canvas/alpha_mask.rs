@@ -0,0 +1,197 @@
+use wgpu::PipelineCompilationOptions;
+
+use crate::math::PhysicalSizeI32;
+
+/// The format of the intermediate texture which the canvas is rendered into
+/// when rendering an alpha mask. The alpha channel is never gamma encoded, so
+/// this doesn't need to be an sRGB format.
+pub(super) const INTERMEDIATE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+
+/// A pass which writes the alpha channel of an intermediate texture onto an
+/// alpha mask target, such as an `R8Unorm` texture.
+///
+/// See [`TargetKind::AlphaMask`](super::TargetKind::AlphaMask).
+#[derive(Debug)]
+pub(super) struct AlphaMaskPipeline {
+    pipeline: wgpu::RenderPipeline,
+    texture_layout: wgpu::BindGroupLayout,
+    target: Option<Target>,
+}
+
+impl AlphaMaskPipeline {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let texture_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("rootvg alpha mask texture layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            }],
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("rootvg alpha mask pipeline layout"),
+            push_constant_ranges: &[],
+            bind_group_layouts: &[&texture_layout],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("rootvg alpha mask shader"),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(include_str!(
+                "shader/alpha_mask.wgsl"
+            ))),
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("rootvg alpha mask pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+                compilation_options: PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    // The coverage is composited over the previous contents
+                    // of the target, the same way as the alpha channel of a
+                    // color target.
+                    blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                front_face: wgpu::FrontFace::Cw,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            pipeline,
+            texture_layout,
+            target: None,
+        }
+    }
+
+    /// The intermediate texture that the canvas should be rendered into,
+    /// creating a new one if the size has changed.
+    pub fn target(&mut self, device: &wgpu::Device, size: PhysicalSizeI32) -> &wgpu::TextureView {
+        if self.target.as_ref().map(|t| t.size) != Some(size) {
+            self.target = Some(Target::new(device, &self.texture_layout, size));
+        }
+
+        &self.target.as_ref().unwrap().view
+    }
+
+    /// Write the coverage of the intermediate texture onto the given target.
+    pub fn render_to_target(
+        &self,
+        target: &wgpu::TextureView,
+        clear_color: Option<wgpu::Color>,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        let load = if let Some(color) = clear_color {
+            wgpu::LoadOp::Clear(coverage_color(color))
+        } else {
+            wgpu::LoadOp::Load
+        };
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("rootvg alpha mask render pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.target.as_ref().unwrap().bind_group, &[]);
+        render_pass.draw(0..6, 0..1);
+    }
+}
+
+/// The color which writes the coverage of the given clear color onto an
+/// alpha mask target. Only the alpha of the clear color is coverage.
+pub(super) fn coverage_color(color: wgpu::Color) -> wgpu::Color {
+    wgpu::Color {
+        r: color.a,
+        g: color.a,
+        b: color.a,
+        a: color.a,
+    }
+}
+
+#[derive(Debug)]
+struct Target {
+    view: wgpu::TextureView,
+    bind_group: wgpu::BindGroup,
+    size: PhysicalSizeI32,
+}
+
+impl Target {
+    fn new(
+        device: &wgpu::Device,
+        texture_layout: &wgpu::BindGroupLayout,
+        size: PhysicalSizeI32,
+    ) -> Self {
+        assert!(size.width > 0);
+        assert!(size.height > 0);
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("rootvg alpha mask intermediate texture"),
+            size: wgpu::Extent3d {
+                width: size.width as u32,
+                height: size.height as u32,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: INTERMEDIATE_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("rootvg alpha mask texture bind group"),
+            layout: texture_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&view),
+            }],
+        });
+
+        Self {
+            view,
+            bind_group,
+            size,
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use wgpu::PipelineCompilationOptions;
+
+use crate::math::RectI32;
+
+/// A pass which fills a region of a target with a color, replacing the
+/// previous contents of the region like a clear load op would.
+///
+/// This is used instead of a clear load op when only part of the target is
+/// redrawn (see [`Canvas::begin_with_damage`](super::Canvas::begin_with_damage)).
+///
+/// The color is set as the blend constant, and the blend state multiplies
+/// the previous contents by zero, so translucent colors are written as is.
+#[derive(Debug)]
+pub(super) struct ClearPipeline {
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl ClearPipeline {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("rootvg clear pipeline layout"),
+            push_constant_ranges: &[],
+            bind_group_layouts: &[],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("rootvg clear shader"),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(include_str!(
+                "shader/clear.wgsl"
+            ))),
+        });
+
+        let replace_with_constant = wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::Constant,
+            dst_factor: wgpu::BlendFactor::Zero,
+            operation: wgpu::BlendOperation::Add,
+        };
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("rootvg clear pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+                compilation_options: PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState {
+                        color: replace_with_constant,
+                        alpha: replace_with_constant,
+                    }),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                front_face: wgpu::FrontFace::Cw,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        });
+
+        Self { pipeline }
+    }
+
+    /// Fill the given region of the target (in physical pixels) with a
+    /// color, keeping the rest of the target.
+    pub fn render_to_target(
+        &self,
+        target: &wgpu::TextureView,
+        color: wgpu::Color,
+        rect: RectI32,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("rootvg clear render pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_blend_constant(color);
+        render_pass.set_scissor_rect(
+            rect.origin.x as u32,
+            rect.origin.y as u32,
+            rect.size.width as u32,
+            rect.size.height as u32,
+        );
+        render_pass.draw(0..6, 0..1);
+    }
+}
@@ -1,9 +1,19 @@
-use crate::math::{RectI32, Vector, VectorI32, ZIndex};
+use crate::clip::RoundedClip;
+use crate::math::{Rect, RectI32, Size, Vector, VectorI32, ZIndex};
 use crate::primitive_group::{PrimitiveBatchKind, PrimitiveGroup};
 use crate::Primitive;
 
 use super::{BatchEntry, BatchKey, Canvas};
 
+#[cfg(all(feature = "text", feature = "quad"))]
+use crate::color::PackedSrgb;
+#[cfg(all(feature = "text", feature = "quad"))]
+use crate::math::Transform;
+#[cfg(all(feature = "text", feature = "quad"))]
+use crate::quad::SolidQuad;
+#[cfg(feature = "text")]
+use crate::text::TextPrimitive;
+
 #[cfg(feature = "custom-primitive")]
 use super::CustomPrimitive;
 #[cfg(feature = "custom-primitive")]
@@ -22,10 +32,87 @@ impl<'a> CanvasCtx<'a> {
         self.canvas.reset_scissor_rect();
     }
 
+    /// Push a new scissoring rectangle onto the stack. The new scissoring
+    /// rectangle is the intersection of the given rectangle and the current
+    /// scissoring rectangle, so a child never draws outside of its parent's
+    /// clipping region.
+    ///
+    /// Call [`CanvasCtx::pop_scissor_rect`] to restore the previous scissoring
+    /// rectangle.
+    pub fn push_scissor_rect(&mut self, scissor_rect: RectI32) {
+        self.canvas.push_scissor_rect(scissor_rect);
+    }
+
+    /// Restore the scissoring rectangle that was active before the last call
+    /// to [`CanvasCtx::push_scissor_rect`].
+    pub fn pop_scissor_rect(&mut self) {
+        self.canvas.pop_scissor_rect();
+    }
+
     pub fn scissor_rect(&self) -> RectI32 {
         self.canvas.scissor_rect
     }
 
+    /// Clip all primitives added after this call to the given rounded
+    /// rectangle (in logical points), for example to keep the contents of a
+    /// card with rounded corners inside of its bounds.
+    ///
+    /// This is applied in addition to the scissoring rectangle. Note that
+    /// text and custom primitives are only clipped by the scissoring
+    /// rectangle.
+    pub fn set_rounded_clip(&mut self, rect: Rect, radius: f32) {
+        self.canvas.set_rounded_clip(rect, radius);
+    }
+
+    /// Stop clipping primitives to a rounded rectangle.
+    pub fn reset_rounded_clip(&mut self) {
+        self.canvas.reset_rounded_clip();
+    }
+
+    pub fn rounded_clip(&self) -> Option<RoundedClip> {
+        self.canvas.rounded_clip
+    }
+
+    /// Multiply the alpha of every primitive added after this call by the
+    /// given value in the range `[0.0, 1.0]`, for example to fade a whole
+    /// panel in or out.
+    ///
+    /// This replaces the current global alpha. Use
+    /// [`CanvasCtx::push_global_alpha`] to fade nested content relative to
+    /// its parent.
+    ///
+    /// Faded text is rendered into an intermediate texture first, so that the
+    /// colors of text spans which were set in the attributes of the text
+    /// buffer fade as well. Note, custom primitives are not affected.
+    pub fn set_global_alpha(&mut self, alpha: f32) {
+        self.canvas.set_global_alpha(alpha);
+    }
+
+    /// Reset the global alpha to `1.0`.
+    pub fn reset_global_alpha(&mut self) {
+        self.canvas.set_global_alpha(1.0);
+    }
+
+    /// Push a new global alpha onto the stack. The new global alpha is the
+    /// product of the given alpha and the current global alpha, so nested
+    /// content fades together with its parent.
+    ///
+    /// Call [`CanvasCtx::pop_global_alpha`] to restore the previous global
+    /// alpha.
+    pub fn push_global_alpha(&mut self, alpha: f32) {
+        self.canvas.push_global_alpha(alpha);
+    }
+
+    /// Restore the global alpha that was active before the last call to
+    /// [`CanvasCtx::push_global_alpha`].
+    pub fn pop_global_alpha(&mut self) {
+        self.canvas.pop_global_alpha();
+    }
+
+    pub fn global_alpha(&self) -> f32 {
+        self.canvas.global_alpha
+    }
+
     pub fn set_z_index(&mut self, z_index: ZIndex) {
         self.canvas.z_index = z_index;
     }
@@ -39,14 +126,23 @@ impl<'a> CanvasCtx<'a> {
             return;
         }
 
-        let key = BatchKey::new(self.canvas.scissor_rect, self.canvas.z_index, 0);
-        let batch_entry = self
-            .canvas
-            .batches
-            .entry(key)
-            .or_insert_with(|| BatchEntry::new());
-
-        add(primitive, batch_entry);
+        let flip_height = self.canvas.flip_height();
+        let global_alpha = self.canvas.global_alpha;
+        let viewport = self.canvas.logical_size;
+
+        let key = BatchKey::new(
+            self.canvas.scissor_rect,
+            self.canvas.rounded_clip,
+            self.canvas.z_index,
+            0,
+        );
+        let batch_entry = self.canvas.batch_entry(key);
+
+        add(
+            prepare_primitive(primitive, flip_height, global_alpha),
+            viewport,
+            batch_entry,
+        );
     }
 
     pub fn add_with_offset(&mut self, primitive: impl Into<Primitive>, offset: Vector) {
@@ -54,14 +150,24 @@ impl<'a> CanvasCtx<'a> {
             return;
         }
 
-        let key = BatchKey::new(self.canvas.scissor_rect, self.canvas.z_index, 0);
-        let batch_entry = self
-            .canvas
-            .batches
-            .entry(key)
-            .or_insert_with(|| BatchEntry::new());
-
-        add_with_offset(primitive, offset, batch_entry);
+        let flip_height = self.canvas.flip_height();
+        let global_alpha = self.canvas.global_alpha;
+        let viewport = self.canvas.logical_size;
+
+        let key = BatchKey::new(
+            self.canvas.scissor_rect,
+            self.canvas.rounded_clip,
+            self.canvas.z_index,
+            0,
+        );
+        let batch_entry = self.canvas.batch_entry(key);
+
+        add_with_offset(
+            prepare_primitive(primitive, flip_height, global_alpha),
+            offset_to_canvas_space(offset, flip_height),
+            viewport,
+            batch_entry,
+        );
     }
 
     pub fn add_batch(&mut self, primitives: impl IntoIterator<Item = impl Into<Primitive>>) {
@@ -69,15 +175,24 @@ impl<'a> CanvasCtx<'a> {
             return;
         }
 
-        let key = BatchKey::new(self.canvas.scissor_rect, self.canvas.z_index, 0);
-        let batch_entry = self
-            .canvas
-            .batches
-            .entry(key)
-            .or_insert_with(|| BatchEntry::new());
+        let flip_height = self.canvas.flip_height();
+        let global_alpha = self.canvas.global_alpha;
+        let viewport = self.canvas.logical_size;
+
+        let key = BatchKey::new(
+            self.canvas.scissor_rect,
+            self.canvas.rounded_clip,
+            self.canvas.z_index,
+            0,
+        );
+        let batch_entry = self.canvas.batch_entry(key);
 
         for primitive in primitives.into_iter() {
-            add(primitive, batch_entry);
+            add(
+                prepare_primitive(primitive, flip_height, global_alpha),
+                viewport,
+                batch_entry,
+            );
         }
     }
 
@@ -90,15 +205,26 @@ impl<'a> CanvasCtx<'a> {
             return;
         }
 
-        let key = BatchKey::new(self.canvas.scissor_rect, self.canvas.z_index, 0);
-        let batch_entry = self
-            .canvas
-            .batches
-            .entry(key)
-            .or_insert_with(|| BatchEntry::new());
+        let flip_height = self.canvas.flip_height();
+        let global_alpha = self.canvas.global_alpha;
+        let viewport = self.canvas.logical_size;
+
+        let key = BatchKey::new(
+            self.canvas.scissor_rect,
+            self.canvas.rounded_clip,
+            self.canvas.z_index,
+            0,
+        );
+        let batch_entry = self.canvas.batch_entry(key);
 
+        let offset = offset_to_canvas_space(offset, flip_height);
         for primitive in primitives.into_iter() {
-            add_with_offset(primitive, offset, batch_entry);
+            add_with_offset(
+                prepare_primitive(primitive, flip_height, global_alpha),
+                offset,
+                viewport,
+                batch_entry,
+            );
         }
     }
 
@@ -111,6 +237,10 @@ impl<'a> CanvasCtx<'a> {
             return;
         }
 
+        let flip_height = self.canvas.flip_height();
+        let global_alpha = self.canvas.global_alpha;
+        let viewport = self.canvas.logical_size;
+
         for batch in group.primitive_batches.iter() {
             let scissor_rect = if let Some(scissor_rect) = batch.scissor_rect {
                 let offset_i32 = VectorI32::new(offset.x.round() as i32, offset.y.round() as i32);
@@ -123,91 +253,127 @@ impl<'a> CanvasCtx<'a> {
                     // Scissor rect is off screen
                     continue;
                 };
+
+                // The batch must still be clipped by the scissor rect which
+                // is currently pushed onto the canvas.
+                let Some(c) = super::intersect_scissor_rect(c, self.canvas.scissor_rect) else {
+                    // Scissor rect is fully clipped
+                    continue;
+                };
                 c
             } else {
                 self.canvas.scissor_rect
             };
 
-            let key = BatchKey::new(scissor_rect, self.canvas.z_index, batch.z_index);
+            let key = BatchKey::new(
+                scissor_rect,
+                self.canvas.rounded_clip,
+                self.canvas.z_index,
+                batch.z_index,
+            );
+
+            let batch_entry = self.canvas.batch_entry(key);
 
-            let batch_entry = self
-                .canvas
-                .batches
-                .entry(key)
-                .or_insert_with(|| BatchEntry::new());
+            let offset = offset_to_canvas_space(offset, flip_height);
 
             match &batch.kind {
                 #[cfg(feature = "quad")]
                 PrimitiveBatchKind::SolidQuad(quads) => {
                     for quad in quads.iter() {
-                        let mut quad_copy = *quad;
-                        quad_copy.position[0] += offset.x;
-                        quad_copy.position[1] += offset.y;
-
-                        batch_entry.solid_quads.push(quad_copy);
+                        add_with_offset(
+                            prepare_primitive(*quad, flip_height, global_alpha),
+                            offset,
+                            viewport,
+                            batch_entry,
+                        );
                     }
                 }
                 #[cfg(all(feature = "quad", feature = "gradient"))]
                 PrimitiveBatchKind::GradientQuad(quads) => {
                     for quad in quads.iter() {
-                        let mut quad_copy = *quad;
-                        quad_copy.position[0] += offset.x;
-                        quad_copy.position[1] += offset.y;
-
-                        batch_entry.gradient_quads.push(quad_copy);
+                        add_with_offset(
+                            prepare_primitive(*quad, flip_height, global_alpha),
+                            offset,
+                            viewport,
+                            batch_entry,
+                        );
+                    }
+                }
+                #[cfg(feature = "quad")]
+                PrimitiveBatchKind::Line(lines) => {
+                    for line in lines.iter() {
+                        add_with_offset(
+                            prepare_primitive(*line, flip_height, global_alpha),
+                            offset,
+                            viewport,
+                            batch_entry,
+                        );
                     }
                 }
                 #[cfg(feature = "text")]
                 PrimitiveBatchKind::Text(text) => {
                     for t in text.iter() {
-                        let mut t_copy = t.clone();
-                        t_copy.pos.x += offset.x;
-                        t_copy.pos.y += offset.y;
-
-                        batch_entry.text.push(t_copy);
+                        add_with_offset(
+                            prepare_primitive(t.clone(), flip_height, global_alpha),
+                            offset,
+                            viewport,
+                            batch_entry,
+                        );
                     }
                 }
                 #[cfg(any(feature = "mesh", feature = "tessellation"))]
                 PrimitiveBatchKind::SolidMesh(meshes) => {
                     for mesh in meshes.iter() {
-                        let mut mesh_copy = mesh.clone();
-
-                        mesh_copy.uniform.offset[0] += offset.x;
-                        mesh_copy.uniform.offset[1] += offset.y;
-
-                        batch_entry.solid_meshes.push(mesh_copy);
+                        add_with_offset(
+                            prepare_primitive(mesh.clone(), flip_height, global_alpha),
+                            offset,
+                            viewport,
+                            batch_entry,
+                        );
                     }
                 }
                 #[cfg(all(any(feature = "mesh", feature = "tessellation"), feature = "gradient"))]
                 PrimitiveBatchKind::GradientMesh(meshes) => {
                     for mesh in meshes.iter() {
-                        let mut mesh_copy = mesh.clone();
-
-                        mesh_copy.uniform.offset[0] += offset.x;
-                        mesh_copy.uniform.offset[1] += offset.y;
-
-                        batch_entry.gradient_meshes.push(mesh_copy);
+                        add_with_offset(
+                            prepare_primitive(mesh.clone(), flip_height, global_alpha),
+                            offset,
+                            viewport,
+                            batch_entry,
+                        );
                     }
                 }
                 #[cfg(feature = "image")]
                 PrimitiveBatchKind::Image(images) => {
                     for image in images.iter() {
-                        let mut image_copy = image.clone();
-
-                        image_copy.vertex.position[0] += offset.x;
-                        image_copy.vertex.position[1] += offset.y;
-
-                        batch_entry.images.push(image_copy);
+                        add_with_offset(
+                            prepare_primitive(image.clone(), flip_height, global_alpha),
+                            offset,
+                            viewport,
+                            batch_entry,
+                        );
+                    }
+                }
+                #[cfg(feature = "backdrop-blur")]
+                PrimitiveBatchKind::BackdropBlur(blurs) => {
+                    for blur in blurs.iter() {
+                        add_with_offset(
+                            prepare_primitive(*blur, flip_height, global_alpha),
+                            offset,
+                            viewport,
+                            batch_entry,
+                        );
                     }
                 }
                 #[cfg(feature = "custom-primitive")]
                 PrimitiveBatchKind::Custom(primitives) => {
                     for p in primitives.iter() {
-                        batch_entry.custom_primitives.push(CustomPrimitive {
-                            primitive: std::rc::Rc::clone(&p.primitive),
-                            offset: Vector::new(p.offset.x + offset.x, p.offset.y + offset.y),
-                            pipeline_id: p.pipeline_id,
-                        });
+                        add_with_offset(
+                            prepare_primitive(p.clone(), flip_height, global_alpha),
+                            offset,
+                            viewport,
+                            batch_entry,
+                        );
                     }
                 }
             }
@@ -244,9 +410,13 @@ impl<'a> CanvasCtx<'a> {
     }
 }
 
-fn add(primitive: impl Into<Primitive>, batch_entry: &mut BatchEntry) {
+fn add(primitive: impl Into<Primitive>, viewport: Size, batch_entry: &mut BatchEntry) {
     let primitive: Primitive = primitive.into();
 
+    if is_offscreen(&primitive, viewport) {
+        return;
+    }
+
     match primitive {
         #[cfg(feature = "quad")]
         Primitive::SolidQuad(p) => {
@@ -256,6 +426,10 @@ fn add(primitive: impl Into<Primitive>, batch_entry: &mut BatchEntry) {
         Primitive::GradientQuad(p) => {
             batch_entry.gradient_quads.push(p);
         }
+        #[cfg(feature = "quad")]
+        Primitive::Line(p) => {
+            batch_entry.lines.push(p);
+        }
 
         #[cfg(any(feature = "mesh", feature = "tessellation"))]
         Primitive::SolidMesh(p) => {
@@ -268,7 +442,7 @@ fn add(primitive: impl Into<Primitive>, batch_entry: &mut BatchEntry) {
 
         #[cfg(feature = "text")]
         Primitive::Text(p) => {
-            batch_entry.text.push(p);
+            add_text(p, batch_entry);
         }
 
         #[cfg(feature = "image")]
@@ -276,6 +450,11 @@ fn add(primitive: impl Into<Primitive>, batch_entry: &mut BatchEntry) {
             batch_entry.images.push(p);
         }
 
+        #[cfg(feature = "backdrop-blur")]
+        Primitive::BackdropBlur(p) => {
+            batch_entry.backdrop_blurs.push(p);
+        }
+
         #[cfg(feature = "custom-primitive")]
         Primitive::Custom(p) => {
             batch_entry.custom_primitives.push(p);
@@ -283,23 +462,37 @@ fn add(primitive: impl Into<Primitive>, batch_entry: &mut BatchEntry) {
     }
 }
 
-fn add_with_offset(primitive: impl Into<Primitive>, offset: Vector, batch_entry: &mut BatchEntry) {
+fn add_with_offset(
+    primitive: impl Into<Primitive>,
+    offset: Vector,
+    viewport: Size,
+    batch_entry: &mut BatchEntry,
+) {
     let primitive: Primitive = primitive.into();
 
-    match primitive {
+    let primitive = match primitive {
         #[cfg(feature = "quad")]
         Primitive::SolidQuad(mut p) => {
             p.position[0] += offset.x;
             p.position[1] += offset.y;
 
-            batch_entry.solid_quads.push(p);
+            Primitive::SolidQuad(p)
         }
         #[cfg(all(feature = "quad", feature = "gradient"))]
         Primitive::GradientQuad(mut p) => {
             p.position[0] += offset.x;
             p.position[1] += offset.y;
 
-            batch_entry.gradient_quads.push(p);
+            Primitive::GradientQuad(p)
+        }
+        #[cfg(feature = "quad")]
+        Primitive::Line(mut p) => {
+            p.from[0] += offset.x;
+            p.from[1] += offset.y;
+            p.to[0] += offset.x;
+            p.to[1] += offset.y;
+
+            Primitive::Line(p)
         }
 
         #[cfg(any(feature = "mesh", feature = "tessellation"))]
@@ -307,14 +500,14 @@ fn add_with_offset(primitive: impl Into<Primitive>, offset: Vector, batch_entry:
             p.uniform.offset[0] += offset.x;
             p.uniform.offset[1] += offset.y;
 
-            batch_entry.solid_meshes.push(p);
+            Primitive::SolidMesh(p)
         }
         #[cfg(all(any(feature = "mesh", feature = "tessellation"), feature = "gradient"))]
         Primitive::GradientMesh(mut p) => {
             p.uniform.offset[0] += offset.x;
             p.uniform.offset[1] += offset.y;
 
-            batch_entry.gradient_meshes.push(p);
+            Primitive::GradientMesh(p)
         }
 
         #[cfg(feature = "text")]
@@ -322,7 +515,7 @@ fn add_with_offset(primitive: impl Into<Primitive>, offset: Vector, batch_entry:
             p.pos.x += offset.x;
             p.pos.y += offset.y;
 
-            batch_entry.text.push(p);
+            Primitive::Text(p)
         }
 
         #[cfg(feature = "image")]
@@ -330,16 +523,371 @@ fn add_with_offset(primitive: impl Into<Primitive>, offset: Vector, batch_entry:
             p.vertex.position[0] += offset.x;
             p.vertex.position[1] += offset.y;
 
-            batch_entry.images.push(p);
+            Primitive::Image(p)
+        }
+
+        #[cfg(feature = "backdrop-blur")]
+        Primitive::BackdropBlur(mut p) => {
+            p.bounds.origin += offset;
+            Primitive::BackdropBlur(p)
         }
 
         #[cfg(feature = "custom-primitive")]
-        Primitive::Custom(p) => {
-            batch_entry.custom_primitives.push(CustomPrimitive {
-                primitive: std::rc::Rc::clone(&p.primitive),
-                offset: Vector::new(p.offset.x + offset.x, p.offset.y + offset.y),
-                pipeline_id: p.pipeline_id,
-            });
+        Primitive::Custom(p) => Primitive::Custom(CustomPrimitive {
+            primitive: std::rc::Rc::clone(&p.primitive),
+            offset: Vector::new(p.offset.x + offset.x, p.offset.y + offset.y),
+            pipeline_id: p.pipeline_id,
+        }),
+    };
+
+    add(primitive, viewport, batch_entry);
+}
+
+/// Returns `true` if the given primitive lies entirely outside of a canvas
+/// with the given logical size, so it can be dropped without being uploaded.
+///
+/// This is checked after all offsets have been applied. Primitives whose
+/// bounds only touch the edge of the canvas cover no pixels and are culled,
+/// while primitives with empty bounds inside of the canvas are kept. Text and
+/// custom primitives are never culled since their bounds are not known.
+fn is_offscreen(primitive: &Primitive, viewport: Size) -> bool {
+    let bounds: Option<Rect> = match primitive {
+        #[cfg(feature = "quad")]
+        Primitive::SolidQuad(p) => Some(p.bounds()),
+        #[cfg(all(feature = "quad", feature = "gradient"))]
+        Primitive::GradientQuad(p) => Some(p.bounds()),
+        #[cfg(feature = "quad")]
+        Primitive::Line(p) => Some(p.bounds()),
+
+        // Meshes with no vertices cover no pixels.
+        #[cfg(any(feature = "mesh", feature = "tessellation"))]
+        Primitive::SolidMesh(p) => match p.bounds() {
+            Some(bounds) => Some(bounds),
+            None => return true,
+        },
+        #[cfg(all(any(feature = "mesh", feature = "tessellation"), feature = "gradient"))]
+        Primitive::GradientMesh(p) => match p.bounds() {
+            Some(bounds) => Some(bounds),
+            None => return true,
+        },
+
+        #[cfg(feature = "text")]
+        Primitive::Text(_) => None,
+
+        #[cfg(feature = "image")]
+        Primitive::Image(p) => Some(p.bounds()),
+
+        #[cfg(feature = "backdrop-blur")]
+        Primitive::BackdropBlur(p) => Some(p.bounds),
+
+        #[cfg(feature = "custom-primitive")]
+        Primitive::Custom(_) => None,
+    };
+
+    let Some(bounds) = bounds else {
+        return false;
+    };
+
+    bounds.max_x() <= 0.0
+        || bounds.max_y() <= 0.0
+        || bounds.min_x() >= viewport.width
+        || bounds.min_y() >= viewport.height
+}
+
+#[cfg(feature = "text")]
+fn add_text(text: TextPrimitive, batch_entry: &mut BatchEntry) {
+    // Quads are rendered before text in the same batch, so highlights and
+    // decoration lines appear behind the text.
+    #[cfg(feature = "quad")]
+    {
+        let mut add_quad = |rect: Rect, mut color: PackedSrgb| {
+            let rect = rect.translate(Vector::new(0.0, -text.scroll_offset));
+            *color.a_mut() *= text.alpha.clamp(0.0, 1.0);
+
+            let rect = if let Some(bounds) = text.clipping_bounds {
+                let Some(rect) = rect.intersection(&bounds) else {
+                    return;
+                };
+                rect
+            } else {
+                rect
+            };
+
+            let mut quad = SolidQuad::builder(rect.size)
+                .position(text.pos + rect.origin.to_vector())
+                .bg_color(color);
+
+            // Rotate the quad about the same anchor as the text.
+            if let Some(rotation) = &text.rotation {
+                let anchor = rotation.anchor - rect.origin;
+
+                quad = quad.transform(
+                    Transform::translation(-anchor.x, -anchor.y)
+                        .then_rotate(rotation.angle)
+                        .then_translate(anchor),
+                );
+            }
+
+            batch_entry.solid_quads.push(quad.into());
+        };
+
+        if let Some(highlight) = &text.highlight {
+            for rect in highlight.rects.iter() {
+                add_quad(*rect, highlight.color);
+            }
+        }
+
+        if let Some(buffer) = &text.buffer {
+            for decoration in buffer.decorations().iter() {
+                add_quad(
+                    decoration.rect,
+                    decoration.color.unwrap_or(text.color).into(),
+                );
+            }
         }
     }
+
+    batch_entry.text.push(text);
+}
+
+/// Convert a primitive into the coordinate space used when rendering and
+/// apply the current global alpha.
+fn prepare_primitive(
+    primitive: impl Into<Primitive>,
+    flip_height: Option<f32>,
+    global_alpha: f32,
+) -> Primitive {
+    apply_global_alpha(to_canvas_space(primitive, flip_height), global_alpha)
+}
+
+/// Convert a primitive from the coordinate space of the user to the top-left
+/// origin, Y-down coordinate space used when rendering.
+///
+/// If `flip_height` is `Some` (the canvas is in Y-up mode), then the bounding
+/// rectangle of quads, images, and text is flipped about the given logical
+/// height of the canvas, so that their position refers to their bottom-left
+/// corner. Their contents are not mirrored, so text and images stay upright.
+/// Mesh vertices and the end points of lines are flipped individually.
+fn to_canvas_space(primitive: impl Into<Primitive>, flip_height: Option<f32>) -> Primitive {
+    let primitive: Primitive = primitive.into();
+
+    let Some(height) = flip_height else {
+        return primitive;
+    };
+
+    match primitive {
+        #[cfg(feature = "quad")]
+        Primitive::SolidQuad(mut p) => {
+            p.position[1] = height - p.position[1] - p.size[1];
+            Primitive::SolidQuad(p)
+        }
+        #[cfg(all(feature = "quad", feature = "gradient"))]
+        Primitive::GradientQuad(mut p) => {
+            p.position[1] = height - p.position[1] - p.size[1];
+            Primitive::GradientQuad(p)
+        }
+        #[cfg(feature = "quad")]
+        Primitive::Line(mut p) => {
+            p.from[1] = height - p.from[1];
+            p.to[1] = height - p.to[1];
+            Primitive::Line(p)
+        }
+
+        #[cfg(any(feature = "mesh", feature = "tessellation"))]
+        Primitive::SolidMesh(mut p) => {
+            flip_mesh_uniforms(&mut p.uniform, height);
+            Primitive::SolidMesh(p)
+        }
+        #[cfg(all(any(feature = "mesh", feature = "tessellation"), feature = "gradient"))]
+        Primitive::GradientMesh(mut p) => {
+            flip_mesh_uniforms(&mut p.uniform, height);
+            Primitive::GradientMesh(p)
+        }
+
+        #[cfg(feature = "text")]
+        Primitive::Text(mut p) => {
+            let text_height = p.buffer.as_ref().map(|b| b.measure().height).unwrap_or(0.0);
+
+            p.pos.y = height - p.pos.y - text_height;
+            Primitive::Text(p)
+        }
+
+        #[cfg(feature = "image")]
+        Primitive::Image(mut p) => {
+            p.vertex.position[1] = height - p.vertex.position[1] - p.vertex.size[1];
+            Primitive::Image(p)
+        }
+
+        #[cfg(feature = "backdrop-blur")]
+        Primitive::BackdropBlur(mut p) => {
+            p.bounds.origin.y = height - p.bounds.origin.y - p.bounds.size.height;
+            Primitive::BackdropBlur(p)
+        }
+
+        #[cfg(feature = "custom-primitive")]
+        Primitive::Custom(mut p) => {
+            p.offset.y = height - p.offset.y;
+            Primitive::Custom(p)
+        }
+    }
+}
+
+/// Convert an offset from the coordinate space of the user to the Y-down
+/// coordinate space used when rendering.
+fn offset_to_canvas_space(offset: Vector, flip_height: Option<f32>) -> Vector {
+    if flip_height.is_some() {
+        Vector::new(offset.x, -offset.y)
+    } else {
+        offset
+    }
+}
+
+/// Flip the vertices of a mesh about the given height by negating the second
+/// row of its transform.
+#[cfg(any(feature = "mesh", feature = "tessellation"))]
+fn flip_mesh_uniforms(uniform: &mut crate::mesh::MeshUniforms, height: f32) {
+    if uniform.has_transform == 0 {
+        uniform.transform = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+        uniform.has_transform = 1;
+    }
+
+    uniform.transform[1] = -uniform.transform[1];
+    uniform.transform[3] = -uniform.transform[3];
+    uniform.transform[5] = -uniform.transform[5];
+
+    uniform.offset[1] = height - uniform.offset[1];
+}
+
+/// Multiply the alpha of all colors in the primitive by `global_alpha`.
+///
+/// Note, the colors of custom primitives are not affected.
+fn apply_global_alpha(primitive: Primitive, global_alpha: f32) -> Primitive {
+    if global_alpha >= 1.0 {
+        return primitive;
+    }
+
+    match primitive {
+        #[cfg(feature = "quad")]
+        Primitive::SolidQuad(mut p) => {
+            *p.color.a_mut() *= global_alpha;
+            *p.border_color.a_mut() *= global_alpha;
+            *p.shadow_color.a_mut() *= global_alpha;
+            Primitive::SolidQuad(p)
+        }
+        #[cfg(all(feature = "quad", feature = "gradient"))]
+        Primitive::GradientQuad(mut p) => {
+            p.gradient.multiply_alpha(global_alpha);
+            p.border_gradient.multiply_alpha(global_alpha);
+            *p.border_color.a_mut() *= global_alpha;
+            Primitive::GradientQuad(p)
+        }
+        #[cfg(feature = "quad")]
+        Primitive::Line(mut p) => {
+            *p.color.a_mut() *= global_alpha;
+            *p.end_color.a_mut() *= global_alpha;
+            Primitive::Line(p)
+        }
+
+        #[cfg(any(feature = "mesh", feature = "tessellation"))]
+        Primitive::SolidMesh(mut p) => {
+            p.uniform.alpha *= global_alpha;
+            Primitive::SolidMesh(p)
+        }
+        #[cfg(all(any(feature = "mesh", feature = "tessellation"), feature = "gradient"))]
+        Primitive::GradientMesh(mut p) => {
+            p.uniform.alpha *= global_alpha;
+            Primitive::GradientMesh(p)
+        }
+
+        #[cfg(feature = "text")]
+        Primitive::Text(mut p) => {
+            // This also fades the colors of text spans which were set in the
+            // attributes of the text buffer, and the highlight and
+            // decorations which are added in `add_text`.
+            p.alpha *= global_alpha;
+            Primitive::Text(p)
+        }
+
+        #[cfg(feature = "image")]
+        Primitive::Image(mut p) => {
+            p.vertex.opacity *= global_alpha;
+            Primitive::Image(p)
+        }
+
+        #[cfg(feature = "backdrop-blur")]
+        Primitive::BackdropBlur(p) => Primitive::BackdropBlur(p),
+
+        #[cfg(feature = "custom-primitive")]
+        Primitive::Custom(p) => Primitive::Custom(p),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::{PhysicalSizeI32, PointI32, RectI32, ScaleFactor, SizeI32};
+    use crate::test_util::TestCanvas;
+    use crate::CanvasConfig;
+
+    fn rect(x: i32, y: i32, width: i32, height: i32) -> RectI32 {
+        RectI32::new(PointI32::new(x, y), SizeI32::new(width, height))
+    }
+
+    #[test]
+    fn nested_scissor_rects_intersect() {
+        let Some(mut test) = TestCanvas::new(CanvasConfig::default()) else {
+            return;
+        };
+        let mut cx = test
+            .canvas
+            .begin(PhysicalSizeI32::new(100, 100), ScaleFactor::new(1.0));
+
+        cx.push_scissor_rect(rect(10, 10, 50, 50));
+        assert_eq!(cx.scissor_rect(), rect(10, 10, 50, 50));
+
+        cx.push_scissor_rect(rect(40, 0, 50, 30));
+        assert_eq!(cx.scissor_rect(), rect(40, 10, 20, 20));
+
+        cx.pop_scissor_rect();
+        assert_eq!(cx.scissor_rect(), rect(10, 10, 50, 50));
+
+        cx.pop_scissor_rect();
+        assert_eq!(cx.scissor_rect(), rect(0, 0, 100, 100));
+    }
+
+    #[cfg(feature = "quad")]
+    #[test]
+    fn empty_scissor_intersection_culls_primitives() {
+        use crate::math::{Point, Size};
+        use crate::quad::SolidQuad;
+
+        let Some(mut test) = TestCanvas::new(CanvasConfig::default()) else {
+            return;
+        };
+        let quad = SolidQuad::builder(Size::new(100.0, 100.0))
+            .position(Point::new(0.0, 0.0))
+            .build();
+
+        let mut cx = test
+            .canvas
+            .begin(PhysicalSizeI32::new(100, 100), ScaleFactor::new(1.0));
+
+        cx.push_scissor_rect(rect(0, 0, 20, 20));
+        cx.push_scissor_rect(rect(50, 50, 20, 20));
+        cx.add(quad);
+
+        // Nested rects stay culled, even if they overlap the parent.
+        cx.push_scissor_rect(rect(0, 0, 20, 20));
+        cx.add(quad);
+        cx.pop_scissor_rect();
+        assert!(test.canvas.is_empty());
+
+        let mut cx = test
+            .canvas
+            .begin(PhysicalSizeI32::new(100, 100), ScaleFactor::new(1.0));
+        cx.push_scissor_rect(rect(0, 0, 20, 20));
+        cx.push_scissor_rect(rect(50, 50, 20, 20));
+        cx.pop_scissor_rect();
+        cx.add(quad);
+        assert!(!test.canvas.is_empty());
+    }
 }
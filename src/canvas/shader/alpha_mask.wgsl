@@ -0,0 +1,27 @@
+var<private> positions: array<vec2<f32>, 6> = array<vec2<f32>, 6>(
+    vec2<f32>(-1.0, 1.0),
+    vec2<f32>(-1.0, -1.0),
+    vec2<f32>(1.0, -1.0),
+    vec2<f32>(-1.0, 1.0),
+    vec2<f32>(1.0, 1.0),
+    vec2<f32>(1.0, -1.0)
+);
+
+@group(0) @binding(0) var u_texture: texture_2d<f32>;
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> @builtin(position) vec4<f32> {
+    return vec4<f32>(positions[vertex_index], 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    // The intermediate texture is the same size as the target, so each pixel
+    // reads exactly one texel.
+    let coverage = textureLoad(u_texture, vec2<i32>(position.xy), 0).a;
+
+    // The coverage is written to every channel, so that it ends up in the
+    // red channel of single channel targets and in the alpha channel of
+    // targets with an alpha channel.
+    return vec4<f32>(coverage);
+}
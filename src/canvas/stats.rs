@@ -0,0 +1,129 @@
+use super::{BatchEntry, BatchKind};
+
+/// Statistics about how the primitives of a frame were batched, returned by
+/// [`Canvas::last_frame_stats`].
+///
+/// [`Canvas::last_frame_stats`]: crate::Canvas::last_frame_stats
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameStats {
+    pub solid_quads: PipelineStats,
+    pub gradient_quads: PipelineStats,
+    pub lines: PipelineStats,
+    pub solid_meshes: PipelineStats,
+    pub gradient_meshes: PipelineStats,
+    pub text: PipelineStats,
+    pub images: PipelineStats,
+    pub backdrop_blurs: PipelineStats,
+    pub custom: PipelineStats,
+    /// The number of times the scissoring rectangle was changed between
+    /// batches.
+    pub scissor_rect_switches: usize,
+}
+
+impl FrameStats {
+    /// The total number of batches (draw calls) across all pipelines.
+    pub fn total_batches(&self) -> usize {
+        self.pipelines().iter().map(|p| p.batches).sum()
+    }
+
+    /// The total number of primitives across all pipelines.
+    pub fn total_primitives(&self) -> usize {
+        self.pipelines().iter().map(|p| p.primitives).sum()
+    }
+
+    fn pipelines(&self) -> [PipelineStats; 9] {
+        [
+            self.solid_quads,
+            self.gradient_quads,
+            self.lines,
+            self.solid_meshes,
+            self.gradient_meshes,
+            self.text,
+            self.images,
+            self.backdrop_blurs,
+            self.custom,
+        ]
+    }
+
+    pub(super) fn collect<'a>(
+        entries: impl IntoIterator<Item = &'a BatchEntry>,
+        order: &[BatchKind],
+    ) -> Self {
+        let mut stats = Self::default();
+
+        #[allow(unused)] // entry is unused if no primitive features are enabled
+        for entry in entries {
+            #[cfg(feature = "quad")]
+            {
+                stats.solid_quads.primitives += entry.solid_quads.len();
+            }
+            #[cfg(all(feature = "quad", feature = "gradient"))]
+            {
+                stats.gradient_quads.primitives += entry.gradient_quads.len();
+            }
+            #[cfg(feature = "quad")]
+            {
+                stats.lines.primitives += entry.lines.len();
+            }
+            #[cfg(any(feature = "mesh", feature = "tessellation"))]
+            {
+                stats.solid_meshes.primitives += entry.solid_meshes.len();
+            }
+            #[cfg(all(any(feature = "mesh", feature = "tessellation"), feature = "gradient"))]
+            {
+                stats.gradient_meshes.primitives += entry.gradient_meshes.len();
+            }
+            #[cfg(feature = "text")]
+            {
+                stats.text.primitives += entry.text.len();
+            }
+            #[cfg(feature = "image")]
+            {
+                stats.images.primitives += entry.images.len();
+            }
+            #[cfg(feature = "backdrop-blur")]
+            {
+                stats.backdrop_blurs.primitives += entry.backdrop_blurs.len();
+            }
+            #[cfg(feature = "custom-primitive")]
+            {
+                stats.custom.primitives += entry.custom_primitives.len();
+            }
+        }
+
+        for kind in order.iter() {
+            match kind {
+                #[cfg(feature = "quad")]
+                BatchKind::SolidQuad { .. } => stats.solid_quads.batches += 1,
+                #[cfg(all(feature = "quad", feature = "gradient"))]
+                BatchKind::GradientQuad { .. } => stats.gradient_quads.batches += 1,
+                #[cfg(feature = "quad")]
+                BatchKind::Line { .. } => stats.lines.batches += 1,
+                #[cfg(any(feature = "mesh", feature = "tessellation"))]
+                BatchKind::SolidMesh { .. } => stats.solid_meshes.batches += 1,
+                #[cfg(all(any(feature = "mesh", feature = "tessellation"), feature = "gradient"))]
+                BatchKind::GradientMesh { .. } => stats.gradient_meshes.batches += 1,
+                #[cfg(feature = "text")]
+                BatchKind::Text { .. } => stats.text.batches += 1,
+                #[cfg(feature = "image")]
+                BatchKind::Image { .. } => stats.images.batches += 1,
+                #[cfg(feature = "backdrop-blur")]
+                BatchKind::BackdropBlur { .. } => stats.backdrop_blurs.batches += 1,
+                #[cfg(feature = "custom-primitive")]
+                BatchKind::Custom { .. } => stats.custom.batches += 1,
+                BatchKind::ScissorRect(_) => stats.scissor_rect_switches += 1,
+            }
+        }
+
+        stats
+    }
+}
+
+/// The number of batches and primitives drawn by a single pipeline.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PipelineStats {
+    /// The number of batches (draw calls).
+    pub batches: usize,
+    /// The number of primitives across all batches.
+    pub primitives: usize,
+}
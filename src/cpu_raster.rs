@@ -0,0 +1,736 @@
+//! Rasterize primitives on the CPU, for environments without a GPU.
+
+use image::RgbaImage;
+
+use crate::color::PackedSrgb;
+use crate::math::{PhysicalSizeI32, ScaleFactor};
+use crate::Primitive;
+
+#[cfg(feature = "quad")]
+use crate::math::Vector;
+#[cfg(feature = "quad")]
+use crate::quad::SolidQuadPrimitive;
+
+#[cfg(feature = "text")]
+use crate::text::{
+    glyphon::cosmic_text::{SwashCache, SwashContent},
+    FontSystem, TextPrimitive,
+};
+
+/// Rasterizes primitives into an image on the CPU.
+///
+/// This uses the same math as the shaders, so the result is close to what the
+/// GPU renders into a target of the default format, but it is slow. It is
+/// meant for generating images where no GPU is available, such as golden
+/// images in CI.
+///
+/// Only solid quads and text are supported, and other primitives are skipped.
+/// The dash pattern of quad borders, and the shadows, outlines, highlights,
+/// icons, and rotation of text are not supported either. Primitives are drawn in the
+/// order they are given, without z indexes or scissor rects.
+pub struct CpuRasterizer {
+    physical_size: PhysicalSizeI32,
+    scale_factor: ScaleFactor,
+    /// The raw components of each pixel, which are the values the GPU stores
+    /// before they are encoded into the format of the render target.
+    pixels: Vec<[f32; 4]>,
+
+    #[cfg(feature = "text")]
+    swash_cache: SwashCache,
+}
+
+impl CpuRasterizer {
+    pub fn new(
+        physical_size: PhysicalSizeI32,
+        scale_factor: ScaleFactor,
+        clear_color: Option<PackedSrgb>,
+    ) -> Self {
+        assert!(physical_size.width > 0);
+        assert!(physical_size.height > 0);
+        assert!(scale_factor.0 > 0.0);
+
+        let clear_color = clear_color.unwrap_or(PackedSrgb::TRANSPARENT).raw();
+
+        Self {
+            physical_size,
+            scale_factor,
+            pixels: vec![clear_color; (physical_size.width * physical_size.height) as usize],
+            #[cfg(feature = "text")]
+            swash_cache: SwashCache::new(),
+        }
+    }
+
+    /// Draw a primitive on top of everything drawn so far.
+    ///
+    /// Primitives which are not supported are skipped.
+    pub fn draw(
+        &mut self,
+        primitive: &Primitive,
+        #[cfg(feature = "text")] font_system: &mut FontSystem,
+    ) {
+        match primitive {
+            #[cfg(feature = "quad")]
+            Primitive::SolidQuad(quad) => self.draw_solid_quad(quad),
+            #[cfg(feature = "text")]
+            Primitive::Text(text) => self.draw_text(text, font_system),
+            #[allow(unreachable_patterns)]
+            _ => {}
+        }
+    }
+
+    /// Draw a solid quad on top of everything drawn so far.
+    #[cfg(feature = "quad")]
+    pub fn draw_solid_quad(&mut self, quad: &SolidQuadPrimitive) {
+        let Some(quad) = QuadParams::new(quad, self.scale_factor.0) else {
+            return;
+        };
+
+        let (min_x, min_y, max_x, max_y) = quad.pixel_bounds;
+        let min_x = min_x.max(0);
+        let min_y = min_y.max(0);
+        let max_x = max_x.min(self.physical_size.width);
+        let max_y = max_y.min(self.physical_size.height);
+
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                // Fragments are shaded at the center of the pixel.
+                let frag_coord = Vector::new(x as f32 + 0.5, y as f32 + 0.5);
+                let color = quad.shade(quad.to_local(frag_coord));
+
+                blend(
+                    &mut self.pixels[(y * self.physical_size.width + x) as usize],
+                    color,
+                );
+            }
+        }
+    }
+
+    /// Draw a text primitive on top of everything drawn so far.
+    #[cfg(feature = "text")]
+    pub fn draw_text(&mut self, text: &TextPrimitive, font_system: &mut FontSystem) {
+        let Some(buffer) = &text.buffer else {
+            return;
+        };
+        let buffer = buffer.raw_buffer();
+
+        let scale_factor = self.scale_factor.0;
+
+        // The same positions and clipping bounds as in the text pipeline.
+        let left = text.pos.x * scale_factor;
+        let top = (text.pos.y * scale_factor).round() - (text.scroll_offset * scale_factor).round();
+
+        let (clip_left, clip_top, clip_right, clip_bottom) = text
+            .clipping_bounds
+            .map(|bounds| {
+                (
+                    ((text.pos.x + bounds.min_x()) * scale_factor).floor() as i32,
+                    ((text.pos.y + bounds.min_y()) * scale_factor).floor() as i32,
+                    ((text.pos.x + bounds.min_x() + bounds.width()) * scale_factor).ceil() as i32,
+                    ((text.pos.y + bounds.min_y() + bounds.height()) * scale_factor).ceil() as i32,
+                )
+            })
+            .unwrap_or((0, 0, self.physical_size.width, self.physical_size.height));
+        let clip_left = clip_left.max(0);
+        let clip_top = clip_top.max(0);
+        let clip_right = clip_right.min(self.physical_size.width);
+        let clip_bottom = clip_bottom.min(self.physical_size.height);
+
+        for run in buffer.layout_runs() {
+            // Skip the lines which are scrolled out of the clipping bounds.
+            let run_top = top + run.line_top * scale_factor;
+            if run_top + run.line_height * scale_factor < clip_top as f32 {
+                continue;
+            }
+            if run_top > clip_bottom as f32 {
+                break;
+            }
+
+            for glyph in run.glyphs.iter() {
+                let physical_glyph = glyph.physical((left, top), scale_factor);
+
+                let Some(image) = self
+                    .swash_cache
+                    .get_image(font_system, physical_glyph.cache_key)
+                else {
+                    continue;
+                };
+
+                let glyph_x = physical_glyph.x + image.placement.left;
+                let glyph_y = (run.line_y * scale_factor).round() as i32 + physical_glyph.y
+                    - image.placement.top;
+                let width = image.placement.width as i32;
+
+                let color: PackedSrgb = glyph
+                    .color_opt
+                    .map(|c| crate::color::RGBA8::new(c.r(), c.g(), c.b(), c.a()))
+                    .unwrap_or(text.color)
+                    .into();
+                let color = color.raw();
+
+                for y in glyph_y.max(clip_top)
+                    ..(glyph_y + image.placement.height as i32).min(clip_bottom)
+                {
+                    for x in glyph_x.max(clip_left)..(glyph_x + width).min(clip_right) {
+                        let i = ((y - glyph_y) * width + (x - glyph_x)) as usize;
+
+                        let color = match image.content {
+                            SwashContent::Mask => {
+                                let coverage = f32::from(image.data[i]) * (1.0 / 255.0);
+                                [color[0], color[1], color[2], color[3] * coverage]
+                            }
+                            SwashContent::Color => {
+                                let [r, g, b, a] = [0, 1, 2, 3].map(|c| image.data[i * 4 + c]);
+                                PackedSrgb::from_rgba8(r, g, b, a).raw()
+                            }
+                            // Subpixel masks are not supported, so use the
+                            // coverage of the green channel.
+                            SwashContent::SubpixelMask => {
+                                let coverage = f32::from(image.data[i * 4 + 1]) * (1.0 / 255.0);
+                                [color[0], color[1], color[2], color[3] * coverage]
+                            }
+                        };
+
+                        blend(
+                            &mut self.pixels[(y * self.physical_size.width + x) as usize],
+                            color,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// The rasterized image.
+    ///
+    /// The pixels are 8-bit sRGB, the same bytes that a render target with the
+    /// default format would store.
+    pub fn to_image(&self) -> RgbaImage {
+        let mut image = RgbaImage::new(
+            self.physical_size.width as u32,
+            self.physical_size.height as u32,
+        );
+
+        for (pixel, [r, g, b, a]) in image.pixels_mut().zip(self.pixels.iter().copied()) {
+            let color = PackedSrgb::from_srgba(
+                r.clamp(0.0, 1.0),
+                g.clamp(0.0, 1.0),
+                b.clamp(0.0, 1.0),
+                a.clamp(0.0, 1.0),
+            )
+            .to_rgba8();
+
+            *pixel = image::Rgba([color.r, color.g, color.b, color.a]);
+        }
+
+        image
+    }
+}
+
+/// Blend a color onto a pixel with the same blend state that the pipelines use
+/// ([`wgpu::BlendState::ALPHA_BLENDING`]).
+#[allow(unused)] // unused if no primitive features are enabled
+fn blend(dst: &mut [f32; 4], [r, g, b, a]: [f32; 4]) {
+    let a = a.clamp(0.0, 1.0);
+    if a <= 0.0 {
+        return;
+    }
+
+    dst[0] = r * a + dst[0] * (1.0 - a);
+    dst[1] = g * a + dst[1] * (1.0 - a);
+    dst[2] = b * a + dst[2] * (1.0 - a);
+    dst[3] = a + dst[3] * (1.0 - a);
+}
+
+/// The parameters of a solid quad in physical pixels, as computed by the
+/// vertex shader.
+#[cfg(feature = "quad")]
+struct QuadParams {
+    color: [f32; 4],
+    border_color: [f32; 4],
+    pos: Vector,
+    size: Vector,
+    border_radius: [f32; 4],
+    border_width: [f32; 4],
+    shadow_color: [f32; 4],
+    shadow_offset: Vector,
+    shadow_blur_radius: f32,
+    shadow_spread: f32,
+    shadow_inset: bool,
+    ellipse: bool,
+    arc: bool,
+    /// The inverse of the linear part of the transform, as a column-major 2 by
+    /// 2 matrix.
+    inv_transform: [f32; 4],
+    /// The pixels covered by the quad and its shadow, in the form
+    /// `(min_x, min_y, max_x, max_y)`.
+    pixel_bounds: (i32, i32, i32, i32),
+}
+
+#[cfg(feature = "quad")]
+impl QuadParams {
+    /// Returns `None` if the transform of the quad is not invertible.
+    fn new(quad: &SolidQuadPrimitive, scale_factor: f32) -> Option<Self> {
+        let [t0, t1, t2, t3] = quad.transform;
+        let det = t0 * t3 - t2 * t1;
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        let inv_transform = [t3 / det, -t1 / det, -t2 / det, t0 / det];
+
+        let input_pos = Vector::from(quad.position);
+        let input_size = Vector::from(quad.size);
+
+        let min_border_radius = input_size.x.min(input_size.y) * 0.5;
+        let arc = quad.flags & 256 != 0;
+        let border_radius = if arc {
+            // The border radius of an arc holds its angles and radius instead.
+            let [start, sweep, round_caps, radius] = quad.border_radius;
+            [start, sweep, round_caps, radius * scale_factor]
+        } else {
+            quad.border_radius
+                .map(|r| r.min(min_border_radius) * scale_factor)
+        };
+
+        // Expand the area covered by the quad so that the shadow doesn't get
+        // cut off.
+        let mut min_pos = input_pos;
+        let mut max_pos = input_pos + input_size;
+        if quad.shadow_color.a() > 0.0 && quad.shadow_inset == 0 {
+            let extent = quad.shadow_spread.max(0.0) + quad.shadow_blur_radius.max(0.0) + 1.0;
+            let extent = Vector::new(extent, extent);
+            let offset = Vector::from(quad.shadow_offset);
+
+            min_pos = min_pos.min(input_pos + offset - extent);
+            max_pos = max_pos.max(input_pos + input_size + offset + extent);
+        }
+
+        let mut pos = input_pos * scale_factor;
+        let mut size = input_size * scale_factor;
+        let mut border_width = quad.border_width.map(|w| w * scale_factor);
+        let mut min_screen_pos = min_pos * scale_factor;
+        let mut max_screen_pos = max_pos * scale_factor;
+
+        // Snap edges to nearest physical pixel, the same way as
+        // `snap_start_edges` and `snap_end_edges` in the quad shader.
+        let snap_all = quad.flags & 1 != 0;
+        let snap_start = [
+            snap_all || quad.flags & 8 != 0,
+            snap_all || quad.flags & 32 != 0,
+        ];
+        let snap_end = [
+            snap_all || quad.flags & 16 != 0,
+            snap_all || quad.flags & 64 != 0,
+        ];
+        if snap_start.contains(&true) || snap_end.contains(&true) {
+            let mut end_pos = (input_pos + input_size) * scale_factor;
+
+            let snap = |v: &mut f32, enabled: bool| {
+                if enabled {
+                    *v = v.round();
+                }
+            };
+            snap(&mut min_screen_pos.x, snap_start[0]);
+            snap(&mut min_screen_pos.y, snap_start[1]);
+            snap(&mut max_screen_pos.x, snap_end[0]);
+            snap(&mut max_screen_pos.y, snap_end[1]);
+            snap(&mut pos.x, snap_start[0]);
+            snap(&mut pos.y, snap_start[1]);
+            snap(&mut end_pos.x, snap_end[0]);
+            snap(&mut end_pos.y, snap_end[1]);
+
+            size = end_pos - pos;
+        }
+        // Snap border width to nearest physical pixel.
+        if quad.flags & 2 != 0 {
+            border_width = border_width.map(f32::round);
+        }
+
+        // The bounds of the transformed corners of the covered area.
+        let corners = [
+            min_screen_pos,
+            Vector::new(max_screen_pos.x, min_screen_pos.y),
+            max_screen_pos,
+            Vector::new(min_screen_pos.x, max_screen_pos.y),
+        ]
+        .map(|corner| {
+            let v = corner - pos;
+            pos + Vector::new(t0 * v.x + t2 * v.y, t1 * v.x + t3 * v.y)
+        });
+        let (mut min_x, mut min_y) = (f32::MAX, f32::MAX);
+        let (mut max_x, mut max_y) = (f32::MIN, f32::MIN);
+        for corner in corners {
+            min_x = min_x.min(corner.x);
+            min_y = min_y.min(corner.y);
+            max_x = max_x.max(corner.x);
+            max_y = max_y.max(corner.y);
+        }
+
+        Some(Self {
+            color: quad.color.raw(),
+            border_color: quad.border_color.raw(),
+            pos,
+            size,
+            border_radius,
+            border_width,
+            shadow_color: quad.shadow_color.raw(),
+            shadow_offset: Vector::from(quad.shadow_offset) * scale_factor,
+            shadow_blur_radius: quad.shadow_blur_radius * scale_factor,
+            shadow_spread: quad.shadow_spread * scale_factor,
+            shadow_inset: quad.shadow_inset != 0,
+            ellipse: quad.flags & 128 != 0,
+            arc,
+            inv_transform,
+            pixel_bounds: (
+                min_x.floor() as i32,
+                min_y.floor() as i32,
+                max_x.ceil() as i32,
+                max_y.ceil() as i32,
+            ),
+        })
+    }
+
+    /// Convert a position in the target to the local space of the quad (before
+    /// the transform is applied).
+    fn to_local(&self, frag_coord: Vector) -> Vector {
+        let [i0, i1, i2, i3] = self.inv_transform;
+        let v = frag_coord - self.pos;
+
+        self.pos + Vector::new(i0 * v.x + i2 * v.y, i1 * v.x + i3 * v.y)
+    }
+
+    /// The color of the fragment at the given local position, as computed by
+    /// the fragment shader.
+    fn shade(&self, local_pos: Vector) -> [f32; 4] {
+        let center = self.pos + self.size * 0.5;
+
+        // Arcs have no border or shadow.
+        if self.arc {
+            let alpha = arc_coverage(local_pos - center, self.border_radius, self.border_width[0]);
+            let [r, g, b, a] = self.color;
+            return [r, g, b, a * alpha];
+        }
+        let [top, right, bottom, left] = self.border_width;
+
+        let mut mixed_color = self.color;
+
+        let radius_alpha = if self.ellipse {
+            let (alpha, border_mix) =
+                ellipse_coverage(local_pos, self.pos, self.size, self.border_width);
+
+            mixed_color = mix(self.color, self.border_color, border_mix);
+            alpha
+        } else {
+            let border_radius = select_border_radius(self.border_radius, local_pos, center);
+
+            if self.border_width.iter().any(|w| *w > 0.0) {
+                // Blend between the widths of the two edges adjacent to the
+                // nearest corner.
+                let corner_widths = select_border_widths(self.border_width, local_pos, center);
+
+                let internal_border =
+                    (border_radius - corner_widths.x.max(corner_widths.y)).max(0.0);
+
+                let internal_distance = distance_alg(
+                    local_pos,
+                    self.pos + Vector::new(left, top),
+                    self.size - Vector::new(left, top) - Vector::new(right, bottom),
+                    internal_border,
+                );
+
+                let border_mix = smoothstep(
+                    (internal_border - 0.5).max(0.0),
+                    internal_border + 0.5,
+                    internal_distance,
+                );
+
+                mixed_color = mix(self.color, self.border_color, border_mix);
+            }
+
+            let dist = distance_alg(local_pos, self.pos, self.size, border_radius);
+
+            1.0 - smoothstep((border_radius - 0.5).max(0.0), border_radius + 0.5, dist)
+        };
+
+        let blur = self.shadow_blur_radius.max(0.5);
+
+        if self.shadow_color[3] > 0.0 && self.shadow_inset {
+            // The inner shadow is cast by the edges of the quad onto a rounded
+            // rectangle which is shrunk by the spread and moved by the offset.
+            let spread = Vector::new(self.shadow_spread, self.shadow_spread);
+            let inner_half_size = (self.size * 0.5 - spread).max(Vector::zero());
+            let inner_center = self.pos + self.shadow_offset + self.size * 0.5;
+
+            let inner_radius = (select_border_radius(self.border_radius, local_pos, inner_center)
+                - self.shadow_spread)
+                .clamp(0.0, inner_half_size.x.min(inner_half_size.y));
+
+            let inner_distance = if self.ellipse {
+                ellipse_sdf(local_pos - inner_center, inner_half_size)
+            } else {
+                signed_rounded_box_sdf(local_pos - inner_center, inner_half_size, inner_radius)
+            };
+
+            // Invert the falloff so that the shadow is darkest outside of the
+            // inner rectangle.
+            let shadow_alpha = smoothstep(-blur, blur, inner_distance) * self.shadow_color[3];
+
+            // Composite the shadow over the background and border. The result
+            // is then clipped to the quad below.
+            let out_alpha = shadow_alpha + mixed_color[3] * (1.0 - shadow_alpha);
+            if out_alpha > 0.0 {
+                let rgb = |i: usize| {
+                    (self.shadow_color[i] * shadow_alpha
+                        + mixed_color[i] * mixed_color[3] * (1.0 - shadow_alpha))
+                        / out_alpha
+                };
+
+                mixed_color = [rgb(0), rgb(1), rgb(2), out_alpha];
+            }
+        }
+
+        let quad_color = [
+            mixed_color[0],
+            mixed_color[1],
+            mixed_color[2],
+            mixed_color[3] * radius_alpha,
+        ];
+
+        if self.shadow_color[3] > 0.0 && !self.shadow_inset {
+            let spread = Vector::new(self.shadow_spread, self.shadow_spread);
+            let shadow_half_size = (self.size * 0.5 + spread).max(Vector::zero());
+            let shadow_center = self.pos + self.shadow_offset + self.size * 0.5;
+
+            let shadow_radius =
+                (select_border_radius(self.border_radius, local_pos, shadow_center)
+                    + self.shadow_spread)
+                    .clamp(0.0, shadow_half_size.x.min(shadow_half_size.y));
+
+            let shadow_distance = if self.ellipse {
+                ellipse_sdf(local_pos - shadow_center, shadow_half_size)
+            } else {
+                signed_rounded_box_sdf(local_pos - shadow_center, shadow_half_size, shadow_radius)
+            };
+
+            // Clip the shadow out from under the quad so that it doesn't show
+            // through semi-transparent quads.
+            let shadow_alpha = (1.0 - smoothstep(-blur, blur, shadow_distance))
+                * self.shadow_color[3]
+                * (1.0 - radius_alpha);
+
+            // Composite the quad over the shadow.
+            let out_alpha = quad_color[3] + shadow_alpha * (1.0 - quad_color[3]);
+            if out_alpha <= 0.0 {
+                return [0.0; 4];
+            }
+
+            let rgb = |i: usize| {
+                (quad_color[i] * quad_color[3]
+                    + self.shadow_color[i] * shadow_alpha * (1.0 - quad_color[3]))
+                    / out_alpha
+            };
+
+            return [rgb(0), rgb(1), rgb(2), out_alpha];
+        }
+
+        quad_color
+    }
+}
+
+// The following functions are ports of the functions of the same name in
+// `rootvg-quad/src/shader/quad.wgsl`.
+
+#[cfg(feature = "quad")]
+fn distance_alg(frag_coord: Vector, position: Vector, size: Vector, radius: f32) -> f32 {
+    let inner_half_size = (size - Vector::new(radius, radius) * 2.0) / 2.0;
+    let top_left = position + Vector::new(radius, radius);
+    rounded_box_sdf(
+        frag_coord - top_left - inner_half_size,
+        inner_half_size,
+        0.0,
+    )
+}
+
+#[cfg(feature = "quad")]
+fn rounded_box_sdf(to_center: Vector, size: Vector, radius: f32) -> f32 {
+    (to_center.abs() - size + Vector::new(radius, radius))
+        .max(Vector::zero())
+        .length()
+        - radius
+}
+
+#[cfg(feature = "quad")]
+fn signed_rounded_box_sdf(to_center: Vector, half_size: Vector, radius: f32) -> f32 {
+    let q = to_center.abs() - half_size + Vector::new(radius, radius);
+    q.x.max(q.y).min(0.0) + q.max(Vector::zero()).length() - radius
+}
+
+#[cfg(feature = "quad")]
+fn ellipse_sdf(to_center: Vector, radii: Vector) -> f32 {
+    let r = radii.max(Vector::new(0.0001, 0.0001));
+    let k0 = Vector::new(to_center.x / r.x, to_center.y / r.y).length();
+    let k1 = Vector::new(to_center.x / (r.x * r.x), to_center.y / (r.y * r.y)).length();
+
+    if k1 == 0.0 {
+        return -r.x.min(r.y);
+    }
+
+    k0 * (k0 - 1.0) / k1
+}
+
+/// Returns the coverage of the ellipse and the amount with which its border
+/// is mixed in.
+#[cfg(feature = "quad")]
+fn ellipse_coverage(
+    frag_coord: Vector,
+    position: Vector,
+    size: Vector,
+    border_width: [f32; 4],
+) -> (f32, f32) {
+    let [top, right, bottom, left] = border_width;
+
+    let half_size = size * 0.5;
+    let alpha = 1.0
+        - smoothstep(
+            -0.5,
+            0.5,
+            ellipse_sdf(frag_coord - position - half_size, half_size),
+        );
+
+    let mut border_mix = 0.0;
+    if border_width.iter().any(|w| *w > 0.0) {
+        let inner_half_size = (size - Vector::new(left + right, top + bottom)) * 0.5;
+
+        border_mix = if inner_half_size.x <= 0.0 || inner_half_size.y <= 0.0 {
+            1.0
+        } else {
+            let inner_center = position + Vector::new(left, top) + inner_half_size;
+            smoothstep(
+                -0.5,
+                0.5,
+                ellipse_sdf(frag_coord - inner_center, inner_half_size),
+            )
+        };
+    }
+
+    (alpha, border_mix)
+}
+
+#[cfg(feature = "quad")]
+fn arc_end_distance(to_center: Vector, angle: f32, radius: f32, half_thickness: f32) -> f32 {
+    let dir = Vector::new(angle.cos(), angle.sin());
+    let along = to_center
+        .dot(dir)
+        .clamp(radius - half_thickness, radius + half_thickness);
+    (to_center - dir * along).length()
+}
+
+#[cfg(feature = "quad")]
+fn arc_sdf(
+    to_center: Vector,
+    radius: f32,
+    half_thickness: f32,
+    start: f32,
+    sweep: f32,
+    round_caps: bool,
+) -> f32 {
+    let ring = (to_center.length() - radius).abs() - half_thickness;
+
+    if sweep >= 6.2831 {
+        return ring;
+    }
+
+    let angle = (to_center.y.atan2(to_center.x) - start).rem_euclid(std::f32::consts::TAU);
+    let in_sweep = angle <= sweep;
+
+    if round_caps {
+        let start_end = Vector::new(start.cos(), start.sin()) * radius;
+        let end_end = Vector::new((start + sweep).cos(), (start + sweep).sin()) * radius;
+
+        let caps = (to_center - start_end)
+            .length()
+            .min((to_center - end_end).length())
+            - half_thickness;
+        return if in_sweep { ring.min(caps) } else { caps };
+    }
+
+    let ends = arc_end_distance(to_center, start, radius, half_thickness).min(arc_end_distance(
+        to_center,
+        start + sweep,
+        radius,
+        half_thickness,
+    ));
+    if in_sweep {
+        ring.max(-ends)
+    } else {
+        ends
+    }
+}
+
+#[cfg(feature = "quad")]
+fn arc_coverage(
+    to_center: Vector,
+    [start, sweep, round_caps, radius]: [f32; 4],
+    thickness: f32,
+) -> f32 {
+    let half_thickness = thickness.max(1.0) * 0.5;
+    let fade = thickness.clamp(0.0, 1.0);
+
+    let dist = arc_sdf(
+        to_center,
+        radius,
+        half_thickness,
+        start,
+        sweep,
+        round_caps > 0.5,
+    );
+
+    (1.0 - smoothstep(-0.5, 0.5, dist)) * fade
+}
+
+#[cfg(feature = "quad")]
+fn select_border_radius(radi: [f32; 4], position: Vector, center: Vector) -> f32 {
+    let rx = if position.x > center.x {
+        radi[1]
+    } else {
+        radi[0]
+    };
+    let ry = if position.x > center.x {
+        radi[2]
+    } else {
+        radi[3]
+    };
+    if position.y > center.y {
+        ry
+    } else {
+        rx
+    }
+}
+
+#[cfg(feature = "quad")]
+fn select_border_widths(widths: [f32; 4], position: Vector, center: Vector) -> Vector {
+    Vector::new(
+        if position.x > center.x {
+            widths[1]
+        } else {
+            widths[3]
+        },
+        if position.y > center.y {
+            widths[2]
+        } else {
+            widths[0]
+        },
+    )
+}
+
+/// The same as `smoothstep` in WGSL.
+#[cfg(feature = "quad")]
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// The same as `mix` in WGSL.
+#[cfg(feature = "quad")]
+fn mix(a: [f32; 4], b: [f32; 4], t: f32) -> [f32; 4] {
+    std::array::from_fn(|i| a[i] + (b[i] - a[i]) * t)
+}
@@ -1,5 +1,11 @@
 #[derive(thiserror::Error, Debug)]
 pub enum RenderError {
+    /// [`PrepareError::AtlasFull`] is returned when the glyphs of a single
+    /// frame don't fit into the glyph atlas. The atlas grows as needed up to
+    /// the `max_texture_dimension_2d` limit of the device, and glyphs which
+    /// are not used in the frame are evicted before this error is returned.
+    ///
+    /// [`PrepareError::AtlasFull`]: crate::text::glyphon::PrepareError::AtlasFull
     #[cfg(feature = "text")]
     #[error("glyphon prepare error: {0}")]
     GlyphonPrepareError(#[from] crate::text::glyphon::PrepareError),
@@ -20,6 +26,36 @@ pub enum RenderError {
     #[error("custom pipeline render error: {0}")]
     CustomPipelineRenderError(Box<dyn std::error::Error>),
 
+    /// A GPU buffer could not be allocated, either because it would exceed
+    /// the `max_buffer_size` limit of the device or because the device ran
+    /// out of memory.
+    ///
+    /// The frame is not rendered, but the canvas is left in a valid state,
+    /// so rendering can be retried with fewer primitives.
+    #[error("failed to allocate a GPU buffer of {requested_bytes} bytes")]
+    OutOfMemory { requested_bytes: u64 },
+
     #[error("unkown render error")]
     Unkown,
 }
+
+impl From<rootvg_core::buffer::BufferAllocationError> for RenderError {
+    fn from(e: rootvg_core::buffer::BufferAllocationError) -> Self {
+        Self::OutOfMemory {
+            requested_bytes: e.requested_bytes,
+        }
+    }
+}
+
+/// An error that occured while reading back the contents of a texture.
+#[derive(thiserror::Error, Debug)]
+pub enum ReadbackError {
+    #[error("reading back textures with format {0:?} is not supported")]
+    UnsupportedFormat(wgpu::TextureFormat),
+    #[error("the rectangle {0:?} is out of bounds of the texture")]
+    OutOfBounds(crate::math::RectI32),
+    #[error("no frame has been rendered with readback enabled")]
+    NoRenderedFrame,
+    #[error("failed to map readback buffer: {0}")]
+    BufferAsyncError(#[from] wgpu::BufferAsyncError),
+}
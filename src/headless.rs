@@ -0,0 +1,305 @@
+use wgpu::MultisampleState;
+
+use crate::{
+    canvas::CanvasConfig,
+    color::PackedSrgb,
+    error::{ReadbackError, RenderError},
+    math::{PhysicalSizeI32, PointI32, RectI32},
+    Canvas,
+};
+
+#[cfg(feature = "text")]
+use crate::text::FontSystem;
+
+#[cfg(all(feature = "text", feature = "svg-icons"))]
+use crate::text::svg::SvgIconSystem;
+
+pub use image::RgbaImage;
+
+#[derive(Debug)]
+pub struct HeadlessConfig {
+    pub power_preference: wgpu::PowerPreference,
+    pub instance_descriptor: wgpu::InstanceDescriptor,
+    pub force_fallback_adapter: bool,
+    pub limits: Option<wgpu::Limits>,
+    pub memory_hints: wgpu::MemoryHints,
+    /// The format of the render targets. This must be one of the 8 bit RGBA or
+    /// BGRA formats.
+    ///
+    /// By default this is `Rgba8UnormSrgb`, or `Rgba8Unorm` if the `web-colors`
+    /// feature is enabled.
+    pub format: wgpu::TextureFormat,
+
+    #[cfg(feature = "msaa")]
+    pub antialiasing: Option<rootvg_msaa::Antialiasing>,
+}
+
+impl Default for HeadlessConfig {
+    fn default() -> Self {
+        Self {
+            power_preference: wgpu::PowerPreference::None,
+            instance_descriptor: wgpu::InstanceDescriptor {
+                backends: wgpu::Backends::all(),
+                ..Default::default()
+            },
+            force_fallback_adapter: false,
+            limits: None,
+            memory_hints: wgpu::MemoryHints::default(),
+
+            // Gamma correction
+            #[cfg(not(feature = "web-colors"))]
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+
+            // No gamma correction
+            #[cfg(feature = "web-colors")]
+            format: wgpu::TextureFormat::Rgba8Unorm,
+
+            // The WebGPU spec only gaurantees a sample count of 1 or 4
+            #[cfg(feature = "msaa")]
+            antialiasing: Some(rootvg_msaa::Antialiasing::MSAAx4),
+        }
+    }
+}
+
+/// A wgpu device and queue which are not tied to a window, useful for
+/// automated screenshot tests and generating thumbnails.
+pub struct HeadlessContext {
+    pub device: wgpu::Device,
+    pub queue: wgpu::Queue,
+    format: wgpu::TextureFormat,
+
+    #[cfg(feature = "msaa")]
+    antialiasing: Option<rootvg_msaa::Antialiasing>,
+}
+
+impl HeadlessContext {
+    pub fn new(config: HeadlessConfig) -> Result<Self, NewHeadlessError> {
+        pollster::block_on(Self::new_async(config))
+    }
+
+    async fn new_async(config: HeadlessConfig) -> Result<Self, NewHeadlessError> {
+        let HeadlessConfig {
+            power_preference,
+            instance_descriptor,
+            force_fallback_adapter,
+            limits,
+            memory_hints,
+            format,
+            #[cfg(feature = "msaa")]
+            antialiasing,
+        } = config;
+
+        let instance = wgpu::Instance::new(instance_descriptor);
+
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference,
+                compatible_surface: None,
+                force_fallback_adapter,
+            })
+            .await
+            .ok_or_else(|| NewHeadlessError::CouldNotGetAdapter)?;
+
+        let limits_vec = if let Some(limits) = limits {
+            vec![limits]
+        } else {
+            vec![wgpu::Limits::default(), wgpu::Limits::downlevel_defaults()]
+        };
+
+        #[allow(unused_mut)]
+        let mut required_features = wgpu::Features::empty();
+        #[cfg(feature = "msaa")]
+        if adapter
+            .features()
+            .contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES)
+        {
+            required_features.insert(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES);
+        }
+
+        let mut device_and_queue = None;
+        for limits in limits_vec.into_iter() {
+            device_and_queue = adapter
+                .request_device(
+                    &wgpu::DeviceDescriptor {
+                        label: Some("rootvg::headless device descriptor"),
+                        required_features,
+                        required_limits: wgpu::Limits {
+                            max_bind_groups: 4,
+                            ..limits
+                        },
+                        memory_hints: memory_hints.clone(),
+                    },
+                    None,
+                )
+                .await
+                .ok();
+
+            if device_and_queue.is_some() {
+                break;
+            }
+        }
+        let (device, queue) =
+            device_and_queue.ok_or_else(|| NewHeadlessError::NoDeviceWithCompatibleLimits)?;
+
+        #[cfg(feature = "msaa")]
+        let antialiasing = antialiasing.filter(|aa| {
+            let flags = adapter.get_texture_format_features(format).flags;
+
+            if !aa.is_supported(flags) {
+                log::warn!("antialiasing mode {aa:?} is not supported, disabling antialiasing");
+                return false;
+            }
+
+            true
+        });
+
+        Ok(Self {
+            device,
+            queue,
+            format,
+            #[cfg(feature = "msaa")]
+            antialiasing,
+        })
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+
+    pub fn canvas_config(&self) -> CanvasConfig {
+        #[cfg(feature = "msaa")]
+        let sample_count = self.antialiasing.map(|aa| aa.sample_count()).unwrap_or(1);
+
+        #[cfg(feature = "msaa")]
+        let fxaa = self.antialiasing == Some(rootvg_msaa::Antialiasing::Fxaa);
+
+        #[cfg(not(feature = "msaa"))]
+        let (sample_count, fxaa) = (1, false);
+
+        CanvasConfig {
+            multisample: MultisampleState {
+                count: sample_count,
+                ..Default::default()
+            },
+            fxaa,
+            ..Default::default()
+        }
+    }
+
+    /// Allocate a texture which can be rendered to and then read back into
+    /// CPU memory.
+    pub fn create_render_target(&self, physical_size: PhysicalSizeI32) -> HeadlessRenderTarget {
+        assert!(physical_size.width > 0);
+        assert!(physical_size.height > 0);
+
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("rootvg headless render target"),
+            size: wgpu::Extent3d {
+                width: physical_size.width as u32,
+                height: physical_size.height as u32,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        HeadlessRenderTarget {
+            texture,
+            view,
+            physical_size,
+        }
+    }
+
+    /// Read back the contents of the given render target.
+    ///
+    /// This blocks until the GPU has finished all submitted work.
+    pub fn read_target(&self, target: &HeadlessRenderTarget) -> Result<RgbaImage, ReadbackError> {
+        let pixels = crate::readback::read_texture_rgba8(
+            &self.device,
+            &self.queue,
+            &target.texture,
+            RectI32::new(PointI32::new(0, 0), target.physical_size),
+        )?;
+
+        Ok(RgbaImage::from_raw(
+            target.physical_size.width as u32,
+            target.physical_size.height as u32,
+            pixels,
+        )
+        .unwrap())
+    }
+
+    /// Render the canvas into a new texture and read back the result.
+    ///
+    /// The `physical_size` must be the same size that was passed to
+    /// [`Canvas::begin`]. The canvas must have been created with the device
+    /// and format of this context.
+    pub fn render_to_image(
+        &self,
+        canvas: &mut Canvas,
+        physical_size: PhysicalSizeI32,
+        clear_color: Option<PackedSrgb>,
+        #[cfg(feature = "text")] font_system: &mut FontSystem,
+        #[cfg(all(feature = "text", feature = "svg-icons"))] svg_icon_system: &mut SvgIconSystem,
+    ) -> Result<RgbaImage, HeadlessRenderError> {
+        let target = self.create_render_target(physical_size);
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("rootvg headless encoder"),
+            });
+
+        canvas.render_to_target(
+            clear_color,
+            &self.device,
+            &self.queue,
+            &mut encoder,
+            &target.view,
+            physical_size,
+            #[cfg(feature = "text")]
+            font_system,
+            #[cfg(all(feature = "text", feature = "svg-icons"))]
+            svg_icon_system,
+        )?;
+
+        self.queue.submit(Some(encoder.finish()));
+
+        Ok(self.read_target(&target)?)
+    }
+}
+
+/// A texture created by [`HeadlessContext::create_render_target`].
+pub struct HeadlessRenderTarget {
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    physical_size: PhysicalSizeI32,
+}
+
+impl HeadlessRenderTarget {
+    pub fn physical_size(&self) -> PhysicalSizeI32 {
+        self.physical_size
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum NewHeadlessError {
+    #[error("failed to get compatible wgpu adapter")]
+    CouldNotGetAdapter,
+    #[error("could not find wgpu device with compatible limits")]
+    NoDeviceWithCompatibleLimits,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum HeadlessRenderError {
+    #[error("{0}")]
+    Render(#[from] RenderError),
+    #[error("{0}")]
+    Readback(#[from] ReadbackError),
+}
@@ -1,18 +1,43 @@
 #[cfg(feature = "default-surface")]
 pub mod surface;
 
+#[cfg(feature = "headless")]
+pub mod headless;
+
+#[cfg(feature = "cpu-raster")]
+pub mod cpu_raster;
+
 mod canvas;
 mod primitive;
 mod primitive_group;
+mod readback;
+
+#[cfg(test)]
+mod test_util;
+
+#[cfg(all(feature = "text", feature = "quad"))]
+mod text_cursor;
 
 pub mod error;
 
-pub use canvas::{Canvas, CanvasCtx};
+pub use canvas::{Canvas, CanvasConfig, CanvasCtx, TargetKind};
+
+#[cfg(feature = "frame-stats")]
+pub use canvas::{FrameStats, PipelineStats};
 pub use primitive::Primitive;
 pub use primitive_group::PrimitiveGroup;
 
+#[cfg(feature = "quad")]
+pub use primitive_group::GridAxis;
+
+#[cfg(all(feature = "text", feature = "quad"))]
+pub use text_cursor::text_cursor_quad;
+
 pub use rootvg_core::*;
 
+#[cfg(feature = "backdrop-blur")]
+pub use rootvg_blur as blur;
+
 #[cfg(feature = "image")]
 pub use rootvg_image as image;
 
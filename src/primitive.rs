@@ -3,10 +3,10 @@ use crate::mesh::GradientMeshPrimitive;
 #[cfg(any(feature = "mesh", feature = "tessellation"))]
 use crate::mesh::{MeshPrimitive, SolidMeshPrimitive};
 
+#[cfg(feature = "quad")]
+use crate::quad::{ArcQuad, Line, LinePrimitive, QuadPrimitive, SolidQuad, SolidQuadPrimitive};
 #[cfg(all(feature = "quad", feature = "gradient"))]
 use crate::quad::{GradientQuad, GradientQuadPrimitive};
-#[cfg(feature = "quad")]
-use crate::quad::{QuadPrimitive, SolidQuad, SolidQuadPrimitive};
 
 #[cfg(feature = "text")]
 use crate::text::TextPrimitive;
@@ -14,15 +14,21 @@ use crate::text::TextPrimitive;
 #[cfg(feature = "image")]
 use crate::image::ImagePrimitive;
 
+#[cfg(feature = "backdrop-blur")]
+use crate::blur::BackdropBlurPrimitive;
+
 #[cfg(feature = "custom-primitive")]
 use crate::pipeline::CustomPrimitive;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Primitive {
     #[cfg(feature = "quad")]
     SolidQuad(SolidQuadPrimitive),
     #[cfg(all(feature = "quad", feature = "gradient"))]
     GradientQuad(GradientQuadPrimitive),
+    #[cfg(feature = "quad")]
+    Line(LinePrimitive),
 
     #[cfg(any(feature = "mesh", feature = "tessellation"))]
     SolidMesh(SolidMeshPrimitive),
@@ -35,7 +41,12 @@ pub enum Primitive {
     #[cfg(feature = "image")]
     Image(ImagePrimitive),
 
+    #[cfg(feature = "backdrop-blur")]
+    BackdropBlur(BackdropBlurPrimitive),
+
+    /// Note, custom primitives cannot be serialized.
     #[cfg(feature = "custom-primitive")]
+    #[cfg_attr(feature = "serde", serde(skip))]
     Custom(CustomPrimitive),
 }
 
@@ -53,6 +64,20 @@ impl From<GradientQuadPrimitive> for Primitive {
     }
 }
 
+#[cfg(feature = "quad")]
+impl From<LinePrimitive> for Primitive {
+    fn from(p: LinePrimitive) -> Self {
+        Primitive::Line(p)
+    }
+}
+
+#[cfg(feature = "quad")]
+impl From<Line> for Primitive {
+    fn from(p: Line) -> Self {
+        Primitive::Line(LinePrimitive::new(&p))
+    }
+}
+
 #[cfg(feature = "quad")]
 impl From<SolidQuad> for Primitive {
     fn from(p: SolidQuad) -> Self {
@@ -60,6 +85,13 @@ impl From<SolidQuad> for Primitive {
     }
 }
 
+#[cfg(feature = "quad")]
+impl From<ArcQuad> for Primitive {
+    fn from(p: ArcQuad) -> Self {
+        Primitive::SolidQuad(p.packed())
+    }
+}
+
 #[cfg(all(feature = "quad", feature = "gradient"))]
 impl From<GradientQuad> for Primitive {
     fn from(p: GradientQuad) -> Self {
@@ -121,6 +153,13 @@ impl From<ImagePrimitive> for Primitive {
     }
 }
 
+#[cfg(feature = "backdrop-blur")]
+impl From<BackdropBlurPrimitive> for Primitive {
+    fn from(p: BackdropBlurPrimitive) -> Self {
+        Primitive::BackdropBlur(p)
+    }
+}
+
 #[cfg(feature = "custom-primitive")]
 impl From<CustomPrimitive> for Primitive {
     fn from(p: CustomPrimitive) -> Self {
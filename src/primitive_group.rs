@@ -3,6 +3,13 @@ use smallvec::{smallvec, SmallVec};
 
 use crate::{math::RectI32, Primitive};
 
+#[cfg(feature = "quad")]
+use crate::color::PackedSrgb;
+#[cfg(feature = "quad")]
+use crate::math::{Point, Size};
+
+mod svg;
+
 #[cfg(all(any(feature = "mesh", feature = "tessellation"), feature = "gradient"))]
 use crate::mesh::GradientMeshPrimitive;
 #[cfg(any(feature = "mesh", feature = "tessellation"))]
@@ -11,7 +18,7 @@ use crate::mesh::{MeshPrimitive, SolidMeshPrimitive};
 #[cfg(all(feature = "quad", feature = "gradient"))]
 use crate::quad::GradientQuadPrimitive;
 #[cfg(feature = "quad")]
-use crate::quad::SolidQuadPrimitive;
+use crate::quad::{LinePrimitive, QuadFlags, SolidQuad, SolidQuadPrimitive};
 
 #[cfg(feature = "text")]
 use crate::text::TextPrimitive;
@@ -19,6 +26,9 @@ use crate::text::TextPrimitive;
 #[cfg(feature = "image")]
 use crate::image::ImagePrimitive;
 
+#[cfg(feature = "backdrop-blur")]
+use crate::blur::BackdropBlurPrimitive;
+
 #[cfg(feature = "custom-primitive")]
 use crate::pipeline::CustomPrimitive;
 
@@ -26,7 +36,14 @@ const STATIC_ALLOC_PRIMITIVES: usize = 4;
 
 /// A group of primitives that can be added to a canvas. This is usally
 /// the output of a single widget.
+///
+/// With the `serde` feature enabled, a group can be serialized in order to
+/// record and replay a frame. Meshes are serialized by value, while text
+/// buffers and textures are serialized as opaque IDs that must be resolved
+/// with [`crate::serde_handle::with_resolver`] when deserializing. Custom
+/// primitives and svg icons cannot be serialized.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PrimitiveGroup {
     pub(crate) primitive_batches: SmallVec<[PrimitiveBatchSlice; STATIC_ALLOC_PRIMITIVES]>,
     current_scissor_rect: Option<RectI32>,
@@ -90,6 +107,8 @@ impl PrimitiveGroup {
             Primitive::SolidQuad(p) => self.add_solid_quad(p),
             #[cfg(all(feature = "quad", feature = "gradient"))]
             Primitive::GradientQuad(p) => self.add_gradient_quad(p),
+            #[cfg(feature = "quad")]
+            Primitive::Line(p) => self.add_line(p),
 
             #[cfg(any(feature = "mesh", feature = "tessellation"))]
             Primitive::SolidMesh(p) => self.add_solid_mesh(p),
@@ -102,6 +121,9 @@ impl PrimitiveGroup {
             #[cfg(feature = "image")]
             Primitive::Image(p) => self.add_image(p),
 
+            #[cfg(feature = "backdrop-blur")]
+            Primitive::BackdropBlur(p) => self.add_backdrop_blur(p),
+
             #[cfg(feature = "custom-primitive")]
             Primitive::Custom(p) => self.add_custom_primitive(p),
         }
@@ -163,6 +185,34 @@ impl PrimitiveGroup {
         };
     }
 
+    #[cfg(feature = "quad")]
+    pub fn add_line(&mut self, line: impl Into<LinePrimitive>) {
+        let line = line.into();
+
+        if self.create_new_batch {
+            self.create_new_batch = false;
+
+            self.primitive_batches.push(PrimitiveBatchSlice {
+                z_index: self.current_z_index,
+                scissor_rect: self.current_scissor_rect,
+                kind: PrimitiveBatchKind::Line(smallvec![line]),
+            });
+        } else {
+            // `self.create_new_batch` is never `false` when `self.primitve_batches` is empty
+            let last_batch = self.primitive_batches.last_mut().unwrap();
+
+            if let PrimitiveBatchKind::Line(batch) = &mut last_batch.kind {
+                batch.push(line);
+            } else {
+                self.primitive_batches.push(PrimitiveBatchSlice {
+                    z_index: self.current_z_index,
+                    scissor_rect: self.current_scissor_rect,
+                    kind: PrimitiveBatchKind::Line(smallvec![line]),
+                });
+            }
+        };
+    }
+
     #[cfg(any(feature = "mesh", feature = "tessellation"))]
     pub fn add_solid_mesh(&mut self, mesh: impl Into<SolidMeshPrimitive>) {
         let mesh: SolidMeshPrimitive = mesh.into();
@@ -288,6 +338,34 @@ impl PrimitiveGroup {
         }
     }
 
+    #[cfg(feature = "backdrop-blur")]
+    pub fn add_backdrop_blur(&mut self, blur: impl Into<BackdropBlurPrimitive>) {
+        let blur: BackdropBlurPrimitive = blur.into();
+
+        if self.create_new_batch {
+            self.create_new_batch = false;
+
+            self.primitive_batches.push(PrimitiveBatchSlice {
+                z_index: self.current_z_index,
+                scissor_rect: self.current_scissor_rect,
+                kind: PrimitiveBatchKind::BackdropBlur(smallvec![blur]),
+            });
+        } else {
+            // `self.create_new_batch` is never `false` when `self.primitve_batches` is empty
+            let last_batch = self.primitive_batches.last_mut().unwrap();
+
+            if let PrimitiveBatchKind::BackdropBlur(batch) = &mut last_batch.kind {
+                batch.push(blur);
+            } else {
+                self.primitive_batches.push(PrimitiveBatchSlice {
+                    z_index: self.current_z_index,
+                    scissor_rect: self.current_scissor_rect,
+                    kind: PrimitiveBatchKind::BackdropBlur(smallvec![blur]),
+                });
+            }
+        }
+    }
+
     #[cfg(feature = "custom-primitive")]
     pub fn add_custom_primitive(&mut self, primitive: CustomPrimitive) {
         if self.create_new_batch {
@@ -384,6 +462,84 @@ impl PrimitiveGroup {
         }
     }
 
+    #[cfg(feature = "quad")]
+    pub fn add_line_batch(&mut self, lines: impl IntoIterator<Item = impl Into<LinePrimitive>>) {
+        if self.create_new_batch {
+            self.create_new_batch = false;
+
+            self.primitive_batches.push(PrimitiveBatchSlice {
+                z_index: self.current_z_index,
+                scissor_rect: self.current_scissor_rect,
+                kind: PrimitiveBatchKind::Line(lines.into_iter().map(|line| line.into()).collect()),
+            });
+        } else {
+            // `self.create_new_batch` is never `false` when `self.primitve_batches` is empty
+            let last_batch = self.primitive_batches.last_mut().unwrap();
+
+            if let PrimitiveBatchKind::Line(batch) = &mut last_batch.kind {
+                for line in lines.into_iter() {
+                    batch.push(line.into());
+                }
+            } else {
+                self.primitive_batches.push(PrimitiveBatchSlice {
+                    z_index: self.current_z_index,
+                    scissor_rect: self.current_scissor_rect,
+                    kind: PrimitiveBatchKind::Line(
+                        lines.into_iter().map(|line| line.into()).collect(),
+                    ),
+                });
+            }
+        }
+    }
+
+    /// Add `count` evenly-spaced grid lines, for example the beat and bar
+    /// lines of a timeline or the rows of a piano roll.
+    ///
+    /// * `origin` - The top-left corner of the first line in logical points.
+    /// * `spacing` - The distance between the start of each line in logical
+    ///   points.
+    /// * `length` - The length of each line in logical points.
+    /// * `axis` - The direction in which the lines run.
+    /// * `width` - The width of each line in logical points.
+    ///
+    /// The lines are drawn as solid quads whose edges are snapped to the
+    /// nearest physical pixel, so that thin lines stay crisp even when the
+    /// spacing is not a whole number of pixels. All of the lines are added to
+    /// a single batch.
+    #[cfg(feature = "quad")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_grid(
+        &mut self,
+        origin: Point,
+        spacing: f32,
+        count: usize,
+        length: f32,
+        axis: GridAxis,
+        color: impl Into<PackedSrgb>,
+        width: f32,
+    ) {
+        let color = color.into();
+
+        let size = match axis {
+            GridAxis::Vertical => Size::new(width, length),
+            GridAxis::Horizontal => Size::new(length, width),
+        };
+
+        self.add_solid_quad_batch((0..count).map(|i| {
+            let offset = spacing * i as f32;
+            let position = match axis {
+                GridAxis::Vertical => Point::new(origin.x + offset, origin.y),
+                GridAxis::Horizontal => Point::new(origin.x, origin.y + offset),
+            };
+
+            SolidQuad::builder(size)
+                .position(position)
+                .bg_color(color)
+                .flags(QuadFlags::SNAP_EDGES_TO_NEAREST_PIXEL)
+                .build()
+        }));
+    }
+
     #[cfg(feature = "text")]
     pub fn add_text_batch(&mut self, buffers: impl IntoIterator<Item = TextPrimitive>) {
         if self.create_new_batch {
@@ -479,7 +635,20 @@ impl PrimitiveGroup {
     }
 }
 
+/// The direction in which the lines of a grid run.
+///
+/// See [`PrimitiveGroup::add_grid`].
+#[cfg(feature = "quad")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GridAxis {
+    /// The lines run from top to bottom and are spaced out horizontally.
+    Vertical,
+    /// The lines run from left to right and are spaced out vertically.
+    Horizontal,
+}
+
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub(crate) struct PrimitiveBatchSlice {
     pub(crate) kind: PrimitiveBatchKind,
     pub(crate) z_index: ZIndex,
@@ -487,11 +656,14 @@ pub(crate) struct PrimitiveBatchSlice {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub(crate) enum PrimitiveBatchKind {
     #[cfg(feature = "quad")]
     SolidQuad(SmallVec<[SolidQuadPrimitive; STATIC_ALLOC_PRIMITIVES]>),
     #[cfg(all(feature = "quad", feature = "gradient"))]
     GradientQuad(SmallVec<[GradientQuadPrimitive; STATIC_ALLOC_PRIMITIVES]>),
+    #[cfg(feature = "quad")]
+    Line(SmallVec<[LinePrimitive; STATIC_ALLOC_PRIMITIVES]>),
 
     #[cfg(feature = "text")]
     Text(SmallVec<[TextPrimitive; STATIC_ALLOC_PRIMITIVES]>),
@@ -504,6 +676,49 @@ pub(crate) enum PrimitiveBatchKind {
     #[cfg(feature = "image")]
     Image(SmallVec<[ImagePrimitive; STATIC_ALLOC_PRIMITIVES]>),
 
+    #[cfg(feature = "backdrop-blur")]
+    BackdropBlur(SmallVec<[BackdropBlurPrimitive; STATIC_ALLOC_PRIMITIVES]>),
+
     #[cfg(feature = "custom-primitive")]
+    #[cfg_attr(feature = "serde", serde(skip))]
     Custom(SmallVec<[CustomPrimitive; STATIC_ALLOC_PRIMITIVES]>),
 }
+
+#[cfg(all(test, feature = "serde", feature = "quad"))]
+mod tests {
+    use super::*;
+    use crate::color::RGBA8;
+    use crate::math::{PointI32, SizeI32};
+    use crate::quad::Line;
+
+    #[test]
+    fn serde_round_trip() {
+        let quad = SolidQuad::builder(Size::new(30.0, 20.0))
+            .position(Point::new(5.0, 10.0))
+            .bg_color(RGBA8::new(10, 20, 30, 255))
+            .build();
+        let line = Line {
+            from: Point::new(0.0, 0.0),
+            to: Point::new(10.0, 10.0),
+            width: 2.0,
+            color: RGBA8::new(255, 0, 0, 255).into(),
+            ..Default::default()
+        };
+
+        let mut group = PrimitiveGroup::new();
+        group.add_solid_quad(quad);
+        group.set_z_index(1);
+        group.set_scissor_rect(RectI32::new(PointI32::new(1, 2), SizeI32::new(30, 40)));
+        group.add_line(line);
+        group.add_solid_quad(quad);
+
+        let json = serde_json::to_string(&group).unwrap();
+        let deserialized: PrimitiveGroup = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, group);
+
+        let primitive = Primitive::from(quad);
+        let json = serde_json::to_string(&primitive).unwrap();
+        let deserialized: Primitive = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, primitive);
+    }
+}
@@ -0,0 +1,859 @@
+//! Export a [`PrimitiveGroup`] to an SVG document.
+
+use std::fmt::{self, Write};
+
+use super::{PrimitiveBatchKind, PrimitiveGroup};
+use crate::color::{PackedSrgb, RGBA8};
+use crate::math::{Box2D, Point, Rect, Size};
+
+#[cfg(feature = "gradient")]
+use crate::gradient::{InterpolationSpace, PackedGradient, PackedGradientKind};
+
+#[cfg(any(feature = "mesh", feature = "tessellation"))]
+use crate::mesh::MeshUniforms;
+
+#[cfg(all(feature = "quad", feature = "gradient"))]
+use crate::quad::GradientQuadPrimitive;
+#[cfg(feature = "quad")]
+use crate::quad::{LineCap, LinePrimitive, SolidQuadPrimitive};
+
+#[cfg(feature = "text")]
+use crate::text::TextPrimitive;
+
+/// The color of the outlines drawn in place of unsupported primitives.
+const PLACEHOLDER_COLOR: RGBA8 = RGBA8::new(255, 0, 255, 255);
+
+impl PrimitiveGroup {
+    /// Export the primitives in this group to a standalone SVG document.
+    ///
+    /// This is a best-effort vector export meant for debugging layouts, so it
+    /// does not match what the canvas renders exactly:
+    ///
+    /// * Quads become `<rect>` elements (or `<path>` elements if their corners
+    /// have different radii). Their borders are drawn as a stroke inset by half
+    /// of the border width, using the largest of the four widths. Shadows are
+    /// not exported.
+    /// * Lines become `<line>` elements stroked with the color at their start.
+    /// * Meshes become `<path>` elements with one fill per run of triangles. A
+    /// triangle is filled with the average color of its vertices (or the
+    /// gradient of its first vertex).
+    /// * Linear and radial gradients become `<linearGradient>` and
+    /// `<radialGradient>` elements. SVG has no conic gradients, so those are
+    /// filled with the color of their first stop.
+    /// * Text becomes one `<text>` element per laid out line, using the size of
+    /// the first glyph and the color of the primitive. Fonts, rich text colors,
+    /// shadows, outlines, highlights, and icons are not exported.
+    /// * Images and backdrop blurs are drawn as outlines of their bounds, and
+    /// custom primitives are only noted with a comment.
+    ///
+    /// Coordinates are in logical points, so the document has the logical size
+    /// of the content. The document always contains the origin so that the
+    /// positions match the canvas. Scissor rects and the clipping bounds of
+    /// text become `<clipPath>` elements. Batches are ordered by their z index
+    /// the same way as in the canvas.
+    pub fn to_svg(&self) -> String {
+        let mut writer = SvgWriter::default();
+
+        // Batches with the same z index are drawn in the order of their
+        // pipelines in the canvas.
+        let mut batches: Vec<_> = self.primitive_batches.iter().collect();
+        batches.sort_by_key(|batch| (batch.z_index, batch.kind.pipeline_order()));
+
+        for batch in batches {
+            let clip_id = batch.scissor_rect.map(|rect| {
+                writer.clip_path(Rect::new(
+                    Point::new(rect.origin.x as f32, rect.origin.y as f32),
+                    Size::new(rect.size.width as f32, rect.size.height as f32),
+                ))
+            });
+
+            if let Some(clip_id) = &clip_id {
+                writer.line(format_args!(r#"<g clip-path="url(#{clip_id})">"#));
+                writer.indent += 1;
+            }
+
+            match &batch.kind {
+                #[cfg(feature = "quad")]
+                PrimitiveBatchKind::SolidQuad(quads) => {
+                    for quad in quads.iter() {
+                        writer.solid_quad(quad);
+                    }
+                }
+                #[cfg(all(feature = "quad", feature = "gradient"))]
+                PrimitiveBatchKind::GradientQuad(quads) => {
+                    for quad in quads.iter() {
+                        writer.gradient_quad(quad);
+                    }
+                }
+                #[cfg(feature = "quad")]
+                PrimitiveBatchKind::Line(lines) => {
+                    for line in lines.iter() {
+                        writer.line_primitive(line);
+                    }
+                }
+                #[cfg(feature = "text")]
+                PrimitiveBatchKind::Text(texts) => {
+                    for text in texts.iter() {
+                        writer.text(text);
+                    }
+                }
+                #[cfg(any(feature = "mesh", feature = "tessellation"))]
+                PrimitiveBatchKind::SolidMesh(meshes) => {
+                    for mesh in meshes.iter() {
+                        writer.solid_mesh(
+                            &mesh
+                                .mesh
+                                .buffers
+                                .vertices
+                                .iter()
+                                .map(|v| (v.position, v.color))
+                                .collect::<Vec<_>>(),
+                            &mesh.mesh.buffers.indices,
+                            &mesh.uniform,
+                        );
+                    }
+                }
+                #[cfg(all(any(feature = "mesh", feature = "tessellation"), feature = "gradient"))]
+                PrimitiveBatchKind::GradientMesh(meshes) => {
+                    for mesh in meshes.iter() {
+                        writer.gradient_mesh(
+                            &mesh
+                                .mesh
+                                .buffers
+                                .vertices
+                                .iter()
+                                .map(|v| (v.position, v.gradient))
+                                .collect::<Vec<_>>(),
+                            &mesh.mesh.buffers.indices,
+                            &mesh.uniform,
+                        );
+                    }
+                }
+                #[cfg(feature = "image")]
+                PrimitiveBatchKind::Image(images) => {
+                    for image in images.iter() {
+                        writer.placeholder(
+                            "image",
+                            Rect::new(image.vertex.position.into(), image.vertex.size.into()),
+                        );
+                    }
+                }
+                #[cfg(feature = "backdrop-blur")]
+                PrimitiveBatchKind::BackdropBlur(blurs) => {
+                    for blur in blurs.iter() {
+                        writer.placeholder("backdrop blur", blur.bounds);
+                    }
+                }
+                #[cfg(feature = "custom-primitive")]
+                PrimitiveBatchKind::Custom(primitives) => {
+                    for _ in primitives.iter() {
+                        writer.line(format_args!("<!-- custom primitive -->"));
+                    }
+                }
+            }
+
+            if clip_id.is_some() {
+                writer.indent -= 1;
+                writer.line(format_args!("</g>"));
+            }
+        }
+
+        writer.finish()
+    }
+}
+
+impl PrimitiveBatchKind {
+    /// The order in which the pipelines of a batch entry are drawn in the
+    /// canvas.
+    fn pipeline_order(&self) -> u8 {
+        match self {
+            #[cfg(feature = "backdrop-blur")]
+            Self::BackdropBlur(_) => 0,
+            #[cfg(feature = "quad")]
+            Self::SolidQuad(_) => 1,
+            #[cfg(all(feature = "quad", feature = "gradient"))]
+            Self::GradientQuad(_) => 2,
+            #[cfg(feature = "quad")]
+            Self::Line(_) => 3,
+            #[cfg(any(feature = "mesh", feature = "tessellation"))]
+            Self::SolidMesh(_) => 4,
+            #[cfg(all(any(feature = "mesh", feature = "tessellation"), feature = "gradient"))]
+            Self::GradientMesh(_) => 5,
+            #[cfg(feature = "text")]
+            Self::Text(_) => 6,
+            #[cfg(feature = "image")]
+            Self::Image(_) => 7,
+            #[cfg(feature = "custom-primitive")]
+            Self::Custom(_) => 8,
+        }
+    }
+}
+
+#[derive(Default)]
+struct SvgWriter {
+    defs: String,
+    body: String,
+    indent: usize,
+    /// The bounds of everything drawn so far.
+    bounds: Option<Box2D>,
+    /// The clipping rectangles defined so far, so that they can be reused.
+    clip_paths: Vec<Rect>,
+    /// The gradients defined so far, so that they can be reused.
+    #[cfg(feature = "gradient")]
+    gradients: Vec<PackedGradient>,
+}
+
+impl SvgWriter {
+    fn line(&mut self, args: fmt::Arguments) {
+        for _ in 0..self.indent + 1 {
+            self.body.push_str("  ");
+        }
+        self.body.write_fmt(args).unwrap();
+        self.body.push('\n');
+    }
+
+    fn add_bounds(&mut self, rect: Rect) {
+        let rect = rect.to_box2d();
+        self.bounds = Some(match self.bounds {
+            Some(bounds) => union(bounds, rect),
+            None => rect,
+        });
+    }
+
+    /// Returns the id of a `<clipPath>` with the given rectangle.
+    fn clip_path(&mut self, rect: Rect) -> String {
+        if let Some(i) = self.clip_paths.iter().position(|r| *r == rect) {
+            return format!("clip{i}");
+        }
+
+        let i = self.clip_paths.len();
+        self.clip_paths.push(rect);
+
+        writeln!(
+            self.defs,
+            r#"    <clipPath id="clip{i}"><rect x="{}" y="{}" width="{}" height="{}"/></clipPath>"#,
+            Num(rect.min_x()),
+            Num(rect.min_y()),
+            Num(rect.width()),
+            Num(rect.height()),
+        )
+        .unwrap();
+
+        format!("clip{i}")
+    }
+
+    #[cfg(feature = "quad")]
+    fn line_primitive(&mut self, line: &LinePrimitive) {
+        self.add_bounds(line.bounds());
+
+        let cap = match line.cap {
+            c if c == LineCap::Square as u32 => "square",
+            c if c == LineCap::Round as u32 => "round",
+            _ => "butt",
+        };
+
+        self.line(format_args!(
+            r#"<line x1="{}" y1="{}" x2="{}" y2="{}"{} stroke-width="{}" stroke-linecap="{cap}"/>"#,
+            Num(line.from[0]),
+            Num(line.from[1]),
+            Num(line.to[0]),
+            Num(line.to[1]),
+            Paint::color(line.color).attrs("stroke"),
+            Num(line.width),
+        ));
+    }
+
+    #[cfg(feature = "quad")]
+    fn solid_quad(&mut self, quad: &SolidQuadPrimitive) {
+        let shape = QuadShape {
+            position: quad.position,
+            size: quad.size,
+            border_radius: quad.border_radius,
+            border_width: quad.border_width,
+            border_dash: quad.border_dash,
+            border_dash_offset: quad.border_dash_offset,
+            transform: quad.transform,
+        };
+
+        self.quad(
+            &shape,
+            &Paint::color(quad.color),
+            &Paint::color(quad.border_color),
+        );
+    }
+
+    #[cfg(all(feature = "quad", feature = "gradient"))]
+    fn gradient_quad(&mut self, quad: &GradientQuadPrimitive) {
+        let shape = QuadShape {
+            position: quad.position,
+            size: quad.size,
+            border_radius: quad.border_radius,
+            border_width: quad.border_width,
+            border_dash: quad.border_dash,
+            border_dash_offset: quad.border_dash_offset,
+            transform: quad.transform,
+        };
+
+        let fill = self.gradient(&quad.gradient);
+        // A border gradient with no stops means that the border color is used.
+        let stroke = if quad.border_gradient.stops().iter().any(|s| s.is_some()) {
+            self.gradient(&quad.border_gradient)
+        } else {
+            Paint::color(quad.border_color)
+        };
+
+        self.quad(&shape, &fill, &stroke);
+    }
+
+    #[cfg(feature = "quad")]
+    fn quad(&mut self, shape: &QuadShape, fill: &Paint, stroke: &Paint) {
+        let [x, y] = shape.position;
+        let [width, height] = shape.size;
+        if width <= 0.0 || height <= 0.0 {
+            return;
+        }
+
+        let rect = Rect::new(Point::new(x, y), Size::new(width, height));
+
+        // The linear part of the transform is applied about the position of
+        // the quad.
+        let [a, b, c, d] = shape.transform;
+        let transform = if shape.transform == [1.0, 0.0, 0.0, 1.0] {
+            self.add_bounds(rect);
+            String::new()
+        } else {
+            let transform =
+                crate::math::Transform::new(a, b, c, d, x - (a * x + c * y), y - (b * x + d * y));
+            self.add_bounds(transform.outer_transformed_rect(&rect));
+            format!(r#" transform="{}""#, Matrix(transform.to_array()))
+        };
+
+        let max_radius = width.min(height) * 0.5;
+        let radii = shape.border_radius.map(|r| r.clamp(0.0, max_radius));
+
+        if !fill.is_none() {
+            let fill = fill.attrs("fill");
+            self.rounded_rect(rect, radii, format_args!("{fill}{transform}"));
+        }
+
+        // SVG strokes are centered on the edges, while the borders of quads
+        // are inside of the quad.
+        let border_width = shape
+            .border_width
+            .iter()
+            .copied()
+            .fold(0.0, f32::max)
+            .min(max_radius);
+        if border_width > 0.0 && !stroke.is_none() {
+            let half = border_width * 0.5;
+            let inset = rect.inflate(-half, -half);
+            let radii = radii.map(|r| (r - half).max(0.0));
+
+            let mut attrs = format!(
+                r#" fill="none"{} stroke-width="{}""#,
+                stroke.attrs("stroke"),
+                Num(border_width)
+            );
+            if shape.border_dash.iter().any(|d| *d > 0.0) {
+                let [d0, d1, d2, d3] = shape.border_dash.map(Num);
+                write!(attrs, r#" stroke-dasharray="{d0} {d1} {d2} {d3}""#).unwrap();
+                if shape.border_dash_offset != 0.0 {
+                    write!(
+                        attrs,
+                        r#" stroke-dashoffset="{}""#,
+                        Num(shape.border_dash_offset)
+                    )
+                    .unwrap();
+                }
+            }
+
+            self.rounded_rect(inset, radii, format_args!("{attrs}{transform}"));
+        }
+    }
+
+    /// Write a rectangle with the radii `[top_left, top_right, bottom_right, bottom_left]`.
+    #[cfg(feature = "quad")]
+    fn rounded_rect(&mut self, rect: Rect, radii: [f32; 4], attrs: fmt::Arguments) {
+        let (x, y, w, h) = (rect.min_x(), rect.min_y(), rect.width(), rect.height());
+        let [tl, tr, br, bl] = radii;
+
+        if radii.iter().all(|r| *r == tl) {
+            let rx = if tl > 0.0 {
+                format!(r#" rx="{}""#, Num(tl))
+            } else {
+                String::new()
+            };
+
+            self.line(format_args!(
+                r#"<rect x="{}" y="{}" width="{}" height="{}"{rx}{attrs}/>"#,
+                Num(x),
+                Num(y),
+                Num(w),
+                Num(h),
+            ));
+        } else {
+            let (right, bottom) = (x + w, y + h);
+
+            self.line(format_args!(
+                r#"<path d="M{} {} H{} A{tr} {tr} 0 0 1 {} {} V{} A{br} {br} 0 0 1 {} {} H{} A{bl} {bl} 0 0 1 {} {} V{} A{tl} {tl} 0 0 1 {} {} Z"{attrs}/>"#,
+                Num(x + tl),
+                Num(y),
+                Num(right - tr),
+                Num(right),
+                Num(y + tr),
+                Num(bottom - br),
+                Num(right - br),
+                Num(bottom),
+                Num(x + bl),
+                Num(x),
+                Num(bottom - bl),
+                Num(y + tl),
+                Num(x + tl),
+                Num(y),
+                tl = Num(tl),
+                tr = Num(tr),
+                br = Num(br),
+                bl = Num(bl),
+            ));
+        }
+    }
+
+    #[cfg(any(feature = "mesh", feature = "tessellation"))]
+    fn solid_mesh(
+        &mut self,
+        vertices: &[([f32; 2], PackedSrgb)],
+        indices: &[u32],
+        uniform: &MeshUniforms,
+    ) {
+        self.mesh(vertices, indices, uniform, |_, [a, b, c]| {
+            let [a, b, c] = [a.raw(), b.raw(), c.raw()];
+            let avg = |i: usize| ((a[i] + b[i] + c[i]) / 3.0).clamp(0.0, 1.0);
+
+            Paint::color(PackedSrgb::from_srgba(avg(0), avg(1), avg(2), avg(3)))
+        });
+    }
+
+    #[cfg(all(any(feature = "mesh", feature = "tessellation"), feature = "gradient"))]
+    fn gradient_mesh(
+        &mut self,
+        vertices: &[([f32; 2], PackedGradient)],
+        indices: &[u32],
+        uniform: &MeshUniforms,
+    ) {
+        self.mesh(vertices, indices, uniform, |writer, [a, _, _]| {
+            writer.gradient(&a)
+        });
+    }
+
+    /// Write the triangles of a mesh, merging consecutive triangles with the
+    /// same paint into a single path.
+    #[cfg(any(feature = "mesh", feature = "tessellation"))]
+    fn mesh<T: Copy>(
+        &mut self,
+        vertices: &[([f32; 2], T)],
+        indices: &[u32],
+        uniform: &MeshUniforms,
+        mut paint: impl FnMut(&mut Self, [T; 3]) -> Paint,
+    ) {
+        let mut runs: Vec<(Paint, String)> = Vec::new();
+        let mut bounds: Option<Box2D> = None;
+
+        for triangle in indices.chunks_exact(3) {
+            let (Some(&a), Some(&b), Some(&c)) = (
+                vertices.get(triangle[0] as usize),
+                vertices.get(triangle[1] as usize),
+                vertices.get(triangle[2] as usize),
+            ) else {
+                continue;
+            };
+
+            let triangle_paint = paint(self, [a.1, b.1, c.1]);
+            if triangle_paint.is_none() {
+                continue;
+            }
+
+            if runs.last().map(|(p, _)| p) != Some(&triangle_paint) {
+                runs.push((triangle_paint, String::new()));
+            }
+            let d = &mut runs.last_mut().unwrap().1;
+            if !d.is_empty() {
+                d.push(' ');
+            }
+
+            let [[ax, ay], [bx, by], [cx, cy]] = [a.0, b.0, c.0];
+            write!(
+                d,
+                "M{} {} L{} {} L{} {} Z",
+                Num(ax),
+                Num(ay),
+                Num(bx),
+                Num(by),
+                Num(cx),
+                Num(cy)
+            )
+            .unwrap();
+
+            for [x, y] in [a.0, b.0, c.0] {
+                let point = Box2D::new(Point::new(x, y), Point::new(x, y));
+                bounds = Some(bounds.map(|b| union(b, point)).unwrap_or(point));
+            }
+        }
+
+        if runs.is_empty() {
+            return;
+        }
+
+        if let Some(bounds) = bounds {
+            self.add_bounds(uniform.transform_bounds(bounds.to_rect()));
+        }
+
+        // The transform is applied before the offset.
+        let mut attrs = format!(
+            r#" transform="translate({} {})"#,
+            Num(uniform.offset[0]),
+            Num(uniform.offset[1])
+        );
+        if uniform.has_transform != 0 {
+            write!(attrs, " {}", Matrix(uniform.transform)).unwrap();
+        }
+        attrs.push('"');
+        if uniform.alpha < 1.0 {
+            write!(attrs, r#" opacity="{}""#, Num(uniform.alpha.max(0.0))).unwrap();
+        }
+
+        self.line(format_args!("<g{attrs}>"));
+        self.indent += 1;
+        for (paint, d) in runs.iter() {
+            self.line(format_args!(r#"<path d="{d}"{}/>"#, paint.attrs("fill")));
+        }
+        self.indent -= 1;
+        self.line(format_args!("</g>"));
+    }
+
+    #[cfg(feature = "text")]
+    fn text(&mut self, text: &TextPrimitive) {
+        let Some(buffer) = &text.buffer else {
+            return;
+        };
+        let buffer = buffer.raw_buffer();
+
+        if text.color.a == 0 {
+            return;
+        }
+
+        // The text is rotated about its anchor after it is clipped.
+        let transform = text.rotation.map(|rotation| {
+            let anchor = text.pos + rotation.anchor.to_vector();
+
+            crate::math::Transform::translation(-anchor.x, -anchor.y)
+                .then_rotate(rotation.angle)
+                .then_translate(anchor.to_vector())
+        });
+
+        if let Some(transform) = &transform {
+            self.line(format_args!(
+                r#"<g transform="{}">"#,
+                Matrix(transform.to_array())
+            ));
+            self.indent += 1;
+        }
+
+        let clip_id = text
+            .clipping_bounds
+            .map(|bounds| self.clip_path(bounds.translate(text.pos.to_vector())));
+
+        if let Some(clip_id) = &clip_id {
+            self.line(format_args!(r#"<g clip-path="url(#{clip_id})">"#));
+            self.indent += 1;
+        }
+
+        let fill = Paint::Color(text.color).attrs("fill");
+
+        // The clipping bounds don't move when the text is scrolled.
+        let text_y = text.pos.y - text.scroll_offset;
+
+        for run in buffer.layout_runs() {
+            let Some(first) = run.glyphs.first() else {
+                continue;
+            };
+
+            // The glyphs of right-to-left text are in visual order.
+            let start = run.glyphs.iter().map(|g| g.start).min().unwrap();
+            let end = run.glyphs.iter().map(|g| g.end).max().unwrap();
+            let Some(line) = run.text.get(start..end) else {
+                continue;
+            };
+
+            let line_rect = Rect::new(
+                Point::new(text.pos.x, text_y + run.line_top),
+                Size::new(run.line_w, run.line_height),
+            );
+
+            // Leave out the lines which are scrolled out of the clipping
+            // bounds.
+            if let Some(bounds) = &text.clipping_bounds {
+                let min_y = text.pos.y + bounds.min_y();
+                if line_rect.max_y() <= min_y || line_rect.min_y() >= min_y + bounds.height() {
+                    continue;
+                }
+            }
+
+            self.add_bounds(match &transform {
+                Some(transform) => transform.outer_transformed_rect(&line_rect),
+                None => line_rect,
+            });
+
+            self.line(format_args!(
+                r#"<text x="{}" y="{}" font-family="sans-serif" font-size="{}" xml:space="preserve"{fill}>{}</text>"#,
+                Num(text.pos.x + first.x),
+                Num(text_y + run.line_y),
+                Num(first.font_size),
+                Escaped(line),
+            ));
+        }
+
+        if clip_id.is_some() {
+            self.indent -= 1;
+            self.line(format_args!("</g>"));
+        }
+
+        if transform.is_some() {
+            self.indent -= 1;
+            self.line(format_args!("</g>"));
+        }
+    }
+
+    /// Write an outline in place of a primitive which cannot be exported.
+    #[allow(unused)] // unused if no unsupported primitive features are enabled
+    fn placeholder(&mut self, name: &str, bounds: Rect) {
+        self.add_bounds(bounds);
+
+        self.line(format_args!("<!-- {name} -->"));
+        self.line(format_args!(
+            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="none"{} stroke-dasharray="4 2"/>"#,
+            Num(bounds.min_x()),
+            Num(bounds.min_y()),
+            Num(bounds.width()),
+            Num(bounds.height()),
+            Paint::Color(PLACEHOLDER_COLOR).attrs("stroke"),
+        ));
+    }
+
+    /// Returns the paint of the given gradient, defining it if needed.
+    #[cfg(feature = "gradient")]
+    fn gradient(&mut self, gradient: &PackedGradient) -> Paint {
+        let stops: Vec<_> = gradient.stops().into_iter().flatten().collect();
+        let Some(first_stop) = stops.first() else {
+            return Paint::None;
+        };
+
+        if let Some(i) = self.gradients.iter().position(|g| g == gradient) {
+            return Paint::Url(format!("gradient{i}"));
+        }
+
+        let [x0, y0, x1, y1] = gradient.direction;
+        let (tag, geometry) = match gradient.kind() {
+            PackedGradientKind::Linear => (
+                "linearGradient",
+                format!(
+                    r#"x1="{}" y1="{}" x2="{}" y2="{}""#,
+                    Num(x0),
+                    Num(y0),
+                    Num(x1),
+                    Num(y1)
+                ),
+            ),
+            PackedGradientKind::Radial => (
+                "radialGradient",
+                format!(
+                    r#"cx="{}" cy="{}" fr="{}" r="{}""#,
+                    Num(x0),
+                    Num(y0),
+                    Num(x1),
+                    Num(y1)
+                ),
+            ),
+            // SVG has no conic gradients.
+            PackedGradientKind::Conic => return Paint::color(first_stop.color),
+        };
+
+        let i = self.gradients.len();
+        self.gradients.push(*gradient);
+
+        // Oklab interpolation has no equivalent in SVG, so it falls back to
+        // sRGB.
+        let interpolation = match gradient.interpolation() {
+            InterpolationSpace::LinearRgb => r#" color-interpolation="linearRGB""#,
+            _ => "",
+        };
+
+        writeln!(
+            self.defs,
+            r#"    <{tag} id="gradient{i}" gradientUnits="userSpaceOnUse" {geometry}{interpolation}>"#
+        )
+        .unwrap();
+        for stop in stops.iter() {
+            let color = stop.color.to_rgba8();
+            let mut opacity = String::new();
+            if color.a < 255 {
+                opacity = format!(r#" stop-opacity="{}""#, Num(f32::from(color.a) / 255.0));
+            }
+
+            writeln!(
+                self.defs,
+                r#"      <stop offset="{}" stop-color="{}"{opacity}/>"#,
+                Num(stop.offset),
+                Hex(color),
+            )
+            .unwrap();
+        }
+        writeln!(self.defs, "    </{tag}>").unwrap();
+
+        Paint::Url(format!("gradient{i}"))
+    }
+
+    fn finish(self) -> String {
+        // Always include the origin so that positions match the canvas.
+        let origin = Box2D::zero();
+        let bounds = self.bounds.map(|b| union(b, origin)).unwrap_or(origin);
+
+        let mut svg = String::new();
+
+        writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="{} {} {} {}">"#,
+            Num(bounds.width()),
+            Num(bounds.height()),
+            Num(bounds.min.x),
+            Num(bounds.min.y),
+            Num(bounds.width()),
+            Num(bounds.height()),
+        )
+        .unwrap();
+
+        if !self.defs.is_empty() {
+            svg.push_str("  <defs>\n");
+            svg.push_str(&self.defs);
+            svg.push_str("  </defs>\n");
+        }
+
+        svg.push_str(&self.body);
+        svg.push_str("</svg>\n");
+
+        svg
+    }
+}
+
+/// The smallest box containing both boxes.
+///
+/// Unlike [`Box2D::union`], this doesn't ignore empty boxes, so that the bounds
+/// include lines and points.
+fn union(a: Box2D, b: Box2D) -> Box2D {
+    Box2D::new(a.min.min(b.min), a.max.max(b.max))
+}
+
+/// The geometry shared by solid and gradient quads.
+#[cfg(feature = "quad")]
+struct QuadShape {
+    position: [f32; 2],
+    size: [f32; 2],
+    border_radius: [f32; 4],
+    border_width: [f32; 4],
+    border_dash: [f32; 4],
+    border_dash_offset: f32,
+    transform: [f32; 4],
+}
+
+/// How a shape is filled or stroked.
+#[derive(PartialEq)]
+enum Paint {
+    None,
+    Color(RGBA8),
+    /// The id of a gradient.
+    #[allow(unused)] // unused if the gradient feature is disabled
+    Url(String),
+}
+
+impl Paint {
+    #[allow(unused)] // unused if no primitive features are enabled
+    fn color(color: impl Into<PackedSrgb>) -> Self {
+        let color = color.into().to_rgba8();
+        if color.a == 0 {
+            Self::None
+        } else {
+            Self::Color(color)
+        }
+    }
+
+    fn is_none(&self) -> bool {
+        *self == Self::None
+    }
+
+    /// The attributes for the given property (`fill` or `stroke`).
+    fn attrs(&self, property: &str) -> String {
+        match self {
+            Self::None => format!(r#" {property}="none""#),
+            Self::Color(color) if color.a < 255 => format!(
+                r#" {property}="{}" {property}-opacity="{}""#,
+                Hex(*color),
+                Num(f32::from(color.a) / 255.0)
+            ),
+            Self::Color(color) => format!(r#" {property}="{}""#, Hex(*color)),
+            Self::Url(id) => format!(r#" {property}="url(#{id})""#),
+        }
+    }
+}
+
+/// Formats a number with at most 3 decimal places, without trailing zeros.
+struct Num(f32);
+
+impl fmt::Display for Num {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = format!("{:.3}", self.0);
+        let s = s.trim_end_matches('0').trim_end_matches('.');
+
+        if s == "-0" {
+            f.write_str("0")
+        } else {
+            f.write_str(s)
+        }
+    }
+}
+
+/// Formats a 2d transform as an SVG `matrix()`.
+#[allow(unused)] // unused if no primitive features are enabled
+struct Matrix([f32; 6]);
+
+impl fmt::Display for Matrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [a, b, c, d, e, g] = self.0.map(Num);
+        write!(f, "matrix({a} {b} {c} {d} {e} {g})")
+    }
+}
+
+/// Formats the RGB channels of a color as a hex color.
+struct Hex(RGBA8);
+
+impl fmt::Display for Hex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.0.r, self.0.g, self.0.b)
+    }
+}
+
+/// Escapes the characters which are not allowed in the text of an element.
+#[allow(unused)] // unused if the text feature is disabled
+struct Escaped<'a>(&'a str);
+
+impl fmt::Display for Escaped<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '&' => f.write_str("&amp;")?,
+                '<' => f.write_str("&lt;")?,
+                '>' => f.write_str("&gt;")?,
+                _ => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
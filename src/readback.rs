@@ -0,0 +1,247 @@
+use crate::error::ReadbackError;
+use crate::math::RectI32;
+
+/// Copy a region of the given texture into CPU memory as tightly-packed RGBA8
+/// pixels.
+///
+/// The texture must have been created with `wgpu::TextureUsages::COPY_SRC`,
+/// it must not be multisampled, and its format must be one of the 8 bit RGBA
+/// or BGRA formats. The bytes are returned as they are stored in the texture
+/// (so sRGB formats result in gamma-encoded bytes).
+///
+/// This blocks until the GPU has finished all submitted work.
+pub(crate) fn read_texture_rgba8(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+    rect: RectI32,
+) -> Result<Vec<u8>, ReadbackError> {
+    let swap_red_blue = match texture.format() {
+        wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => false,
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => true,
+        format => return Err(ReadbackError::UnsupportedFormat(format)),
+    };
+
+    if rect.origin.x < 0
+        || rect.origin.y < 0
+        || rect.size.width <= 0
+        || rect.size.height <= 0
+        || rect.max_x() as u32 > texture.width()
+        || rect.max_y() as u32 > texture.height()
+    {
+        return Err(ReadbackError::OutOfBounds(rect));
+    }
+
+    let width = rect.size.width as u32;
+    let height = rect.size.height as u32;
+
+    // Each row in the buffer must be padded to a multiple of 256 bytes.
+    let unpadded_bytes_per_row = width * 4;
+    let padded_bytes_per_row = unpadded_bytes_per_row.div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+        * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("rootvg readback buffer"),
+        size: padded_bytes_per_row as u64 * height as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("rootvg readback encoder"),
+    });
+
+    encoder.copy_texture_to_buffer(
+        wgpu::ImageCopyTexture {
+            texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d {
+                x: rect.origin.x as u32,
+                y: rect.origin.y as u32,
+                z: 0,
+            },
+            aspect: wgpu::TextureAspect::All,
+        },
+        wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_bytes_per_row),
+                rows_per_image: Some(height),
+            },
+        },
+        wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    );
+
+    queue.submit(Some(encoder.finish()));
+
+    let slice = buffer.slice(..);
+
+    let (sender, receiver) = std::sync::mpsc::channel();
+    slice.map_async(wgpu::MapMode::Read, move |res| {
+        let _ = sender.send(res);
+    });
+    device.poll(wgpu::Maintain::Wait);
+
+    // The callback is guaranteed to have been called once `poll` returns.
+    receiver.recv().unwrap()?;
+
+    let mut pixels = Vec::with_capacity((unpadded_bytes_per_row * height) as usize);
+    {
+        let data = slice.get_mapped_range();
+
+        for row in data.chunks_exact(padded_bytes_per_row as usize) {
+            pixels.extend_from_slice(&row[0..unpadded_bytes_per_row as usize]);
+        }
+    }
+    buffer.unmap();
+
+    if swap_red_blue {
+        for pixel in pixels.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+    }
+
+    Ok(pixels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::{PointI32, SizeI32};
+    use crate::test_util::device;
+
+    // Rows of 5 pixels are 20 bytes, so every row of the copy is padded.
+    const WIDTH: u32 = 5;
+    const HEIGHT: u32 = 3;
+
+    fn create_texture(device: &wgpu::Device, format: wgpu::TextureFormat) -> wgpu::Texture {
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d {
+                width: WIDTH,
+                height: HEIGHT,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::COPY_SRC
+                | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        })
+    }
+
+    fn whole(texture: &wgpu::Texture) -> RectI32 {
+        RectI32::new(
+            PointI32::new(0, 0),
+            SizeI32::new(texture.width() as i32, texture.height() as i32),
+        )
+    }
+
+    #[test]
+    fn solid_color_render_is_read_back_as_rgba() {
+        let Some((device, queue)) = device() else {
+            return;
+        };
+
+        for format in [
+            wgpu::TextureFormat::Rgba8Unorm,
+            wgpu::TextureFormat::Bgra8Unorm,
+        ] {
+            let texture = create_texture(&device, format);
+            let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+            let mut encoder =
+                device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+            encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color {
+                            r: 1.0,
+                            g: 0.2,
+                            b: 0.0,
+                            a: 0.6,
+                        }),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            queue.submit(Some(encoder.finish()));
+
+            let pixels = read_texture_rgba8(&device, &queue, &texture, whole(&texture)).unwrap();
+
+            assert_eq!(pixels.len(), (WIDTH * HEIGHT * 4) as usize, "{format:?}");
+            for pixel in pixels.chunks_exact(4) {
+                assert_eq!(pixel, [255, 51, 0, 153], "{format:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn region_is_read_back_without_row_padding() {
+        let Some((device, queue)) = device() else {
+            return;
+        };
+
+        let texture = create_texture(&device, wgpu::TextureFormat::Bgra8Unorm);
+
+        // The blue channel holds the index of each pixel.
+        let data: Vec<u8> = (0..(WIDTH * HEIGHT) as u8)
+            .flat_map(|i| [i, 0, 255, 255])
+            .collect();
+        queue.write_texture(
+            texture.as_image_copy(),
+            &data,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(WIDTH * 4),
+                rows_per_image: Some(HEIGHT),
+            },
+            texture.size(),
+        );
+
+        let rect = RectI32::new(PointI32::new(1, 1), SizeI32::new(3, 2));
+        let pixels = read_texture_rgba8(&device, &queue, &texture, rect).unwrap();
+
+        let expected: Vec<u8> = [6, 7, 8, 11, 12, 13]
+            .into_iter()
+            .flat_map(|i| [255, 0, i, 255])
+            .collect();
+        assert_eq!(pixels, expected);
+    }
+
+    #[test]
+    fn invalid_reads_are_rejected() {
+        let Some((device, queue)) = device() else {
+            return;
+        };
+
+        let texture = create_texture(&device, wgpu::TextureFormat::Rgba8Unorm);
+        let rect = RectI32::new(PointI32::new(1, 0), SizeI32::new(WIDTH as i32, 1));
+        assert!(matches!(
+            read_texture_rgba8(&device, &queue, &texture, rect),
+            Err(ReadbackError::OutOfBounds(r)) if r == rect
+        ));
+
+        let texture = create_texture(&device, wgpu::TextureFormat::Rgba16Float);
+        assert!(matches!(
+            read_texture_rgba8(&device, &queue, &texture, whole(&texture)),
+            Err(ReadbackError::UnsupportedFormat(
+                wgpu::TextureFormat::Rgba16Float
+            ))
+        ));
+    }
+}
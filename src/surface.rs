@@ -1,3 +1,4 @@
+use std::sync::Arc;
 use wgpu::MultisampleState;
 
 use crate::{
@@ -14,6 +15,35 @@ pub struct DefaultSurfaceConfig {
     pub limits: Option<wgpu::Limits>,
     pub desired_maximum_frame_latency: u32,
     pub memory_hints: wgpu::MemoryHints,
+    /// If this is `Some`, only adapters accepted by this filter are
+    /// considered. Use this to pick a specific GPU, for example the same one
+    /// a host application is using.
+    ///
+    /// Of the accepted adapters that are compatible with the surface, the
+    /// first one matching `power_preference` is used. If no adapter is
+    /// accepted, [`NewSurfaceError::NoAdapterMatchesFilter`] is returned.
+    ///
+    /// On the web adapters can't be enumerated, so the filter is only applied
+    /// to the adapter that would have been picked without it.
+    ///
+    /// By default this is set to `None`.
+    pub adapter_filter: Option<AdapterFilter>,
+    /// If this is `Some`, the surface is configured with this texture format
+    /// instead of the one picked automatically, for example when a capture
+    /// or streaming setup requires a specific format. If the surface doesn't
+    /// support the format, [`NewSurfaceError::UnsupportedTextureFormat`] is
+    /// returned.
+    ///
+    /// If the format is not gamma corrected the same way as the automatically
+    /// picked format (sRGB, or non-sRGB with the `web-colors` feature), the
+    /// surface is rendered to through a view with the sRGB variant of the
+    /// format toggled, so colors stay correct. In that case
+    /// [`DefaultSurface::format`] returns the format of that view, and views
+    /// of the surface textures should be created with
+    /// [`DefaultSurface::create_view`].
+    ///
+    /// By default this is set to `None`.
+    pub preferred_format: Option<wgpu::TextureFormat>,
 
     #[cfg(feature = "msaa")]
     pub antialiasing: Option<rootvg_msaa::Antialiasing>,
@@ -35,6 +65,8 @@ impl Clone for DefaultSurfaceConfig {
             limits: self.limits.clone(),
             desired_maximum_frame_latency: self.desired_maximum_frame_latency,
             memory_hints: self.memory_hints.clone(),
+            adapter_filter: self.adapter_filter.clone(),
+            preferred_format: self.preferred_format,
 
             #[cfg(feature = "msaa")]
             antialiasing: self.antialiasing,
@@ -57,6 +89,8 @@ impl Default for DefaultSurfaceConfig {
             limits: None,
             desired_maximum_frame_latency: 2,
             memory_hints: wgpu::MemoryHints::default(),
+            adapter_filter: None,
+            preferred_format: None,
 
             #[cfg(feature = "msaa")]
             antialiasing: Some(rootvg_msaa::Antialiasing::MSAAx8),
@@ -64,6 +98,33 @@ impl Default for DefaultSurfaceConfig {
     }
 }
 
+/// A predicate used to select the adapter of a [`DefaultSurface`]. See
+/// [`DefaultSurfaceConfig::adapter_filter`].
+///
+/// Clones of a filter share the same closure.
+#[derive(Clone)]
+pub struct AdapterFilter(Arc<dyn Fn(&wgpu::AdapterInfo) -> bool + Send + Sync>);
+
+impl AdapterFilter {
+    /// Create a new filter which accepts the adapters for which `f` returns
+    /// `true`.
+    pub fn new(f: impl Fn(&wgpu::AdapterInfo) -> bool + Send + Sync + 'static) -> Self {
+        Self(Arc::new(f))
+    }
+
+    /// Returns `true` if the adapter with the given info is accepted by
+    /// this filter.
+    pub fn matches(&self, info: &wgpu::AdapterInfo) -> bool {
+        (self.0)(info)
+    }
+}
+
+impl std::fmt::Debug for AdapterFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("AdapterFilter").finish_non_exhaustive()
+    }
+}
+
 struct SurfaceConfigInner {
     present_mode: wgpu::PresentMode,
     power_preference: wgpu::PowerPreference,
@@ -71,6 +132,8 @@ struct SurfaceConfigInner {
     limits: Option<wgpu::Limits>,
     desired_maximum_frame_latency: u32,
     memory_hints: wgpu::MemoryHints,
+    adapter_filter: Option<AdapterFilter>,
+    preferred_format: Option<wgpu::TextureFormat>,
 
     #[cfg(feature = "msaa")]
     antialiasing: Option<rootvg_msaa::Antialiasing>,
@@ -86,6 +149,8 @@ pub struct DefaultSurface<'a> {
 
     #[cfg(feature = "msaa")]
     largest_compatible_aa: Option<rootvg_msaa::Antialiasing>,
+    #[cfg(feature = "msaa")]
+    format_feature_flags: wgpu::TextureFormatFeatureFlags,
 }
 
 impl<'a> DefaultSurface<'a> {
@@ -115,6 +180,8 @@ impl<'a> DefaultSurface<'a> {
             limits,
             desired_maximum_frame_latency,
             memory_hints,
+            adapter_filter,
+            preferred_format,
         } = config;
 
         let instance = wgpu::Instance::new(instance_descriptor);
@@ -143,6 +210,8 @@ impl<'a> DefaultSurface<'a> {
                 limits,
                 desired_maximum_frame_latency,
                 memory_hints,
+                adapter_filter,
+                preferred_format,
                 #[cfg(feature = "msaa")]
                 antialiasing,
             },
@@ -178,6 +247,8 @@ impl<'a> DefaultSurface<'a> {
             limits,
             desired_maximum_frame_latency,
             memory_hints,
+            adapter_filter,
+            preferred_format,
         } = config;
 
         let instance = wgpu::Instance::new(instance_descriptor);
@@ -206,100 +277,117 @@ impl<'a> DefaultSurface<'a> {
                 limits,
                 desired_maximum_frame_latency,
                 memory_hints,
+                adapter_filter,
+                preferred_format,
                 #[cfg(feature = "msaa")]
                 antialiasing,
             },
         ))
     }
 
-    async fn new_async(
+    /// Create a new surface from a device and queue which have already been
+    /// created, for example to share them with a host application.
+    ///
+    /// - `size` - The size of the surface in physical pixels
+    /// - `scale_factor` - The scale factor of the surface in pixels per point
+    /// - `adapter` - The adapter that `device` was requested from
+    /// - `device` and `queue` - The device and queue to render with
+    /// - `surface` - The surface to render to, created by the same
+    /// `wgpu::Instance` as `adapter`
+    /// - `config` - Additional settings for the surface
+    ///
+    /// Only `present_mode`, `desired_maximum_frame_latency`,
+    /// `preferred_format`, and `antialiasing` are used from `config`, since the other settings only
+    /// affect how the adapter and device are created.
+    ///
+    /// If the device was not created with
+    /// `wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES`, only
+    /// `Antialiasing::MSAAx4` is available for multisampling.
+    pub fn from_device(
         physical_size: PhysicalSizeI32,
         scale_factor: ScaleFactor,
-        instance: wgpu::Instance,
+        adapter: &wgpu::Adapter,
+        device: wgpu::Device,
+        queue: wgpu::Queue,
         surface: wgpu::Surface<'a>,
-        config: SurfaceConfigInner,
+        config: DefaultSurfaceConfig,
     ) -> Result<Self, NewSurfaceError> {
-        let SurfaceConfigInner {
+        assert!(physical_size.width > 0);
+        assert!(physical_size.height > 0);
+
+        let DefaultSurfaceConfig {
             #[cfg(feature = "msaa")]
             antialiasing,
             present_mode,
             power_preference,
+            instance_descriptor: _,
             force_fallback_adapter,
             limits,
             desired_maximum_frame_latency,
             memory_hints,
+            adapter_filter,
+            preferred_format,
         } = config;
 
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
+        Self::configure(
+            physical_size,
+            scale_factor,
+            adapter,
+            device,
+            queue,
+            surface,
+            &SurfaceConfigInner {
+                present_mode,
                 power_preference,
-                compatible_surface: Some(&surface),
                 force_fallback_adapter,
-            })
-            .await
-            .ok_or_else(|| NewSurfaceError::CouldNotGetAdapter)?;
+                limits,
+                desired_maximum_frame_latency,
+                memory_hints,
+                adapter_filter,
+                preferred_format,
+                #[cfg(feature = "msaa")]
+                antialiasing,
+            },
+        )
+    }
+
+    async fn new_async(
+        physical_size: PhysicalSizeI32,
+        scale_factor: ScaleFactor,
+        instance: wgpu::Instance,
+        surface: wgpu::Surface<'a>,
+        config: SurfaceConfigInner,
+    ) -> Result<Self, NewSurfaceError> {
+        let adapter = Self::request_adapter(&instance, &surface, &config).await?;
 
         // WGPU already logs this at info level
         //log::info!("selected wgpu adapter: {:#?}", adapter.get_info());
 
-        let (texture_format, alpha_mode) = {
-            let capabilities = surface.get_capabilities(&adapter);
-
-            let mut formats = capabilities.formats.iter().copied();
-
-            log::trace!("available texture formats: {formats:#?}");
-
-            // Gamma correction
-            #[cfg(not(feature = "web-colors"))]
-            let format = formats.find(wgpu::TextureFormat::is_srgb);
-
-            // No gamma correction
-            #[cfg(feature = "web-colors")]
-            let format = formats.find(|format| !wgpu::TextureFormat::is_srgb(format));
-
-            let format = format.or_else(|| {
-                log::warn!("no texture format found!");
-
-                capabilities.formats.first().copied()
-            });
-
-            let alpha_modes = capabilities.alpha_modes;
-
-            log::trace!("available alpha modes: {alpha_modes:#?}");
-
-            let preferred_alpha = if alpha_modes.contains(&wgpu::CompositeAlphaMode::PostMultiplied)
-            {
-                wgpu::CompositeAlphaMode::PostMultiplied
-            } else {
-                wgpu::CompositeAlphaMode::Auto
-            };
-
-            (
-                format.ok_or_else(|| NewSurfaceError::NoCompatibleTextureFormat)?,
-                preferred_alpha,
-            )
-        };
-
-        log::info!(
-            "selected wgpu texture format: {texture_format:?} with alpha mode: {alpha_mode:?}"
-        );
+        let SurfaceConfigInner {
+            #[cfg(all(feature = "msaa", not(target_arch = "wasm32")))]
+            antialiasing,
+            limits,
+            memory_hints,
+            ..
+        } = &config;
 
         let limits_vec = if let Some(limits) = limits {
-            vec![limits]
+            vec![limits.clone()]
         } else {
             vec![wgpu::Limits::default(), wgpu::Limits::downlevel_defaults()]
         };
 
         let mut limits = limits_vec.clone().into_iter().map(|limits| wgpu::Limits {
-            max_bind_groups: 2,
+            max_bind_groups: 4,
             ..limits
         });
 
         let mut required_features = wgpu::Features::empty();
         #[cfg(all(feature = "msaa", not(target_arch = "wasm32")))]
-        if let Some(antialiasing) = antialiasing {
+        if let Some(antialiasing) = *antialiasing {
             // The WebGPU spec only gaurantees a sample count of 1 or 4
-            if antialiasing != rootvg_msaa::Antialiasing::MSAAx4 {
+            if antialiasing.sample_count() > 1 && antialiasing != rootvg_msaa::Antialiasing::MSAAx4
+            {
                 required_features.insert(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES);
             }
         }
@@ -316,7 +404,7 @@ impl<'a> DefaultSurface<'a> {
                     required_features = wgpu::Features::empty();
 
                     let mut limits = limits_vec.clone().into_iter().map(|limits| wgpu::Limits {
-                        max_bind_groups: 2,
+                        max_bind_groups: 4,
                         ..limits
                     });
 
@@ -344,6 +432,180 @@ impl<'a> DefaultSurface<'a> {
             }
         };
 
+        Self::configure(
+            physical_size,
+            scale_factor,
+            &adapter,
+            device,
+            queue,
+            surface,
+            &config,
+        )
+    }
+
+    async fn request_adapter(
+        instance: &wgpu::Instance,
+        surface: &wgpu::Surface<'a>,
+        config: &SurfaceConfigInner,
+    ) -> Result<wgpu::Adapter, NewSurfaceError> {
+        let Some(adapter_filter) = &config.adapter_filter else {
+            return instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: config.power_preference,
+                    compatible_surface: Some(surface),
+                    force_fallback_adapter: config.force_fallback_adapter,
+                })
+                .await
+                .ok_or_else(|| NewSurfaceError::CouldNotGetAdapter);
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let mut adapters: Vec<_> = instance
+                .enumerate_adapters(wgpu::Backends::all())
+                .into_iter()
+                .filter(|adapter| {
+                    let info = adapter.get_info();
+
+                    adapter.is_surface_supported(surface)
+                        && (!config.force_fallback_adapter
+                            || info.device_type == wgpu::DeviceType::Cpu)
+                        && adapter_filter.matches(&info)
+                })
+                .collect();
+
+            if adapters.is_empty() {
+                return Err(NewSurfaceError::NoAdapterMatchesFilter);
+            }
+
+            // Prefer the same kind of adapter that `request_adapter` would.
+            let preferred_type = match config.power_preference {
+                wgpu::PowerPreference::HighPerformance => Some(wgpu::DeviceType::DiscreteGpu),
+                wgpu::PowerPreference::LowPower => Some(wgpu::DeviceType::IntegratedGpu),
+                wgpu::PowerPreference::None => None,
+            };
+            let index = preferred_type
+                .and_then(|device_type| {
+                    adapters
+                        .iter()
+                        .position(|adapter| adapter.get_info().device_type == device_type)
+                })
+                .unwrap_or(0);
+
+            Ok(adapters.swap_remove(index))
+        }
+
+        // Adapters can't be enumerated on the web.
+        #[cfg(target_arch = "wasm32")]
+        {
+            let adapter = instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: config.power_preference,
+                    compatible_surface: Some(surface),
+                    force_fallback_adapter: config.force_fallback_adapter,
+                })
+                .await
+                .ok_or_else(|| NewSurfaceError::CouldNotGetAdapter)?;
+
+            if adapter_filter.matches(&adapter.get_info()) {
+                Ok(adapter)
+            } else {
+                Err(NewSurfaceError::NoAdapterMatchesFilter)
+            }
+        }
+    }
+
+    fn configure(
+        physical_size: PhysicalSizeI32,
+        scale_factor: ScaleFactor,
+        adapter: &wgpu::Adapter,
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        surface: wgpu::Surface<'a>,
+        config: &SurfaceConfigInner,
+    ) -> Result<Self, NewSurfaceError> {
+        let SurfaceConfigInner {
+            #[cfg(feature = "msaa")]
+            antialiasing,
+            present_mode,
+            desired_maximum_frame_latency,
+            preferred_format,
+            ..
+        } = *config;
+
+        let (texture_format, alpha_mode) = {
+            let capabilities = surface.get_capabilities(adapter);
+
+            let mut formats = capabilities.formats.iter().copied();
+
+            log::trace!("available texture formats: {formats:#?}");
+
+            let format = if let Some(preferred_format) = preferred_format {
+                if !capabilities.formats.contains(&preferred_format) {
+                    return Err(NewSurfaceError::UnsupportedTextureFormat(preferred_format));
+                }
+
+                Some(preferred_format)
+            } else {
+                // Gamma correction
+                #[cfg(not(feature = "web-colors"))]
+                let format = formats.find(wgpu::TextureFormat::is_srgb);
+
+                // No gamma correction
+                #[cfg(feature = "web-colors")]
+                let format = formats.find(|format| !wgpu::TextureFormat::is_srgb(format));
+
+                format.or_else(|| {
+                    log::warn!("no texture format found!");
+
+                    capabilities.formats.first().copied()
+                })
+            };
+
+            let alpha_modes = capabilities.alpha_modes;
+
+            log::trace!("available alpha modes: {alpha_modes:#?}");
+
+            let preferred_alpha = if alpha_modes.contains(&wgpu::CompositeAlphaMode::PostMultiplied)
+            {
+                wgpu::CompositeAlphaMode::PostMultiplied
+            } else {
+                wgpu::CompositeAlphaMode::Auto
+            };
+
+            (
+                format.ok_or_else(|| NewSurfaceError::NoCompatibleTextureFormat)?,
+                preferred_alpha,
+            )
+        };
+
+        log::info!(
+            "selected wgpu texture format: {texture_format:?} with alpha mode: {alpha_mode:?}"
+        );
+
+        // Render through a view with the sRGB variant toggled if the format
+        // isn't gamma corrected the way the canvas expects.
+        #[cfg(not(feature = "web-colors"))]
+        let color_correct_format = texture_format.add_srgb_suffix();
+        #[cfg(feature = "web-colors")]
+        let color_correct_format = texture_format.remove_srgb_suffix();
+
+        let view_formats = if color_correct_format == texture_format {
+            vec![]
+        } else if adapter
+            .get_downlevel_capabilities()
+            .flags
+            .contains(wgpu::DownlevelFlags::SURFACE_VIEW_FORMATS)
+        {
+            log::info!("rendering to the surface through a {color_correct_format:?} view");
+
+            vec![color_correct_format]
+        } else {
+            log::warn!("surface view formats are not supported, colors will be incorrect");
+
+            vec![]
+        };
+
         let surface_config = wgpu::SurfaceConfiguration {
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
             format: texture_format,
@@ -351,53 +613,37 @@ impl<'a> DefaultSurface<'a> {
             height: physical_size.height as u32,
             present_mode,
             alpha_mode,
-            view_formats: vec![],
+            view_formats,
             desired_maximum_frame_latency,
         };
         surface.configure(&device, &surface_config);
 
         #[cfg(feature = "msaa")]
-        let largest_compatible_aa = {
-            let format_feature_flags = adapter.get_texture_format_features(texture_format).flags;
-            let mut largest_compatible_aa = antialiasing;
-            loop {
-                match largest_compatible_aa {
-                    Some(rootvg_msaa::Antialiasing::MSAAx16) => {
-                        if format_feature_flags
-                            .contains(wgpu::TextureFormatFeatureFlags::MULTISAMPLE_X16)
-                        {
-                            break;
-                        }
-                        largest_compatible_aa = Some(rootvg_msaa::Antialiasing::MSAAx8);
-                    }
-                    Some(rootvg_msaa::Antialiasing::MSAAx8) => {
-                        if format_feature_flags
-                            .contains(wgpu::TextureFormatFeatureFlags::MULTISAMPLE_X8)
-                        {
-                            break;
-                        }
-                        largest_compatible_aa = Some(rootvg_msaa::Antialiasing::MSAAx4);
-                    }
-                    Some(rootvg_msaa::Antialiasing::MSAAx4) => {
-                        if format_feature_flags
-                            .contains(wgpu::TextureFormatFeatureFlags::MULTISAMPLE_X4)
-                        {
-                            break;
-                        }
-                        largest_compatible_aa = Some(rootvg_msaa::Antialiasing::MSAAx2);
-                    }
-                    Some(rootvg_msaa::Antialiasing::MSAAx2) => {
-                        if format_feature_flags
-                            .contains(wgpu::TextureFormatFeatureFlags::MULTISAMPLE_X2)
-                        {
-                            break;
-                        }
-                        largest_compatible_aa = None;
-                    }
-                    None => break,
-                }
+        let format_feature_flags = {
+            let view_format = surface_config
+                .view_formats
+                .first()
+                .copied()
+                .unwrap_or(texture_format);
+            let mut flags = adapter.get_texture_format_features(view_format).flags;
+
+            // Without this feature we are limited to the sample counts guaranteed
+            // by the WebGPU spec.
+            if !device
+                .features()
+                .contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES)
+            {
+                flags &= wgpu::TextureFormatFeatureFlags::MULTISAMPLE_X4;
             }
 
+            flags
+        };
+
+        #[cfg(feature = "msaa")]
+        let largest_compatible_aa = {
+            let largest_compatible_aa =
+                rootvg_msaa::Antialiasing::largest_compatible(antialiasing, format_feature_flags);
+
             log::info!(
                 "requested AA mode: {:?} | largest compatible antialiasing mode: {:?}",
                 antialiasing,
@@ -416,6 +662,8 @@ impl<'a> DefaultSurface<'a> {
 
             #[cfg(feature = "msaa")]
             largest_compatible_aa,
+            #[cfg(feature = "msaa")]
+            format_feature_flags,
         })
     }
 
@@ -446,8 +694,26 @@ impl<'a> DefaultSurface<'a> {
         self.surface.get_current_texture()
     }
 
+    /// The format which the canvas should render to.
+    ///
+    /// This differs from the format of the surface textures when
+    /// [`DefaultSurfaceConfig::preferred_format`] is not gamma corrected the
+    /// way the canvas expects.
     pub fn format(&self) -> wgpu::TextureFormat {
-        self.surface_config.format
+        self.surface_config
+            .view_formats
+            .first()
+            .copied()
+            .unwrap_or(self.surface_config.format)
+    }
+
+    /// Create a view of the given surface texture with the format returned by
+    /// [`DefaultSurface::format`].
+    pub fn create_view(&self, frame: &wgpu::SurfaceTexture) -> wgpu::TextureView {
+        frame.texture.create_view(&wgpu::TextureViewDescriptor {
+            format: Some(self.format()),
+            ..Default::default()
+        })
     }
 
     pub fn canvas_config(&self) -> CanvasConfig {
@@ -457,14 +723,18 @@ impl<'a> DefaultSurface<'a> {
             .map(|aa| aa.sample_count())
             .unwrap_or(1);
 
+        #[cfg(feature = "msaa")]
+        let fxaa = self.largest_compatible_aa == Some(rootvg_msaa::Antialiasing::Fxaa);
+
         #[cfg(not(feature = "msaa"))]
-        let sample_count = 1;
+        let (sample_count, fxaa) = (1, false);
 
         CanvasConfig {
             multisample: MultisampleState {
                 count: sample_count,
                 ..Default::default()
             },
+            fxaa,
             ..Default::default()
         }
     }
@@ -473,6 +743,20 @@ impl<'a> DefaultSurface<'a> {
     pub fn largest_compatible_aa(&self) -> Option<rootvg_msaa::Antialiasing> {
         self.largest_compatible_aa
     }
+
+    /// Returns the largest antialiasing mode which is less than or equal to
+    /// `requested` and is supported by the format of this surface.
+    ///
+    /// Use this to validate the mode passed to [`Canvas::set_antialiasing`].
+    ///
+    /// [`Canvas::set_antialiasing`]: crate::Canvas::set_antialiasing
+    #[cfg(feature = "msaa")]
+    pub fn compatible_aa(
+        &self,
+        requested: Option<rootvg_msaa::Antialiasing>,
+    ) -> Option<rootvg_msaa::Antialiasing> {
+        rootvg_msaa::Antialiasing::largest_compatible(requested, self.format_feature_flags)
+    }
 }
 
 #[cfg(feature = "default-surface")]
@@ -482,8 +766,12 @@ pub enum NewSurfaceError {
     CouldNotCreateSurface(#[from] wgpu::CreateSurfaceError),
     #[error("failed to get compatible wgpu adapter")]
     CouldNotGetAdapter,
+    #[error("no compatible wgpu adapter matches the adapter filter")]
+    NoAdapterMatchesFilter,
     #[error("could not find compatible wgpu texture format")]
     NoCompatibleTextureFormat,
+    #[error("the preferred texture format {0:?} is not supported by the surface")]
+    UnsupportedTextureFormat(wgpu::TextureFormat),
     #[error("could not find wgpu device with compatible limits")]
     NoDeviceWithCompatibleLimits,
 }
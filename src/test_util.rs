@@ -0,0 +1,148 @@
+//! Helpers for tests which render with a real GPU device.
+
+use crate::color::{PackedSrgb, SRGBA8_TEXTURE_FORMAT};
+use crate::error::RenderError;
+use crate::math::{PhysicalSizeI32, PointI32, RectI32, SizeI32};
+use crate::{Canvas, CanvasConfig};
+
+#[cfg(feature = "text")]
+use crate::text::{glyphon::cosmic_text::fontdb, FontSystem};
+
+#[cfg(all(feature = "text", feature = "svg-icons"))]
+use crate::text::svg::SvgIconSystem;
+
+/// Create a device with the limits of the default adapter, or `None` if
+/// there is no adapter, in which case the test should be skipped.
+pub(crate) fn device() -> Option<(wgpu::Device, wgpu::Queue)> {
+    device_with_limits(|_| {})
+}
+
+/// Like [`device`], but the limits of the adapter can be lowered first.
+pub(crate) fn device_with_limits(
+    adjust_limits: impl FnOnce(&mut wgpu::Limits),
+) -> Option<(wgpu::Device, wgpu::Queue)> {
+    pollster::block_on(async {
+        let instance = wgpu::Instance::default();
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions::default())
+            .await?;
+
+        let mut limits = adapter.limits();
+        adjust_limits(&mut limits);
+
+        adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    label: Some("rootvg test device"),
+                    required_features: wgpu::Features::empty(),
+                    required_limits: limits,
+                    memory_hints: wgpu::MemoryHints::default(),
+                },
+                None,
+            )
+            .await
+            .ok()
+    })
+}
+
+/// A canvas which renders into textures of a test device.
+pub(crate) struct TestCanvas {
+    pub device: wgpu::Device,
+    pub queue: wgpu::Queue,
+    pub canvas: Canvas,
+    #[cfg(feature = "text")]
+    pub font_system: FontSystem,
+    #[cfg(all(feature = "text", feature = "svg-icons"))]
+    pub svg_icon_system: SvgIconSystem,
+}
+
+impl TestCanvas {
+    /// Returns `None` if there is no GPU adapter, see [`device`].
+    pub fn new(config: CanvasConfig) -> Option<Self> {
+        Some(Self::with_device(device()?, config))
+    }
+
+    pub fn with_device((device, queue): (wgpu::Device, wgpu::Queue), config: CanvasConfig) -> Self {
+        // No system fonts are loaded, so that tests don't depend on them.
+        #[cfg(feature = "text")]
+        let mut font_system =
+            FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+
+        let canvas = Canvas::new(
+            &device,
+            &queue,
+            SRGBA8_TEXTURE_FORMAT,
+            config,
+            #[cfg(feature = "text")]
+            &mut font_system,
+        );
+
+        Self {
+            device,
+            queue,
+            canvas,
+            #[cfg(feature = "text")]
+            font_system,
+            #[cfg(all(feature = "text", feature = "svg-icons"))]
+            svg_icon_system: SvgIconSystem::default(),
+        }
+    }
+
+    /// Create a render target which can be read back with
+    /// [`TestCanvas::read`].
+    pub fn create_target(&self, size: PhysicalSizeI32) -> wgpu::Texture {
+        self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("rootvg test render target"),
+            size: wgpu::Extent3d {
+                width: size.width as u32,
+                height: size.height as u32,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: SRGBA8_TEXTURE_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        })
+    }
+
+    /// Render the current frame of the canvas into the target and submit it.
+    pub fn render(
+        &mut self,
+        target: &wgpu::Texture,
+        clear_color: Option<PackedSrgb>,
+    ) -> Result<bool, RenderError> {
+        let view = target.create_view(&wgpu::TextureViewDescriptor::default());
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+        let res = self.canvas.render_to_target(
+            clear_color,
+            &self.device,
+            &self.queue,
+            &mut encoder,
+            &view,
+            PhysicalSizeI32::new(target.width() as i32, target.height() as i32),
+            #[cfg(feature = "text")]
+            &mut self.font_system,
+            #[cfg(all(feature = "text", feature = "svg-icons"))]
+            &mut self.svg_icon_system,
+        );
+
+        self.queue.submit(Some(encoder.finish()));
+
+        res
+    }
+
+    /// Read back the whole target as tightly-packed RGBA8 pixels.
+    pub fn read(&self, target: &wgpu::Texture) -> Vec<u8> {
+        let rect = RectI32::new(
+            PointI32::new(0, 0),
+            SizeI32::new(target.width() as i32, target.height() as i32),
+        );
+
+        crate::readback::read_texture_rgba8(&self.device, &self.queue, target, rect).unwrap()
+    }
+}
@@ -0,0 +1,33 @@
+use crate::color::PackedSrgb;
+use crate::math::{Point, Size};
+use crate::quad::{SolidQuad, SolidQuadPrimitive};
+use crate::text::RcTextBuffer;
+
+/// Create a thin quad for a text cursor (caret) placed before the byte at
+/// `index` in the text of the buffer. See [`RcTextBuffer::cursor_rect`].
+///
+/// * `buffer` - The text buffer
+/// * `text_pos` - The position of the text primitive which draws the buffer
+/// * `index` - The byte index of the cursor in the whole text
+/// * `width` - The width of the cursor in logical points
+/// * `color` - The color of the cursor
+///
+/// The cursor is centered on the position between the glyphs. To make the
+/// cursor blink, simply skip adding it to the canvas every other interval.
+pub fn text_cursor_quad(
+    buffer: &RcTextBuffer,
+    text_pos: Point,
+    index: usize,
+    width: f32,
+    color: impl Into<PackedSrgb>,
+) -> SolidQuadPrimitive {
+    let rect = buffer.cursor_rect(index);
+
+    SolidQuad::builder(Size::new(width, rect.size.height))
+        .position(Point::new(
+            text_pos.x + rect.origin.x - (width * 0.5),
+            text_pos.y + rect.origin.y,
+        ))
+        .bg_color(color)
+        .into()
+}